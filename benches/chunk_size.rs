@@ -0,0 +1,67 @@
+//! Compares the speed of streaming through channel data with different read sizes.
+//!
+//! A 128 MB file is generated in the temporary directory unless the path of a file
+//! to read is given by the `RSTDMS_BENCH_FILE` environment variable.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rstdms::{
+    channel_statistics, ChunkConfig, ReadOptions, StatisticsOptions, TdmsFile, TdmsWriter,
+    WriteObject,
+};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+const MB: usize = 1024 * 1024;
+
+const VALUES_PER_SEGMENT: usize = 1024 * 1024;
+
+const SEGMENTS: usize = 16;
+
+fn bench_file() -> PathBuf {
+    if let Some(path) = std::env::var_os("RSTDMS_BENCH_FILE") {
+        return PathBuf::from(path);
+    }
+    let path = std::env::temp_dir().join("rstdms_chunk_size_bench.tdms");
+    if !path.exists() {
+        let mut writer = TdmsWriter::new(BufWriter::new(File::create(&path).unwrap()));
+        let values: Vec<f64> = (0..VALUES_PER_SEGMENT).map(|i| i as f64).collect();
+        for _ in 0..SEGMENTS {
+            writer
+                .write_segment(&[WriteObject::channel("Group", "Channel")
+                    .with_data(&values)
+                    .unwrap()])
+                .unwrap();
+        }
+    }
+    path
+}
+
+fn statistics(c: &mut Criterion) {
+    let path = bench_file();
+    let data_length = std::fs::metadata(&path).unwrap().len();
+    let mut group = c.benchmark_group("statistics");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(data_length));
+    for &megabytes in [1, 4, 16, 64].iter() {
+        let options = ReadOptions::new().chunk_config(ChunkConfig::new(megabytes * MB));
+        let tdms_file = TdmsFile::open_with_options(&path, &options).unwrap();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{}MB", megabytes)),
+            &tdms_file,
+            |b, tdms_file| {
+                b.iter(|| {
+                    for group in tdms_file.groups() {
+                        for channel in group.channels() {
+                            channel_statistics(&channel, &StatisticsOptions::default()).unwrap();
+                        }
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, statistics);
+criterion_main!(benches);
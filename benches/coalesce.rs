@@ -0,0 +1,59 @@
+//! Compares reading a channel written in many small segments with and without
+//! coalescing the reads of consecutive segments.
+//!
+//! A file with 30,000 single channel segments is generated in the temporary directory.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rstdms::{ReadOptions, TdmsFile, TdmsWriter, WriteObject};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+const VALUES_PER_SEGMENT: usize = 100;
+
+const SEGMENTS: usize = 30_000;
+
+fn bench_file() -> PathBuf {
+    let path = std::env::temp_dir().join("rstdms_coalesce_bench.tdms");
+    if !path.exists() {
+        let mut writer = TdmsWriter::new(BufWriter::new(File::create(&path).unwrap()));
+        for segment in 0..SEGMENTS {
+            let start = segment * VALUES_PER_SEGMENT;
+            let values: Vec<f64> = (start..start + VALUES_PER_SEGMENT)
+                .map(|i| i as f64)
+                .collect();
+            writer
+                .write_segment(&[WriteObject::channel("Group", "Channel")
+                    .with_data(&values)
+                    .unwrap()])
+                .unwrap();
+        }
+    }
+    path
+}
+
+fn read_all_data(c: &mut Criterion) {
+    let path = bench_file();
+    let mut group = c.benchmark_group("read_all_data");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements((SEGMENTS * VALUES_PER_SEGMENT) as u64));
+    for &coalesce in [false, true].iter() {
+        let options = ReadOptions::new().coalesce_reads(coalesce);
+        let tdms_file = TdmsFile::open_with_options(&path, &options).unwrap();
+        let name = if coalesce { "coalesced" } else { "per_segment" };
+        group.bench_with_input(
+            BenchmarkId::from_parameter(name),
+            &tdms_file,
+            |b, tdms_file| {
+                let group = tdms_file.group("Group").unwrap();
+                let channel = group.channel("Channel").unwrap();
+                let mut values = vec![0.0; channel.len() as usize];
+                b.iter(|| channel.read_all_data(&mut values).unwrap())
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, read_all_data);
+criterion_main!(benches);
@@ -0,0 +1,4739 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{BufWriter, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local, Utc};
+use eframe::egui::color::Hsva;
+use eframe::egui::{Color32, ScrollArea, Sense};
+use eframe::{egui, epi};
+use egui::plot::{Legend, Line, Plot, VLine, Value, Values};
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+
+use crate::plot_image::{self, ImageLine, ImageSubplot, PlotImage};
+use crate::spectrum::{self, Spectrum, WindowFunction};
+use rstdms::properties::names::UNIT_STRING;
+use rstdms::{
+    channel_statistics_with_control, export_csv_with_control, CancellationToken, Channel,
+    ChannelStatistics, CsvExportOptions, CsvTimeColumn, FileSummary, Group, MinMaxBucket,
+    NumericStatistics, ReadControl, ReadOptions, SegmentInfo, SkippedSegment, StatisticsOptions,
+    TdmsFile, TdmsProperty, TdmsReadError, TdmsValue, TdsType, Timestamp, WaveformTiming, XMode,
+};
+
+/// Identifies an open file. Ids are not reused after a file is closed,
+/// so a stale id can't refer to a different file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FileId(u64);
+
+/// Identifies a channel by its file, the name of its group and its own name
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ChannelId {
+    pub file: FileId,
+    pub group: String,
+    pub channel: String,
+}
+
+/// An object selected in the channel tree, whose properties are displayed
+#[derive(Clone, Debug, PartialEq)]
+enum TreeSelection {
+    File(FileId),
+    Group(FileId, String),
+    Channel(ChannelId),
+}
+
+impl TreeSelection {
+    fn file_id(&self) -> FileId {
+        match self {
+            TreeSelection::File(file_id) => *file_id,
+            TreeSelection::Group(file_id, _) => *file_id,
+            TreeSelection::Channel(channel_id) => channel_id.file,
+        }
+    }
+}
+
+/// An open file with its channel tree
+struct OpenFile<R>
+where
+    R: Read + Seek,
+{
+    id: FileId,
+    /// Path of the file, which worker threads reopen to load channel data
+    path: PathBuf,
+    /// File name shown in the file selector and in the plot legend
+    name: String,
+    tdms_file: TdmsFile<R>,
+    groups: Vec<GroupEntry>,
+}
+
+/// A property formatted for display in the properties panel
+struct PropertyRow {
+    name: String,
+    value: String,
+    /// The full value when it was too long to display and has been truncated
+    full_value: Option<String>,
+}
+
+/// Maximum number of characters of a property value to display before truncating
+const MAX_PROPERTY_CHARS: usize = 80;
+
+/// A group in the channel tree, with display strings built once when a file is opened
+struct GroupEntry {
+    name: String,
+    channels: Vec<ChannelEntry>,
+}
+
+struct ChannelEntry {
+    id: ChannelId,
+    label: String,
+    data_type: String,
+    length: u64,
+    /// Length formatted for display, eg. "12.5 M"
+    length_label: String,
+    /// Unit from the channel's `unit_string` property, or empty if it has none
+    unit: String,
+    /// Lowercase "group/channel" path matched against the tree filter
+    search_key: String,
+}
+
+/// Order of channels within a group in the channel tree
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum ChannelSort {
+    /// The order channels are stored in the file
+    File,
+    Name,
+    /// Longest channels first
+    Length,
+}
+
+/// Action on the channels of a group, from its checkbox or context menu
+enum GroupAction {
+    Select(Vec<ChannelId>),
+    Deselect(Vec<ChannelId>),
+    Export(Vec<ChannelId>),
+}
+
+/// How many of a group's channels are selected, shown by its checkbox
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum GroupCheck {
+    None,
+    Some,
+    All,
+}
+
+/// Data loaded for plotting a channel
+#[derive(Clone)]
+enum PlotData {
+    /// All values of a channel, paired with their time from the channel's waveform
+    /// timing, or their index if it has none
+    Raw(Vec<(f64, f64)>),
+    /// A min/max envelope of a channel with too many values to plot directly, with each
+    /// bucket paired with the time or index at its midpoint
+    Envelope(Vec<(f64, MinMaxBucket)>),
+    /// Every `stride`th value of a channel with too many values to plot directly,
+    /// starting from the first of `length` values
+    Strided {
+        values: Vec<f64>,
+        stride: u64,
+        length: u64,
+    },
+}
+
+impl PlotData {
+    /// Get the number of channel values the data covers
+    fn value_count(&self) -> u64 {
+        match self {
+            PlotData::Raw(values) => values.len() as u64,
+            PlotData::Envelope(buckets) => buckets
+                .last()
+                .map(|(_, bucket)| bucket.first_index + bucket.count)
+                .unwrap_or(0),
+            PlotData::Strided { length, .. } => *length,
+        }
+    }
+
+    /// Whether some values of the channel were left out
+    fn is_decimated(&self) -> bool {
+        !matches!(self, PlotData::Raw(_))
+    }
+}
+
+/// Data loaded for the values of a channel within the zoomed range, at a higher
+/// resolution than the data loaded for the whole channel
+struct DetailData {
+    /// Start and end indices of the values loaded
+    window: (u64, u64),
+    values: PlotData,
+}
+
+/// Data and timing loaded for a channel
+#[derive(Clone)]
+struct ChannelData {
+    values: PlotData,
+    /// Waveform timing from the channel's properties, if it has any
+    timing: Option<WaveformTiming>,
+}
+
+/// How the x-axis is displayed for channels with waveform timing
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum TimeAxis {
+    /// Seconds relative to each channel's own start time
+    Relative,
+    /// Seconds from the earliest start time of the plotted channels, so channels
+    /// with different start times line up
+    Absolute,
+}
+
+/// How channels with more values than the maximum number of points are reduced for plotting
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum Decimation {
+    /// The minimum and maximum of each bucket of values, so peaks are always shown
+    Envelope,
+    /// Every nth value, which is faster to draw but can miss peaks
+    Stride,
+}
+
+/// How channels are plotted
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PlotMode {
+    /// Each channel is plotted against time or sample index
+    Time,
+    /// One channel is plotted against another
+    Xy,
+}
+
+/// How channels plotted against time are arranged
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum PlotLayout {
+    /// All channels share one plot
+    Overlaid,
+    /// Each channel has its own plot and y-axis, stacked vertically with a shared x-axis
+    Stacked,
+}
+
+/// View shown in the central panel
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CentralTab {
+    Plot,
+    Spectrum,
+    /// Values of a string or timestamp channel, which can't be plotted
+    Table,
+}
+
+/// A page of values of the channel shown in the table view
+enum TablePage {
+    Strings(Vec<String>),
+    Timestamps(Vec<Timestamp>),
+}
+
+/// Number of values read at a time for the table view
+const TABLE_PAGE_ROWS: u64 = 1000;
+
+/// Maximum number of pages of the table view kept in memory
+const MAX_TABLE_PAGES: usize = 16;
+
+/// Spectrum computed from the start of a channel's data
+struct SpectrumData {
+    spectrum: Spectrum,
+    /// Number of values the spectrum was computed from
+    value_count: u64,
+    /// Whether the channel has more values than were used
+    truncated: bool,
+}
+
+/// Overview of a file shown in the file properties window
+struct FileSummaryView {
+    file: FileId,
+    path: PathBuf,
+    /// Size of the file on disk, if it could be read
+    size: Option<u64>,
+    summary: Result<FileSummary, String>,
+    /// Properties of the root object
+    properties: Vec<PropertyRow>,
+}
+
+/// Segments of a file listed in the segments window
+struct SegmentView {
+    file: FileId,
+    segments: Result<Vec<SegmentInfo>, String>,
+    skipped: Vec<SkippedSegment>,
+    /// Segments whose objects are listed
+    expanded: HashSet<usize>,
+    /// Rows of the table, as a segment index and, for object rows, the index
+    /// of the object within the segment
+    rows: Vec<(usize, Option<usize>)>,
+    /// Byte offset entered to find what is stored there
+    locate_text: String,
+    /// Description of what is stored at the last located byte offset
+    location: Option<String>,
+}
+
+impl SegmentView {
+    fn new(
+        file: FileId,
+        segments: Result<Vec<SegmentInfo>, String>,
+        skipped: Vec<SkippedSegment>,
+    ) -> Self {
+        let mut view = SegmentView {
+            file,
+            segments,
+            skipped,
+            expanded: HashSet::new(),
+            rows: Vec::new(),
+            locate_text: String::new(),
+            location: None,
+        };
+        view.update_rows();
+        view
+    }
+
+    /// Show or hide the objects of a segment
+    fn toggle_expanded(&mut self, segment_index: usize) {
+        if !self.expanded.remove(&segment_index) {
+            self.expanded.insert(segment_index);
+        }
+        self.update_rows();
+    }
+
+    fn update_rows(&mut self) {
+        self.rows.clear();
+        if let Ok(segments) = self.segments.as_ref() {
+            for (index, segment) in segments.iter().enumerate() {
+                self.rows.push((index, None));
+                if self.expanded.contains(&index) {
+                    self.rows
+                        .extend((0..segment.objects.len()).map(|object| (index, Some(object))));
+                }
+            }
+        }
+    }
+}
+
+/// Points of one channel plotted against another
+struct XyData {
+    x_channel: ChannelId,
+    y_channel: ChannelId,
+    points: Vec<Value>,
+}
+
+/// Work running on a worker thread, such as loading a channel or exporting data
+struct WorkerJob<T> {
+    /// Fraction of the work done so far, updated by the worker
+    progress: Arc<Mutex<f32>>,
+    cancellation_token: CancellationToken,
+    receiver: Receiver<rstdms::Result<T>>,
+}
+
+impl<T: Send + 'static> WorkerJob<T> {
+    /// Run work on a new thread. The work is given the progress to update
+    /// and a token that is cancelled when the job is cancelled.
+    fn spawn<F>(work: F) -> WorkerJob<T>
+    where
+        F: FnOnce(&Mutex<f32>, CancellationToken) -> rstdms::Result<T> + Send + 'static,
+    {
+        let progress = Arc::new(Mutex::new(0.0));
+        let cancellation_token = CancellationToken::new();
+        let (sender, receiver) = mpsc::channel();
+        {
+            let progress = progress.clone();
+            let cancellation_token = cancellation_token.clone();
+            thread::spawn(move || {
+                let result = work(&progress, cancellation_token);
+                // The receiver is dropped if the job was cancelled, so ignore send errors
+                let _ = sender.send(result);
+            });
+        }
+        WorkerJob {
+            progress,
+            cancellation_token,
+            receiver,
+        }
+    }
+
+    fn cancel(&self) {
+        self.cancellation_token.cancel();
+    }
+
+    fn progress(&self) -> f32 {
+        get_progress(&self.progress)
+    }
+}
+
+/// A line ready to be added to the plot
+struct PlotLine {
+    name: String,
+    /// Unit from the channel's `unit_string` property, or empty if it has none
+    unit: String,
+    color: Color32,
+    points: Vec<Value>,
+}
+
+/// Lines to plot with a description of their x-axis
+struct PlotView {
+    lines: Vec<PlotLine>,
+    x_label: String,
+    /// Shown when channels are plotted against different kinds of x-axis
+    warning: Option<String>,
+    /// How sample indices map to x values for each channel plotted against time
+    channel_axes: Vec<ChannelAxis>,
+    /// Range of x values of all plotted points
+    x_range: Option<(f64, f64)>,
+    /// Statistics of the plotted values of each channel within the zoomed range
+    visible_statistics: Vec<StatisticsRow>,
+}
+
+impl PlotLine {
+    /// Name with the unit if known, eg. "Group / AI0 [V]"
+    fn label(&self) -> String {
+        match self.unit.as_str() {
+            "" => self.name.clone(),
+            unit => format!("{} [{}]", self.name, unit),
+        }
+    }
+}
+
+impl PlotView {
+    /// Group the lines into one subplot per channel. Envelopes are plotted
+    /// as two lines with the same name, which share a subplot.
+    fn subplots(&self) -> Vec<Vec<&PlotLine>> {
+        let mut subplots: Vec<Vec<&PlotLine>> = Vec::new();
+        for line in self.lines.iter() {
+            match subplots
+                .iter_mut()
+                .find(|subplot| subplot[0].name == line.name)
+            {
+                Some(subplot) => subplot.push(line),
+                None => subplots.push(vec![line]),
+            }
+        }
+        subplots
+    }
+
+    /// Units of the plotted lines without duplicates, where lines
+    /// without a unit have an empty string
+    fn units(&self) -> Vec<&str> {
+        let mut units: Vec<&str> = Vec::new();
+        for line in self.lines.iter() {
+            if !units.contains(&line.unit.as_str()) {
+                units.push(&line.unit);
+            }
+        }
+        units
+    }
+
+    /// The unit of all plotted lines, if they all have the same unit
+    fn shared_unit(&self) -> Option<&str> {
+        match self.units()[..] {
+            [unit] if !unit.is_empty() => Some(unit),
+            _ => None,
+        }
+    }
+
+    /// Whether lines with different units are plotted
+    fn has_mixed_units(&self) -> bool {
+        self.units().iter().filter(|unit| !unit.is_empty()).count() > 1
+    }
+}
+
+/// Maps the sample indices of a plotted channel to x values
+#[derive(Clone)]
+struct ChannelAxis {
+    channel_id: ChannelId,
+    name: String,
+    /// x value of the first sample
+    offset: f64,
+    /// Difference in x between consecutive samples
+    scale: f64,
+}
+
+impl ChannelAxis {
+    /// Get the (possibly fractional) sample index at an x value
+    fn sample_position(&self, x: f64) -> f64 {
+        (x - self.offset) / self.scale
+    }
+
+    /// Get the start and end indices of the samples of a channel with `length`
+    /// values that are within an x range, including one sample either side
+    fn visible_samples(&self, range: (f64, f64), length: u64) -> (u64, u64) {
+        let clamp = |position: f64| {
+            if position.is_nan() {
+                0
+            } else {
+                position.max(0.0).min(length as f64) as u64
+            }
+        };
+        let first = self.sample_position(range.0);
+        let last = self.sample_position(range.1);
+        (
+            clamp(first.min(last).floor() - 1.0),
+            clamp(first.max(last).ceil() + 2.0),
+        )
+    }
+}
+
+/// Values the statistics panel is computed over
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum StatisticsScope {
+    /// All values of each channel, read from the file
+    Channel,
+    /// Values within the zoomed range, or all plotted values if not zoomed,
+    /// computed from the loaded data
+    Visible,
+}
+
+/// Statistics of a plotted channel, or a message explaining why they aren't available
+struct StatisticsRow {
+    name: String,
+    statistics: Result<NumericStatistics, String>,
+}
+
+/// Values of a channel at the plot cursors
+struct CursorReadout {
+    name: String,
+    values: [Option<f64>; 2],
+}
+
+/// Maximum relative difference in length of channels plotted against each other.
+/// Channels within this tolerance are truncated to the shorter length.
+const XY_LENGTH_TOLERANCE: f64 = 0.01;
+
+/// Number of values read at a time when loading data on worker threads
+const CHUNK_VALUES: usize = 64 * 1024;
+
+/// Maximum number of values used to compute a spectrum
+const MAX_SPECTRUM_VALUES: u64 = 1 << 20;
+
+/// Default maximum number of points plotted per channel before decimating
+const DEFAULT_MAX_POINTS: usize = 5000;
+
+/// Range of the maximum number of points plotted per channel that can be chosen
+const MAX_POINTS_RANGE: std::ops::RangeInclusive<usize> = 1_000..=1_000_000;
+
+/// Default maximum number of rows of plot data copied to the clipboard
+const DEFAULT_MAX_COPY_ROWS: usize = 100_000;
+
+/// Maximum number of channels loaded at once. Further loads wait until one finishes.
+const MAX_CONCURRENT_LOADS: usize = 4;
+
+/// Maximum number of channels plotted when selecting all channels of a group
+const MAX_GROUP_PLOT_CHANNELS: usize = 32;
+
+/// Smallest height of each plot in the stacked layout, in points
+const MIN_SUBPLOT_HEIGHT: f32 = 120.0;
+
+/// Maximum number of files in the recent files menu
+const MAX_RECENT_FILES: usize = 10;
+
+/// Settings saved when the viewer closes and restored when it next starts.
+/// Missing settings take their default values, so settings saved by older
+/// versions can still be read.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct AppSettings {
+    /// Most recently opened files, most recent first
+    recent_files: Vec<PathBuf>,
+    max_points: usize,
+    decimation: Decimation,
+    max_copy_rows: usize,
+    time_axis: TimeAxis,
+    channel_sort: ChannelSort,
+    follow_interval: f64,
+    plot_layout: PlotLayout,
+    image_size: (u32, u32),
+    image_caption: bool,
+    confirm_load_gb: f64,
+    overview: bool,
+}
+
+impl Default for AppSettings {
+    fn default() -> AppSettings {
+        AppSettings {
+            recent_files: Vec::new(),
+            max_points: DEFAULT_MAX_POINTS,
+            decimation: Decimation::Envelope,
+            max_copy_rows: DEFAULT_MAX_COPY_ROWS,
+            time_axis: TimeAxis::Relative,
+            channel_sort: ChannelSort::File,
+            follow_interval: DEFAULT_FOLLOW_INTERVAL,
+            plot_layout: PlotLayout::Overlaid,
+            image_size: plot_image::DEFAULT_IMAGE_SIZE,
+            image_caption: true,
+            confirm_load_gb: DEFAULT_CONFIRM_LOAD_GB,
+            overview: false,
+        }
+    }
+}
+
+/// Default estimated size of a channel's data, in gigabytes, above which plotting
+/// the channel must be confirmed
+const DEFAULT_CONFIRM_LOAD_GB: f64 = 1.0;
+
+/// Directory holding the metadata caches of files opened with an overview
+fn overview_cache_dir() -> PathBuf {
+    std::env::temp_dir().join("rstdms-view-cache")
+}
+
+/// Default time between refreshes of files being followed
+const DEFAULT_FOLLOW_INTERVAL: f64 = 2.0;
+
+/// How long a warning that refreshing a followed file failed is shown for
+const FOLLOW_WARNING_DURATION: Duration = Duration::from_secs(5);
+
+/// Time the zoomed range must stay unchanged before loading data within it,
+/// so adjusting the range doesn't start a load every frame
+const DETAIL_LOAD_DELAY: Duration = Duration::from_millis(300);
+
+pub struct TemplateApp<R>
+where
+    R: Read + Seek,
+{
+    /// Open files, in the order they were opened
+    files: Vec<OpenFile<R>>,
+    /// File whose channel tree is shown
+    active_file: Option<FileId>,
+    /// Id given to the next file opened
+    next_file_id: u64,
+    /// Most recently opened files, most recent first
+    recent_files: Vec<PathBuf>,
+    /// Whether channels from different files can be plotted together. When this
+    /// is off, only channels from the active file are plotted.
+    compare_mode: bool,
+    /// Text used to filter the channels shown in the tree
+    channel_filter: String,
+    /// Order of channels within each group of the tree
+    channel_sort: ChannelSort,
+    /// Object selected in the channel tree
+    tree_selection: Option<TreeSelection>,
+    /// Channels shown in the tree in display order, recorded when the tree is
+    /// drawn, for moving the selection with the keyboard
+    tree_order: Vec<ChannelId>,
+    /// Channel a range selected with shift and the arrow keys starts from
+    range_anchor: Option<ChannelId>,
+    /// Whether the tree scrolls to the selected channel when next drawn
+    scroll_to_selection: bool,
+    /// Properties of the selected object, formatted when the selection changes
+    property_rows: Vec<PropertyRow>,
+    /// Channels to plot, in the order they were selected
+    selected_channels: Vec<ChannelId>,
+    /// Data loaded for each selected channel
+    channel_data: HashMap<ChannelId, ChannelData>,
+    /// Channels with more values than this are decimated for plotting
+    max_points: usize,
+    decimation: Decimation,
+    /// Maximum number of rows of plot data copied to the clipboard
+    max_copy_rows: usize,
+    /// How channels with waveform timing are plotted
+    time_axis: TimeAxis,
+    /// Whether channels are plotted against time or against each other
+    plot_mode: PlotMode,
+    plot_layout: PlotLayout,
+    /// Channel plotted on the x-axis in XY mode
+    xy_x_channel: Option<ChannelId>,
+    /// Channel plotted on the y-axis in XY mode
+    xy_y_channel: Option<ChannelId>,
+    /// Whether the XY plot uses the same scale for both axes
+    equal_aspect: bool,
+    /// Points loaded for the XY plot
+    xy_data: Option<XyData>,
+    /// Load of the XY plot points in progress
+    xy_load: Option<WorkerJob<XyData>>,
+    /// Whether the plot cursors are shown
+    cursors_enabled: bool,
+    /// Whether cursors snap to the samples of the first plotted channel
+    snap_cursors: bool,
+    /// x values of the two cursors, or None to place them when next shown
+    cursor_positions: Option<[f64; 2]>,
+    /// Values of each channel at the cursors, or None if they need reading
+    cursor_readouts: Option<Vec<CursorReadout>>,
+    /// Range of x values the plot is zoomed to, or None to show all data
+    zoom_range: Option<(f64, f64)>,
+    /// Where the plot's context menu was opened, or None if it is closed
+    plot_menu_position: Option<egui::Pos2>,
+    /// When to start loading data within the zoomed range, if a load is due
+    detail_load_due: Option<Instant>,
+    /// Data within the zoomed range for channels plotted as an envelope
+    detail_data: HashMap<ChannelId, DetailData>,
+    /// Loads of data within the zoomed range in progress
+    detail_loads: HashMap<ChannelId, WorkerJob<DetailData>>,
+    /// Whether the statistics panel is shown
+    show_statistics: bool,
+    statistics_scope: StatisticsScope,
+    /// Statistics of all values of each channel, computed when first shown
+    channel_statistics: HashMap<ChannelId, Result<NumericStatistics, String>>,
+    /// Computations of channel statistics in progress
+    statistics_loads: HashMap<ChannelId, WorkerJob<ChannelStatistics>>,
+    /// Whether open files are refreshed periodically to show data as it is written
+    follow: bool,
+    /// Time between refreshes in seconds when following files
+    follow_interval: f64,
+    /// Whether a zoomed plot scrolls to keep the latest data visible when following files
+    auto_scroll: bool,
+    /// When files were last refreshed
+    last_refresh: Option<Instant>,
+    /// Stops the thread requesting repaints so files are refreshed on time
+    follow_ticker: Option<CancellationToken>,
+    /// Error from the last refresh and when it happened, shown for a short time
+    follow_warning: Option<(String, Instant)>,
+    /// View shown in the central panel
+    central_tab: CentralTab,
+    /// Channel to show the spectrum of, or None to use the first selected channel
+    spectrum_channel: Option<ChannelId>,
+    spectrum_window: WindowFunction,
+    /// Channel and window the current spectrum was or is being computed for
+    spectrum_request: Option<(ChannelId, WindowFunction)>,
+    spectrum: Option<SpectrumData>,
+    spectrum_load: Option<WorkerJob<SpectrumData>>,
+    /// String or timestamp channel shown in the table view
+    table_channel: Option<ChannelId>,
+    /// Pages of values of the table channel read so far, by page index
+    table_pages: HashMap<u64, TablePage>,
+    /// Whether timestamps in the table are shown in UTC rather than local time
+    table_utc: bool,
+    /// Plot built from the loaded channel data, or None if it needs rebuilding
+    cached_plot: Option<PlotView>,
+    /// Channels currently being loaded in the background
+    loads: HashMap<ChannelId, WorkerJob<ChannelData>>,
+    /// Channels waiting to be loaded once fewer loads are running
+    queued_loads: Vec<ChannelId>,
+    /// Channels waiting for confirmation before being plotted, with the estimated
+    /// size in bytes of their data
+    pending_loads: Vec<(ChannelId, u64)>,
+    /// Plotting channels with more data than this many gigabytes, estimated from the
+    /// file's metadata, must be confirmed
+    confirm_load_gb: f64,
+    /// Whether files are opened with a per segment overview of their data, stored in a
+    /// metadata cache, which is drawn while channels are loading
+    overview: bool,
+    /// Export currently running in the background
+    export: Option<WorkerJob<u64>>,
+    /// Whether the settings window is shown
+    show_settings: bool,
+    /// Whether the window summarizing the active file is shown
+    show_file_summary: bool,
+    /// Summary of the active file, computed when the file properties window is shown
+    file_summary: Option<FileSummaryView>,
+    /// Whether the window listing the segments of the active file is shown
+    show_segments: bool,
+    /// Segments of the active file, read when the segments window is shown
+    segment_view: Option<SegmentView>,
+    /// Size in pixels of saved plot images
+    image_size: (u32, u32),
+    /// Whether saved plot images are captioned with the file and channel names
+    image_caption: bool,
+    /// Error from a failed load or export, shown until dismissed
+    error_message: Option<String>,
+    /// TDMS file to offer to open with the error message, after an index file was opened
+    suggested_file: Option<PathBuf>,
+}
+
+impl<R> Default for TemplateApp<R>
+where
+    R: Read + Seek,
+{
+    fn default() -> Self {
+        Self {
+            files: Vec::new(),
+            active_file: None,
+            next_file_id: 0,
+            recent_files: Vec::new(),
+            compare_mode: false,
+            channel_filter: String::new(),
+            channel_sort: ChannelSort::File,
+            tree_selection: None,
+            tree_order: Vec::new(),
+            range_anchor: None,
+            scroll_to_selection: false,
+            property_rows: Vec::new(),
+            selected_channels: Vec::new(),
+            channel_data: HashMap::new(),
+            max_points: DEFAULT_MAX_POINTS,
+            decimation: Decimation::Envelope,
+            max_copy_rows: DEFAULT_MAX_COPY_ROWS,
+            time_axis: TimeAxis::Relative,
+            plot_mode: PlotMode::Time,
+            plot_layout: PlotLayout::Overlaid,
+            xy_x_channel: None,
+            xy_y_channel: None,
+            equal_aspect: false,
+            xy_data: None,
+            xy_load: None,
+            cursors_enabled: false,
+            snap_cursors: false,
+            cursor_positions: None,
+            cursor_readouts: None,
+            zoom_range: None,
+            plot_menu_position: None,
+            detail_load_due: None,
+            detail_data: HashMap::new(),
+            detail_loads: HashMap::new(),
+            show_statistics: false,
+            statistics_scope: StatisticsScope::Visible,
+            channel_statistics: HashMap::new(),
+            statistics_loads: HashMap::new(),
+            follow: false,
+            follow_interval: DEFAULT_FOLLOW_INTERVAL,
+            auto_scroll: true,
+            last_refresh: None,
+            follow_ticker: None,
+            follow_warning: None,
+            central_tab: CentralTab::Plot,
+            spectrum_channel: None,
+            spectrum_window: WindowFunction::Hann,
+            spectrum_request: None,
+            spectrum: None,
+            spectrum_load: None,
+            table_channel: None,
+            table_pages: HashMap::new(),
+            table_utc: false,
+            cached_plot: None,
+            loads: HashMap::new(),
+            queued_loads: Vec::new(),
+            pending_loads: Vec::new(),
+            confirm_load_gb: DEFAULT_CONFIRM_LOAD_GB,
+            overview: false,
+            export: None,
+            show_settings: false,
+            show_file_summary: false,
+            file_summary: None,
+            show_segments: false,
+            segment_view: None,
+            image_size: plot_image::DEFAULT_IMAGE_SIZE,
+            image_caption: true,
+            error_message: None,
+            suggested_file: None,
+        }
+    }
+}
+
+// Helper functions for loading channels, calls out to rstdms lib functions
+impl TemplateApp<std::fs::File> {
+    fn open_dialog(&mut self) {
+        if let Some(path) = FileDialog::new().pick_file() {
+            self.open_file(path);
+        }
+    }
+
+    /// Open a TDMS file and make it the active file. If the file is already open,
+    /// it is made active rather than being opened again.
+    pub fn open_file(&mut self, path: PathBuf) {
+        if let Some(file_id) = self
+            .files
+            .iter()
+            .find(|file| file.path == path)
+            .map(|file| file.id)
+        {
+            self.set_active_file(file_id);
+            return;
+        }
+        let opened = if self.overview {
+            let options = ReadOptions::new().overview(true);
+            TdmsFile::open_cached_with_options(&path, overview_cache_dir(), &options)
+        } else {
+            TdmsFile::open(&path)
+        };
+        match opened {
+            Ok(tdms_file) => {
+                let id = FileId(self.next_file_id);
+                self.next_file_id += 1;
+                let name = match path.file_name() {
+                    Some(name) => name.to_string_lossy().into_owned(),
+                    None => path.display().to_string(),
+                };
+                let groups = build_channel_tree(id, &tdms_file);
+                self.add_recent_file(path.clone());
+                self.files.push(OpenFile {
+                    id,
+                    path,
+                    name,
+                    tdms_file,
+                    groups,
+                });
+                self.set_active_file(id);
+            }
+            Err(TdmsReadError::IndexFileProvided { data_path, .. }) if data_path.exists() => {
+                self.error_message = Some(format!(
+                    "{} is a TDMS index file, which only contains metadata. \
+                    Open the data file {} instead?",
+                    path.display(),
+                    data_path.display()
+                ));
+                self.suggested_file = Some(data_path);
+            }
+            Err(err) => {
+                if !path.exists() {
+                    self.recent_files.retain(|recent_file| recent_file != &path);
+                }
+                self.error_message = Some(format!("Could not open {}", err));
+                self.suggested_file = None;
+            }
+        }
+    }
+
+    /// Open the first dropped TDMS file
+    fn open_dropped_files(&mut self, paths: Vec<PathBuf>) {
+        let (tdms_paths, other_paths): (Vec<PathBuf>, Vec<PathBuf>) =
+            paths.into_iter().partition(|path| match path.extension() {
+                // Index files are opened to offer to open their data file instead
+                Some(extension) => {
+                    extension.eq_ignore_ascii_case("tdms")
+                        || extension.eq_ignore_ascii_case("tdms_index")
+                }
+                None => false,
+            });
+        if let Some(path) = tdms_paths.into_iter().next() {
+            self.open_file(path);
+        }
+        if !other_paths.is_empty() {
+            let names: Vec<String> = other_paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            self.error_message = Some(format!(
+                "Only .tdms files can be opened, ignored: {}",
+                names.join(", ")
+            ));
+        }
+    }
+
+    /// Ask for an output path and export the given channels to CSV
+    fn export_dialog(&mut self, channels: Vec<ChannelId>) {
+        if channels.is_empty() {
+            self.error_message = Some(String::from("Select channels to export"));
+            return;
+        }
+        if channels.iter().any(|id| id.file != channels[0].file) {
+            self.error_message = Some(String::from(
+                "Channels from different files can't be exported together",
+            ));
+            return;
+        }
+        if let Some(path) = FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name("export.csv")
+            .save_file()
+        {
+            self.start_export(path, channels);
+        }
+    }
+
+    /// Ask for an output path and save the current plot as a PNG image
+    fn save_image_dialog(&mut self) {
+        if self.selected_channels.is_empty() {
+            self.error_message = Some(String::from("Select channels to plot"));
+            return;
+        }
+        if let Some(mut path) = FileDialog::new()
+            .add_filter("PNG image", &["png"])
+            .set_file_name("plot.png")
+            .save_file()
+        {
+            if path.extension().is_none() {
+                path.set_extension("png");
+            }
+            if let Err(err) = self.save_image(&path) {
+                self.error_message =
+                    Some(format!("Could not save image {}: {}", path.display(), err));
+            }
+        }
+    }
+
+    /// Render the plot as shown, including the zoomed range and cursors, to a PNG file
+    fn save_image(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let caption = if self.image_caption {
+            Some(self.image_caption_text())
+        } else {
+            None
+        };
+        let (x_range, cursors) = match self.plot_mode {
+            PlotMode::Time => {
+                let cursors = match self.cursor_positions {
+                    Some(positions) if self.cursors_enabled => positions.to_vec(),
+                    _ => Vec::new(),
+                };
+                (self.zoom_range, cursors)
+            }
+            PlotMode::Xy => (None, Vec::new()),
+        };
+        let stacked = self.plot_mode == PlotMode::Time && self.plot_layout == PlotLayout::Stacked;
+        let size = self.image_size;
+        let plot_view = self.plot_view();
+        let image_line = |line: &PlotLine| ImageLine {
+            name: line.label(),
+            color: (line.color.r(), line.color.g(), line.color.b()),
+            points: line.points.iter().map(|point| (point.x, point.y)).collect(),
+        };
+        let subplots = if stacked {
+            plot_view
+                .subplots()
+                .into_iter()
+                .map(|lines| ImageSubplot {
+                    label: Some(lines[0].label()),
+                    legend: false,
+                    lines: lines.into_iter().map(image_line).collect(),
+                })
+                .collect()
+        } else {
+            vec![ImageSubplot {
+                label: plot_view.shared_unit().map(String::from),
+                legend: true,
+                lines: plot_view.lines.iter().map(image_line).collect(),
+            }]
+        };
+        let image = PlotImage {
+            subplots,
+            x_label: plot_view.x_label.clone(),
+            x_range,
+            cursors,
+            caption,
+        };
+        plot_image::save_png(path, &image, size)
+    }
+
+    /// Names of the files and channels plotted, eg. "run1.tdms: Group / AI0, Group / AI1"
+    fn image_caption_text(&self) -> String {
+        let mut file_names: Vec<&str> = Vec::new();
+        for channel_id in self.selected_channels.iter() {
+            if let Some(file) = self.file(channel_id.file) {
+                if !file_names.contains(&file.name.as_str()) {
+                    file_names.push(&file.name);
+                }
+            }
+        }
+        let channel_names: Vec<String> = self
+            .selected_channels
+            .iter()
+            .map(|channel_id| format!("{} / {}", channel_id.group, channel_id.channel))
+            .collect();
+        format!("{}: {}", file_names.join(", "), channel_names.join(", "))
+    }
+}
+
+/// Build the channel tree of a file, with display strings built once when the file is opened
+fn build_channel_tree<R: Read + Seek>(file_id: FileId, tdms_file: &TdmsFile<R>) -> Vec<GroupEntry> {
+    tdms_file
+        .groups()
+        .map(|group| {
+            let channels = group
+                .channels()
+                .map(|channel| {
+                    // Only metadata is used, so building the tree doesn't read any data
+                    let data_type = match channel.data_type() {
+                        Some(data_type) => data_type.to_string(),
+                        None => String::from("no data"),
+                    };
+                    let unit = channel
+                        .properties()
+                        .iter()
+                        .find(|property| property.name == UNIT_STRING)
+                        .map(|property| match &property.value {
+                            TdmsValue::String(unit) => unit.clone(),
+                            value => format_property_value(value),
+                        })
+                        .unwrap_or_default();
+                    ChannelEntry {
+                        id: ChannelId {
+                            file: file_id,
+                            group: group.name().to_string(),
+                            channel: channel.name().to_string(),
+                        },
+                        // here we strip new lines for display purposes.
+                        label: channel.name().replace("\n", " "),
+                        data_type,
+                        length: channel.len(),
+                        length_label: format_length(channel.len()),
+                        unit,
+                        search_key: format!("{}/{}", group.name(), channel.name()).to_lowercase(),
+                    }
+                })
+                .collect();
+            GroupEntry {
+                name: group.name().to_string(),
+                channels,
+            }
+        })
+        .collect()
+}
+
+impl<R: Read + Seek> TemplateApp<R> {
+    fn file(&self, file_id: FileId) -> Option<&OpenFile<R>> {
+        self.files.iter().find(|file| file.id == file_id)
+    }
+
+    /// Get the unit of a channel shown in the channel tree, or an empty string if it has none
+    fn channel_unit(&self, channel_id: &ChannelId) -> String {
+        self.file(channel_id.file)
+            .and_then(|file| {
+                file.groups
+                    .iter()
+                    .find(|group| group.name == channel_id.group)
+            })
+            .and_then(|group| {
+                group
+                    .channels
+                    .iter()
+                    .find(|channel| &channel.id == channel_id)
+            })
+            .map(|channel| channel.unit.clone())
+            .unwrap_or_default()
+    }
+
+    /// Move a file to the top of the recent files list
+    fn add_recent_file(&mut self, path: PathBuf) {
+        self.recent_files.retain(|recent_file| recent_file != &path);
+        self.recent_files.insert(0, path);
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+
+    fn settings(&self) -> AppSettings {
+        AppSettings {
+            recent_files: self.recent_files.clone(),
+            max_points: self.max_points,
+            decimation: self.decimation,
+            max_copy_rows: self.max_copy_rows,
+            time_axis: self.time_axis,
+            channel_sort: self.channel_sort,
+            follow_interval: self.follow_interval,
+            plot_layout: self.plot_layout,
+            image_size: self.image_size,
+            image_caption: self.image_caption,
+            confirm_load_gb: self.confirm_load_gb,
+            overview: self.overview,
+        }
+    }
+
+    /// Restore saved settings. Files already opened, eg. from the command line,
+    /// stay at the top of the recent files list, and files that no longer exist
+    /// are removed from it.
+    fn apply_settings(&mut self, settings: AppSettings) {
+        let mut recent_files = std::mem::take(&mut self.recent_files);
+        for path in settings.recent_files {
+            if path.exists() && !recent_files.contains(&path) {
+                recent_files.push(path);
+            }
+        }
+        recent_files.truncate(MAX_RECENT_FILES);
+        self.recent_files = recent_files;
+        if settings.max_points != self.max_points || settings.decimation != self.decimation {
+            self.max_points = settings.max_points;
+            self.decimation = settings.decimation;
+            self.reload_selection();
+        }
+        self.max_copy_rows = settings.max_copy_rows;
+        self.time_axis = settings.time_axis;
+        self.channel_sort = settings.channel_sort;
+        self.follow_interval = settings.follow_interval;
+        self.plot_layout = settings.plot_layout;
+        self.image_size = settings.image_size;
+        self.image_caption = settings.image_caption;
+        self.confirm_load_gb = settings.confirm_load_gb;
+        self.overview = settings.overview;
+        self.cached_plot = None;
+    }
+
+    /// Show the channel tree of a file. Unless in compare mode, channels
+    /// from other files are removed from the plot.
+    fn set_active_file(&mut self, file_id: FileId) {
+        self.active_file = Some(file_id);
+        if !self.compare_mode {
+            self.retain_selection(|channel_id| channel_id.file == file_id);
+        }
+        if let Some(selection) = self.tree_selection.as_ref() {
+            if selection.file_id() != file_id {
+                self.select_tree_item(None);
+            }
+        }
+    }
+
+    /// Close a file, removing its channels from the plot and freeing their data
+    fn close_file(&mut self, file_id: FileId) {
+        self.retain_selection(|channel_id| channel_id.file != file_id);
+        if let Some(selection) = self.tree_selection.as_ref() {
+            if selection.file_id() == file_id {
+                self.select_tree_item(None);
+            }
+        }
+        self.files.retain(|file| file.id != file_id);
+        if self.active_file == Some(file_id) {
+            self.active_file = self.files.last().map(|file| file.id);
+        }
+    }
+
+    /// Turn compare mode on or off. Turning it off removes channels that
+    /// aren't in the active file from the plot.
+    fn set_compare_mode(&mut self, compare_mode: bool) {
+        self.compare_mode = compare_mode;
+        if !compare_mode {
+            let active_file = self.active_file;
+            self.retain_selection(|channel_id| Some(channel_id.file) == active_file);
+        }
+        self.cached_plot = None;
+    }
+
+    /// Select an object in the tree and format its properties for display
+    fn select_tree_item(&mut self, selection: Option<TreeSelection>) {
+        self.property_rows.clear();
+        let file = selection
+            .as_ref()
+            .and_then(|selection| self.file(selection.file_id()));
+        if let (Some(file), Some(selection)) = (file, selection.as_ref()) {
+            let file = &file.tdms_file;
+            let properties = match selection {
+                TreeSelection::File(_) => Some(file.properties().to_vec()),
+                TreeSelection::Group(_, group_name) => file
+                    .group(group_name)
+                    .map(|group| group.properties().to_vec()),
+                TreeSelection::Channel(channel_id) => {
+                    file.group(&channel_id.group).and_then(|group| {
+                        group
+                            .channel(&channel_id.channel)
+                            .map(|channel| channel.properties().to_vec())
+                    })
+                }
+            };
+            self.property_rows = properties
+                .unwrap_or_default()
+                .iter()
+                .map(property_row)
+                .collect();
+        }
+        self.tree_selection = selection;
+    }
+
+    fn select_channel(&mut self, channel_id: ChannelId) {
+        if self.selected_channels.contains(&channel_id) {
+            return;
+        }
+        if !self.is_plottable(&channel_id) {
+            self.show_table(channel_id);
+            return;
+        }
+        let estimated_bytes = self.estimated_load_bytes(&channel_id);
+        if estimated_bytes as f64 > self.confirm_load_gb * 1e9 {
+            if !self.pending_loads.iter().any(|(id, _)| id == &channel_id) {
+                self.pending_loads.push((channel_id, estimated_bytes));
+            }
+            return;
+        }
+        self.plot_channel(channel_id);
+    }
+
+    /// Add a channel to the plot and start loading its data
+    fn plot_channel(&mut self, channel_id: ChannelId) {
+        if self.central_tab == CentralTab::Table {
+            self.central_tab = CentralTab::Plot;
+        }
+        self.start_load(channel_id.clone());
+        self.selected_channels.push(channel_id);
+        self.cached_plot = None;
+        self.reset_cursors();
+    }
+
+    /// Estimated size in bytes of all values of a channel read as f64 for plotting
+    fn estimated_load_bytes(&self, channel_id: &ChannelId) -> u64 {
+        self.file(channel_id.file)
+            .and_then(|file| {
+                let group = file.tdms_file.group(&channel_id.group)?;
+                let channel = group.channel(&channel_id.channel)?;
+                Some(channel.estimated_read_bytes(Some(TdsType::DoubleFloat)))
+            })
+            .unwrap_or(0)
+    }
+
+    /// Whether a channel is plotted rather than shown in the table view
+    fn is_plottable(&self, channel_id: &ChannelId) -> bool {
+        let data_type = self.file(channel_id.file).and_then(|file| {
+            let group = file.tdms_file.group(&channel_id.group)?;
+            group.channel(&channel_id.channel)?.data_type()
+        });
+        !data_type.is_some_and(|data_type| data_type.is_string() || data_type.is_timestamp())
+    }
+
+    /// Plot the channels of a group that can be plotted. Only the first
+    /// `MAX_GROUP_PLOT_CHANNELS` not already plotted are selected, to keep large
+    /// groups responsive, and their data is loaded a few channels at a time.
+    fn select_group_channels(&mut self, group_name: &str, channels: Vec<ChannelId>) {
+        let new_channels: Vec<ChannelId> = channels
+            .into_iter()
+            .filter(|id| !self.selected_channels.contains(id) && self.is_plottable(id))
+            .collect();
+        if new_channels.len() > MAX_GROUP_PLOT_CHANNELS {
+            self.error_message = Some(format!(
+                "Only the first {} of {} channels in {} were plotted. \
+                 Export the group to get the data of all channels.",
+                MAX_GROUP_PLOT_CHANNELS,
+                new_channels.len(),
+                group_name
+            ));
+        }
+        for channel_id in new_channels.into_iter().take(MAX_GROUP_PLOT_CHANNELS) {
+            self.select_channel(channel_id);
+        }
+    }
+
+    /// Navigate the channel tree with the keyboard. Up and down move the selected
+    /// channel, and with shift also plot the channels moved over. Enter or space
+    /// toggles plotting the selected channel, and Ctrl+A plots all channels of its group.
+    fn handle_tree_keys(&mut self, ctx: &egui::CtxRef) {
+        if ctx.wants_keyboard_input() || self.tree_order.is_empty() {
+            return;
+        }
+        let (down, up, toggle, select_all, shift) = {
+            let input = ctx.input();
+            (
+                input.key_pressed(egui::Key::ArrowDown),
+                input.key_pressed(egui::Key::ArrowUp),
+                input.key_pressed(egui::Key::Enter) || input.key_pressed(egui::Key::Space),
+                input.modifiers.command && input.key_pressed(egui::Key::A),
+                input.modifiers.shift,
+            )
+        };
+        let selected_channel = match self.tree_selection.as_ref() {
+            Some(TreeSelection::Channel(channel_id)) => Some(channel_id.clone()),
+            _ => None,
+        };
+        let position = selected_channel
+            .as_ref()
+            .and_then(|channel_id| self.tree_order.iter().position(|id| id == channel_id));
+
+        if down || up {
+            let new_position = match position {
+                Some(position) if down => (position + 1).min(self.tree_order.len() - 1),
+                Some(position) => position.saturating_sub(1),
+                // Start from the first channel of a selected group
+                None => match self.tree_selection.as_ref() {
+                    Some(TreeSelection::Group(file_id, group_name)) => self
+                        .tree_order
+                        .iter()
+                        .position(|id| id.file == *file_id && &id.group == group_name)
+                        .unwrap_or(0),
+                    _ => 0,
+                },
+            };
+            let channel_id = self.tree_order[new_position].clone();
+            if shift {
+                let anchor = self
+                    .range_anchor
+                    .clone()
+                    .or_else(|| selected_channel.clone())
+                    .unwrap_or_else(|| channel_id.clone());
+                let anchor_position = self
+                    .tree_order
+                    .iter()
+                    .position(|id| id == &anchor)
+                    .unwrap_or(new_position);
+                let range = anchor_position.min(new_position)..=anchor_position.max(new_position);
+                let channels: Vec<ChannelId> = self.tree_order[range].to_vec();
+                for id in channels {
+                    if self.is_plottable(&id) {
+                        self.select_channel(id);
+                    }
+                }
+                self.range_anchor = Some(anchor);
+            } else {
+                self.range_anchor = None;
+            }
+            self.select_tree_item(Some(TreeSelection::Channel(channel_id)));
+            self.scroll_to_selection = true;
+        }
+
+        if toggle {
+            if let Some(channel_id) = selected_channel.as_ref() {
+                let plotted = self.selected_channels.contains(channel_id)
+                    || self.table_channel.as_ref() == Some(channel_id);
+                if plotted {
+                    self.deselect_channel(channel_id);
+                } else {
+                    self.select_channel(channel_id.clone());
+                }
+            }
+        }
+
+        if select_all {
+            let group = match self.tree_selection.as_ref() {
+                Some(TreeSelection::Channel(channel_id)) => {
+                    Some((channel_id.file, channel_id.group.clone()))
+                }
+                Some(TreeSelection::Group(file_id, group_name)) => {
+                    Some((*file_id, group_name.clone()))
+                }
+                _ => None,
+            };
+            if let Some((file_id, group_name)) = group {
+                let channels = self
+                    .file(file_id)
+                    .and_then(|file| file.groups.iter().find(|group| group.name == group_name))
+                    .map(|group| group.channels.iter().map(|c| c.id.clone()).collect())
+                    .unwrap_or_default();
+                self.select_group_channels(&group_name, channels);
+            }
+        }
+    }
+
+    /// Get the summary of the active file, computing it if the active file has changed
+    fn file_summary(&mut self) -> Option<&FileSummaryView> {
+        let file_id = self.active_file?;
+        if self.file_summary.as_ref().map(|view| view.file) != Some(file_id) {
+            let file = self.file(file_id)?;
+            let view = FileSummaryView {
+                file: file_id,
+                path: file.path.clone(),
+                size: std::fs::metadata(&file.path)
+                    .ok()
+                    .map(|metadata| metadata.len()),
+                summary: file.tdms_file.summary().map_err(|err| err.to_string()),
+                properties: file
+                    .tdms_file
+                    .properties()
+                    .iter()
+                    .map(property_row)
+                    .collect(),
+            };
+            self.file_summary = Some(view);
+        }
+        self.file_summary.as_ref()
+    }
+
+    /// Get the segments of the active file, reading them if the active file has changed
+    fn segment_view(&mut self) -> Option<&mut SegmentView> {
+        let file_id = self.active_file?;
+        if self.segment_view.as_ref().map(|view| view.file) != Some(file_id) {
+            let file = self.file(file_id)?;
+            let segments = file.tdms_file.segments().map_err(|err| err.to_string());
+            let skipped = file.tdms_file.skipped_segments().to_vec();
+            self.segment_view = Some(SegmentView::new(file_id, segments, skipped));
+        }
+        self.segment_view.as_mut()
+    }
+
+    /// Describe what is stored at the byte offset entered in the segments window,
+    /// and list the objects of the segment containing it
+    fn locate_offset(&mut self, file_id: FileId) {
+        let text = match self.segment_view.as_ref() {
+            Some(view) => view.locate_text.trim().to_string(),
+            None => return,
+        };
+        let (location, segment_index) = match text.parse::<u64>() {
+            Ok(byte_offset) => match self
+                .file(file_id)
+                .map(|file| file.tdms_file.locate(byte_offset))
+            {
+                Some(Ok(Some(location))) => {
+                    let description = match (
+                        location.object_path_at_offset,
+                        location.value_index_within_channel,
+                    ) {
+                        (Some(path), Some(value_index)) => {
+                            format!("value {} of {}", value_index, path)
+                        }
+                        (Some(path), None) => format!("raw data of {}", path),
+                        (None, _) => String::from("lead in or metadata"),
+                    };
+                    (
+                        format!(
+                            "Offset {} is in segment {}, {}",
+                            byte_offset, location.segment_index, description
+                        ),
+                        Some(location.segment_index),
+                    )
+                }
+                Some(Err(err)) => (
+                    format!("Offset {} could not be located: {}", byte_offset, err),
+                    None,
+                ),
+                Some(Ok(None)) | None => (
+                    format!("Offset {} is past the end of the segments", byte_offset),
+                    None,
+                ),
+            },
+            Err(_) => (format!("\"{}\" is not a valid byte offset", text), None),
+        };
+        if let Some(view) = self.segment_view.as_mut() {
+            view.location = Some(location);
+            if let Some(segment_index) = segment_index {
+                if !view.expanded.contains(&segment_index) {
+                    view.toggle_expanded(segment_index);
+                }
+            }
+        }
+    }
+
+    /// Zoom the plot to the samples within a segment of a plotted channel,
+    /// preferring the channel selected in the tree
+    fn show_segment_in_plot(&mut self, file_id: FileId, segment: &SegmentInfo) {
+        let mut channel_axes: Vec<ChannelAxis> = self
+            .plot_view()
+            .channel_axes
+            .iter()
+            .filter(|axis| axis.channel_id.file == file_id)
+            .cloned()
+            .collect();
+        if let Some(TreeSelection::Channel(selected)) = self.tree_selection.as_ref() {
+            channel_axes.sort_by_key(|axis| &axis.channel_id != selected);
+        }
+        let file = match self.file(file_id) {
+            Some(file) => file,
+            None => return,
+        };
+        let range = channel_axes.iter().find_map(|axis| {
+            let group = file.tdms_file.group(&axis.channel_id.group)?;
+            let path = group.channel(&axis.channel_id.channel)?.path();
+            let object = segment.objects.iter().find(|object| object.path == path)?;
+            let first = object.first_value?;
+            if object.number_of_values == 0 {
+                return None;
+            }
+            let last = first + object.number_of_values - 1;
+            let start = axis.offset + axis.scale * first as f64;
+            let end = axis.offset + axis.scale * last as f64;
+            // Show half a sample either side so a single sample is still visible
+            let margin = axis.scale.abs() * 0.5;
+            Some((start.min(end) - margin, start.max(end) + margin))
+        });
+        match range {
+            Some(range) => {
+                self.central_tab = CentralTab::Plot;
+                self.set_zoom_range(Some(range));
+            }
+            None => {
+                self.error_message = Some(String::from(
+                    "None of the plotted channels have data in this segment",
+                ))
+            }
+        }
+    }
+
+    /// Show the values of a channel that can't be plotted in the table view
+    fn show_table(&mut self, channel_id: ChannelId) {
+        self.table_channel = Some(channel_id);
+        self.table_pages.clear();
+        self.central_tab = CentralTab::Table;
+    }
+
+    fn clear_table(&mut self) {
+        self.table_channel = None;
+        self.table_pages.clear();
+    }
+
+    /// Get a page of values of the table channel, reading it from the file if needed
+    fn table_page(&mut self, page: u64) -> Option<&TablePage> {
+        if !self.table_pages.contains_key(&page) {
+            let channel_id = self.table_channel.as_ref()?;
+            let file = self.file(channel_id.file)?;
+            let group = file.tdms_file.group(&channel_id.group)?;
+            let channel = group.channel(&channel_id.channel)?;
+            let offset = page * TABLE_PAGE_ROWS;
+            let count = channel.len().saturating_sub(offset).min(TABLE_PAGE_ROWS) as usize;
+            let result = match channel.data_type() {
+                Some(TdsType::String) => {
+                    let mut values = vec![String::new(); count];
+                    channel
+                        .read_strings(offset, &mut values)
+                        .map(|values_read| {
+                            values.truncate(values_read);
+                            TablePage::Strings(values)
+                        })
+                }
+                _ => {
+                    let mut values = vec![Timestamp::default(); count];
+                    channel.read_data(offset, &mut values).map(|values_read| {
+                        values.truncate(values_read);
+                        TablePage::Timestamps(values)
+                    })
+                }
+            };
+            // Keep an empty page after an error so reading isn't retried every frame
+            let table_page = match result {
+                Ok(table_page) => table_page,
+                Err(err) => {
+                    self.error_message = Some(format!(
+                        "Error reading {} / {}: {}",
+                        channel_id.group, channel_id.channel, err
+                    ));
+                    TablePage::Strings(Vec::new())
+                }
+            };
+            if self.table_pages.len() >= MAX_TABLE_PAGES {
+                self.table_pages.clear();
+            }
+            self.table_pages.insert(page, table_page);
+        }
+        self.table_pages.get(&page)
+    }
+
+    /// Move the cursors back to their initial positions and re-read their values
+    fn reset_cursors(&mut self) {
+        self.cursor_positions = None;
+        self.cursor_readouts = None;
+    }
+
+    fn deselect_channel(&mut self, channel_id: &ChannelId) {
+        if self.table_channel.as_ref() == Some(channel_id) {
+            self.clear_table();
+        }
+        self.selected_channels.retain(|id| id != channel_id);
+        self.channel_data.remove(channel_id);
+        self.cancel_load(channel_id);
+        self.detail_data.remove(channel_id);
+        if let Some(load) = self.detail_loads.remove(channel_id) {
+            load.cancel();
+        }
+        self.channel_statistics.remove(channel_id);
+        if let Some(load) = self.statistics_loads.remove(channel_id) {
+            load.cancel();
+        }
+        if self.xy_x_channel.as_ref() == Some(channel_id)
+            || self.xy_y_channel.as_ref() == Some(channel_id)
+        {
+            if self.xy_x_channel.as_ref() == Some(channel_id) {
+                self.xy_x_channel = None;
+            }
+            if self.xy_y_channel.as_ref() == Some(channel_id) {
+                self.xy_y_channel = None;
+            }
+            self.start_xy_load();
+        }
+        self.cached_plot = None;
+        self.reset_cursors();
+    }
+
+    /// Deselect all channels that don't match a predicate
+    fn retain_selection<F: Fn(&ChannelId) -> bool>(&mut self, keep: F) {
+        if let Some(channel_id) = self.table_channel.as_ref() {
+            if !keep(channel_id) {
+                self.clear_table();
+            }
+        }
+        let removed: Vec<ChannelId> = self
+            .selected_channels
+            .iter()
+            .filter(|channel_id| !keep(channel_id))
+            .cloned()
+            .collect();
+        for channel_id in removed.iter() {
+            self.deselect_channel(channel_id);
+        }
+    }
+
+    fn clear_selection(&mut self) {
+        self.clear_table();
+        self.selected_channels.clear();
+        self.channel_data.clear();
+        for (_, load) in self.loads.drain() {
+            load.cancel();
+        }
+        self.queued_loads.clear();
+        self.channel_statistics.clear();
+        for (_, load) in self.statistics_loads.drain() {
+            load.cancel();
+        }
+        self.xy_x_channel = None;
+        self.xy_y_channel = None;
+        self.start_xy_load();
+        self.set_zoom_range(None);
+        self.cached_plot = None;
+        self.reset_cursors();
+    }
+
+    /// Reload all selected channels, after settings that affect loading have changed.
+    /// The current data is kept until the new data has loaded.
+    fn reload_selection(&mut self) {
+        for channel_id in self.selected_channels.clone() {
+            self.start_load(channel_id);
+        }
+        self.start_xy_load();
+        self.clear_detail_data();
+    }
+
+    /// Zoom the plot to a range of x values, or show all data if the range is None.
+    /// Data within the range is loaded at full resolution once the range stops changing.
+    fn set_zoom_range(&mut self, range: Option<(f64, f64)>) {
+        if range == self.zoom_range {
+            return;
+        }
+        self.zoom_range = range;
+        self.clear_detail_data();
+        self.cached_plot = None;
+    }
+
+    /// Discard data loaded within the zoomed range, scheduling a new load if zoomed
+    fn clear_detail_data(&mut self) {
+        for (_, load) in self.detail_loads.drain() {
+            load.cancel();
+        }
+        self.detail_data.clear();
+        self.detail_load_due = self.zoom_range.map(|_| Instant::now() + DETAIL_LOAD_DELAY);
+    }
+
+    /// Start loading data within the zoomed range for channels plotted as an envelope,
+    /// once the range has stopped changing
+    fn start_detail_loads(&mut self) {
+        match self.detail_load_due {
+            Some(due) if Instant::now() >= due => self.detail_load_due = None,
+            _ => return,
+        }
+        let zoom_range = match self.zoom_range {
+            Some(zoom_range) => zoom_range,
+            None => return,
+        };
+        let channel_axes = self.plot_view().channel_axes.clone();
+        for axis in channel_axes {
+            let channel_id = axis.channel_id.clone();
+            let length = match self.channel_data.get(&channel_id) {
+                // Channels with all values loaded are already at full resolution
+                Some(data) if data.values.is_decimated() => data.values.value_count(),
+                _ => continue,
+            };
+            let window = axis.visible_samples(zoom_range, length);
+            if window == (0, length)
+                || self.detail_loads.contains_key(&channel_id)
+                || self
+                    .detail_data
+                    .get(&channel_id)
+                    .is_some_and(|detail| detail.window == window)
+            {
+                continue;
+            }
+            let path = match self.file(channel_id.file) {
+                Some(file) => file.path.clone(),
+                None => continue,
+            };
+            let max_points = self.max_points;
+            let decimation = self.decimation;
+            let job = {
+                let channel_id = channel_id.clone();
+                WorkerJob::spawn(move |progress, cancellation_token| {
+                    load_detail(
+                        &path,
+                        &channel_id,
+                        window,
+                        max_points,
+                        decimation,
+                        progress,
+                        cancellation_token,
+                    )
+                })
+            };
+            self.detail_loads.insert(channel_id, job);
+        }
+    }
+
+    /// Start computing statistics of all values of the selected channels that don't
+    /// have them yet, if they are shown
+    fn start_statistics_loads(&mut self) {
+        if !self.show_statistics || self.statistics_scope != StatisticsScope::Channel {
+            return;
+        }
+        for channel_id in self.selected_channels.iter() {
+            if self.channel_statistics.contains_key(channel_id)
+                || self.statistics_loads.contains_key(channel_id)
+            {
+                continue;
+            }
+            let path = match self.file(channel_id.file) {
+                Some(file) => file.path.clone(),
+                None => continue,
+            };
+            let job = {
+                let channel_id = channel_id.clone();
+                WorkerJob::spawn(move |progress, cancellation_token| {
+                    load_statistics(&path, &channel_id, progress, cancellation_token)
+                })
+            };
+            self.statistics_loads.insert(channel_id.clone(), job);
+        }
+    }
+
+    /// Check for completed computations of channel statistics. Failures are shown in
+    /// the statistics panel rather than as errors, as they are only informational.
+    fn poll_statistics_loads(&mut self) {
+        let mut finished = Vec::new();
+        for (channel_id, load) in self.statistics_loads.iter() {
+            let statistics = match load.receiver.try_recv() {
+                Ok(Ok(ChannelStatistics::Numeric(statistics))) => Ok(statistics),
+                Ok(Ok(ChannelStatistics::NoData)) => Err(String::from("No data")),
+                Ok(Ok(_)) => Err(String::from("Not numeric")),
+                Ok(Err(TdmsReadError::Cancelled)) => {
+                    finished.push(channel_id.clone());
+                    continue;
+                }
+                Ok(Err(err)) => Err(err.to_string()),
+                Err(TryRecvError::Empty) => continue,
+                Err(TryRecvError::Disconnected) => Err(String::from("Stopped unexpectedly")),
+            };
+            self.channel_statistics
+                .insert(channel_id.clone(), statistics);
+            finished.push(channel_id.clone());
+        }
+        for channel_id in finished {
+            self.statistics_loads.remove(&channel_id);
+        }
+    }
+
+    /// Check for completed loads of data within the zoomed range
+    fn poll_detail_loads(&mut self) {
+        let mut finished = Vec::new();
+        for (channel_id, load) in self.detail_loads.iter() {
+            match load.receiver.try_recv() {
+                Ok(Ok(data)) => {
+                    self.detail_data.insert(channel_id.clone(), data);
+                    self.cached_plot = None;
+                    finished.push(channel_id.clone());
+                }
+                Ok(Err(TdmsReadError::Cancelled)) => finished.push(channel_id.clone()),
+                Ok(Err(err)) => {
+                    self.error_message = Some(format!(
+                        "Error loading zoomed data of {} / {}: {}",
+                        channel_id.group, channel_id.channel, err
+                    ));
+                    finished.push(channel_id.clone());
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => {
+                    self.error_message = Some(format!(
+                        "Loading zoomed data of {} / {} stopped unexpectedly",
+                        channel_id.group, channel_id.channel
+                    ));
+                    finished.push(channel_id.clone());
+                }
+            }
+        }
+        for channel_id in finished {
+            self.detail_loads.remove(&channel_id);
+        }
+    }
+
+    /// Start loading the points of the XY plot if in XY mode and both channels
+    /// are chosen, replacing any previously loaded points
+    fn start_xy_load(&mut self) {
+        if let Some(load) = self.xy_load.take() {
+            load.cancel();
+        }
+        self.xy_data = None;
+        self.cached_plot = None;
+        if self.plot_mode != PlotMode::Xy {
+            return;
+        }
+        let (x_channel, y_channel) = match (self.xy_x_channel.clone(), self.xy_y_channel.clone()) {
+            (Some(x_channel), Some(y_channel)) => (x_channel, y_channel),
+            _ => return,
+        };
+        let (x_path, y_path) = match (self.file(x_channel.file), self.file(y_channel.file)) {
+            (Some(x_file), Some(y_file)) => (x_file.path.clone(), y_file.path.clone()),
+            _ => return,
+        };
+        let max_points = self.max_points;
+        self.xy_load = Some(WorkerJob::spawn(move |progress, cancellation_token| {
+            load_xy(
+                (&x_path, x_channel),
+                (&y_path, y_channel),
+                max_points,
+                progress,
+                cancellation_token,
+            )
+        }));
+    }
+
+    /// Start computing a spectrum if the channel or window has changed
+    /// since the current spectrum was requested
+    fn update_spectrum(&mut self) {
+        let channel_id = match self.spectrum_channel.as_ref() {
+            Some(channel_id) if self.selected_channels.contains(channel_id) => {
+                Some(channel_id.clone())
+            }
+            _ => self.selected_channels.first().cloned(),
+        };
+        let request = channel_id.map(|channel_id| (channel_id, self.spectrum_window));
+        if request == self.spectrum_request {
+            return;
+        }
+        if let Some(load) = self.spectrum_load.take() {
+            load.cancel();
+        }
+        self.spectrum = None;
+        self.spectrum_request = request.clone();
+        let (channel_id, window) = match request {
+            Some(request) => request,
+            None => return,
+        };
+        if !spectrum::is_available() {
+            return;
+        }
+        let path = match self.file(channel_id.file) {
+            Some(file) => file.path.clone(),
+            None => return,
+        };
+        self.spectrum_load = Some(WorkerJob::spawn(move |progress, cancellation_token| {
+            load_spectrum(&path, &channel_id, window, progress, cancellation_token)
+        }));
+    }
+
+    /// Check whether the spectrum has been computed
+    fn poll_spectrum_load(&mut self) {
+        let result = match self.spectrum_load.as_ref() {
+            Some(load) => load.receiver.try_recv(),
+            None => return,
+        };
+        match result {
+            Ok(Ok(data)) => self.spectrum = Some(data),
+            Ok(Err(TdmsReadError::Cancelled)) => {}
+            Ok(Err(err)) => self.error_message = Some(format!("Error computing spectrum: {}", err)),
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.error_message = Some(String::from("Computing spectrum stopped unexpectedly"))
+            }
+        }
+        self.spectrum_load = None;
+    }
+
+    /// Check whether the XY plot points have loaded
+    fn poll_xy_load(&mut self) {
+        let result = match self.xy_load.as_ref() {
+            Some(load) => load.receiver.try_recv(),
+            None => return,
+        };
+        match result {
+            Ok(Ok(data)) => {
+                self.xy_data = Some(data);
+                self.cached_plot = None;
+            }
+            Ok(Err(TdmsReadError::Cancelled)) => {}
+            Ok(Err(err)) => self.error_message = Some(format!("Error loading XY plot: {}", err)),
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.error_message = Some(String::from("Loading XY plot stopped unexpectedly"))
+            }
+        }
+        self.xy_load = None;
+    }
+
+    /// Refresh open files if following them and the refresh interval has passed,
+    /// then extend the data of plotted channels that have grown
+    fn refresh_followed_files(&mut self) {
+        if !self.follow {
+            return;
+        }
+        let interval = Duration::from_secs_f64(self.follow_interval);
+        if let Some(last_refresh) = self.last_refresh {
+            if last_refresh.elapsed() < interval {
+                return;
+            }
+        }
+        self.last_refresh = Some(Instant::now());
+
+        let mut refreshed = false;
+        for file in self.files.iter_mut() {
+            match file.tdms_file.refresh() {
+                Ok(true) => {
+                    file.groups = build_channel_tree(file.id, &file.tdms_file);
+                    refreshed = true;
+                }
+                Ok(false) => {}
+                // The file may be temporarily locked or partly written,
+                // so warn and try again at the next refresh
+                Err(err) => {
+                    self.follow_warning = Some((
+                        format!("Error refreshing {}: {}", file.name, err),
+                        Instant::now(),
+                    ))
+                }
+            }
+        }
+        if refreshed {
+            self.select_tree_item(self.tree_selection.clone());
+            self.file_summary = None;
+            self.segment_view = None;
+            // The last page of the table may have been partly filled
+            self.table_pages.clear();
+        }
+
+        for channel_id in self.selected_channels.clone() {
+            if self.loads.contains_key(&channel_id) {
+                continue;
+            }
+            let length = self.file(channel_id.file).and_then(|file| {
+                let group = file.tdms_file.group(&channel_id.group)?;
+                let channel = group.channel(&channel_id.channel)?;
+                Some(channel.len())
+            });
+            let data = match (self.channel_data.get(&channel_id), length) {
+                (Some(data), Some(length)) if length > data.values.value_count() => data.clone(),
+                _ => continue,
+            };
+            self.channel_statistics.remove(&channel_id);
+            self.start_extend(channel_id, data);
+        }
+    }
+
+    /// Start or stop the thread that requests repaints while following files,
+    /// as the app is otherwise only repainted in response to input
+    fn update_follow_ticker(&mut self, repaint_signal: Arc<dyn epi::RepaintSignal>) {
+        match (self.follow, self.follow_ticker.as_ref()) {
+            (true, None) => {
+                let token = CancellationToken::new();
+                let interval = Duration::from_secs_f64(self.follow_interval);
+                {
+                    let token = token.clone();
+                    thread::spawn(move || {
+                        while !token.is_cancelled() {
+                            thread::sleep(interval);
+                            repaint_signal.request_repaint();
+                        }
+                    });
+                }
+                self.follow_ticker = Some(token);
+            }
+            (false, Some(token)) => {
+                token.cancel();
+                self.follow_ticker = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Shift the zoomed range to keep the latest data visible when following files
+    fn scroll_to_latest(&mut self) {
+        if !self.follow || !self.auto_scroll {
+            return;
+        }
+        let (start, end) = match self.zoom_range {
+            Some(zoom_range) => zoom_range,
+            None => return,
+        };
+        if let Some((_, x_max)) = self.plot_view().x_range {
+            if x_max > end {
+                self.set_zoom_range(Some((x_max - (end - start), x_max)));
+            }
+        }
+    }
+
+    /// Start reading the values added to a channel since its data was loaded,
+    /// appending them to the existing data
+    fn start_extend(&mut self, channel_id: ChannelId, data: ChannelData) {
+        let path = match self.file(channel_id.file) {
+            Some(file) => file.path.clone(),
+            None => return,
+        };
+        let max_points = self.max_points;
+        let decimation = self.decimation;
+        let job = {
+            let channel_id = channel_id.clone();
+            WorkerJob::spawn(move |progress, cancellation_token| {
+                extend_channel(
+                    &path,
+                    &channel_id,
+                    data,
+                    max_points,
+                    decimation,
+                    progress,
+                    cancellation_token,
+                )
+            })
+        };
+        self.loads.insert(channel_id, job);
+    }
+
+    /// Start loading a channel on a worker thread, cancelling any load already
+    /// in progress for the channel. The load is queued if the maximum number of
+    /// loads are running.
+    fn start_load(&mut self, channel_id: ChannelId) {
+        self.cancel_load(&channel_id);
+        if self.loads.len() >= MAX_CONCURRENT_LOADS {
+            self.queued_loads.push(channel_id);
+            return;
+        }
+        let path = match self.file(channel_id.file) {
+            Some(file) => file.path.clone(),
+            None => return,
+        };
+        let max_points = self.max_points;
+        let decimation = self.decimation;
+        let job = {
+            let channel_id = channel_id.clone();
+            WorkerJob::spawn(move |progress, cancellation_token| {
+                load_channel(
+                    &path,
+                    &channel_id,
+                    max_points,
+                    decimation,
+                    progress,
+                    cancellation_token,
+                )
+            })
+        };
+        self.loads.insert(channel_id, job);
+    }
+
+    fn cancel_load(&mut self, channel_id: &ChannelId) {
+        if let Some(load) = self.loads.remove(channel_id) {
+            load.cancel();
+        }
+        self.queued_loads.retain(|id| id != channel_id);
+    }
+
+    /// Start exporting channels to CSV on a worker thread, with a time column
+    /// matching the current time axis setting
+    fn start_export(&mut self, output_path: PathBuf, channels: Vec<ChannelId>) {
+        self.cancel_export();
+        let path = match channels.first().and_then(|id| self.file(id.file)) {
+            Some(file) => file.path.clone(),
+            None => return,
+        };
+        let options = CsvExportOptions {
+            time_column: match self.time_axis {
+                TimeAxis::Relative => CsvTimeColumn::Relative,
+                TimeAxis::Absolute => CsvTimeColumn::Absolute,
+            },
+            ..CsvExportOptions::default()
+        };
+        self.export = Some(WorkerJob::spawn(move |progress, cancellation_token| {
+            export_channels(
+                &path,
+                &output_path,
+                &channels,
+                &options,
+                progress,
+                cancellation_token,
+            )
+        }));
+    }
+
+    fn cancel_export(&mut self) {
+        if let Some(export) = self.export.take() {
+            export.cancel();
+        }
+    }
+
+    /// Check whether the running export has completed
+    fn poll_export(&mut self) {
+        let result = match self.export.as_ref() {
+            Some(export) => export.receiver.try_recv(),
+            None => return,
+        };
+        match result {
+            Ok(Ok(_)) | Ok(Err(TdmsReadError::Cancelled)) => {}
+            Ok(Err(err)) => self.error_message = Some(format!("Error exporting data: {}", err)),
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.error_message = Some(String::from("Export stopped unexpectedly"))
+            }
+        }
+        self.export = None;
+    }
+
+    /// Check for completed loads, swapping in their data
+    fn poll_loads(&mut self) {
+        let mut finished = Vec::new();
+        for (channel_id, load) in self.loads.iter() {
+            match load.receiver.try_recv() {
+                Ok(Ok(data)) => {
+                    self.channel_data.insert(channel_id.clone(), data);
+                    self.cached_plot = None;
+                    if self.zoom_range.is_some() {
+                        self.detail_load_due = Some(Instant::now());
+                    }
+                    finished.push(channel_id.clone());
+                }
+                Ok(Err(TdmsReadError::Cancelled)) => finished.push(channel_id.clone()),
+                Ok(Err(err)) => {
+                    self.error_message = Some(format!(
+                        "Error loading {} / {}: {}",
+                        channel_id.group, channel_id.channel, err
+                    ));
+                    finished.push(channel_id.clone());
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => {
+                    self.error_message = Some(format!(
+                        "Loading {} / {} stopped unexpectedly",
+                        channel_id.group, channel_id.channel
+                    ));
+                    finished.push(channel_id.clone());
+                }
+            }
+        }
+        for channel_id in finished {
+            self.loads.remove(&channel_id);
+        }
+        while self.loads.len() < MAX_CONCURRENT_LOADS && !self.queued_loads.is_empty() {
+            let channel_id = self.queued_loads.remove(0);
+            self.start_load(channel_id);
+        }
+    }
+
+    /// Get the plot, building it from the loaded data only when
+    /// the selection, loaded data or time axis have changed
+    fn plot_view(&mut self) -> &PlotView {
+        let plot_view = match self.cached_plot.take() {
+            Some(plot_view) => plot_view,
+            None => {
+                // Channel axes may have changed, so cursor values need reading again
+                self.cursor_readouts = None;
+                self.build_plot_view()
+            }
+        };
+        self.cached_plot.get_or_insert(plot_view)
+    }
+
+    fn build_plot_view(&self) -> PlotView {
+        if self.plot_mode == PlotMode::Xy {
+            return self.build_xy_plot_view();
+        }
+        let loaded: Vec<(usize, &ChannelId, &ChannelData)> = self
+            .selected_channels
+            .iter()
+            .enumerate()
+            .filter_map(|(index, id)| self.channel_data.get(id).map(|data| (index, id, data)))
+            .collect();
+
+        let start_times: Vec<Option<DateTime<Utc>>> = loaded
+            .iter()
+            .map(|(_, _, data)| {
+                data.timing
+                    .and_then(|timing| timing.start_time)
+                    .and_then(|start_time| start_time.to_datetime())
+            })
+            .collect();
+        let reference_time = match self.time_axis {
+            TimeAxis::Absolute => start_times.iter().flatten().min().cloned(),
+            TimeAxis::Relative => None,
+        };
+
+        let mut lines = Vec::new();
+        let mut channel_axes = Vec::new();
+        let mut visible_statistics = Vec::new();
+        let mut timed_count = 0;
+        let mut missing_start_time = false;
+        for ((index, channel_id, data), start_time) in loaded.iter().zip(start_times.iter()) {
+            let mut name = format!("{} / {}", channel_id.group, channel_id.channel);
+            if self.compare_mode {
+                if let Some(file) = self.file(channel_id.file) {
+                    name = format!("{}: {}", file.name, name);
+                }
+            }
+            // Loaded points have x values from each channel's own timing, which are
+            // shifted to line up channels with different start times. The axis maps a
+            // (possibly fractional) sample index to a shifted x value.
+            let (shift, offset, scale) = match data.timing {
+                Some(timing) => {
+                    timed_count += 1;
+                    let start_offset = match (reference_time, start_time) {
+                        (Some(reference_time), Some(start_time)) => {
+                            duration_seconds(*start_time - reference_time)
+                        }
+                        (Some(_), None) => {
+                            missing_start_time = true;
+                            0.0
+                        }
+                        (None, _) => 0.0,
+                    };
+                    (
+                        start_offset,
+                        start_offset + timing.start_offset,
+                        timing.increment,
+                    )
+                }
+                None => {
+                    name.push_str(" (index)");
+                    (0.0, 0.0, 1.0)
+                }
+            };
+            let axis = ChannelAxis {
+                channel_id: (*channel_id).clone(),
+                name: name.clone(),
+                offset,
+                scale,
+            };
+            let x = |sample_index: f64| offset + sample_index * scale;
+            let color = line_color(*index);
+            let unit = self.channel_unit(channel_id);
+
+            // When zoomed, only the samples within the zoomed range are plotted,
+            // using data loaded at a higher resolution for the range if available
+            let length = data.values.value_count();
+            let (window, values, raw_offset) = match self.zoom_range {
+                Some(zoom_range) => {
+                    let window = axis.visible_samples(zoom_range, length);
+                    match self.detail_data.get(channel_id) {
+                        Some(detail) if detail.window == window => {
+                            (window, &detail.values, window.0)
+                        }
+                        _ => (window, &data.values, 0),
+                    }
+                }
+                None => ((0, length), &data.values, 0),
+            };
+            channel_axes.push(axis);
+            match values {
+                PlotData::Raw(values) => {
+                    let start = (window.0 - raw_offset).min(values.len() as u64) as usize;
+                    let end = (window.1 - raw_offset).min(values.len() as u64) as usize;
+                    let points: Vec<Value> = values[start..end]
+                        .iter()
+                        .map(|&(x, y)| Value::new(shift + x, y))
+                        .collect();
+                    // The window includes a sample either side of the zoomed range
+                    // so lines reach the edges, but these aren't visible
+                    let mut statistics = NumericStatistics::new();
+                    for point in points.iter() {
+                        let visible = match self.zoom_range {
+                            Some((zoom_start, zoom_end)) => {
+                                point.x >= zoom_start && point.x <= zoom_end
+                            }
+                            None => true,
+                        };
+                        if visible {
+                            statistics.update(point.y);
+                        }
+                    }
+                    visible_statistics.push(StatisticsRow {
+                        name: name.clone(),
+                        statistics: Ok(statistics),
+                    });
+                    lines.push(PlotLine {
+                        name,
+                        unit,
+                        color,
+                        points,
+                    });
+                }
+                PlotData::Strided { values, stride, .. } => {
+                    visible_statistics.push(StatisticsRow {
+                        name: name.clone(),
+                        statistics: Err(String::from(
+                            "Decimated, zoom in further for full resolution data",
+                        )),
+                    });
+                    // Index into the values of the first sample at or after a sample index
+                    let value_index = |sample: u64| {
+                        (sample.saturating_sub(raw_offset).div_ceil(*stride) as usize)
+                            .min(values.len())
+                    };
+                    // Include a value either side of the window so lines reach the edges
+                    let start = value_index(window.0).saturating_sub(1);
+                    let end = (value_index(window.1) + 1).min(values.len());
+                    let points: Vec<Value> = values[start..end]
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &y)| {
+                            let sample = raw_offset + (start + i) as u64 * stride;
+                            Value::new(x(sample as f64), y)
+                        })
+                        .collect();
+                    lines.push(PlotLine {
+                        name,
+                        unit,
+                        color,
+                        points,
+                    });
+                }
+                PlotData::Envelope(buckets) => {
+                    visible_statistics.push(StatisticsRow {
+                        name: name.clone(),
+                        statistics: Err(String::from(
+                            "Decimated, zoom in further for full resolution data",
+                        )),
+                    });
+                    // Plot the envelope as two lines with the same name,
+                    // so they share a legend entry
+                    let buckets: Vec<&(f64, MinMaxBucket)> = buckets
+                        .iter()
+                        .filter(|(_, b)| {
+                            b.first_index + b.count > window.0 && b.first_index < window.1
+                        })
+                        .collect();
+                    lines.push(PlotLine {
+                        name: name.clone(),
+                        unit: unit.clone(),
+                        color,
+                        points: buckets
+                            .iter()
+                            .map(|(x, b)| Value::new(shift + x, b.min))
+                            .collect(),
+                    });
+                    lines.push(PlotLine {
+                        name,
+                        unit,
+                        color,
+                        points: buckets
+                            .iter()
+                            .map(|(x, b)| Value::new(shift + x, b.max))
+                            .collect(),
+                    });
+                }
+            }
+        }
+        lines.extend(self.overview_lines(reference_time));
+
+        let x_label = match reference_time {
+            _ if timed_count == 0 => String::from("Sample index"),
+            Some(reference_time) => format!(
+                "Time (s) from {}",
+                reference_time
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S%.6f")
+            ),
+            None => String::from("Time (s)"),
+        };
+        let warning = if timed_count > 0 && timed_count < loaded.len() {
+            Some(String::from(
+                "Some channels have no timing properties and are plotted against sample index",
+            ))
+        } else if self.time_axis == TimeAxis::Absolute
+            && timed_count > 0
+            && reference_time.is_none()
+        {
+            Some(String::from(
+                "No channels have a start time, so relative time is shown",
+            ))
+        } else if missing_start_time {
+            Some(String::from(
+                "Some channels have no start time and are plotted relative to the earliest start time",
+            ))
+        } else {
+            None
+        };
+        let x_range = lines.iter().flat_map(|line| line.points.iter()).fold(
+            None,
+            |range, point| match range {
+                None => Some((point.x, point.x)),
+                Some((min, max)) => Some((point.x.min(min), point.x.max(max))),
+            },
+        );
+        PlotView {
+            lines,
+            x_label,
+            warning,
+            channel_axes,
+            x_range,
+            visible_statistics,
+        }
+    }
+
+    /// Grey lines of the minimum and maximum of each segment of plotted channels that
+    /// are still loading, if their file was opened with an overview
+    fn overview_lines(&self, reference_time: Option<DateTime<Utc>>) -> Vec<PlotLine> {
+        let mut lines = Vec::new();
+        for channel_id in self.selected_channels.iter() {
+            let loading =
+                self.loads.contains_key(channel_id) || self.queued_loads.contains(channel_id);
+            if !loading || self.channel_data.contains_key(channel_id) {
+                continue;
+            }
+            let overview = self.file(channel_id.file).and_then(|file| {
+                let group = file.tdms_file.group(&channel_id.group)?;
+                let channel = group.channel(&channel_id.channel)?;
+                Some((
+                    channel.overview()?,
+                    channel.overview_value_ranges()?,
+                    channel.waveform_timing(),
+                ))
+            });
+            let (envelopes, ranges, timing) = match overview {
+                Some(overview) => overview,
+                None => continue,
+            };
+            let (offset, scale) = match timing {
+                Some(timing) => {
+                    let start_time = timing.start_time.and_then(|time| time.to_datetime());
+                    let start_offset = match (reference_time, start_time) {
+                        (Some(reference_time), Some(start_time)) => {
+                            duration_seconds(start_time - reference_time)
+                        }
+                        _ => 0.0,
+                    };
+                    (start_offset + timing.start_offset, timing.increment)
+                }
+                None => (0.0, 1.0),
+            };
+            let x = |sample_index: u64| offset + sample_index as f64 * scale;
+            // Each segment's range is drawn as a step across the segment's samples
+            let mut min_points = Vec::new();
+            let mut max_points = Vec::new();
+            for (&(min, max), range) in envelopes.iter().zip(ranges.iter()) {
+                if min.is_nan() {
+                    continue;
+                }
+                for sample_index in [range.start, range.end - 1] {
+                    min_points.push(Value::new(x(sample_index), min));
+                    max_points.push(Value::new(x(sample_index), max));
+                }
+            }
+            let name = format!("{} / {} (overview)", channel_id.group, channel_id.channel);
+            let unit = self.channel_unit(channel_id);
+            let color = Color32::from_gray(160);
+            lines.push(PlotLine {
+                name: name.clone(),
+                unit: unit.clone(),
+                color,
+                points: min_points,
+            });
+            lines.push(PlotLine {
+                name,
+                unit,
+                color,
+                points: max_points,
+            });
+        }
+        lines
+    }
+
+    /// Read the values of each plotted channel at the cursors from the file, so the
+    /// values are exact even when the plotted data is decimated. Values are linearly
+    /// interpolated between samples.
+    fn read_cursor_values(&self, plot_view: &PlotView, positions: [f64; 2]) -> Vec<CursorReadout> {
+        plot_view
+            .channel_axes
+            .iter()
+            .map(|axis| CursorReadout {
+                name: axis.name.clone(),
+                values: [
+                    self.read_value_at(axis, positions[0]),
+                    self.read_value_at(axis, positions[1]),
+                ],
+            })
+            .collect()
+    }
+
+    /// Format the samples of the plotted channels within the visible x range as TSV,
+    /// with an x column followed by a column per channel. Values are read from the
+    /// file, so are exact even when the plotted data is decimated. Channels with
+    /// different timing each get their own x column. Returns the text and whether
+    /// rows were left out because there were more than `max_copy_rows`.
+    fn visible_data_tsv(&mut self) -> rstdms::Result<(String, bool)> {
+        let zoom_range = self.zoom_range;
+        let plot_view = self.plot_view();
+        let x_label = plot_view.x_label.clone();
+        let channel_axes = plot_view.channel_axes.clone();
+        let range = match zoom_range.or(plot_view.x_range) {
+            Some(range) => range,
+            None => return Ok((String::new(), false)),
+        };
+
+        let mut truncated = false;
+        let mut columns: Vec<(&ChannelAxis, u64, Vec<f64>)> = Vec::new();
+        for axis in channel_axes.iter() {
+            let file = match self.file(axis.channel_id.file) {
+                Some(file) => file,
+                None => continue,
+            };
+            let group = find_group(&file.tdms_file, &axis.channel_id)?;
+            let channel = find_channel(&group, &axis.channel_id)?;
+            let length = channel.len();
+            let clamp = |position: f64| position.max(0.0).min(length as f64) as u64;
+            let first = axis.sample_position(range.0);
+            let last = axis.sample_position(range.1);
+            let start = clamp(first.min(last).ceil());
+            let end = clamp(first.max(last).floor() + 1.0);
+            let count = end.saturating_sub(start);
+            if count > self.max_copy_rows as u64 {
+                truncated = true;
+            }
+            let mut values = vec![0.0; count.min(self.max_copy_rows as u64) as usize];
+            let values_read = channel.read_data_as_f64(start, &mut values)?;
+            values.truncate(values_read);
+            columns.push((axis, start, values));
+        }
+
+        let shared_x = columns.windows(2).all(|pair| {
+            let (a, a_start, _) = &pair[0];
+            let (b, b_start, _) = &pair[1];
+            a.offset == b.offset && a.scale == b.scale && a_start == b_start
+        });
+        let mut text = String::new();
+        for (index, (axis, _, _)) in columns.iter().enumerate() {
+            if index == 0 || !shared_x {
+                if index > 0 {
+                    text.push('\t');
+                }
+                text.push_str(&x_label);
+            }
+            text.push('\t');
+            text.push_str(&axis.name);
+        }
+        let rows = columns
+            .iter()
+            .map(|(_, _, values)| values.len())
+            .max()
+            .unwrap_or(0);
+        for row in 0..rows {
+            text.push('\n');
+            for (index, (axis, start, values)) in columns.iter().enumerate() {
+                if index > 0 {
+                    text.push('\t');
+                }
+                if index == 0 || !shared_x {
+                    let sample = (start + row as u64) as f64;
+                    if row < values.len() || shared_x {
+                        text.push_str(&(axis.offset + sample * axis.scale).to_string());
+                    }
+                    text.push('\t');
+                }
+                if let Some(value) = values.get(row) {
+                    text.push_str(&value.to_string());
+                }
+            }
+        }
+        Ok((text, truncated))
+    }
+
+    fn read_value_at(&self, axis: &ChannelAxis, x: f64) -> Option<f64> {
+        let file = self.file(axis.channel_id.file)?;
+        let group = file.tdms_file.group(&axis.channel_id.group)?;
+        let channel = group.channel(&axis.channel_id.channel)?;
+        let position = axis.sample_position(x);
+        if channel.len() == 0
+            || position.is_nan()
+            || position < 0.0
+            || position > (channel.len() - 1) as f64
+        {
+            return None;
+        }
+        let index = position.floor() as u64;
+        let mut values = [0.0; 2];
+        match channel.read_data_as_f64(index, &mut values).ok()? {
+            0 => None,
+            1 => Some(values[0]),
+            _ => Some(values[0] + (values[1] - values[0]) * (position - index as f64)),
+        }
+    }
+
+    fn build_xy_plot_view(&self) -> PlotView {
+        let data = match self.xy_data.as_ref() {
+            Some(data) => data,
+            None => {
+                let warning = if self.xy_x_channel.is_none() || self.xy_y_channel.is_none() {
+                    Some(String::from(
+                        "Choose selected channels to plot on the X and Y axes",
+                    ))
+                } else {
+                    None
+                };
+                return PlotView {
+                    lines: Vec::new(),
+                    x_label: String::new(),
+                    warning,
+                    channel_axes: Vec::new(),
+                    x_range: None,
+                    visible_statistics: Vec::new(),
+                };
+            }
+        };
+        let channel_name = |channel_id: &ChannelId| {
+            let name = format!("{} / {}", channel_id.group, channel_id.channel);
+            match self.file(channel_id.file) {
+                Some(file) if self.compare_mode => format!("{}: {}", file.name, name),
+                _ => name,
+            }
+        };
+        let x_name = channel_name(&data.x_channel);
+        let y_name = channel_name(&data.y_channel);
+        PlotView {
+            lines: vec![PlotLine {
+                name: format!("{} vs {}", y_name, x_name),
+                unit: self.channel_unit(&data.y_channel),
+                color: line_color(0),
+                points: data.points.clone(),
+            }],
+            x_label: format!("X: {}", x_name),
+            warning: None,
+            channel_axes: Vec::new(),
+            x_range: None,
+            visible_statistics: Vec::new(),
+        }
+    }
+}
+
+/// Compute the spectrum of the start of a channel's data. This runs on a worker thread,
+/// so reopens the file.
+fn load_spectrum(
+    path: &Path,
+    channel_id: &ChannelId,
+    window: WindowFunction,
+    progress: &Mutex<f32>,
+    cancellation_token: CancellationToken,
+) -> rstdms::Result<SpectrumData> {
+    let file = TdmsFile::open(path)?;
+    let group = find_group(&file, channel_id)?;
+    let channel = find_channel(&group, channel_id)?;
+    let length = channel.len().min(MAX_SPECTRUM_VALUES);
+    let mut values = vec![0.0; length as usize];
+    let mut position = 0;
+    while position < values.len() {
+        if cancellation_token.is_cancelled() {
+            return Err(TdmsReadError::Cancelled);
+        }
+        let end = (position + CHUNK_VALUES).min(values.len());
+        let values_read = channel.read_data_as_f64(position as u64, &mut values[position..end])?;
+        if values_read == 0 {
+            break;
+        }
+        position += values_read;
+        set_progress(progress, position as f32 / values.len() as f32);
+    }
+    values.truncate(position);
+
+    let sample_interval = channel.waveform_timing().map(|timing| timing.increment);
+    let spectrum =
+        spectrum::compute_spectrum(&values, sample_interval, window).ok_or_else(|| {
+            TdmsReadError::Unsupported(String::from(
+                "Spectrum view requires building with the spectrum feature",
+            ))
+        })?;
+    Ok(SpectrumData {
+        spectrum,
+        value_count: values.len() as u64,
+        truncated: channel.len() > values.len() as u64,
+    })
+}
+
+/// Load one channel against another for an XY plot. This runs on a worker thread,
+/// so reopens the files. Channels are decimated by keeping every nth point, which
+/// preserves the shape of the curve, unlike a min/max envelope.
+fn load_xy(
+    (x_path, x_channel_id): (&Path, ChannelId),
+    (y_path, y_channel_id): (&Path, ChannelId),
+    max_points: usize,
+    progress: &Mutex<f32>,
+    cancellation_token: CancellationToken,
+) -> rstdms::Result<XyData> {
+    let x_file = TdmsFile::open(x_path)?;
+    let y_file = TdmsFile::open(y_path)?;
+    let x_group = find_group(&x_file, &x_channel_id)?;
+    let y_group = find_group(&y_file, &y_channel_id)?;
+    let x_channel = find_channel(&x_group, &x_channel_id)?;
+    let y_channel = find_channel(&y_group, &y_channel_id)?;
+
+    let length = x_channel.len().min(y_channel.len());
+    let longest = x_channel.len().max(y_channel.len());
+    if (longest - length) as f64 > longest as f64 * XY_LENGTH_TOLERANCE {
+        return Err(TdmsReadError::Incompatible(format!(
+            "Channels have different lengths ({} and {})",
+            x_channel.len(),
+            y_channel.len()
+        )));
+    }
+
+    let stride = length.div_ceil(max_points.max(1) as u64).max(1);
+    let mut points = Vec::with_capacity((length / stride + 1) as usize);
+    let mut x_buffer = vec![0.0; CHUNK_VALUES];
+    let mut y_buffer = vec![0.0; CHUNK_VALUES];
+    let mut index = 0;
+    while index < length {
+        if cancellation_token.is_cancelled() {
+            return Err(TdmsReadError::Cancelled);
+        }
+        if stride >= CHUNK_VALUES as u64 {
+            // Points are far apart, so read each one individually rather than every value
+            let x_read = x_channel.read_data_as_f64(index, &mut x_buffer[..1])?;
+            let y_read = y_channel.read_data_as_f64(index, &mut y_buffer[..1])?;
+            if x_read == 0 || y_read == 0 {
+                break;
+            }
+            points.push(Value::new(x_buffer[0], y_buffer[0]));
+            index += stride;
+        } else {
+            let count = (length - index).min(CHUNK_VALUES as u64) as usize;
+            let x_read = x_channel.read_data_as_f64(index, &mut x_buffer[..count])?;
+            let y_read = y_channel.read_data_as_f64(index, &mut y_buffer[..count])?;
+            let values_read = x_read.min(y_read);
+            if values_read == 0 {
+                break;
+            }
+            // Keep the values at indices that are a multiple of the stride
+            let first = (index.div_ceil(stride) * stride - index) as usize;
+            for i in (first..values_read).step_by(stride as usize) {
+                points.push(Value::new(x_buffer[i], y_buffer[i]));
+            }
+            index += values_read as u64;
+        }
+        set_progress(progress, index.min(length) as f32 / length as f32);
+    }
+    Ok(XyData {
+        x_channel: x_channel_id,
+        y_channel: y_channel_id,
+        points,
+    })
+}
+
+fn find_group<'a, R: Read + Seek>(
+    file: &'a TdmsFile<R>,
+    channel_id: &'a ChannelId,
+) -> rstdms::Result<Group<'a, R>> {
+    file.try_group(&channel_id.group)
+}
+
+fn find_channel<'a, R: Read + Seek>(
+    group: &'a Group<R>,
+    channel_id: &ChannelId,
+) -> rstdms::Result<Channel<'a, R>> {
+    group.try_channel(&channel_id.channel)
+}
+
+/// Export channels to a CSV file. This runs on a worker thread, so reopens the file.
+fn export_channels(
+    path: &Path,
+    output_path: &Path,
+    channel_ids: &[ChannelId],
+    options: &CsvExportOptions,
+    progress: &Mutex<f32>,
+    cancellation_token: CancellationToken,
+) -> rstdms::Result<u64> {
+    let file = TdmsFile::open(path)?;
+    let groups = channel_ids
+        .iter()
+        .map(|channel_id| find_group(&file, channel_id))
+        .collect::<rstdms::Result<Vec<_>>>()?;
+    let channels = groups
+        .iter()
+        .zip(channel_ids.iter())
+        .map(|(group, channel_id)| find_channel(group, channel_id))
+        .collect::<rstdms::Result<Vec<_>>>()?;
+    let output = BufWriter::new(std::fs::File::create(output_path)?);
+    let mut control = ReadControl::new()
+        .on_progress(|processed, total| {
+            set_progress(progress, processed as f32 / total as f32);
+        })
+        .cancellation_token(cancellation_token);
+    export_csv_with_control(&channels, output, options, &mut control)
+}
+
+/// Set the progress of a worker. A poisoned lock is ignored, as the progress value
+/// is always valid and a panicking worker is reported when its result is received.
+fn set_progress(progress: &Mutex<f32>, value: f32) {
+    *progress.lock().unwrap_or_else(|err| err.into_inner()) = value;
+}
+
+fn get_progress(progress: &Mutex<f32>) -> f32 {
+    *progress.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Convert a duration to seconds, keeping sub-microsecond precision where possible
+fn duration_seconds(duration: chrono::Duration) -> f64 {
+    match duration.num_nanoseconds() {
+        Some(nanoseconds) => nanoseconds as f64 * 1e-9,
+        None => duration.num_milliseconds() as f64 * 1e-3,
+    }
+}
+
+/// Load a channel's data for plotting. This runs on a worker thread, so reopens the file.
+fn load_channel(
+    path: &Path,
+    channel_id: &ChannelId,
+    max_points: usize,
+    decimation: Decimation,
+    progress: &Mutex<f32>,
+    cancellation_token: CancellationToken,
+) -> rstdms::Result<ChannelData> {
+    let file = TdmsFile::open(path)?;
+    let group = find_group(&file, channel_id)?;
+    let channel = find_channel(&group, channel_id)?;
+    let timing = channel.waveform_timing();
+    let values = read_plot_data(
+        &channel,
+        (0, channel.len()),
+        max_points,
+        decimation,
+        progress,
+        cancellation_token,
+    )?;
+    Ok(ChannelData { values, timing })
+}
+
+/// Extend the loaded data of a channel with values added since it was loaded. Raw
+/// values are appended, and new values of decimated data are decimated with the existing
+/// bucket size or stride. If the data would then have too many points, the whole channel
+/// is loaded again. This runs on a worker thread, so reopens the file.
+fn extend_channel(
+    path: &Path,
+    channel_id: &ChannelId,
+    data: ChannelData,
+    max_points: usize,
+    decimation: Decimation,
+    progress: &Mutex<f32>,
+    cancellation_token: CancellationToken,
+) -> rstdms::Result<ChannelData> {
+    let file = TdmsFile::open(path)?;
+    let group = find_group(&file, channel_id)?;
+    let channel = find_channel(&group, channel_id)?;
+    let length = channel.len();
+    let timing = channel.waveform_timing();
+    // Points already loaded have x values from the previous timing, so if the timing
+    // properties have changed the whole channel is loaded again
+    let same_timing = timing == data.timing;
+    let values = match data.values {
+        PlotData::Raw(mut points) if same_timing && length <= max_points as u64 => {
+            let previous_length = points.len() as u64;
+            let mut control = ReadControl::new()
+                .on_progress(|processed, total| {
+                    set_progress(progress, processed as f32 / total as f32);
+                })
+                .cancellation_token(cancellation_token);
+            points.extend(channel.read_xy_with_control(
+                x_mode(timing),
+                previous_length,
+                length - previous_length,
+                &mut control,
+            )?);
+            PlotData::Raw(points)
+        }
+        PlotData::Envelope(mut buckets) if same_timing && buckets.len() < max_points => {
+            // The last bucket may not have been full, so is read again
+            let bucket_size = buckets.first().map_or(1, |(_, bucket)| bucket.count);
+            let offset = buckets.pop().map_or(0, |(_, bucket)| bucket.first_index);
+            let new_length = length.saturating_sub(offset);
+            let mut control = ReadControl::new()
+                .on_progress(|processed, total| {
+                    set_progress(progress, processed as f32 / total as f32);
+                })
+                .cancellation_token(cancellation_token);
+            buckets.extend(channel.read_xy_decimated_with_control(
+                x_mode(timing),
+                offset,
+                new_length,
+                new_length.div_ceil(bucket_size) as usize,
+                &mut control,
+            )?);
+            PlotData::Envelope(buckets)
+        }
+        PlotData::Strided {
+            mut values, stride, ..
+        } if length.div_ceil(stride) <= max_points as u64 => {
+            let offset = values.len() as u64 * stride;
+            values.extend(read_strided(
+                &channel,
+                (offset, length),
+                stride,
+                progress,
+                cancellation_token,
+            )?);
+            PlotData::Strided {
+                values,
+                stride,
+                length,
+            }
+        }
+        _ => read_plot_data(
+            &channel,
+            (0, length),
+            max_points,
+            decimation,
+            progress,
+            cancellation_token,
+        )?,
+    };
+    Ok(ChannelData { values, timing })
+}
+
+/// Load the data of a channel within a window of sample indices for plotting
+/// while zoomed. This runs on a worker thread, so reopens the file.
+fn load_detail(
+    path: &Path,
+    channel_id: &ChannelId,
+    window: (u64, u64),
+    max_points: usize,
+    decimation: Decimation,
+    progress: &Mutex<f32>,
+    cancellation_token: CancellationToken,
+) -> rstdms::Result<DetailData> {
+    let file = TdmsFile::open(path)?;
+    let group = find_group(&file, channel_id)?;
+    let channel = find_channel(&group, channel_id)?;
+    let values = read_plot_data(
+        &channel,
+        window,
+        max_points,
+        decimation,
+        progress,
+        cancellation_token,
+    )?;
+    Ok(DetailData { window, values })
+}
+
+/// Compute statistics of all values of a channel. This runs on a worker thread,
+/// so reopens the file.
+fn load_statistics(
+    path: &Path,
+    channel_id: &ChannelId,
+    progress: &Mutex<f32>,
+    cancellation_token: CancellationToken,
+) -> rstdms::Result<ChannelStatistics> {
+    let file = TdmsFile::open(path)?;
+    let group = find_group(&file, channel_id)?;
+    let channel = find_channel(&group, channel_id)?;
+    let mut control = ReadControl::new()
+        .on_progress(|processed, total| {
+            set_progress(progress, processed as f32 / total as f32);
+        })
+        .cancellation_token(cancellation_token);
+    channel_statistics_with_control(&channel, &StatisticsOptions::default(), &mut control)
+}
+
+/// Read the values of a channel within a window of sample indices, decimated
+/// if there are more than `max_points` values
+fn read_plot_data<R: Read + Seek>(
+    channel: &Channel<R>,
+    window: (u64, u64),
+    max_points: usize,
+    decimation: Decimation,
+    progress: &Mutex<f32>,
+    cancellation_token: CancellationToken,
+) -> rstdms::Result<PlotData> {
+    let (offset, end) = window;
+    let length = end.saturating_sub(offset);
+    if length > max_points as u64 && decimation == Decimation::Stride {
+        let stride = length.div_ceil(max_points.max(1) as u64);
+        let values = read_strided(channel, window, stride, progress, cancellation_token)?;
+        return Ok(PlotData::Strided {
+            values,
+            stride,
+            length,
+        });
+    }
+    let x_mode = x_mode(channel.waveform_timing());
+    let mut control = ReadControl::new()
+        .on_progress(|processed, total| {
+            set_progress(progress, processed as f32 / total as f32);
+        })
+        .cancellation_token(cancellation_token);
+    if length > max_points as u64 {
+        // Each bucket is plotted as two points, its minimum and maximum
+        let buckets = channel.read_xy_decimated_with_control(
+            x_mode,
+            offset,
+            length,
+            max_points / 2,
+            &mut control,
+        )?;
+        Ok(PlotData::Envelope(buckets))
+    } else {
+        let points = channel.read_xy_with_control(x_mode, offset, length, &mut control)?;
+        Ok(PlotData::Raw(points))
+    }
+}
+
+/// How plotted points of a channel are positioned, by time relative to the channel's
+/// own start time if it has waveform timing, or by index
+fn x_mode(timing: Option<WaveformTiming>) -> XMode {
+    match timing {
+        Some(_) => XMode::RelativeTime,
+        None => XMode::Index,
+    }
+}
+
+/// Read every `stride`th value of a channel within a window of sample indices,
+/// starting from the first
+fn read_strided<R: Read + Seek>(
+    channel: &Channel<R>,
+    window: (u64, u64),
+    stride: u64,
+    progress: &Mutex<f32>,
+    cancellation_token: CancellationToken,
+) -> rstdms::Result<Vec<f64>> {
+    let (offset, end) = window;
+    let length = end.saturating_sub(offset);
+    let mut values = Vec::with_capacity(length.div_ceil(stride) as usize);
+    let mut buffer = vec![0.0; CHUNK_VALUES];
+    let mut index = 0;
+    while index < length {
+        if cancellation_token.is_cancelled() {
+            return Err(TdmsReadError::Cancelled);
+        }
+        if stride >= CHUNK_VALUES as u64 {
+            // Values are far apart, so read each one individually rather than every value
+            if channel.read_data_as_f64(offset + index, &mut buffer[..1])? == 0 {
+                break;
+            }
+            values.push(buffer[0]);
+            index += stride;
+        } else {
+            let count = (length - index).min(CHUNK_VALUES as u64) as usize;
+            let values_read = channel.read_data_as_f64(offset + index, &mut buffer[..count])?;
+            if values_read == 0 {
+                break;
+            }
+            // Keep the values at indices that are a multiple of the stride
+            let first = (index.div_ceil(stride) * stride - index) as usize;
+            values.extend(
+                buffer[..values_read]
+                    .iter()
+                    .skip(first)
+                    .step_by(stride as usize),
+            );
+            index += values_read as u64;
+        }
+        set_progress(progress, index.min(length) as f32 / length as f32);
+    }
+    Ok(values)
+}
+
+/// Format a number of values compactly, eg. "12.5 M" for 12,500,000
+fn format_length(length: u64) -> String {
+    const SUFFIXES: [(u64, &str); 3] = [(1_000_000_000, "G"), (1_000_000, "M"), (1_000, "k")];
+    for &(size, suffix) in SUFFIXES.iter() {
+        if length >= size {
+            return format!("{:.1} {}", length as f64 / size as f64, suffix);
+        }
+    }
+    length.to_string()
+}
+
+/// Format a timestamp in local time, or in UTC if `utc` is true
+fn format_timestamp(timestamp: &Timestamp, utc: bool) -> String {
+    match timestamp.to_datetime() {
+        Some(datetime) if utc => datetime.format("%Y-%m-%d %H:%M:%S%.6f UTC").to_string(),
+        Some(datetime) => datetime
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S%.6f")
+            .to_string(),
+        None => String::from("Invalid timestamp"),
+    }
+}
+
+fn property_row(property: &TdmsProperty) -> PropertyRow {
+    let value = format_property_value(&property.value);
+    if value.chars().count() > MAX_PROPERTY_CHARS {
+        let truncated: String = value.chars().take(MAX_PROPERTY_CHARS).collect();
+        PropertyRow {
+            name: property.name.to_string(),
+            value: format!("{}...", truncated),
+            full_value: Some(value),
+        }
+    } else {
+        PropertyRow {
+            name: property.name.to_string(),
+            value,
+            full_value: None,
+        }
+    }
+}
+
+fn format_property_value(value: &TdmsValue) -> String {
+    match value.as_timestamp().and_then(|value| value.to_datetime()) {
+        Some(datetime) => datetime
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S%.6f")
+            .to_string(),
+        None => value.to_string(),
+    }
+}
+
+impl<R: Read + Seek> TemplateApp<R> {
+    /// Table of the index and value of each value of the table channel. Only the visible
+    /// rows are drawn, and values are read a page at a time as they are scrolled to.
+    fn table_ui(&mut self, ui: &mut egui::Ui) {
+        let (channel_id, length) = match self.table_channel.as_ref().and_then(|channel_id| {
+            let file = self.file(channel_id.file)?;
+            let group = file.tdms_file.group(&channel_id.group)?;
+            let channel = group.channel(&channel_id.channel)?;
+            Some((channel_id.clone(), channel.len()))
+        }) {
+            Some(table_channel) => table_channel,
+            None => {
+                ui.label("Select a string or timestamp channel to view its values");
+                return;
+            }
+        };
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "{} / {} ({} values)",
+                channel_id.group, channel_id.channel, length
+            ));
+            ui.checkbox(&mut self.table_utc, "UTC")
+                .on_hover_text("Show timestamps in UTC rather than local time");
+        });
+        ui.separator();
+
+        let row_height = ui.fonts().row_height(egui::TextStyle::Body);
+        ScrollArea::vertical().show_rows(ui, row_height, length as usize, |ui, rows| {
+            let utc = self.table_utc;
+            for row in rows {
+                let row = row as u64;
+                let index_in_page = (row % TABLE_PAGE_ROWS) as usize;
+                let value = match self.table_page(row / TABLE_PAGE_ROWS) {
+                    Some(TablePage::Strings(values)) => values.get(index_in_page).cloned(),
+                    Some(TablePage::Timestamps(values)) => values
+                        .get(index_in_page)
+                        .map(|timestamp| format_timestamp(timestamp, utc)),
+                    None => None,
+                };
+                ui.horizontal(|ui| {
+                    ui.monospace(format!("{:>10}", row));
+                    ui.label(value.unwrap_or_default().replace("\n", " "));
+                });
+            }
+        });
+    }
+
+    fn settings_ui(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("settings_grid")
+            .num_columns(2)
+            .spacing([24.0, 8.0])
+            .show(ui, |ui| {
+                ui.label("Max points per channel:");
+                let response = ui.add(
+                    egui::Slider::new(&mut self.max_points, MAX_POINTS_RANGE).logarithmic(true),
+                );
+                // Reload once the slider is released rather than on every change while dragging
+                if response.drag_released() || (response.changed() && !response.dragged()) {
+                    self.reload_selection();
+                }
+                ui.end_row();
+
+                ui.label("Decimation:");
+                ui.horizontal(|ui| {
+                    let envelope = ui
+                        .radio_value(
+                            &mut self.decimation,
+                            Decimation::Envelope,
+                            "Min/max envelope",
+                        )
+                        .on_hover_text("Plot the range of values, so peaks are always shown");
+                    let stride = ui
+                        .radio_value(&mut self.decimation, Decimation::Stride, "Stride")
+                        .on_hover_text("Plot every nth value, which is faster but can miss peaks");
+                    if envelope.changed() || stride.changed() {
+                        self.reload_selection();
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Max rows copied:");
+                ui.add(
+                    egui::DragValue::new(&mut self.max_copy_rows)
+                        .clamp_range(1_000..=10_000_000)
+                        .speed(1_000),
+                )
+                .on_hover_text("Maximum number of samples per channel copied from the plot");
+                ui.end_row();
+
+                ui.label("Confirm plotting above:");
+                ui.add(
+                    egui::DragValue::new(&mut self.confirm_load_gb)
+                        .clamp_range(0.1..=1_000.0)
+                        .speed(0.1)
+                        .suffix(" GB"),
+                )
+                .on_hover_text(
+                    "Ask before plotting a channel with more data than this, \
+                     estimated from the file's metadata",
+                );
+                ui.end_row();
+
+                ui.label("Overview:");
+                ui.checkbox(&mut self.overview, "Draw an overview while loading")
+                    .on_hover_text(
+                        "Read the range of each channel's values in each segment when \
+                         opening a file, which reads all of its data once. The ranges are \
+                         cached so later opens of the file are quick.",
+                    );
+                ui.end_row();
+
+                ui.label("Follow interval:");
+                let response = ui.add(
+                    egui::DragValue::new(&mut self.follow_interval)
+                        .clamp_range(0.1..=60.0)
+                        .speed(0.1)
+                        .suffix(" s"),
+                );
+                if response.changed() {
+                    // Restart the ticker with the new interval
+                    if let Some(token) = self.follow_ticker.take() {
+                        token.cancel();
+                    }
+                }
+                ui.end_row();
+
+                ui.label("Time axis:");
+                ui.horizontal(|ui| {
+                    let relative =
+                        ui.radio_value(&mut self.time_axis, TimeAxis::Relative, "Relative");
+                    let absolute =
+                        ui.radio_value(&mut self.time_axis, TimeAxis::Absolute, "Absolute");
+                    if relative.changed() || absolute.changed() {
+                        // x values change, so the zoomed range and cursors no longer apply
+                        self.set_zoom_range(None);
+                        self.reset_cursors();
+                        self.cached_plot = None;
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Sort channels:");
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.channel_sort, ChannelSort::File, "File order");
+                    ui.radio_value(&mut self.channel_sort, ChannelSort::Name, "Name");
+                    ui.radio_value(&mut self.channel_sort, ChannelSort::Length, "Length");
+                });
+                ui.end_row();
+
+                ui.label("Image size:");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut self.image_size.0)
+                            .clamp_range(100..=10_000)
+                            .suffix(" px"),
+                    );
+                    ui.label("x");
+                    ui.add(
+                        egui::DragValue::new(&mut self.image_size.1)
+                            .clamp_range(100..=10_000)
+                            .suffix(" px"),
+                    );
+                    ui.checkbox(&mut self.image_caption, "Caption")
+                        .on_hover_text("Caption saved images with the file and channel names");
+                });
+                ui.end_row();
+            });
+        ui.separator();
+        ui.label("Settings are saved when the viewer is closed.");
+    }
+
+    /// Overview of the active file and its root properties
+    fn file_summary_ui(&mut self, ui: &mut egui::Ui) {
+        let view = match self.file_summary() {
+            Some(view) => view,
+            None => {
+                ui.label("Open a file to view its properties");
+                return;
+            }
+        };
+        let summary = match view.summary.as_ref() {
+            Ok(summary) => summary,
+            Err(err) => {
+                ui.colored_label(Color32::RED, format!("Could not summarize file: {}", err));
+                return;
+            }
+        };
+        let format_time = |time: &DateTime<Utc>| {
+            time.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S%.6f")
+                .to_string()
+        };
+        let rows = [
+            ("Path", view.path.display().to_string()),
+            (
+                "Size",
+                match view.size {
+                    Some(size) => format!("{}B ({} bytes)", format_length(size), size),
+                    None => String::from("-"),
+                },
+            ),
+            ("Segments", summary.segment_count.to_string()),
+            (
+                "Interleaved segments",
+                summary.interleaved_segments.to_string(),
+            ),
+            (
+                "Big endian segments",
+                summary.big_endian_segments.to_string(),
+            ),
+            (
+                "Format version",
+                match summary.format_version {
+                    Some(version) => version.to_string(),
+                    None => String::from("-"),
+                },
+            ),
+            ("Groups", summary.group_count.to_string()),
+            ("Channels", summary.channel_count.to_string()),
+            (
+                "Total samples",
+                format!(
+                    "{} ({})",
+                    format_length(summary.total_values),
+                    summary.total_values
+                ),
+            ),
+            (
+                "Time span",
+                match summary.time_span.as_ref() {
+                    Some((start, end)) => format!(
+                        "{} to {} ({} s)",
+                        format_time(start),
+                        format_time(end),
+                        duration_seconds(*end - *start)
+                    ),
+                    None => String::from("No channels with waveform start times"),
+                },
+            ),
+        ];
+        egui::Grid::new("file_summary")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                for (name, value) in rows.iter() {
+                    ui.label(*name);
+                    ui.label(value);
+                    ui.end_row();
+                }
+            });
+        for warning in summary.warnings() {
+            ui.colored_label(Color32::YELLOW, warning);
+        }
+        for path in summary.mixed_layout_channels.iter() {
+            ui.label(format!("{} has both interleaved and contiguous data", path));
+        }
+        for path in summary.mixed_byte_order_channels.iter() {
+            ui.label(format!("{} has both big and little endian data", path));
+        }
+
+        ui.separator();
+        ui.label("Properties");
+        if view.properties.is_empty() {
+            ui.label("No properties");
+        } else {
+            ScrollArea::vertical().show(ui, |ui| {
+                property_grid(ui, "file_summary_properties", &view.properties);
+            });
+        }
+    }
+
+    /// List the segments of the active file as they were read, for finding out
+    /// why a file isn't read as expected
+    fn segments_ui(&mut self, ui: &mut egui::Ui) {
+        let view = match self.segment_view() {
+            Some(view) => view,
+            None => {
+                ui.label("Open a file to view its segments");
+                return;
+            }
+        };
+        let segments = match view.segments.as_ref() {
+            Ok(segments) => segments,
+            Err(err) => {
+                ui.colored_label(
+                    Color32::RED,
+                    format!("Could not describe segments: {}", err),
+                );
+                return;
+            }
+        };
+        ui.label(format!(
+            "{} segments. Incomplete segments are shown in yellow. Segments whose DAQmxRawData \
+            flag doesn't match their raw data indexes are marked. \
+            Click a segment to list its objects.",
+            segments.len()
+        ));
+        for skipped in view.skipped.iter() {
+            ui.colored_label(
+                Color32::RED,
+                format!("Skipped segment at {}: {}", skipped.position, skipped.error),
+            );
+        }
+        let mut locate = false;
+        let locate_text = &mut view.locate_text;
+        ui.horizontal(|ui| {
+            ui.label("Byte offset");
+            ui.text_edit_singleline(locate_text);
+            if ui
+                .button("Locate")
+                .on_hover_text(
+                    "Find the segment, object and value stored at a byte offset in the file",
+                )
+                .clicked()
+            {
+                locate = true;
+            }
+        });
+        if let Some(location) = view.location.as_ref() {
+            ui.label(location);
+        }
+        ui.separator();
+        ui.monospace(format!(
+            "{:>8} {:>12} {:>7} {:<32} {:>7} {:>10} {:>7}",
+            "Index", "Position", "Version", "ToC flags", "Objects", "Data size", "Chunks"
+        ));
+
+        let file_id = view.file;
+        let mut toggle = None;
+        let mut show_in_plot = None;
+        let row_height = ui.spacing().interact_size.y;
+        ScrollArea::vertical().show_rows(ui, row_height, view.rows.len(), |ui, rows| {
+            for row in rows {
+                let (segment_index, object_index) = view.rows[row];
+                let segment = &segments[segment_index];
+                ui.horizontal(|ui| match object_index {
+                    None => {
+                        let mut toc_flags = segment.toc_flag_names().join(" ");
+                        let unknown_bits = segment.toc_mask().unknown_bits();
+                        if unknown_bits != 0 {
+                            toc_flags.push_str(&format!(" {:#x}", unknown_bits));
+                        }
+                        if segment.daqmx_flag_mismatch() {
+                            toc_flags.push_str(if segment.has_daqmx_indexes {
+                                " (DAQmx indexes without flag)"
+                            } else {
+                                " (no DAQmx indexes)"
+                            });
+                        }
+                        let text = format!(
+                            "{:>8} {:>12} {:>7} {:<32} {:>7} {:>10} {:>7}",
+                            segment_index,
+                            segment.position,
+                            segment.version,
+                            toc_flags,
+                            segment.objects.len(),
+                            format!("{}B", format_length(segment.data_length)),
+                            segment.chunk_count,
+                        );
+                        let mut label = egui::Label::new(text).monospace().sense(Sense::click());
+                        if segment.incomplete {
+                            label = label.text_color(Color32::YELLOW);
+                        }
+                        if ui.add(label).clicked() {
+                            toggle = Some(segment_index);
+                        }
+                        if ui
+                            .small_button("Plot")
+                            .on_hover_text("Zoom the plot to this segment's data")
+                            .clicked()
+                        {
+                            show_in_plot = Some(segment.clone());
+                        }
+                    }
+                    Some(object_index) => {
+                        let object = &segment.objects[object_index];
+                        let data = match (object.data_type, object.first_value) {
+                            (Some(data_type), Some(first_value)) => format!(
+                                "{}, {} values from {}",
+                                data_type, object.number_of_values, first_value
+                            ),
+                            (Some(data_type), None) => format!("{}, no values", data_type),
+                            (None, _) => String::from("no data"),
+                        };
+                        ui.add_space(ui.spacing().indent);
+                        ui.monospace(format!("{}  {}", object.path, data));
+                    }
+                });
+            }
+        });
+
+        if let Some(segment_index) = toggle {
+            view.toggle_expanded(segment_index);
+        }
+        if locate {
+            self.locate_offset(file_id);
+        }
+        if let Some(segment) = show_in_plot {
+            self.show_segment_in_plot(file_id, &segment);
+        }
+    }
+
+    fn spectrum_ui(&mut self, ui: &mut egui::Ui) {
+        if !spectrum::is_available() {
+            ui.label("Spectrum view requires building with the spectrum feature");
+            return;
+        }
+        let selected_channels = &self.selected_channels;
+        let spectrum_channel = &mut self.spectrum_channel;
+        let spectrum_window = &mut self.spectrum_window;
+        ui.horizontal(|ui| {
+            let selected_text = match spectrum_channel
+                .as_ref()
+                .or_else(|| selected_channels.first())
+            {
+                Some(channel_id) => format!("{} / {}", channel_id.group, channel_id.channel),
+                None => String::from("None"),
+            };
+            egui::ComboBox::from_label("Channel")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for channel_id in selected_channels.iter() {
+                        ui.selectable_value(
+                            spectrum_channel,
+                            Some(channel_id.clone()),
+                            format!("{} / {}", channel_id.group, channel_id.channel),
+                        );
+                    }
+                });
+            egui::ComboBox::from_label("Window")
+                .selected_text(spectrum_window.name())
+                .show_ui(ui, |ui| {
+                    for window in WindowFunction::ALL.iter() {
+                        ui.selectable_value(spectrum_window, *window, window.name());
+                    }
+                });
+        });
+        self.update_spectrum();
+
+        if let Some(load) = self.spectrum_load.as_ref() {
+            ui.add(egui::ProgressBar::new(load.progress()).text("Computing spectrum"));
+        }
+        let data = match self.spectrum.as_ref() {
+            Some(data) => data,
+            None => {
+                if self.spectrum_request.is_none() {
+                    ui.label("Select a channel to show its spectrum");
+                }
+                return;
+            }
+        };
+        if data.truncated {
+            ui.label(format!("Spectrum of the first {} values", data.value_count));
+        }
+        if data.spectrum.in_hertz {
+            ui.label("Frequency (Hz)");
+        } else {
+            ui.colored_label(
+                Color32::YELLOW,
+                "Channel has no wf_increment property, so frequency is shown as the bin index",
+            );
+        }
+        let points = data
+            .spectrum
+            .frequencies
+            .iter()
+            .zip(data.spectrum.magnitudes_db.iter())
+            .map(|(&frequency, &magnitude)| Value::new(frequency, magnitude))
+            .collect();
+        let plot = Plot::new("Spectrum")
+            .view_aspect(1.0)
+            .line(
+                Line::new(Values::from_values(points))
+                    .color(line_color(0))
+                    .name("Amplitude (dB)"),
+            )
+            .legend(Legend::default());
+        ui.add(plot);
+    }
+}
+
+/// Grid of property names and values, with truncated values shown in full on hover
+fn property_grid(ui: &mut egui::Ui, id: &str, rows: &[PropertyRow]) {
+    egui::Grid::new(id)
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for row in rows.iter() {
+                ui.label(&row.name);
+                let response = ui.label(&row.value);
+                if let Some(full_value) = row.full_value.as_ref() {
+                    response.on_hover_text(full_value);
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// A checkbox showing whether none, some or all of a group's channels are selected.
+/// egui's checkbox has no partially checked state, so this draws its own.
+fn group_checkbox(ui: &mut egui::Ui, check: GroupCheck) -> egui::Response {
+    let size = egui::vec2(ui.spacing().icon_width, ui.spacing().interact_size.y);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+    let visuals = ui.style().interact(&response);
+    let (small_icon_rect, big_icon_rect) = ui.spacing().icon_rectangles(rect);
+    ui.painter().rect(
+        big_icon_rect.expand(visuals.expansion),
+        visuals.corner_radius,
+        visuals.bg_fill,
+        visuals.bg_stroke,
+    );
+    let points = match check {
+        GroupCheck::None => Vec::new(),
+        GroupCheck::Some => vec![
+            egui::pos2(small_icon_rect.left(), small_icon_rect.center().y),
+            egui::pos2(small_icon_rect.right(), small_icon_rect.center().y),
+        ],
+        GroupCheck::All => vec![
+            egui::pos2(small_icon_rect.left(), small_icon_rect.center().y),
+            egui::pos2(small_icon_rect.center().x, small_icon_rect.bottom()),
+            egui::pos2(small_icon_rect.right(), small_icon_rect.top()),
+        ],
+    };
+    if !points.is_empty() {
+        ui.painter()
+            .add(egui::Shape::line(points, visuals.fg_stroke));
+    }
+    response
+}
+
+/// Show one plot per channel, stacked vertically. The plots' own zooming and
+/// dragging are replaced by changes to the zoomed range, which all plots show,
+/// so zooming or panning one plot moves them all. Returns the new zoomed range,
+/// or None to show all data, if it was changed, and the combined response of the plots.
+fn stacked_plots_ui(
+    ui: &mut egui::Ui,
+    plot_view: &PlotView,
+    zoom_range: Option<(f64, f64)>,
+    cursor_positions: Option<[f64; 2]>,
+) -> (Option<Option<(f64, f64)>>, Option<egui::Response>) {
+    let subplots = plot_view.subplots();
+    let x_range = match zoom_range.or(plot_view.x_range) {
+        Some(x_range) if !subplots.is_empty() => x_range,
+        _ => return (None, None),
+    };
+    let height = (ui.available_width() / subplots.len() as f32).max(MIN_SUBPLOT_HEIGHT);
+    let mut new_zoom_range = None;
+    let mut plots_response: Option<egui::Response> = None;
+    ScrollArea::vertical().show(ui, |ui| {
+        for (index, lines) in subplots.iter().enumerate() {
+            ui.colored_label(lines[0].color, lines[0].label());
+            // Every plot includes the same x range, so the x-axes line up
+            let mut plot = Plot::new(("Stacked", index))
+                .height(height)
+                .allow_drag(false)
+                .allow_zoom(false)
+                .include_x(x_range.0)
+                .include_x(x_range.1);
+            for line in lines.iter() {
+                plot = plot.line(
+                    Line::new(Values::from_values(line.points.clone()))
+                        .color(line.color)
+                        .name(&line.name),
+                );
+            }
+            if let Some(positions) = cursor_positions {
+                for position in positions.iter() {
+                    plot = plot.vline(VLine::new(*position).color(Color32::LIGHT_GRAY));
+                }
+            }
+            let response = ui.add(plot);
+            if let Some(range) = linked_zoom(ui, &response, x_range) {
+                new_zoom_range = Some(range);
+            }
+            plots_response = Some(match plots_response.take() {
+                Some(plots_response) => plots_response.union(response),
+                None => response,
+            });
+        }
+    });
+    (new_zoom_range, plots_response)
+}
+
+/// Get the x range after dragging or zooming a plot showing `x_range`,
+/// which the plot extends by its margin. Double clicking zooms out.
+fn linked_zoom(
+    ui: &egui::Ui,
+    response: &egui::Response,
+    x_range: (f64, f64),
+) -> Option<Option<(f64, f64)>> {
+    if response.double_clicked() {
+        return Some(None);
+    }
+    let width = x_range.1 - x_range.0;
+    // Plots add 5% of the range to each side
+    let shown_width = width * 1.1;
+    let per_point = shown_width / response.rect.width().max(1.0) as f64;
+    if response.dragged() {
+        let shift = -response.drag_delta().x as f64 * per_point;
+        if shift != 0.0 {
+            return Some(Some((x_range.0 + shift, x_range.1 + shift)));
+        }
+    }
+    let hover_pos = response.hover_pos()?;
+    let input = ui.input();
+    let zoom = input.zoom_delta_2d().x as f64;
+    if zoom != 1.0 {
+        let shown_start = x_range.0 - width * 0.05;
+        let pointer_x = shown_start + (hover_pos.x - response.rect.left()) as f64 * per_point;
+        return Some(Some((
+            pointer_x - (pointer_x - x_range.0) / zoom,
+            pointer_x + (x_range.1 - pointer_x) / zoom,
+        )));
+    }
+    let shift = -input.scroll_delta.x as f64 * per_point;
+    if shift != 0.0 {
+        return Some(Some((x_range.0 + shift, x_range.1 + shift)));
+    }
+    None
+}
+
+/// Get a distinct colour for the line at the given index
+fn line_color(index: usize) -> Color32 {
+    let golden_ratio = (5.0_f32.sqrt() - 1.0) / 2.0;
+    let hue = (index as f32 * golden_ratio).fract();
+    Hsva::new(hue, 0.85, 0.5, 1.0).into()
+}
+
+impl epi::App for TemplateApp<std::fs::File> {
+    /// The name is also used for the directory settings are saved in
+    fn name(&self) -> &str {
+        "rstdms"
+    }
+
+    /// Called by the framework to restore the settings saved when the app last closed.
+    /// The window size and panel layout are restored by the framework itself.
+    fn setup(
+        &mut self,
+        _ctx: &egui::CtxRef,
+        _frame: &mut epi::Frame<'_>,
+        storage: Option<&dyn epi::Storage>,
+    ) {
+        if let Some(settings) = storage.and_then(|storage| epi::get_value(storage, epi::APP_KEY)) {
+            self.apply_settings(settings);
+        }
+    }
+
+    /// Called by the framework to save settings on shutdown and periodically
+    fn save(&mut self, storage: &mut dyn epi::Storage) {
+        epi::set_value(storage, epi::APP_KEY, &self.settings());
+    }
+
+    /// Called each time the UI needs repainting, which may be many times per second.
+    /// Put your widgets into a `SidePanel`, `TopPanel`, `CentralPanel`, `Window` or `Area`.
+    fn update(&mut self, ctx: &egui::CtxRef, frame: &mut epi::Frame<'_>) {
+        let dropped_files: Vec<PathBuf> = ctx
+            .input()
+            .raw
+            .dropped_files
+            .iter()
+            .filter_map(|file| file.path.clone())
+            .collect();
+        if !dropped_files.is_empty() {
+            self.open_dropped_files(dropped_files);
+        }
+
+        self.poll_loads();
+        self.poll_export();
+        self.poll_xy_load();
+        self.poll_spectrum_load();
+        self.poll_detail_loads();
+        self.start_detail_loads();
+        self.poll_statistics_loads();
+        self.start_statistics_loads();
+        self.update_follow_ticker(frame.repaint_signal());
+        self.refresh_followed_files();
+        if !self.loads.is_empty()
+            || self.export.is_some()
+            || self.xy_load.is_some()
+            || self.spectrum_load.is_some()
+            || !self.detail_loads.is_empty()
+            || !self.statistics_loads.is_empty()
+            || self.detail_load_due.is_some()
+        {
+            // Keep repainting so progress is shown and results are picked up
+            ctx.request_repaint();
+        }
+
+        let mut dismiss_error = false;
+        let mut open_suggested = false;
+        if let Some(message) = self.error_message.as_ref() {
+            let suggested_file = self.suggested_file.as_ref();
+            egui::Window::new("Error")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(message);
+                    ui.horizontal(|ui| {
+                        if suggested_file.is_some() && ui.button("Open data file").clicked() {
+                            open_suggested = true;
+                        }
+                        if ui.button("Dismiss").clicked() {
+                            dismiss_error = true;
+                        }
+                    });
+                });
+        }
+        if dismiss_error || open_suggested {
+            self.error_message = None;
+            let suggested_file = self.suggested_file.take();
+            if let (true, Some(path)) = (open_suggested, suggested_file) {
+                self.open_file(path);
+            }
+        }
+
+        let mut confirm_loads = None;
+        if !self.pending_loads.is_empty() {
+            let pending_loads = &self.pending_loads;
+            let total_bytes: u64 = pending_loads.iter().map(|(_, bytes)| bytes).sum();
+            egui::Window::new("Large channels")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "Plotting these channels will read about {}B of data:",
+                        format_length(total_bytes)
+                    ));
+                    for (channel_id, bytes) in pending_loads.iter() {
+                        ui.label(format!(
+                            "{} / {}: {}B",
+                            channel_id.group,
+                            channel_id.channel,
+                            format_length(*bytes)
+                        ));
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("Plot").clicked() {
+                            confirm_loads = Some(true);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            confirm_loads = Some(false);
+                        }
+                    });
+                });
+        }
+        if let Some(confirmed) = confirm_loads {
+            for (channel_id, _) in std::mem::take(&mut self.pending_loads) {
+                // The file may have been closed while waiting for confirmation
+                if confirmed
+                    && self.file(channel_id.file).is_some()
+                    && !self.selected_channels.contains(&channel_id)
+                {
+                    self.plot_channel(channel_id);
+                }
+            }
+        }
+
+        self.handle_tree_keys(ctx);
+        // Drawn before the plot, so a click that opens the menu doesn't also close it
+        self.plot_menu_ui(ctx);
+
+        let mut show_settings = self.show_settings;
+        egui::Window::new("Settings")
+            .open(&mut show_settings)
+            .resizable(false)
+            .show(ctx, |ui| self.settings_ui(ui));
+        self.show_settings = show_settings;
+
+        let mut show_file_summary = self.show_file_summary;
+        egui::Window::new("File Properties")
+            .open(&mut show_file_summary)
+            .show(ctx, |ui| self.file_summary_ui(ui));
+        self.show_file_summary = show_file_summary;
+
+        let mut show_segments = self.show_segments;
+        egui::Window::new("Segments")
+            .open(&mut show_segments)
+            .default_width(600.0)
+            .show(ctx, |ui| self.segments_ui(ui));
+        self.show_segments = show_segments;
+
+        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+            // The top panel is often a good place for a menu bar:
+            egui::menu::bar(ui, |ui| {
+                let mut recent_file = None;
+                let mut save_image = false;
+                egui::menu::menu(ui, "File", |ui| {
+                    if ui.button("Open...").clicked() {
+                        self.open_dialog();
+                    }
+                    if ui.button("Properties...").clicked() {
+                        self.show_file_summary = true;
+                    }
+                    if ui.button("Save plot as image...").clicked() {
+                        save_image = true;
+                    }
+                    if ui.button("Settings...").clicked() {
+                        self.show_settings = true;
+                    }
+                    ui.collapsing("Open Recent", |ui| {
+                        if self.recent_files.is_empty() {
+                            ui.label("No recent files");
+                        }
+                        for path in self.recent_files.iter() {
+                            if ui.button(path.display().to_string()).clicked() {
+                                recent_file = Some(path.clone());
+                            }
+                        }
+                    });
+                    if ui.button("Quit").clicked() {
+                        frame.quit();
+                    }
+                });
+                egui::menu::menu(ui, "View", |ui| {
+                    ui.checkbox(&mut self.show_segments, "Segments")
+                        .on_hover_text("List the segments of the active file as they were read");
+                });
+                if let Some(path) = recent_file {
+                    self.open_file(path);
+                }
+                if save_image {
+                    self.central_tab = CentralTab::Plot;
+                    self.save_image_dialog();
+                }
+            });
+        });
+
+        egui::SidePanel::left("side_panel")
+            .min_width(200.0)
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.heading("Side Panel");
+
+                if ui.button("Load File").clicked() {
+                    self.open_dialog()
+                }
+                if ui.button("Clear all").clicked() {
+                    self.clear_selection();
+                }
+                if ui.button("Export...").clicked() {
+                    self.export_dialog(self.selected_channels.clone());
+                }
+                let mut cancel_export = false;
+                if let Some(export) = self.export.as_ref() {
+                    ui.horizontal(|ui| {
+                        let progress = export.progress();
+                        ui.add(egui::ProgressBar::new(progress).text("Exporting"));
+                        cancel_export = ui.button("Cancel").clicked();
+                    });
+                }
+                if cancel_export {
+                    self.cancel_export();
+                }
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.follow, "Follow")
+                        .on_hover_text(format!(
+                            "Refresh files every {} s to show data as it is written",
+                            self.follow_interval
+                        ));
+                    ui.checkbox(&mut self.auto_scroll, "Auto-scroll")
+                        .on_hover_text("Keep the latest data visible when zoomed");
+                });
+                let warning_expired = match self.follow_warning.as_ref() {
+                    Some((warning, time)) if time.elapsed() < FOLLOW_WARNING_DURATION => {
+                        ui.colored_label(Color32::YELLOW, warning);
+                        false
+                    }
+                    Some(_) => true,
+                    None => false,
+                };
+                if warning_expired {
+                    self.follow_warning = None;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Filter:");
+                    ui.text_edit_singleline(&mut self.channel_filter);
+                    if ui.button("Clear").clicked() {
+                        self.channel_filter.clear();
+                    }
+                });
+                let mut activated_file = None;
+                let mut closed_file = None;
+                ui.horizontal_wrapped(|ui| {
+                    for file in self.files.iter() {
+                        let is_active = self.active_file == Some(file.id);
+                        if ui
+                            .selectable_label(is_active, &file.name)
+                            .on_hover_text(file.path.display().to_string())
+                            .clicked()
+                        {
+                            activated_file = Some(file.id);
+                        }
+                        if ui.small_button("x").on_hover_text("Close file").clicked() {
+                            closed_file = Some(file.id);
+                        }
+                    }
+                });
+                if let Some(file_id) = activated_file {
+                    self.set_active_file(file_id);
+                }
+                if let Some(file_id) = closed_file {
+                    self.close_file(file_id);
+                }
+                let mut compare_mode = self.compare_mode;
+                if ui
+                    .checkbox(&mut compare_mode, "Compare mode")
+                    .on_hover_text("Plot channels from different files together")
+                    .changed()
+                {
+                    self.set_compare_mode(compare_mode);
+                }
+
+                let active_file = self
+                    .active_file
+                    .and_then(|file_id| self.files.iter().find(|file| file.id == file_id));
+                let groups: &[GroupEntry] = match active_file {
+                    Some(file) => &file.groups,
+                    None => &[],
+                };
+                let filter = self.channel_filter.trim().to_lowercase();
+                let filtering = !filter.is_empty();
+                if filtering {
+                    let match_count = groups
+                        .iter()
+                        .flat_map(|group| group.channels.iter())
+                        .filter(|channel| channel.search_key.contains(&filter))
+                        .count();
+                    ui.label(format!("{} matching channels", match_count));
+                }
+
+                let selected_channels = &self.selected_channels;
+                let table_channel = self.table_channel.as_ref();
+                let channel_sort = self.channel_sort;
+                let tree_selection = self.tree_selection.as_ref();
+                let mut clicked_item = None;
+                let mut exported_channel = None;
+                let mut toggled_channels = Vec::new();
+                let mut group_action = None;
+                let mut tree_order = Vec::new();
+                let scroll_to_selection = self.scroll_to_selection;
+                ScrollArea::vertical().show(ui, |ui| {
+                    let file_id = match active_file {
+                        Some(file) => file.id,
+                        None => return,
+                    };
+                    let file_selected = tree_selection == Some(&TreeSelection::File(file_id));
+                    if ui.selectable_label(file_selected, "File").clicked() {
+                        clicked_item = Some(TreeSelection::File(file_id));
+                    }
+                    for group in groups.iter() {
+                        let mut channels: Vec<&ChannelEntry> = group
+                            .channels
+                            .iter()
+                            .filter(|channel| !filtering || channel.search_key.contains(&filter))
+                            .collect();
+                        match channel_sort {
+                            ChannelSort::File => {}
+                            ChannelSort::Name => channels.sort_by(|a, b| a.label.cmp(&b.label)),
+                            ChannelSort::Length => {
+                                channels.sort_by_key(|channel| std::cmp::Reverse(channel.length))
+                            }
+                        }
+                        if filtering && channels.is_empty() {
+                            continue;
+                        }
+                        let is_selected = match tree_selection {
+                            Some(TreeSelection::Group(id, name)) => {
+                                *id == file_id && name == &group.name
+                            }
+                            _ => false,
+                        };
+                        // Filtered headers use their own ids so they can start expanded
+                        // without changing which groups are expanded once the filter is cleared
+                        let header = egui::CollapsingHeader::new(group.name.replace("\n", " "));
+                        let header = if filtering {
+                            header
+                                .id_source(("filtered", file_id, &group.name))
+                                .default_open(true)
+                        } else {
+                            header.id_source((file_id, &group.name))
+                        };
+                        let checked_count = channels
+                            .iter()
+                            .filter(|channel| {
+                                selected_channels.contains(&channel.id)
+                                    || table_channel == Some(&channel.id)
+                            })
+                            .count();
+                        let group_check = match checked_count {
+                            0 => GroupCheck::None,
+                            count if count == channels.len() => GroupCheck::All,
+                            _ => GroupCheck::Some,
+                        };
+                        let visible_ids =
+                            || channels.iter().map(|channel| channel.id.clone()).collect();
+                        // The checkbox can't be part of the collapsing header, so the
+                        // header is shown with an empty body and the channels below it
+                        let (check_response, response) = ui
+                            .horizontal(|ui| {
+                                let check_response = group_checkbox(ui, group_check);
+                                let response = header
+                                    .selectable(true)
+                                    .selected(is_selected)
+                                    .show(ui, |_| ());
+                                (check_response, response)
+                            })
+                            .inner;
+                        if check_response.clicked() {
+                            let action = match group_check {
+                                GroupCheck::All => GroupAction::Deselect(visible_ids()),
+                                _ => GroupAction::Select(visible_ids()),
+                            };
+                            group_action = Some((group.name.clone(), action));
+                        }
+                        let header_response = &response.header_response;
+                        if header_response.clicked() {
+                            clicked_item = Some(TreeSelection::Group(file_id, group.name.clone()));
+                        }
+                        let menu_id = ui.make_persistent_id(("group_menu", file_id, &group.name));
+                        if header_response.secondary_clicked() {
+                            ui.memory().toggle_popup(menu_id);
+                        }
+                        egui::popup::popup_below_widget(ui, menu_id, header_response, |ui| {
+                            let mut action = None;
+                            if ui.button("Select all channels").clicked() {
+                                action = Some(GroupAction::Select(visible_ids()));
+                            }
+                            if ui.button("Deselect all").clicked() {
+                                action = Some(GroupAction::Deselect(visible_ids()));
+                            }
+                            if ui.button("Export group to CSV...").clicked() {
+                                action = Some(GroupAction::Export(
+                                    group
+                                        .channels
+                                        .iter()
+                                        .map(|channel| channel.id.clone())
+                                        .collect(),
+                                ));
+                            }
+                            if let Some(action) = action {
+                                group_action = Some((group.name.clone(), action));
+                                ui.memory().close_popup();
+                            }
+                        });
+                        if response.body_returned.is_none() {
+                            continue;
+                        }
+                        ui.indent(("channels", file_id, &group.name), |ui| {
+                            for channel in channels {
+                                let is_selected = tree_selection
+                                    == Some(&TreeSelection::Channel(channel.id.clone()));
+                                tree_order.push(channel.id.clone());
+                                ui.horizontal(|ui| {
+                                    let mut checked = selected_channels.contains(&channel.id)
+                                        || table_channel == Some(&channel.id);
+                                    if ui.checkbox(&mut checked, "").changed() {
+                                        toggled_channels.push((channel.id.clone(), checked));
+                                    }
+                                    let response = ui.selectable_label(is_selected, &channel.label);
+                                    if is_selected && scroll_to_selection {
+                                        response.scroll_to_me(egui::Align::Center);
+                                    }
+                                    ui.with_layout(egui::Layout::right_to_left(), |ui| {
+                                        ui.label(&channel.unit);
+                                        ui.label(&channel.length_label)
+                                            .on_hover_text(format!("{} values", channel.length));
+                                        ui.label(&channel.data_type);
+                                    });
+                                    if response.clicked() {
+                                        clicked_item =
+                                            Some(TreeSelection::Channel(channel.id.clone()));
+                                    }
+                                    let menu_id =
+                                        ui.make_persistent_id(("channel_menu", &channel.id));
+                                    if response.secondary_clicked() {
+                                        ui.memory().toggle_popup(menu_id);
+                                    }
+                                    egui::popup::popup_below_widget(ui, menu_id, &response, |ui| {
+                                        if ui.button("Export...").clicked() {
+                                            exported_channel = Some(channel.id.clone());
+                                            ui.memory().close_popup();
+                                        }
+                                    });
+                                });
+                            }
+                        });
+                    }
+                });
+                self.tree_order = tree_order;
+                self.scroll_to_selection = false;
+                if let Some(item) = clicked_item {
+                    self.range_anchor = None;
+                    // Clicking the selected item again clears the selection
+                    if self.tree_selection.as_ref() == Some(&item) {
+                        self.select_tree_item(None);
+                    } else {
+                        self.select_tree_item(Some(item));
+                    }
+                }
+                if let Some(channel_id) = exported_channel {
+                    self.export_dialog(vec![channel_id]);
+                }
+                match group_action {
+                    Some((group_name, GroupAction::Select(channels))) => {
+                        self.select_group_channels(&group_name, channels)
+                    }
+                    Some((_, GroupAction::Deselect(channels))) => {
+                        for channel_id in channels.iter() {
+                            self.deselect_channel(channel_id);
+                        }
+                    }
+                    Some((_, GroupAction::Export(channels))) => self.export_dialog(channels),
+                    None => {}
+                }
+                for (channel_id, checked) in toggled_channels {
+                    if checked {
+                        self.select_channel(channel_id);
+                    } else {
+                        self.deselect_channel(&channel_id);
+                    }
+                }
+            });
+
+        egui::TopBottomPanel::bottom("properties_panel")
+            .resizable(true)
+            .show(ctx, |ui| {
+                let property_rows = &self.property_rows;
+                let tree_selection = self.tree_selection.as_ref();
+                egui::CollapsingHeader::new("Properties")
+                    .default_open(true)
+                    .show(ui, |ui| match tree_selection {
+                        None => {
+                            ui.label(
+                                "Select the file, a group or a channel to view its properties",
+                            );
+                        }
+                        Some(_) if property_rows.is_empty() => {
+                            ui.label("No properties");
+                        }
+                        Some(_) => {
+                            ScrollArea::vertical().show(ui, |ui| {
+                                property_grid(ui, "properties", property_rows);
+                            });
+                        }
+                    });
+            });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // The central panel the region left after adding TopPanel's and SidePanel's
+
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.central_tab, CentralTab::Plot, "Plot");
+                ui.selectable_value(&mut self.central_tab, CentralTab::Spectrum, "Spectrum");
+                ui.selectable_value(&mut self.central_tab, CentralTab::Table, "Table");
+            });
+            ui.separator();
+            if self.central_tab == CentralTab::Spectrum {
+                self.spectrum_ui(ui);
+                return;
+            }
+            if self.central_tab == CentralTab::Table {
+                self.table_ui(ui);
+                return;
+            }
+
+            for channel_id in self.selected_channels.iter() {
+                if let Some(load) = self.loads.get(channel_id) {
+                    let progress = load.progress();
+                    ui.add(egui::ProgressBar::new(progress).text(format!(
+                        "Loading {} / {}",
+                        channel_id.group, channel_id.channel
+                    )));
+                }
+            }
+            if !self.queued_loads.is_empty() {
+                ui.label(format!(
+                    "{} more channels waiting to load",
+                    self.queued_loads.len()
+                ));
+            }
+
+            let mut plot_mode = self.plot_mode;
+            let mut x_channel = self.xy_x_channel.clone();
+            let mut y_channel = self.xy_y_channel.clone();
+            let mut save_image = false;
+            ui.horizontal(|ui| {
+                ui.label("Plot:");
+                ui.radio_value(&mut plot_mode, PlotMode::Time, "Against time");
+                ui.radio_value(&mut plot_mode, PlotMode::Xy, "XY");
+                if plot_mode == PlotMode::Time {
+                    ui.separator();
+                    ui.radio_value(&mut self.plot_layout, PlotLayout::Overlaid, "Overlaid");
+                    ui.radio_value(&mut self.plot_layout, PlotLayout::Stacked, "Stacked")
+                        .on_hover_text("Plot each channel on its own y-axis");
+                }
+                if plot_mode == PlotMode::Xy {
+                    let selected_channels = &self.selected_channels;
+                    let mut channel_combo = |label: &str, channel: &mut Option<ChannelId>| {
+                        let selected_text = match channel {
+                            Some(channel_id) => {
+                                format!("{} / {}", channel_id.group, channel_id.channel)
+                            }
+                            None => String::from("None"),
+                        };
+                        egui::ComboBox::from_label(label)
+                            .selected_text(selected_text)
+                            .show_ui(ui, |ui| {
+                                for channel_id in selected_channels.iter() {
+                                    ui.selectable_value(
+                                        channel,
+                                        Some(channel_id.clone()),
+                                        format!("{} / {}", channel_id.group, channel_id.channel),
+                                    );
+                                }
+                            });
+                    };
+                    channel_combo("X", &mut x_channel);
+                    channel_combo("Y", &mut y_channel);
+                    ui.checkbox(&mut self.equal_aspect, "Equal aspect");
+                }
+                if ui.button("Save image...").clicked() {
+                    save_image = true;
+                }
+            });
+            if plot_mode != self.plot_mode
+                || x_channel != self.xy_x_channel
+                || y_channel != self.xy_y_channel
+            {
+                self.plot_mode = plot_mode;
+                self.xy_x_channel = x_channel;
+                self.xy_y_channel = y_channel;
+                self.start_xy_load();
+            }
+            if save_image {
+                self.save_image_dialog();
+            }
+            if let Some(load) = self.xy_load.as_ref() {
+                ui.add(egui::ProgressBar::new(load.progress()).text("Loading XY plot"));
+            }
+
+            if self.plot_mode == PlotMode::Time {
+                self.zoom_ui(ui);
+            }
+
+            if self.plot_mode == PlotMode::Time {
+                self.scroll_to_latest();
+            }
+            let equal_aspect = self.plot_mode == PlotMode::Xy && self.equal_aspect;
+            let zoom_range = match self.plot_mode {
+                PlotMode::Time => self.zoom_range,
+                PlotMode::Xy => None,
+            };
+            let cursor_positions = match self.plot_mode {
+                PlotMode::Time if self.cursors_enabled => self.cursor_positions,
+                _ => None,
+            };
+            let time_mode = self.plot_mode == PlotMode::Time;
+            let stacked = time_mode && self.plot_layout == PlotLayout::Stacked;
+            let plot_view = self.plot_view();
+            if let Some(warning) = plot_view.warning.as_ref() {
+                ui.colored_label(Color32::YELLOW, warning);
+            }
+            let mut stack_plots = false;
+            if !stacked && time_mode && plot_view.has_mixed_units() {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        Color32::YELLOW,
+                        format!(
+                            "Channels with different units are plotted on one axis: {}",
+                            plot_view
+                                .units()
+                                .into_iter()
+                                .filter(|unit| !unit.is_empty())
+                                .collect::<Vec<&str>>()
+                                .join(", ")
+                        ),
+                    );
+                    stack_plots = ui.button("Use stacked plots").clicked();
+                });
+            }
+            match plot_view.shared_unit() {
+                Some(unit) if !stacked => {
+                    ui.label(format!("{}, y-axis: {}", plot_view.x_label, unit))
+                }
+                _ => ui.label(&plot_view.x_label),
+            };
+            if stacked {
+                let (new_zoom_range, response) =
+                    stacked_plots_ui(ui, plot_view, zoom_range, cursor_positions);
+                if let Some(range) = new_zoom_range {
+                    self.set_zoom_range(range);
+                }
+                if let Some(response) = response {
+                    self.open_plot_menu(&response);
+                }
+                self.cursors_ui(ui);
+                self.statistics_ui(ui);
+                return;
+            }
+            let mut plot = Plot::new("Channels")
+                .legend(Legend::default())
+                .view_aspect(1.0);
+            if equal_aspect {
+                plot = plot.data_aspect(1.0);
+            }
+            if let Some((start, end)) = zoom_range {
+                // Changing the included range also resets any zooming done on the plot
+                plot = plot.include_x(start).include_x(end);
+            }
+            for line in plot_view.lines.iter() {
+                plot = plot.line(
+                    Line::new(Values::from_values(line.points.clone()))
+                        .color(line.color)
+                        .name(line.label()),
+                );
+            }
+            if let Some(positions) = cursor_positions {
+                for position in positions.iter() {
+                    plot = plot.vline(VLine::new(*position).color(Color32::LIGHT_GRAY));
+                }
+            }
+            let response = ui.add(plot);
+            if time_mode {
+                self.open_plot_menu(&response);
+            }
+            if stack_plots {
+                self.plot_layout = PlotLayout::Stacked;
+            }
+            self.cursors_ui(ui);
+            self.statistics_ui(ui);
+        });
+    }
+}
+
+impl TemplateApp<std::fs::File> {
+    /// Open the plot's context menu where the plot was right clicked
+    fn open_plot_menu(&mut self, response: &egui::Response) {
+        if response.secondary_clicked() {
+            self.plot_menu_position = response.interact_pointer_pos();
+        }
+    }
+
+    /// Context menu of the plot, which closes when clicking outside it
+    fn plot_menu_ui(&mut self, ctx: &egui::CtxRef) {
+        let position = match self.plot_menu_position {
+            Some(position) => position,
+            None => return,
+        };
+        let mut copy_data = false;
+        let response = egui::Area::new("plot_menu")
+            .order(egui::Order::Foreground)
+            .fixed_pos(position)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    copy_data = ui
+                        .button("Copy data")
+                        .on_hover_text("Copy the visible samples of the plotted channels as TSV")
+                        .clicked();
+                });
+            })
+            .response;
+        let clicked_outside = {
+            let pointer = &ctx.input().pointer;
+            pointer.any_click()
+                && !pointer
+                    .interact_pos()
+                    .is_some_and(|pos| response.rect.contains(pos))
+        };
+        if copy_data {
+            self.copy_visible_data(ctx);
+        }
+        if copy_data || clicked_outside {
+            self.plot_menu_position = None;
+        }
+    }
+
+    /// Copy the visible samples of the plotted channels to the clipboard as TSV
+    fn copy_visible_data(&mut self, ctx: &egui::CtxRef) {
+        match self.visible_data_tsv() {
+            Ok((text, truncated)) => {
+                ctx.output().copied_text = text;
+                if truncated {
+                    self.error_message = Some(format!(
+                        "Only the first {} visible samples of each channel were copied. \
+                         Zoom in or increase the row limit in the settings to copy more.",
+                        self.max_copy_rows
+                    ));
+                }
+            }
+            Err(err) => self.error_message = Some(format!("Could not copy data: {}", err)),
+        }
+    }
+
+    /// Controls for adjusting the zoomed range of the plot
+    fn zoom_ui(&mut self, ui: &mut egui::Ui) {
+        let (mut start, mut end) = match self.zoom_range {
+            Some(zoom_range) => zoom_range,
+            None => return,
+        };
+        let mut zoom_out = false;
+        ui.horizontal(|ui| {
+            let speed = (end - start).abs().max(f64::EPSILON) / 100.0;
+            ui.label("Zoomed to");
+            ui.add(egui::DragValue::new(&mut start).speed(speed));
+            ui.label("to");
+            ui.add(egui::DragValue::new(&mut end).speed(speed));
+            if !self.detail_loads.is_empty() {
+                ui.label("Loading detail...");
+            }
+            zoom_out = ui.button("Zoom out").clicked();
+        });
+        if zoom_out {
+            self.set_zoom_range(None);
+        } else if start < end {
+            self.set_zoom_range(Some((start, end)));
+        }
+    }
+
+    /// Table of statistics of each plotted channel, which can be copied as TSV
+    fn statistics_ui(&mut self, ui: &mut egui::Ui) {
+        if self.plot_mode != PlotMode::Time {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.show_statistics, "Statistics");
+            if self.show_statistics {
+                ui.radio_value(
+                    &mut self.statistics_scope,
+                    StatisticsScope::Visible,
+                    "Zoomed range",
+                );
+                ui.radio_value(
+                    &mut self.statistics_scope,
+                    StatisticsScope::Channel,
+                    "Whole channels",
+                );
+            }
+        });
+        if !self.show_statistics {
+            return;
+        }
+
+        let rows: Vec<(&str, Result<&NumericStatistics, &str>)> = match self.statistics_scope {
+            StatisticsScope::Visible => self
+                .cached_plot
+                .iter()
+                .flat_map(|plot_view| plot_view.visible_statistics.iter())
+                .map(|row| {
+                    (
+                        row.name.as_str(),
+                        row.statistics.as_ref().map_err(|e| e.as_str()),
+                    )
+                })
+                .collect(),
+            StatisticsScope::Channel => self
+                .cached_plot
+                .iter()
+                .flat_map(|plot_view| plot_view.channel_axes.iter())
+                .map(|axis| {
+                    let statistics = match self.channel_statistics.get(&axis.channel_id) {
+                        Some(statistics) => statistics.as_ref().map_err(|e| e.as_str()),
+                        None => Err("Computing..."),
+                    };
+                    (axis.name.as_str(), statistics)
+                })
+                .collect(),
+        };
+        const HEADERS: [&str; 7] = ["Channel", "Min", "Max", "Mean", "RMS", "Count", "NaN count"];
+        let format_value = |value: Option<f64>| match value {
+            Some(value) => value.to_string(),
+            None => String::from("-"),
+        };
+        let cells = |statistics: &NumericStatistics| {
+            vec![
+                format_value(statistics.min()),
+                format_value(statistics.max()),
+                format_value(statistics.mean()),
+                format_value(statistics.rms()),
+                statistics.count().to_string(),
+                statistics.nan_count().to_string(),
+            ]
+        };
+
+        egui::Grid::new("statistics")
+            .num_columns(HEADERS.len())
+            .striped(true)
+            .show(ui, |ui| {
+                for header in HEADERS.iter() {
+                    ui.label(*header);
+                }
+                ui.end_row();
+                for (name, statistics) in rows.iter() {
+                    ui.label(*name);
+                    match statistics {
+                        Ok(statistics) => {
+                            for cell in cells(statistics) {
+                                ui.label(cell);
+                            }
+                        }
+                        Err(message) => {
+                            ui.label(*message);
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        if ui.button("Copy as TSV").clicked() {
+            let mut text = HEADERS.join("\t");
+            for (name, statistics) in rows.iter() {
+                text.push('\n');
+                text.push_str(name);
+                let values = match statistics {
+                    Ok(statistics) => cells(statistics),
+                    Err(_) => Vec::new(),
+                };
+                for value in values {
+                    text.push('\t');
+                    text.push_str(&value);
+                }
+            }
+            ui.output().copied_text = text;
+        }
+    }
+
+    /// Controls for positioning the plot cursors, and the values of each channel at them
+    fn cursors_ui(&mut self, ui: &mut egui::Ui) {
+        let plot_view = match self.cached_plot.take() {
+            Some(plot_view) => plot_view,
+            None => return,
+        };
+        let x_range = match plot_view.x_range {
+            Some(x_range) if !plot_view.channel_axes.is_empty() => x_range,
+            _ => {
+                self.cached_plot = Some(plot_view);
+                return;
+            }
+        };
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.cursors_enabled, "Cursors");
+            if self.cursors_enabled {
+                ui.checkbox(&mut self.snap_cursors, "Snap to samples");
+                if ui.button("Reset").clicked() {
+                    self.reset_cursors();
+                }
+                if let Some([first, second]) = self.cursor_positions {
+                    if first != second && ui.button("Zoom to cursors").clicked() {
+                        self.set_zoom_range(Some((first.min(second), first.max(second))));
+                    }
+                }
+            }
+        });
+        if self.cursors_enabled {
+            let (x_min, x_max) = x_range;
+            let mut positions = self.cursor_positions.unwrap_or([
+                x_min + (x_max - x_min) / 3.0,
+                x_min + (x_max - x_min) * 2.0 / 3.0,
+            ]);
+            let mut changed = self.cursor_positions.is_none();
+            for (i, position) in positions.iter_mut().enumerate() {
+                changed |= ui
+                    .add(
+                        egui::Slider::new(position, x_min..=x_max)
+                            .text(format!("Cursor {}", i + 1)),
+                    )
+                    .changed();
+            }
+            if changed {
+                if self.snap_cursors {
+                    // Snap to the nearest sample of the first plotted channel
+                    let axis = &plot_view.channel_axes[0];
+                    for position in positions.iter_mut() {
+                        let index = axis.sample_position(*position).round().max(0.0);
+                        *position = axis.offset + index * axis.scale;
+                    }
+                }
+                self.cursor_positions = Some(positions);
+                self.cursor_readouts = None;
+            }
+            if self.cursor_readouts.is_none() {
+                self.cursor_readouts = Some(self.read_cursor_values(&plot_view, positions));
+            }
+
+            ui.label(format!("Δx: {}", positions[1] - positions[0]));
+            let format_value = |value: Option<f64>| match value {
+                Some(value) => value.to_string(),
+                None => String::from("-"),
+            };
+            egui::Grid::new("cursor_readouts")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Channel");
+                    ui.label("Cursor 1");
+                    ui.label("Cursor 2");
+                    ui.label("Δy");
+                    ui.end_row();
+                    for readout in self.cursor_readouts.iter().flatten() {
+                        ui.label(&readout.name);
+                        ui.label(format_value(readout.values[0]));
+                        ui.label(format_value(readout.values[1]));
+                        let delta = match readout.values {
+                            [Some(first), Some(second)] => Some(second - first),
+                            _ => None,
+                        };
+                        ui.label(format_value(delta));
+                        ui.end_row();
+                    }
+                });
+        }
+        self.cached_plot = Some(plot_view);
+    }
+}
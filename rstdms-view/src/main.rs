@@ -0,0 +1,34 @@
+// #![warn(clippy::all)]
+use flexi_logger::{opt_format, Logger};
+use std::env;
+use std::path::PathBuf;
+
+mod app;
+mod plot_image;
+mod spectrum;
+pub use app::TemplateApp;
+
+fn main() {
+    // Initialize a logger for logging debug messages, useful during prototyping
+    // "rstdms=debug, rstdms_view=debug"
+    Logger::with_env_or_str("rstdms=error, rstdms_view=error")
+        .log_to_file()
+        .directory("log_files")
+        .format(opt_format)
+        .start()
+        .unwrap();
+
+    // Create the gui stuff
+    let mut app = TemplateApp::default();
+
+    // call with cargo run -p rstdms-view -- Example.tdms to open a file at startup
+    if let Some(path) = env::args_os().nth(1) {
+        app.open_file(PathBuf::from(path));
+    }
+
+    let native_options = eframe::NativeOptions {
+        drag_and_drop_support: true,
+        ..eframe::NativeOptions::default()
+    };
+    eframe::run_native(Box::new(app), native_options);
+}
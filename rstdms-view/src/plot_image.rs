@@ -0,0 +1,168 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::path::Path;
+
+use eframe::egui;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use plotters::style::{register_font, FontStyle};
+
+/// A line to draw in a saved plot image
+pub struct ImageLine {
+    pub name: String,
+    pub color: (u8, u8, u8),
+    pub points: Vec<(f64, f64)>,
+}
+
+/// Lines drawn on one set of axes
+pub struct ImageSubplot {
+    /// y-axis label
+    pub label: Option<String>,
+    /// Whether to show a legend of the line names
+    pub legend: bool,
+    pub lines: Vec<ImageLine>,
+}
+
+/// Everything drawn in a saved plot image
+pub struct PlotImage {
+    /// Plots stacked vertically, sharing the x-axis
+    pub subplots: Vec<ImageSubplot>,
+    pub x_label: String,
+    /// Range of x values to show, or None to fit all points
+    pub x_range: Option<(f64, f64)>,
+    /// x positions of vertical cursor lines
+    pub cursors: Vec<f64>,
+    /// Text shown above the plot
+    pub caption: Option<String>,
+}
+
+/// Default size in pixels of saved plot images
+pub const DEFAULT_IMAGE_SIZE: (u32, u32) = (1600, 1000);
+
+/// Render a plot to a PNG file with the given size in pixels
+pub fn save_png(path: &Path, plot: &PlotImage, size: (u32, u32)) -> Result<(), Box<dyn Error>> {
+    register_fonts()?;
+    let text_size = (size.1 as f64 / 50.0).max(12.0);
+
+    let root = BitMapBackend::new(path, size).into_drawing_area();
+    root.fill(&WHITE)?;
+    let plot_area = match plot.caption.as_ref() {
+        Some(caption) => root.titled(caption, ("sans-serif", text_size * 1.2))?,
+        None => root.clone(),
+    };
+
+    let x_range = match plot.x_range {
+        Some(range) => range,
+        None => padded_range(
+            plot.subplots
+                .iter()
+                .flat_map(|subplot| subplot.lines.iter())
+                .flat_map(|line| line.points.iter().map(|point| point.0)),
+            0.0,
+        ),
+    };
+    let areas = plot_area.split_evenly((plot.subplots.len().max(1), 1));
+    for (index, (subplot, area)) in plot.subplots.iter().zip(areas.iter()).enumerate() {
+        // Only the bottom subplot has an x-axis label
+        let x_label = if index + 1 == plot.subplots.len() {
+            plot.x_label.as_str()
+        } else {
+            ""
+        };
+        draw_subplot(area, subplot, x_label, x_range, &plot.cursors, text_size)?;
+    }
+
+    root.present()?;
+    Ok(())
+}
+
+fn draw_subplot(
+    area: &DrawingArea<BitMapBackend, Shift>,
+    subplot: &ImageSubplot,
+    x_label: &str,
+    x_range: (f64, f64),
+    cursors: &[f64],
+    text_size: f64,
+) -> Result<(), Box<dyn Error>> {
+    // Points outside the plotted area would be drawn along its edges, so are left out
+    let visible = |point: &&(f64, f64)| {
+        point.0 >= x_range.0 && point.0 <= x_range.1 && point.0.is_finite() && point.1.is_finite()
+    };
+    let y_range = padded_range(
+        subplot
+            .lines
+            .iter()
+            .flat_map(|line| line.points.iter().filter(visible).map(|point| point.1)),
+        0.05,
+    );
+
+    let mut chart = ChartBuilder::on(area)
+        .margin(text_size)
+        .x_label_area_size(text_size * 3.0)
+        .y_label_area_size(text_size * 5.0)
+        .build_cartesian_2d(x_range.0..x_range.1, y_range.0..y_range.1)?;
+    chart
+        .configure_mesh()
+        .x_desc(x_label)
+        .y_desc(subplot.label.as_deref().unwrap_or(""))
+        .label_style(("sans-serif", text_size))
+        .axis_desc_style(("sans-serif", text_size))
+        .draw()?;
+
+    for line in subplot.lines.iter() {
+        let color = RGBColor(line.color.0, line.color.1, line.color.2);
+        let style = color.stroke_width(2);
+        let points = line.points.iter().filter(visible).cloned();
+        chart
+            .draw_series(LineSeries::new(points, style))?
+            .label(line.name.as_str())
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], style));
+    }
+    for &cursor in cursors.iter() {
+        if cursor < x_range.0 || cursor > x_range.1 {
+            continue;
+        }
+        let points = vec![(cursor, y_range.0), (cursor, y_range.1)];
+        let style = RGBColor(90, 90, 90).stroke_width(2);
+        chart.draw_series(DashedLineSeries::new(points, 10, 6, style))?;
+    }
+    if subplot.legend && !subplot.lines.is_empty() {
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperRight)
+            .label_font(("sans-serif", text_size))
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+    }
+    Ok(())
+}
+
+/// Use the font bundled with egui for text, so images look the same on every system
+fn register_fonts() -> Result<(), Box<dyn Error>> {
+    let definitions = egui::FontDefinitions::default();
+    match definitions.font_data.get("Ubuntu-Light") {
+        Some(Cow::Borrowed(bytes)) => register_font("sans-serif", FontStyle::Normal, bytes)
+            .map_err(|_| "The plot font could not be loaded".into()),
+        _ => Err("The plot font is not available".into()),
+    }
+}
+
+/// Get the range of finite values, extended by a fraction of its width on each side.
+/// Empty or zero width ranges are widened so they can still be drawn.
+fn padded_range<I: Iterator<Item = f64>>(values: I, padding: f64) -> (f64, f64) {
+    let (min, max) = values
+        .filter(|value| value.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+            (min.min(value), max.max(value))
+        });
+    if min > max {
+        (0.0, 1.0)
+    } else if min == max {
+        let half_width = (min.abs() * 0.5).max(0.5);
+        (min - half_width, max + half_width)
+    } else {
+        let margin = (max - min) * padding;
+        (min - margin, max + margin)
+    }
+}
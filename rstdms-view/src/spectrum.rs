@@ -0,0 +1,132 @@
+/// Window functions applied to data before computing a spectrum
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowFunction {
+    Rectangular,
+    Hann,
+    FlatTop,
+}
+
+impl WindowFunction {
+    pub const ALL: [WindowFunction; 3] = [
+        WindowFunction::Hann,
+        WindowFunction::FlatTop,
+        WindowFunction::Rectangular,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WindowFunction::Rectangular => "Rectangular",
+            WindowFunction::Hann => "Hann",
+            WindowFunction::FlatTop => "Flat top",
+        }
+    }
+
+    /// Get the window coefficients for `length` values
+    fn coefficients(&self, length: usize) -> Vec<f64> {
+        let cosine_sum = |coefficients: &[f64]| {
+            let scale = 2.0 * std::f64::consts::PI / (length.max(2) - 1) as f64;
+            (0..length)
+                .map(|i| {
+                    coefficients
+                        .iter()
+                        .enumerate()
+                        .map(|(k, a)| {
+                            let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+                            sign * a * (k as f64 * scale * i as f64).cos()
+                        })
+                        .sum()
+                })
+                .collect()
+        };
+        match self {
+            WindowFunction::Rectangular => vec![1.0; length],
+            WindowFunction::Hann => cosine_sum(&[0.5, 0.5]),
+            WindowFunction::FlatTop => cosine_sum(&[
+                0.215_578_95,
+                0.416_631_58,
+                0.277_263_158,
+                0.083_578_947,
+                0.006_947_368,
+            ]),
+        }
+    }
+}
+
+/// A single-sided amplitude spectrum
+pub struct Spectrum {
+    /// Frequency of each bin in Hz, or the bin index if the sample interval is unknown
+    pub frequencies: Vec<f64>,
+    /// Amplitude of each bin in dB
+    pub magnitudes_db: Vec<f64>,
+    /// Whether frequencies are in Hz rather than bin indices
+    pub in_hertz: bool,
+}
+
+/// Whether spectra can be computed, which requires the `spectrum` feature
+pub fn is_available() -> bool {
+    cfg!(feature = "spectrum")
+}
+
+/// Compute the amplitude spectrum of evenly spaced values, using the sample interval
+/// in seconds if known. Returns None if built without the `spectrum` feature.
+pub fn compute_spectrum(
+    values: &[f64],
+    sample_interval: Option<f64>,
+    window: WindowFunction,
+) -> Option<Spectrum> {
+    if values.is_empty() {
+        return Some(Spectrum {
+            frequencies: Vec::new(),
+            magnitudes_db: Vec::new(),
+            in_hertz: false,
+        });
+    }
+    let coefficients = window.coefficients(values.len());
+    let windowed: Vec<f64> = values
+        .iter()
+        .zip(coefficients.iter())
+        .map(|(value, coefficient)| value * coefficient)
+        .collect();
+    let magnitudes = fft_magnitudes(windowed)?;
+
+    // Scale so a sine wave's peak has its amplitude, correcting for the window's gain
+    let window_sum: f64 = coefficients.iter().sum();
+    let bin_count = values.len() / 2 + 1;
+    let magnitudes_db = magnitudes
+        .iter()
+        .take(bin_count)
+        .enumerate()
+        .map(|(i, magnitude)| {
+            let single_sided = if i == 0 { 1.0 } else { 2.0 };
+            20.0 * (single_sided * magnitude / window_sum).log10()
+        })
+        .collect();
+    let (bin_width, in_hertz) = match sample_interval {
+        Some(interval) if interval > 0.0 => (1.0 / (values.len() as f64 * interval), true),
+        _ => (1.0, false),
+    };
+    Some(Spectrum {
+        frequencies: (0..bin_count).map(|i| i as f64 * bin_width).collect(),
+        magnitudes_db,
+        in_hertz,
+    })
+}
+
+#[cfg(feature = "spectrum")]
+fn fft_magnitudes(values: Vec<f64>) -> Option<Vec<f64>> {
+    use rustfft::num_complex::Complex;
+    use rustfft::FftPlanner;
+
+    let mut buffer: Vec<Complex<f64>> = values
+        .into_iter()
+        .map(|value| Complex::new(value, 0.0))
+        .collect();
+    let fft = FftPlanner::new().plan_fft_forward(buffer.len());
+    fft.process(&mut buffer);
+    Some(buffer.iter().map(|value| value.norm()).collect())
+}
+
+#[cfg(not(feature = "spectrum"))]
+fn fft_magnitudes(_values: Vec<f64>) -> Option<Vec<f64>> {
+    None
+}
@@ -0,0 +1,1373 @@
+extern crate clap;
+
+use clap::{App, AppSettings, Arg, ArgGroup, ArgMatches, SubCommand};
+use rstdms::{
+    format_suggestions, similar_names, Channel, ChannelStatistics, CsvExportOptions, CsvTimeColumn,
+    DiffOptions, ErrorPolicy, FinalizeResult, ImportOptions, IndexStatus, LineProtocolOptions,
+    MergeCompatibility, MergeOptions, NativeType, PropertyFilter, PropertyPrecedence,
+    RawExportOptions, ReadOptions, SnapshotOptions, StatisticsOptions, TdmsFile, TdsType,
+    Timestamp, WaveformTiming,
+};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::time::Instant;
+
+fn main() {
+    match main_impl() {
+        Ok(exit_code) => std::process::exit(exit_code),
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    }
+}
+
+fn main_impl() -> Result<i32, String> {
+    let matches = App::new("tdms")
+        .version("0.0.1")
+        .about("Tools for working with TDMS files")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("diff")
+                .about("Compares two TDMS files, exiting with a non-zero status if they differ")
+                .arg(
+                    Arg::with_name("left")
+                        .help("Path to the first TDMS file")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("right")
+                        .help("Path to the second TDMS file")
+                        .required(true)
+                        .index(2),
+                )
+                .arg(
+                    Arg::with_name("data")
+                        .long("data")
+                        .help("Compare channel data values as well as metadata"),
+                )
+                .arg(
+                    Arg::with_name("tol")
+                        .long("tol")
+                        .takes_value(true)
+                        .help("Absolute tolerance for comparing floating point values"),
+                )
+                .arg(
+                    Arg::with_name("rtol")
+                        .long("rtol")
+                        .takes_value(true)
+                        .help("Relative tolerance for comparing floating point values"),
+                )
+                .arg(
+                    Arg::with_name("ignore-property")
+                        .long("ignore-property")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("Name of a property to ignore, may be given multiple times"),
+                )
+                .arg(
+                    Arg::with_name("property")
+                        .long("property")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .conflicts_with("data")
+                        .help("Only compare the named property, may be given multiple times"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("repair")
+                .about("Salvages readable data from a truncated or damaged TDMS file")
+                .arg(
+                    Arg::with_name("input")
+                        .help("Path to the damaged TDMS file")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .help("Path to write the repaired TDMS file to")
+                        .required_unless("in-place-finalize")
+                        .index(2),
+                )
+                .arg(
+                    Arg::with_name("in-place-finalize")
+                        .long("in-place-finalize")
+                        .conflicts_with("output")
+                        .help("Only fix the length of an incomplete final segment, modifying the input file"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("merge")
+                .about("Concatenates the data from multiple TDMS files into a single file")
+                .arg(
+                    Arg::with_name("inputs")
+                        .help("Paths to the TDMS files to merge, in order")
+                        .required(true)
+                        .multiple(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Path to write the merged TDMS file to"),
+                )
+                .arg(
+                    Arg::with_name("union")
+                        .long("union")
+                        .help("Allow files with different channels, leaving gaps in channels missing from a file"),
+                )
+                .arg(
+                    Arg::with_name("last-properties")
+                        .long("last-properties")
+                        .help("Use property values from the last file they appear in rather than the first"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("stats")
+                .about("Prints summary statistics for each channel in a TDMS file")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Path to the TDMS file to read")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("Output statistics as JSON"),
+                )
+                .arg(
+                    Arg::with_name("fragmentation")
+                        .long("fragmentation")
+                        .conflicts_with("json")
+                        .help("Also print how channel data is divided between segments"),
+                )
+                .arg(
+                    Arg::with_name("channel")
+                        .long("channel")
+                        .takes_value(true)
+                        .help("Only include channels with a \"group/channel\" path matching this pattern, where * matches any characters"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("summary")
+                .about("Prints an overview of a TDMS file and its root properties")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Path to the TDMS file to read")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("skip-errors")
+                        .long("skip-errors")
+                        .help("Skip segments that cannot be read rather than failing, reporting them as warnings"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("cat")
+                .about("Prints the values of a channel, one per line")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Path to the TDMS file to read")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("channel")
+                        .long("channel")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Channel to print, as \"group/channel\""),
+                )
+                .arg(
+                    Arg::with_name("head")
+                        .long("head")
+                        .takes_value(true)
+                        .help("Only print the first N values"),
+                )
+                .arg(
+                    Arg::with_name("tail")
+                        .long("tail")
+                        .takes_value(true)
+                        .help("Only print the last N values"),
+                )
+                .arg(
+                    Arg::with_name("range")
+                        .long("range")
+                        .takes_value(true)
+                        .help("Only print values with index in the range A..B, excluding B"),
+                )
+                .group(ArgGroup::with_name("selection").args(&["head", "tail", "range"]))
+                .arg(
+                    Arg::with_name("paired-time")
+                        .long("paired-time")
+                        .help("Print the waveform time of each value before the value"),
+                )
+                .arg(
+                    Arg::with_name("offsets")
+                        .long("offsets")
+                        .help("Print the segment index and byte offset in the file of each value before the value"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Exports channel data as CSV, with one column per channel, or as InfluxDB line protocol")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Path to the TDMS file to read")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("where")
+                        .long("where")
+                        .takes_value(true)
+                        .help("Only export channels with properties matching an expression, eg. \"unit_string == 'bar' && wf_increment < 0.001\""),
+                )
+                .arg(
+                    Arg::with_name("channel")
+                        .long("channel")
+                        .takes_value(true)
+                        .help("Only export channels with a \"group/channel\" path matching this pattern, where * matches any characters"),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["csv", "line-protocol"])
+                        .default_value("csv")
+                        .help("Format to export, either CSV or InfluxDB line protocol with one line per value"),
+                )
+                .arg(
+                    Arg::with_name("time")
+                        .long("time")
+                        .takes_value(true)
+                        .possible_values(&["none", "relative", "absolute"])
+                        .default_value("none")
+                        .help("Time column to write before the channel values of a CSV export, computed from the waveform timing shared by all channels"),
+                )
+                .arg(
+                    Arg::with_name("measurement")
+                        .long("measurement")
+                        .takes_value(true)
+                        .help("Measurement of the lines of a line protocol export, instead of each channel's group name"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .help("Path to write the CSV file to, instead of standard output"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export-raw")
+                .about("Exports each channel to a file of raw little endian values, with a JSON file describing it")
+                .arg(
+                    Arg::with_name("path")
+                        .help("Path to the TDMS file to read")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("output-dir")
+                        .short("o")
+                        .long("output-dir")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Directory to write the files to, which is created if it doesn't exist"),
+                )
+                .arg(
+                    Arg::with_name("where")
+                        .long("where")
+                        .takes_value(true)
+                        .help("Only export channels with properties matching an expression, eg. \"unit_string == 'bar' && wf_increment < 0.001\""),
+                )
+                .arg(
+                    Arg::with_name("channel")
+                        .long("channel")
+                        .takes_value(true)
+                        .help("Only export channels with a \"group/channel\" path matching this pattern, where * matches any characters"),
+                )
+                .arg(
+                    Arg::with_name("native")
+                        .long("native")
+                        .help("Write integer and floating point values with their original type rather than converting to f64"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("index")
+                .about("Creates or refreshes the .tdms_index files of TDMS files")
+                .arg(
+                    Arg::with_name("paths")
+                        .help("TDMS files, or directories to index the .tdms files in")
+                        .required(true)
+                        .multiple(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("recursive")
+                        .short("r")
+                        .long("recursive")
+                        .help("Also index .tdms files in subdirectories"),
+                )
+                .arg(
+                    Arg::with_name("check")
+                        .long("check")
+                        .help("Only check that existing indexes match their data files, without writing any"),
+                )
+                .arg(
+                    Arg::with_name("jobs")
+                        .short("j")
+                        .long("jobs")
+                        .takes_value(true)
+                        .help("Number of files to process at once, defaults to the number of CPUs"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("import")
+                .about("Converts a CSV file with a header row to a TDMS file, with one channel per column")
+                .arg(
+                    Arg::with_name("input")
+                        .help("Path to the CSV file to read")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Path to write the TDMS file to"),
+                )
+                .arg(
+                    Arg::with_name("group")
+                        .long("group")
+                        .takes_value(true)
+                        .default_value("Group")
+                        .help("Name of the group to write the channels to"),
+                )
+                .arg(
+                    Arg::with_name("time-column")
+                        .long("time-column")
+                        .takes_value(true)
+                        .help("Column of times, written as waveform timing if the times have a constant rate"),
+                )
+                .arg(
+                    Arg::with_name("units")
+                        .long("units")
+                        .help("Split column names of the form \"name (unit)\" into the channel name and unit"),
+                )
+                .arg(
+                    Arg::with_name("delimiter")
+                        .long("delimiter")
+                        .takes_value(true)
+                        .default_value(",")
+                        .help("Character separating the fields of each row"),
+                )
+                .arg(
+                    Arg::with_name("rows-per-segment")
+                        .long("rows-per-segment")
+                        .takes_value(true)
+                        .help("Number of rows written per segment, which are also used to detect column types"),
+                )
+                .arg(
+                    Arg::with_name("skip-malformed")
+                        .long("skip-malformed")
+                        .help("Skip malformed rows rather than failing, reporting them as warnings"),
+                ),
+        )
+        .get_matches();
+
+    match matches.subcommand() {
+        ("diff", Some(sub_matches)) => diff(sub_matches),
+        ("repair", Some(sub_matches)) => repair(sub_matches),
+        ("merge", Some(sub_matches)) => merge(sub_matches),
+        ("stats", Some(sub_matches)) => stats(sub_matches),
+        ("summary", Some(sub_matches)) => summary(sub_matches),
+        ("cat", Some(sub_matches)) => cat(sub_matches),
+        ("export", Some(sub_matches)) => export(sub_matches),
+        ("export-raw", Some(sub_matches)) => export_raw(sub_matches),
+        ("index", Some(sub_matches)) => index(sub_matches),
+        ("import", Some(sub_matches)) => import(sub_matches),
+        _ => Err(String::from("A subcommand is required")),
+    }
+}
+
+fn diff(matches: &ArgMatches) -> Result<i32, String> {
+    let left = open_file(matches.value_of("left").unwrap())?;
+    let right = open_file(matches.value_of("right").unwrap())?;
+    let ignore_properties: Vec<String> = matches
+        .values_of("ignore-property")
+        .map(|values| values.map(String::from).collect())
+        .unwrap_or_default();
+
+    if !matches.is_present("data") {
+        // Metadata only, so compare snapshots of the files as changes from left to right
+        let options = SnapshotOptions {
+            properties: matches
+                .values_of("property")
+                .map(|values| values.map(String::from).collect()),
+            ignore_properties,
+        };
+        let changes = left
+            .snapshot_with_options(&options)
+            .diff(&right.snapshot_with_options(&options));
+        println!("{}", changes);
+        return Ok(if changes.is_empty() { 0 } else { 1 });
+    }
+
+    let options = DiffOptions {
+        compare_data: true,
+        ignore_properties,
+        absolute_tolerance: parse_float_arg(matches, "tol")?.unwrap_or(0.0),
+        relative_tolerance: parse_float_arg(matches, "rtol")?.unwrap_or(0.0),
+        ..DiffOptions::default()
+    };
+
+    let report = match rstdms::diff(&left, &right, &options) {
+        Ok(report) => report,
+        Err(err) => return Err(format!("Error comparing files: {}", err)),
+    };
+    println!("{}", report);
+
+    Ok(if report.is_empty() { 0 } else { 1 })
+}
+
+fn repair(matches: &ArgMatches) -> Result<i32, String> {
+    let input_path = matches.value_of("input").unwrap();
+
+    if matches.is_present("in-place-finalize") {
+        let mut file = match OpenOptions::new().read(true).write(true).open(input_path) {
+            Ok(file) => file,
+            Err(err) => return Err(format!("Error opening path {}: {}", input_path, err)),
+        };
+        return match rstdms::finalize_in_place(&mut file) {
+            Ok(FinalizeResult::AlreadyComplete) => {
+                println!("File is already complete, no changes made");
+                Ok(0)
+            }
+            Ok(FinalizeResult::Finalized {
+                segment_position,
+                next_segment_offset,
+            }) => {
+                println!(
+                    "Set next segment offset of segment at position {} to {}",
+                    segment_position, next_segment_offset
+                );
+                Ok(0)
+            }
+            Err(err) => Err(format!("Error finalizing {}: {}", input_path, err)),
+        };
+    }
+
+    let output_path = matches.value_of("output").unwrap();
+    check_output_is_not_input(output_path, &[input_path])?;
+    let input = match File::open(input_path) {
+        Ok(file) => file,
+        Err(err) => return Err(format!("Error opening path {}: {}", input_path, err)),
+    };
+    let output = match File::create(output_path) {
+        Ok(file) => BufWriter::new(file),
+        Err(err) => return Err(format!("Error creating path {}: {}", output_path, err)),
+    };
+    let report = match rstdms::repair(input, output) {
+        Ok(report) => report,
+        Err(err) => return Err(format!("Error repairing {}: {}", input_path, err)),
+    };
+
+    for skipped in report.skipped_segments.iter() {
+        match skipped.resume_position {
+            Some(resume_position) => println!(
+                "Skipped segment at position {} (resumed at {}): {}",
+                skipped.position, resume_position, skipped.error
+            ),
+            None => println!(
+                "Skipped data from position {} to end of file: {}",
+                skipped.position, skipped.error
+            ),
+        }
+    }
+    for (group, channel, values) in report.truncated_channels.iter() {
+        println!(
+            "Dropped {} truncated value(s) from {} / {}",
+            values, group, channel
+        );
+    }
+    for (group, channel, data_type) in report.unsupported_channels.iter() {
+        println!(
+            "Could not copy data for {} / {} with unsupported type {:?}",
+            group, channel, data_type
+        );
+    }
+    let total_values: u64 = report.copied_channels.iter().map(|(_, _, n)| n).sum();
+    println!(
+        "Copied {} value(s) in {} channel(s) to {}",
+        total_values,
+        report.copied_channels.len(),
+        output_path
+    );
+
+    Ok(0)
+}
+
+fn merge(matches: &ArgMatches) -> Result<i32, String> {
+    let inputs: Vec<PathBuf> = matches
+        .values_of("inputs")
+        .unwrap()
+        .map(PathBuf::from)
+        .collect();
+    let output_path = matches.value_of("output").unwrap();
+
+    let options = MergeOptions {
+        compatibility: if matches.is_present("union") {
+            MergeCompatibility::Union
+        } else {
+            MergeCompatibility::Strict
+        },
+        property_precedence: if matches.is_present("last-properties") {
+            PropertyPrecedence::Last
+        } else {
+            PropertyPrecedence::First
+        },
+    };
+
+    check_output_is_not_input(output_path, &inputs)?;
+    let output = match File::create(output_path) {
+        Ok(file) => BufWriter::new(file),
+        Err(err) => return Err(format!("Error creating path {}: {}", output_path, err)),
+    };
+    if let Err(err) = rstdms::merge(&inputs, output, &options) {
+        return Err(format!("Error merging files: {}", err));
+    }
+    println!("Merged {} file(s) into {}", inputs.len(), output_path);
+
+    Ok(0)
+}
+
+fn stats(matches: &ArgMatches) -> Result<i32, String> {
+    let tdms_file = open_file(matches.value_of("path").unwrap())?;
+    let pattern = matches.value_of("channel");
+    let json = matches.is_present("json");
+    let options = StatisticsOptions::default();
+
+    if json {
+        println!("[");
+    } else {
+        println!(
+            "{:<40} {:>12} {:>14} {:>14} {:>14} {:>14} {:>8}",
+            "Channel", "Count", "Min", "Max", "Mean", "Std dev", "NaN"
+        );
+    }
+    let mut channel_paths = Vec::new();
+    let mut channels = Vec::new();
+    for group in tdms_file.groups() {
+        for channel in group.channels() {
+            let channel_path = format!("{}/{}", group.name(), channel.name());
+            if pattern.is_none_or(|pattern| wildcard_match(pattern, &channel_path)) {
+                channel_paths.push(channel_path);
+                channels.push(channel);
+            }
+        }
+    }
+    // Statistics of all channels are computed in a single pass through the file
+    let channel_statistics = rstdms::channels_statistics(&channels, &options)
+        .map_err(|err| format!("Error computing statistics: {}", err))?;
+    let mut first = true;
+    for (channel_path, statistics) in channel_paths.iter().zip(channel_statistics.iter()) {
+        if json {
+            if !first {
+                println!(",");
+            }
+            print!("  {}", statistics_json(channel_path, statistics));
+        } else {
+            println!("{}", statistics_row(channel_path, statistics));
+        }
+        first = false;
+    }
+    if json {
+        if !first {
+            println!();
+        }
+        println!("]");
+    }
+    if matches.is_present("fragmentation") {
+        match tdms_file.fragmentation_report() {
+            Ok(report) => println!("\n{}", report),
+            Err(err) => return Err(format!("Error computing fragmentation: {}", err)),
+        }
+    }
+
+    Ok(0)
+}
+
+fn summary(matches: &ArgMatches) -> Result<i32, String> {
+    let path = matches.value_of("path").unwrap();
+    let error_policy = if matches.is_present("skip-errors") {
+        ErrorPolicy::Skip
+    } else {
+        ErrorPolicy::Fail
+    };
+    let options = ReadOptions::new().error_policy(error_policy);
+    let tdms_file = match TdmsFile::open_with_options(path, &options) {
+        Ok(tdms_file) => tdms_file,
+        Err(err) => return Err(format!("Error reading TDMS file {}", err)),
+    };
+    let summary = match tdms_file.summary() {
+        Ok(summary) => summary,
+        Err(err) => return Err(format!("Error summarizing {}: {}", path, err)),
+    };
+
+    println!("Path: {}", path);
+    print!("{}", summary);
+    let properties = tdms_file.properties();
+    if !properties.is_empty() {
+        println!("Properties:");
+        for property in properties {
+            println!("  {}", property);
+        }
+    }
+
+    Ok(0)
+}
+
+/// The outcome of indexing or checking the index of one file
+enum IndexOutcome {
+    Indexed(usize),
+    UpToDate,
+    Checked(IndexStatus),
+    Failed(String),
+}
+
+fn index(matches: &ArgMatches) -> Result<i32, String> {
+    let check = matches.is_present("check");
+    let jobs = match matches.value_of("jobs") {
+        Some(jobs) => match jobs.parse::<usize>() {
+            Ok(jobs) if jobs > 0 => jobs,
+            _ => return Err(format!("Invalid number of jobs: {}", jobs)),
+        },
+        None => std::thread::available_parallelism().map_or(1, |jobs| jobs.get()),
+    };
+    let mut paths = Vec::new();
+    for path in matches.values_of("paths").unwrap() {
+        let path = Path::new(path);
+        if path.is_dir() {
+            find_tdms_files(path, matches.is_present("recursive"), &mut paths)
+                .map_err(|err| format!("Error listing {}: {}", path.display(), err))?;
+        } else {
+            paths.push(path.to_path_buf());
+        }
+    }
+
+    // Workers take the next file to process, and results are printed as they arrive
+    let start = Instant::now();
+    let next_path = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let (mut indexed, mut current, mut not_current, mut failed) = (0, 0, 0, 0);
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(paths.len()) {
+            let sender = sender.clone();
+            let (paths, next_path) = (&paths, &next_path);
+            scope.spawn(move || {
+                while let Some(path) = paths.get(next_path.fetch_add(1, Ordering::Relaxed)) {
+                    let file_start = Instant::now();
+                    let outcome = index_file(path, check);
+                    if sender.send((path, outcome, file_start.elapsed())).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        for (path, outcome, elapsed) in receiver {
+            let milliseconds = elapsed.as_secs_f64() * 1000.0;
+            match outcome {
+                IndexOutcome::Indexed(segments) => {
+                    indexed += 1;
+                    println!(
+                        "{}: indexed {} segment(s) in {:.1} ms",
+                        path.display(),
+                        segments,
+                        milliseconds
+                    );
+                }
+                IndexOutcome::UpToDate => {
+                    current += 1;
+                    println!("{}: up to date", path.display());
+                }
+                IndexOutcome::Checked(status) => {
+                    if status == IndexStatus::Current {
+                        current += 1;
+                    } else {
+                        not_current += 1;
+                    }
+                    println!("{}: {}", path.display(), status);
+                }
+                IndexOutcome::Failed(err) => {
+                    failed += 1;
+                    eprintln!("{}: error: {}", path.display(), err);
+                }
+            }
+        }
+    });
+
+    let seconds = start.elapsed().as_secs_f64();
+    if check {
+        println!(
+            "{} current, {} missing or stale, {} failed, in {:.2} s",
+            current, not_current, failed, seconds
+        );
+        Ok(if not_current + failed > 0 { 1 } else { 0 })
+    } else {
+        println!(
+            "{} indexed, {} up to date, {} failed, in {:.2} s",
+            indexed, current, failed, seconds
+        );
+        Ok(if failed > 0 { 1 } else { 0 })
+    }
+}
+
+/// Check a file's index, and write it if it isn't current unless only checking
+fn index_file(path: &Path, check: bool) -> IndexOutcome {
+    let status = match rstdms::check_index(path) {
+        Ok(status) => status,
+        Err(err) => return IndexOutcome::Failed(err.to_string()),
+    };
+    if check {
+        return IndexOutcome::Checked(status);
+    }
+    if status == IndexStatus::Current {
+        return IndexOutcome::UpToDate;
+    }
+    match rstdms::write_index(path) {
+        Ok(segments) => IndexOutcome::Indexed(segments),
+        Err(err) => IndexOutcome::Failed(err.to_string()),
+    }
+}
+
+/// Add the .tdms files in a directory to `paths` in name order, including those in
+/// subdirectories if `recursive` is set
+fn find_tdms_files(
+    directory: &Path,
+    recursive: bool,
+    paths: &mut Vec<PathBuf>,
+) -> std::io::Result<()> {
+    let mut entries = std::fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<PathBuf>>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            if recursive {
+                find_tdms_files(&path, recursive, paths)?;
+            }
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("tdms"))
+        {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn statistics_row(channel_path: &str, statistics: &ChannelStatistics) -> String {
+    let format_value = |value: Option<f64>| match value {
+        Some(value) => format!("{:>14.6}", value),
+        None => format!("{:>14}", "-"),
+    };
+    match statistics {
+        ChannelStatistics::NoData => format!("{:<40} {:>12}", channel_path, 0),
+        ChannelStatistics::Numeric(stats) => format!(
+            "{:<40} {:>12} {} {} {} {} {:>8}",
+            channel_path,
+            stats.count(),
+            format_value(stats.min()),
+            format_value(stats.max()),
+            format_value(stats.mean()),
+            format_value(stats.std_dev()),
+            stats.nan_count()
+        ),
+        ChannelStatistics::String(stats) => format!(
+            "{:<40} {:>12} distinct: {}{}",
+            channel_path,
+            stats.count(),
+            stats.distinct_count(),
+            if stats.distinct_limit_reached() {
+                "+"
+            } else {
+                ""
+            }
+        ),
+        ChannelStatistics::Timestamp(stats) => format!(
+            "{:<40} {:>12} first: {} last: {}",
+            channel_path,
+            stats.count(),
+            format_timestamp(stats.first()),
+            format_timestamp(stats.last())
+        ),
+        ChannelStatistics::Unsupported(data_type) => {
+            format!("{:<40} unsupported data type {}", channel_path, data_type)
+        }
+    }
+}
+
+fn statistics_json(channel_path: &str, statistics: &ChannelStatistics) -> String {
+    let json_number = |value: Option<f64>| match value {
+        Some(value) if value.is_finite() => format!("{}", value),
+        _ => String::from("null"),
+    };
+    let fields = match statistics {
+        ChannelStatistics::NoData => String::from("\"count\": 0"),
+        ChannelStatistics::Numeric(stats) => format!(
+            "\"count\": {}, \"min\": {}, \"max\": {}, \"mean\": {}, \"std_dev\": {}, \"nan_count\": {}",
+            stats.count(),
+            json_number(stats.min()),
+            json_number(stats.max()),
+            json_number(stats.mean()),
+            json_number(stats.std_dev()),
+            stats.nan_count()
+        ),
+        ChannelStatistics::String(stats) => format!(
+            "\"count\": {}, \"distinct_count\": {}, \"distinct_limit_reached\": {}",
+            stats.count(),
+            stats.distinct_count(),
+            stats.distinct_limit_reached()
+        ),
+        ChannelStatistics::Timestamp(stats) => format!(
+            "\"count\": {}, \"first\": {}, \"last\": {}",
+            stats.count(),
+            json_timestamp(stats.first()),
+            json_timestamp(stats.last())
+        ),
+        ChannelStatistics::Unsupported(data_type) => format!(
+            "\"unsupported_type\": {}",
+            json_string(&format!("{:?}", data_type))
+        ),
+    };
+    format!("{{\"channel\": {}, {}}}", json_string(channel_path), fields)
+}
+
+fn cat(matches: &ArgMatches) -> Result<i32, String> {
+    let tdms_file = open_file(matches.value_of("path").unwrap())?;
+    let channel_path = matches.value_of("channel").unwrap();
+    let (group_name, channel_name) = find_channel(&tdms_file, channel_path)?;
+    let group = tdms_file.group(&group_name).unwrap();
+    let channel = group.channel(&channel_name).unwrap();
+
+    let length = channel.len();
+    let (start, end) = if let Some(head) = matches.value_of("head") {
+        (0, parse_count(head, "head")?.min(length))
+    } else if let Some(tail) = matches.value_of("tail") {
+        (length.saturating_sub(parse_count(tail, "tail")?), length)
+    } else if let Some(range) = matches.value_of("range") {
+        parse_range(range, length)?
+    } else {
+        (0, length)
+    };
+
+    let timing = if matches.is_present("paired-time") {
+        match channel.waveform_timing() {
+            Some(timing) => Some(timing),
+            None => {
+                return Err(format!(
+                    "Channel {} does not have waveform timing properties",
+                    channel_path
+                ))
+            }
+        }
+    } else {
+        None
+    };
+
+    let prefix = LinePrefix {
+        timing,
+        offsets: matches.is_present("offsets"),
+    };
+
+    let data_type = match channel.data_type() {
+        Some(data_type) => data_type,
+        None => return Ok(0),
+    };
+    let stdout = std::io::stdout();
+    let mut output = BufWriter::new(stdout.lock());
+    let result = match data_type {
+        TdsType::I8 => {
+            print_values::<i8, _, _>(&channel, start, end, prefix, &mut output, |v| v.to_string())
+        }
+        TdsType::I16 => {
+            print_values::<i16, _, _>(&channel, start, end, prefix, &mut output, |v| v.to_string())
+        }
+        TdsType::I32 => {
+            print_values::<i32, _, _>(&channel, start, end, prefix, &mut output, |v| v.to_string())
+        }
+        TdsType::I64 => {
+            print_values::<i64, _, _>(&channel, start, end, prefix, &mut output, |v| v.to_string())
+        }
+        TdsType::U8 => {
+            print_values::<u8, _, _>(&channel, start, end, prefix, &mut output, |v| v.to_string())
+        }
+        TdsType::U16 => {
+            print_values::<u16, _, _>(&channel, start, end, prefix, &mut output, |v| v.to_string())
+        }
+        TdsType::U32 => {
+            print_values::<u32, _, _>(&channel, start, end, prefix, &mut output, |v| v.to_string())
+        }
+        TdsType::U64 => {
+            print_values::<u64, _, _>(&channel, start, end, prefix, &mut output, |v| v.to_string())
+        }
+        TdsType::SingleFloat | TdsType::SingleFloatWithUnit => {
+            print_values::<f32, _, _>(&channel, start, end, prefix, &mut output, |v| v.to_string())
+        }
+        TdsType::DoubleFloat | TdsType::DoubleFloatWithUnit => {
+            print_values::<f64, _, _>(&channel, start, end, prefix, &mut output, |v| v.to_string())
+        }
+        TdsType::TimeStamp => {
+            print_values::<Timestamp, _, _>(&channel, start, end, prefix, &mut output, |v| {
+                format_timestamp(Some(*v))
+            })
+        }
+        TdsType::String => print_strings(&channel, start, end, prefix, &mut output),
+        data_type => {
+            return Err(format!(
+                "Reading data of type {:?} is not supported",
+                data_type
+            ))
+        }
+    };
+    match result.and_then(|()| output.flush().map_err(|err| err.to_string())) {
+        Ok(()) => Ok(0),
+        Err(err) => Err(format!("Error reading channel {}: {}", channel_path, err)),
+    }
+}
+
+const CAT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Channels selected by the "where" and "channel" arguments
+fn selected_channels<'a>(
+    tdms_file: &'a TdmsFile<File>,
+    matches: &ArgMatches,
+) -> Result<Vec<Channel<'a, File>>, String> {
+    let filter = match matches.value_of("where") {
+        Some(expression) => Some(PropertyFilter::parse(expression).map_err(|err| err.to_string())?),
+        None => None,
+    };
+    let pattern = matches.value_of("channel");
+    let channels = tdms_file.find_channels_by(|channel| {
+        let channel_path = format!("{}/{}", channel.group_name(), channel.name());
+        pattern.is_none_or(|pattern| wildcard_match(pattern, &channel_path))
+            && filter
+                .as_ref()
+                .is_none_or(|filter| filter.matches(channel.properties()))
+    });
+    if channels.is_empty() {
+        return Err(String::from("No channels match the selection"));
+    }
+    Ok(channels)
+}
+
+fn export(matches: &ArgMatches) -> Result<i32, String> {
+    let tdms_file = open_file(matches.value_of("path").unwrap())?;
+    let channels = selected_channels(&tdms_file, matches)?;
+    if matches.value_of("format") == Some("line-protocol") {
+        return export_line_protocol(&channels, matches);
+    }
+    let options = CsvExportOptions {
+        time_column: match matches.value_of("time") {
+            Some("relative") => CsvTimeColumn::Relative,
+            Some("absolute") => CsvTimeColumn::Absolute,
+            _ => CsvTimeColumn::None,
+        },
+        ..CsvExportOptions::default()
+    };
+    let result = match matches.value_of("output") {
+        Some(output_path) => {
+            let output = File::create(output_path)
+                .map_err(|err| format!("Error creating {}: {}", output_path, err))?;
+            rstdms::export_csv(&channels, BufWriter::new(output), &options)
+        }
+        None => {
+            let stdout = std::io::stdout();
+            let output = BufWriter::new(stdout.lock());
+            rstdms::export_csv(&channels, output, &options)
+        }
+    };
+    result.map_err(|err| format!("Error exporting channels: {}", err))?;
+    Ok(0)
+}
+
+fn export_line_protocol(channels: &[Channel<File>], matches: &ArgMatches) -> Result<i32, String> {
+    let options = LineProtocolOptions {
+        measurement: matches.value_of("measurement").map(String::from),
+        ..LineProtocolOptions::default()
+    };
+    let result = match matches.value_of("output") {
+        Some(output_path) => {
+            let output = File::create(output_path)
+                .map_err(|err| format!("Error creating {}: {}", output_path, err))?;
+            rstdms::export_line_protocol(channels, BufWriter::new(output), &options)
+        }
+        None => {
+            let stdout = std::io::stdout();
+            let output = BufWriter::new(stdout.lock());
+            rstdms::export_line_protocol(channels, output, &options)
+        }
+    };
+    result.map_err(|err| format!("Error exporting channels: {}", err))?;
+    Ok(0)
+}
+
+fn export_raw(matches: &ArgMatches) -> Result<i32, String> {
+    let tdms_file = open_file(matches.value_of("path").unwrap())?;
+    let channels = selected_channels(&tdms_file, matches)?;
+    let channel_paths: Vec<&str> = channels.iter().map(|channel| channel.path()).collect();
+    let options = RawExportOptions {
+        native: matches.is_present("native"),
+        ..RawExportOptions::default()
+    };
+    let output_dir = Path::new(matches.value_of("output-dir").unwrap());
+    let files = tdms_file
+        .export_raw_split(output_dir, &channel_paths, &options)
+        .map_err(|err| format!("Error exporting channels: {}", err))?;
+    for file in files.iter() {
+        println!(
+            "{} -> {} ({} x {})",
+            file.channel_path,
+            file.data_path.display(),
+            file.count,
+            file.data_type
+        );
+    }
+    Ok(0)
+}
+
+fn import(matches: &ArgMatches) -> Result<i32, String> {
+    let input_path = matches.value_of("input").unwrap();
+    let output_path = matches.value_of("output").unwrap();
+    let delimiter = matches.value_of("delimiter").unwrap();
+    let mut delimiter_chars = delimiter.chars();
+    let delimiter = match (delimiter_chars.next(), delimiter_chars.next()) {
+        (Some(delimiter), None) => delimiter,
+        _ => {
+            return Err(format!(
+                "Invalid value for --delimiter: {}, expected a single character",
+                delimiter
+            ))
+        }
+    };
+    let mut options = ImportOptions {
+        group_name: matches.value_of("group").unwrap().to_string(),
+        parse_units: matches.is_present("units"),
+        time_column: matches.value_of("time-column").map(String::from),
+        delimiter,
+        malformed_rows: if matches.is_present("skip-malformed") {
+            ErrorPolicy::Skip
+        } else {
+            ErrorPolicy::Fail
+        },
+        ..ImportOptions::default()
+    };
+    if let Some(rows) = matches.value_of("rows-per-segment") {
+        options.rows_per_segment = parse_count(rows, "rows-per-segment")? as usize;
+    }
+
+    let input = match File::open(input_path) {
+        Ok(file) => file,
+        Err(err) => return Err(format!("Error opening path {}: {}", input_path, err)),
+    };
+    let output = match File::create(output_path) {
+        Ok(file) => BufWriter::new(file),
+        Err(err) => return Err(format!("Error creating path {}: {}", output_path, err)),
+    };
+    let report = match rstdms::import_csv(input, output, &options) {
+        Ok(report) => report,
+        Err(err) => return Err(format!("Error importing {}: {}", input_path, err)),
+    };
+
+    for skipped in report.skipped_rows.iter() {
+        println!("Skipped row at line {}: {}", skipped.line, skipped.error);
+    }
+    if report.waveform_timing {
+        println!("Wrote the time column as waveform timing");
+    }
+    println!(
+        "Imported {} row(s) in {} channel(s) to {}",
+        report.rows_imported,
+        report.channels.len(),
+        output_path
+    );
+
+    Ok(0)
+}
+
+/// Columns printed by `tdms cat` before each value
+#[derive(Clone, Copy)]
+struct LinePrefix {
+    timing: Option<WaveformTiming>,
+    offsets: bool,
+}
+
+fn print_values<T, R, F>(
+    channel: &Channel<R>,
+    start: u64,
+    end: u64,
+    prefix: LinePrefix,
+    output: &mut impl Write,
+    format_value: F,
+) -> Result<(), String>
+where
+    T: NativeType + Clone + Default,
+    R: Read + Seek,
+    F: Fn(&T) -> String,
+{
+    let mut buffer = vec![T::default(); CAT_CHUNK_SIZE].into_boxed_slice();
+    let mut offset = start;
+    while offset < end {
+        let count = (end - offset).min(CAT_CHUNK_SIZE as u64) as usize;
+        let values_read = channel
+            .read_data(offset, &mut buffer[..count])
+            .map_err(|err| err.to_string())?;
+        if values_read == 0 {
+            break;
+        }
+        for (i, value) in buffer[..values_read].iter().enumerate() {
+            write_value(
+                output,
+                channel,
+                prefix,
+                offset + i as u64,
+                &format_value(value),
+            )?;
+        }
+        offset += values_read as u64;
+    }
+    Ok(())
+}
+
+fn print_strings<R: Read + Seek>(
+    channel: &Channel<R>,
+    start: u64,
+    end: u64,
+    prefix: LinePrefix,
+    output: &mut impl Write,
+) -> Result<(), String> {
+    let mut buffer = vec![String::new(); CAT_CHUNK_SIZE].into_boxed_slice();
+    let mut offset = start;
+    while offset < end {
+        let count = (end - offset).min(CAT_CHUNK_SIZE as u64) as usize;
+        let values_read = channel
+            .read_strings(offset, &mut buffer[..count])
+            .map_err(|err| err.to_string())?;
+        if values_read == 0 {
+            break;
+        }
+        for (i, value) in buffer[..values_read].iter().enumerate() {
+            write_value(
+                output,
+                channel,
+                prefix,
+                offset + i as u64,
+                &json_string(value),
+            )?;
+        }
+        offset += values_read as u64;
+    }
+    Ok(())
+}
+
+fn write_value<R: Read + Seek>(
+    output: &mut impl Write,
+    channel: &Channel<R>,
+    prefix: LinePrefix,
+    index: u64,
+    value: &str,
+) -> Result<(), String> {
+    let location = if prefix.offsets {
+        channel.locate_value(index).map_err(|err| err.to_string())?
+    } else {
+        None
+    };
+    write_line(output, prefix, location, index, value).map_err(|err| err.to_string())
+}
+
+/// Write a value with the prefixes selected, where `location` is the segment index and
+/// byte offset of the value if it could be found
+fn write_line(
+    output: &mut impl Write,
+    prefix: LinePrefix,
+    location: Option<(usize, u64)>,
+    index: u64,
+    value: &str,
+) -> std::io::Result<()> {
+    if prefix.offsets {
+        match location {
+            Some((segment_index, byte_offset)) => {
+                write!(output, "{}\t{}\t", segment_index, byte_offset)?
+            }
+            None => write!(output, "\t\t")?,
+        }
+    }
+    match prefix.timing {
+        Some(timing) => match timing.absolute_time(index) {
+            Some(time) => writeln!(output, "{}\t{}", time.to_rfc3339(), value),
+            None => writeln!(output, "{}\t{}", timing.relative_time(index), value),
+        },
+        None => writeln!(output, "{}", value),
+    }
+}
+
+/// Find a channel from a "group/channel" path, returning the group and channel names.
+/// Group names may themselves contain slashes, so each group is checked as a prefix.
+fn find_channel<R: Read + Seek>(
+    tdms_file: &TdmsFile<R>,
+    channel_path: &str,
+) -> Result<(String, String), String> {
+    let mut channel_paths = Vec::new();
+    for group in tdms_file.groups() {
+        let prefix = format!("{}/", group.name());
+        if let Some(channel_name) = channel_path.strip_prefix(&prefix) {
+            if group.channel(channel_name).is_some() {
+                return Ok((group.name().to_string(), channel_name.to_string()));
+            }
+        }
+        for channel in group.channels() {
+            channel_paths.push(format!("{}{}", prefix, channel.name()));
+        }
+    }
+    let suggestions = similar_names(channel_path, channel_paths.iter().map(String::as_str));
+    Err(format!(
+        "Channel {:?} not found{}",
+        channel_path,
+        format_suggestions(&suggestions)
+    ))
+}
+
+fn parse_count(value: &str, name: &str) -> Result<u64, String> {
+    value
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid value for --{}: {}", name, value))
+}
+
+/// Parse a range in the form "A..B", with either bound optional,
+/// and clamp it to the channel length
+fn parse_range(value: &str, length: u64) -> Result<(u64, u64), String> {
+    let invalid = || format!("Invalid value for --range: {}, expected A..B", value);
+    let separator = value.find("..").ok_or_else(invalid)?;
+    let parse_bound = |bound: &str, default: u64| {
+        if bound.is_empty() {
+            Ok(default)
+        } else {
+            bound.parse::<u64>().map_err(|_| invalid())
+        }
+    };
+    let start = parse_bound(&value[..separator], 0)?;
+    let end = parse_bound(&value[separator + 2..], length)?;
+    if start > end {
+        return Err(invalid());
+    }
+    Ok((start.min(length), end.min(length)))
+}
+
+fn format_timestamp(timestamp: Option<Timestamp>) -> String {
+    match timestamp.and_then(|timestamp| timestamp.to_datetime()) {
+        Some(datetime) => datetime.to_rfc3339(),
+        None => String::from("-"),
+    }
+}
+
+fn json_timestamp(timestamp: Option<Timestamp>) -> String {
+    match timestamp.and_then(|timestamp| timestamp.to_datetime()) {
+        Some(datetime) => json_string(&datetime.to_rfc3339()),
+        None => String::from("null"),
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Match text against a pattern where `*` matches any sequence of characters
+/// and `?` matches any single character
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn open_file(path: &str) -> Result<TdmsFile<File>, String> {
+    TdmsFile::open(path).map_err(|err| format!("Error reading TDMS file {}", err))
+}
+
+/// Check that an output path doesn't name one of the input files, which would be
+/// emptied when the output is created, before the input is read
+fn check_output_is_not_input<P: AsRef<Path>>(
+    output_path: &str,
+    input_paths: &[P],
+) -> Result<(), String> {
+    // An output that doesn't exist yet can't be an input
+    let output = match std::fs::canonicalize(output_path) {
+        Ok(output) => output,
+        Err(_) => return Ok(()),
+    };
+    for input_path in input_paths {
+        if std::fs::canonicalize(input_path).is_ok_and(|input| input == output) {
+            return Err(format!(
+                "Output path {} is also an input, write the output to a different path",
+                output_path
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn parse_float_arg(matches: &ArgMatches, name: &str) -> Result<Option<f64>, String> {
+    match matches.value_of(name) {
+        Some(value) => match value.parse::<f64>() {
+            Ok(value) => Ok(Some(value)),
+            Err(_) => Err(format!("Invalid value for --{}: {}", name, value)),
+        },
+        None => Ok(None),
+    }
+}
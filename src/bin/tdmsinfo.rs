@@ -2,7 +2,6 @@ extern crate clap;
 
 use clap::{App, Arg};
 use rstdms::TdmsFile;
-use std::fs::File;
 
 fn main() {
     match main_impl() {
@@ -24,19 +23,23 @@ fn main_impl() -> Result<(), String> {
                 .required(true)
                 .index(1),
         )
+        .arg(
+            Arg::with_name("segments")
+                .long("segments")
+                .help("Print each segment's position, size and table of contents flags"),
+        )
+        .arg(
+            Arg::with_name("fragmentation")
+                .long("fragmentation")
+                .help("Print how channel data is divided between segments"),
+        )
         .get_matches();
 
     let path = matches.value_of("path").unwrap();
-    let file = match File::open(path) {
-        Ok(file) => file,
-        Err(err) => {
-            return Err(format!("Error opening path {}: {}", path, err));
-        }
-    };
-    let tdms_file = match TdmsFile::new(file) {
+    let tdms_file = match TdmsFile::open(path) {
         Ok(tdms_file) => tdms_file,
         Err(err) => {
-            return Err(format!("Error reading TDMS file {}: {}", path, err));
+            return Err(format!("Error reading TDMS file {}", err));
         }
     };
 
@@ -46,6 +49,47 @@ fn main_impl() -> Result<(), String> {
             println!("{} / {}", group.name(), channel.name());
         }
     }
+    if matches.is_present("segments") {
+        let segments = match tdms_file.segments() {
+            Ok(segments) => segments,
+            Err(err) => return Err(format!("Error describing segments: {}", err)),
+        };
+        for (index, segment) in segments.iter().enumerate() {
+            let mut flags = segment.toc_flag_names().join(" ");
+            let unknown_bits = segment.toc_mask().unknown_bits();
+            if unknown_bits != 0 {
+                flags.push_str(&format!(" {:#x}", unknown_bits));
+            }
+            println!(
+                "Segment {} at {}: {} bytes of data, {} object(s), flags: {}",
+                index,
+                segment.position,
+                segment.data_length,
+                segment.objects.len(),
+                flags
+            );
+        }
+        for group in tdms_file.groups() {
+            for channel in group.channels() {
+                let info = channel.info();
+                if info.interleaved.is_mixed() {
+                    println!("{} has both interleaved and contiguous data", info.path);
+                }
+                if info.big_endian.is_mixed() {
+                    println!("{} has both big and little endian data", info.path);
+                }
+            }
+        }
+    }
+    if matches.is_present("fragmentation") {
+        match tdms_file.fragmentation_report() {
+            Ok(report) => println!("{}", report),
+            Err(err) => return Err(format!("Error computing fragmentation: {}", err)),
+        }
+    }
+    for warning in tdms_file.warnings() {
+        println!("Warning: {}", warning);
+    }
 
     Ok(())
 }
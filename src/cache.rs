@@ -0,0 +1,235 @@
+use crate::daqmx::DaqmxScaler;
+use crate::error::{Result, TdmsReadError};
+use crate::fingerprint::{fnv1a_hash, HASHED_PREFIX_LENGTH};
+use crate::options::{ErrorPolicy, ReadOptions};
+use crate::overview::Overview;
+use crate::properties::TdmsValue;
+use crate::tdms_reader::{SkippedSegment, TdmsReader, TdmsWarning};
+use bincode::Options;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Version of the cache file layout, to be incremented whenever the cached structures change
+const CACHE_FORMAT_VERSION: u32 = 6;
+
+const CACHE_EXTENSION: &str = "rstdms-cache";
+
+/// Identifies the file and read options that a cache was created from. A cache is only
+/// used if its key matches the key computed for the file being opened.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct CacheKey {
+    format_version: u32,
+    crate_version: String,
+    file_length: u64,
+    /// Modification time of the file as seconds and nanoseconds since the Unix epoch
+    modified: Option<(u64, u32)>,
+    prefix_hash: u64,
+    skip_errors: bool,
+    strict_toc_flags: bool,
+    /// Codes and sizes of registered custom data types
+    custom_types: Vec<(u32, usize)>,
+}
+
+/// Names and values of an object's properties
+pub type CachedProperties = Vec<(String, TdmsValue)>;
+
+/// The metadata of a file in a form that can be serialized. Object ids, object lists and
+/// segments are stored by their index. Channel data indexes aren't stored as they can be
+/// rebuilt from the segments without reading the file.
+#[derive(Serialize, Deserialize)]
+pub struct CachedMetadata {
+    /// Object paths as written in the file, in order of object id
+    pub object_paths: Vec<String>,
+    /// Property names and values of each object, in order of object id
+    pub properties: Vec<CachedProperties>,
+    /// Segment index and waveform timing properties of each update to an object's
+    /// waveform timing, in order of object id
+    pub waveform_property_updates: Vec<Vec<(usize, CachedProperties)>>,
+    pub object_lists: Vec<Vec<CachedSegmentObject>>,
+    pub segments: Vec<CachedSegment>,
+    /// Most recent raw data index of each object, in order of object id
+    pub raw_data_indexes: Vec<Option<CachedRawDataIndex>>,
+    /// Number of raw data indexes written in full for each object, in order of object id
+    pub new_raw_data_index_counts: Vec<u64>,
+    pub skipped_segments: Vec<SkippedSegment>,
+    pub warnings: Vec<TdmsWarning>,
+    pub read_position: u64,
+}
+
+/// Object id of each numeric channel with the segment index, minimum and maximum of
+/// each segment in which it has values
+pub type CachedOverview = Vec<(usize, Vec<(usize, f64, f64)>)>;
+
+#[derive(Serialize, Deserialize)]
+pub struct CachedSegment {
+    pub position: u64,
+    pub next_segment_position: u64,
+    pub data_position: u64,
+    pub version: u32,
+    pub toc_flags: u32,
+    pub incomplete: bool,
+    pub object_list: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CachedSegmentObject {
+    pub object_id: usize,
+    pub raw_data_index: Option<CachedRawDataIndex>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct CachedRawDataIndex {
+    pub number_of_values: u64,
+    pub data_type: u32,
+    pub data_size: u64,
+    pub daqmx: Option<DaqmxScaler>,
+}
+
+/// The cache of a file's metadata, and its overview if the file was read with one.
+/// Problems with the cache are never errors, the file is parsed instead.
+pub struct MetadataCache {
+    cache_path: PathBuf,
+    key: CacheKey,
+}
+
+impl MetadataCache {
+    /// Find the cache in `cache_dir` for the file at `path` read with `options`
+    pub fn new(
+        path: &Path,
+        cache_dir: &Path,
+        file: &mut BufReader<File>,
+        options: &ReadOptions,
+    ) -> Result<MetadataCache> {
+        Ok(MetadataCache {
+            cache_path: cache_path(path, cache_dir),
+            key: cache_key(file, options)?,
+        })
+    }
+
+    /// Load the metadata and any overview from the cache, or None if there is no up to
+    /// date cache for the file
+    pub fn load(&self, options: &ReadOptions) -> Option<(TdmsReader, Option<Overview>)> {
+        match load_cache(&self.cache_path, &self.key, options) {
+            Ok(cached) => {
+                debug!("Read metadata from cache {}", self.cache_path.display());
+                Some(cached)
+            }
+            Err(e) => {
+                debug!(
+                    "Not using metadata cache {}: {}",
+                    self.cache_path.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Write the metadata and overview of the file to the cache, replacing any
+    /// existing cache
+    pub fn save(&self, tdms_reader: &TdmsReader, overview: Option<&Overview>) {
+        if let Err(e) = save_cache(&self.cache_path, &self.key, tdms_reader, overview) {
+            debug!(
+                "Could not write metadata cache {}: {}",
+                self.cache_path.display(),
+                e
+            );
+        }
+    }
+}
+
+fn cache_key(file: &mut BufReader<File>, options: &ReadOptions) -> Result<CacheKey> {
+    let file_metadata = file.get_ref().metadata()?;
+    let modified = file_metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| (duration.as_secs(), duration.subsec_nanos()));
+    let mut prefix = Vec::new();
+    file.seek(SeekFrom::Start(0))?;
+    file.by_ref()
+        .take(HASHED_PREFIX_LENGTH)
+        .read_to_end(&mut prefix)?;
+    Ok(CacheKey {
+        format_version: CACHE_FORMAT_VERSION,
+        crate_version: String::from(env!("CARGO_PKG_VERSION")),
+        file_length: file_metadata.len(),
+        modified,
+        prefix_hash: fnv1a_hash(&prefix),
+        skip_errors: options.get_error_policy() == ErrorPolicy::Skip,
+        strict_toc_flags: options.get_strict_toc_flags(),
+        custom_types: options.get_custom_types().sizes(),
+    })
+}
+
+/// Path of the cache for a file, named from a hash of the file's absolute path
+fn cache_path(path: &Path, cache_dir: &Path) -> PathBuf {
+    let absolute_path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let path_hash = fnv1a_hash(absolute_path.to_string_lossy().as_bytes());
+    cache_dir.join(format!("{:016x}.{}", path_hash, CACHE_EXTENSION))
+}
+
+fn load_cache(
+    cache_path: &Path,
+    key: &CacheKey,
+    read_options: &ReadOptions,
+) -> Result<(TdmsReader, Option<Overview>)> {
+    let cache_file = File::open(cache_path)?;
+    let cache_length = cache_file.metadata()?.len();
+    // Limit allocations so that a corrupt length in the cache can't exhaust memory
+    let options = bincode::options().with_limit(cache_length);
+    let mut reader = BufReader::new(cache_file);
+    let cached_key: CacheKey = options.deserialize_from(&mut reader).map_err(cache_error)?;
+    if cached_key != *key {
+        return Err(TdmsReadError::InvalidCache(String::from(
+            "the cache is out of date",
+        )));
+    }
+    let cached_metadata: CachedMetadata =
+        options.deserialize_from(&mut reader).map_err(cache_error)?;
+    let cached_overview: Option<CachedOverview> =
+        options.deserialize_from(&mut reader).map_err(cache_error)?;
+    let tdms_reader = TdmsReader::from_cached(cached_metadata, read_options.get_custom_types())?;
+    let overview = cached_overview
+        .map(|cached_overview| Overview::from_cached(cached_overview, &tdms_reader))
+        .transpose()?;
+    Ok((tdms_reader, overview))
+}
+
+fn save_cache(
+    cache_path: &Path,
+    key: &CacheKey,
+    tdms_reader: &TdmsReader,
+    overview: Option<&Overview>,
+) -> Result<()> {
+    if let Some(cache_dir) = cache_path.parent() {
+        std::fs::create_dir_all(cache_dir)?;
+    }
+    // Write to a temporary file first so that a partially written cache is never read
+    let temp_path = cache_path.with_extension(format!("{}.tmp", std::process::id()));
+    let mut writer = BufWriter::new(File::create(&temp_path)?);
+    let result = bincode::options()
+        .serialize_into(&mut writer, key)
+        .and_then(|_| bincode::options().serialize_into(&mut writer, &tdms_reader.to_cached()))
+        .and_then(|_| {
+            let overview = overview.map(Overview::to_cached);
+            bincode::options().serialize_into(&mut writer, &overview)
+        })
+        .map_err(cache_error)
+        .and_then(|_| Ok(writer.flush()?));
+    match result {
+        Ok(()) => Ok(std::fs::rename(&temp_path, cache_path)?),
+        Err(e) => {
+            let _ = std::fs::remove_file(&temp_path);
+            Err(e)
+        }
+    }
+}
+
+fn cache_error(error: bincode::Error) -> TdmsReadError {
+    TdmsReadError::InvalidCache(error.to_string())
+}
@@ -0,0 +1,149 @@
+use std::any::Any;
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+/// Size of the reads made by operations that stream through channel data, such as
+/// chunk iteration, decimation, statistics, export and hashing.
+///
+/// Larger reads are usually faster on network storage, while smaller reads use less
+/// memory. Each operation reads a whole number of values of at least this many bytes
+/// where possible, but always reads at least one value at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkConfig {
+    /// Number of bytes of data read at a time
+    pub bytes: usize,
+}
+
+impl ChunkConfig {
+    /// Number of bytes read at a time unless configured otherwise
+    pub const DEFAULT_BYTES: usize = 4 * 1024 * 1024;
+
+    pub fn new(bytes: usize) -> ChunkConfig {
+        ChunkConfig { bytes }
+    }
+
+    /// Number of values of `value_size` bytes read at a time, which is at least one
+    pub fn values(&self, value_size: usize) -> usize {
+        (self.bytes / value_size.max(1)).max(1)
+    }
+}
+
+impl Default for ChunkConfig {
+    fn default() -> ChunkConfig {
+        ChunkConfig::new(ChunkConfig::DEFAULT_BYTES)
+    }
+}
+
+/// Maximum number of unused buffers kept by a `ScratchPool`
+const MAX_POOLED_BUFFERS: usize = 4;
+
+/// Buffers for reading chunks of data that are kept for reuse by later reads of the
+/// same file, so that repeated streaming operations don't allocate a new buffer each
+/// time. The number of buffers kept is bounded, and the pool can be shared between
+/// threads.
+pub(crate) struct ScratchPool {
+    buffers: Mutex<Vec<Box<dyn Any + Send>>>,
+}
+
+impl ScratchPool {
+    pub fn new() -> ScratchPool {
+        ScratchPool {
+            buffers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Take a buffer of `length` default values, reusing an unused buffer of
+    /// the same type if there is one. The buffer is returned to the pool when dropped.
+    pub fn take<T: Clone + Default + Send + 'static>(&self, length: usize) -> Scratch<'_, T> {
+        let mut buffer = self.take_buffer().unwrap_or_default();
+        buffer.clear();
+        buffer.resize(length, T::default());
+        Scratch { pool: self, buffer }
+    }
+
+    fn take_buffer<T: Send + 'static>(&self) -> Option<Vec<T>> {
+        let mut buffers = self.buffers.lock().ok()?;
+        let index = buffers.iter().position(|buffer| buffer.is::<Vec<T>>())?;
+        buffers
+            .swap_remove(index)
+            .downcast::<Vec<T>>()
+            .ok()
+            .map(|buffer| *buffer)
+    }
+
+    fn give_back<T: Send + 'static>(&self, buffer: Vec<T>) {
+        if let Ok(mut buffers) = self.buffers.lock() {
+            if buffers.len() < MAX_POOLED_BUFFERS {
+                buffers.push(Box::new(buffer));
+            }
+        }
+    }
+}
+
+/// A buffer taken from a `ScratchPool`, which is returned to the pool when dropped
+pub(crate) struct Scratch<'p, T: Send + 'static> {
+    pool: &'p ScratchPool,
+    buffer: Vec<T>,
+}
+
+impl<'p, T: Send + 'static> Deref for Scratch<'p, T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.buffer
+    }
+}
+
+impl<'p, T: Send + 'static> DerefMut for Scratch<'p, T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.buffer
+    }
+}
+
+impl<'p, T: Send + 'static> Drop for Scratch<'p, T> {
+    fn drop(&mut self) {
+        self.pool.give_back(std::mem::take(&mut self.buffer));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn values_per_chunk() {
+        let config = ChunkConfig::new(1024);
+
+        assert_eq!(config.values(8), 128);
+        assert_eq!(config.values(3), 341);
+        assert_eq!(ChunkConfig::new(4).values(8), 1);
+        assert_eq!(config.values(0), 1024);
+    }
+
+    #[test]
+    pub fn buffers_are_reused() {
+        let pool = ScratchPool::new();
+        let pointer = {
+            let mut buffer = pool.take::<f64>(100);
+            buffer[99] = 1.0;
+            buffer.as_ptr()
+        };
+
+        let buffer = pool.take::<f64>(50);
+        assert_eq!(buffer.as_ptr(), pointer);
+        assert_eq!(*buffer, vec![0.0; 50]);
+
+        // Buffers of other types aren't reused
+        let other = pool.take::<i32>(10);
+        assert_eq!(other.len(), 10);
+    }
+
+    #[test]
+    pub fn pool_is_bounded() {
+        let pool = ScratchPool::new();
+        let buffers: Vec<Scratch<u8>> = (0..10).map(|_| pool.take(8)).collect();
+        drop(buffers);
+
+        assert_eq!(pool.buffers.lock().unwrap().len(), MAX_POOLED_BUFFERS);
+    }
+}
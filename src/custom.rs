@@ -0,0 +1,242 @@
+use crate::error::{Result, TdmsReadError};
+use crate::inspect::Layout;
+use crate::types::TdsType;
+use crate::Channel;
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::Arc;
+
+/// Values of a custom data type, as returned by a `CustomDecoder`
+#[derive(Debug, Clone, PartialEq)]
+pub enum CustomValues {
+    /// One f64 value for each value read
+    Numbers(Vec<f64>),
+
+    /// The raw bytes of the values, passed through without decoding
+    Bytes(Vec<u8>),
+}
+
+impl CustomValues {
+    /// Append values decoded from the next chunk of data
+    fn extend(&mut self, values: CustomValues) -> Result<()> {
+        match (self, values) {
+            (CustomValues::Numbers(numbers), CustomValues::Numbers(more)) => numbers.extend(more),
+            (CustomValues::Bytes(bytes), CustomValues::Bytes(more)) => bytes.extend(more),
+            _ => {
+                return Err(TdmsReadError::InvalidData(String::from(
+                    "Custom decoder returned numbers for some chunks of data and bytes for others",
+                )))
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Decodes channel data of a vendor specific data type, registered with
+/// `ReadOptions::register_custom_type`
+pub trait CustomDecoder: Send + Sync {
+    /// Decode `count` values from their raw bytes, which are the registered size of the
+    /// type in bytes per value, in the order they are stored in the file. `big_endian`
+    /// is whether the data was written in big endian byte order. Data is decoded a chunk
+    /// at a time, so this is called once for each chunk read.
+    fn decode(&self, bytes: &[u8], count: usize, big_endian: bool) -> Result<CustomValues>;
+}
+
+/// A decoder that passes the raw bytes of values through without decoding them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PassthroughDecoder;
+
+impl CustomDecoder for PassthroughDecoder {
+    fn decode(&self, bytes: &[u8], _count: usize, _big_endian: bool) -> Result<CustomValues> {
+        Ok(CustomValues::Bytes(bytes.to_vec()))
+    }
+}
+
+#[derive(Clone)]
+struct CustomType {
+    code: u32,
+    size: usize,
+    decoder: Arc<dyn CustomDecoder>,
+}
+
+/// Custom data types registered with the read options
+#[derive(Clone, Default)]
+pub(crate) struct CustomTypes {
+    types: Vec<CustomType>,
+}
+
+impl CustomTypes {
+    /// Register a type, replacing any type previously registered with the same code
+    pub fn register(&mut self, code: u32, size: usize, decoder: Arc<dyn CustomDecoder>) {
+        self.types.retain(|custom_type| custom_type.code != code);
+        self.types.push(CustomType {
+            code,
+            size,
+            decoder,
+        });
+    }
+
+    /// The data type of channel raw data with a type code. Standard types take precedence
+    /// over registered types, and codes of neither are invalid.
+    pub fn data_type(&self, code: u32) -> Result<TdsType> {
+        let error = match TdsType::from_u32(code) {
+            Ok(data_type) => return Ok(data_type),
+            Err(error) => error,
+        };
+        match self.get(code) {
+            Some(custom_type) if custom_type.size == 0 => Err(TdmsReadError::InvalidArgument(
+                format!("Custom type {:#x} was registered with a size of zero", code),
+            )),
+            Some(custom_type) => Ok(TdsType::Custom {
+                code,
+                size: custom_type.size,
+            }),
+            None => Err(error),
+        }
+    }
+
+    /// The decoder registered for a type code
+    pub fn decoder(&self, code: u32) -> Option<&dyn CustomDecoder> {
+        self.get(code)
+            .map(|custom_type| custom_type.decoder.as_ref())
+    }
+
+    /// Codes and sizes of the registered types, in order of code
+    #[cfg(feature = "cache")]
+    pub fn sizes(&self) -> Vec<(u32, usize)> {
+        let mut sizes: Vec<(u32, usize)> = self
+            .types
+            .iter()
+            .map(|custom_type| (custom_type.code, custom_type.size))
+            .collect();
+        sizes.sort_unstable();
+        sizes
+    }
+
+    fn get(&self, code: u32) -> Option<&CustomType> {
+        self.types
+            .iter()
+            .find(|custom_type| custom_type.code == code)
+    }
+}
+
+impl fmt::Debug for CustomTypes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(
+                self.types.iter().map(|custom_type| {
+                    format!("{:#x} ({} bytes)", custom_type.code, custom_type.size)
+                }),
+            )
+            .finish()
+    }
+}
+
+/// Read up to `length` values of a channel with a custom data type starting from the
+/// value at index `offset`, decoding each chunk of data with the registered decoder
+pub(crate) fn read_custom<R: Read + Seek>(
+    channel: &Channel<R>,
+    offset: u64,
+    length: usize,
+) -> Result<CustomValues> {
+    let (code, size) = match channel.data_type() {
+        Some(TdsType::Custom { code, size }) => (code, size),
+        Some(data_type) => {
+            return Err(TdmsReadError::TypeMismatch {
+                data_type,
+                requested: String::from("custom values"),
+            })
+        }
+        None => return Ok(CustomValues::Numbers(Vec::new())),
+    };
+    let decoder = channel
+        .file
+        .options
+        .get_custom_types()
+        .decoder(code)
+        .ok_or_else(|| {
+            TdmsReadError::Unsupported(format!("No decoder is registered for type {:#x}", code))
+        })?;
+    let extents = channel
+        .file
+        .tdms_reader
+        .channel_data_extents(channel.object_id)?;
+    let chunk_values = channel.chunk_config().values(size) as u64;
+    let end = offset.saturating_add(length as u64).min(channel.len());
+    let reader = &mut *channel.file.file_reader.borrow_mut();
+    let mut bytes = channel.scratch().take::<u8>(0);
+    let mut rows = channel.scratch().take::<u8>(0);
+    let mut values: Option<CustomValues> = None;
+
+    let mut extent_start = 0;
+    for extent in extents.iter() {
+        let extent_end = extent_start + extent.values;
+        let mut position = offset.max(extent_start);
+        while position < end.min(extent_end) {
+            let count = (end.min(extent_end) - position).min(chunk_values);
+            let index_in_extent = position - extent_start;
+            bytes.clear();
+            match extent.layout {
+                Layout::Contiguous => {
+                    bytes.resize(count as usize * size, 0);
+                    reader.seek(SeekFrom::Start(
+                        extent.file_offset + index_in_extent * size as u64,
+                    ))?;
+                    reader.read_exact(&mut bytes)?;
+                }
+                Layout::Interleaved { stride } => {
+                    // Read the span of rows and keep this channel's bytes from each one
+                    rows.clear();
+                    rows.resize(((count - 1) * stride) as usize + size, 0);
+                    reader.seek(SeekFrom::Start(
+                        extent.file_offset + index_in_extent * stride,
+                    ))?;
+                    reader.read_exact(&mut rows)?;
+                    for row in rows.chunks(stride as usize) {
+                        bytes.extend_from_slice(&row[..size]);
+                    }
+                }
+            }
+            let decoded = decoder.decode(&bytes, count as usize, extent.big_endian)?;
+            let decoded_length = match decoded {
+                CustomValues::Numbers(ref numbers) => numbers.len(),
+                CustomValues::Bytes(ref decoded_bytes) => decoded_bytes.len() / size,
+            };
+            if decoded_length != count as usize {
+                return Err(TdmsReadError::InvalidData(format!(
+                    "Custom decoder for type {:#x} returned {} values when decoding {}",
+                    code, decoded_length, count
+                )));
+            }
+            match values {
+                Some(ref mut values) => values.extend(decoded)?,
+                None => values = Some(decoded),
+            }
+            position += count;
+        }
+        extent_start = extent_end;
+        if extent_start >= end {
+            break;
+        }
+    }
+    Ok(values.unwrap_or(CustomValues::Numbers(Vec::new())))
+}
+
+/// Read values of a channel with a custom data type converted to f64 by its decoder,
+/// returning the number of values read
+pub(crate) fn read_custom_f64<R: Read + Seek>(
+    channel: &Channel<R>,
+    offset: u64,
+    buffer: &mut [f64],
+) -> Result<usize> {
+    match read_custom(channel, offset, buffer.len())? {
+        CustomValues::Numbers(numbers) => {
+            buffer[..numbers.len()].copy_from_slice(&numbers);
+            Ok(numbers.len())
+        }
+        CustomValues::Bytes(_) => Err(TdmsReadError::TypeMismatch {
+            data_type: channel.data_type().unwrap_or(TdsType::Void),
+            requested: String::from("numeric values, as its decoder passes bytes through"),
+        }),
+    }
+}
@@ -0,0 +1,274 @@
+use crate::properties::names::{
+    scale_property_name, LINEAR_INPUT_SOURCE, LINEAR_SLOPE, LINEAR_Y_INTERCEPT,
+    NI_NUMBER_OF_SCALES, SCALE_TYPE,
+};
+use crate::properties::{TdmsProperty, TdmsValue};
+use crate::types::TdsType;
+
+/// The first scaler of an object with DAQmx raw data, along with the shape of the
+/// segment's raw buffers. Only data with a single format changing scaler reading
+/// integers from a single raw buffer can be read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DaqmxScaler {
+    /// Whether the object uses digital line scalers rather than format changing scalers
+    pub digital_line: bool,
+
+    /// Number of scalers of the object
+    pub scaler_count: u32,
+
+    /// Number of raw buffers in the segment
+    pub buffer_count: u32,
+
+    /// DAQmx data type code of the raw values
+    pub raw_data_type: u32,
+
+    /// Offset in bytes of the scaler's values within a row of its raw buffer
+    pub byte_offset: u32,
+
+    /// Width in bytes of a row of the scaler's raw buffer
+    pub row_width: u32,
+
+    /// Index of the scale applied to the raw values, from the `NI_Scale[n]_*` properties
+    pub scale_id: u32,
+}
+
+impl DaqmxScaler {
+    /// The data type of the raw values, if they can be read
+    pub fn raw_type(&self) -> Option<TdsType> {
+        match self.raw_data_type {
+            0 => Some(TdsType::U8),
+            1 => Some(TdsType::I8),
+            2 => Some(TdsType::U16),
+            3 => Some(TdsType::I16),
+            4 => Some(TdsType::U32),
+            5 => Some(TdsType::I32),
+            6 => Some(TdsType::U64),
+            7 => Some(TdsType::I64),
+            _ => None,
+        }
+    }
+
+    /// Why the raw data can't be read, or None if it can
+    pub fn unsupported_reason(&self) -> Option<&'static str> {
+        if self.digital_line {
+            Some("digital line scalers are not supported")
+        } else if self.scaler_count != 1 {
+            Some("only a single scaler per channel is supported")
+        } else if self.buffer_count != 1 {
+            Some("only a single raw data buffer is supported")
+        } else if self.row_width == 0 {
+            Some("the scaler's raw buffer is missing or has zero width")
+        } else {
+            match self.raw_type().and_then(|raw_type| raw_type.size()) {
+                None => Some("only integer raw data is supported"),
+                Some(size) if self.byte_offset as u64 + size as u64 > self.row_width as u64 => {
+                    Some("the scaler's values extend past the end of its raw buffer rows")
+                }
+                Some(_) => None,
+            }
+        }
+    }
+}
+
+/// A linear scale converting raw DAQmx values to engineering units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearScale {
+    pub slope: f64,
+    pub intercept: f64,
+}
+
+impl LinearScale {
+    /// Read a linear scale from the `NI_Scale[n]_*` properties of a channel,
+    /// returning None if the scale doesn't exist or isn't linear
+    pub fn from_properties(properties: &[TdmsProperty], scale_id: u32) -> Option<LinearScale> {
+        ScaleDescriptor::from_properties(properties, scale_id as usize)?.linear_scale()
+    }
+
+    pub fn apply(&self, raw_value: f64) -> f64 {
+        raw_value * self.slope + self.intercept
+    }
+}
+
+/// A scale applied to raw DAQmx values, described by a channel's `NI_Scale[n]_*` properties
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaleDescriptor {
+    /// Index n of the scale's properties
+    pub index: usize,
+
+    /// Type of the scale, eg. "Linear" or "Polynomial"
+    pub scale_type: String,
+
+    /// Slope of a linear scale
+    pub slope: Option<f64>,
+
+    /// Y intercept of a linear scale
+    pub intercept: Option<f64>,
+
+    /// Index of the scale whose output is the input to this scale
+    pub input_source: Option<usize>,
+}
+
+impl ScaleDescriptor {
+    /// Read the scale at `index` from an object's properties,
+    /// returning None if there is no `NI_Scale[n]_Scale_Type` property
+    pub fn from_properties(properties: &[TdmsProperty], index: usize) -> Option<ScaleDescriptor> {
+        let find = |suffix: &str| {
+            let name = scale_property_name(index, suffix);
+            properties
+                .iter()
+                .find(|property| property.name == name.as_str())
+                .map(|property| &property.value)
+        };
+        let scale_type = find(SCALE_TYPE).and_then(TdmsValue::as_str)?.to_string();
+        Some(ScaleDescriptor {
+            index,
+            scale_type,
+            slope: find(LINEAR_SLOPE).and_then(TdmsValue::as_f64),
+            intercept: find(LINEAR_Y_INTERCEPT).and_then(TdmsValue::as_f64),
+            input_source: find(LINEAR_INPUT_SOURCE)
+                .and_then(TdmsValue::as_f64)
+                .map(|index| index as usize),
+        })
+    }
+
+    /// Read all scales of an object, from index 0 up to the `NI_Number_Of_Scales`
+    /// property. Indexes without a scale type are left out.
+    pub fn all_from_properties(properties: &[TdmsProperty]) -> Vec<ScaleDescriptor> {
+        let scale_count = properties
+            .iter()
+            .find(|property| property.name == NI_NUMBER_OF_SCALES)
+            .and_then(|property| property.value.as_f64())
+            .unwrap_or(0.0) as usize;
+        (0..scale_count)
+            .filter_map(|index| ScaleDescriptor::from_properties(properties, index))
+            .collect()
+    }
+
+    /// Whether this is a linear scale
+    pub fn is_linear(&self) -> bool {
+        self.scale_type == "Linear"
+    }
+
+    /// The linear scale to apply to raw values, or None if this isn't a linear scale.
+    /// A missing y intercept is taken as zero.
+    pub(crate) fn linear_scale(&self) -> Option<LinearScale> {
+        if !self.is_linear() {
+            return None;
+        }
+        Some(LinearScale {
+            slope: self.slope?,
+            intercept: self.intercept.unwrap_or(0.0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn scaler() -> DaqmxScaler {
+        DaqmxScaler {
+            digital_line: false,
+            scaler_count: 1,
+            buffer_count: 1,
+            raw_data_type: 3,
+            byte_offset: 0,
+            row_width: 4,
+            scale_id: 0,
+        }
+    }
+
+    #[test]
+    pub fn single_integer_scaler_is_supported() {
+        assert_eq!(scaler().unsupported_reason(), None);
+        assert_eq!(scaler().raw_type(), Some(TdsType::I16));
+
+        let unsupported = [
+            DaqmxScaler {
+                digital_line: true,
+                ..scaler()
+            },
+            DaqmxScaler {
+                scaler_count: 2,
+                ..scaler()
+            },
+            DaqmxScaler {
+                buffer_count: 2,
+                ..scaler()
+            },
+            DaqmxScaler {
+                raw_data_type: 9,
+                ..scaler()
+            },
+            DaqmxScaler {
+                row_width: 0,
+                ..scaler()
+            },
+            DaqmxScaler {
+                byte_offset: 3,
+                ..scaler()
+            },
+        ];
+        for scaler in unsupported.iter() {
+            assert!(scaler.unsupported_reason().is_some(), "{:?}", scaler);
+        }
+    }
+
+    #[test]
+    pub fn linear_scale_from_properties() {
+        let properties = vec![
+            TdmsProperty::new(
+                "NI_Scale[1]_Scale_Type",
+                TdmsValue::String(String::from("Linear")),
+            ),
+            TdmsProperty::new("NI_Scale[1]_Linear_Slope", TdmsValue::Float64(0.5)),
+            TdmsProperty::new("NI_Scale[1]_Linear_Y_Intercept", TdmsValue::Float64(-1.0)),
+        ];
+
+        let scale = LinearScale::from_properties(&properties, 1).unwrap();
+
+        assert_eq!(scale.apply(4.0), 1.0);
+        assert_eq!(LinearScale::from_properties(&properties, 0), None);
+    }
+
+    #[test]
+    pub fn scale_descriptors_from_properties() {
+        let properties = vec![
+            TdmsProperty::new(NI_NUMBER_OF_SCALES, TdmsValue::Int32(2)),
+            TdmsProperty::new(
+                &scale_property_name(0, SCALE_TYPE),
+                TdmsValue::String(String::from("Polynomial")),
+            ),
+            TdmsProperty::new(
+                &scale_property_name(1, SCALE_TYPE),
+                TdmsValue::String(String::from("Linear")),
+            ),
+            TdmsProperty::new(
+                &scale_property_name(1, LINEAR_SLOPE),
+                TdmsValue::Float64(2.0),
+            ),
+            TdmsProperty::new(
+                &scale_property_name(1, LINEAR_INPUT_SOURCE),
+                TdmsValue::Uint32(0),
+            ),
+        ];
+
+        let scales = ScaleDescriptor::all_from_properties(&properties);
+
+        assert_eq!(scales.len(), 2);
+        assert!(!scales[0].is_linear());
+        assert_eq!(scales[0].linear_scale(), None);
+        assert_eq!(
+            scales[1],
+            ScaleDescriptor {
+                index: 1,
+                scale_type: String::from("Linear"),
+                slope: Some(2.0),
+                intercept: None,
+                input_source: Some(0),
+            }
+        );
+        assert_eq!(scales[1].linear_scale().unwrap().apply(3.0), 6.0);
+    }
+}
@@ -0,0 +1,264 @@
+use crate::custom;
+use crate::error::{Result, TdmsReadError};
+use crate::progress::ReadControl;
+use crate::types::{NativeType, NativeTypeId, TdsType};
+use crate::Channel;
+use std::io::{Read, Seek};
+
+/// The range of values within a bucket of consecutive channel values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinMaxBucket {
+    /// Index of the first value in the bucket
+    pub first_index: u64,
+
+    /// Number of values in the bucket
+    pub count: u64,
+
+    /// Minimum value in the bucket, ignoring NaN values. This is NaN if all values are NaN.
+    pub min: f64,
+
+    /// Maximum value in the bucket, ignoring NaN values. This is NaN if all values are NaN.
+    pub max: f64,
+}
+
+/// Conversion of numeric channel values to f64
+pub(crate) trait NumericValue: NativeType + Copy + Default {
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_numeric_value {
+    ($($type:ty),*) => {
+        $(
+            impl NumericValue for $type {
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric_value!(i8, i16, i32, i64, u8, u16, u32, u64, f32, f64);
+
+/// Read numeric channel data of any type starting from the value at index `offset`,
+/// converting values to f64
+pub(crate) fn read_data_as_f64<R: Read + Seek>(
+    channel: &Channel<R>,
+    offset: u64,
+    buffer: &mut [f64],
+) -> Result<usize> {
+    let data_type = match channel.data_type() {
+        Some(data_type) => data_type,
+        None => return Ok(0),
+    };
+    match data_type {
+        TdsType::DaqmxRawData => return channel.read_daqmx_scaled(offset, buffer),
+        TdsType::Custom { .. } => return custom::read_custom_f64(channel, offset, buffer),
+        _ => {}
+    }
+    match data_type.native_type() {
+        Some(NativeTypeId::I8) => read_converted::<i8, _>(channel, offset, buffer),
+        Some(NativeTypeId::I16) => read_converted::<i16, _>(channel, offset, buffer),
+        Some(NativeTypeId::I32) => read_converted::<i32, _>(channel, offset, buffer),
+        Some(NativeTypeId::I64) => read_converted::<i64, _>(channel, offset, buffer),
+        Some(NativeTypeId::U8) => read_converted::<u8, _>(channel, offset, buffer),
+        Some(NativeTypeId::U16) => read_converted::<u16, _>(channel, offset, buffer),
+        Some(NativeTypeId::U32) => read_converted::<u32, _>(channel, offset, buffer),
+        Some(NativeTypeId::U64) => read_converted::<u64, _>(channel, offset, buffer),
+        Some(NativeTypeId::F32) => read_converted::<f32, _>(channel, offset, buffer),
+        Some(NativeTypeId::F64) => channel.read_native(offset, buffer),
+        Some(NativeTypeId::Timestamp) | None => Err(TdmsReadError::TypeMismatch {
+            data_type,
+            requested: String::from("numeric values"),
+        }),
+    }
+}
+
+fn read_converted<T: NumericValue, R: Read + Seek>(
+    channel: &Channel<R>,
+    offset: u64,
+    buffer: &mut [f64],
+) -> Result<usize> {
+    let mut native_buffer = channel.scratch().take::<T>(buffer.len());
+    let values_read = channel.read_native(offset, &mut native_buffer[..])?;
+    for (target, value) in buffer.iter_mut().zip(native_buffer[..values_read].iter()) {
+        *target = value.to_f64();
+    }
+    Ok(values_read)
+}
+
+/// Read a min/max envelope of `length` values of a numeric channel starting at `offset`,
+/// dividing the values into at most `bucket_count` buckets of equal size
+/// (apart from the final bucket, which may be smaller)
+pub(crate) fn read_decimated<R: Read + Seek>(
+    channel: &Channel<R>,
+    offset: u64,
+    length: u64,
+    bucket_count: usize,
+    control: &mut ReadControl,
+) -> Result<Vec<MinMaxBucket>> {
+    let length = length.min(channel.len().saturating_sub(offset));
+    if length == 0 || bucket_count == 0 {
+        return Ok(Vec::new());
+    }
+    let bucket_size = length.div_ceil(bucket_count as u64);
+    let mut buckets = Vec::with_capacity(length.div_ceil(bucket_size) as usize);
+    let chunk_values = channel.chunk_config().values(std::mem::size_of::<f64>());
+    let mut buffer = channel
+        .scratch()
+        .take::<f64>(chunk_values.min(length as usize));
+    let mut bucket = MinMaxBucket::empty(offset);
+
+    let end = offset + length;
+    let mut position = offset;
+    while position < end {
+        if control.is_cancelled() {
+            return Err(TdmsReadError::Cancelled);
+        }
+        let count = (end - position).min(buffer.len() as u64) as usize;
+        let values_read = read_data_as_f64(channel, position, &mut buffer[..count])?;
+        if values_read == 0 {
+            break;
+        }
+        for &value in buffer[..values_read].iter() {
+            bucket.add(value);
+            if bucket.count == bucket_size {
+                let next_index = bucket.first_index + bucket.count;
+                buckets.push(bucket);
+                bucket = MinMaxBucket::empty(next_index);
+            }
+        }
+        position += values_read as u64;
+        control.report_progress(position - offset, length);
+    }
+    if bucket.count > 0 {
+        buckets.push(bucket);
+    }
+    Ok(buckets)
+}
+
+impl MinMaxBucket {
+    fn empty(first_index: u64) -> MinMaxBucket {
+        MinMaxBucket {
+            first_index,
+            count: 0,
+            min: f64::NAN,
+            max: f64::NAN,
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        self.count += 1;
+        // f64::min and f64::max ignore NaN unless both values are NaN
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::progress::CancellationToken;
+    use crate::writer::{TdmsWriter, WriteObject};
+    use crate::TdmsFile;
+    use std::io::Cursor;
+
+    fn write_file(values: &[i16]) -> TdmsFile<Cursor<Vec<u8>>> {
+        let mut writer = TdmsWriter::new(Vec::new());
+        writer
+            .write_segment(&[WriteObject::channel("Group", "Channel")
+                .with_data(values)
+                .unwrap()])
+            .unwrap();
+        TdmsFile::new(Cursor::new(writer.into_inner())).unwrap()
+    }
+
+    #[test]
+    fn decimate_into_buckets() {
+        let tdms_file = write_file(&[3, -1, 4, 1, -5, 9, 2, 6, 5, 3, 5]);
+        let group = tdms_file.group("Group").unwrap();
+        let channel = group.channel("Channel").unwrap();
+
+        let buckets = channel.read_decimated(1, 100, 3).unwrap();
+
+        assert_eq!(
+            buckets,
+            vec![
+                MinMaxBucket {
+                    first_index: 1,
+                    count: 4,
+                    min: -5.0,
+                    max: 4.0
+                },
+                MinMaxBucket {
+                    first_index: 5,
+                    count: 4,
+                    min: 2.0,
+                    max: 9.0
+                },
+                MinMaxBucket {
+                    first_index: 9,
+                    count: 2,
+                    min: 3.0,
+                    max: 5.0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn decimate_with_progress_and_cancellation() {
+        let values: Vec<i16> = (0..1000).map(|i| i as i16).collect();
+        let tdms_file = write_file(&values);
+        let group = tdms_file.group("Group").unwrap();
+        let channel = group.channel("Channel").unwrap();
+
+        let mut progress = Vec::new();
+        let buckets = channel
+            .read_decimated_with_control(
+                0,
+                1000,
+                10,
+                &mut ReadControl::new().on_progress(|done, total| progress.push((done, total))),
+            )
+            .unwrap();
+        assert_eq!(buckets.len(), 10);
+        assert_eq!(progress.last(), Some(&(1000, 1000)));
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = channel.read_decimated_with_control(
+            0,
+            1000,
+            10,
+            &mut ReadControl::new().cancellation_token(token),
+        );
+        match result {
+            Err(TdmsReadError::Cancelled) => {}
+            other => panic!("Expected cancellation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn read_integers_as_f64() {
+        let tdms_file = write_file(&[1, 2, 3]);
+        let group = tdms_file.group("Group").unwrap();
+        let channel = group.channel("Channel").unwrap();
+
+        let mut buffer = vec![0.0; 2];
+        assert_eq!(channel.read_data_as_f64(1, &mut buffer).unwrap(), 2);
+        assert_eq!(buffer, vec![2.0, 3.0]);
+    }
+
+    #[test]
+    fn bucket_min_max_ignores_nan() {
+        let mut bucket = MinMaxBucket::empty(0);
+        bucket.add(f64::NAN);
+        bucket.add(2.0);
+        bucket.add(-1.0);
+
+        assert_eq!(bucket.count, 3);
+        assert_eq!(bucket.min, -1.0);
+        assert_eq!(bucket.max, 2.0);
+    }
+}
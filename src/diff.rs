@@ -0,0 +1,538 @@
+use crate::error::Result;
+use crate::types::{NativeType, NativeTypeId, TdsType};
+use crate::{Channel, Group, TdmsFile, TdmsProperty, TdmsValue, Timestamp};
+use std::fmt;
+use std::io::{Read, Seek};
+
+/// Options controlling how two TDMS files are compared
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    /// Whether to compare channel data values, rather than only metadata
+    pub compare_data: bool,
+
+    /// Names of properties to ignore when comparing objects, eg. timestamps
+    pub ignore_properties: Vec<String>,
+
+    /// Absolute tolerance used when comparing floating point values
+    pub absolute_tolerance: f64,
+
+    /// Relative tolerance used when comparing floating point values
+    pub relative_tolerance: f64,
+
+    /// Number of values read from each file at a time when comparing data, or None
+    /// to use the chunk configuration of the first file's read options
+    pub chunk_size: Option<usize>,
+}
+
+impl Default for DiffOptions {
+    fn default() -> DiffOptions {
+        DiffOptions {
+            compare_data: false,
+            ignore_properties: Vec::new(),
+            absolute_tolerance: 0.0,
+            relative_tolerance: 0.0,
+            chunk_size: None,
+        }
+    }
+}
+
+/// Identifies which of the two compared files something was found in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffSide {
+    Left,
+    Right,
+}
+
+/// An object within a TDMS file that a difference applies to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DiffObject {
+    Root,
+    Group(String),
+    Channel(String, String),
+}
+
+/// A single difference found between two TDMS files
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// A group exists in only one of the files
+    GroupOnlyIn { side: DiffSide, group: String },
+
+    /// A channel exists in only one of the files
+    ChannelOnlyIn {
+        side: DiffSide,
+        group: String,
+        channel: String,
+    },
+
+    /// A channel has a different data type in each file
+    DataType {
+        group: String,
+        channel: String,
+        left: Option<TdsType>,
+        right: Option<TdsType>,
+    },
+
+    /// A channel has a different number of values in each file
+    Length {
+        group: String,
+        channel: String,
+        left: u64,
+        right: u64,
+    },
+
+    /// A property is missing from one file or has a different value in each file
+    Property {
+        object: DiffObject,
+        name: String,
+        left: Option<TdmsValue>,
+        right: Option<TdmsValue>,
+    },
+
+    /// Channel data values differ, starting at the given value index
+    Data {
+        group: String,
+        channel: String,
+        first_index: u64,
+    },
+
+    /// Channel data could not be compared as reading its data type is not supported
+    DataNotCompared {
+        group: String,
+        channel: String,
+        data_type: TdsType,
+    },
+}
+
+/// The result of comparing two TDMS files
+#[derive(Debug, Clone, Default)]
+pub struct DiffReport {
+    pub differences: Vec<Difference>,
+}
+
+impl DiffReport {
+    /// Whether the files were found to be equivalent
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+/// Compare two TDMS files, returning a report of all differences found.
+///
+/// Groups, channels, data types, lengths and properties are always compared.
+/// When `options.compare_data` is set, channel data is also compared by streaming
+/// both files in chunks of `options.chunk_size` values, so memory use is bounded
+/// regardless of the channel lengths.
+pub fn diff<A: Read + Seek, B: Read + Seek>(
+    left: &TdmsFile<A>,
+    right: &TdmsFile<B>,
+    options: &DiffOptions,
+) -> Result<DiffReport> {
+    let mut differences = Vec::new();
+
+    diff_properties(
+        DiffObject::Root,
+        left.properties(),
+        right.properties(),
+        options,
+        &mut differences,
+    );
+
+    for left_group in left.groups() {
+        match right.group(left_group.name()) {
+            Some(right_group) => {
+                diff_groups(&left_group, &right_group, options, &mut differences)?;
+            }
+            None => differences.push(Difference::GroupOnlyIn {
+                side: DiffSide::Left,
+                group: left_group.name().to_string(),
+            }),
+        }
+    }
+    for right_group in right.groups() {
+        if left.group(right_group.name()).is_none() {
+            differences.push(Difference::GroupOnlyIn {
+                side: DiffSide::Right,
+                group: right_group.name().to_string(),
+            });
+        }
+    }
+
+    Ok(DiffReport { differences })
+}
+
+fn diff_groups<A: Read + Seek, B: Read + Seek>(
+    left: &Group<A>,
+    right: &Group<B>,
+    options: &DiffOptions,
+    differences: &mut Vec<Difference>,
+) -> Result<()> {
+    let group_name = left.name();
+    diff_properties(
+        DiffObject::Group(group_name.to_string()),
+        left.properties(),
+        right.properties(),
+        options,
+        differences,
+    );
+
+    for left_channel in left.channels() {
+        match right.channel(left_channel.name()) {
+            Some(right_channel) => {
+                diff_channels(
+                    group_name,
+                    &left_channel,
+                    &right_channel,
+                    options,
+                    differences,
+                )?;
+            }
+            None => differences.push(Difference::ChannelOnlyIn {
+                side: DiffSide::Left,
+                group: group_name.to_string(),
+                channel: left_channel.name().to_string(),
+            }),
+        }
+    }
+    for right_channel in right.channels() {
+        if left.channel(right_channel.name()).is_none() {
+            differences.push(Difference::ChannelOnlyIn {
+                side: DiffSide::Right,
+                group: group_name.to_string(),
+                channel: right_channel.name().to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn diff_channels<A: Read + Seek, B: Read + Seek>(
+    group_name: &str,
+    left: &Channel<A>,
+    right: &Channel<B>,
+    options: &DiffOptions,
+    differences: &mut Vec<Difference>,
+) -> Result<()> {
+    let channel_name = left.name();
+    diff_properties(
+        DiffObject::Channel(group_name.to_string(), channel_name.to_string()),
+        left.properties(),
+        right.properties(),
+        options,
+        differences,
+    );
+
+    let (left_type, right_type) = (left.data_type(), right.data_type());
+    if left_type != right_type {
+        differences.push(Difference::DataType {
+            group: group_name.to_string(),
+            channel: channel_name.to_string(),
+            left: left_type,
+            right: right_type,
+        });
+    }
+
+    let (left_length, right_length) = (left.len(), right.len());
+    if left_length != right_length {
+        differences.push(Difference::Length {
+            group: group_name.to_string(),
+            channel: channel_name.to_string(),
+            left: left_length,
+            right: right_length,
+        });
+    }
+
+    if !options.compare_data || left_type != right_type {
+        return Ok(());
+    }
+    let data_type = match left_type {
+        Some(data_type) => data_type,
+        None => return Ok(()),
+    };
+
+    let first_difference = match data_type.native_type() {
+        Some(NativeTypeId::I8) => first_data_difference::<i8, _, _>(left, right, options)?,
+        Some(NativeTypeId::I16) => first_data_difference::<i16, _, _>(left, right, options)?,
+        Some(NativeTypeId::I32) => first_data_difference::<i32, _, _>(left, right, options)?,
+        Some(NativeTypeId::I64) => first_data_difference::<i64, _, _>(left, right, options)?,
+        Some(NativeTypeId::U8) => first_data_difference::<u8, _, _>(left, right, options)?,
+        Some(NativeTypeId::U16) => first_data_difference::<u16, _, _>(left, right, options)?,
+        Some(NativeTypeId::U32) => first_data_difference::<u32, _, _>(left, right, options)?,
+        Some(NativeTypeId::U64) => first_data_difference::<u64, _, _>(left, right, options)?,
+        Some(NativeTypeId::F32) => first_data_difference::<f32, _, _>(left, right, options)?,
+        Some(NativeTypeId::F64) => first_data_difference::<f64, _, _>(left, right, options)?,
+        Some(NativeTypeId::Timestamp) => {
+            first_data_difference::<Timestamp, _, _>(left, right, options)?
+        }
+        None => {
+            differences.push(Difference::DataNotCompared {
+                group: group_name.to_string(),
+                channel: channel_name.to_string(),
+                data_type,
+            });
+            return Ok(());
+        }
+    };
+    if let Some(first_index) = first_difference {
+        differences.push(Difference::Data {
+            group: group_name.to_string(),
+            channel: channel_name.to_string(),
+            first_index,
+        });
+    }
+    Ok(())
+}
+
+/// Compare the data of two channels chunk by chunk, returning the index of the
+/// first differing value within the overlapping length of the channels.
+fn first_data_difference<T: DiffValue, A: Read + Seek, B: Read + Seek>(
+    left: &Channel<A>,
+    right: &Channel<B>,
+    options: &DiffOptions,
+) -> Result<Option<u64>> {
+    let length = std::cmp::min(left.len(), right.len());
+    let chunk_size = options
+        .chunk_size
+        .unwrap_or_else(|| left.chunk_config().values(std::mem::size_of::<T>()))
+        .max(1);
+    let mut left_buffer = left.scratch().take::<T>(chunk_size);
+    let mut right_buffer = right.scratch().take::<T>(chunk_size);
+    let mut offset = 0;
+    while offset < length {
+        let count = std::cmp::min(chunk_size as u64, length - offset) as usize;
+        let left_read = left.read_data(offset, &mut left_buffer[..count])?;
+        let right_read = right.read_data(offset, &mut right_buffer[..count])?;
+        let count = std::cmp::min(left_read, right_read);
+        if count == 0 {
+            break;
+        }
+        let mismatch = left_buffer[..count]
+            .iter()
+            .zip(right_buffer[..count].iter())
+            .position(|(l, r)| !l.matches(r, options));
+        if let Some(index) = mismatch {
+            return Ok(Some(offset + index as u64));
+        }
+        offset += count as u64;
+    }
+    Ok(None)
+}
+
+fn diff_properties(
+    object: DiffObject,
+    left: &[TdmsProperty],
+    right: &[TdmsProperty],
+    options: &DiffOptions,
+    differences: &mut Vec<Difference>,
+) {
+    let ignored = |name: &str| options.ignore_properties.iter().any(|n| n == name);
+    for left_property in left.iter().filter(|p| !ignored(&p.name)) {
+        let right_value = right
+            .iter()
+            .find(|p| p.name == left_property.name)
+            .map(|p| &p.value);
+        if right_value != Some(&left_property.value) {
+            differences.push(Difference::Property {
+                object: object.clone(),
+                name: left_property.name.to_string(),
+                left: Some(left_property.value.clone()),
+                right: right_value.cloned(),
+            });
+        }
+    }
+    for right_property in right.iter().filter(|p| !ignored(&p.name)) {
+        if !left.iter().any(|p| p.name == right_property.name) {
+            differences.push(Difference::Property {
+                object: object.clone(),
+                name: right_property.name.to_string(),
+                left: None,
+                right: Some(right_property.value.clone()),
+            });
+        }
+    }
+}
+
+/// A channel data value that can be compared between files
+trait DiffValue: NativeType + Clone + Default {
+    fn matches(&self, other: &Self, options: &DiffOptions) -> bool;
+}
+
+macro_rules! exact_diff_value {
+    ($($t:ty),*) => {
+        $(
+            impl DiffValue for $t {
+                fn matches(&self, other: &Self, _options: &DiffOptions) -> bool {
+                    self == other
+                }
+            }
+        )*
+    };
+}
+
+exact_diff_value!(i8, i16, i32, i64, u8, u16, u32, u64, Timestamp);
+
+impl DiffValue for f32 {
+    fn matches(&self, other: &Self, options: &DiffOptions) -> bool {
+        floats_match(*self as f64, *other as f64, options)
+    }
+}
+
+impl DiffValue for f64 {
+    fn matches(&self, other: &Self, options: &DiffOptions) -> bool {
+        floats_match(*self, *other, options)
+    }
+}
+
+fn floats_match(left: f64, right: f64, options: &DiffOptions) -> bool {
+    if left == right || (left.is_nan() && right.is_nan()) {
+        return true;
+    }
+    let difference = (left - right).abs();
+    let scale = left.abs().max(right.abs());
+    difference <= options.absolute_tolerance + options.relative_tolerance * scale
+}
+
+impl fmt::Display for DiffSide {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DiffSide::Left => write!(f, "left"),
+            DiffSide::Right => write!(f, "right"),
+        }
+    }
+}
+
+impl fmt::Display for DiffObject {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DiffObject::Root => write!(f, "/"),
+            DiffObject::Group(ref group) => write!(f, "{}", group),
+            DiffObject::Channel(ref group, ref channel) => write!(f, "{} / {}", group, channel),
+        }
+    }
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Difference::GroupOnlyIn { side, ref group } => {
+                write!(f, "Group {} only exists in {} file", group, side)
+            }
+            Difference::ChannelOnlyIn {
+                side,
+                ref group,
+                ref channel,
+            } => write!(
+                f,
+                "Channel {} / {} only exists in {} file",
+                group, channel, side
+            ),
+            Difference::DataType {
+                ref group,
+                ref channel,
+                left,
+                right,
+            } => write!(
+                f,
+                "Channel {} / {} data type differs: {:?} != {:?}",
+                group, channel, left, right
+            ),
+            Difference::Length {
+                ref group,
+                ref channel,
+                left,
+                right,
+            } => write!(
+                f,
+                "Channel {} / {} length differs: {} != {}",
+                group, channel, left, right
+            ),
+            Difference::Property {
+                ref object,
+                ref name,
+                ref left,
+                ref right,
+            } => match (left, right) {
+                (Some(left), Some(right)) => write!(
+                    f,
+                    "Property {} of {} differs: {} != {}",
+                    name, object, left, right
+                ),
+                (Some(_), None) => write!(
+                    f,
+                    "Property {} of {} only exists in left file",
+                    name, object
+                ),
+                _ => write!(
+                    f,
+                    "Property {} of {} only exists in right file",
+                    name, object
+                ),
+            },
+            Difference::Data {
+                ref group,
+                ref channel,
+                first_index,
+            } => write!(
+                f,
+                "Channel {} / {} data differs, first at index {}",
+                group, channel, first_index
+            ),
+            Difference::DataNotCompared {
+                ref group,
+                ref channel,
+                data_type,
+            } => write!(
+                f,
+                "Channel {} / {} data of type {:?} was not compared",
+                group, channel, data_type
+            ),
+        }
+    }
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.differences.is_empty() {
+            return write!(f, "Files are equivalent");
+        }
+        writeln!(f, "Found {} difference(s):", self.differences.len())?;
+        for difference in self.differences.iter() {
+            writeln!(f, "  {}", difference)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn floats_match_within_absolute_tolerance() {
+        let options = DiffOptions {
+            absolute_tolerance: 1e-9,
+            ..DiffOptions::default()
+        };
+
+        assert!(floats_match(1.0, 1.0 + 1e-10, &options));
+        assert!(!floats_match(1.0, 1.0 + 1e-8, &options));
+    }
+
+    #[test]
+    fn floats_match_within_relative_tolerance() {
+        let options = DiffOptions {
+            relative_tolerance: 1e-6,
+            ..DiffOptions::default()
+        };
+
+        assert!(floats_match(1e6, 1e6 + 0.5, &options));
+        assert!(!floats_match(1e6, 1e6 + 2.0, &options));
+    }
+
+    #[test]
+    fn nan_values_match() {
+        let options = DiffOptions::default();
+
+        assert!(floats_match(f64::NAN, f64::NAN, &options));
+        assert!(!floats_match(f64::NAN, 0.0, &options));
+    }
+}
@@ -1,20 +1,203 @@
-/// All possible errors that may be returned when reading a TDMS file
+use crate::names::format_suggestions;
+use crate::types::TdsType;
+
+/// All possible errors that may be returned when reading a TDMS file.
+/// New variants may be added in future versions, so matches need a wildcard arm.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum TdmsReadError {
-    /// Invalid data format
-    TdmsError(String),
+    /// An error that doesn't have a more specific variant
+    Other(String),
+    /// The file contains data that doesn't follow the TDMS format
+    InvalidData(String),
+    /// Data was requested as a type that doesn't match the channel's data type
+    TypeMismatch {
+        /// The channel's data type
+        data_type: TdsType,
+        /// What the data was requested as, eg. "strings"
+        requested: String,
+    },
+    /// The file or a requested operation uses a feature that isn't supported
+    Unsupported(String),
+    /// An argument or option passed to a function is invalid
+    InvalidArgument(String),
+    /// An object doesn't have a property that the operation needs
+    MissingProperty {
+        path: String,
+        property: &'static str,
+    },
+    /// Input files can't be combined as their structure or data types differ
+    Incompatible(String),
+    /// A metadata cache file can't be used and the metadata needs reading from the file
+    InvalidCache(String),
     /// An IO error reading the underlying file
     IoError(std::io::Error),
     /// An error decoding UTF-8 strings
     Utf8Error(std::string::FromUtf8Error),
+    /// The read was cancelled using a cancellation token
+    Cancelled,
+    /// An error opening or reading the file at a path
+    PathError(std::path::PathBuf, Box<TdmsReadError>),
+    /// The file is not a TDMS file, and may be in another format
+    NotATdmsFile(DetectedFormat),
+    /// A .tdms_index file was opened from a path rather than the TDMS file it indexes
+    IndexFileProvided {
+        /// Path of the index file that was opened
+        index_path: std::path::PathBuf,
+        /// Path of the TDMS file the index belongs to, which may not exist
+        data_path: std::path::PathBuf,
+    },
+    /// The file at a path couldn't be opened for reading because another program,
+    /// such as LabVIEW while logging, has it open without sharing reads
+    FileLocked(std::path::PathBuf),
+    /// A segment has a TDMS format version that can't be read
+    UnsupportedVersion(u32),
+    /// A segment has table of contents flags with unknown meanings and
+    /// `ReadOptions::strict_toc_flags` is set
+    UnknownTocFlags(u32),
+    /// A segment's metadata ended before it was completely read, usually because
+    /// the file was truncated
+    TruncatedMetadata {
+        /// Position in the file of the start of the segment
+        segment_position: u64,
+        /// What was being read when the metadata ended, eg. "object path string"
+        parsing: &'static str,
+    },
+    /// An object has raw data with more than one dimension, which can't be read
+    UnsupportedDimension { path: String, dimension: u32 },
+    /// A channel has DAQmx raw data in a layout or with a scale that can't be read
+    UnsupportedDaqmxData { path: String, reason: &'static str },
+    /// A group has more values than fit in an Excel worksheet when exporting
+    /// without `XlsxExportOptions::split_sheets`
+    TooManyRows {
+        group: String,
+        rows: u64,
+        max_rows: u64,
+    },
+    /// A row of CSV data being imported is malformed
+    MalformedCsv {
+        /// Line number of the start of the row, counting from 1
+        line: u64,
+        message: String,
+    },
+    /// An error writing an Excel workbook
+    #[cfg(feature = "xlsx")]
+    XlsxError(rust_xlsxwriter::XlsxError),
+    /// There is no group with the requested name
+    NoSuchGroup {
+        name: String,
+        /// Names of groups with similar names, which may have been meant instead
+        similar_names: Vec<String>,
+    },
+    /// There is no channel with the requested name in the group
+    NoSuchChannel {
+        group: String,
+        name: String,
+        /// Names of channels in the group with similar names
+        similar_names: Vec<String>,
+    },
+}
+
+/// The format of a file that is not a TDMS file, detected from its first bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectedFormat {
+    /// The file is empty
+    Empty,
+    /// A TDMS index file, which only contains the metadata of a TDMS file
+    TdmsIndex,
+    /// An XML based TDM file
+    Tdm,
+    /// An XML file other than a TDM file
+    Xml,
+    /// A zip archive
+    Zip,
+    /// The format was not recognised
+    Unknown,
+}
+
+impl DetectedFormat {
+    /// Detect the format of a file from its first bytes
+    pub fn detect(bytes: &[u8]) -> DetectedFormat {
+        if bytes.is_empty() {
+            return DetectedFormat::Empty;
+        }
+        if bytes.starts_with(b"TDSh") {
+            return DetectedFormat::TdmsIndex;
+        }
+        if bytes.starts_with(b"PK") {
+            return DetectedFormat::Zip;
+        }
+        let text = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+        let start = text
+            .iter()
+            .position(|byte| !byte.is_ascii_whitespace())
+            .unwrap_or(text.len());
+        if text[start..].starts_with(b"<") {
+            let text = String::from_utf8_lossy(text).to_ascii_lowercase();
+            if text.contains("usi:tdm") || text.contains("<tdm") {
+                return DetectedFormat::Tdm;
+            }
+            if text[start..].starts_with("<?xml") {
+                return DetectedFormat::Xml;
+            }
+        }
+        DetectedFormat::Unknown
+    }
+}
+
+impl std::fmt::Display for DetectedFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            DetectedFormat::Empty => write!(f, "the file is empty"),
+            DetectedFormat::TdmsIndex => write!(
+                f,
+                "this looks like a .tdms_index file, open the .tdms file instead"
+            ),
+            DetectedFormat::Tdm => write!(
+                f,
+                "this looks like a .tdm file, which uses the XML based TDM format rather than TDMS"
+            ),
+            DetectedFormat::Xml => write!(f, "this looks like an XML file"),
+            DetectedFormat::Zip => write!(
+                f,
+                "this looks like a zip archive, extract the .tdms file from it first"
+            ),
+            DetectedFormat::Unknown => {
+                write!(f, "the file does not start with a TDMS segment header")
+            }
+        }
+    }
 }
 
 impl std::error::Error for TdmsReadError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match *self {
-            TdmsReadError::TdmsError(_) => None,
+            TdmsReadError::Other(_) => None,
+            TdmsReadError::InvalidData(_) => None,
+            TdmsReadError::TypeMismatch { .. } => None,
+            TdmsReadError::Unsupported(_) => None,
+            TdmsReadError::InvalidArgument(_) => None,
+            TdmsReadError::MissingProperty { .. } => None,
+            TdmsReadError::Incompatible(_) => None,
+            TdmsReadError::InvalidCache(_) => None,
             TdmsReadError::IoError(ref e) => Some(e),
             TdmsReadError::Utf8Error(ref e) => Some(e),
+            TdmsReadError::Cancelled => None,
+            TdmsReadError::PathError(_, ref e) => Some(e.as_ref()),
+            TdmsReadError::NotATdmsFile(_) => None,
+            TdmsReadError::IndexFileProvided { .. } => None,
+            TdmsReadError::FileLocked(_) => None,
+            TdmsReadError::UnsupportedVersion(_) => None,
+            TdmsReadError::UnknownTocFlags(_) => None,
+            TdmsReadError::TruncatedMetadata { .. } => None,
+            TdmsReadError::UnsupportedDimension { .. } => None,
+            TdmsReadError::UnsupportedDaqmxData { .. } => None,
+            TdmsReadError::TooManyRows { .. } => None,
+            TdmsReadError::MalformedCsv { .. } => None,
+            #[cfg(feature = "xlsx")]
+            TdmsReadError::XlsxError(ref e) => Some(e),
+            TdmsReadError::NoSuchGroup { .. } => None,
+            TdmsReadError::NoSuchChannel { .. } => None,
         }
     }
 }
@@ -22,13 +205,127 @@ impl std::error::Error for TdmsReadError {
 impl std::fmt::Display for TdmsReadError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match *self {
-            TdmsReadError::TdmsError(ref s) => write!(f, "{}", s),
-            TdmsReadError::IoError(_) => write!(f, "IO error"),
-            TdmsReadError::Utf8Error(_) => write!(f, "UTF-8 decode error"),
+            TdmsReadError::Other(ref s) => write!(f, "{}", s),
+            TdmsReadError::InvalidData(ref s) => write!(f, "{}", s),
+            TdmsReadError::TypeMismatch {
+                data_type,
+                ref requested,
+            } => {
+                write!(
+                    f,
+                    "Cannot read data of type {:?} as {}",
+                    data_type, requested
+                )?;
+                if data_type.is_integer() || data_type.is_float() {
+                    write!(
+                        f,
+                        ". Use Channel::read_data_as_f64 or ReadBuilder::convert_lossy \
+                        to convert numeric data to f64"
+                    )?;
+                }
+                Ok(())
+            }
+            TdmsReadError::Unsupported(ref s) => write!(f, "{}", s),
+            TdmsReadError::InvalidArgument(ref s) => write!(f, "{}", s),
+            TdmsReadError::MissingProperty { ref path, property } => {
+                write!(f, "Object {} has no {} property", path, property)
+            }
+            TdmsReadError::Incompatible(ref s) => write!(f, "{}", s),
+            TdmsReadError::InvalidCache(ref s) => write!(f, "Invalid metadata cache: {}", s),
+            TdmsReadError::IoError(ref e) => write!(f, "IO error: {}", e),
+            TdmsReadError::Utf8Error(ref e) => write!(f, "UTF-8 decode error: {}", e),
+            TdmsReadError::Cancelled => write!(f, "Read was cancelled"),
+            TdmsReadError::PathError(ref path, ref e) => write!(f, "{}: {}", path.display(), e),
+            TdmsReadError::NotATdmsFile(ref format) => write!(f, "Not a TDMS file: {}", format),
+            TdmsReadError::IndexFileProvided {
+                ref index_path,
+                ref data_path,
+            } => write!(
+                f,
+                "{} is a TDMS index file, which only contains metadata. Open {} instead",
+                index_path.display(),
+                data_path.display()
+            ),
+            TdmsReadError::FileLocked(ref path) => write!(
+                f,
+                "{} is locked by another program, which doesn't allow reading it while it is open",
+                path.display()
+            ),
+            TdmsReadError::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported TDMS version {}, only versions 4712 and 4713 can be read",
+                version
+            ),
+            TdmsReadError::UnknownTocFlags(bits) => {
+                write!(f, "Segment has unknown table of contents flags {:#x}", bits)
+            }
+            TdmsReadError::TruncatedMetadata {
+                segment_position,
+                parsing,
+            } => write!(
+                f,
+                "Metadata of segment at position {} ended unexpectedly while reading the {}",
+                segment_position, parsing
+            ),
+            TdmsReadError::UnsupportedDimension {
+                ref path,
+                dimension,
+            } => write!(
+                f,
+                "Object {} has raw data with dimension {}, only one dimensional data can be read",
+                path, dimension
+            ),
+            TdmsReadError::UnsupportedDaqmxData { ref path, reason } => write!(
+                f,
+                "Channel {} has DAQmx raw data that can't be read, {}",
+                path, reason
+            ),
+            TdmsReadError::TooManyRows {
+                ref group,
+                rows,
+                max_rows,
+            } => write!(
+                f,
+                "Group {} has {} rows, but an Excel worksheet can only hold {} rows of data. \
+                Set XlsxExportOptions::split_sheets to split it over numbered worksheets",
+                group, rows, max_rows
+            ),
+            TdmsReadError::MalformedCsv { line, ref message } => {
+                write!(f, "Malformed CSV row at line {}: {}", line, message)
+            }
+            #[cfg(feature = "xlsx")]
+            TdmsReadError::XlsxError(ref e) => write!(f, "Error writing Excel workbook: {}", e),
+            TdmsReadError::NoSuchGroup {
+                ref name,
+                ref similar_names,
+            } => write!(
+                f,
+                "No group named {:?}{}",
+                name,
+                format_suggestions(similar_names)
+            ),
+            TdmsReadError::NoSuchChannel {
+                ref group,
+                ref name,
+                ref similar_names,
+            } => write!(
+                f,
+                "No channel named {:?} in group {:?}{}",
+                name,
+                group,
+                format_suggestions(similar_names)
+            ),
         }
     }
 }
 
+#[cfg(feature = "xlsx")]
+impl From<rust_xlsxwriter::XlsxError> for TdmsReadError {
+    fn from(err: rust_xlsxwriter::XlsxError) -> TdmsReadError {
+        TdmsReadError::XlsxError(err)
+    }
+}
+
 impl From<std::io::Error> for TdmsReadError {
     fn from(err: std::io::Error) -> TdmsReadError {
         TdmsReadError::IoError(err)
@@ -42,3 +339,33 @@ impl From<std::string::FromUtf8Error> for TdmsReadError {
 }
 
 pub type Result<T> = std::result::Result<T, TdmsReadError>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::error::Error;
+
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+    #[test]
+    fn error_is_send_and_sync() {
+        assert_send_sync::<TdmsReadError>();
+    }
+
+    #[test]
+    fn source_is_underlying_error() {
+        let error = TdmsReadError::from(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "eof",
+        ));
+        let source = error.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::UnexpectedEof
+        );
+
+        let error = TdmsReadError::PathError("file.tdms".into(), Box::new(error));
+        assert!(error.source().unwrap().is::<TdmsReadError>());
+        assert!(TdmsReadError::Cancelled.source().is_none());
+    }
+}
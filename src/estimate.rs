@@ -0,0 +1,54 @@
+use crate::types::TdsType;
+use crate::Channel;
+use std::io::{Read, Seek};
+use std::mem::size_of;
+
+/// Length in bytes assumed for each value formatted as a string, when reading a channel
+/// that isn't stored as strings as strings
+const FORMATTED_VALUE_LENGTH: u64 = 16;
+
+/// Size of the offset stored before the string data of a segment for each string value
+const STRING_OFFSET_SIZE: u64 = 4;
+
+/// Estimate the memory needed to read all values of a channel as the target type,
+/// or its own type if the target is None
+pub(crate) fn channel_read_bytes<R: Read + Seek>(
+    channel: &Channel<R>,
+    target: Option<TdsType>,
+) -> u64 {
+    let data_type = match channel.data_type() {
+        Some(data_type) => data_type,
+        None => return 0,
+    };
+    let length = channel.len();
+    match target.unwrap_or(data_type) {
+        TdsType::String => {
+            let string_bytes = if data_type == TdsType::String {
+                stored_string_bytes(channel)
+            } else {
+                length.saturating_mul(FORMATTED_VALUE_LENGTH)
+            };
+            length
+                .saturating_mul(size_of::<String>() as u64)
+                .saturating_add(string_bytes)
+        }
+        // Types without a fixed size, such as DAQmx raw data, are read as scaled f64 values
+        target => length.saturating_mul(target.size().unwrap_or(size_of::<f64>()) as u64),
+    }
+}
+
+/// Number of bytes of string data stored for a string channel, not including the offsets
+/// stored before the data of each segment
+fn stored_string_bytes<R: Read + Seek>(channel: &Channel<R>) -> u64 {
+    // Data with a layout that can't be read would fail to be read rather than use memory
+    channel
+        .data_extents()
+        .unwrap_or_default()
+        .iter()
+        .map(|extent| {
+            extent
+                .byte_len
+                .saturating_sub(extent.values * STRING_OFFSET_SIZE)
+        })
+        .sum()
+}
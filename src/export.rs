@@ -0,0 +1,264 @@
+use crate::error::{Result, TdmsReadError};
+use crate::progress::ReadControl;
+use crate::timestamp::Timestamp;
+use crate::types::{NativeType, NativeTypeId, TdsType};
+use crate::waveform::WaveformTiming;
+use crate::Channel;
+use std::fmt::Display;
+use std::io::{Read, Seek, Write};
+
+/// The column written before the channel values when exporting to CSV
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvTimeColumn {
+    /// No time column is written
+    None,
+
+    /// Time in seconds relative to the waveform start time
+    Relative,
+
+    /// Absolute time of each value as an RFC 3339 timestamp
+    Absolute,
+}
+
+/// Options for exporting channel data to CSV
+#[derive(Debug, Clone)]
+pub struct CsvExportOptions {
+    /// The column written before the channel values. Times are computed from the
+    /// waveform timing shared by all channels. If any channel has no waveform timing
+    /// or timing that differs from the others, the sample index is written instead.
+    pub time_column: CsvTimeColumn,
+
+    /// Index of the first value of each channel to export
+    pub offset: u64,
+
+    /// Maximum number of values of each channel to export, or None to export
+    /// all values from the offset onwards
+    pub length: Option<u64>,
+
+    /// Number of values read from each channel at a time, or None to use the chunk
+    /// configuration of the first channel's file, shared between all channels
+    pub chunk_size: Option<usize>,
+}
+
+impl Default for CsvExportOptions {
+    fn default() -> CsvExportOptions {
+        CsvExportOptions {
+            time_column: CsvTimeColumn::None,
+            offset: 0,
+            length: None,
+            chunk_size: None,
+        }
+    }
+}
+
+/// Export channel data as CSV, with one column per channel and one row per value.
+///
+/// Channels may have different lengths, in which case the shorter channels have empty
+/// values in the later rows. Data is read in chunks so channels of any size can be exported.
+/// Returns the number of rows written, not including the header.
+pub fn export_csv<R: Read + Seek, W: Write>(
+    channels: &[Channel<R>],
+    writer: W,
+    options: &CsvExportOptions,
+) -> Result<u64> {
+    export_csv_with_control(channels, writer, options, &mut ReadControl::new())
+}
+
+/// Export channel data as CSV as with `export_csv`, reporting progress in rows
+/// and checking for cancellation using the given read control
+pub fn export_csv_with_control<R: Read + Seek, W: Write>(
+    channels: &[Channel<R>],
+    writer: W,
+    options: &CsvExportOptions,
+    control: &mut ReadControl,
+) -> Result<u64> {
+    for channel in channels {
+        if let Some(data_type) = channel.data_type() {
+            if !is_supported(data_type) {
+                return Err(TdmsReadError::Unsupported(format!(
+                    "Cannot export channel {} / {} as its data type {:?} is not supported",
+                    channel.group_name(),
+                    channel.name(),
+                    data_type
+                )));
+            }
+        }
+    }
+
+    let mut writer = writer;
+    let timing = WaveformTiming::common(channels.iter().map(|channel| channel.waveform_timing()));
+    let time_column = match (options.time_column, timing) {
+        (CsvTimeColumn::None, _) => None,
+        (time_column, Some(timing)) => Some(TimeColumn::Time(time_column, timing)),
+        (_, None) => Some(TimeColumn::Index),
+    };
+
+    let mut header = Vec::new();
+    match time_column {
+        Some(TimeColumn::Time(..)) => header.push(String::from("Time")),
+        Some(TimeColumn::Index) => header.push(String::from("Index")),
+        None => {}
+    }
+    for channel in channels {
+        header.push(format!("{}/{}", channel.group_name(), channel.name()));
+    }
+    write_row(&mut writer, &header)?;
+
+    let channel_rows: Vec<u64> = channels
+        .iter()
+        .map(|channel| {
+            let available = channel.len().saturating_sub(options.offset);
+            match options.length {
+                Some(length) => available.min(length),
+                None => available,
+            }
+        })
+        .collect();
+    let total_rows = channel_rows.iter().cloned().max().unwrap_or(0);
+    let chunk_size = match (options.chunk_size, channels.first()) {
+        (Some(chunk_size), _) => chunk_size,
+        (None, Some(channel)) => channel
+            .chunk_config()
+            .values(std::mem::size_of::<f64>() * channels.len()),
+        (None, None) => 1,
+    }
+    .max(1) as u64;
+
+    let mut columns: Vec<Vec<String>> = vec![Vec::new(); channels.len()];
+    let mut row = Vec::with_capacity(channels.len() + 1);
+    let mut rows_written = 0;
+    while rows_written < total_rows {
+        if control.is_cancelled() {
+            return Err(TdmsReadError::Cancelled);
+        }
+        let chunk_rows = (total_rows - rows_written).min(chunk_size);
+        for ((channel, column), rows) in channels
+            .iter()
+            .zip(columns.iter_mut())
+            .zip(channel_rows.iter())
+        {
+            column.clear();
+            let count = rows.saturating_sub(rows_written).min(chunk_rows);
+            if count > 0 {
+                read_formatted(
+                    channel,
+                    options.offset + rows_written,
+                    count as usize,
+                    column,
+                )?;
+            }
+        }
+        for row_index in 0..chunk_rows as usize {
+            row.clear();
+            let index = options.offset + rows_written + row_index as u64;
+            match time_column {
+                Some(TimeColumn::Time(CsvTimeColumn::Absolute, timing)) => row.push(
+                    timing
+                        .absolute_timestamp(index)
+                        .and_then(|time| time.to_rfc3339())
+                        .unwrap_or_default(),
+                ),
+                Some(TimeColumn::Time(_, timing)) => {
+                    row.push(timing.relative_time(index).to_string())
+                }
+                Some(TimeColumn::Index) => row.push(index.to_string()),
+                None => {}
+            }
+            for column in columns.iter() {
+                row.push(column.get(row_index).cloned().unwrap_or_default());
+            }
+            write_row(&mut writer, &row)?;
+        }
+        rows_written += chunk_rows;
+        control.report_progress(rows_written, total_rows);
+    }
+    writer.flush()?;
+    Ok(rows_written)
+}
+
+enum TimeColumn {
+    Time(CsvTimeColumn, WaveformTiming),
+    Index,
+}
+
+fn is_supported(data_type: TdsType) -> bool {
+    data_type == TdsType::String || data_type.native_type().is_some()
+}
+
+/// Read `count` values of a channel starting at `offset`, formatted as strings
+fn read_formatted<R: Read + Seek>(
+    channel: &Channel<R>,
+    offset: u64,
+    count: usize,
+    column: &mut Vec<String>,
+) -> Result<()> {
+    let data_type = match channel.data_type() {
+        Some(data_type) => data_type,
+        None => return Ok(()),
+    };
+    if data_type == TdsType::String {
+        column.resize(count, String::new());
+        let values_read = channel.read_strings(offset, column)?;
+        column.truncate(values_read);
+        return Ok(());
+    }
+    match data_type.native_type() {
+        Some(NativeTypeId::I8) => read_displayed::<i8, _>(channel, offset, count, column),
+        Some(NativeTypeId::I16) => read_displayed::<i16, _>(channel, offset, count, column),
+        Some(NativeTypeId::I32) => read_displayed::<i32, _>(channel, offset, count, column),
+        Some(NativeTypeId::I64) => read_displayed::<i64, _>(channel, offset, count, column),
+        Some(NativeTypeId::U8) => read_displayed::<u8, _>(channel, offset, count, column),
+        Some(NativeTypeId::U16) => read_displayed::<u16, _>(channel, offset, count, column),
+        Some(NativeTypeId::U32) => read_displayed::<u32, _>(channel, offset, count, column),
+        Some(NativeTypeId::U64) => read_displayed::<u64, _>(channel, offset, count, column),
+        Some(NativeTypeId::F32) => read_displayed::<f32, _>(channel, offset, count, column),
+        Some(NativeTypeId::F64) => read_displayed::<f64, _>(channel, offset, count, column),
+        Some(NativeTypeId::Timestamp) => {
+            let mut buffer = channel.scratch().take::<Timestamp>(count);
+            let values_read = channel.read_data(offset, &mut buffer[..])?;
+            column.extend(
+                buffer[..values_read]
+                    .iter()
+                    .map(|timestamp| timestamp.to_rfc3339().unwrap_or_default()),
+            );
+            Ok(())
+        }
+        None => Err(TdmsReadError::Unsupported(format!(
+            "Unsupported data type for CSV export: {:?}",
+            data_type
+        ))),
+    }
+}
+
+fn read_displayed<T: NativeType + Display + Copy + Default, R: Read + Seek>(
+    channel: &Channel<R>,
+    offset: u64,
+    count: usize,
+    column: &mut Vec<String>,
+) -> Result<()> {
+    let mut buffer = channel.scratch().take::<T>(count);
+    let values_read = channel.read_data(offset, &mut buffer[..])?;
+    column.extend(buffer[..values_read].iter().map(|value| value.to_string()));
+    Ok(())
+}
+
+fn write_row<W: Write>(writer: &mut W, fields: &[String]) -> Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        write_field(writer, field)?;
+    }
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Write a field, quoting it if it contains characters with special meaning in CSV
+fn write_field<W: Write>(writer: &mut W, field: &str) -> Result<()> {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+    } else {
+        writer.write_all(field.as_bytes())?;
+    }
+    Ok(())
+}
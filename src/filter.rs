@@ -0,0 +1,456 @@
+use crate::error::{Result, TdmsReadError};
+use crate::properties::{TdmsProperty, TdmsValue};
+use std::cmp::Ordering;
+
+/// A condition on an object's properties, for selecting channels by their properties.
+///
+/// Filters are parsed from expressions comparing property names with string, number or
+/// boolean values, eg. `unit_string == 'bar' && wf_increment < 0.001`. The comparison
+/// operators `==`, `!=`, `<`, `<=`, `>` and `>=` can be combined with `&&` and `||`,
+/// where `&&` binds more tightly, and grouped with parentheses. Strings are compared
+/// lexically and numbers compare with any integer or floating point property.
+/// A comparison with a missing property, or a property of a different type, is false.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyFilter {
+    expression: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    Compare {
+        name: String,
+        operator: Operator,
+        value: Literal,
+    },
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    String(String),
+    Number(f64),
+    Boolean(bool),
+}
+
+impl PropertyFilter {
+    /// Parse a filter expression
+    pub fn parse(expression: &str) -> Result<PropertyFilter> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+        let expression = parser.parse_or()?;
+        match parser.next() {
+            None => Ok(PropertyFilter { expression }),
+            Some(token) => Err(filter_error(
+                token.offset,
+                &format!("unexpected {}", token.kind.describe()),
+            )),
+        }
+    }
+
+    /// Whether the properties satisfy the filter. Properties are only looked up
+    /// when needed to decide the result.
+    pub fn matches(&self, properties: &[TdmsProperty]) -> bool {
+        self.expression.evaluate(properties)
+    }
+}
+
+impl Expression {
+    fn evaluate(&self, properties: &[TdmsProperty]) -> bool {
+        match self {
+            Expression::Compare {
+                name,
+                operator,
+                value,
+            } => properties
+                .iter()
+                .find(|property| property.name.as_str() == name)
+                .and_then(|property| compare(&property.value, value))
+                .is_some_and(|ordering| operator.accepts(ordering)),
+            Expression::And(left, right) => left.evaluate(properties) && right.evaluate(properties),
+            Expression::Or(left, right) => left.evaluate(properties) || right.evaluate(properties),
+        }
+    }
+}
+
+impl Operator {
+    fn accepts(self, ordering: Ordering) -> bool {
+        match self {
+            Operator::Equal => ordering == Ordering::Equal,
+            Operator::NotEqual => ordering != Ordering::Equal,
+            Operator::Less => ordering == Ordering::Less,
+            Operator::LessOrEqual => ordering != Ordering::Greater,
+            Operator::Greater => ordering == Ordering::Greater,
+            Operator::GreaterOrEqual => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// Order a property value relative to a literal, or None if they can't be compared
+fn compare(value: &TdmsValue, literal: &Literal) -> Option<Ordering> {
+    match literal {
+        Literal::String(literal) => value.as_str().map(|value| value.cmp(literal.as_str())),
+        Literal::Number(literal) => value.as_f64()?.partial_cmp(literal),
+        Literal::Boolean(literal) => value.as_bool().map(|value| value.cmp(literal)),
+    }
+}
+
+struct Token {
+    kind: TokenKind,
+    /// Character offset of the token within the expression
+    offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Name(String),
+    Literal(Literal),
+    Operator(Operator),
+    And,
+    Or,
+    OpenParen,
+    CloseParen,
+}
+
+impl TokenKind {
+    fn describe(&self) -> String {
+        match self {
+            TokenKind::Name(name) => format!("property name {}", name),
+            TokenKind::Literal(_) => String::from("value"),
+            TokenKind::Operator(_) => String::from("comparison operator"),
+            TokenKind::And => String::from("&&"),
+            TokenKind::Or => String::from("||"),
+            TokenKind::OpenParen => String::from("("),
+            TokenKind::CloseParen => String::from(")"),
+        }
+    }
+}
+
+fn filter_error(offset: usize, message: &str) -> TdmsReadError {
+    TdmsReadError::InvalidArgument(format!(
+        "Invalid property filter at character {}: {}",
+        offset, message
+    ))
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut offset = 0;
+    while offset < chars.len() {
+        let start = offset;
+        let next = chars.get(offset + 1).copied();
+        let kind = match chars[offset] {
+            c if c.is_whitespace() => {
+                offset += 1;
+                continue;
+            }
+            '(' => TokenKind::OpenParen,
+            ')' => TokenKind::CloseParen,
+            '&' if next == Some('&') => TokenKind::And,
+            '|' if next == Some('|') => TokenKind::Or,
+            '=' if next == Some('=') => TokenKind::Operator(Operator::Equal),
+            '!' if next == Some('=') => TokenKind::Operator(Operator::NotEqual),
+            '<' if next == Some('=') => TokenKind::Operator(Operator::LessOrEqual),
+            '>' if next == Some('=') => TokenKind::Operator(Operator::GreaterOrEqual),
+            '<' => TokenKind::Operator(Operator::Less),
+            '>' => TokenKind::Operator(Operator::Greater),
+            quote @ ('\'' | '"') => {
+                let length = chars[offset + 1..]
+                    .iter()
+                    .position(|c| *c == quote)
+                    .ok_or_else(|| filter_error(start, "unterminated string"))?;
+                let value: String = chars[offset + 1..offset + 1 + length].iter().collect();
+                offset += length + 2;
+                tokens.push(Token {
+                    kind: TokenKind::Literal(Literal::String(value)),
+                    offset: start,
+                });
+                continue;
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                let length = chars[offset..]
+                    .iter()
+                    .enumerate()
+                    .take_while(|(i, c)| {
+                        c.is_ascii_alphanumeric()
+                            || **c == '.'
+                            // Signs are allowed at the start and in an exponent
+                            || ((**c == '-' || **c == '+')
+                                && (*i == 0 || matches!(chars[offset + i - 1], 'e' | 'E')))
+                    })
+                    .count();
+                let text: String = chars[offset..offset + length].iter().collect();
+                let value = text
+                    .parse::<f64>()
+                    .map_err(|_| filter_error(start, &format!("invalid number {}", text)))?;
+                offset += length;
+                tokens.push(Token {
+                    kind: TokenKind::Literal(Literal::Number(value)),
+                    offset: start,
+                });
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let length = chars[offset..]
+                    .iter()
+                    .take_while(|c| c.is_alphanumeric() || **c == '_' || **c == '.')
+                    .count();
+                let name: String = chars[offset..offset + length].iter().collect();
+                offset += length;
+                let kind = match name.as_str() {
+                    "true" => TokenKind::Literal(Literal::Boolean(true)),
+                    "false" => TokenKind::Literal(Literal::Boolean(false)),
+                    _ => TokenKind::Name(name),
+                };
+                tokens.push(Token {
+                    kind,
+                    offset: start,
+                });
+                continue;
+            }
+            c => return Err(filter_error(start, &format!("unexpected character {}", c))),
+        };
+        offset += match kind {
+            TokenKind::OpenParen
+            | TokenKind::CloseParen
+            | TokenKind::Operator(Operator::Less)
+            | TokenKind::Operator(Operator::Greater) => 1,
+            _ => 2,
+        };
+        tokens.push(Token {
+            kind,
+            offset: start,
+        });
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser, where each level of precedence has its own method
+struct Parser<'t> {
+    tokens: &'t [Token],
+    position: usize,
+}
+
+impl<'t> Parser<'t> {
+    fn next(&mut self) -> Option<&'t Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&'t TokenKind> {
+        self.tokens.get(self.position).map(|token| &token.kind)
+    }
+
+    /// The offset to report for an error at the current token
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or_else(|| self.tokens.last())
+            .map_or(0, |token| token.offset)
+    }
+
+    fn parse_or(&mut self) -> Result<Expression> {
+        let mut expression = self.parse_and()?;
+        while self.peek() == Some(&TokenKind::Or) {
+            self.position += 1;
+            let right = self.parse_and()?;
+            expression = Expression::Or(Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    fn parse_and(&mut self) -> Result<Expression> {
+        let mut expression = self.parse_term()?;
+        while self.peek() == Some(&TokenKind::And) {
+            self.position += 1;
+            let right = self.parse_term()?;
+            expression = Expression::And(Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    /// Parse a parenthesized expression or a comparison
+    fn parse_term(&mut self) -> Result<Expression> {
+        let offset = self.offset();
+        match self.next().map(|token| &token.kind) {
+            Some(TokenKind::OpenParen) => {
+                let expression = self.parse_or()?;
+                match self.next().map(|token| &token.kind) {
+                    Some(TokenKind::CloseParen) => Ok(expression),
+                    _ => Err(filter_error(offset, "unmatched (")),
+                }
+            }
+            Some(TokenKind::Name(name)) => {
+                let offset = self.offset();
+                let operator = match self.next().map(|token| &token.kind) {
+                    Some(TokenKind::Operator(operator)) => *operator,
+                    _ => return Err(filter_error(offset, "expected a comparison operator")),
+                };
+                let offset = self.offset();
+                let value = match self.next().map(|token| &token.kind) {
+                    Some(TokenKind::Literal(value)) => value.clone(),
+                    _ => return Err(filter_error(offset, "expected a value")),
+                };
+                Ok(Expression::Compare {
+                    name: name.clone(),
+                    operator,
+                    value,
+                })
+            }
+            Some(kind) => Err(filter_error(
+                offset,
+                &format!("expected a property name but found {}", kind.describe()),
+            )),
+            None => Err(filter_error(offset, "expected a property name")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn properties() -> Vec<TdmsProperty> {
+        vec![
+            TdmsProperty::new("unit_string", TdmsValue::String(String::from("bar"))),
+            TdmsProperty::new("wf_increment", TdmsValue::Float64(0.0001)),
+            TdmsProperty::new("wf_samples", TdmsValue::Int32(1000)),
+            TdmsProperty::new("enabled", TdmsValue::Boolean(true)),
+        ]
+    }
+
+    fn matches(expression: &str) -> bool {
+        PropertyFilter::parse(expression)
+            .unwrap()
+            .matches(&properties())
+    }
+
+    #[test]
+    pub fn compare_strings() {
+        assert!(matches("unit_string == 'bar'"));
+        assert!(matches("unit_string == \"bar\""));
+        assert!(!matches("unit_string != 'bar'"));
+        assert!(matches("unit_string > 'abc'"));
+        assert!(matches("unit_string < 'baz'"));
+        assert!(!matches("unit_string == 'Bar'"));
+    }
+
+    #[test]
+    pub fn compare_numbers() {
+        assert!(matches("wf_increment < 0.001"));
+        assert!(matches("wf_increment == 1e-4"));
+        assert!(matches("wf_samples >= 1000"));
+        assert!(!matches("wf_samples > 1000"));
+        assert!(matches("wf_samples > -1.5"));
+        assert!(matches("wf_samples <= +1000"));
+    }
+
+    #[test]
+    pub fn compare_booleans() {
+        assert!(matches("enabled == true"));
+        assert!(!matches("enabled != true"));
+    }
+
+    #[test]
+    pub fn missing_and_mismatched_properties_are_false() {
+        assert!(!matches("missing == 'bar'"));
+        assert!(!matches("missing != 'bar'"));
+        assert!(!matches("unit_string == 1"));
+        assert!(!matches("wf_samples == '1000'"));
+    }
+
+    #[test]
+    pub fn combine_comparisons() {
+        assert!(matches("unit_string == 'bar' && wf_increment < 0.001"));
+        assert!(!matches("unit_string == 'bar' && wf_increment > 0.001"));
+        assert!(matches("unit_string == 'psi' || wf_samples == 1000"));
+        // && binds more tightly than ||
+        assert!(matches(
+            "wf_samples == 1000 || missing == 1 && missing == 2"
+        ));
+        assert!(!matches(
+            "(wf_samples == 1000 || missing == 1) && missing == 2"
+        ));
+    }
+
+    #[test]
+    pub fn parse_precedence() {
+        let filter = PropertyFilter::parse("a == 1 || b == 2 && c == 3").unwrap();
+        let compare = |name: &str, value: f64| Expression::Compare {
+            name: String::from(name),
+            operator: Operator::Equal,
+            value: Literal::Number(value),
+        };
+        assert_eq!(
+            filter.expression,
+            Expression::Or(
+                Box::new(compare("a", 1.0)),
+                Box::new(Expression::And(
+                    Box::new(compare("b", 2.0)),
+                    Box::new(compare("c", 3.0))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    pub fn invalid_expressions() {
+        let errors = [
+            (
+                "",
+                "Invalid property filter at character 0: expected a property name",
+            ),
+            (
+                "unit_string 'bar'",
+                "Invalid property filter at character 12: expected a comparison operator",
+            ),
+            (
+                "unit_string ==",
+                "Invalid property filter at character 12: expected a value",
+            ),
+            (
+                "unit_string == 'bar",
+                "Invalid property filter at character 15: unterminated string",
+            ),
+            (
+                "(a == 1",
+                "Invalid property filter at character 0: unmatched (",
+            ),
+            (
+                "a == 1 b == 2",
+                "Invalid property filter at character 7: unexpected property name b",
+            ),
+            (
+                "a = 1",
+                "Invalid property filter at character 2: unexpected character =",
+            ),
+            (
+                "a == 1.2.3",
+                "Invalid property filter at character 5: invalid number 1.2.3",
+            ),
+            (
+                "&& a == 1",
+                "Invalid property filter at character 0: expected a property name but found &&",
+            ),
+        ];
+        for (expression, message) in errors.iter() {
+            let error = PropertyFilter::parse(expression).unwrap_err();
+            assert_eq!(error.to_string(), *message, "Parsing {:?}", expression);
+        }
+    }
+}
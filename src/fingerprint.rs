@@ -0,0 +1,76 @@
+use crate::error::Result;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// Number of bytes at the start of a file that are hashed to detect changes to the file.
+/// This covers the lead in and usually all of the metadata of the first segment.
+pub(crate) const HASHED_PREFIX_LENGTH: u64 = 64 * 1024;
+
+/// Identifies the contents of a file, for detecting that a file has changed since
+/// something derived from it was saved
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct FileFingerprint {
+    file_length: u64,
+    /// Modification time of the file as seconds and nanoseconds since the Unix epoch,
+    /// if the file was opened from a path
+    modified: Option<(u64, u32)>,
+    prefix_hash: u64,
+}
+
+impl FileFingerprint {
+    /// Compute the fingerprint of a file, leaving the reader at an unspecified position
+    pub(crate) fn new<R: Read + Seek>(reader: &mut R, path: Option<&Path>) -> Result<Self> {
+        let modified = path
+            .and_then(|path| std::fs::metadata(path).ok())
+            .and_then(|metadata| metadata.modified().ok())
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| (duration.as_secs(), duration.subsec_nanos()));
+        let file_length = reader.seek(SeekFrom::End(0))?;
+        let mut prefix = Vec::new();
+        reader.seek(SeekFrom::Start(0))?;
+        reader
+            .by_ref()
+            .take(HASHED_PREFIX_LENGTH)
+            .read_to_end(&mut prefix)?;
+        Ok(FileFingerprint {
+            file_length,
+            modified,
+            prefix_hash: fnv1a_hash(&prefix),
+        })
+    }
+}
+
+/// 64 bit FNV-1a hash, which unlike the standard library hasher is stable across
+/// Rust versions so can be stored
+pub(crate) fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    pub fn fnv1a_hash_values() {
+        assert_eq!(fnv1a_hash(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a_hash(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a_hash(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn fingerprint_changes_with_contents() {
+        let fingerprint =
+            |bytes: &[u8]| FileFingerprint::new(&mut Cursor::new(bytes.to_vec()), None).unwrap();
+
+        assert_eq!(fingerprint(b"TDSm1234"), fingerprint(b"TDSm1234"));
+        assert_ne!(fingerprint(b"TDSm1234"), fingerprint(b"TDSm1235"));
+        assert_ne!(fingerprint(b"TDSm1234"), fingerprint(b"TDSm12345"));
+    }
+}
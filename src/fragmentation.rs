@@ -0,0 +1,170 @@
+use crate::error::Result;
+use crate::object_map::ObjectMap;
+use crate::object_path::ObjectPath;
+use crate::tdms_reader::TdmsReader;
+use std::fmt;
+
+/// How a file's data is divided between segments, computed from its metadata.
+/// Files written in many small segments are slow to read, and a report can help
+/// decide whether the writer's buffering should be changed.
+#[derive(Debug, Clone)]
+pub struct FragmentationReport {
+    /// Number of segments read
+    pub segment_count: usize,
+
+    /// Total number of bytes of segment lead ins and metadata
+    pub metadata_bytes: u64,
+
+    /// Total number of bytes of raw data
+    pub data_bytes: u64,
+
+    /// Fragmentation of each channel with data, in the order channels were first written
+    pub channels: Vec<ChannelFragmentation>,
+}
+
+/// How a channel's data is divided between segments
+#[derive(Debug, Clone)]
+pub struct ChannelFragmentation {
+    /// TDMS path of the channel, eg. "/'Group'/'Channel'"
+    pub path: String,
+
+    /// Number of segments with raw data for the channel
+    pub segment_count: u64,
+
+    /// Fewest values of the channel in a segment
+    pub min_values_per_segment: u64,
+
+    /// Most values of the channel in a segment
+    pub max_values_per_segment: u64,
+
+    /// Mean number of values of the channel per segment
+    pub mean_values_per_segment: f64,
+
+    /// Total number of bytes of the channel's raw data
+    pub data_bytes: u64,
+
+    /// Fraction of segments that reused the raw data index from a previous segment
+    /// rather than writing it in full
+    pub reused_index_fraction: f64,
+}
+
+impl FragmentationReport {
+    /// Fraction of the file used by segment lead ins and metadata rather than raw data
+    pub fn metadata_fraction(&self) -> f64 {
+        let total_bytes = self.metadata_bytes + self.data_bytes;
+        if total_bytes == 0 {
+            0.0
+        } else {
+            self.metadata_bytes as f64 / total_bytes as f64
+        }
+    }
+}
+
+impl fmt::Display for FragmentationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} segments, {} bytes of metadata ({:.1}%), {} bytes of data",
+            self.segment_count,
+            self.metadata_bytes,
+            100.0 * self.metadata_fraction(),
+            self.data_bytes
+        )?;
+        write!(
+            f,
+            "{:<40} {:>10} {:>12} {:>12} {:>12} {:>14} {:>8}",
+            "Channel",
+            "Segments",
+            "Min values",
+            "Mean values",
+            "Max values",
+            "Data bytes",
+            "Reused"
+        )?;
+        for channel in self.channels.iter() {
+            write!(
+                f,
+                "\n{:<40} {:>10} {:>12} {:>12.1} {:>12} {:>14} {:>7.1}%",
+                channel.path,
+                channel.segment_count,
+                channel.min_values_per_segment,
+                channel.mean_values_per_segment,
+                channel.max_values_per_segment,
+                channel.data_bytes,
+                100.0 * channel.reused_index_fraction
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Totals for a channel accumulated over segments
+struct ChannelTotals {
+    segment_count: u64,
+    min_values: u64,
+    max_values: u64,
+    total_values: u64,
+    data_bytes: u64,
+}
+
+pub fn fragmentation_report(tdms_reader: &TdmsReader) -> Result<FragmentationReport> {
+    let mut metadata_bytes = 0;
+    let mut data_bytes = 0;
+    let mut channel_totals: ObjectMap<ChannelTotals> = ObjectMap::new();
+    for segment in tdms_reader.segments() {
+        metadata_bytes += segment.header_length();
+        data_bytes += segment.data_length();
+        let object_list = tdms_reader.object_list(segment);
+        let chunk_count = segment.chunk_count(object_list);
+        for (object, number_of_values) in segment.channel_value_counts(object_list)? {
+            // Only objects with a raw data index have a value count
+            let raw_data_index = object.raw_data_index.as_ref().unwrap();
+            let object_bytes = match raw_data_index.data_type.size() {
+                Some(type_size) => number_of_values * type_size as u64,
+                // Variable sized data declares its size per chunk
+                None => (raw_data_index.data_size * chunk_count).min(segment.data_length()),
+            };
+            let totals = channel_totals.get_or_insert_with(object.object_id, || ChannelTotals {
+                segment_count: 0,
+                min_values: u64::MAX,
+                max_values: 0,
+                total_values: 0,
+                data_bytes: 0,
+            });
+            totals.segment_count += 1;
+            totals.min_values = totals.min_values.min(number_of_values);
+            totals.max_values = totals.max_values.max(number_of_values);
+            totals.total_values += number_of_values;
+            totals.data_bytes += object_bytes;
+        }
+    }
+
+    let channels = tdms_reader
+        .objects()
+        // Other objects can't have data, even if they have a raw data index
+        .filter(|(_, path)| matches!(path, ObjectPath::Channel(_, _)))
+        .filter_map(|(object_id, path)| {
+            let totals = channel_totals.get(object_id)?;
+            let new_indexes = tdms_reader
+                .new_raw_data_index_count(object_id)
+                .min(totals.segment_count);
+            Some(ChannelFragmentation {
+                path: path.to_string(),
+                segment_count: totals.segment_count,
+                min_values_per_segment: totals.min_values,
+                max_values_per_segment: totals.max_values,
+                mean_values_per_segment: totals.total_values as f64 / totals.segment_count as f64,
+                data_bytes: totals.data_bytes,
+                reused_index_fraction: (totals.segment_count - new_indexes) as f64
+                    / totals.segment_count as f64,
+            })
+        })
+        .collect();
+
+    Ok(FragmentationReport {
+        segment_count: tdms_reader.segments().len(),
+        metadata_bytes,
+        data_bytes,
+        channels,
+    })
+}
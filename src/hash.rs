@@ -0,0 +1,162 @@
+use crate::error::Result;
+use crate::visit;
+use crate::{Channel, TdmsFile};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Seek};
+use std::ops::ControlFlow;
+
+/// An algorithm for hashing the raw data of channels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HashAlgo {
+    /// SHA-256, for hashes that are practically impossible to collide deliberately
+    #[cfg(feature = "sha256")]
+    Sha256,
+    /// The 128 bit xxh3 hash, which is much faster but not cryptographic.
+    /// The hash fills the first 16 bytes of the result and the rest are zero.
+    #[cfg(feature = "xxh3")]
+    Xxh3,
+}
+
+/// Hashes of the data of every channel in a file, for checking integrity or finding
+/// files with the same data. Only raw data is hashed, so files with different
+/// properties or a different segment layout can have the same hashes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContentManifest {
+    /// Algorithm used to compute the hashes
+    pub algorithm: HashAlgo,
+
+    /// Hash of the raw data of each channel, keyed by channel path
+    pub channels: BTreeMap<String, [u8; 32]>,
+
+    /// Hash of all channel paths and hashes in order of path, so that it doesn't
+    /// depend on the order channels appear in the file's metadata
+    pub data_hash: [u8; 32],
+}
+
+enum Hasher {
+    #[cfg(feature = "sha256")]
+    Sha256(sha2::Sha256),
+    #[cfg(feature = "xxh3")]
+    Xxh3(Box<xxhash_rust::xxh3::Xxh3>),
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgo) -> Hasher {
+        match algorithm {
+            #[cfg(feature = "sha256")]
+            HashAlgo::Sha256 => {
+                use sha2::Digest;
+                Hasher::Sha256(sha2::Sha256::new())
+            }
+            #[cfg(feature = "xxh3")]
+            HashAlgo::Xxh3 => Hasher::Xxh3(Box::new(xxhash_rust::xxh3::Xxh3::new())),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            #[cfg(feature = "sha256")]
+            Hasher::Sha256(hasher) => sha2::Digest::update(hasher, bytes),
+            #[cfg(feature = "xxh3")]
+            Hasher::Xxh3(hasher) => hasher.update(bytes),
+        }
+    }
+
+    fn finish(self) -> [u8; 32] {
+        let mut hash = [0; 32];
+        match self {
+            #[cfg(feature = "sha256")]
+            Hasher::Sha256(hasher) => {
+                hash.copy_from_slice(&sha2::Digest::finalize(hasher));
+            }
+            #[cfg(feature = "xxh3")]
+            Hasher::Xxh3(hasher) => {
+                hash[..16].copy_from_slice(&hasher.digest128().to_le_bytes());
+            }
+        }
+        hash
+    }
+}
+
+/// Hash the raw bytes of a channel's data, as stored in the file
+pub fn channel_hash<R: Read + Seek>(channel: &Channel<R>, algorithm: HashAlgo) -> Result<[u8; 32]> {
+    let mut hasher = Hasher::new(algorithm);
+    channel.read_raw_bytes(|bytes| hasher.update(bytes))?;
+    Ok(hasher.finish())
+}
+
+/// Hash the data of every channel in a file, in a single pass through the file's segments
+/// that updates each channel's hash with the raw bytes of its values in each segment
+pub fn content_manifest<R: Read + Seek>(
+    tdms_file: &TdmsFile<R>,
+    algorithm: HashAlgo,
+) -> Result<ContentManifest> {
+    // Channels without data have the hash of no bytes
+    let mut hashers: HashMap<&str, Hasher> = tdms_file
+        .find_channels_by(|_| true)
+        .iter()
+        .map(|channel| (channel.path(), Hasher::new(algorithm)))
+        .collect();
+    visit::for_each_segment_values(tdms_file, 0, |channel, segment_values| {
+        if let Some(hasher) = hashers.get_mut(channel.path()) {
+            channel.read_segment_raw_bytes(
+                segment_values.segment_index,
+                segment_values.value_count,
+                |bytes| hasher.update(bytes),
+            )?;
+        }
+        Ok(ControlFlow::Continue(()))
+    })?;
+    let channels: BTreeMap<String, [u8; 32]> = hashers
+        .into_iter()
+        .map(|(path, hasher)| (path.to_string(), hasher.finish()))
+        .collect();
+    let mut hasher = Hasher::new(algorithm);
+    for (path, hash) in channels.iter() {
+        // Prefix paths with their length so that path and hash boundaries are unambiguous
+        hasher.update(&(path.len() as u64).to_le_bytes());
+        hasher.update(path.as_bytes());
+        hasher.update(hash);
+    }
+    Ok(ContentManifest {
+        algorithm,
+        channels,
+        data_hash: hasher.finish(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hash_of(algorithm: HashAlgo, pieces: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = Hasher::new(algorithm);
+        for piece in pieces {
+            hasher.update(piece);
+        }
+        hasher.finish()
+    }
+
+    #[test]
+    #[cfg(feature = "sha256")]
+    pub fn sha256_of_known_input() {
+        let hash = hash_of(HashAlgo::Sha256, &[b"ab", b"c"]);
+        assert_eq!(
+            hash,
+            hex_literal::hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "xxh3")]
+    pub fn xxh3_is_padded_with_zeros() {
+        let hash = hash_of(HashAlgo::Xxh3, &[b"ab", b"c"]);
+        assert_eq!(
+            hash[..16],
+            xxhash_rust::xxh3::xxh3_128(b"abc").to_le_bytes()[..]
+        );
+        assert_eq!(hash[16..], [0; 16]);
+    }
+}
@@ -0,0 +1,753 @@
+use crate::error::{Result, TdmsReadError};
+use crate::names::{format_suggestions, similar_names};
+use crate::options::ErrorPolicy;
+use crate::properties::names::{
+    UNIT_STRING, WF_INCREMENT, WF_START_OFFSET, WF_START_TIME, WF_XNAME,
+};
+use crate::properties::{TdmsProperty, TdmsValue};
+use crate::timestamp::Timestamp;
+use crate::types::TdsType;
+use crate::writer::{TdmsWriter, WriteObject};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read, Write};
+
+/// Options for importing CSV data as a TDMS file
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Name of the group the channels are written to
+    pub group_name: String,
+
+    /// Whether column names of the form "name (unit)" are split into the channel name
+    /// and a `unit_string` property
+    pub parse_units: bool,
+
+    /// Name of a column holding the time of each row, either as numbers of seconds or
+    /// as ISO 8601 timestamps. If the times increase at a constant rate within the rows
+    /// used to detect column types, they are written as waveform timing properties of
+    /// the other channels rather than as a channel, and any later row with a time that
+    /// doesn't continue the constant rate from the rows imported before it is malformed.
+    /// Otherwise the column is written as
+    /// a channel like any other column.
+    pub time_column: Option<String>,
+
+    /// Largest difference between the interval between two rows and the detected time
+    /// increment for times to be considered constant rate, as a fraction of the increment
+    pub time_tolerance: f64,
+
+    /// Number of rows written per segment. Column types are detected from the rows of
+    /// the first segment, so only this many rows are held in memory at a time.
+    pub rows_per_segment: usize,
+
+    /// Character separating the fields of each row
+    pub delimiter: char,
+
+    /// Whether malformed rows are skipped and recorded in the import report,
+    /// or cause the import to fail
+    pub malformed_rows: ErrorPolicy,
+}
+
+impl Default for ImportOptions {
+    fn default() -> ImportOptions {
+        ImportOptions {
+            group_name: String::from("Group"),
+            parse_units: false,
+            time_column: None,
+            time_tolerance: 1e-3,
+            rows_per_segment: 100_000,
+            delimiter: ',',
+            malformed_rows: ErrorPolicy::Fail,
+        }
+    }
+}
+
+/// Describes the result of importing CSV data
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    /// Number of rows written to the TDMS file
+    pub rows_imported: u64,
+
+    /// Name and data type of each channel written
+    pub channels: Vec<(String, TdsType)>,
+
+    /// Whether the time column was written as waveform timing properties
+    pub waveform_timing: bool,
+
+    /// Rows that were malformed and skipped when using `ErrorPolicy::Skip`
+    pub skipped_rows: Vec<SkippedRow>,
+}
+
+/// A malformed CSV row that was skipped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedRow {
+    /// Line number of the start of the row, counting from 1
+    pub line: u64,
+
+    /// Description of why the row is malformed
+    pub error: String,
+}
+
+/// Import CSV data as a TDMS file, with one channel per column.
+///
+/// The first row holds the column names. The data type of each column is detected from
+/// the values in the first segment's rows: columns are written as 64 bit integers if
+/// every value is an integer, as 64 bit floats if every value is a number, with empty
+/// values written as NaN, as timestamps if every value is an ISO 8601 timestamp, and
+/// otherwise as strings. Timestamps without a time zone are taken to be in UTC.
+///
+/// Data is written in segments of `ImportOptions::rows_per_segment` rows, so CSV data
+/// of any size can be imported.
+pub fn import_csv<R: Read, W: Write>(
+    csv: R,
+    tdms_out: W,
+    options: &ImportOptions,
+) -> Result<ImportReport> {
+    let mut records = CsvRecords::new(BufReader::new(csv), options.delimiter);
+    let mut fields = Vec::new();
+    if records.next_record(&mut fields)?.is_none() {
+        return Err(TdmsReadError::MalformedCsv {
+            line: 1,
+            message: String::from("CSV data has no header row"),
+        });
+    }
+    let columns = parse_header(&fields, options)?;
+    let time_index = match options.time_column {
+        Some(ref time_column) => Some(find_time_column(&fields, time_column)?),
+        None => None,
+    };
+
+    let rows_per_segment = options.rows_per_segment.max(1);
+    let mut report = ImportReport::default();
+    let mut first_rows = Vec::new();
+    while first_rows.len() < rows_per_segment {
+        let line = match records.next_record(&mut fields)? {
+            Some(line) => line,
+            None => break,
+        };
+        if fields.len() != columns.len() {
+            skip_row(
+                &mut report,
+                options,
+                line,
+                field_count_error(columns.len(), fields.len()),
+            )?;
+            continue;
+        }
+        first_rows.push((line, fields.clone()));
+    }
+
+    let column_types: Vec<ColumnType> = (0..columns.len())
+        .map(|index| sniff_type(first_rows.iter().map(|(_, row)| row[index].as_str())))
+        .collect();
+    let time = time_index.and_then(|index| {
+        detect_time(
+            first_rows.iter().map(|(_, row)| row[index].as_str()),
+            column_types[index],
+            options.time_tolerance,
+        )
+        .map(|time| (index, time))
+    });
+    report.waveform_timing = time.is_some();
+
+    let mut channels = Vec::new();
+    for (index, (column, column_type)) in columns.iter().zip(column_types.iter()).enumerate() {
+        if matches!(time, Some((time_index, _)) if time_index == index) {
+            continue;
+        }
+        let mut properties = Vec::new();
+        if let Some(ref unit) = column.unit {
+            properties.push(TdmsProperty::new(
+                UNIT_STRING,
+                TdmsValue::String(unit.clone()),
+            ));
+        }
+        if let Some((time_index, ref time)) = time {
+            properties.extend(time.properties(&columns[time_index].name));
+        }
+        report
+            .channels
+            .push((column.name.clone(), column_type.data_type()));
+        channels.push(ImportChannel {
+            column: index,
+            name: column.name.clone(),
+            properties,
+            values: ColumnValues::new(*column_type),
+        });
+    }
+
+    let mut writer = TdmsWriter::new(tdms_out);
+    let mut segment_rows = 0;
+    let mut first_segment = true;
+    let mut row_values = Vec::with_capacity(channels.len());
+    let mut first_rows = first_rows.into_iter();
+    loop {
+        let (line, row) = match first_rows.next() {
+            Some(row) => row,
+            None => match records.next_record(&mut fields)? {
+                Some(line) => (line, std::mem::take(&mut fields)),
+                None => break,
+            },
+        };
+        let parsed = if row.len() != columns.len() {
+            Err(field_count_error(columns.len(), row.len()))
+        } else {
+            parse_row(
+                &row,
+                &channels,
+                &columns,
+                time,
+                report.rows_imported,
+                &mut row_values,
+            )
+        };
+        match parsed {
+            Ok(()) => {
+                for (channel, value) in channels.iter_mut().zip(row_values.drain(..)) {
+                    channel.values.push(value);
+                }
+                report.rows_imported += 1;
+                segment_rows += 1;
+            }
+            Err(error) => skip_row(&mut report, options, line, error)?,
+        }
+        fields = row;
+        if segment_rows == rows_per_segment {
+            write_segment(&mut writer, &mut channels, options, first_segment)?;
+            first_segment = false;
+            segment_rows = 0;
+        }
+    }
+    if segment_rows > 0 || first_segment {
+        write_segment(&mut writer, &mut channels, options, first_segment)?;
+    }
+    writer.flush()?;
+    Ok(report)
+}
+
+/// A column name, split into the channel name and unit if units are parsed
+struct Column {
+    name: String,
+    unit: Option<String>,
+}
+
+fn parse_header(fields: &[String], options: &ImportOptions) -> Result<Vec<Column>> {
+    let mut names = HashSet::new();
+    let mut columns = Vec::with_capacity(fields.len());
+    for field in fields {
+        let column = match parse_unit(field) {
+            Some((name, unit)) if options.parse_units => Column {
+                name: name.to_string(),
+                unit: Some(unit.to_string()),
+            },
+            _ => Column {
+                name: field.trim().to_string(),
+                unit: None,
+            },
+        };
+        if !names.insert(column.name.clone()) {
+            return Err(TdmsReadError::InvalidArgument(format!(
+                "CSV data has more than one column named {:?}",
+                column.name
+            )));
+        }
+        columns.push(column);
+    }
+    Ok(columns)
+}
+
+/// Split a column name of the form "name (unit)" into the name and unit
+fn parse_unit(field: &str) -> Option<(&str, &str)> {
+    let field = field.trim();
+    let without_close = field.strip_suffix(')')?;
+    let open = without_close.rfind('(')?;
+    let name = without_close[..open].trim_end();
+    if name.is_empty() {
+        return None;
+    }
+    Some((name, without_close[open + 1..].trim()))
+}
+
+fn find_time_column(fields: &[String], time_column: &str) -> Result<usize> {
+    // The time column may be given with or without its unit
+    let position = fields.iter().position(|field| {
+        let field = field.trim();
+        field == time_column || matches!(parse_unit(field), Some((name, _)) if name == time_column)
+    });
+    position.ok_or_else(|| {
+        let similar = similar_names(time_column, fields.iter().map(|field| field.as_str()));
+        TdmsReadError::InvalidArgument(format!(
+            "CSV data has no time column named {:?}{}",
+            time_column,
+            format_suggestions(&similar)
+        ))
+    })
+}
+
+fn field_count_error(expected: usize, found: usize) -> String {
+    format!("expected {} fields but found {}", expected, found)
+}
+
+fn skip_row(
+    report: &mut ImportReport,
+    options: &ImportOptions,
+    line: u64,
+    error: String,
+) -> Result<()> {
+    match options.malformed_rows {
+        ErrorPolicy::Fail => Err(TdmsReadError::MalformedCsv {
+            line,
+            message: error,
+        }),
+        ErrorPolicy::Skip => {
+            report.skipped_rows.push(SkippedRow { line, error });
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Integer,
+    Float,
+    Timestamp,
+    String,
+}
+
+impl ColumnType {
+    fn data_type(&self) -> TdsType {
+        match *self {
+            ColumnType::Integer => TdsType::I64,
+            ColumnType::Float => TdsType::DoubleFloat,
+            ColumnType::Timestamp => TdsType::TimeStamp,
+            ColumnType::String => TdsType::String,
+        }
+    }
+}
+
+/// Detect the type of a column from its values
+fn sniff_type<'a, I: Iterator<Item = &'a str>>(values: I) -> ColumnType {
+    let mut integer = true;
+    let mut float = true;
+    let mut timestamp = true;
+    let mut any_value = false;
+    for value in values {
+        let value = value.trim();
+        if value.is_empty() {
+            integer = false;
+            timestamp = false;
+            continue;
+        }
+        any_value = true;
+        integer = integer && value.parse::<i64>().is_ok();
+        float = float && value.parse::<f64>().is_ok();
+        timestamp = timestamp && parse_timestamp(value).is_some();
+        if !float && !timestamp {
+            return ColumnType::String;
+        }
+    }
+    match (any_value, integer, float, timestamp) {
+        (false, _, _, _) => ColumnType::String,
+        (true, true, _, _) => ColumnType::Integer,
+        (true, _, true, _) => ColumnType::Float,
+        (true, _, _, true) => ColumnType::Timestamp,
+        _ => ColumnType::String,
+    }
+}
+
+/// Parse an ISO 8601 timestamp, which is taken to be in UTC if it has no time zone
+fn parse_timestamp(value: &str) -> Option<Timestamp> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(Timestamp::from_datetime(datetime.with_timezone(&Utc)));
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|datetime| Timestamp::from_datetime(Utc.from_utc_datetime(&datetime)))
+}
+
+/// Constant rate timing detected from the time column
+#[derive(Debug, Clone, Copy)]
+struct DetectedTime {
+    start_time: Option<Timestamp>,
+    start_offset: f64,
+    increment: f64,
+    tolerance: f64,
+}
+
+impl DetectedTime {
+    /// Time in seconds of a time column value relative to the start time
+    fn seconds(&self, value: &str) -> Option<f64> {
+        seconds_since(value.trim(), self.start_time)
+    }
+
+    fn properties(&self, time_name: &str) -> Vec<TdmsProperty> {
+        let mut properties = Vec::with_capacity(4);
+        if let Some(start_time) = self.start_time {
+            properties.push(TdmsProperty::new(
+                WF_START_TIME,
+                TdmsValue::Timestamp(start_time),
+            ));
+        }
+        properties.push(TdmsProperty::new(
+            WF_START_OFFSET,
+            TdmsValue::Float64(self.start_offset),
+        ));
+        properties.push(TdmsProperty::new(
+            WF_INCREMENT,
+            TdmsValue::Float64(self.increment),
+        ));
+        properties.push(TdmsProperty::new(
+            WF_XNAME,
+            TdmsValue::String(time_name.to_string()),
+        ));
+        properties
+    }
+
+    /// Check that the time of the row at `index` follows the constant rate
+    fn check(&self, value: &str, index: u64) -> std::result::Result<(), String> {
+        let expected = self.start_offset + index as f64 * self.increment;
+        match self.seconds(value) {
+            Some(seconds) if (seconds - expected).abs() <= self.tolerance * self.increment => {
+                Ok(())
+            }
+            Some(_) => Err(format!(
+                "time {:?} does not follow the constant rate of the time column, \
+                expected {} seconds",
+                value, expected
+            )),
+            None => Err(format!("could not parse time {:?}", value)),
+        }
+    }
+}
+
+fn seconds_since(value: &str, start_time: Option<Timestamp>) -> Option<f64> {
+    match start_time {
+        Some(start_time) => {
+            let timestamp = parse_timestamp(value)?;
+            Some((timestamp.to_unix_nanos() - start_time.to_unix_nanos()) as f64 / 1e9)
+        }
+        None => value
+            .parse::<f64>()
+            .ok()
+            .filter(|seconds| seconds.is_finite()),
+    }
+}
+
+/// Detect whether the values of a time column increase at a constant rate
+fn detect_time<'a, I: Iterator<Item = &'a str>>(
+    values: I,
+    column_type: ColumnType,
+    tolerance: f64,
+) -> Option<DetectedTime> {
+    let values: Vec<&str> = values.map(|value| value.trim()).collect();
+    let start_time = match column_type {
+        ColumnType::Timestamp => Some(parse_timestamp(values.first()?)?),
+        ColumnType::Integer | ColumnType::Float => None,
+        ColumnType::String => return None,
+    };
+    let seconds: Vec<f64> = values
+        .iter()
+        .map(|value| seconds_since(value, start_time))
+        .collect::<Option<_>>()?;
+    if seconds.len() < 2 {
+        return None;
+    }
+    let start_offset = seconds[0];
+    let increment = (seconds[seconds.len() - 1] - start_offset) / (seconds.len() - 1) as f64;
+    if increment <= 0.0 {
+        return None;
+    }
+    let time = DetectedTime {
+        start_time,
+        start_offset,
+        increment,
+        tolerance,
+    };
+    let constant_rate = seconds.iter().enumerate().all(|(index, seconds)| {
+        let expected = start_offset + index as f64 * increment;
+        (seconds - expected).abs() <= tolerance * increment
+    });
+    if constant_rate {
+        Some(time)
+    } else {
+        None
+    }
+}
+
+/// A value of a row, parsed according to its column's type
+enum Value {
+    Integer(i64),
+    Float(f64),
+    Timestamp(Timestamp),
+    String(String),
+}
+
+/// Values of a column buffered to be written in the next segment
+enum ColumnValues {
+    Integer(Vec<i64>),
+    Float(Vec<f64>),
+    Timestamp(Vec<Timestamp>),
+    String(Vec<String>),
+}
+
+impl ColumnValues {
+    fn new(column_type: ColumnType) -> ColumnValues {
+        match column_type {
+            ColumnType::Integer => ColumnValues::Integer(Vec::new()),
+            ColumnType::Float => ColumnValues::Float(Vec::new()),
+            ColumnType::Timestamp => ColumnValues::Timestamp(Vec::new()),
+            ColumnType::String => ColumnValues::String(Vec::new()),
+        }
+    }
+
+    fn parse(&self, field: &str) -> std::result::Result<Value, String> {
+        let trimmed = field.trim();
+        match *self {
+            ColumnValues::Integer(_) => trimmed
+                .parse()
+                .map(Value::Integer)
+                .map_err(|_| format!("could not parse {:?} as an integer", field)),
+            ColumnValues::Float(_) if trimmed.is_empty() => Ok(Value::Float(f64::NAN)),
+            ColumnValues::Float(_) => trimmed
+                .parse()
+                .map(Value::Float)
+                .map_err(|_| format!("could not parse {:?} as a number", field)),
+            ColumnValues::Timestamp(_) => parse_timestamp(trimmed)
+                .map(Value::Timestamp)
+                .ok_or_else(|| format!("could not parse {:?} as a timestamp", field)),
+            ColumnValues::String(_) => Ok(Value::String(field.to_string())),
+        }
+    }
+
+    fn push(&mut self, value: Value) {
+        match (self, value) {
+            (ColumnValues::Integer(values), Value::Integer(value)) => values.push(value),
+            (ColumnValues::Float(values), Value::Float(value)) => values.push(value),
+            (ColumnValues::Timestamp(values), Value::Timestamp(value)) => values.push(value),
+            (ColumnValues::String(values), Value::String(value)) => values.push(value),
+            _ => unreachable!("Values are parsed according to the column type"),
+        }
+    }
+
+    /// Set this column's buffered values as the data of a write object, clearing the buffer
+    fn write_data(&mut self, object: WriteObject) -> Result<WriteObject> {
+        let object = match *self {
+            ColumnValues::Integer(ref values) => object.with_data(values)?,
+            ColumnValues::Float(ref values) => object.with_data(values)?,
+            ColumnValues::Timestamp(ref values) => object.with_data(values)?,
+            ColumnValues::String(ref values) => object.with_string_data(values)?,
+        };
+        match *self {
+            ColumnValues::Integer(ref mut values) => values.clear(),
+            ColumnValues::Float(ref mut values) => values.clear(),
+            ColumnValues::Timestamp(ref mut values) => values.clear(),
+            ColumnValues::String(ref mut values) => values.clear(),
+        }
+        Ok(object)
+    }
+}
+
+struct ImportChannel {
+    column: usize,
+    name: String,
+    properties: Vec<TdmsProperty>,
+    values: ColumnValues,
+}
+
+/// Parse the values of a row into `row_values`, or describe why the row is malformed
+fn parse_row(
+    row: &[String],
+    channels: &[ImportChannel],
+    columns: &[Column],
+    time: Option<(usize, DetectedTime)>,
+    row_index: u64,
+    row_values: &mut Vec<Value>,
+) -> std::result::Result<(), String> {
+    row_values.clear();
+    if let Some((time_index, ref time)) = time {
+        time.check(&row[time_index], row_index)?;
+    }
+    for channel in channels {
+        let value = channel
+            .values
+            .parse(&row[channel.column])
+            .map_err(|error| format!("{} in column {:?}", error, columns[channel.column].name))?;
+        row_values.push(value);
+    }
+    Ok(())
+}
+
+fn write_segment<W: Write>(
+    writer: &mut TdmsWriter<W>,
+    channels: &mut [ImportChannel],
+    options: &ImportOptions,
+    first_segment: bool,
+) -> Result<()> {
+    let mut objects = Vec::with_capacity(channels.len() + 1);
+    if first_segment {
+        objects.push(WriteObject::group(&options.group_name));
+    }
+    for channel in channels.iter_mut() {
+        let mut object = WriteObject::channel(&options.group_name, &channel.name);
+        if first_segment {
+            object = object.with_properties(&channel.properties);
+        }
+        objects.push(channel.values.write_data(object)?);
+    }
+    writer.write_segment(&objects)
+}
+
+/// Reads the records of CSV data, which may contain quoted fields with delimiters,
+/// quotes written as two quote characters, and line breaks
+struct CsvRecords<R: BufRead> {
+    reader: R,
+    delimiter: char,
+    line: String,
+    /// Number of lines read so far
+    line_number: u64,
+}
+
+impl<R: BufRead> CsvRecords<R> {
+    fn new(reader: R, delimiter: char) -> CsvRecords<R> {
+        CsvRecords {
+            reader,
+            delimiter,
+            line: String::new(),
+            line_number: 0,
+        }
+    }
+
+    /// Read a line into the line buffer without its line ending,
+    /// returning false at the end of the data
+    fn read_line(&mut self) -> Result<bool> {
+        self.line.clear();
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Ok(false);
+        }
+        self.line_number += 1;
+        if self.line_number == 1 && self.line.starts_with('\u{feff}') {
+            self.line.drain(..'\u{feff}'.len_utf8());
+        }
+        let length = self.line.trim_end_matches(&['\r', '\n'][..]).len();
+        self.line.truncate(length);
+        Ok(true)
+    }
+
+    /// Read the fields of the next record, skipping blank lines. Returns the line number
+    /// the record starts on, or None at the end of the data.
+    fn next_record(&mut self, fields: &mut Vec<String>) -> Result<Option<u64>> {
+        fields.clear();
+        loop {
+            if !self.read_line()? {
+                return Ok(None);
+            }
+            if !self.line.is_empty() {
+                break;
+            }
+        }
+        let start_line = self.line_number;
+        let mut field = String::new();
+        let mut in_quotes = false;
+        loop {
+            let mut chars = self.line.chars().peekable();
+            while let Some(c) = chars.next() {
+                if in_quotes {
+                    if c == '"' {
+                        if chars.peek() == Some(&'"') {
+                            chars.next();
+                            field.push('"');
+                        } else {
+                            in_quotes = false;
+                        }
+                    } else {
+                        field.push(c);
+                    }
+                } else if c == self.delimiter {
+                    fields.push(std::mem::take(&mut field));
+                } else if c == '"' && field.is_empty() {
+                    in_quotes = true;
+                } else {
+                    field.push(c);
+                }
+            }
+            if !in_quotes {
+                break;
+            }
+            field.push('\n');
+            if !self.read_line()? {
+                return Err(TdmsReadError::MalformedCsv {
+                    line: start_line,
+                    message: String::from("quoted field is not closed before the end of the data"),
+                });
+            }
+        }
+        fields.push(field);
+        Ok(Some(start_line))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn records(csv: &str) -> Vec<(u64, Vec<String>)> {
+        let mut records = CsvRecords::new(csv.as_bytes(), ',');
+        let mut fields = Vec::new();
+        let mut result = Vec::new();
+        while let Some(line) = records.next_record(&mut fields).unwrap() {
+            result.push((line, fields.clone()));
+        }
+        result
+    }
+
+    #[test]
+    pub fn parse_quoted_fields() {
+        let parsed = records("\u{feff}a,b\r\n\"x,1\",\"say \"\"hi\"\"\"\n\n\"two\nlines\",\n");
+
+        assert_eq!(
+            parsed,
+            vec![
+                (1, vec![String::from("a"), String::from("b")]),
+                (2, vec![String::from("x,1"), String::from("say \"hi\"")]),
+                (4, vec![String::from("two\nlines"), String::new()]),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn unclosed_quote_is_an_error() {
+        let mut records = CsvRecords::new("a\n\"b\nc".as_bytes(), ',');
+        let mut fields = Vec::new();
+        records.next_record(&mut fields).unwrap();
+
+        match records.next_record(&mut fields) {
+            Err(TdmsReadError::MalformedCsv { line, .. }) => assert_eq!(line, 2),
+            other => panic!("Expected malformed CSV error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn sniff_column_types() {
+        assert_eq!(sniff_type(["1", "-2"].iter().cloned()), ColumnType::Integer);
+        assert_eq!(sniff_type(["1", "2.5"].iter().cloned()), ColumnType::Float);
+        assert_eq!(sniff_type(["1", ""].iter().cloned()), ColumnType::Float);
+        assert_eq!(
+            sniff_type(
+                ["2021-03-04T05:06:07.5Z", "2021-03-04 05:06:08"]
+                    .iter()
+                    .cloned()
+            ),
+            ColumnType::Timestamp
+        );
+        assert_eq!(sniff_type(["1", "a"].iter().cloned()), ColumnType::String);
+        assert_eq!(sniff_type(["", ""].iter().cloned()), ColumnType::String);
+    }
+
+    #[test]
+    pub fn split_units_from_names() {
+        assert_eq!(parse_unit("Pressure (bar)"), Some(("Pressure", "bar")));
+        assert_eq!(parse_unit("Speed (m/s) "), Some(("Speed", "m/s")));
+        assert_eq!(parse_unit("Pressure"), None);
+        assert_eq!(parse_unit("(bar)"), None);
+    }
+}
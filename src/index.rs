@@ -0,0 +1,217 @@
+use crate::error::{Result, TdmsReadError};
+use crate::toc::{TocFlag, TocMask};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const SEGMENT_TAG: [u8; 4] = *b"TDSm";
+const INDEX_SEGMENT_TAG: [u8; 4] = *b"TDSh";
+const LEAD_IN_LENGTH: u64 = 28;
+const INCOMPLETE_SEGMENT_OFFSET: u64 = 0xFFFFFFFFFFFFFFFF;
+
+/// Whether the .tdms_index file of a TDMS file matches it, found with `check_index`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexStatus {
+    /// The index has the same segments as the data file
+    Current,
+
+    /// There is no index file
+    Missing,
+
+    /// The index doesn't have the same segments as the data file, which has been
+    /// written to since the index was created
+    Stale {
+        index_segments: usize,
+        index_end: u64,
+        data_segments: usize,
+        data_end: u64,
+    },
+
+    /// The index file could not be read
+    Invalid(String),
+}
+
+impl fmt::Display for IndexStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IndexStatus::Current => write!(f, "current"),
+            IndexStatus::Missing => write!(f, "missing"),
+            IndexStatus::Stale {
+                index_segments,
+                index_end,
+                data_segments,
+                data_end,
+            } => write!(
+                f,
+                "stale, index has {} segment(s) ending at {} but the data file has {} ending at {}",
+                index_segments, index_end, data_segments, data_end
+            ),
+            IndexStatus::Invalid(ref reason) => write!(f, "invalid, {}", reason),
+        }
+    }
+}
+
+/// Path of the .tdms_index file for a TDMS file
+pub fn index_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    path.as_ref().with_extension("tdms_index")
+}
+
+/// Write the .tdms_index file for a TDMS file, replacing any existing index. The index
+/// contains a copy of the lead in and metadata of each complete segment, so the file's
+/// structure can be read without reading through its data. An incomplete final segment
+/// of a file that is still being written is left out, so the index is stale once the
+/// segment is complete. Returns the number of segments indexed.
+///
+/// The index is written to a temporary file that replaces the index once complete, so
+/// an existing index is never left partly written.
+pub fn write_index<P: AsRef<Path>>(path: P) -> Result<usize> {
+    let path = path.as_ref();
+    let index_path = index_path(path);
+    let temporary_path = index_path.with_extension("tdms_index.tmp");
+    let mut reader = BufReader::new(File::open(path)?);
+    let result = File::create(&temporary_path)
+        .map_err(TdmsReadError::from)
+        .and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            let scan = scan_segments(
+                &mut reader,
+                SegmentSource::Data,
+                Some(&mut writer as &mut dyn Write),
+            )?;
+            writer
+                .into_inner()
+                .map_err(|error| TdmsReadError::IoError(error.into_error()))?
+                .sync_all()?;
+            Ok(scan.segment_count)
+        });
+    match result {
+        Ok(segment_count) => {
+            fs::rename(&temporary_path, &index_path)?;
+            Ok(segment_count)
+        }
+        Err(error) => {
+            let _ = fs::remove_file(&temporary_path);
+            Err(error)
+        }
+    }
+}
+
+/// Check whether the .tdms_index file of a TDMS file matches it, by comparing the
+/// number of complete segments and the position of the end of the last one. Only lead
+/// ins are read, so this is much quicker than opening the file. Errors reading the data
+/// file are returned, while errors reading the index give `IndexStatus::Invalid`.
+pub fn check_index<P: AsRef<Path>>(path: P) -> Result<IndexStatus> {
+    let path = path.as_ref();
+    let mut data_reader = BufReader::new(File::open(path)?);
+    let data = scan_segments(&mut data_reader, SegmentSource::Data, None)?;
+    let index_file = match File::open(index_path(path)) {
+        Ok(index_file) => index_file,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(IndexStatus::Missing),
+        Err(error) => return Err(error.into()),
+    };
+    let index = match scan_segments(&mut BufReader::new(index_file), SegmentSource::Index, None) {
+        Ok(index) => index,
+        Err(error) => return Ok(IndexStatus::Invalid(error.to_string())),
+    };
+    if index == data {
+        Ok(IndexStatus::Current)
+    } else {
+        Ok(IndexStatus::Stale {
+            index_segments: index.segment_count,
+            index_end: index.end,
+            data_segments: data.segment_count,
+            data_end: data.end,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SegmentSource {
+    Data,
+    Index,
+}
+
+/// The complete segments found by following the lead ins of a data or index file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SegmentScan {
+    segment_count: usize,
+
+    /// Position in the data file of the end of the last complete segment
+    end: u64,
+}
+
+/// Follow the segment lead ins of a data or index file up to the end of the file or an
+/// incomplete segment, optionally writing the lead in and metadata of each segment as
+/// an index segment
+fn scan_segments<R: Read + Seek>(
+    reader: &mut R,
+    source: SegmentSource,
+    mut index_writer: Option<&mut dyn Write>,
+) -> Result<SegmentScan> {
+    let file_length = reader.seek(SeekFrom::End(0))?;
+    let (expected_tag, tag_name) = match source {
+        SegmentSource::Data => (SEGMENT_TAG, "segment"),
+        SegmentSource::Index => (INDEX_SEGMENT_TAG, "index segment"),
+    };
+    let mut scan = SegmentScan {
+        segment_count: 0,
+        end: 0,
+    };
+    // Position of the next lead in, which is the end of the last segment for a data file
+    let mut position = 0;
+    let mut lead_in = [0u8; LEAD_IN_LENGTH as usize];
+    let mut metadata = Vec::new();
+    while position + LEAD_IN_LENGTH <= file_length {
+        reader.seek(SeekFrom::Start(position))?;
+        reader.read_exact(&mut lead_in)?;
+        if lead_in[..4] != expected_tag {
+            return Err(TdmsReadError::InvalidData(format!(
+                "Invalid {} header at position {}: {:?}",
+                tag_name,
+                position,
+                &lead_in[..4]
+            )));
+        }
+        let toc_mask = TocMask::from_flags(LittleEndian::read_u32(&lead_in[4..8]));
+        let (next_segment_offset, raw_data_offset) = if toc_mask.has_flag(TocFlag::BigEndian) {
+            (
+                BigEndian::read_u64(&lead_in[12..20]),
+                BigEndian::read_u64(&lead_in[20..28]),
+            )
+        } else {
+            (
+                LittleEndian::read_u64(&lead_in[12..20]),
+                LittleEndian::read_u64(&lead_in[20..28]),
+            )
+        };
+        if next_segment_offset == INCOMPLETE_SEGMENT_OFFSET || raw_data_offset > next_segment_offset
+        {
+            break;
+        }
+        let segment_end = scan.end + LEAD_IN_LENGTH + next_segment_offset;
+        let next_position = match source {
+            SegmentSource::Data if segment_end > file_length => break,
+            SegmentSource::Data => segment_end,
+            SegmentSource::Index => position + LEAD_IN_LENGTH + raw_data_offset,
+        };
+        if let Some(ref mut writer) = index_writer {
+            metadata.resize(raw_data_offset as usize, 0);
+            reader.read_exact(&mut metadata)?;
+            writer.write_all(&INDEX_SEGMENT_TAG)?;
+            writer.write_all(&lead_in[4..])?;
+            writer.write_all(&metadata)?;
+        }
+        scan.segment_count += 1;
+        scan.end = segment_end;
+        position = next_position;
+    }
+    if source == SegmentSource::Index && position != file_length {
+        return Err(TdmsReadError::InvalidData(format!(
+            "Index ends with a partial segment at position {}",
+            position
+        )));
+    }
+    Ok(scan)
+}
@@ -0,0 +1,270 @@
+use crate::error::Result;
+use crate::object_path::ObjectPathId;
+use crate::tdms_reader::TdmsReader;
+use crate::toc::{TocFlag, TocMask};
+use crate::types::TdsType;
+
+/// Metadata of a segment as it was read, for inspecting the structure of a file
+#[derive(Debug, Clone)]
+pub struct SegmentInfo {
+    /// Position in the file of the start of the segment
+    pub position: u64,
+
+    /// Position in the file of the start of the next segment
+    pub next_segment_position: u64,
+
+    /// Table of contents flags from the segment lead in
+    pub toc_flags: u32,
+
+    /// TDMS format version number, 4712 for TDMS 1.0 or 4713 for TDMS 2.0
+    pub version: u32,
+
+    /// Number of bytes of raw data
+    pub data_length: u64,
+
+    /// Number of chunks of raw data, including a truncated final chunk
+    pub chunk_count: u64,
+
+    /// Whether the segment was not completely written, so its length was
+    /// determined from the length of the file
+    pub incomplete: bool,
+
+    /// Whether any object in the segment has a DAQmx raw data index. This should match
+    /// the DAQmxRawData table of contents flag.
+    pub has_daqmx_indexes: bool,
+
+    /// Objects in the segment's object list, in order
+    pub objects: Vec<SegmentObjectInfo>,
+}
+
+impl SegmentInfo {
+    /// Table of contents flags from the segment lead in
+    pub fn toc_mask(&self) -> TocMask {
+        TocMask::from_flags(self.toc_flags)
+    }
+
+    /// Names of the known flags set in the table of contents, eg. "MetaData"
+    pub fn toc_flag_names(&self) -> Vec<&'static str> {
+        self.toc_mask()
+            .known_flags()
+            .map(|flag| flag.name())
+            .collect()
+    }
+
+    /// Whether the InterleavedData table of contents flag is set, so the values of
+    /// channels are interleaved within each chunk of raw data
+    pub fn interleaved(&self) -> bool {
+        self.toc_mask().has_flag(TocFlag::InterleavedData)
+    }
+
+    /// Whether the BigEndian table of contents flag is set, so the segment's metadata
+    /// and raw data are stored in big endian byte order
+    pub fn big_endian(&self) -> bool {
+        self.toc_mask().has_flag(TocFlag::BigEndian)
+    }
+
+    /// Whether the DAQmxRawData table of contents flag doesn't match whether objects
+    /// have DAQmx raw data indexes, for segments with raw data
+    pub fn daqmx_flag_mismatch(&self) -> bool {
+        let toc_mask = self.toc_mask();
+        toc_mask.has_flag(TocFlag::RawData)
+            && toc_mask.has_flag(TocFlag::DaqMxRawData) != self.has_daqmx_indexes
+    }
+}
+
+/// An object in the object list of a segment
+#[derive(Debug, Clone)]
+pub struct SegmentObjectInfo {
+    /// TDMS path of the object, eg. "/'Group'/'Channel'"
+    pub path: String,
+
+    /// Data type of the object's raw data, or None if it has no raw data in the segment
+    pub data_type: Option<TdsType>,
+
+    /// Number of values of the object in the segment
+    pub number_of_values: u64,
+
+    /// Index of the object's first value in the segment within all of its values,
+    /// or None if it has no values in the segment
+    pub first_value: Option<u64>,
+}
+
+/// Describe each segment read from a file
+pub fn segment_info(tdms_reader: &TdmsReader) -> Result<Vec<SegmentInfo>> {
+    tdms_reader
+        .segments()
+        .iter()
+        .enumerate()
+        .map(|(segment_index, segment)| {
+            let object_list = tdms_reader.object_list(segment);
+            let objects = object_list
+                .objects()
+                .iter()
+                .map(|object| {
+                    let path = tdms_reader
+                        .get_object_path_string(object.object_id)
+                        .unwrap_or("/")
+                        .to_string();
+                    let data_type = object
+                        .raw_data_index
+                        .map(|raw_data_index| raw_data_index.data_type);
+                    let first_value = tdms_reader
+                        .get_channel_data_index(object.object_id)
+                        .and_then(|index| index.segment_start_value(segment_index));
+                    Ok(SegmentObjectInfo {
+                        path,
+                        data_type,
+                        number_of_values: segment
+                            .channel_value_count(object.object_id, object_list)?,
+                        first_value,
+                    })
+                })
+                .collect::<Result<Vec<SegmentObjectInfo>>>()?;
+            Ok(SegmentInfo {
+                position: segment.position,
+                next_segment_position: segment.next_segment_position,
+                toc_flags: segment.toc_mask().flags(),
+                version: segment.version,
+                data_length: segment.data_length(),
+                chunk_count: segment.chunk_count(object_list),
+                incomplete: segment.incomplete,
+                has_daqmx_indexes: object_list.has_daqmx_data(),
+                objects,
+            })
+        })
+        .collect()
+}
+
+/// What is stored at a byte offset in a file, found with `TdmsFile::locate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocationInfo {
+    /// Index of the segment containing the offset, as in `TdmsFile::segments`
+    pub segment_index: usize,
+
+    /// TDMS path of the object whose raw data contains the offset, or None if the offset
+    /// is within the segment's lead in or metadata, or its raw data could not be mapped
+    /// to an object
+    pub object_path_at_offset: Option<String>,
+
+    /// Index within all of the object's values of the value stored at the offset.
+    /// This is None for offsets within the string data of a string channel, as only
+    /// the offset table is mapped to values, or within values cut off by truncation.
+    pub value_index_within_channel: Option<u64>,
+}
+
+/// Find what is stored at a byte offset in the file
+pub fn locate(tdms_reader: &TdmsReader, byte_offset: u64) -> Result<Option<LocationInfo>> {
+    let segments = tdms_reader.segments();
+    let segment_index =
+        segments.partition_point(|segment| segment.next_segment_position <= byte_offset);
+    let segment = match segments.get(segment_index) {
+        Some(segment) if segment.position <= byte_offset => segment,
+        _ => return Ok(None),
+    };
+    let object_list = tdms_reader.object_list(segment);
+    let (object_id, value_in_segment) = match segment.locate_position(byte_offset, object_list)? {
+        Some(location) => location,
+        None => {
+            return Ok(Some(LocationInfo {
+                segment_index,
+                object_path_at_offset: None,
+                value_index_within_channel: None,
+            }))
+        }
+    };
+    let value_index_within_channel = tdms_reader
+        .get_channel_data_index(object_id)
+        .and_then(|index| index.segment_values(segment_index))
+        .zip(value_in_segment)
+        .filter(|((_, number_of_values), value)| value < number_of_values)
+        .map(|((start_value, _), value)| start_value + value);
+    Ok(Some(LocationInfo {
+        segment_index,
+        object_path_at_offset: tdms_reader
+            .get_object_path_string(object_id)
+            .map(|path| path.to_string()),
+        value_index_within_channel,
+    }))
+}
+
+/// How values are arranged within a `DataExtent`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// Values are stored one after another. String data is stored as a table of
+    /// offsets of the end of each string followed by the string bytes.
+    Contiguous,
+
+    /// Values are interleaved with the values of other channels, with each value
+    /// starting `stride` bytes after the previous one
+    Interleaved { stride: u64 },
+}
+
+/// A range of bytes in the file containing raw values of a channel, found with
+/// `Channel::data_extents`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataExtent {
+    /// Position in the file of the first byte of the first value
+    pub file_offset: u64,
+
+    /// Length in bytes of the extent. For interleaved data this spans from the start of
+    /// the first value to the end of the last, including the other channels' values
+    /// between them.
+    pub byte_len: u64,
+
+    /// Number of the channel's values in the extent
+    pub values: u64,
+
+    /// Data type of the raw values
+    pub data_type: TdsType,
+
+    /// How values are arranged within the extent
+    pub layout: Layout,
+
+    /// Whether values are stored in big endian byte order
+    pub big_endian: bool,
+}
+
+impl DataExtent {
+    /// Extend this extent with the following extent if it continues directly on from it
+    pub(crate) fn try_merge(&mut self, next: &DataExtent) -> bool {
+        let end = match self.layout {
+            Layout::Contiguous => self.file_offset + self.byte_len,
+            Layout::Interleaved { stride } => self.file_offset + self.values * stride,
+        };
+        if next.file_offset != end
+            || next.layout != self.layout
+            || next.data_type != self.data_type
+            || next.big_endian != self.big_endian
+            || self.data_type == TdsType::String
+        {
+            return false;
+        }
+        self.byte_len = next.file_offset + next.byte_len - self.file_offset;
+        self.values += next.values;
+        true
+    }
+}
+
+/// Find the segment containing a channel's value at `value_index` and the byte offset
+/// of the value in the file
+pub fn locate_value(
+    tdms_reader: &TdmsReader,
+    channel_id: ObjectPathId,
+    value_index: u64,
+) -> Result<Option<(usize, u64)>> {
+    let (segment_index, value_in_segment) = match tdms_reader
+        .get_channel_data_index(channel_id)
+        .and_then(|index| index.value_segment(value_index))
+    {
+        Some(location) => location,
+        None => return Ok(None),
+    };
+    let segment = &tdms_reader.segments()[segment_index];
+    Ok(segment
+        .value_position(
+            channel_id,
+            tdms_reader.object_list(segment),
+            value_in_segment,
+        )?
+        .map(|byte_offset| (segment_index, byte_offset)))
+}
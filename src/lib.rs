@@ -1,30 +1,147 @@
 extern crate byteorder;
-extern crate chrono;
-extern crate id_arena;
 extern crate num_enum;
 
+// Declared first so its macros are available to the other modules
+#[macro_use]
+mod trace;
+
+#[cfg(feature = "cache")]
+mod cache;
+mod chunk;
+mod custom;
+mod daqmx;
+mod decimate;
+mod diff;
 mod error;
+mod estimate;
+mod export;
+mod filter;
+mod fingerprint;
+mod fragmentation;
+#[cfg(any(feature = "sha256", feature = "xxh3"))]
+mod hash;
+#[cfg(feature = "chrono")]
+mod import;
+mod index;
+mod inspect;
 mod interleaved;
+mod line_protocol;
+mod merge;
+mod names;
 mod object_map;
 mod object_path;
-mod properties;
+mod options;
+mod overview;
+mod prefetch;
+mod progress;
+pub mod properties;
+mod raw_export;
+/// Low level building blocks for writing TDMS data, for crafting test files. Unlike
+/// `TdmsWriter`, nothing is checked for consistency, so callers can assemble truncated
+/// segments, repeated objects or unusual table of contents flags.
+#[cfg(feature = "raw-writer")]
+pub mod raw_writer;
+mod read_builder;
+mod reader_cell;
+mod repair;
+mod rewrite;
 mod segment;
+mod shared_file;
+mod snapshot;
+mod stats;
+mod strided;
+mod summary;
 mod tdms_reader;
 pub mod timestamp;
 mod toc;
 mod types;
+mod visit;
+mod waveform;
+mod writer;
+#[cfg(feature = "xlsx")]
+mod xlsx;
+mod xy;
 
-use crate::error::{Result, TdmsReadError};
+#[cfg(feature = "cache")]
+use crate::cache::MetadataCache;
+pub use crate::chunk::ChunkConfig;
+use crate::chunk::ScratchPool;
+pub use crate::custom::{CustomDecoder, CustomValues, PassthroughDecoder};
+use crate::daqmx::LinearScale;
+pub use crate::daqmx::ScaleDescriptor;
+pub use crate::decimate::MinMaxBucket;
+pub use crate::diff::{diff, DiffObject, DiffOptions, DiffReport, DiffSide, Difference};
+pub use crate::error::{DetectedFormat, Result, TdmsReadError};
+pub use crate::export::{export_csv, export_csv_with_control, CsvExportOptions, CsvTimeColumn};
+pub use crate::filter::PropertyFilter;
+use crate::fingerprint::FileFingerprint;
+pub use crate::fragmentation::{ChannelFragmentation, FragmentationReport};
+#[cfg(any(feature = "sha256", feature = "xxh3"))]
+pub use crate::hash::{ContentManifest, HashAlgo};
+#[cfg(feature = "chrono")]
+pub use crate::import::{import_csv, ImportOptions, ImportReport, SkippedRow};
+pub use crate::index::{check_index, index_path, write_index, IndexStatus};
+pub use crate::inspect::{DataExtent, Layout, LocationInfo, SegmentInfo, SegmentObjectInfo};
+pub use crate::line_protocol::{export_line_protocol, LineProtocolOptions};
+pub use crate::merge::{merge, MergeCompatibility, MergeOptions, PropertyPrecedence};
+pub use crate::names::{format_suggestions, similar_names};
 use crate::object_path::{path_from_channel, path_from_group, ObjectPath, ObjectPathId};
+pub use crate::options::{ErrorPolicy, ReadOptions};
+use crate::overview::Overview;
+pub use crate::progress::{CancellationToken, ReadControl};
+pub use crate::properties::{FromTdmsValue, PropertyName, TdmsProperty, TdmsValue};
+pub use crate::raw_export::{RawExportFile, RawExportOptions};
+pub use crate::read_builder::{Lossy, Native, ReadBuilder, ReadChunks, ReadPosition};
+use crate::reader_cell::ReaderCell;
+pub use crate::repair::{finalize_in_place, repair, FinalizeResult, RepairReport};
+pub use crate::rewrite::{rewrite, RewriteObject, RewriteOptions, RewriteReport};
+pub use crate::snapshot::{
+    ChannelSnapshot, FileSnapshot, GroupSnapshot, PropertySnapshot, SnapshotChange, SnapshotDiff,
+    SnapshotOptions,
+};
+pub use crate::stats::{
+    channel_statistics, channel_statistics_with_control, channels_statistics, ChannelStatistics,
+    NumericStatistics, StatisticsOptions, StringStatistics, TimestampStatistics,
+};
+pub use crate::summary::{ChannelInfo, FileSummary, SegmentFlagUsage};
 use crate::tdms_reader::{read_metadata, TdmsReader};
+pub use crate::tdms_reader::{SkippedSegment, TdmsWarning, TdmsWarningKind};
 pub use crate::timestamp::Timestamp;
-pub use crate::types::NativeType;
-use std::cell::RefCell;
+pub use crate::toc::{TocFlag, TocMask};
+pub use crate::types::{NativeType, TdsType};
+pub use crate::visit::{DataVisitor, ValuesRef};
+#[cfg(feature = "chrono")]
+pub use crate::waveform::Gap;
+pub use crate::waveform::{SegmentTiming, WaveformTiming};
+pub use crate::writer::{TdmsWriter, WriteObject};
+#[cfg(feature = "xlsx")]
+pub use crate::xlsx::{XlsxExportOptions, XlsxTimeColumn, XLSX_MAX_DATA_ROWS};
+pub use crate::xy::XMode;
+#[cfg(feature = "chrono")]
+use chrono::Duration;
+use std::fs::File;
 use std::io::{BufReader, Read, Seek};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 
+/// A TDMS file opened for reading, with its metadata parsed.
+///
+/// Data is read through a shared reference to the file, by groups and channels that
+/// borrow it. Each read takes the underlying reader for that read only, so channels
+/// can be read in any order. Without the `sync` feature a file can be moved to another
+/// thread but not shared between threads. With the `sync` feature, reads through a
+/// shared reference are synchronized with a mutex, so a `TdmsFile<File>` can be shared
+/// between threads, although reads from different threads take turns rather than
+/// running in parallel.
 pub struct TdmsFile<R: Read + Seek> {
-    file_reader: RefCell<BufReader<R>>,
+    file_reader: ReaderCell<BufReader<R>>,
     tdms_reader: TdmsReader,
+    options: ReadOptions,
+    path: Option<PathBuf>,
+    scratch: ScratchPool,
+
+    /// Per segment envelopes of channel values, if read with `ReadOptions::overview`
+    overview: Option<Overview>,
 }
 
 pub struct Group<'a, R: Read + Seek> {
@@ -42,20 +159,269 @@ pub struct GroupIterator<'a, R: Read + Seek> {
     object_iterator: std::vec::IntoIter<ObjectPathId>,
 }
 
+/// Order in which to iterate over the channels of a group with `Group::channels_in_order`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelOrder {
+    /// The order in which channels first appear in the file's metadata
+    File,
+
+    /// Sorted by channel name
+    Name,
+}
+
 pub struct ChannelIterator<'a, R: Read + Seek> {
     file: &'a TdmsFile<R>,
     object_iterator: std::vec::IntoIter<ObjectPathId>,
 }
 
+impl TdmsFile<File> {
+    /// Open the TDMS file at a path, parsing its metadata.
+    /// Reads from the file are buffered.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<TdmsFile<File>> {
+        TdmsFile::open_with_options(path, &ReadOptions::default())
+    }
+
+    /// Open the TDMS file at a path, parsing its metadata using the specified read options
+    pub fn open_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &ReadOptions,
+    ) -> Result<TdmsFile<File>> {
+        let path = path.as_ref();
+        let with_path = |error| open_error(path, error);
+        let file = shared_file::open_shared(path).map_err(with_path)?;
+        let mut tdms_file = TdmsFile::new_with_options(file, options).map_err(with_path)?;
+        tdms_file.path = Some(path.to_path_buf());
+        Ok(tdms_file)
+    }
+
+    /// Open the TDMS file at a path, loading its parsed metadata from a cache in
+    /// `cache_dir` if the file hasn't changed since the cache was written.
+    /// Otherwise the metadata is parsed and written to the cache for next time.
+    /// Caches that are out of date, corrupt or can't be written are ignored.
+    #[cfg(feature = "cache")]
+    pub fn open_cached<P: AsRef<Path>, C: AsRef<Path>>(
+        path: P,
+        cache_dir: C,
+    ) -> Result<TdmsFile<File>> {
+        TdmsFile::open_cached_with_options(path, cache_dir, &ReadOptions::default())
+    }
+
+    /// Open the TDMS file at a path using the specified read options, loading its
+    /// parsed metadata from a cache in `cache_dir` if possible.
+    /// Caches are only used when opening with the same options.
+    #[cfg(feature = "cache")]
+    pub fn open_cached_with_options<P: AsRef<Path>, C: AsRef<Path>>(
+        path: P,
+        cache_dir: C,
+        options: &ReadOptions,
+    ) -> Result<TdmsFile<File>> {
+        let path = path.as_ref();
+        let with_path = |error| open_error(path, error);
+        let file = shared_file::open_shared(path).map_err(with_path)?;
+        let mut file_reader = BufReader::new(file);
+        let cache = MetadataCache::new(path, cache_dir.as_ref(), &mut file_reader, options)
+            .map_err(with_path)?;
+        let (tdms_reader, overview, mut cache_changed) = match cache.load(options) {
+            // An overview in the cache is kept for next time but only used if requested
+            Some((tdms_reader, overview)) => (
+                tdms_reader,
+                overview.filter(|_| options.get_overview()),
+                false,
+            ),
+            None => {
+                let tdms_reader = read_metadata(&mut file_reader, options).map_err(with_path)?;
+                (tdms_reader, None, true)
+            }
+        };
+        let mut tdms_file = TdmsFile {
+            file_reader: ReaderCell::new(file_reader),
+            tdms_reader,
+            options: options.clone(),
+            path: Some(path.to_path_buf()),
+            scratch: ScratchPool::new(),
+            overview,
+        };
+        // A cache written without an overview is updated once the overview is read
+        cache_changed |= tdms_file.read_overview().map_err(with_path)?;
+        if cache_changed {
+            cache.save(&tdms_file.tdms_reader, tdms_file.overview.as_ref());
+        }
+        Ok(tdms_file)
+    }
+}
+
+/// Error opening the file at `path`, suggesting the TDMS file to open instead if the
+/// file is a .tdms_index file
+fn open_error(path: &Path, error: TdmsReadError) -> TdmsReadError {
+    match error {
+        TdmsReadError::NotATdmsFile(DetectedFormat::TdmsIndex) => {
+            TdmsReadError::IndexFileProvided {
+                index_path: path.to_path_buf(),
+                data_path: path.with_extension("tdms"),
+            }
+        }
+        error @ TdmsReadError::FileLocked(_) => error,
+        error => TdmsReadError::PathError(path.to_path_buf(), Box::new(error)),
+    }
+}
+
 impl<R: Read + Seek> TdmsFile<R> {
     /// Create a new TdmsFile object, parsing TDMS metadata from the reader
     pub fn new(file_reader: R) -> Result<TdmsFile<R>> {
+        TdmsFile::new_with_options(file_reader, &ReadOptions::default())
+    }
+
+    /// Create a new TdmsFile object, parsing TDMS metadata from the reader
+    /// using the specified read options
+    pub fn new_with_options(file_reader: R, options: &ReadOptions) -> Result<TdmsFile<R>> {
         let mut file_reader = BufReader::new(file_reader);
-        let tdms_reader = read_metadata(&mut file_reader)?;
-        Ok(TdmsFile {
-            file_reader: RefCell::new(file_reader),
+        let tdms_reader = read_metadata(&mut file_reader, options)?;
+        let mut tdms_file = TdmsFile {
+            file_reader: ReaderCell::new(file_reader),
             tdms_reader,
-        })
+            options: options.clone(),
+            path: None,
+            scratch: ScratchPool::new(),
+            overview: None,
+        };
+        tdms_file.read_overview()?;
+        Ok(tdms_file)
+    }
+
+    /// Read the overview of the file's data if the read options ask for one and it
+    /// wasn't loaded from a cache, returning whether it was read
+    fn read_overview(&mut self) -> Result<bool> {
+        if !self.options.get_overview() || self.overview.is_some() {
+            return Ok(false);
+        }
+        self.overview = Some(Overview::read(self)?);
+        Ok(true)
+    }
+
+    /// Get the path of the file if it was opened from a path
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Read any data written to the file since it was opened or last refreshed,
+    /// for following a file that is still being written.
+    /// Returns whether any new data was found.
+    ///
+    /// Only segments added since the last read are read, unless the final segment
+    /// was incomplete, in which case all metadata is read again. Segments that are
+    /// still being written are left until their metadata is complete. If the file was
+    /// opened with an overview, it is updated with the new data.
+    pub fn refresh(&mut self) -> Result<bool> {
+        let segment_count = self.tdms_reader.segments().len();
+        let refreshed = self
+            .tdms_reader
+            .refresh(self.file_reader.get_mut(), &self.options)?;
+        if refreshed {
+            if let Some(mut overview) = self.overview.take() {
+                // The last segment is recomputed as it may have been incomplete
+                overview.update_from(self, segment_count.saturating_sub(1))?;
+                self.overview = Some(overview);
+            }
+        }
+        Ok(refreshed)
+    }
+
+    /// Get segments that could not be read and were skipped.
+    /// This is only ever non-empty when reading with `ErrorPolicy::Skip`.
+    pub fn skipped_segments(&self) -> &[SkippedSegment] {
+        self.tdms_reader.skipped_segments()
+    }
+
+    /// Get non-fatal anomalies found when reading the file, such as properties that
+    /// changed type or channels missing values because the file was truncated
+    pub fn warnings(&self) -> &[TdmsWarning] {
+        self.tdms_reader.warnings()
+    }
+
+    /// Describe each segment read from the file, for inspecting how it was read
+    pub fn segments(&self) -> Result<Vec<SegmentInfo>> {
+        inspect::segment_info(&self.tdms_reader)
+    }
+
+    /// Find which segment, object and value are stored at a byte offset in the file.
+    /// Returns None if the offset is past the end of the segments that were read.
+    pub fn locate(&self, byte_offset: u64) -> Result<Option<LocationInfo>> {
+        inspect::locate(&self.tdms_reader, byte_offset)
+    }
+
+    /// Describe how the file's data is divided between segments, for diagnosing
+    /// files that are slow to read because they were written in many small segments
+    pub fn fragmentation_report(&self) -> Result<FragmentationReport> {
+        fragmentation::fragmentation_report(&self.tdms_reader)
+    }
+
+    /// TDMS format version number of the file, 4712 for TDMS 1.0 or 4713 for TDMS 2.0,
+    /// taken from the first segment. Returns None if the file has no segments.
+    pub fn format_version(&self) -> Option<u32> {
+        self.tdms_reader
+            .segments()
+            .first()
+            .map(|segment| segment.version)
+    }
+
+    /// Summarize the file from its metadata, without reading any data
+    pub fn summary(&self) -> Result<FileSummary> {
+        summary::file_summary(self)
+    }
+
+    /// Take an owned snapshot of the file's groups, channels and properties, which can
+    /// be stored and compared with snapshots of other files using `FileSnapshot::diff`
+    pub fn snapshot(&self) -> FileSnapshot {
+        snapshot::file_snapshot(self, &SnapshotOptions::default())
+    }
+
+    /// Take a snapshot of the file, including only the properties selected by the options
+    pub fn snapshot_with_options(&self, options: &SnapshotOptions) -> FileSnapshot {
+        snapshot::file_snapshot(self, options)
+    }
+
+    /// Export the file to an Excel workbook at `path`, with a worksheet for each group
+    /// that has a column for each channel and a row for each value, and a final
+    /// "Properties" worksheet listing the properties of every object. Channel data is
+    /// read in chunks, and numbers are stored with full f64 precision.
+    #[cfg(feature = "xlsx")]
+    pub fn export_xlsx<P: AsRef<Path>>(&self, path: P, options: &XlsxExportOptions) -> Result<()> {
+        xlsx::export_xlsx(self, path.as_ref(), options)
+    }
+
+    /// Export channels to `out_dir` as files of raw little endian values without a header,
+    /// for memory mapping. Channels are given by their paths, as returned by
+    /// `Channel::path`. Each channel is written to `group__channel.f64`, with characters
+    /// that aren't safe in file names replaced and a number appended to names that are
+    /// already used, alongside a `group__channel.json` file describing the data type,
+    /// number of values and unit. Channels are read together a chunk at a time, so the
+    /// file is read through once however many channels are exported.
+    pub fn export_raw_split(
+        &self,
+        out_dir: &Path,
+        channels: &[&str],
+        options: &RawExportOptions,
+    ) -> Result<Vec<RawExportFile>> {
+        raw_export::export_raw_split(self, out_dir, channels, options)
+    }
+
+    /// Hash the raw data of every channel in the file, giving a manifest of channel
+    /// paths and hashes and a hash of all data that doesn't depend on channel order
+    #[cfg(any(feature = "sha256", feature = "xxh3"))]
+    pub fn content_manifest(&self, algorithm: HashAlgo) -> Result<ContentManifest> {
+        hash::content_manifest(self, algorithm)
+    }
+
+    /// Pass the data of every channel in the file to a visitor, a chunk at a time, in a
+    /// single pass through the file's segments in file order. Only one chunk of values is
+    /// held in memory at a time. Within a segment, the values of each channel are passed
+    /// in the order the channels are listed in the segment's metadata, so chunks of
+    /// different channels are interleaved in the order they were written.
+    /// Channels with data of a type that can't be read are skipped.
+    ///
+    /// Reading stops as soon as the visitor returns `ControlFlow::Break`.
+    pub fn visit_data(&self, visitor: &mut dyn DataVisitor) -> Result<()> {
+        visit::visit_data(self, visitor, None)
     }
 
     /// Get a group within the TDMS file
@@ -66,10 +432,97 @@ impl<R: Read + Seek> TdmsFile<R> {
             .map(move |object_id| Group::new(self, object_id))
     }
 
-    /// Get an iterator over groups within this TDMS file
+    /// Get a group within the TDMS file, or an error suggesting groups with
+    /// similar names if it doesn't exist
+    pub fn try_group<'a>(&'a self, group_name: &'a str) -> Result<Group<'a, R>> {
+        self.group(group_name).ok_or_else(|| {
+            let group_names: Vec<String> = self
+                .groups()
+                .map(|group| group.name().to_string())
+                .collect();
+            TdmsReadError::NoSuchGroup {
+                name: group_name.to_string(),
+                similar_names: similar_names(group_name, group_names.iter().map(String::as_str)),
+            }
+        })
+    }
+
+    /// Get an iterator over groups within this TDMS file, in the order they first appear.
+    /// This includes groups whose channels were written without a group object,
+    /// which have no properties, and groups that have no channels. Like the order of
+    /// channels and properties, this is the same every time the file is read.
+    /// The root object ("/") is never returned as a group, as its properties are the
+    /// file's properties, returned by `properties`.
     pub fn groups<'a>(&'a self) -> GroupIterator<'a, R> {
         GroupIterator::new(self)
     }
+
+    /// Get all channels in the file for which the predicate returns true,
+    /// in the order they first appear
+    pub fn find_channels_by<'a, F>(&'a self, mut predicate: F) -> Vec<Channel<'a, R>>
+    where
+        F: FnMut(&Channel<'a, R>) -> bool,
+    {
+        self.tdms_reader
+            .objects()
+            .filter(|(_, path)| matches!(path, ObjectPath::Channel(_, _)))
+            .map(|(object_id, _)| Channel::new(self, object_id))
+            .filter(|channel| predicate(channel))
+            .collect()
+    }
+
+    /// Get all channels in the file with properties matching a filter,
+    /// in the order they first appear
+    pub fn channels_matching(&self, filter: &PropertyFilter) -> Vec<Channel<'_, R>> {
+        self.find_channels_by(|channel| filter.matches(channel.properties()))
+    }
+
+    /// Estimate the memory in bytes needed to read all values of the channels with the
+    /// given paths, as returned by `Channel::path`, from metadata without reading any
+    /// data. See `Channel::estimated_read_bytes` for how each channel is estimated.
+    pub fn estimated_read_bytes(&self, channels: &[&str], target: Option<TdsType>) -> Result<u64> {
+        let mut bytes: u64 = 0;
+        for path in channels {
+            let channel = self.channel_by_path(path)?;
+            bytes = bytes.saturating_add(channel.estimated_read_bytes(target));
+        }
+        Ok(bytes)
+    }
+
+    /// Get a channel from its path, or an error suggesting similar names if it doesn't exist
+    fn channel_by_path(&self, path: &str) -> Result<Channel<'_, R>> {
+        let (group_name, channel_name) = match ObjectPath::parse(path)? {
+            ObjectPath::Channel(group_name, channel_name) => (group_name, channel_name),
+            _ => {
+                return Err(TdmsReadError::InvalidArgument(format!(
+                    "{} is not a channel path",
+                    path
+                )))
+            }
+        };
+        let channel_path = path_from_channel(&group_name, &channel_name);
+        match self.tdms_reader.get_object_id(&channel_path) {
+            Some(object_id) => Ok(Channel::new(self, object_id)),
+            None => {
+                self.try_group(&group_name)?.try_channel(&channel_name)?;
+                Err(TdmsReadError::InvalidArgument(format!(
+                    "No channel with path {}",
+                    path
+                )))
+            }
+        }
+    }
+
+    /// Get the properties of the root object of the file, in the order they first appear.
+    /// Properties written again in later segments keep their position and have the
+    /// latest value. The root object has no data, and any raw data index written for it
+    /// is ignored with a `RootObjectData` warning.
+    pub fn properties(&self) -> &[TdmsProperty] {
+        match self.tdms_reader.get_object_id("/") {
+            Some(object_id) => self.tdms_reader.get_properties(object_id),
+            None => &[],
+        }
+    }
 }
 
 impl<'a, R: Read + Seek> Group<'a, R> {
@@ -77,8 +530,8 @@ impl<'a, R: Read + Seek> Group<'a, R> {
         Group { file, object_id }
     }
 
-    /// Get the name of this group
-    pub fn name(&self) -> &str {
+    /// Get the name of this group, with any quotes unescaped
+    pub fn name(&self) -> &'a str {
         let group_path = self
             .file
             .tdms_reader
@@ -93,6 +546,15 @@ impl<'a, R: Read + Seek> Group<'a, R> {
         }
     }
 
+    /// Get the TDMS path of this group as it was written in the file, eg. "/'Group'",
+    /// with any quotes in the name escaped by doubling them
+    pub fn path(&self) -> &'a str {
+        self.file
+            .tdms_reader
+            .get_object_path_string(self.object_id)
+            .unwrap()
+    }
+
     /// Get a channel within this group
     pub fn channel<'b>(&'b self, channel_name: &str) -> Option<Channel<'b, R>> {
         let channel_path = path_from_channel(self.name(), channel_name);
@@ -102,9 +564,85 @@ impl<'a, R: Read + Seek> Group<'a, R> {
             .map(move |object_id| Channel::new(self.file, object_id))
     }
 
-    /// Get an iterator over channels within this group
-    pub fn channels<'b>(&'b self) -> ChannelIterator<'b, R> {
-        ChannelIterator::new(self.file, self.name())
+    /// Get a channel within this group, or an error suggesting channels with
+    /// similar names if it doesn't exist
+    pub fn try_channel<'b>(&'b self, channel_name: &str) -> Result<Channel<'b, R>> {
+        self.channel(channel_name).ok_or_else(|| {
+            let channel_names: Vec<String> = self
+                .channels()
+                .map(|channel| channel.name().to_string())
+                .collect();
+            TdmsReadError::NoSuchChannel {
+                group: self.name().to_string(),
+                name: channel_name.to_string(),
+                similar_names: similar_names(
+                    channel_name,
+                    channel_names.iter().map(String::as_str),
+                ),
+            }
+        })
+    }
+
+    /// Get an iterator over channels within this group, in the order in which they
+    /// first appear in the file's metadata. This order is the same every time the file
+    /// is read, whichever read options are used.
+    pub fn channels(&self) -> ChannelIterator<'a, R> {
+        self.channels_in_order(ChannelOrder::File)
+    }
+
+    /// Get an iterator over channels within this group in the given order
+    pub fn channels_in_order(&self, order: ChannelOrder) -> ChannelIterator<'a, R> {
+        ChannelIterator::new(self.file, self.name(), order)
+    }
+
+    /// Get the metadata of each channel within this group in file order, as owned values
+    pub fn channel_infos(&self) -> Vec<ChannelInfo> {
+        self.channels().map(|channel| channel.info()).collect()
+    }
+
+    /// Get the number of channels within this group
+    pub fn len(&self) -> usize {
+        self.channel_count()
+    }
+
+    /// Whether this group has no channels
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of channels within this group
+    pub fn channel_count(&self) -> usize {
+        let group_name = self.name();
+        self.file
+            .tdms_reader
+            .objects()
+            .filter(|(_, path)| matches!(path, ObjectPath::Channel(g, _) if g == group_name))
+            .count()
+    }
+
+    /// Get the properties of this group, in the same order as `TdmsFile::properties`
+    pub fn properties(&self) -> &[TdmsProperty] {
+        self.file.tdms_reader.get_properties(self.object_id)
+    }
+
+    /// Get the value of a property of this group, eg. `properties::names::NI_CHANNEL_NAME`
+    pub fn property(&self, name: &str) -> Option<&TdmsValue> {
+        self.properties()
+            .iter()
+            .find(|property| property.name == name)
+            .map(|property| &property.value)
+    }
+
+    /// Get the value of a property of this group converted to the type `T`, or None
+    /// if the property doesn't exist or its value can't be represented as a `T`
+    pub fn get_property<T: FromTdmsValue>(&self, name: &str) -> Option<T> {
+        self.property(name).and_then(T::from_tdms_value)
+    }
+
+    /// Get the waveform timing shared by all channels in this group, or None if the
+    /// group has no channels or any channel's timing is missing or different
+    pub fn common_waveform_timing(&self) -> Option<WaveformTiming> {
+        WaveformTiming::common(self.channels().map(|channel| channel.waveform_timing()))
     }
 }
 
@@ -113,8 +651,8 @@ impl<'a, R: Read + Seek> Channel<'a, R> {
         Channel { file, object_id }
     }
 
-    /// Get the name of this channel
-    pub fn name(&self) -> &str {
+    /// Get the name of this channel, with any quotes unescaped
+    pub fn name(&self) -> &'a str {
         let channel_path = self
             .file
             .tdms_reader
@@ -129,6 +667,102 @@ impl<'a, R: Read + Seek> Channel<'a, R> {
         }
     }
 
+    /// Get the name of the group this channel belongs to, with any quotes unescaped
+    pub fn group_name(&self) -> &'a str {
+        let channel_path = self
+            .file
+            .tdms_reader
+            .get_object_path(self.object_id)
+            .unwrap();
+        match channel_path {
+            ObjectPath::Channel(ref group_name, _) => group_name,
+            _ => panic!(
+                "Expected a channel path for object id {:?}, got {:?}",
+                self.object_id, channel_path
+            ),
+        }
+    }
+
+    /// Get the TDMS path of this channel as it was written in the file,
+    /// eg. "/'Group'/'Channel'", with any quotes in names escaped by doubling them
+    pub fn path(&self) -> &'a str {
+        self.file
+            .tdms_reader
+            .get_object_path_string(self.object_id)
+            .unwrap()
+    }
+
+    /// Get the properties of this channel, in the same order as `TdmsFile::properties`
+    pub fn properties(&self) -> &[TdmsProperty] {
+        self.file.tdms_reader.get_properties(self.object_id)
+    }
+
+    /// Get the metadata of this channel as an owned value
+    pub fn info(&self) -> ChannelInfo {
+        ChannelInfo::new(self)
+    }
+
+    /// Get the waveform timing of this channel from its properties,
+    /// or None if the channel is not a waveform
+    pub fn waveform_timing(&self) -> Option<WaveformTiming> {
+        WaveformTiming::from_properties(self.properties())
+    }
+
+    /// Get the waveform timing of each range of this channel's values, starting from each
+    /// segment where the channel's timing properties were written. Only the latest
+    /// values of properties are kept by `properties`, so when acquisition is paused and
+    /// resumed in a later segment, this gives the start time of each part of the data.
+    pub fn segments_timing(&self) -> Vec<SegmentTiming> {
+        let tdms_reader = &self.file.tdms_reader;
+        let channel_data_index = match tdms_reader.get_channel_data_index(self.object_id) {
+            Some(channel_data_index) => channel_data_index,
+            None => return Vec::new(),
+        };
+        let updates = tdms_reader
+            .get_waveform_property_updates(self.object_id)
+            .iter()
+            .map(|update| (update.segment_index, &update.properties[..]));
+        waveform::segments_timing(
+            updates,
+            |segment_index| channel_data_index.start_value_from_segment(segment_index),
+            channel_data_index.number_of_values,
+        )
+    }
+
+    /// Find gaps in this channel's timing, where the start time written for a segment
+    /// differs by more than `tolerance` from the time extrapolated from the previous
+    /// segments. Time going backwards is also reported as a gap.
+    #[cfg(feature = "chrono")]
+    pub fn detect_gaps(&self, tolerance: Duration) -> Vec<Gap> {
+        waveform::detect_gaps(&self.segments_timing(), tolerance)
+    }
+
+    /// Waveform timing properties written for this channel in each segment they were
+    /// written in, with the index of the channel's first value at or after the segment
+    pub(crate) fn waveform_property_updates(&self) -> Vec<(u64, &'a [TdmsProperty])> {
+        let tdms_reader = &self.file.tdms_reader;
+        let channel_data_index = match tdms_reader.get_channel_data_index(self.object_id) {
+            Some(channel_data_index) => channel_data_index,
+            None => return Vec::new(),
+        };
+        tdms_reader
+            .get_waveform_property_updates(self.object_id)
+            .iter()
+            .map(|update| {
+                let start_value = channel_data_index.start_value_from_segment(update.segment_index);
+                (start_value, &update.properties[..])
+            })
+            .collect()
+    }
+
+    /// Get the data type of this channel, or None if the channel has no data
+    pub fn data_type(&self) -> Option<TdsType> {
+        self.file
+            .tdms_reader
+            .get_channel_data_index(self.object_id)
+            .map(|channel_data_index| channel_data_index.data_type)
+    }
+
     /// Get the total number of values in this channel
     pub fn len(&'a self) -> u64 {
         match self.file.tdms_reader.get_channel_data_index(self.object_id) {
@@ -137,40 +771,403 @@ impl<'a, R: Read + Seek> Channel<'a, R> {
         }
     }
 
-    /// Read all data for this channel into the given buffer.
+    /// Find the index of the segment containing the value at index `value_index` and the
+    /// byte offset of the value in the file. For string data this is the offset of the
+    /// value's entry in the segment's offset table.
+    /// Returns None if the index is past the end of the channel or the value's position
+    /// cannot be determined, as for DAQmx data that cannot be read.
+    pub fn locate_value(&self, value_index: u64) -> Result<Option<(usize, u64)>> {
+        inspect::locate_value(&self.file.tdms_reader, self.object_id, value_index)
+    }
+
+    /// Get the ranges of bytes in the file containing this channel's raw data, in file order.
+    /// Extents of contiguous data never overlap, while interleaved extents span the
+    /// values of other channels between this channel's values.
+    /// DAQmx data that cannot be read has no extents.
+    pub fn data_extents(&self) -> Result<Vec<DataExtent>> {
+        self.file.tdms_reader.channel_data_extents(self.object_id)
+    }
+
+    /// Estimate the memory in bytes needed to read all values of the channel, computed
+    /// from metadata without reading any data. `target` is the type values are read as,
+    /// or None for the channel's own type. DAQmx raw data is counted as scaled f64 values.
+    /// Reading as strings counts the size of each `String` plus the string data stored in
+    /// the file, or an estimated formatted length for channels not stored as strings.
+    pub fn estimated_read_bytes(&self, target: Option<TdsType>) -> u64 {
+        estimate::channel_read_bytes(self, target)
+    }
+
+    /// Get the number of values declared in the metadata of the final segment that are
+    /// missing from the data because the file was truncated
+    pub fn truncated_values(&self) -> Result<u64> {
+        self.file
+            .tdms_reader
+            .get_truncated_value_count(self.object_id)
+    }
+
+    /// Hash the raw bytes of this channel's data as stored in the file, without
+    /// decoding values. Data written in big endian byte order has a different hash
+    /// to the same values written in little endian order.
+    #[cfg(any(feature = "sha256", feature = "xxh3"))]
+    pub fn content_hash(&self, algorithm: HashAlgo) -> Result<[u8; 32]> {
+        hash::channel_hash(self, algorithm)
+    }
+
+    /// Read all data for this channel into the given buffer. The buffer's type must be
+    /// the channel's native type, eg. `f64` for `DoubleFloat` or `DoubleFloatWithUnit`
+    /// data, otherwise a `TypeMismatch` error is returned rather than reinterpreting the
+    /// bytes. Use `read_data_as_f64` to convert numeric data of any type.
     pub fn read_all_data<T: NativeType>(&'a self, buffer: &mut [T]) -> Result<()> {
-        match self.file.tdms_reader.get_channel_data_index(self.object_id) {
-            Some(channel_data_index) => {
-                if channel_data_index.number_of_values > buffer.len() as u64 {
-                    return Err(TdmsReadError::TdmsError(format!(
-                        "Buffer length needs to be at least {}, received a buffer with length {}",
-                        channel_data_index.number_of_values,
-                        buffer.len()
-                    )));
-                }
-                let tdms_type = channel_data_index.data_type;
-                let expected_native_type = tdms_type.native_type();
-                match expected_native_type {
-                    Some(expected_native_type) if expected_native_type == T::native_type() => {
-                        // Buffer type matches expected native type, safe to read data
-                        self.file.tdms_reader.read_channel_data(
-                            &mut *self.file.file_reader.borrow_mut(),
-                            self.object_id,
-                            buffer,
-                        )?;
-                        Ok(())
-                    }
-                    Some(expected_native_type) => Err(TdmsReadError::TdmsError(format!(
-                        "Expected a buffer with item type {:?}",
-                        expected_native_type
-                    ))),
-                    None => Err(TdmsReadError::TdmsError(format!(
-                        "Reading data of type {:?} is not supported",
-                        tdms_type
-                    ))),
+        let length = self.len();
+        if length > buffer.len() as u64 {
+            return Err(TdmsReadError::InvalidArgument(format!(
+                "Buffer length needs to be at least {}, received a buffer with length {}",
+                length,
+                buffer.len()
+            )));
+        }
+        self.read().into_slice(buffer)?;
+        Ok(())
+    }
+
+    /// Read channel data starting from the value at index `offset` into the given buffer,
+    /// reading at most `buffer.len()` values.
+    /// Returns the number of values read, which is less than the buffer length
+    /// only when the end of the channel is reached.
+    pub fn read_data<T: NativeType>(&'a self, offset: u64, buffer: &mut [T]) -> Result<usize> {
+        self.read().range(offset..).into_slice(buffer)
+    }
+
+    /// Start building a read of this channel's data, for reads that need options such
+    /// as a range of values, decimation, conversion or progress reporting.
+    ///
+    /// ```no_run
+    /// # fn main() -> rstdms::Result<()> {
+    /// let tdms_file = rstdms::TdmsFile::open("data.tdms")?;
+    /// let group = tdms_file.try_group("Group")?;
+    /// let channel = group.try_channel("Channel")?;
+    /// let values = channel.read().range(1000..2000).decimate(10).into_vec::<f64>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read<'c>(&'c self) -> ReadBuilder<'c, 'a, R> {
+        ReadBuilder::new(self)
+    }
+
+    /// Continue iterating over chunks of values from where a previous iterator over
+    /// this channel's data stopped, as recorded by `ReadChunks::position`.
+    /// This is shorthand for `channel.read().resume_from(position)?.chunks()`.
+    pub fn data_chunks_from<'c, T: NativeType>(
+        &'c self,
+        position: &ReadPosition,
+    ) -> Result<ReadChunks<'c, 'a, R, Native, T>> {
+        Ok(self.read().resume_from(position)?.chunks())
+    }
+
+    /// Read every `stride`th value of this channel starting from the first, giving
+    /// `ceil(len / stride)` values for a quick preview of a large channel. Contiguous data
+    /// is read by seeking between values that are far enough apart, so reading every
+    /// 1000th value only reads a small fraction of the data. Interleaved data is read
+    /// in full and values skipped. Unlike `read_decimated`, values aren't summarized,
+    /// so peaks between the values read are missed.
+    pub fn read_strided<T: NativeType>(&self, stride: usize) -> Result<Vec<T>> {
+        strided::read_strided(self, stride)
+    }
+
+    /// Read numeric channel data of any type starting from the value at index `offset`,
+    /// converting values to f64. Returns the number of values read.
+    pub fn read_data_as_f64(&self, offset: u64, buffer: &mut [f64]) -> Result<usize> {
+        self.read()
+            .range(offset..)
+            .convert_lossy()
+            .into_slice(buffer)
+    }
+
+    /// Read up to `length` values of a channel with a custom data type registered with
+    /// `ReadOptions::register_custom_type`, starting from the value at index `offset`.
+    /// Values are decoded by the registered decoder a chunk at a time.
+    pub fn read_custom(&self, offset: u64, length: usize) -> Result<CustomValues> {
+        custom::read_custom(self, offset, length)
+    }
+
+    /// Read data of the channel's native type, which must already have been checked
+    pub(crate) fn read_native<T: NativeType>(
+        &self,
+        offset: u64,
+        buffer: &mut [T],
+    ) -> Result<usize> {
+        let tdms_reader = &self.file.tdms_reader;
+        if tdms_reader.get_channel_data_index(self.object_id).is_none() {
+            return Ok(0);
+        }
+        let reader = &mut *self.file.file_reader.borrow_mut();
+        if self.file.options.get_coalesce_reads() {
+            let mut scratch = self.scratch().take::<u8>(0);
+            tdms_reader.read_channel_data_coalesced(
+                reader,
+                self.object_id,
+                offset,
+                buffer,
+                self.chunk_config().bytes as u64,
+                &mut scratch,
+            )
+        } else {
+            tdms_reader.read_channel_data(reader, self.object_id, offset, buffer)
+        }
+    }
+
+    /// Pass all raw bytes of this channel's data to `consume` in order,
+    /// returning the total number of bytes
+    #[cfg_attr(not(any(feature = "sha256", feature = "xxh3")), allow(dead_code))]
+    pub(crate) fn read_raw_bytes<F: FnMut(&[u8])>(&self, consume: F) -> Result<u64> {
+        let mut buffer = self.scratch().take::<u8>(0);
+        self.file.tdms_reader.read_channel_bytes(
+            &mut *self.file.file_reader.borrow_mut(),
+            self.object_id,
+            self.chunk_config().bytes.max(1),
+            &mut buffer,
+            consume,
+        )
+    }
+
+    /// Pass the raw bytes of this channel's `segment_values` values in the segment at
+    /// `segment_index` to `consume`, returning the number of bytes
+    #[cfg_attr(not(any(feature = "sha256", feature = "xxh3")), allow(dead_code))]
+    pub(crate) fn read_segment_raw_bytes<F: FnMut(&[u8])>(
+        &self,
+        segment_index: usize,
+        segment_values: u64,
+        mut consume: F,
+    ) -> Result<u64> {
+        let mut buffer = self.scratch().take::<u8>(0);
+        self.file.tdms_reader.read_channel_segment_bytes(
+            &mut *self.file.file_reader.borrow_mut(),
+            self.object_id,
+            segment_index,
+            segment_values,
+            self.chunk_config().bytes.max(1),
+            &mut buffer,
+            &mut consume,
+        )
+    }
+
+    /// Size of the reads made when streaming through this channel's data
+    pub(crate) fn chunk_config(&self) -> ChunkConfig {
+        self.file.options.get_chunk_config()
+    }
+
+    /// Fingerprint of the current contents of the file the channel belongs to
+    pub(crate) fn file_fingerprint(&self) -> Result<FileFingerprint> {
+        FileFingerprint::new(&mut *self.file.file_reader.borrow_mut(), self.file_path())
+    }
+
+    /// Buffers for streaming through data that are reused between reads of the file
+    pub(crate) fn scratch(&self) -> &'a ScratchPool {
+        &self.file.scratch
+    }
+
+    /// Read DAQmx raw data, converting raw values with the channel's linear scale
+    pub(crate) fn read_daqmx_scaled(&self, offset: u64, buffer: &mut [f64]) -> Result<usize> {
+        let unsupported = |reason| TdmsReadError::UnsupportedDaqmxData {
+            path: self.path().to_string(),
+            reason,
+        };
+        self.file.tdms_reader.read_channel_daqmx_data(
+            &mut *self.file.file_reader.borrow_mut(),
+            self.object_id,
+            offset,
+            buffer,
+            |scaler| {
+                if let Some(reason) = scaler.unsupported_reason() {
+                    return Err(unsupported(reason));
                 }
+                LinearScale::from_properties(self.properties(), scaler.scale_id)
+                    .ok_or_else(|| unsupported("only linear scales are supported"))
+            },
+        )
+    }
+
+    /// Path of the file the channel belongs to, if it was opened from a path
+    pub(crate) fn file_path(&self) -> Option<&Path> {
+        self.file.path()
+    }
+
+    /// Positions and lengths of the raw data in the file containing values of the
+    /// channel from index `offset` up to `offset + length`
+    pub(crate) fn data_ranges(&self, offset: u64, length: u64) -> Vec<(u64, u64)> {
+        self.file
+            .tdms_reader
+            .channel_data_ranges(self.object_id, offset, length)
+    }
+
+    /// Check that data of this channel can be read into a buffer of type T
+    pub(crate) fn check_native_type<T: NativeType>(&self) -> Result<()> {
+        let tdms_type = match self.data_type() {
+            Some(tdms_type) => tdms_type,
+            None => return Ok(()),
+        };
+        match tdms_type.native_type() {
+            Some(native_type) if native_type == T::native_type() => Ok(()),
+            Some(_) => Err(TdmsReadError::TypeMismatch {
+                data_type: tdms_type,
+                requested: format!("{:?} values", T::native_type()),
+            }),
+            None => Err(TdmsReadError::Unsupported(format!(
+                "Reading data of type {:?} is not supported",
+                tdms_type
+            ))),
+        }
+    }
+
+    /// Check that data of this channel can be converted to f64
+    pub(crate) fn check_numeric_type(&self) -> Result<()> {
+        match self.data_type() {
+            // DAQmx raw data is scaled to f64, and custom types are converted by their decoder
+            Some(TdsType::DaqmxRawData) | Some(TdsType::Custom { .. }) => Ok(()),
+            Some(tdms_type) if tdms_type.native_type().is_none() || tdms_type.is_timestamp() => {
+                Err(TdmsReadError::TypeMismatch {
+                    data_type: tdms_type,
+                    requested: String::from("numeric values"),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Minimum and maximum of the channel's values in each segment in which it has
+    /// values, for drawing a coarse overview of the data without reading it. NaN values
+    /// are ignored, and both are NaN for a segment with only NaN values. Returns None if
+    /// the file wasn't opened with `ReadOptions::overview`, or the channel isn't numeric
+    /// or its values couldn't be read.
+    pub fn overview(&self) -> Option<Vec<(f64, f64)>> {
+        let envelopes = self
+            .file
+            .overview
+            .as_ref()?
+            .channel_envelopes(self.object_id)?;
+        Some(
+            envelopes
+                .iter()
+                .map(|envelope| (envelope.min, envelope.max))
+                .collect(),
+        )
+    }
+
+    /// Indexes of the values covered by each entry of `overview`
+    pub fn overview_value_ranges(&self) -> Option<Vec<Range<u64>>> {
+        let envelopes = self
+            .file
+            .overview
+            .as_ref()?
+            .channel_envelopes(self.object_id)?;
+        let data_index = self
+            .file
+            .tdms_reader
+            .get_channel_data_index(self.object_id)?;
+        envelopes
+            .iter()
+            .map(|envelope| {
+                let (start_value, value_count) =
+                    data_index.segment_values(envelope.segment_index)?;
+                Some(start_value..start_value + value_count)
+            })
+            .collect()
+    }
+
+    /// Read a min/max envelope of up to `length` values of a numeric channel starting at
+    /// the value at index `offset`, dividing the values into at most `bucket_count`
+    /// buckets of consecutive values. The data is read in chunks so memory use is bounded
+    /// by the number of buckets rather than the channel length.
+    pub fn read_decimated(
+        &self,
+        offset: u64,
+        length: u64,
+        bucket_count: usize,
+    ) -> Result<Vec<MinMaxBucket>> {
+        decimate::read_decimated(self, offset, length, bucket_count, &mut ReadControl::new())
+    }
+
+    /// Read a min/max envelope of channel data as with `read_decimated`, reporting
+    /// progress and checking for cancellation using the given read control
+    pub fn read_decimated_with_control(
+        &self,
+        offset: u64,
+        length: u64,
+        bucket_count: usize,
+        control: &mut ReadControl,
+    ) -> Result<Vec<MinMaxBucket>> {
+        decimate::read_decimated(self, offset, length, bucket_count, control)
+    }
+
+    /// Read all values of a numeric channel as (x, y) pairs for plotting, with x values
+    /// given by `x_mode`. Modes other than `XMode::Index` require the channel to have
+    /// waveform timing properties.
+    pub fn read_xy(&self, x_mode: XMode) -> Result<Vec<(f64, f64)>> {
+        xy::read_xy(self, x_mode, 0, self.len(), &mut ReadControl::new())
+    }
+
+    /// Read up to `length` values starting at the value at index `offset` as (x, y)
+    /// pairs, reporting progress and checking for cancellation using the given read control
+    pub fn read_xy_with_control(
+        &self,
+        x_mode: XMode,
+        offset: u64,
+        length: u64,
+        control: &mut ReadControl,
+    ) -> Result<Vec<(f64, f64)>> {
+        xy::read_xy(self, x_mode, offset, length, control)
+    }
+
+    /// Read a min/max envelope of all values of a numeric channel as with
+    /// `read_decimated`, pairing each bucket with the x value at its midpoint
+    pub fn read_xy_decimated(
+        &self,
+        x_mode: XMode,
+        bucket_count: usize,
+    ) -> Result<Vec<(f64, MinMaxBucket)>> {
+        xy::read_xy_decimated(
+            self,
+            x_mode,
+            0,
+            self.len(),
+            bucket_count,
+            &mut ReadControl::new(),
+        )
+    }
+
+    /// Read a min/max envelope of up to `length` values starting at the value at index
+    /// `offset`, pairing each bucket with the x value at its midpoint, and reporting
+    /// progress and checking for cancellation using the given read control
+    pub fn read_xy_decimated_with_control(
+        &self,
+        x_mode: XMode,
+        offset: u64,
+        length: u64,
+        bucket_count: usize,
+        control: &mut ReadControl,
+    ) -> Result<Vec<(f64, MinMaxBucket)>> {
+        xy::read_xy_decimated(self, x_mode, offset, length, bucket_count, control)
+    }
+
+    /// Read string channel data starting from the value at index `offset` into the given
+    /// buffer, reading at most `buffer.len()` values.
+    /// Returns the number of values read, which is less than the buffer length
+    /// only when the end of the channel is reached.
+    pub fn read_strings(&self, offset: u64, buffer: &mut [String]) -> Result<usize> {
+        match self.file.tdms_reader.get_channel_data_index(self.object_id) {
+            Some(channel_data_index) if channel_data_index.data_type == TdsType::String => {
+                self.file.tdms_reader.read_channel_strings(
+                    &mut *self.file.file_reader.borrow_mut(),
+                    self.object_id,
+                    offset,
+                    buffer,
+                )
             }
-            None => Ok(()),
+            Some(channel_data_index) => Err(TdmsReadError::TypeMismatch {
+                data_type: channel_data_index.data_type,
+                requested: String::from("strings"),
+            }),
+            None => Ok(0),
         }
     }
 }
@@ -204,8 +1201,8 @@ impl<'a, R: Read + Seek> Iterator for GroupIterator<'a, R> {
 }
 
 impl<'a, R: Read + Seek> ChannelIterator<'a, R> {
-    fn new(file: &'a TdmsFile<R>, group_name: &str) -> ChannelIterator<'a, R> {
-        let channel_objects: Vec<ObjectPathId> = file
+    fn new(file: &'a TdmsFile<R>, group_name: &str, order: ChannelOrder) -> ChannelIterator<'a, R> {
+        let mut channel_objects: Vec<ObjectPathId> = file
             .tdms_reader
             .objects()
             .filter(|(_, path)| match path {
@@ -214,6 +1211,9 @@ impl<'a, R: Read + Seek> ChannelIterator<'a, R> {
             })
             .map(|(id, _)| id)
             .collect();
+        if order == ChannelOrder::Name {
+            channel_objects.sort_by_key(|&object_id| Channel::new(file, object_id).name());
+        }
         ChannelIterator {
             file,
             object_iterator: channel_objects.into_iter(),
@@ -239,12 +1239,22 @@ impl<R: Read + Seek> std::fmt::Debug for TdmsFile<R> {
 
 impl<'a, R: Read + Seek> std::fmt::Debug for Group<'a, R> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Group").finish()
+        f.debug_struct("Group").field("name", &self.name()).finish()
+    }
+}
+
+/// Displays the group's name with any quotes unescaped
+impl<'a, R: Read + Seek> std::fmt::Display for Group<'a, R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
     }
 }
 
 impl<'a, R: Read + Seek> std::fmt::Debug for Channel<'a, R> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Group").finish()
+        f.debug_struct("Channel")
+            .field("group_name", &self.group_name())
+            .field("name", &self.name())
+            .finish()
     }
 }
@@ -0,0 +1,243 @@
+use crate::error::{Result, TdmsReadError};
+use crate::types::TdsType;
+use crate::visit::{self, DataVisitor, ValuesRef};
+use crate::waveform::WaveformTiming;
+use crate::Channel;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, Read, Seek, Write};
+use std::ops::ControlFlow;
+
+/// Options for exporting channel data as InfluxDB line protocol
+#[derive(Debug, Clone)]
+pub struct LineProtocolOptions {
+    /// Measurement of every line, or None to use the name of each channel's group
+    pub measurement: Option<String>,
+
+    /// Key of the field holding each value
+    pub field_key: String,
+}
+
+impl Default for LineProtocolOptions {
+    fn default() -> LineProtocolOptions {
+        LineProtocolOptions {
+            measurement: None,
+            field_key: String::from("value"),
+        }
+    }
+}
+
+/// Export channel data as InfluxDB line protocol, with one line per value of the form
+/// `<measurement>,channel=<channel name> <field key>=<value> <timestamp>`.
+///
+/// The timestamp is the waveform time of the value in nanoseconds since the Unix epoch,
+/// and is left out if the channel has no waveform timing with a start time. Integers
+/// are written as integer fields, timestamp values as integer nanoseconds since the
+/// Unix epoch and strings as string fields. NaN and infinite values can't be written
+/// and are skipped.
+///
+/// The file is read in a single pass with `TdmsFile::visit_data`, so lines of different
+/// channels are interleaved in the order their values were written. The channels must
+/// all belong to the same file. Returns the number of lines written.
+pub fn export_line_protocol<R: Read + Seek, W: Write>(
+    channels: &[Channel<R>],
+    writer: W,
+    options: &LineProtocolOptions,
+) -> Result<u64> {
+    let tdms_file = match visit::channels_file(channels)? {
+        Some(tdms_file) => tdms_file,
+        None => return Ok(0),
+    };
+    for channel in channels {
+        if let Some(data_type) = channel.data_type() {
+            if !visit::is_visitable(data_type) {
+                return Err(TdmsReadError::Unsupported(format!(
+                    "Cannot export channel {} / {} as its data type {:?} is not supported",
+                    channel.group_name(),
+                    channel.name(),
+                    data_type
+                )));
+            }
+        }
+    }
+
+    let field_key = escape_key(&options.field_key);
+    let mut visitor = LineProtocolVisitor {
+        channels: channels
+            .iter()
+            .map(|channel| {
+                let measurement = options
+                    .measurement
+                    .as_deref()
+                    .unwrap_or_else(|| channel.group_name());
+                let prefix = format!(
+                    "{},channel={} {}=",
+                    escape_measurement(measurement),
+                    escape_key(channel.name()),
+                    field_key
+                );
+                let line_channel = LineChannel {
+                    prefix,
+                    timing: channel
+                        .waveform_timing()
+                        .filter(|timing| timing.start_time.is_some()),
+                    next_index: 0,
+                };
+                (channel.path(), line_channel)
+            })
+            .collect(),
+        writer,
+        line: String::new(),
+        lines_written: 0,
+        error: None,
+    };
+    visit::visit_data(tdms_file, &mut visitor, None)?;
+    if let Some(error) = visitor.error {
+        return Err(error.into());
+    }
+    visitor.writer.flush()?;
+    Ok(visitor.lines_written)
+}
+
+/// A channel being exported, with the start of each of its lines
+struct LineChannel {
+    prefix: String,
+    timing: Option<WaveformTiming>,
+
+    /// Index of the channel's next value
+    next_index: u64,
+}
+
+/// Writes each chunk of values as lines, stopping at the first write error
+struct LineProtocolVisitor<'a, W: Write> {
+    channels: HashMap<&'a str, LineChannel>,
+    writer: W,
+
+    /// Buffer each line is formatted in before it is written
+    line: String,
+
+    lines_written: u64,
+    error: Option<io::Error>,
+}
+
+impl<'a, W: Write> LineProtocolVisitor<'a, W> {
+    /// Write a line for each value, where `format_value` appends the value to the line
+    /// and returns false if it can't be written
+    fn write_lines<T, F>(&mut self, path: &str, values: &[T], mut format_value: F) -> io::Result<()>
+    where
+        F: FnMut(&mut String, &T) -> bool,
+    {
+        let channel = match self.channels.get_mut(path) {
+            Some(channel) => channel,
+            None => return Ok(()),
+        };
+        for value in values {
+            let index = channel.next_index;
+            channel.next_index += 1;
+            self.line.clear();
+            self.line.push_str(&channel.prefix);
+            if !format_value(&mut self.line, value) {
+                continue;
+            }
+            if let Some(timestamp) = channel
+                .timing
+                .and_then(|timing| timing.absolute_timestamp(index))
+            {
+                let _ = write!(self.line, " {}", timestamp.to_unix_nanos());
+            }
+            self.line.push('\n');
+            self.writer.write_all(self.line.as_bytes())?;
+            self.lines_written += 1;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> DataVisitor for LineProtocolVisitor<'a, W> {
+    fn visit_channel(&mut self, path: &str, _data_type: TdsType) -> bool {
+        self.channels.contains_key(path)
+    }
+
+    fn chunk(&mut self, path: &str, _data_type: TdsType, values: ValuesRef<'_>) -> ControlFlow<()> {
+        let result = match values {
+            ValuesRef::I8(values) => self.write_lines(path, values, format_integer),
+            ValuesRef::I16(values) => self.write_lines(path, values, format_integer),
+            ValuesRef::I32(values) => self.write_lines(path, values, format_integer),
+            ValuesRef::I64(values) => self.write_lines(path, values, format_integer),
+            ValuesRef::U8(values) => self.write_lines(path, values, format_unsigned),
+            ValuesRef::U16(values) => self.write_lines(path, values, format_unsigned),
+            ValuesRef::U32(values) => self.write_lines(path, values, format_unsigned),
+            ValuesRef::U64(values) => self.write_lines(path, values, format_unsigned),
+            ValuesRef::F32(values) => self.write_lines(path, values, |line, value| {
+                format_float(line, *value as f64)
+            }),
+            ValuesRef::F64(values) => {
+                self.write_lines(path, values, |line, value| format_float(line, *value))
+            }
+            ValuesRef::Timestamp(values) => self.write_lines(path, values, |line, value| {
+                format_integer(line, &value.to_unix_nanos())
+            }),
+            ValuesRef::String(values) => {
+                self.write_lines(path, values, |line, value| format_string(line, value))
+            }
+        };
+        match result {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(error) => {
+                self.error = Some(error);
+                ControlFlow::Break(())
+            }
+        }
+    }
+}
+
+fn format_integer<T: std::fmt::Display>(line: &mut String, value: &T) -> bool {
+    let _ = write!(line, "{}i", value);
+    true
+}
+
+fn format_unsigned<T: std::fmt::Display>(line: &mut String, value: &T) -> bool {
+    let _ = write!(line, "{}u", value);
+    true
+}
+
+fn format_float(line: &mut String, value: f64) -> bool {
+    if !value.is_finite() {
+        return false;
+    }
+    let _ = write!(line, "{}", value);
+    true
+}
+
+fn format_string(line: &mut String, value: &str) -> bool {
+    line.push('"');
+    for character in value.chars() {
+        if character == '"' || character == '\\' {
+            line.push('\\');
+        }
+        line.push(character);
+    }
+    line.push('"');
+    true
+}
+
+/// Escape the characters with special meaning in a measurement
+fn escape_measurement(measurement: &str) -> String {
+    escape(measurement, &[',', ' '])
+}
+
+/// Escape the characters with special meaning in a tag or field key or a tag value
+fn escape_key(key: &str) -> String {
+    escape(key, &[',', '=', ' '])
+}
+
+fn escape(text: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        if special.contains(&character) {
+            escaped.push('\\');
+        }
+        escaped.push(character);
+    }
+    escaped
+}
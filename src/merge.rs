@@ -0,0 +1,236 @@
+use crate::error::{Result, TdmsReadError};
+use crate::properties::TdmsProperty;
+use crate::types::TdsType;
+use crate::writer::{copy_channel_data_with_timing, TdmsWriter, WriteObject};
+use crate::TdmsFile;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Determines which sets of channels are allowed to be merged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeCompatibility {
+    /// All files must contain exactly the same groups and channels,
+    /// with the same data types
+    Strict,
+
+    /// Files may contain different groups and channels. Channels that are missing
+    /// from a file contribute no data for that file, so there will be gaps in the
+    /// merged data. Channels that appear in multiple files must have the same data type.
+    Union,
+}
+
+/// Determines which file's value is used when a property is set in multiple files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyPrecedence {
+    /// Use the value from the first file the property appears in
+    First,
+
+    /// Use the value from the last file the property appears in
+    Last,
+}
+
+/// Options for merging TDMS files
+#[derive(Debug, Clone)]
+pub struct MergeOptions {
+    /// Which sets of channels are allowed to be merged
+    pub compatibility: MergeCompatibility,
+
+    /// Which file's value is used for properties set in multiple files
+    pub property_precedence: PropertyPrecedence,
+}
+
+impl Default for MergeOptions {
+    fn default() -> MergeOptions {
+        MergeOptions {
+            compatibility: MergeCompatibility::Strict,
+            property_precedence: PropertyPrecedence::First,
+        }
+    }
+}
+
+/// Key identifying a group (with no channel name) or a channel
+type ObjectKey = (String, Option<String>);
+
+struct MergedObject {
+    key: ObjectKey,
+    data_type: Option<TdsType>,
+    properties: Vec<TdmsProperty>,
+}
+
+#[derive(Default)]
+struct MergedLayout {
+    root_properties: Vec<TdmsProperty>,
+    objects: Vec<MergedObject>,
+    object_indices: HashMap<ObjectKey, usize>,
+}
+
+/// Merge multiple TDMS files into a single file.
+///
+/// The data for each channel is the concatenation of the channel's data in each input
+/// file, in the order the files are given. Data is streamed to the output in bounded
+/// chunks so files of any size can be merged.
+///
+/// Properties are merged according to `MergeOptions::property_precedence`. Waveform
+/// timing properties are also written with each file's data, in the segment of the first
+/// value they apply to, so the start time of each file is kept and gaps in time between
+/// files can be found with `Channel::segments_timing` and `Channel::detect_gaps`. As the
+/// latest values of properties are used, a merged channel's `wf_start_time` property is
+/// the start time of the last file with data for the channel.
+pub fn merge<W: Write>(inputs: &[PathBuf], output: W, options: &MergeOptions) -> Result<()> {
+    let mut layout = MergedLayout::default();
+    let mut first_file_keys: Option<Vec<ObjectKey>> = None;
+    for path in inputs {
+        let tdms_file = open_input(path)?;
+        let file_keys = layout.add_file(&tdms_file, path, options)?;
+        if options.compatibility == MergeCompatibility::Strict {
+            match first_file_keys {
+                None => first_file_keys = Some(file_keys),
+                Some(ref first_keys) => check_same_objects(first_keys, &file_keys, path)?,
+            }
+        }
+    }
+
+    let mut writer = TdmsWriter::new(output);
+    let mut metadata_objects = vec![WriteObject::root().with_properties(&layout.root_properties)];
+    for object in layout.objects.iter() {
+        let write_object = match object.key {
+            (ref group_name, None) => WriteObject::group(group_name),
+            (ref group_name, Some(ref channel_name)) => {
+                WriteObject::channel(group_name, channel_name)
+            }
+        };
+        metadata_objects.push(write_object.with_properties(&object.properties));
+    }
+    writer.write_segment(&metadata_objects)?;
+
+    for path in inputs {
+        let tdms_file = open_input(path)?;
+        for group in tdms_file.groups() {
+            for channel in group.channels() {
+                if copy_channel_data_with_timing(&channel, &mut writer)?.is_none() {
+                    return Err(TdmsReadError::Unsupported(format!(
+                        "Cannot merge data for channel {} / {} in {} as its data type is not supported",
+                        group.name(),
+                        channel.name(),
+                        path.display()
+                    )));
+                }
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn open_input(path: &Path) -> Result<TdmsFile<File>> {
+    TdmsFile::open(path)
+}
+
+fn check_same_objects(
+    first_keys: &[ObjectKey],
+    file_keys: &[ObjectKey],
+    path: &Path,
+) -> Result<()> {
+    let missing = first_keys.iter().find(|key| !file_keys.contains(key));
+    let extra = file_keys.iter().find(|key| !first_keys.contains(key));
+    match (missing, extra) {
+        (Some(key), _) => Err(TdmsReadError::Incompatible(format!(
+            "{} is missing {}, which is in the first input file",
+            path.display(),
+            describe_object(key)
+        ))),
+        (None, Some(key)) => Err(TdmsReadError::Incompatible(format!(
+            "{} contains {}, which is not in the first input file",
+            path.display(),
+            describe_object(key)
+        ))),
+        (None, None) => Ok(()),
+    }
+}
+
+fn describe_object(key: &ObjectKey) -> String {
+    match key {
+        (group_name, None) => format!("group {}", group_name),
+        (group_name, Some(channel_name)) => format!("channel {} / {}", group_name, channel_name),
+    }
+}
+
+impl MergedLayout {
+    /// Add the objects and properties of a file, returning the keys of all objects in the file
+    fn add_file(
+        &mut self,
+        tdms_file: &TdmsFile<File>,
+        path: &Path,
+        options: &MergeOptions,
+    ) -> Result<Vec<ObjectKey>> {
+        let precedence = options.property_precedence;
+        merge_properties(
+            &mut self.root_properties,
+            tdms_file.properties(),
+            precedence,
+        );
+
+        let mut file_keys = Vec::new();
+        for group in tdms_file.groups() {
+            let key = (group.name().to_string(), None);
+            let object = self.get_or_add_object(key.clone());
+            merge_properties(&mut object.properties, group.properties(), precedence);
+            file_keys.push(key);
+
+            for channel in group.channels() {
+                let key = (group.name().to_string(), Some(channel.name().to_string()));
+                let object = self.get_or_add_object(key.clone());
+                match (object.data_type, channel.data_type()) {
+                    (Some(merged_type), Some(channel_type)) if merged_type != channel_type => {
+                        return Err(TdmsReadError::Incompatible(format!(
+                            "Channel {} / {} has data type {:?} in {} but {:?} in a previous input file",
+                            group.name(),
+                            channel.name(),
+                            channel_type,
+                            path.display(),
+                            merged_type
+                        )));
+                    }
+                    (None, Some(channel_type)) => object.data_type = Some(channel_type),
+                    _ => {}
+                }
+                merge_properties(&mut object.properties, channel.properties(), precedence);
+                file_keys.push(key);
+            }
+        }
+        Ok(file_keys)
+    }
+
+    fn get_or_add_object(&mut self, key: ObjectKey) -> &mut MergedObject {
+        let objects = &mut self.objects;
+        let index = *self.object_indices.entry(key.clone()).or_insert_with(|| {
+            objects.push(MergedObject {
+                key,
+                data_type: None,
+                properties: Vec::new(),
+            });
+            objects.len() - 1
+        });
+        &mut self.objects[index]
+    }
+}
+
+fn merge_properties(
+    merged: &mut Vec<TdmsProperty>,
+    properties: &[TdmsProperty],
+    precedence: PropertyPrecedence,
+) {
+    for property in properties {
+        match merged.iter_mut().find(|p| p.name == property.name) {
+            Some(existing) => {
+                if precedence == PropertyPrecedence::Last {
+                    existing.value = property.value.clone();
+                }
+            }
+            None => merged.push(property.clone()),
+        }
+    }
+}
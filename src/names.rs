@@ -0,0 +1,109 @@
+/// Find names that are similar to a name that wasn't found, for suggesting what may
+/// have been meant. Names are similar if they differ only in case or surrounding
+/// whitespace, or are within a small edit distance. The most similar names are first.
+pub fn similar_names<'a, I>(name: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let normalized_name = normalize(name);
+    let max_distance = (normalized_name.chars().count() / 4).clamp(1, 3);
+    let mut matches: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .filter_map(|candidate| {
+            let distance = edit_distance(&normalized_name, &normalize(candidate));
+            if distance <= max_distance {
+                Some((distance, candidate))
+            } else {
+                None
+            }
+        })
+        .collect();
+    matches.sort();
+    matches
+        .into_iter()
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Format names as a suggestion to append to an error message,
+/// eg. ", did you mean "Channel " or "channel"?"
+pub fn format_suggestions(names: &[String]) -> String {
+    if names.is_empty() {
+        return String::new();
+    }
+    let quoted: Vec<String> = names.iter().map(|name| format!("{:?}", name)).collect();
+    format!(", did you mean {}?", quoted.join(" or "))
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Levenshtein distance between two strings, counted in characters
+fn edit_distance(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=right.len()).collect();
+    let mut current_row = vec![0; right.len() + 1];
+    for (i, left_char) in left.chars().enumerate() {
+        current_row[0] = i + 1;
+        for (j, right_char) in right.iter().enumerate() {
+            let substitution_cost = if left_char == *right_char { 0 } else { 1 };
+            current_row[j + 1] = (previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(current_row[j] + 1);
+        }
+        std::mem::swap(&mut previous_row, &mut current_row);
+    }
+    previous_row[right.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn edit_distances() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("Voltage", "Voltgae"), 2);
+        assert_eq!(edit_distance("µs", "us"), 1);
+    }
+
+    #[test]
+    pub fn finds_names_differing_in_whitespace_and_case() {
+        let candidates = ["Voltage ", "Current", "VOLTAGE", "Temperature"];
+
+        let names = similar_names("Voltage", candidates.iter().copied());
+
+        assert_eq!(names, vec!["VOLTAGE", "Voltage "]);
+    }
+
+    #[test]
+    pub fn finds_names_with_small_edit_distance() {
+        let candidates = ["Channel1", "Channel12", "Chanel", "Other"];
+
+        let names = similar_names("Channel", candidates.iter().copied());
+
+        assert_eq!(names, vec!["Chanel", "Channel1"]);
+    }
+
+    #[test]
+    pub fn short_names_allow_one_edit() {
+        let candidates = ["a", "ab", "abc"];
+
+        let names = similar_names("b", candidates.iter().copied());
+
+        assert_eq!(names, vec!["a", "ab"]);
+    }
+
+    #[test]
+    pub fn format_suggestion_list() {
+        assert_eq!(format_suggestions(&[]), "");
+        assert_eq!(
+            format_suggestions(&[String::from("a "), String::from("A")]),
+            ", did you mean \"a \" or \"A\"?"
+        );
+    }
+}
@@ -41,6 +41,24 @@ impl<T> ObjectMap<T> {
         }
     }
 
+    /// Get a mutable reference to the value associated with an object,
+    /// first setting it to the result of `default` if not set
+    pub fn get_or_insert_with<F: FnOnce() -> T>(
+        &mut self,
+        object: ObjectPathId,
+        default: F,
+    ) -> &mut T {
+        if self.get(object).is_none() {
+            self.set(object, default());
+        }
+        self.values[object.as_usize()].as_mut().unwrap()
+    }
+
+    /// Iterate over mutable references to all values that are set
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.values.iter_mut().filter_map(|value| value.as_mut())
+    }
+
     /// Clear all values without changing the vector capacity
     pub fn clear(&mut self) {
         self.values.clear();
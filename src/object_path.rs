@@ -1,6 +1,7 @@
 use crate::error::{Result, TdmsReadError};
-use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
 
 pub fn path_from_group(group_name: &str) -> String {
     format!("/'{}'", group_name.replace("'", "''"))
@@ -21,6 +22,19 @@ pub enum ObjectPath {
     Channel(String, String),
 }
 
+/// Formats the object path as it is written in TDMS files, eg. "/'Group'/'Channel'"
+impl fmt::Display for ObjectPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ObjectPath::Root => write!(f, "/"),
+            ObjectPath::Group(ref group) => write!(f, "{}", path_from_group(group)),
+            ObjectPath::Channel(ref group, ref channel) => {
+                write!(f, "{}", path_from_channel(group, channel))
+            }
+        }
+    }
+}
+
 enum PathParserState {
     /// We're expecting the beginning of a new componet
     ComponentStart,
@@ -58,7 +72,7 @@ impl ObjectPath {
                             parser_state = PathParserState::InComponent(start_index + 2);
                         }
                         _ => {
-                            return Err(TdmsReadError::TdmsError(format!(
+                            return Err(TdmsReadError::InvalidData(format!(
                                 "Invalid object path {}",
                                 input_string
                             )))
@@ -81,7 +95,7 @@ impl ObjectPath {
                         }
                         (None, _) => {
                             // Unexpected end of path
-                            return Err(TdmsReadError::TdmsError(format!(
+                            return Err(TdmsReadError::InvalidData(format!(
                                 "Invalid object path {}",
                                 input_string
                             )));
@@ -100,7 +114,7 @@ impl ObjectPath {
                 group_name.replace("''", "'"),
                 channel_name.replace("''", "'"),
             )),
-            _ => Err(TdmsReadError::TdmsError(format!(
+            _ => Err(TdmsReadError::InvalidData(format!(
                 "Invalid object path '{}' with more than 2 components",
                 input_string
             ))),
@@ -119,8 +133,10 @@ impl ObjectPathId {
 
 #[derive(Debug)]
 pub struct ObjectPathCache {
-    path_to_id: HashMap<String, ObjectPathId>,
+    /// Paths as written in the file, shared between the map keys and `id_to_path_string`
+    path_to_id: HashMap<Arc<str>, ObjectPathId>,
     id_to_path: Vec<ObjectPath>,
+    id_to_path_string: Vec<Arc<str>>,
 }
 
 impl ObjectPathCache {
@@ -128,6 +144,7 @@ impl ObjectPathCache {
         ObjectPathCache {
             path_to_id: HashMap::new(),
             id_to_path: Vec::new(),
+            id_to_path_string: Vec::new(),
         }
     }
 
@@ -147,6 +164,13 @@ impl ObjectPathCache {
         }
     }
 
+    /// Get the path of an object as it was written in the file, eg. "/'Group'/'Channel'"
+    pub fn get_path_string(&self, object_path_id: ObjectPathId) -> Option<&str> {
+        self.id_to_path_string
+            .get(object_path_id.as_usize())
+            .map(|path| path.as_ref())
+    }
+
     pub fn get_or_create_id(&mut self, path: String) -> Result<ObjectPathId> {
         let (path_id, created) = self.get_or_create_id_internal(path)?;
         if created {
@@ -162,6 +186,20 @@ impl ObjectPathCache {
         Ok(path_id)
     }
 
+    pub fn len(&self) -> usize {
+        self.id_to_path.len()
+    }
+
+    /// Remove the paths created after the first `len`, which nothing else may refer to
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.id_to_path.len() {
+            for path in self.id_to_path_string.drain(len..) {
+                self.path_to_id.remove(&path);
+            }
+            self.id_to_path.truncate(len);
+        }
+    }
+
     pub fn objects(&self) -> impl Iterator<Item = (ObjectPathId, &ObjectPath)> {
         self.id_to_path
             .iter()
@@ -170,17 +208,16 @@ impl ObjectPathCache {
     }
 
     fn get_or_create_id_internal(&mut self, path: String) -> Result<(ObjectPathId, bool)> {
-        match self.path_to_id.entry(path) {
-            Entry::Occupied(occupied_entry) => Ok((*occupied_entry.get(), false)),
-            Entry::Vacant(vacant_entry) => {
-                let object_path = ObjectPath::parse(vacant_entry.key())?;
-                let next_id = self.id_to_path.len();
-                let new_id = ObjectPathId(next_id);
-                self.id_to_path.push(object_path);
-                vacant_entry.insert(new_id);
-                Ok((new_id, true))
-            }
+        if let Some(&obj_id) = self.path_to_id.get(path.as_str()) {
+            return Ok((obj_id, false));
         }
+        let object_path = ObjectPath::parse(&path)?;
+        let path: Arc<str> = Arc::from(path);
+        let new_id = ObjectPathId(self.id_to_path.len());
+        self.id_to_path.push(object_path);
+        self.id_to_path_string.push(path.clone());
+        self.path_to_id.insert(path, new_id);
+        Ok((new_id, true))
     }
 }
 
@@ -198,6 +235,24 @@ mod test {
         assert_eq!(found_id, Some(object_id));
     }
 
+    #[test]
+    fn retrieve_path_string() {
+        let mut object_path_cache = ObjectPathCache::new();
+        let object_id = object_path_cache
+            .get_or_create_id("/'group'/'it''s'".to_string())
+            .unwrap();
+        let group_id = object_path_cache.get_id("/'group'").unwrap();
+
+        assert_eq!(
+            object_path_cache.get_path_string(object_id),
+            Some("/'group'/'it''s'")
+        );
+        assert_eq!(
+            object_path_cache.get_path_string(group_id),
+            Some("/'group'")
+        );
+    }
+
     #[test]
     fn different_ids() {
         let mut object_path_cache = ObjectPathCache::new();
@@ -0,0 +1,140 @@
+use crate::chunk::ChunkConfig;
+use crate::custom::{CustomDecoder, CustomTypes};
+use std::sync::Arc;
+
+/// Determines how errors encountered while reading TDMS metadata are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Return an error as soon as any segment cannot be read
+    Fail,
+
+    /// Skip over segments that cannot be read, recording them so they can be
+    /// inspected with `TdmsFile::skipped_segments`
+    Skip,
+}
+
+/// Options for reading a TDMS file
+#[derive(Debug, Clone)]
+pub struct ReadOptions {
+    error_policy: ErrorPolicy,
+    parallel_metadata: bool,
+    strict_toc_flags: bool,
+    chunk_config: ChunkConfig,
+    coalesce_reads: bool,
+    custom_types: CustomTypes,
+    overview: bool,
+}
+
+impl ReadOptions {
+    /// Create read options with default values
+    pub fn new() -> ReadOptions {
+        ReadOptions {
+            error_policy: ErrorPolicy::Fail,
+            parallel_metadata: false,
+            strict_toc_flags: false,
+            chunk_config: ChunkConfig::default(),
+            coalesce_reads: true,
+            custom_types: CustomTypes::default(),
+            overview: false,
+        }
+    }
+
+    /// Set how errors reading segment metadata should be handled
+    pub fn error_policy(mut self, error_policy: ErrorPolicy) -> ReadOptions {
+        self.error_policy = error_policy;
+        self
+    }
+
+    /// Set whether segment metadata should be parsed in parallel, which can be much faster
+    /// for files with many segments. The result is the same as when reading sequentially,
+    /// and the file is read sequentially if any segment can't be read. This requires the
+    /// `parallel` feature, otherwise metadata is always read sequentially.
+    pub fn parallel_metadata(mut self, parallel_metadata: bool) -> ReadOptions {
+        self.parallel_metadata = parallel_metadata;
+        self
+    }
+
+    /// Set whether segments with unknown table of contents flags should be refused
+    /// rather than read with a warning. Unknown flags may be used by newer versions of
+    /// the format to change the segment layout, so reading these segments could give
+    /// the wrong data. Refused segments are errors handled by the error policy.
+    pub fn strict_toc_flags(mut self, strict_toc_flags: bool) -> ReadOptions {
+        self.strict_toc_flags = strict_toc_flags;
+        self
+    }
+
+    /// Set the size of the reads made when streaming through channel data, for example
+    /// when iterating over chunks, computing statistics, exporting or hashing
+    pub fn chunk_config(mut self, chunk_config: ChunkConfig) -> ReadOptions {
+        self.chunk_config = chunk_config;
+        self
+    }
+
+    /// Set whether the data of consecutive segments that are close together in the file
+    /// should be read with a single read, up to the chunk configuration size, rather than
+    /// reading each segment's data separately. This makes reading files with many small
+    /// segments much faster and is enabled by default.
+    pub fn coalesce_reads(mut self, coalesce_reads: bool) -> ReadOptions {
+        self.coalesce_reads = coalesce_reads;
+        self
+    }
+
+    /// Register a vendor specific data type for channel raw data, so that files using
+    /// type code `code` can be read rather than being invalid. Values are `size` bytes
+    /// each, and are decoded a chunk at a time by `decoder` when read with
+    /// `Channel::read_custom` or converted to f64. Codes of the standard types can't be
+    /// replaced, and registering a code again replaces the previous registration.
+    pub fn register_custom_type(
+        mut self,
+        code: u32,
+        size: usize,
+        decoder: Arc<dyn CustomDecoder>,
+    ) -> ReadOptions {
+        self.custom_types.register(code, size, decoder);
+        self
+    }
+
+    /// Set whether to compute the minimum and maximum of each numeric channel's values
+    /// in each segment when opening the file, for drawing an overview of the data with
+    /// `Channel::overview` before reading it. This reads all of the file's data once, so
+    /// it is mainly useful with `TdmsFile::open_cached_with_options`, which stores the
+    /// overview in the cache so it is only computed when the file changes.
+    pub fn overview(mut self, overview: bool) -> ReadOptions {
+        self.overview = overview;
+        self
+    }
+
+    pub fn get_error_policy(&self) -> ErrorPolicy {
+        self.error_policy
+    }
+
+    pub fn get_parallel_metadata(&self) -> bool {
+        self.parallel_metadata
+    }
+
+    pub fn get_strict_toc_flags(&self) -> bool {
+        self.strict_toc_flags
+    }
+
+    pub fn get_chunk_config(&self) -> ChunkConfig {
+        self.chunk_config
+    }
+
+    pub fn get_coalesce_reads(&self) -> bool {
+        self.coalesce_reads
+    }
+
+    pub fn get_overview(&self) -> bool {
+        self.overview
+    }
+
+    pub(crate) fn get_custom_types(&self) -> &CustomTypes {
+        &self.custom_types
+    }
+}
+
+impl Default for ReadOptions {
+    fn default() -> ReadOptions {
+        ReadOptions::new()
+    }
+}
@@ -0,0 +1,175 @@
+#[cfg(feature = "cache")]
+use crate::cache::CachedOverview;
+use crate::error::{Result, TdmsReadError};
+use crate::object_path::ObjectPathId;
+#[cfg(feature = "cache")]
+use crate::tdms_reader::TdmsReader;
+use crate::visit::{self, SegmentValues};
+use crate::{Channel, TdmsFile};
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+use std::ops::ControlFlow;
+
+/// Minimum and maximum of a channel's values in one segment. Both are NaN if all of the
+/// segment's values are NaN.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SegmentEnvelope {
+    pub segment_index: usize,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Per segment envelopes of the values of each numeric channel in a file, computed when
+/// opening the file with `ReadOptions::overview`
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Overview {
+    /// Envelopes of each segment with values, in segment order
+    channels: HashMap<ObjectPathId, Vec<SegmentEnvelope>>,
+}
+
+impl Overview {
+    /// Compute the overview of a file by reading all of its numeric channel data
+    pub fn read<R: Read + Seek>(tdms_file: &TdmsFile<R>) -> Result<Overview> {
+        let mut overview = Overview::default();
+        overview.update_from(tdms_file, 0)?;
+        Ok(overview)
+    }
+
+    /// Recompute the envelopes of segments from the segment at index `first_segment`,
+    /// after segments have been added to the file
+    pub fn update_from<R: Read + Seek>(
+        &mut self,
+        tdms_file: &TdmsFile<R>,
+        first_segment: usize,
+    ) -> Result<()> {
+        for envelopes in self.channels.values_mut() {
+            envelopes.retain(|envelope| envelope.segment_index < first_segment);
+        }
+        visit::for_each_segment_values(tdms_file, first_segment, |channel, segment_values| {
+            if channel.check_numeric_type().is_err() {
+                return Ok(ControlFlow::Continue(()));
+            }
+            // Channels with earlier values but no envelopes couldn't be read, and are
+            // left out rather than having an incomplete overview
+            if segment_values.start_value > 0 && !self.channels.contains_key(&channel.object_id) {
+                return Ok(ControlFlow::Continue(()));
+            }
+            match segment_envelope(channel, segment_values) {
+                Ok(envelope) => self
+                    .channels
+                    .entry(channel.object_id)
+                    .or_default()
+                    .push(envelope),
+                Err(error @ TdmsReadError::IoError(_)) => return Err(error),
+                Err(_) => {
+                    self.channels.remove(&channel.object_id);
+                }
+            }
+            Ok(ControlFlow::Continue(()))
+        })
+    }
+
+    /// Envelopes of each segment in which a channel has values, or None if the channel
+    /// isn't numeric or its values couldn't be read
+    pub fn channel_envelopes(&self, object_id: ObjectPathId) -> Option<&[SegmentEnvelope]> {
+        self.channels
+            .get(&object_id)
+            .map(|envelopes| &envelopes[..])
+    }
+
+    /// Copy the overview into a form that can be written to a cache, with channels in
+    /// order of object id
+    #[cfg(feature = "cache")]
+    pub fn to_cached(&self) -> CachedOverview {
+        let mut cached: CachedOverview = self
+            .channels
+            .iter()
+            .map(|(object_id, envelopes)| {
+                let envelopes = envelopes
+                    .iter()
+                    .map(|envelope| (envelope.segment_index, envelope.min, envelope.max))
+                    .collect();
+                (object_id.as_usize(), envelopes)
+            })
+            .collect();
+        cached.sort_by_key(|(object_id, _)| *object_id);
+        cached
+    }
+
+    /// Restore an overview loaded from a cache, for the metadata loaded from the same cache
+    #[cfg(feature = "cache")]
+    pub fn from_cached(cached: CachedOverview, tdms_reader: &TdmsReader) -> Result<Overview> {
+        let object_ids: HashMap<usize, ObjectPathId> = tdms_reader
+            .objects()
+            .map(|(object_id, _)| (object_id.as_usize(), object_id))
+            .collect();
+        let segment_count = tdms_reader.segments().len();
+        let invalid = |message: &str| TdmsReadError::InvalidCache(String::from(message));
+        let mut channels = HashMap::with_capacity(cached.len());
+        for (object_id, envelopes) in cached {
+            let object_id = *object_ids
+                .get(&object_id)
+                .ok_or_else(|| invalid("overview object id is out of range"))?;
+            let envelopes = envelopes
+                .into_iter()
+                .map(|(segment_index, min, max)| {
+                    if segment_index < segment_count {
+                        Ok(SegmentEnvelope {
+                            segment_index,
+                            min,
+                            max,
+                        })
+                    } else {
+                        Err(invalid("overview segment index is out of range"))
+                    }
+                })
+                .collect::<Result<Vec<_>>>()?;
+            channels.insert(object_id, envelopes);
+        }
+        Ok(Overview { channels })
+    }
+}
+
+/// Read a channel's values in one segment a chunk at a time to find their envelope
+fn segment_envelope<R: Read + Seek>(
+    channel: &Channel<R>,
+    segment_values: SegmentValues,
+) -> Result<SegmentEnvelope> {
+    let SegmentValues {
+        segment_index,
+        start_value,
+        value_count,
+    } = segment_values;
+    let chunk_size = channel
+        .chunk_config()
+        .values(std::mem::size_of::<f64>())
+        .min(value_count as usize);
+    let mut buffer = channel.scratch().take::<f64>(chunk_size);
+    let mut min = f64::NAN;
+    let mut max = f64::NAN;
+    let end = start_value + value_count;
+    let mut offset = start_value;
+    while offset < end {
+        let length = buffer.len().min((end - offset) as usize);
+        let values_read = channel.read_data_as_f64(offset, &mut buffer[..length])?;
+        if values_read == 0 {
+            return Err(TdmsReadError::InvalidData(format!(
+                "Expected {} values of channel {} from index {} but none could be read",
+                end - offset,
+                channel.path(),
+                offset
+            )));
+        }
+        // min and max ignore NaN values unless both are NaN
+        for value in &buffer[..values_read] {
+            min = min.min(*value);
+            max = max.max(*value);
+        }
+        offset += values_read as u64;
+    }
+    Ok(SegmentEnvelope {
+        segment_index,
+        min,
+        max,
+    })
+}
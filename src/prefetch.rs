@@ -0,0 +1,100 @@
+use crate::shared_file;
+use log::debug;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// Size of the buffer that prefetched bytes are read into
+const PREFETCH_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Reads ranges of a file on a background thread ahead of when they're needed,
+/// so that the operating system has them cached by the time they're read for
+/// decoding. This overlaps waiting for slow storage, such as network file systems,
+/// with decoding and processing data that has already been read.
+pub struct Prefetcher {
+    sender: Option<SyncSender<Vec<(u64, u64)>>>,
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Prefetcher {
+    /// Start prefetching from a separate handle to the file at `path`, with at most
+    /// `queue_length` requests waiting to be read. Returns None if the file can't be opened.
+    pub fn start(path: &Path, queue_length: usize) -> Option<Prefetcher> {
+        let mut file = match shared_file::open_shared(path) {
+            Ok(file) => file,
+            Err(e) => {
+                debug!("Not prefetching from {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        let (sender, receiver) = sync_channel::<Vec<(u64, u64)>>(queue_length);
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread_stopped = stopped.clone();
+        let thread = std::thread::spawn(move || {
+            let mut buffer = vec![0u8; PREFETCH_BUFFER_SIZE];
+            for ranges in receiver {
+                for (position, length) in ranges {
+                    let result =
+                        read_range(&mut file, &mut buffer, position, length, &thread_stopped);
+                    if let Err(e) = result {
+                        debug!("Prefetching from position {} failed: {}", position, e);
+                    }
+                }
+            }
+        });
+        Some(Prefetcher {
+            sender: Some(sender),
+            stopped,
+            thread: Some(thread),
+        })
+    }
+
+    /// Request ranges of the file to be read, given as positions and lengths.
+    /// The request is dropped if the queue is full, so this never blocks.
+    pub fn request(&self, ranges: Vec<(u64, u64)>) {
+        if let Some(sender) = self.sender.as_ref() {
+            match sender.try_send(ranges) {
+                Ok(()) | Err(TrySendError::Full(_)) => {}
+                Err(TrySendError::Disconnected(_)) => debug!("Prefetch thread has stopped"),
+            }
+        }
+    }
+}
+
+impl Drop for Prefetcher {
+    fn drop(&mut self) {
+        // Abandon any queued requests and close the queue so the thread finishes
+        self.stopped.store(true, Ordering::Relaxed);
+        self.sender = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Read a range of the file into the buffer, discarding the bytes, until the range
+/// is read or prefetching is stopped
+fn read_range(
+    file: &mut File,
+    buffer: &mut [u8],
+    position: u64,
+    length: u64,
+    stopped: &AtomicBool,
+) -> std::io::Result<()> {
+    file.seek(SeekFrom::Start(position))?;
+    let mut remaining = length;
+    while remaining > 0 && !stopped.load(Ordering::Relaxed) {
+        let read_length = remaining.min(buffer.len() as u64) as usize;
+        let bytes_read = file.read(&mut buffer[..read_length])?;
+        if bytes_read == 0 {
+            break;
+        }
+        remaining -= bytes_read as u64;
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A token used to cancel a long running read, which may be shared between threads.
+/// Reads that are cancelled return `TdmsReadError::Cancelled`.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a new token that has not been cancelled
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Request cancellation of any reads using this token or a clone of it
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Allows monitoring the progress of a long running read and cancelling it
+#[derive(Default)]
+pub struct ReadControl<'a> {
+    on_progress: Option<Box<dyn FnMut(u64, u64) + 'a>>,
+    cancellation_token: Option<CancellationToken>,
+}
+
+impl<'a> ReadControl<'a> {
+    /// Create a read control with no progress callback or cancellation token
+    pub fn new() -> ReadControl<'a> {
+        ReadControl::default()
+    }
+
+    /// Set a callback to be called periodically during the read with the number of
+    /// values processed so far and the total number of values to be processed
+    pub fn on_progress<F: FnMut(u64, u64) + 'a>(mut self, on_progress: F) -> ReadControl<'a> {
+        self.on_progress = Some(Box::new(on_progress));
+        self
+    }
+
+    /// Set a token that may be used to cancel the read
+    pub fn cancellation_token(mut self, token: CancellationToken) -> ReadControl<'a> {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        match self.cancellation_token {
+            Some(ref token) => token.is_cancelled(),
+            None => false,
+        }
+    }
+
+    pub(crate) fn report_progress(&mut self, processed: u64, total: u64) {
+        if let Some(ref mut on_progress) = self.on_progress {
+            on_progress(processed, total);
+        }
+    }
+}
@@ -1,11 +1,71 @@
 use crate::error::{Result, TdmsReadError};
 use crate::timestamp::Timestamp;
-use byteorder::ReadBytesExt;
-use std::io::Read;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Read, Write};
+use std::ops::Deref;
+use std::sync::Arc;
 
-use crate::types::{read_string, read_timestamp, ByteOrderExt, TdsType};
+use crate::types::{
+    read_string, read_timestamp, write_string, write_timestamp, ByteOrderExt, TdsType,
+};
 
-#[derive(Debug, PartialEq)]
+/// Names of properties with well known meanings, as written by LabVIEW and DAQmx
+pub mod names {
+    /// Name of the file, written as a property of the root object
+    pub const NAME: &str = "name";
+    pub const DESCRIPTION: &str = "description";
+    pub const TITLE: &str = "title";
+    pub const AUTHOR: &str = "author";
+
+    /// Unit of a channel's values
+    pub const UNIT_STRING: &str = "unit_string";
+
+    /// Absolute time of a waveform's first sample
+    pub const WF_START_TIME: &str = "wf_start_time";
+    /// Time in seconds of a waveform's first sample, relative to the start time
+    pub const WF_START_OFFSET: &str = "wf_start_offset";
+    /// Time in seconds between samples of a waveform
+    pub const WF_INCREMENT: &str = "wf_increment";
+    /// Number of samples in each write of a waveform
+    pub const WF_SAMPLES: &str = "wf_samples";
+    pub const WF_XNAME: &str = "wf_xname";
+    pub const WF_XUNIT_STRING: &str = "wf_xunit_string";
+
+    /// Names of the channels of a DAQmx task, written on group objects by DAQmx
+    pub const NI_CHANNEL_NAME: &str = "NI_ChannelName";
+    pub const NI_UNIT_DESCRIPTION: &str = "NI_UnitDescription";
+    pub const NI_DATA_TYPE: &str = "NI_DataType";
+    pub const NI_CHANNEL_LENGTH: &str = "NI_ChannelLength";
+    /// Number of scales applied to DAQmx raw data, described by `NI_Scale[n]_*` properties
+    pub const NI_NUMBER_OF_SCALES: &str = "NI_Number_Of_Scales";
+    /// Whether data has been scaled, either "scaled" or "unscaled"
+    pub const NI_SCALING_STATUS: &str = "NI_Scaling_Status";
+    pub const NI_EXP_START_TIME_STAMP: &str = "NI_ExpStartTimeStamp";
+    pub const NI_EXP_TIME_STAMP: &str = "NI_ExpTimeStamp";
+    pub const NI_EXP_X_DIMENSION: &str = "NI_ExpXDimension";
+    pub const NI_EXP_IS_RELATIVE_TIME: &str = "NI_ExpIsRelativeTime";
+
+    /// Suffixes of the `NI_Scale[n]_*` properties describing a scale,
+    /// used with `scale_property_name`
+    pub const SCALE_TYPE: &str = "Scale_Type";
+    pub const LINEAR_SLOPE: &str = "Linear_Slope";
+    pub const LINEAR_Y_INTERCEPT: &str = "Linear_Y_Intercept";
+    /// Index of the scale whose output is the input to this scale
+    pub const LINEAR_INPUT_SOURCE: &str = "Linear_Input_Source";
+
+    /// Name of a property describing the scale at `index`, eg.
+    /// `scale_property_name(1, LINEAR_SLOPE)` is "NI_Scale[1]_Linear_Slope"
+    pub fn scale_property_name(index: usize, suffix: &str) -> String {
+        format!("NI_Scale[{}]_{}", index, suffix)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TdmsValue {
     Int8(i8),
     Int16(i16),
@@ -18,16 +78,112 @@ pub enum TdmsValue {
     Float32(f32),
     Float64(f64),
     String(String),
+    Boolean(bool),
     Timestamp(Timestamp),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct TdmsProperty {
-    pub name: String,
+    pub name: PropertyName,
     pub value: TdmsValue,
 }
 
-fn read_value<R: Read, O: ByteOrderExt>(type_id: TdsType, reader: &mut R) -> Result<TdmsValue> {
+/// The name of a property. Files usually use the same few property names for many
+/// objects, so properties read from a file share the storage for each distinct name.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PropertyName(Arc<str>);
+
+impl PropertyName {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for PropertyName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for PropertyName {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for PropertyName {
+    fn from(name: &str) -> PropertyName {
+        PropertyName(Arc::from(name))
+    }
+}
+
+impl From<String> for PropertyName {
+    fn from(name: String) -> PropertyName {
+        PropertyName(Arc::from(name))
+    }
+}
+
+impl PartialEq<str> for PropertyName {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for PropertyName {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for PropertyName {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl fmt::Display for PropertyName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Debug for PropertyName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+/// The distinct property names read from a file
+pub(crate) struct PropertyNameCache {
+    names: HashSet<PropertyName>,
+}
+
+impl PropertyNameCache {
+    pub fn new() -> PropertyNameCache {
+        PropertyNameCache {
+            names: HashSet::new(),
+        }
+    }
+
+    /// Get the shared property name equal to `name`, adding it if it's new
+    pub fn get_or_insert(&mut self, name: &str) -> PropertyName {
+        match self.names.get(name) {
+            Some(existing) => existing.clone(),
+            None => {
+                let name = PropertyName::from(name);
+                self.names.insert(name.clone());
+                name
+            }
+        }
+    }
+}
+
+pub(crate) fn read_value<R: Read, O: ByteOrderExt>(
+    type_id: TdsType,
+    reader: &mut R,
+) -> Result<TdmsValue> {
     match type_id {
         TdsType::I8 => Ok(TdmsValue::Int8(reader.read_i8()?)),
         TdsType::I16 => Ok(TdmsValue::Int16(reader.read_i16::<O>()?)),
@@ -40,21 +196,236 @@ fn read_value<R: Read, O: ByteOrderExt>(type_id: TdsType, reader: &mut R) -> Res
         TdsType::SingleFloat => Ok(TdmsValue::Float32(reader.read_f32::<O>()?)),
         TdsType::DoubleFloat => Ok(TdmsValue::Float64(reader.read_f64::<O>()?)),
         TdsType::String => Ok(TdmsValue::String(read_string::<R, O>(reader)?)),
+        TdsType::Boolean => Ok(TdmsValue::Boolean(reader.read_u8()? != 0)),
         TdsType::TimeStamp => Ok(TdmsValue::Timestamp(read_timestamp::<R, O>(reader)?)),
-        _ => Err(TdmsReadError::TdmsError(format!(
+        _ => Err(TdmsReadError::Unsupported(format!(
             "Unsupported property type {:?}",
             type_id
         ))),
     }
 }
 
+fn write_value<W: Write>(value: &TdmsValue, writer: &mut W) -> Result<()> {
+    match *value {
+        TdmsValue::Int8(value) => writer.write_i8(value)?,
+        TdmsValue::Int16(value) => writer.write_i16::<LittleEndian>(value)?,
+        TdmsValue::Int32(value) => writer.write_i32::<LittleEndian>(value)?,
+        TdmsValue::Int64(value) => writer.write_i64::<LittleEndian>(value)?,
+        TdmsValue::Uint8(value) => writer.write_u8(value)?,
+        TdmsValue::Uint16(value) => writer.write_u16::<LittleEndian>(value)?,
+        TdmsValue::Uint32(value) => writer.write_u32::<LittleEndian>(value)?,
+        TdmsValue::Uint64(value) => writer.write_u64::<LittleEndian>(value)?,
+        TdmsValue::Float32(value) => writer.write_f32::<LittleEndian>(value)?,
+        TdmsValue::Float64(value) => writer.write_f64::<LittleEndian>(value)?,
+        TdmsValue::String(ref value) => write_string(writer, value)?,
+        TdmsValue::Boolean(value) => writer.write_u8(value as u8)?,
+        TdmsValue::Timestamp(ref value) => write_timestamp(writer, value)?,
+    }
+    Ok(())
+}
+
+impl TdmsValue {
+    /// The TDMS data type used to store this value
+    pub fn data_type(&self) -> TdsType {
+        match *self {
+            TdmsValue::Int8(_) => TdsType::I8,
+            TdmsValue::Int16(_) => TdsType::I16,
+            TdmsValue::Int32(_) => TdsType::I32,
+            TdmsValue::Int64(_) => TdsType::I64,
+            TdmsValue::Uint8(_) => TdsType::U8,
+            TdmsValue::Uint16(_) => TdsType::U16,
+            TdmsValue::Uint32(_) => TdsType::U32,
+            TdmsValue::Uint64(_) => TdsType::U64,
+            TdmsValue::Float32(_) => TdsType::SingleFloat,
+            TdmsValue::Float64(_) => TdsType::DoubleFloat,
+            TdmsValue::String(_) => TdsType::String,
+            TdmsValue::Boolean(_) => TdsType::Boolean,
+            TdmsValue::Timestamp(_) => TdsType::TimeStamp,
+        }
+    }
+
+    /// The value if it is a string
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            TdmsValue::String(ref value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value of any integer or floating point value as an f64.
+    /// Large 64 bit integers may lose precision.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            TdmsValue::Int8(value) => Some(value as f64),
+            TdmsValue::Int16(value) => Some(value as f64),
+            TdmsValue::Int32(value) => Some(value as f64),
+            TdmsValue::Int64(value) => Some(value as f64),
+            TdmsValue::Uint8(value) => Some(value as f64),
+            TdmsValue::Uint16(value) => Some(value as f64),
+            TdmsValue::Uint32(value) => Some(value as f64),
+            TdmsValue::Uint64(value) => Some(value as f64),
+            TdmsValue::Float32(value) => Some(value as f64),
+            TdmsValue::Float64(value) => Some(value),
+            TdmsValue::String(_) | TdmsValue::Boolean(_) | TdmsValue::Timestamp(_) => None,
+        }
+    }
+
+    /// The value of any integer value as an i64, or None if it is not an integer
+    /// or is a u64 too large to be represented
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            TdmsValue::Int8(value) => Some(value as i64),
+            TdmsValue::Int16(value) => Some(value as i64),
+            TdmsValue::Int32(value) => Some(value as i64),
+            TdmsValue::Int64(value) => Some(value),
+            TdmsValue::Uint8(value) => Some(value as i64),
+            TdmsValue::Uint16(value) => Some(value as i64),
+            TdmsValue::Uint32(value) => Some(value as i64),
+            TdmsValue::Uint64(value) => i64::try_from(value).ok(),
+            _ => None,
+        }
+    }
+
+    /// The value if it is a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            TdmsValue::Boolean(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value if it is a timestamp
+    pub fn as_timestamp(&self) -> Option<Timestamp> {
+        match *self {
+            TdmsValue::Timestamp(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Types that property values can be converted to with `get_property`.
+/// Numeric values convert to any numeric type that can represent them exactly,
+/// apart from f64, which any numeric value converts to.
+pub trait FromTdmsValue: Sized {
+    fn from_tdms_value(value: &TdmsValue) -> Option<Self>;
+}
+
+impl FromTdmsValue for f64 {
+    fn from_tdms_value(value: &TdmsValue) -> Option<f64> {
+        value.as_f64()
+    }
+}
+
+impl FromTdmsValue for i64 {
+    fn from_tdms_value(value: &TdmsValue) -> Option<i64> {
+        value.as_i64()
+    }
+}
+
+macro_rules! impl_from_tdms_value_integer {
+    ($($type:ty),*) => {
+        $(
+            impl FromTdmsValue for $type {
+                fn from_tdms_value(value: &TdmsValue) -> Option<$type> {
+                    match *value {
+                        TdmsValue::Uint64(value) => <$type>::try_from(value).ok(),
+                        _ => value.as_i64().and_then(|value| <$type>::try_from(value).ok()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_tdms_value_integer!(i8, i16, i32, u8, u16, u32, u64);
+
+impl FromTdmsValue for bool {
+    fn from_tdms_value(value: &TdmsValue) -> Option<bool> {
+        value.as_bool()
+    }
+}
+
+impl FromTdmsValue for String {
+    fn from_tdms_value(value: &TdmsValue) -> Option<String> {
+        value.as_str().map(String::from)
+    }
+}
+
+impl FromTdmsValue for Timestamp {
+    fn from_tdms_value(value: &TdmsValue) -> Option<Timestamp> {
+        value.as_timestamp()
+    }
+}
+
+/// Formats values for display. Floats are written with enough digits to be parsed
+/// back to the same value, and timestamps in RFC 3339 format in UTC.
+impl fmt::Display for TdmsValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TdmsValue::Int8(value) => value.fmt(f),
+            TdmsValue::Int16(value) => value.fmt(f),
+            TdmsValue::Int32(value) => value.fmt(f),
+            TdmsValue::Int64(value) => value.fmt(f),
+            TdmsValue::Uint8(value) => value.fmt(f),
+            TdmsValue::Uint16(value) => value.fmt(f),
+            TdmsValue::Uint32(value) => value.fmt(f),
+            TdmsValue::Uint64(value) => value.fmt(f),
+            TdmsValue::Float32(value) => value.fmt(f),
+            TdmsValue::Float64(value) => value.fmt(f),
+            TdmsValue::String(ref value) => value.fmt(f),
+            TdmsValue::Boolean(value) => value.fmt(f),
+            TdmsValue::Timestamp(value) => match value.format_rfc3339("Z") {
+                Some(formatted) => f.pad(&formatted),
+                None => write!(
+                    f,
+                    "{} s + {} / 2^64 s since 1904-01-01",
+                    value.seconds(),
+                    value.second_fractions()
+                ),
+            },
+        }
+    }
+}
+
 impl TdmsProperty {
+    pub fn new(name: &str, value: TdmsValue) -> TdmsProperty {
+        TdmsProperty {
+            name: PropertyName::from(name),
+            value,
+        }
+    }
+
     pub fn read<R: Read, O: ByteOrderExt>(reader: &mut R) -> Result<TdmsProperty> {
+        let (name, value) = TdmsProperty::read_name_and_value::<R, O>(reader)?;
+        Ok(TdmsProperty {
+            name: PropertyName::from(name),
+            value,
+        })
+    }
+
+    /// Read a property's name and value, so that the name can be replaced with
+    /// a shared name from a `PropertyNameCache`
+    pub fn read_name_and_value<R: Read, O: ByteOrderExt>(
+        reader: &mut R,
+    ) -> Result<(String, TdmsValue)> {
         let name = read_string::<R, O>(reader)?;
         let type_id_raw = reader.read_u32::<O>()?;
         let type_id = TdsType::from_u32(type_id_raw)?;
         let value = read_value::<R, O>(type_id, reader)?;
-        Ok(TdmsProperty { name, value })
+        Ok((name, value))
+    }
+
+    /// Write the property in little endian format
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_string(writer, &self.name)?;
+        writer.write_u32::<LittleEndian>(self.value.data_type().to_u32())?;
+        write_value(&self.value, writer)
+    }
+}
+
+impl fmt::Display for TdmsProperty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} = {}", self.name, self.value)
     }
 }
 
@@ -63,7 +434,6 @@ mod test {
     extern crate hex_literal;
 
     use byteorder::LittleEndian;
-    use chrono::{Duration, TimeZone, Utc};
     use hex_literal::hex;
     use std::io::Cursor;
 
@@ -126,12 +496,33 @@ mod test {
         );
 
         if let TdmsValue::Timestamp(ts) = property.value {
-            let expected_time = Utc
-                .ymd(2015, 9, 8)
-                .and_hms(10, 5, 47)
-                .checked_add_signed(Duration::nanoseconds(669260594))
-                .unwrap();
-            assert_eq!(ts.to_datetime(), Some(expected_time));
+            assert_eq!(
+                ts.to_rfc3339().unwrap(),
+                "2015-09-08T10:05:47.669260594+00:00"
+            );
+        }
+    }
+
+    #[test]
+    pub fn write_and_read_property() {
+        let properties = vec![
+            TdmsProperty::new("int", TdmsValue::Int16(-5)),
+            TdmsProperty::new("float", TdmsValue::Float64(1.5)),
+            TdmsProperty::new("string", TdmsValue::String(String::from("value"))),
+            TdmsProperty::new("bool", TdmsValue::Boolean(true)),
+            TdmsProperty::new(
+                "timestamp",
+                TdmsValue::Timestamp(Timestamp::new(3524551547, 1234567890)),
+            ),
+        ];
+
+        for property in properties {
+            let mut bytes = Vec::new();
+            property.write(&mut bytes).unwrap();
+            let mut reader = Cursor::new(bytes);
+            let read_property = TdmsProperty::read::<_, LittleEndian>(&mut reader).unwrap();
+
+            assert_eq!(read_property, property);
         }
     }
 
@@ -166,4 +557,81 @@ mod test {
             _ => panic!("Unexpected error variant"),
         }
     }
+
+    #[test]
+    pub fn can_read_boolean_property() {
+        let mut reader = Cursor::new(hex!(
+            "
+            04 00 00 00
+            66 6C 61 67
+            21 00 00 00
+            01
+            "
+        ));
+        let property = TdmsProperty::read::<_, LittleEndian>(&mut reader).unwrap();
+
+        assert_eq!(property.name, "flag");
+        assert_eq!(property.value, TdmsValue::Boolean(true));
+    }
+
+    #[test]
+    pub fn display_values() {
+        assert_eq!(TdmsValue::Int8(-5).to_string(), "-5");
+        assert_eq!(
+            TdmsValue::Uint64(u64::MAX).to_string(),
+            "18446744073709551615"
+        );
+        assert_eq!(TdmsValue::Float32(0.1).to_string(), "0.1");
+        assert_eq!(
+            TdmsValue::Float64(1.0 / 3.0).to_string(),
+            "0.3333333333333333"
+        );
+        assert_eq!(
+            TdmsValue::Float64(1.0 / 3.0)
+                .to_string()
+                .parse::<f64>()
+                .unwrap(),
+            1.0 / 3.0
+        );
+        assert_eq!(TdmsValue::String(String::from("text")).to_string(), "text");
+        assert_eq!(TdmsValue::Boolean(false).to_string(), "false");
+        assert_eq!(
+            TdmsValue::Timestamp(Timestamp::new(3524551547, 1 << 63)).to_string(),
+            "2015-09-08T10:05:47.500Z"
+        );
+        assert_eq!(
+            TdmsValue::Timestamp(Timestamp::new(i64::MAX, 0)).to_string(),
+            "9223372036854775807 s + 0 / 2^64 s since 1904-01-01"
+        );
+        assert_eq!(
+            TdmsProperty::new("wf_increment", TdmsValue::Float64(0.25)).to_string(),
+            "wf_increment = 0.25"
+        );
+    }
+
+    #[test]
+    pub fn value_accessors() {
+        assert_eq!(TdmsValue::String(String::from("a")).as_str(), Some("a"));
+        assert_eq!(TdmsValue::Int32(1).as_str(), None);
+
+        assert_eq!(TdmsValue::Int16(-3).as_f64(), Some(-3.0));
+        assert_eq!(TdmsValue::Uint32(7).as_f64(), Some(7.0));
+        assert_eq!(TdmsValue::Float32(0.5).as_f64(), Some(0.5));
+        assert_eq!(TdmsValue::Boolean(true).as_f64(), None);
+
+        assert_eq!(TdmsValue::Uint8(255).as_i64(), Some(255));
+        assert_eq!(TdmsValue::Int64(i64::MIN).as_i64(), Some(i64::MIN));
+        assert_eq!(TdmsValue::Uint64(u64::MAX).as_i64(), None);
+        assert_eq!(TdmsValue::Float64(1.0).as_i64(), None);
+
+        assert_eq!(TdmsValue::Boolean(true).as_bool(), Some(true));
+        assert_eq!(TdmsValue::Uint8(1).as_bool(), None);
+
+        let timestamp = Timestamp::new(1, 2);
+        assert_eq!(
+            TdmsValue::Timestamp(timestamp).as_timestamp(),
+            Some(timestamp)
+        );
+        assert_eq!(TdmsValue::String(String::new()).as_timestamp(), None);
+    }
 }
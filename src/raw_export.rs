@@ -0,0 +1,347 @@
+use crate::error::{Result, TdmsReadError};
+use crate::properties::names::UNIT_STRING;
+use crate::properties::TdmsValue;
+use crate::types::{NativeType, NativeTypeId, TdsType};
+use crate::{Channel, TdmsFile};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+/// Options for exporting channels to raw binary files with `TdmsFile::export_raw_split`
+#[derive(Debug, Clone, Default)]
+pub struct RawExportOptions {
+    /// Whether values of integer and floating point channels are written with their
+    /// original type, rather than converting all values to f64
+    pub native: bool,
+
+    /// Number of values read from each channel at a time, or None to use the chunk
+    /// configuration of the file's read options
+    pub chunk_size: Option<usize>,
+}
+
+/// A channel's data file written by `TdmsFile::export_raw_split`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawExportFile {
+    /// Path of the channel, as returned by `Channel::path`
+    pub channel_path: String,
+
+    /// Path of the file of raw little endian values
+    pub data_path: PathBuf,
+
+    /// Path of the JSON file describing the data file
+    pub metadata_path: PathBuf,
+
+    /// Type of the values in the data file
+    pub data_type: TdsType,
+
+    /// Number of values written
+    pub count: u64,
+}
+
+/// Type of the values written for a channel
+#[derive(Debug, Clone, Copy)]
+enum Output {
+    F64,
+    Native(NativeTypeId),
+}
+
+impl Output {
+    fn new<R: Read + Seek>(channel: &Channel<R>, native: bool) -> Result<Output> {
+        let data_type = match channel.data_type() {
+            Some(data_type) => data_type,
+            None => return Ok(Output::F64),
+        };
+        match data_type.native_type() {
+            Some(NativeTypeId::Timestamp) => {}
+            Some(native_type) if native => return Ok(Output::Native(native_type)),
+            Some(_) => return Ok(Output::F64),
+            // Scaled DAQmx data and custom types are only readable as f64
+            None if matches!(data_type, TdsType::DaqmxRawData | TdsType::Custom { .. }) => {
+                return Ok(Output::F64)
+            }
+            None => {}
+        }
+        Err(TdmsReadError::Unsupported(format!(
+            "Cannot export channel {} / {} as raw values as its data type {} is not numeric",
+            channel.group_name(),
+            channel.name(),
+            data_type
+        )))
+    }
+
+    /// The type of the values written and the extension of the data file
+    fn data_type(self) -> (TdsType, &'static str) {
+        match self {
+            Output::F64 | Output::Native(NativeTypeId::F64) => (TdsType::DoubleFloat, "f64"),
+            Output::Native(NativeTypeId::F32) => (TdsType::SingleFloat, "f32"),
+            Output::Native(NativeTypeId::I8) => (TdsType::I8, "i8"),
+            Output::Native(NativeTypeId::I16) => (TdsType::I16, "i16"),
+            Output::Native(NativeTypeId::I32) => (TdsType::I32, "i32"),
+            Output::Native(NativeTypeId::I64) => (TdsType::I64, "i64"),
+            Output::Native(NativeTypeId::U8) => (TdsType::U8, "u8"),
+            Output::Native(NativeTypeId::U16) => (TdsType::U16, "u16"),
+            Output::Native(NativeTypeId::U32) => (TdsType::U32, "u32"),
+            Output::Native(NativeTypeId::U64) => (TdsType::U64, "u64"),
+            Output::Native(NativeTypeId::Timestamp) => (TdsType::TimeStamp, "timestamp"),
+        }
+    }
+}
+
+/// Export channels to a directory with a file of raw little endian values and a JSON
+/// description for each channel. Channels are read together a chunk at a time, so
+/// reads move through the file once rather than once for each channel.
+pub(crate) fn export_raw_split<R: Read + Seek>(
+    tdms_file: &TdmsFile<R>,
+    out_dir: &Path,
+    channel_paths: &[&str],
+    options: &RawExportOptions,
+) -> Result<Vec<RawExportFile>> {
+    let channels = channel_paths
+        .iter()
+        .map(|path| tdms_file.channel_by_path(path))
+        .collect::<Result<Vec<_>>>()?;
+    let outputs = channels
+        .iter()
+        .map(|channel| Output::new(channel, options.native))
+        .collect::<Result<Vec<_>>>()?;
+
+    fs::create_dir_all(out_dir)?;
+    let mut used_names = HashSet::new();
+    let mut files = Vec::with_capacity(channels.len());
+    let mut writers = Vec::with_capacity(channels.len());
+    for (channel, output) in channels.iter().zip(outputs.iter()) {
+        let name = unique_file_name(channel.group_name(), channel.name(), &mut used_names);
+        let (data_type, extension) = output.data_type();
+        let data_path = out_dir.join(format!("{}.{}", name, extension));
+        writers.push(BufWriter::new(File::create(&data_path)?));
+        files.push(RawExportFile {
+            channel_path: channel.path().to_string(),
+            data_path,
+            metadata_path: out_dir.join(format!("{}.json", name)),
+            data_type,
+            count: 0,
+        });
+    }
+
+    let chunk_size = match (options.chunk_size, channels.first()) {
+        (Some(chunk_size), _) => chunk_size,
+        (None, Some(channel)) => channel
+            .chunk_config()
+            .values(std::mem::size_of::<f64>() * channels.len()),
+        (None, None) => 1,
+    }
+    .max(1) as u64;
+    let total_values = channels
+        .iter()
+        .map(|channel| channel.len())
+        .max()
+        .unwrap_or(0);
+    let mut position = 0;
+    while position < total_values {
+        for (((channel, output), writer), file) in channels
+            .iter()
+            .zip(outputs.iter())
+            .zip(writers.iter_mut())
+            .zip(files.iter_mut())
+        {
+            let count = channel.len().saturating_sub(position).min(chunk_size) as usize;
+            if count > 0 {
+                file.count += write_chunk(channel, *output, position, count, writer)? as u64;
+            }
+        }
+        position += chunk_size;
+    }
+
+    for (((channel, output), writer), file) in channels
+        .iter()
+        .zip(outputs.iter())
+        .zip(writers)
+        .zip(files.iter())
+    {
+        writer
+            .into_inner()
+            .map_err(|error| TdmsReadError::IoError(error.into_error()))?;
+        let (_, dtype) = output.data_type();
+        fs::write(&file.metadata_path, metadata_json(channel, file, dtype))?;
+    }
+    Ok(files)
+}
+
+/// Read `count` values of a channel starting at `offset` and write them to a data file,
+/// returning the number of values written
+fn write_chunk<R: Read + Seek, W: Write>(
+    channel: &Channel<R>,
+    output: Output,
+    offset: u64,
+    count: usize,
+    writer: &mut W,
+) -> Result<usize> {
+    match output {
+        Output::F64 => {
+            let mut buffer = channel.scratch().take::<f64>(count);
+            let values_read = channel.read_data_as_f64(offset, &mut buffer[..])?;
+            f64::write_values(&buffer[..values_read], writer)?;
+            Ok(values_read)
+        }
+        Output::Native(NativeTypeId::I8) => {
+            write_native::<i8, _, _>(channel, offset, count, writer)
+        }
+        Output::Native(NativeTypeId::I16) => {
+            write_native::<i16, _, _>(channel, offset, count, writer)
+        }
+        Output::Native(NativeTypeId::I32) => {
+            write_native::<i32, _, _>(channel, offset, count, writer)
+        }
+        Output::Native(NativeTypeId::I64) => {
+            write_native::<i64, _, _>(channel, offset, count, writer)
+        }
+        Output::Native(NativeTypeId::U8) => {
+            write_native::<u8, _, _>(channel, offset, count, writer)
+        }
+        Output::Native(NativeTypeId::U16) => {
+            write_native::<u16, _, _>(channel, offset, count, writer)
+        }
+        Output::Native(NativeTypeId::U32) => {
+            write_native::<u32, _, _>(channel, offset, count, writer)
+        }
+        Output::Native(NativeTypeId::U64) => {
+            write_native::<u64, _, _>(channel, offset, count, writer)
+        }
+        Output::Native(NativeTypeId::F32) => {
+            write_native::<f32, _, _>(channel, offset, count, writer)
+        }
+        Output::Native(NativeTypeId::F64) => {
+            write_native::<f64, _, _>(channel, offset, count, writer)
+        }
+        Output::Native(NativeTypeId::Timestamp) => Err(TdmsReadError::Unsupported(String::from(
+            "Timestamps cannot be exported as raw values",
+        ))),
+    }
+}
+
+fn write_native<T: NativeType, R: Read + Seek, W: Write>(
+    channel: &Channel<R>,
+    offset: u64,
+    count: usize,
+    writer: &mut W,
+) -> Result<usize> {
+    let mut buffer = channel.scratch().take::<T>(count);
+    let values_read = channel.read_native(offset, &mut buffer[..])?;
+    T::write_values(&buffer[..values_read], writer)?;
+    Ok(values_read)
+}
+
+/// Name for a channel's files of the form `group__channel`, with characters that aren't
+/// safe in file names replaced. A number is appended if the name has already been used,
+/// ignoring case as file names are case insensitive on some platforms.
+fn unique_file_name(group: &str, channel: &str, used_names: &mut HashSet<String>) -> String {
+    let name = format!("{}__{}", sanitize(group), sanitize(channel));
+    let mut unique_name = name.clone();
+    let mut number = 2;
+    while !used_names.insert(unique_name.to_lowercase()) {
+        unique_name = format!("{}_{}", name, number);
+        number += 1;
+    }
+    unique_name
+}
+
+fn sanitize(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if sanitized.is_empty() {
+        String::from("_")
+    } else {
+        sanitized
+    }
+}
+
+/// JSON description of a channel's data file
+fn metadata_json<R: Read + Seek>(
+    channel: &Channel<R>,
+    file: &RawExportFile,
+    dtype: &str,
+) -> String {
+    let unit = channel
+        .properties()
+        .iter()
+        .find(|property| property.name == UNIT_STRING)
+        .map(|property| match property.value {
+            TdmsValue::String(ref unit) => json_string(unit),
+            ref value => json_string(&value.to_string()),
+        })
+        .unwrap_or_else(|| String::from("null"));
+    let data_file = file
+        .data_path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    format!(
+        "{{\n  \"group\": {},\n  \"channel\": {},\n  \"data_file\": {},\n  \
+        \"dtype\": \"{}\",\n  \"byte_order\": \"little\",\n  \"count\": {},\n  \"unit\": {}\n}}\n",
+        json_string(channel.group_name()),
+        json_string(channel.name()),
+        json_string(&data_file),
+        dtype,
+        file.count,
+        unit
+    )
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn file_names_are_sanitized_and_unique() {
+        let mut used_names = HashSet::new();
+
+        assert_eq!(
+            unique_file_name("Group", "Channel", &mut used_names),
+            "Group__Channel"
+        );
+        assert_eq!(
+            unique_file_name("a/b", "c: d", &mut used_names),
+            "a_b__c__d"
+        );
+        assert_eq!(unique_file_name("", "Tëmp", &mut used_names), "___Tëmp");
+        assert_eq!(
+            unique_file_name("Group", "Channel", &mut used_names),
+            "Group__Channel_2"
+        );
+        assert_eq!(
+            unique_file_name("group", "channel", &mut used_names),
+            "group__channel_3"
+        );
+        assert_eq!(
+            unique_file_name("a?b", "c: d", &mut used_names),
+            "a_b__c__d_2"
+        );
+    }
+}
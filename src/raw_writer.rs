@@ -0,0 +1,149 @@
+use crate::error::Result;
+use crate::properties::TdmsProperty;
+use crate::types::{write_string, NativeType, TdsType};
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::io::Write;
+
+/// Tag at the start of each segment of a TDMS file
+pub const SEGMENT_TAG: [u8; 4] = *b"TDSm";
+
+/// Tag at the start of each segment of a TDMS index file
+pub const INDEX_SEGMENT_TAG: [u8; 4] = *b"TDSh";
+
+/// Version number of TDMS 1.0
+pub const VERSION_1_0: u32 = 4712;
+
+/// Version number of TDMS 2.0
+pub const VERSION_2_0: u32 = 4713;
+
+/// Next segment offset written in the lead in of a segment that was not completely written
+pub const INCOMPLETE_SEGMENT_OFFSET: u64 = u64::MAX;
+
+/// Length of a segment's lead in, which the lead in offsets are relative to
+pub const LEAD_IN_LENGTH: u64 = 28;
+
+/// Raw data index of an object in a segment's metadata
+#[derive(Debug, Clone, PartialEq)]
+pub enum RawIndex {
+    /// The object has no raw data in the segment
+    NoData,
+
+    /// The object's raw data index is the same as in the previous segment
+    MatchesPrevious,
+
+    /// Values of a fixed size data type
+    Values {
+        data_type: TdsType,
+        number_of_values: u64,
+    },
+
+    /// String values, with the total size in bytes of the offsets and string data
+    Strings {
+        number_of_values: u64,
+        total_size: u64,
+    },
+
+    /// Bytes written exactly as given in place of the index, starting with the
+    /// index length, for DAQmx indexes or deliberately invalid indexes
+    Bytes(Vec<u8>),
+}
+
+/// Write the lead in of a segment. The offsets are from the end of the lead in to the
+/// next segment and to the segment's raw data.
+pub fn write_lead_in<W: Write>(
+    writer: &mut W,
+    toc_mask: u32,
+    version: u32,
+    next_segment_offset: u64,
+    raw_data_offset: u64,
+) -> Result<()> {
+    writer.write_all(&SEGMENT_TAG)?;
+    writer.write_u32::<LittleEndian>(toc_mask)?;
+    writer.write_u32::<LittleEndian>(version)?;
+    writer.write_u64::<LittleEndian>(next_segment_offset)?;
+    writer.write_u64::<LittleEndian>(raw_data_offset)?;
+    Ok(())
+}
+
+/// Write the number of objects at the start of a segment's metadata
+pub fn write_object_count<W: Write>(writer: &mut W, object_count: u32) -> Result<()> {
+    writer.write_u32::<LittleEndian>(object_count)?;
+    Ok(())
+}
+
+/// Write the metadata of one object, with its raw data index and properties
+pub fn write_object_meta<W: Write>(
+    writer: &mut W,
+    path: &str,
+    index: &RawIndex,
+    properties: &[TdmsProperty],
+) -> Result<()> {
+    write_string(writer, path)?;
+    match index {
+        RawIndex::NoData => writer.write_u32::<LittleEndian>(0xFFFFFFFF)?,
+        RawIndex::MatchesPrevious => writer.write_u32::<LittleEndian>(0)?,
+        RawIndex::Values {
+            data_type,
+            number_of_values,
+        } => {
+            writer.write_u32::<LittleEndian>(20)?;
+            writer.write_u32::<LittleEndian>(data_type.to_u32())?;
+            writer.write_u32::<LittleEndian>(1)?;
+            writer.write_u64::<LittleEndian>(*number_of_values)?;
+        }
+        RawIndex::Strings {
+            number_of_values,
+            total_size,
+        } => {
+            writer.write_u32::<LittleEndian>(28)?;
+            writer.write_u32::<LittleEndian>(TdsType::String.to_u32())?;
+            writer.write_u32::<LittleEndian>(1)?;
+            writer.write_u64::<LittleEndian>(*number_of_values)?;
+            writer.write_u64::<LittleEndian>(*total_size)?;
+        }
+        RawIndex::Bytes(bytes) => writer.write_all(bytes)?,
+    }
+    writer.write_u32::<LittleEndian>(properties.len() as u32)?;
+    for property in properties {
+        property.write(writer)?;
+    }
+    Ok(())
+}
+
+/// Write values of a fixed size type as raw data
+pub fn write_raw_values<W: Write, T: NativeType>(writer: &mut W, values: &[T]) -> Result<()> {
+    T::write_values(values, writer)
+}
+
+/// Write strings as raw data, as a table of end offsets followed by the string bytes
+pub fn write_raw_strings<W: Write, S: AsRef<str>>(writer: &mut W, values: &[S]) -> Result<()> {
+    let mut end_offset = 0;
+    for value in values {
+        end_offset += value.as_ref().len() as u32;
+        writer.write_u32::<LittleEndian>(end_offset)?;
+    }
+    for value in values {
+        writer.write_all(value.as_ref().as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Write a complete segment with the given metadata and raw data bytes, computing the
+/// lead in offsets from their lengths
+pub fn write_segment<W: Write>(
+    writer: &mut W,
+    toc_mask: u32,
+    metadata: &[u8],
+    data: &[u8],
+) -> Result<()> {
+    write_lead_in(
+        writer,
+        toc_mask,
+        VERSION_2_0,
+        (metadata.len() + data.len()) as u64,
+        metadata.len() as u64,
+    )?;
+    writer.write_all(metadata)?;
+    writer.write_all(data)?;
+    Ok(())
+}
@@ -0,0 +1,530 @@
+use crate::chunk::ChunkConfig;
+use crate::decimate;
+use crate::error::{Result, TdmsReadError};
+use crate::fingerprint::FileFingerprint;
+use crate::prefetch::Prefetcher;
+use crate::progress::{CancellationToken, ReadControl};
+use crate::types::NativeType;
+use crate::Channel;
+use std::io::{Read, Seek};
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+/// Read mode of a `ReadBuilder` that reads values as the channel's native type
+pub struct Native;
+
+/// Read mode of a `ReadBuilder` that reads numeric values of any type converted to f64
+pub struct Lossy;
+
+/// Builds a read of channel data from composable options, created with `Channel::read`.
+///
+/// Values are read as the channel's native type unless `convert_lossy` is used,
+/// which changes the type of value the terminal methods produce to f64.
+pub struct ReadBuilder<'c, 'a, R: Read + Seek, M = Native> {
+    channel: &'c Channel<'a, R>,
+    start: u64,
+    end: Option<u64>,
+    stride: u64,
+    chunk_size: Option<usize>,
+    chunk_config: ChunkConfig,
+    prefetch: usize,
+    control: ReadControl<'c>,
+    /// Number of chunks already produced when resuming a read
+    resume_chunk_index: u64,
+    mode: PhantomData<M>,
+}
+
+/// Iterator over chunks of channel values, created with `ReadBuilder::chunks`
+pub struct ReadChunks<'c, 'a, R: Read + Seek, M, T> {
+    builder: ReadBuilder<'c, 'a, R, M>,
+    position: u64,
+    /// Number of chunks produced, including those produced before resuming
+    chunk_index: u64,
+    started: bool,
+    finished: bool,
+    /// Background reader of upcoming chunks, or None if not prefetching or not yet started
+    prefetcher: Option<Prefetcher>,
+    prefetch_started: bool,
+    values: PhantomData<T>,
+}
+
+/// Where an iteration over chunks of channel values stopped, from `ReadChunks::position`,
+/// so that the read can be continued later with `ReadBuilder::resume_from`, possibly
+/// in another process when the `serde` feature is enabled.
+///
+/// A position records a fingerprint of the file it was taken from, made from the file's
+/// length, modification time and a hash of its start, and resuming a read of a file that
+/// has changed since is an error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReadPosition {
+    channel_path: String,
+    /// Index of the segment containing the next value to read, or None if the read
+    /// reached the end of the channel
+    segment_index: Option<usize>,
+    chunk_index: u64,
+    /// Index of the next value to read within the channel
+    value_offset: u64,
+    end: Option<u64>,
+    stride: u64,
+    fingerprint: FileFingerprint,
+}
+
+impl ReadPosition {
+    /// Index within the channel of the next value to be read
+    pub fn value_offset(&self) -> u64 {
+        self.value_offset
+    }
+
+    /// Number of chunks produced before this position
+    pub fn chunk_index(&self) -> u64 {
+        self.chunk_index
+    }
+}
+
+impl<'c, 'a, R: Read + Seek> ReadBuilder<'c, 'a, R, Native> {
+    pub(crate) fn new(channel: &'c Channel<'a, R>) -> ReadBuilder<'c, 'a, R, Native> {
+        ReadBuilder {
+            channel,
+            start: 0,
+            end: None,
+            stride: 1,
+            chunk_size: None,
+            chunk_config: channel.chunk_config(),
+            prefetch: 0,
+            control: ReadControl::new(),
+            resume_chunk_index: 0,
+            mode: PhantomData,
+        }
+    }
+
+    /// Read numeric values of any type, converting them to f64.
+    /// 64 bit integers may lose precision.
+    pub fn convert_lossy(self) -> ReadBuilder<'c, 'a, R, Lossy> {
+        ReadBuilder {
+            channel: self.channel,
+            start: self.start,
+            end: self.end,
+            stride: self.stride,
+            chunk_size: self.chunk_size,
+            chunk_config: self.chunk_config,
+            prefetch: self.prefetch,
+            control: self.control,
+            resume_chunk_index: self.resume_chunk_index,
+            mode: PhantomData,
+        }
+    }
+
+    /// Read all selected values into a new vector
+    pub fn into_vec<T: NativeType>(self) -> Result<Vec<T>> {
+        self.channel.check_native_type::<T>()?;
+        self.read_to_vec(read_native)
+    }
+
+    /// Read selected values into a buffer, reading at most `buffer.len()` values.
+    /// Returns the number of values read.
+    pub fn into_slice<T: NativeType>(self, buffer: &mut [T]) -> Result<usize> {
+        self.channel.check_native_type::<T>()?;
+        self.read_to_slice(buffer, read_native)
+    }
+
+    /// Iterate over the selected values in chunks of at most `chunk_size` values
+    pub fn chunks<T: NativeType>(self) -> ReadChunks<'c, 'a, R, Native, T> {
+        ReadChunks::new(self)
+    }
+}
+
+impl<'c, 'a, R: Read + Seek> ReadBuilder<'c, 'a, R, Lossy> {
+    /// Read all selected values into a new vector
+    pub fn into_vec(self) -> Result<Vec<f64>> {
+        self.channel.check_numeric_type()?;
+        self.read_to_vec(read_lossy)
+    }
+
+    /// Read selected values into a buffer, reading at most `buffer.len()` values.
+    /// Returns the number of values read.
+    pub fn into_slice(self, buffer: &mut [f64]) -> Result<usize> {
+        self.channel.check_numeric_type()?;
+        self.read_to_slice(buffer, read_lossy)
+    }
+
+    /// Iterate over the selected values in chunks of at most `chunk_size` values
+    pub fn chunks(self) -> ReadChunks<'c, 'a, R, Lossy, f64> {
+        ReadChunks::new(self)
+    }
+}
+
+impl<'c, 'a, R: Read + Seek, M> ReadBuilder<'c, 'a, R, M> {
+    /// Read only values with indices within a range, eg. `100..200` or `100..`.
+    /// The range is limited to the length of the channel.
+    pub fn range<B: RangeBounds<u64>>(mut self, range: B) -> ReadBuilder<'c, 'a, R, M> {
+        self.start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        self.end = match range.end_bound() {
+            Bound::Included(&end) => Some(end.saturating_add(1)),
+            Bound::Excluded(&end) => Some(end),
+            Bound::Unbounded => None,
+        };
+        self
+    }
+
+    /// Read only every `stride`th value of the selected range, starting with the first.
+    /// A stride of zero is an error when the read is made.
+    pub fn decimate(mut self, stride: u64) -> ReadBuilder<'c, 'a, R, M> {
+        self.stride = stride;
+        self
+    }
+
+    /// Set the maximum number of values in each chunk produced by `chunks`, which is also
+    /// the number of values read from the file at a time when decimating.
+    /// This overrides the chunk configuration.
+    pub fn chunk_size(mut self, chunk_size: usize) -> ReadBuilder<'c, 'a, R, M> {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Set the size in bytes of each chunk produced by `chunks`, instead of using the
+    /// chunk configuration of the file's read options
+    pub fn chunk_config(mut self, chunk_config: ChunkConfig) -> ReadBuilder<'c, 'a, R, M> {
+        self.chunk_size = None;
+        self.chunk_config = chunk_config;
+        self
+    }
+
+    /// Read the data of up to `chunks` chunks ahead on a background thread when
+    /// iterating with `chunks`, so that reading from slow storage overlaps with
+    /// processing the current chunk. This only has an effect for files opened from
+    /// a path, which are read ahead using a separate file handle. Zero disables prefetching.
+    pub fn prefetch(mut self, chunks: usize) -> ReadBuilder<'c, 'a, R, M> {
+        self.prefetch = chunks;
+        self
+    }
+
+    /// Continue a read from a position recorded with `ReadChunks::position`, reading the
+    /// same range of values with the same stride from the next value that was not read.
+    /// Returns an error if the position is for a different channel or if the file has
+    /// changed since the position was recorded.
+    pub fn resume_from(mut self, position: &ReadPosition) -> Result<ReadBuilder<'c, 'a, R, M>> {
+        if position.channel_path != self.channel.path() {
+            return Err(TdmsReadError::InvalidArgument(format!(
+                "Read position is for channel {} rather than {}",
+                position.channel_path,
+                self.channel.path()
+            )));
+        }
+        let segment_index = self
+            .channel
+            .locate_value(position.value_offset)?
+            .map(|(segment_index, _)| segment_index);
+        if position.fingerprint != self.channel.file_fingerprint()?
+            || position.segment_index != segment_index
+        {
+            return Err(TdmsReadError::Incompatible(String::from(
+                "File has changed since the read position was recorded",
+            )));
+        }
+        self.start = position.value_offset;
+        self.end = position.end;
+        self.stride = position.stride;
+        self.resume_chunk_index = position.chunk_index;
+        Ok(self)
+    }
+
+    /// Set a callback to be called after each read from the file with the number of
+    /// values processed so far and the total number of values in the selected range
+    pub fn progress<F: FnMut(u64, u64) + 'c>(
+        mut self,
+        on_progress: F,
+    ) -> ReadBuilder<'c, 'a, R, M> {
+        self.control = std::mem::take(&mut self.control).on_progress(on_progress);
+        self
+    }
+
+    /// Set a token that may be used to cancel the read
+    pub fn cancel(mut self, token: CancellationToken) -> ReadBuilder<'c, 'a, R, M> {
+        self.control = std::mem::take(&mut self.control).cancellation_token(token);
+        self
+    }
+
+    /// Number of values that the read will produce
+    pub fn len(&self) -> u64 {
+        let (start, end) = self.bounds();
+        if start >= end || self.stride == 0 {
+            0
+        } else {
+            (end - start - 1) / self.stride + 1
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn bounds(&self) -> (u64, u64) {
+        let length = self.channel.len();
+        let end = self.end.map_or(length, |end| end.min(length));
+        (self.start, end)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.stride == 0 {
+            return Err(TdmsReadError::InvalidArgument(String::from(
+                "Decimation stride must be greater than zero",
+            )));
+        }
+        if self.chunk_size == Some(0) {
+            return Err(TdmsReadError::InvalidArgument(String::from(
+                "Chunk size must be greater than zero",
+            )));
+        }
+        Ok(())
+    }
+
+    /// Number of values of type `T` in each chunk
+    fn chunk_values<T>(&self) -> usize {
+        self.chunk_size
+            .unwrap_or_else(|| self.chunk_config.values(std::mem::size_of::<T>()))
+    }
+
+    fn read_to_vec<T: Clone + Default + Send + 'static>(
+        mut self,
+        read: ReadFn<R, T>,
+    ) -> Result<Vec<T>> {
+        self.validate()?;
+        let mut values = vec![T::default(); self.len() as usize];
+        let mut position = self.start;
+        let values_read = self.read_values(&mut position, &mut values, read)?;
+        values.truncate(values_read);
+        Ok(values)
+    }
+
+    fn read_to_slice<T: Clone + Default + Send + 'static>(
+        mut self,
+        buffer: &mut [T],
+        read: ReadFn<R, T>,
+    ) -> Result<usize> {
+        self.validate()?;
+        let mut position = self.start;
+        self.read_values(&mut position, buffer, read)
+    }
+
+    /// Read selected values from `position` into `output` until it is full or the end
+    /// of the range is reached, updating `position` to the index of the next value to
+    /// be read. Returns the number of values read.
+    fn read_values<T: Clone + Default + Send + 'static>(
+        &mut self,
+        position: &mut u64,
+        output: &mut [T],
+        read: ReadFn<R, T>,
+    ) -> Result<usize> {
+        let (start, end) = self.bounds();
+        let total = end.saturating_sub(start);
+        let mut scratch = self.channel.scratch().take::<T>(0);
+        let mut values_written = 0;
+        while values_written < output.len() && *position < end {
+            if self.control.is_cancelled() {
+                return Err(TdmsReadError::Cancelled);
+            }
+            let values_wanted = (output.len() - values_written) as u64;
+            if self.stride == 1 {
+                let count = values_wanted.min(end - *position) as usize;
+                let target = &mut output[values_written..values_written + count];
+                let values_read = read(self.channel, *position, target)?;
+                if values_read == 0 {
+                    break;
+                }
+                values_written += values_read;
+                *position += values_read as u64;
+            } else {
+                // Read the span of values up to the last one wanted and keep every stride'th
+                let span = ((values_wanted - 1) * self.stride + 1)
+                    .min(end - *position)
+                    .min(self.chunk_values::<T>() as u64) as usize;
+                if scratch.len() < span {
+                    scratch.resize(span, T::default());
+                }
+                let values_read = read(self.channel, *position, &mut scratch[..span])?;
+                if values_read == 0 {
+                    break;
+                }
+                let mut values_kept = 0;
+                for value in scratch[..values_read].iter().step_by(self.stride as usize) {
+                    output[values_written + values_kept] = value.clone();
+                    values_kept += 1;
+                }
+                values_written += values_kept;
+                *position += values_kept as u64 * self.stride;
+            }
+            self.control
+                .report_progress((*position).min(end) - start, total);
+        }
+        Ok(values_written)
+    }
+}
+
+impl<'c, 'a, R: Read + Seek, M, T> ReadChunks<'c, 'a, R, M, T> {
+    fn new(builder: ReadBuilder<'c, 'a, R, M>) -> ReadChunks<'c, 'a, R, M, T> {
+        let position = builder.start;
+        let chunk_index = builder.resume_chunk_index;
+        ReadChunks {
+            builder,
+            position,
+            chunk_index,
+            started: false,
+            finished: false,
+            prefetcher: None,
+            prefetch_started: false,
+            values: PhantomData,
+        }
+    }
+
+    /// Position of the next chunk to be read, which may be saved to continue the read
+    /// later with `ReadBuilder::resume_from`
+    pub fn position(&self) -> Result<ReadPosition> {
+        let channel = self.builder.channel;
+        Ok(ReadPosition {
+            channel_path: channel.path().to_string(),
+            segment_index: channel
+                .locate_value(self.position)?
+                .map(|(segment_index, _)| segment_index),
+            chunk_index: self.chunk_index,
+            value_offset: self.position,
+            end: self.builder.end,
+            stride: self.builder.stride,
+            fingerprint: channel.file_fingerprint()?,
+        })
+    }
+
+    fn next_chunk(&mut self, read: ReadFn<R, T>) -> Option<Result<Vec<T>>>
+    where
+        T: Clone + Default + Send + 'static,
+    {
+        if self.finished {
+            return None;
+        }
+        self.request_prefetch();
+        let chunk = self.read_chunk(read);
+        match chunk {
+            Ok(ref values) if values.is_empty() => {
+                self.finished = true;
+                None
+            }
+            Ok(_) => {
+                self.chunk_index += 1;
+                Some(chunk)
+            }
+            Err(_) => {
+                self.finished = true;
+                Some(chunk)
+            }
+        }
+    }
+
+    /// Request the chunk `prefetch` chunks after the next one to be read ahead,
+    /// or all chunks up to that one when starting
+    fn request_prefetch(&mut self) {
+        let depth = self.builder.prefetch as u64;
+        if depth == 0 {
+            return;
+        }
+        let first_chunk = if !self.prefetch_started {
+            self.prefetch_started = true;
+            self.prefetcher = self
+                .builder
+                .channel
+                .file_path()
+                .and_then(|path| Prefetcher::start(path, self.builder.prefetch));
+            1
+        } else {
+            depth
+        };
+        let prefetcher = match self.prefetcher.as_ref() {
+            Some(prefetcher) => prefetcher,
+            None => return,
+        };
+        let (_, end) = self.builder.bounds();
+        let span = (self.builder.chunk_values::<T>() as u64).saturating_mul(self.builder.stride);
+        for chunk in first_chunk..=depth {
+            let start = self.position.saturating_add(chunk.saturating_mul(span));
+            if start >= end {
+                break;
+            }
+            let ranges = self
+                .builder
+                .channel
+                .data_ranges(start, span.min(end - start));
+            if !ranges.is_empty() {
+                prefetcher.request(ranges);
+            }
+        }
+    }
+
+    fn read_chunk(&mut self, read: ReadFn<R, T>) -> Result<Vec<T>>
+    where
+        T: Clone + Default + Send + 'static,
+    {
+        self.builder.validate()?;
+        let (_, end) = self.builder.bounds();
+        let remaining = end
+            .saturating_sub(self.position)
+            .div_ceil(self.builder.stride);
+        let chunk_values = self.builder.chunk_values::<T>() as u64;
+        let mut values = vec![T::default(); remaining.min(chunk_values) as usize];
+        let values_read = self
+            .builder
+            .read_values(&mut self.position, &mut values, read)?;
+        values.truncate(values_read);
+        Ok(values)
+    }
+}
+
+impl<'c, 'a, R: Read + Seek, T: NativeType> Iterator for ReadChunks<'c, 'a, R, Native, T> {
+    type Item = Result<Vec<T>>;
+
+    fn next(&mut self) -> Option<Result<Vec<T>>> {
+        if !self.started {
+            self.started = true;
+            if let Err(error) = self.builder.channel.check_native_type::<T>() {
+                self.finished = true;
+                return Some(Err(error));
+            }
+        }
+        self.next_chunk(read_native)
+    }
+}
+
+impl<'c, 'a, R: Read + Seek> Iterator for ReadChunks<'c, 'a, R, Lossy, f64> {
+    type Item = Result<Vec<f64>>;
+
+    fn next(&mut self) -> Option<Result<Vec<f64>>> {
+        if !self.started {
+            self.started = true;
+            if let Err(error) = self.builder.channel.check_numeric_type() {
+                self.finished = true;
+                return Some(Err(error));
+            }
+        }
+        self.next_chunk(read_lossy)
+    }
+}
+
+type ReadFn<R, T> = for<'c, 'a> fn(&'c Channel<'a, R>, u64, &mut [T]) -> Result<usize>;
+
+fn read_native<R: Read + Seek, T: NativeType>(
+    channel: &Channel<R>,
+    offset: u64,
+    buffer: &mut [T],
+) -> Result<usize> {
+    channel.read_native(offset, buffer)
+}
+
+fn read_lossy<R: Read + Seek>(
+    channel: &Channel<R>,
+    offset: u64,
+    buffer: &mut [f64],
+) -> Result<usize> {
+    decimate::read_data_as_f64(channel, offset, buffer)
+}
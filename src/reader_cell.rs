@@ -0,0 +1,74 @@
+#[cfg(not(feature = "sync"))]
+use std::cell::{RefCell, RefMut};
+#[cfg(feature = "sync")]
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Exclusive access to the reader of a `ReaderCell`, released when dropped
+#[cfg(not(feature = "sync"))]
+pub(crate) type ReaderGuard<'a, R> = RefMut<'a, R>;
+
+/// Exclusive access to the reader of a `ReaderCell`, released when dropped
+#[cfg(feature = "sync")]
+pub(crate) type ReaderGuard<'a, R> = MutexGuard<'a, R>;
+
+/// Holds the reader of a `TdmsFile`, so that data can be read through a shared reference
+/// to the file. Each read borrows the reader for the duration of that read only, and the
+/// borrow is released when the read returns, whether it succeeded or failed.
+///
+/// Without the `sync` feature the reader is held in a `RefCell`, so a file can be moved
+/// to another thread but not shared between threads. With the `sync` feature it is held
+/// in a `Mutex`, so a file can be shared between threads and reads from different
+/// threads take turns.
+pub(crate) struct ReaderCell<R> {
+    #[cfg(not(feature = "sync"))]
+    reader: RefCell<R>,
+
+    #[cfg(feature = "sync")]
+    reader: Mutex<R>,
+}
+
+impl<R> ReaderCell<R> {
+    #[cfg(not(feature = "sync"))]
+    pub fn new(reader: R) -> ReaderCell<R> {
+        ReaderCell {
+            reader: RefCell::new(reader),
+        }
+    }
+
+    #[cfg(feature = "sync")]
+    pub fn new(reader: R) -> ReaderCell<R> {
+        ReaderCell {
+            reader: Mutex::new(reader),
+        }
+    }
+
+    /// Borrow the reader for a read. Reads must not be nested, as the reader is already
+    /// borrowed by the outer read.
+    #[cfg(not(feature = "sync"))]
+    pub fn borrow_mut(&self) -> ReaderGuard<'_, R> {
+        self.reader.borrow_mut()
+    }
+
+    /// Borrow the reader for a read, waiting for any read in another thread to finish.
+    /// Reads must not be nested, as the reader is already borrowed by the outer read.
+    #[cfg(feature = "sync")]
+    pub fn borrow_mut(&self) -> ReaderGuard<'_, R> {
+        // Every read seeks to the data it reads, so a reader left part way through a
+        // read by a panic in another thread can still be used
+        self.reader.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get the reader when the file is borrowed mutably, without any locking
+    #[cfg(not(feature = "sync"))]
+    pub fn get_mut(&mut self) -> &mut R {
+        self.reader.get_mut()
+    }
+
+    /// Get the reader when the file is borrowed mutably, without any locking
+    #[cfg(feature = "sync")]
+    pub fn get_mut(&mut self) -> &mut R {
+        self.reader
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,153 @@
+use crate::error::{Result, TdmsReadError};
+use crate::options::{ErrorPolicy, ReadOptions};
+use crate::tdms_reader::{read_metadata, SkippedSegment};
+use crate::types::TdsType;
+use crate::writer::{copy_channel_data, TdmsWriter, WriteObject};
+use crate::TdmsFile;
+use byteorder::{BigEndian, LittleEndian, WriteBytesExt};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+
+/// Offset of the next segment offset field within a segment lead in
+const NEXT_SEGMENT_OFFSET_POSITION: u64 = 12;
+const LEAD_IN_LENGTH: u64 = 28;
+
+/// Describes data that was lost when repairing a TDMS file
+#[derive(Debug, Clone, Default)]
+pub struct RepairReport {
+    /// Segments that could not be read
+    pub skipped_segments: Vec<SkippedSegment>,
+
+    /// Channels that had values missing from a truncated final segment,
+    /// as (group name, channel name, number of values dropped)
+    pub truncated_channels: Vec<(String, String, u64)>,
+
+    /// Channels whose data could not be copied as reading their data type is not supported,
+    /// as (group name, channel name, data type)
+    pub unsupported_channels: Vec<(String, String, TdsType)>,
+
+    /// Number of values copied for each channel, as (group name, channel name, number of values)
+    pub copied_channels: Vec<(String, String, u64)>,
+}
+
+/// The outcome of finalizing an incomplete TDMS file in place
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FinalizeResult {
+    /// The final segment of the file was already complete so no change was made
+    AlreadyComplete,
+
+    /// The next segment offset of the final segment was updated
+    Finalized {
+        segment_position: u64,
+        next_segment_offset: u64,
+    },
+}
+
+/// Salvage all readable data from a damaged TDMS file and write it as a new, clean file.
+///
+/// The source is read with `ErrorPolicy::Skip` so unreadable segments are dropped,
+/// and an incomplete final segment is truncated to whole values. All objects and
+/// their properties are written to the first segment of the output, followed by the
+/// data for each channel written contiguously.
+pub fn repair<R: Read + Seek, W: Write>(source: R, destination: W) -> Result<RepairReport> {
+    let options = ReadOptions::new().error_policy(ErrorPolicy::Skip);
+    let tdms_file = TdmsFile::new_with_options(source, &options)?;
+    let mut writer = TdmsWriter::new(destination);
+    let mut report = RepairReport {
+        skipped_segments: tdms_file.skipped_segments().to_vec(),
+        ..RepairReport::default()
+    };
+
+    let mut metadata_objects = vec![WriteObject::root().with_properties(tdms_file.properties())];
+    for group in tdms_file.groups() {
+        metadata_objects.push(WriteObject::group(group.name()).with_properties(group.properties()));
+        for channel in group.channels() {
+            metadata_objects.push(
+                WriteObject::channel(group.name(), channel.name())
+                    .with_properties(channel.properties()),
+            );
+        }
+    }
+    writer.write_segment(&metadata_objects)?;
+
+    for group in tdms_file.groups() {
+        for channel in group.channels() {
+            let group_name = group.name().to_string();
+            let channel_name = channel.name().to_string();
+            let truncated = channel.truncated_values()?;
+            if truncated > 0 {
+                report.truncated_channels.push((
+                    group_name.clone(),
+                    channel_name.clone(),
+                    truncated,
+                ));
+            }
+            let data_type = match channel.data_type() {
+                Some(data_type) => data_type,
+                None => continue,
+            };
+            let copied = match copy_channel_data(&channel, &mut writer)? {
+                Some(copied) => copied,
+                None => {
+                    report
+                        .unsupported_channels
+                        .push((group_name, channel_name, data_type));
+                    continue;
+                }
+            };
+            report
+                .copied_channels
+                .push((group_name, channel_name, copied));
+        }
+    }
+    writer.flush()?;
+
+    Ok(report)
+}
+
+/// Finalize an incomplete TDMS file in place, without rewriting it.
+///
+/// When a writer is interrupted, the final segment's next segment offset is left
+/// as 0xFFFFFFFFFFFFFFFF. If the data written to the final segment contains a
+/// whole number of chunks, the offset is replaced with the correct value.
+/// An error is returned if the final segment ends with a partial chunk, in which
+/// case `repair` must be used instead.
+pub fn finalize_in_place<F: Read + Write + Seek>(file: &mut F) -> Result<FinalizeResult> {
+    let tdms_reader = {
+        let mut reader = BufReader::new(&mut *file);
+        read_metadata(&mut reader, &ReadOptions::default())?
+    };
+    let segment = match tdms_reader.segments().last() {
+        Some(segment) if segment.incomplete => segment,
+        _ => return Ok(FinalizeResult::AlreadyComplete),
+    };
+
+    let chunk_size = tdms_reader.object_list(segment).chunk_size();
+    let data_length = segment.data_length();
+    let partial_chunk_bytes = if chunk_size == 0 {
+        data_length
+    } else {
+        data_length % chunk_size
+    };
+    if partial_chunk_bytes != 0 {
+        return Err(TdmsReadError::Unsupported(format!(
+            "The final segment at position {} ends with a partial chunk of {} bytes and cannot be finalized in place",
+            segment.position, partial_chunk_bytes
+        )));
+    }
+
+    let next_segment_offset = segment.next_segment_position - segment.position - LEAD_IN_LENGTH;
+    file.seek(SeekFrom::Start(
+        segment.position + NEXT_SEGMENT_OFFSET_POSITION,
+    ))?;
+    if segment.big_endian() {
+        file.write_u64::<BigEndian>(next_segment_offset)?;
+    } else {
+        file.write_u64::<LittleEndian>(next_segment_offset)?;
+    }
+    file.flush()?;
+
+    Ok(FinalizeResult::Finalized {
+        segment_position: segment.position,
+        next_segment_offset,
+    })
+}
@@ -0,0 +1,414 @@
+use crate::decimate::read_data_as_f64;
+use crate::error::{Result, TdmsReadError};
+use crate::properties::TdmsProperty;
+use crate::types::{NativeType, NativeTypeId, TdsType};
+use crate::writer::{copy_channel_data_as, TdmsWriter, WriteObject};
+use crate::{Channel, TdmsFile};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{Read, Seek, Write};
+
+/// An object in a rewritten file, identified by its name in the rewritten file
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RewriteObject {
+    Root,
+    Group(String),
+    Channel(String, String),
+}
+
+enum PropertyEdit {
+    Set(TdmsProperty),
+    Remove(String),
+}
+
+type ChannelMap = dyn Fn(&str, &str) -> Option<(String, String)>;
+
+/// Options for rewriting a TDMS file with renamed or dropped channels,
+/// edited properties and converted data types.
+///
+/// Channels are renamed or dropped using their names in the source file. Property edits
+/// and data type conversions refer to objects by their names in the rewritten file,
+/// after any renaming.
+pub struct RewriteOptions {
+    renames: HashMap<(String, String), Option<(String, String)>>,
+    channel_map: Option<Box<ChannelMap>>,
+    property_edits: Vec<(RewriteObject, PropertyEdit)>,
+    /// Ordered so that errors for missing channels don't depend on hash order
+    conversions: BTreeMap<(String, String), TdsType>,
+}
+
+impl RewriteOptions {
+    /// Create options that rewrite a file without changes
+    pub fn new() -> RewriteOptions {
+        RewriteOptions {
+            renames: HashMap::new(),
+            channel_map: None,
+            property_edits: Vec::new(),
+            conversions: BTreeMap::new(),
+        }
+    }
+
+    /// Rename a channel, which may also move it to a different group
+    pub fn rename_channel(
+        mut self,
+        group: &str,
+        channel: &str,
+        new_group: &str,
+        new_channel: &str,
+    ) -> RewriteOptions {
+        self.renames.insert(
+            (group.to_string(), channel.to_string()),
+            Some((new_group.to_string(), new_channel.to_string())),
+        );
+        self
+    }
+
+    /// Leave a channel and its properties out of the rewritten file
+    pub fn drop_channel(mut self, group: &str, channel: &str) -> RewriteOptions {
+        self.renames
+            .insert((group.to_string(), channel.to_string()), None);
+        self
+    }
+
+    /// Map the group and channel name of each channel not renamed or dropped with
+    /// `rename_channel` or `drop_channel` to its new group and channel name,
+    /// or to None to drop the channel
+    pub fn map_channels<F>(mut self, map: F) -> RewriteOptions
+    where
+        F: Fn(&str, &str) -> Option<(String, String)> + 'static,
+    {
+        self.channel_map = Some(Box::new(map));
+        self
+    }
+
+    /// Set a property of an object, replacing any existing value
+    pub fn set_property(mut self, object: RewriteObject, property: TdmsProperty) -> RewriteOptions {
+        self.property_edits
+            .push((object, PropertyEdit::Set(property)));
+        self
+    }
+
+    /// Remove a property from an object
+    pub fn remove_property(mut self, object: RewriteObject, name: &str) -> RewriteOptions {
+        self.property_edits
+            .push((object, PropertyEdit::Remove(name.to_string())));
+        self
+    }
+
+    /// Convert the data of a channel to a different numeric type. Values are converted
+    /// through 64 bit floats, so integers larger than 2^53 may lose precision. Values
+    /// converted to an integer type are rounded to the nearest integer and clamped to the
+    /// range of the type, with NaN converted to zero.
+    pub fn convert_channel(
+        mut self,
+        group: &str,
+        channel: &str,
+        data_type: TdsType,
+    ) -> RewriteOptions {
+        self.conversions
+            .insert((group.to_string(), channel.to_string()), data_type);
+        self
+    }
+
+    fn new_name(&self, group: &str, channel: &str) -> Option<(String, String)> {
+        match self.renames.get(&(group.to_string(), channel.to_string())) {
+            Some(new_name) => new_name.clone(),
+            None => match self.channel_map {
+                Some(ref map) => map(group, channel),
+                None => Some((group.to_string(), channel.to_string())),
+            },
+        }
+    }
+
+    /// Apply property edits for an object to its properties in the source file
+    fn edit_properties(
+        &self,
+        object: &RewriteObject,
+        properties: &[TdmsProperty],
+    ) -> Vec<TdmsProperty> {
+        let mut properties = properties.to_vec();
+        for (edit_object, edit) in self.property_edits.iter() {
+            if edit_object != object {
+                continue;
+            }
+            match edit {
+                PropertyEdit::Set(property) => {
+                    match properties
+                        .iter_mut()
+                        .find(|existing| existing.name == property.name)
+                    {
+                        Some(existing) => existing.value = property.value.clone(),
+                        None => properties.push(property.clone()),
+                    }
+                }
+                PropertyEdit::Remove(name) => properties.retain(|property| property.name != *name),
+            }
+        }
+        properties
+    }
+}
+
+impl Default for RewriteOptions {
+    fn default() -> RewriteOptions {
+        RewriteOptions::new()
+    }
+}
+
+/// Describes the channels written when rewriting a TDMS file
+#[derive(Debug, Clone, Default)]
+pub struct RewriteReport {
+    /// Number of values written for each channel, as (group name, channel name,
+    /// number of values) using names in the rewritten file
+    pub copied_channels: Vec<(String, String, u64)>,
+
+    /// Channels left out of the rewritten file, as (group name, channel name)
+    /// using names in the source file
+    pub dropped_channels: Vec<(String, String)>,
+}
+
+struct RewriteChannel<'a, R: Read + Seek> {
+    channel: Channel<'a, R>,
+    group_name: String,
+    name: String,
+    conversion: Option<TdsType>,
+}
+
+/// Rewrite a TDMS file with channels renamed, dropped or converted to other data types,
+/// and properties edited, without otherwise changing the data.
+///
+/// All objects and their properties are written to the first segment of the output,
+/// followed by the data for each channel written contiguously, as with `repair`.
+/// Dropped channels are left out along with their properties, as are groups left
+/// with no channels because all of their channels were dropped or moved to other groups.
+/// A group that channels are moved into keeps the properties of any group of
+/// the same name in the source file.
+pub fn rewrite<R: Read + Seek, W: Write>(
+    source: R,
+    destination: W,
+    options: &RewriteOptions,
+) -> Result<RewriteReport> {
+    let tdms_file = TdmsFile::new(source)?;
+    let mut report = RewriteReport::default();
+
+    let mut channels = Vec::new();
+    let mut channel_names = HashSet::new();
+    let mut group_names = Vec::new();
+    let mut written_groups = HashSet::new();
+    for group in tdms_file.groups() {
+        if group.channels().next().is_none() && written_groups.insert(group.name().to_string()) {
+            group_names.push(group.name().to_string());
+        }
+        for channel in group.channels() {
+            let (group_name, name) = match options.new_name(group.name(), channel.name()) {
+                Some(new_name) => new_name,
+                None => {
+                    report
+                        .dropped_channels
+                        .push((group.name().to_string(), channel.name().to_string()));
+                    continue;
+                }
+            };
+            if !channel_names.insert((group_name.clone(), name.clone())) {
+                return Err(TdmsReadError::InvalidArgument(format!(
+                    "More than one channel would be written as {} / {}",
+                    group_name, name
+                )));
+            }
+            if written_groups.insert(group_name.clone()) {
+                group_names.push(group_name.clone());
+            }
+            let conversion = match options.conversions.get(&(group_name.clone(), name.clone())) {
+                Some(&data_type) => {
+                    check_conversion(&channel, data_type)?;
+                    Some(data_type)
+                }
+                None => None,
+            };
+            channels.push(RewriteChannel {
+                channel,
+                group_name,
+                name,
+                conversion,
+            });
+        }
+    }
+    check_objects_exist(options, &group_names, &channel_names)?;
+
+    let mut writer = TdmsWriter::new(destination);
+    let mut metadata_objects = vec![WriteObject::root()
+        .with_properties(&options.edit_properties(&RewriteObject::Root, tdms_file.properties()))];
+    for group_name in group_names.iter() {
+        let source_properties = match tdms_file.group(group_name) {
+            Some(group) => group.properties().to_vec(),
+            None => Vec::new(),
+        };
+        let object = RewriteObject::Group(group_name.clone());
+        metadata_objects.push(
+            WriteObject::group(group_name)
+                .with_properties(&options.edit_properties(&object, &source_properties)),
+        );
+        for channel in channels
+            .iter()
+            .filter(|channel| channel.group_name == *group_name)
+        {
+            let object = RewriteObject::Channel(group_name.clone(), channel.name.clone());
+            metadata_objects.push(
+                WriteObject::channel(group_name, &channel.name).with_properties(
+                    &options.edit_properties(&object, channel.channel.properties()),
+                ),
+            );
+        }
+    }
+    writer.write_segment(&metadata_objects)?;
+
+    for channel in channels.iter() {
+        let target = (channel.group_name.as_str(), channel.name.as_str());
+        let copied = match channel.conversion {
+            Some(data_type) => convert_channel_data(&channel.channel, target, data_type, &mut writer)?,
+            None => match copy_channel_data_as(&channel.channel, target, &mut writer)? {
+                Some(copied) => copied,
+                None => {
+                    return Err(TdmsReadError::Unsupported(format!(
+                        "Cannot rewrite data for channel {} / {} as its data type {:?} is not supported",
+                        channel.channel.group_name(),
+                        channel.channel.name(),
+                        channel.channel.data_type()
+                    )))
+                }
+            },
+        };
+        report
+            .copied_channels
+            .push((channel.group_name.clone(), channel.name.clone(), copied));
+    }
+    writer.flush()?;
+
+    Ok(report)
+}
+
+/// Check that a channel's data can be converted to a data type
+fn check_conversion<R: Read + Seek>(channel: &Channel<R>, data_type: TdsType) -> Result<()> {
+    if !is_numeric(data_type) {
+        return Err(TdmsReadError::Unsupported(format!(
+            "Cannot convert channel {} / {} to {:?}, only numeric types are supported",
+            channel.group_name(),
+            channel.name(),
+            data_type
+        )));
+    }
+    match channel.data_type() {
+        Some(source_type) if source_type != TdsType::DaqmxRawData && !is_numeric(source_type) => {
+            Err(TdmsReadError::Unsupported(format!(
+                "Cannot convert channel {} / {} from {:?}, only numeric data can be converted",
+                channel.group_name(),
+                channel.name(),
+                source_type
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn is_numeric(data_type: TdsType) -> bool {
+    !matches!(
+        data_type.native_type(),
+        None | Some(NativeTypeId::Timestamp)
+    )
+}
+
+/// Check that every object with property edits or conversions is in the rewritten file
+fn check_objects_exist(
+    options: &RewriteOptions,
+    group_names: &[String],
+    channel_names: &HashSet<(String, String)>,
+) -> Result<()> {
+    let edited_objects = options
+        .property_edits
+        .iter()
+        .map(|(object, _)| object.clone());
+    let converted_objects = options
+        .conversions
+        .keys()
+        .map(|(group, channel)| RewriteObject::Channel(group.clone(), channel.clone()));
+    for object in edited_objects.chain(converted_objects) {
+        let exists = match object {
+            RewriteObject::Root => true,
+            RewriteObject::Group(ref group) => group_names.contains(group),
+            RewriteObject::Channel(ref group, ref channel) => {
+                channel_names.contains(&(group.clone(), channel.clone()))
+            }
+        };
+        if !exists {
+            return Err(TdmsReadError::InvalidArgument(format!(
+                "Cannot edit {:?} as it is not in the rewritten file",
+                object
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Write all data for a channel converted to another native type,
+/// returning the number of values written
+fn convert_channel_data<R: Read + Seek, W: Write>(
+    channel: &Channel<R>,
+    target: (&str, &str),
+    data_type: TdsType,
+    writer: &mut TdmsWriter<W>,
+) -> Result<u64> {
+    let native_type = match data_type.native_type() {
+        Some(native_type) => native_type,
+        None => {
+            return Err(TdmsReadError::Unsupported(format!(
+                "Cannot convert channel data to {:?}",
+                data_type
+            )))
+        }
+    };
+    match native_type {
+        NativeTypeId::I8 => write_converted(channel, target, writer, |value| value.round() as i8),
+        NativeTypeId::I16 => write_converted(channel, target, writer, |value| value.round() as i16),
+        NativeTypeId::I32 => write_converted(channel, target, writer, |value| value.round() as i32),
+        NativeTypeId::I64 => write_converted(channel, target, writer, |value| value.round() as i64),
+        NativeTypeId::U8 => write_converted(channel, target, writer, |value| value.round() as u8),
+        NativeTypeId::U16 => write_converted(channel, target, writer, |value| value.round() as u16),
+        NativeTypeId::U32 => write_converted(channel, target, writer, |value| value.round() as u32),
+        NativeTypeId::U64 => write_converted(channel, target, writer, |value| value.round() as u64),
+        NativeTypeId::F32 => write_converted(channel, target, writer, |value| value as f32),
+        NativeTypeId::F64 => write_converted(channel, target, writer, |value| value),
+        NativeTypeId::Timestamp => Err(TdmsReadError::Unsupported(String::from(
+            "Cannot convert channel data to timestamps",
+        ))),
+    }
+}
+
+fn write_converted<T, R, W, F>(
+    channel: &Channel<R>,
+    target: (&str, &str),
+    writer: &mut TdmsWriter<W>,
+    convert: F,
+) -> Result<u64>
+where
+    T: NativeType,
+    R: Read + Seek,
+    W: Write,
+    F: Fn(f64) -> T,
+{
+    let (group_name, channel_name) = target;
+    let chunk_size = channel.chunk_config().values(std::mem::size_of::<f64>());
+    let mut buffer = channel.scratch().take::<f64>(chunk_size);
+    let mut converted = Vec::with_capacity(chunk_size);
+    let mut offset = 0;
+    loop {
+        let values_read = read_data_as_f64(channel, offset, &mut buffer)?;
+        if values_read == 0 {
+            break;
+        }
+        converted.clear();
+        converted.extend(buffer[..values_read].iter().map(|&value| convert(value)));
+        writer.write_segment(&[
+            WriteObject::channel(group_name, channel_name).with_data(&converted)?
+        ])?;
+        offset += values_read as u64;
+    }
+    Ok(offset)
+}
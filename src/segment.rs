@@ -1,159 +1,954 @@
+use crate::daqmx::{DaqmxScaler, LinearScale};
+use crate::decimate::NumericValue;
 use crate::error::{Result, TdmsReadError};
+use crate::inspect::{DataExtent, Layout};
 use crate::interleaved::InterleavedReader;
 use crate::object_map::ObjectMap;
 use crate::object_path::ObjectPathId;
 use crate::toc::{TocFlag, TocMask};
 use crate::types::{ByteOrderExt, NativeType, TdsType};
-use byteorder::{BigEndian, LittleEndian};
-use id_arena::{Arena, Id};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
+use std::cmp::min;
 use std::io::{Read, Seek, SeekFrom};
+use std::ops::Index;
 
 #[derive(Debug)]
 pub struct TdmsSegment {
+    pub position: u64,
     pub next_segment_position: u64,
-    pub objects: Vec<SegmentObject>,
+    /// TDMS format version number, 4712 for TDMS 1.0 or 4713 for TDMS 2.0
+    pub version: u32,
+    /// The segment's objects, which are shared with other segments that have the same objects
+    pub object_list: ObjectListId,
+    /// Whether the segment was not completely written, so its length was determined
+    /// from the length of the file rather than the segment lead in
+    pub incomplete: bool,
     toc_mask: TocMask,
     data_position: u64,
 }
 
+/// Describes how the raw data of a segment is divided into chunks
+struct ChunkInfo {
+    /// Size in bytes of one chunk of data for all objects in the segment
+    chunk_size: u64,
+
+    /// Number of complete chunks in the segment
+    complete_chunks: u64,
+
+    /// Number of bytes in a final, truncated chunk
+    partial_chunk_bytes: u64,
+}
+
+impl ChunkInfo {
+    fn total_chunks(&self) -> u64 {
+        if self.partial_chunk_bytes > 0 {
+            self.complete_chunks + 1
+        } else {
+            self.complete_chunks
+        }
+    }
+
+    /// Number of whole values for a channel in the segment. This is the only place a
+    /// truncated final chunk is accounted for, values are then read using the counts
+    /// stored in the channel's data index.
+    fn value_count(&self, layout: &ChannelLayout) -> u64 {
+        let mut count = self.complete_chunks * layout.values_per_chunk;
+        if self.partial_chunk_bytes > 0 {
+            count += partial_chunk_value_count(layout, self.partial_chunk_bytes);
+        }
+        count
+    }
+}
+
+/// Describes where data for a channel is located within a chunk
+struct ChannelLayout {
+    /// Number of values for the channel in a complete chunk
+    values_per_chunk: u64,
+
+    /// Data type of the channel's raw data
+    data_type: TdsType,
+
+    /// Size of the channel's data type, or None for variable sized types
+    type_size: Option<u64>,
+
+    /// Offset in bytes to the channel data from the start of a chunk for contiguous data,
+    /// or from the start of a row for interleaved data
+    offset: u64,
+
+    /// Width in bytes of one row of interleaved data, or None for contiguous data
+    row_width: Option<u64>,
+}
+
+impl ChannelLayout {
+    /// Number of values of the channel in the chunk at `chunk_index`, given the number of
+    /// values of the channel in the whole segment, which accounts for a truncated final
+    /// chunk and is only calculated when building the channel's data index
+    fn chunk_value_count(&self, chunk_index: u64, segment_values: u64) -> u64 {
+        min(
+            self.values_per_chunk,
+            segment_values.saturating_sub(chunk_index * self.values_per_chunk),
+        )
+    }
+}
+
 impl TdmsSegment {
     pub fn new(
         toc_mask: TocMask,
+        version: u32,
+        position: u64,
         data_position: u64,
         next_segment_position: u64,
-        objects: Vec<SegmentObject>,
+        incomplete: bool,
+        object_list: ObjectListId,
     ) -> TdmsSegment {
         TdmsSegment {
+            position,
             toc_mask,
+            version,
             data_position,
             next_segment_position,
-            objects,
+            incomplete,
+            object_list,
+        }
+    }
+
+    /// Whether data in this segment is stored in big endian byte order
+    pub fn big_endian(&self) -> bool {
+        self.toc_mask.has_flag(TocFlag::BigEndian)
+    }
+
+    /// Whether the values of channels in this segment's data chunks are interleaved
+    pub fn interleaved(&self) -> bool {
+        self.toc_mask.has_flag(TocFlag::InterleavedData)
+    }
+
+    /// Total number of bytes of raw data in this segment
+    pub fn data_length(&self) -> u64 {
+        self.next_segment_position
+            .saturating_sub(self.data_position)
+    }
+
+    /// Position in the file of the start of this segment's raw data
+    pub fn data_position(&self) -> u64 {
+        self.data_position
+    }
+
+    /// Number of bytes of the lead in and metadata of this segment
+    pub fn header_length(&self) -> u64 {
+        self.data_position.saturating_sub(self.position)
+    }
+
+    /// Number of values for a channel in this segment, accounting for multiple chunks
+    /// and a truncated final chunk
+    pub fn channel_value_count(
+        &self,
+        channel_id: ObjectPathId,
+        objects: &ObjectList,
+    ) -> Result<u64> {
+        let layout = match self.channel_layout(channel_id, objects)? {
+            Some(layout) => layout,
+            None => return Ok(0),
+        };
+        Ok(self.chunk_info(objects).value_count(&layout))
+    }
+
+    /// Number of values for a channel declared by the segment's raw data index but
+    /// missing from a truncated final chunk
+    pub fn channel_truncated_count(
+        &self,
+        channel_id: ObjectPathId,
+        objects: &ObjectList,
+    ) -> Result<u64> {
+        let layout = match self.channel_layout(channel_id, objects)? {
+            Some(layout) => layout,
+            None => return Ok(0),
+        };
+        let chunks = self.chunk_info(objects);
+        if chunks.partial_chunk_bytes > 0 {
+            Ok(layout.values_per_chunk
+                - partial_chunk_value_count(&layout, chunks.partial_chunk_bytes))
+        } else {
+            Ok(0)
+        }
+    }
+
+    pub fn toc_mask(&self) -> &TocMask {
+        &self.toc_mask
+    }
+
+    /// Number of chunks of raw data in this segment, including a truncated final chunk
+    pub fn chunk_count(&self, objects: &ObjectList) -> u64 {
+        self.chunk_info(objects).total_chunks()
+    }
+
+    /// Number of values for each channel with data in this segment, in object order
+    pub fn channel_value_counts<'a>(
+        &self,
+        objects: &'a ObjectList,
+    ) -> Result<Vec<(&'a SegmentObject, u64)>> {
+        let chunks = self.chunk_info(objects);
+        Ok(self
+            .channel_layouts(objects)?
+            .into_iter()
+            .map(|(object, layout)| (object, chunks.value_count(&layout)))
+            .collect())
+    }
+
+    /// Position in the file of a channel's value at index `value_in_segment` within this
+    /// segment. For string data this is the value's entry in the offset table.
+    /// Returns None if the channel has no data in the segment or the position of its
+    /// values cannot be determined, as for unsupported DAQmx data.
+    pub fn value_position(
+        &self,
+        channel_id: ObjectPathId,
+        objects: &ObjectList,
+        value_in_segment: u64,
+    ) -> Result<Option<u64>> {
+        let (object, layout) = match self
+            .channel_layouts(objects)?
+            .into_iter()
+            .find(|(object, _)| object.object_id == channel_id)
+        {
+            Some(object_layout) => object_layout,
+            None => return Ok(None),
+        };
+        if layout.values_per_chunk == 0 {
+            return Ok(None);
+        }
+        let chunk_index = value_in_segment / layout.values_per_chunk;
+        let index_in_chunk = value_in_segment % layout.values_per_chunk;
+        let chunk_position = self.data_position + chunk_index * objects.chunk_size();
+        let value_size = match (layout.row_width, layout.type_size, object.raw_data_index) {
+            (Some(row_width), Some(_), _) => row_width,
+            (None, Some(type_size), _) => type_size,
+            (None, None, Some(index)) if index.daqmx.is_none() => 4,
+            _ => return Ok(None),
+        };
+        Ok(Some(
+            chunk_position + layout.offset + index_in_chunk * value_size,
+        ))
+    }
+
+    /// Find the object whose raw data contains the byte at `position` in the file, and
+    /// the index within this segment of the value containing it. The value index is None
+    /// when the byte is within the string data of a string channel, or is padding
+    /// rather than part of a value.
+    /// Returns None if the position is not within this segment's raw data.
+    pub fn locate_position(
+        &self,
+        position: u64,
+        objects: &ObjectList,
+    ) -> Result<Option<(ObjectPathId, Option<u64>)>> {
+        let chunk_size = objects.chunk_size();
+        if position < self.data_position
+            || position >= self.next_segment_position
+            || chunk_size == 0
+        {
+            return Ok(None);
+        }
+        let relative_position = position - self.data_position;
+        let chunk_index = relative_position / chunk_size;
+        let position_in_chunk = relative_position % chunk_size;
+        for (object, layout) in self.channel_layouts(objects)? {
+            let data_size = object
+                .raw_data_index
+                .map(|index| index.data_size)
+                .unwrap_or(0);
+            let index_in_chunk = match (layout.row_width, layout.type_size) {
+                (Some(row_width), Some(type_size)) if row_width > 0 => {
+                    if position_in_chunk < layout.offset {
+                        continue;
+                    }
+                    let row_position = position_in_chunk - layout.offset;
+                    let row = row_position / row_width;
+                    if row >= layout.values_per_chunk || row_position % row_width >= type_size {
+                        continue;
+                    }
+                    row
+                }
+                (None, Some(type_size)) if type_size > 0 => {
+                    if position_in_chunk < layout.offset
+                        || position_in_chunk >= layout.offset + data_size
+                    {
+                        continue;
+                    }
+                    (position_in_chunk - layout.offset) / type_size
+                }
+                (None, None)
+                    if object
+                        .raw_data_index
+                        .is_some_and(|index| index.daqmx.is_none()) =>
+                {
+                    if position_in_chunk < layout.offset
+                        || position_in_chunk >= layout.offset + data_size
+                    {
+                        continue;
+                    }
+                    let index = (position_in_chunk - layout.offset) / 4;
+                    if index >= layout.values_per_chunk {
+                        return Ok(Some((object.object_id, None)));
+                    }
+                    index
+                }
+                _ => continue,
+            };
+            return Ok(Some((
+                object.object_id,
+                Some(chunk_index * layout.values_per_chunk + index_in_chunk),
+            )));
+        }
+        Ok(None)
+    }
+
+    fn chunk_info(&self, objects: &ObjectList) -> ChunkInfo {
+        let chunk_size = objects.chunk_size();
+        let data_length = self.data_length();
+        if chunk_size == 0 {
+            ChunkInfo {
+                chunk_size,
+                complete_chunks: 0,
+                partial_chunk_bytes: 0,
+            }
+        } else {
+            ChunkInfo {
+                chunk_size,
+                complete_chunks: data_length / chunk_size,
+                partial_chunk_bytes: data_length % chunk_size,
+            }
+        }
+    }
+
+    fn channel_layout(
+        &self,
+        channel_id: ObjectPathId,
+        objects: &ObjectList,
+    ) -> Result<Option<ChannelLayout>> {
+        Ok(self
+            .channel_layouts(objects)?
+            .into_iter()
+            .find(|(object, _)| object.object_id == channel_id)
+            .map(|(_, layout)| layout))
+    }
+
+    /// Where data is located within a chunk for each object with data in the segment
+    fn channel_layouts<'a>(
+        &self,
+        objects: &'a ObjectList,
+    ) -> Result<Vec<(&'a SegmentObject, ChannelLayout)>> {
+        let interleaved = self.interleaved();
+        let mut offset = 0;
+        let mut layouts = Vec::new();
+        let mut length = None;
+        let mut daqmx_offset = None;
+        for obj in objects.objects() {
+            if let Some(raw_data_index) = &obj.raw_data_index {
+                if raw_data_index.number_of_values == 0 {
+                    // Writers flushing an empty buffer write an index without values,
+                    // which takes no space in a chunk or in the rows of interleaved data
+                    if !interleaved && raw_data_index.daqmx.is_none() {
+                        offset += raw_data_index.data_size;
+                    }
+                    continue;
+                }
+                if let Some(scaler) = &raw_data_index.daqmx {
+                    // All DAQmx objects read from the same raw buffers
+                    let buffers_offset = *daqmx_offset.get_or_insert_with(|| {
+                        let buffers_offset = offset;
+                        offset += objects.daqmx_buffers_size();
+                        buffers_offset
+                    });
+                    let layout = match scaler.raw_type() {
+                        Some(raw_type) if scaler.unsupported_reason().is_none() => ChannelLayout {
+                            values_per_chunk: raw_data_index.number_of_values,
+                            data_type: raw_data_index.data_type,
+                            type_size: raw_type.size().map(|size| size as u64),
+                            offset: buffers_offset + scaler.byte_offset as u64,
+                            row_width: Some(scaler.row_width as u64),
+                        },
+                        _ => ChannelLayout {
+                            values_per_chunk: raw_data_index.number_of_values,
+                            data_type: raw_data_index.data_type,
+                            type_size: None,
+                            offset: buffers_offset,
+                            row_width: None,
+                        },
+                    };
+                    layouts.push((obj, layout));
+                    continue;
+                }
+                let type_size = raw_data_index.data_type.size().map(|size| size as u64);
+                if interleaved {
+                    if raw_data_index.data_type == TdsType::String {
+                        return Err(TdmsReadError::InvalidData(format!(
+                            "Segment at position {} has interleaved string data, but strings \
+                            can't be interleaved as their values don't have a fixed size",
+                            self.position
+                        )));
+                    }
+                    if type_size.is_none() {
+                        return Err(TdmsReadError::Unsupported(format!(
+                            "Cannot read unsized data type {:?} in interleaved data chunk",
+                            raw_data_index.data_type
+                        )));
+                    }
+                    match length {
+                        None => length = Some(raw_data_index.number_of_values),
+                        Some(length) => {
+                            if raw_data_index.number_of_values != length {
+                                return Err(TdmsReadError::InvalidData(format!(
+                                    "Different data lengths in interleaved data segment. Expected length {} but got {}",
+                                    length, raw_data_index.number_of_values)));
+                            }
+                        }
+                    }
+                }
+                layouts.push((
+                    obj,
+                    ChannelLayout {
+                        values_per_chunk: raw_data_index.number_of_values,
+                        data_type: raw_data_index.data_type,
+                        type_size,
+                        offset,
+                        row_width: None,
+                    },
+                ));
+                offset += match type_size {
+                    Some(type_size) if interleaved => type_size,
+                    _ => raw_data_index.data_size,
+                };
+            }
+        }
+        if interleaved {
+            for (obj, layout) in layouts.iter_mut() {
+                if obj
+                    .raw_data_index
+                    .is_some_and(|index| index.daqmx.is_none())
+                {
+                    layout.row_width = Some(offset);
+                }
+            }
         }
+        Ok(layouts)
     }
 
+    /// Read data for a channel from this segment into the buffer, skipping the first `skip`
+    /// values of the channel within the segment. `segment_values` is the number of values
+    /// of the channel in the segment from the channel's data index.
+    /// Returns the number of values read.
     pub fn read_channel_data<R: Read + Seek, T: NativeType>(
         &self,
         reader: &mut R,
         channel_id: ObjectPathId,
+        skip: u64,
+        segment_values: u64,
+        buffer: &mut [T],
+        objects: &ObjectList,
+    ) -> Result<usize> {
+        if self.toc_mask.has_flag(TocFlag::BigEndian) {
+            self.read_channel_data_with_order::<_, _, BigEndian>(
+                reader,
+                channel_id,
+                skip,
+                segment_values,
+                buffer,
+                objects,
+            )
+        } else {
+            self.read_channel_data_with_order::<_, _, LittleEndian>(
+                reader,
+                channel_id,
+                skip,
+                segment_values,
+                buffer,
+                objects,
+            )
+        }
+    }
+
+    fn read_channel_data_with_order<R: Read + Seek, T: NativeType, O: ByteOrderExt>(
+        &self,
+        reader: &mut R,
+        channel_id: ObjectPathId,
+        skip: u64,
+        segment_values: u64,
         buffer: &mut [T],
-        raw_data_indexes: &Arena<RawDataIndex>,
+        objects: &ObjectList,
     ) -> Result<usize> {
-        let interleaved = self.toc_mask.has_flag(TocFlag::InterleavedData);
-        let big_endian = self.toc_mask.has_flag(TocFlag::BigEndian);
-        match (interleaved, big_endian) {
-            (false, false) => self.read_contiguous_channel_data::<_, _, LittleEndian>(
+        let layout = match self.channel_layout(channel_id, objects)? {
+            Some(layout) if layout.values_per_chunk > 0 => layout,
+            _ => return Ok(0),
+        };
+        let type_size = match layout.type_size {
+            Some(type_size) => type_size,
+            None => {
+                return Err(TdmsReadError::TypeMismatch {
+                    data_type: layout.data_type,
+                    requested: String::from("fixed size values"),
+                })
+            }
+        };
+        let chunks = self.chunk_info(objects);
+
+        let mut chunk_index = skip / layout.values_per_chunk;
+        let mut skip_in_chunk = skip % layout.values_per_chunk;
+        let mut values_read = 0;
+        while values_read < buffer.len() {
+            let chunk_values = layout.chunk_value_count(chunk_index, segment_values);
+            if skip_in_chunk >= chunk_values {
+                break;
+            }
+            let count = min(
+                chunk_values - skip_in_chunk,
+                (buffer.len() - values_read) as u64,
+            ) as usize;
+            let chunk_position = self.data_position + chunk_index * chunks.chunk_size;
+            match layout.row_width {
+                None => {
+                    reader.seek(SeekFrom::Start(
+                        chunk_position + layout.offset + skip_in_chunk * type_size,
+                    ))?;
+                    T::read_values::<_, O>(&mut buffer[values_read..], reader, count)?;
+                }
+                Some(row_width) => {
+                    reader.seek(SeekFrom::Start(chunk_position + skip_in_chunk * row_width))?;
+                    let mut rows = vec![0; count * row_width as usize];
+                    reader.read_exact(&mut rows)?;
+                    let mut interleaved_reader = InterleavedReader::new(
+                        &rows,
+                        row_width as usize,
+                        type_size as usize,
+                        layout.offset as usize,
+                    );
+                    T::read_values::<_, O>(
+                        &mut buffer[values_read..],
+                        &mut interleaved_reader,
+                        count,
+                    )?;
+                }
+            }
+            values_read += count;
+            chunk_index += 1;
+            skip_in_chunk = 0;
+        }
+        Ok(values_read)
+    }
+
+    /// Read DAQmx raw data for a channel from this segment into the buffer, converting
+    /// raw values to f64 with the linear scale given by `scale` for the channel's scaler.
+    /// Skips the first `skip` values of the channel within the segment and returns the
+    /// number of values read.
+    #[allow(clippy::too_many_arguments)]
+    pub fn read_daqmx_data<R: Read + Seek, S>(
+        &self,
+        reader: &mut R,
+        channel_id: ObjectPathId,
+        skip: u64,
+        segment_values: u64,
+        buffer: &mut [f64],
+        objects: &ObjectList,
+        scale: S,
+    ) -> Result<usize>
+    where
+        S: FnOnce(&DaqmxScaler) -> Result<LinearScale>,
+    {
+        let scaler = match objects
+            .objects()
+            .iter()
+            .find(|object| object.object_id == channel_id)
+            .and_then(|object| object.raw_data_index.as_ref())
+        {
+            Some(RawDataIndex {
+                daqmx: Some(scaler),
+                ..
+            }) => *scaler,
+            Some(raw_data_index) => {
+                return Err(TdmsReadError::TypeMismatch {
+                    data_type: raw_data_index.data_type,
+                    requested: String::from("DAQmx raw data"),
+                })
+            }
+            None => return Ok(0),
+        };
+        let scale = scale(&scaler)?;
+        match scaler.raw_type() {
+            Some(TdsType::I8) => self.read_scaled::<_, i8>(
+                reader,
+                channel_id,
+                skip,
+                segment_values,
+                buffer,
+                objects,
+                scale,
+            ),
+            Some(TdsType::I16) => self.read_scaled::<_, i16>(
+                reader,
+                channel_id,
+                skip,
+                segment_values,
+                buffer,
+                objects,
+                scale,
+            ),
+            Some(TdsType::I32) => self.read_scaled::<_, i32>(
+                reader,
+                channel_id,
+                skip,
+                segment_values,
+                buffer,
+                objects,
+                scale,
+            ),
+            Some(TdsType::I64) => self.read_scaled::<_, i64>(
+                reader,
+                channel_id,
+                skip,
+                segment_values,
+                buffer,
+                objects,
+                scale,
+            ),
+            Some(TdsType::U8) => self.read_scaled::<_, u8>(
                 reader,
                 channel_id,
+                skip,
+                segment_values,
                 buffer,
-                raw_data_indexes,
+                objects,
+                scale,
             ),
-            (false, true) => self.read_contiguous_channel_data::<_, _, BigEndian>(
+            Some(TdsType::U16) => self.read_scaled::<_, u16>(
                 reader,
                 channel_id,
+                skip,
+                segment_values,
                 buffer,
-                raw_data_indexes,
+                objects,
+                scale,
             ),
-            (true, false) => self.read_interleaved_channel_data::<_, _, LittleEndian>(
+            Some(TdsType::U32) => self.read_scaled::<_, u32>(
                 reader,
                 channel_id,
+                skip,
+                segment_values,
                 buffer,
-                raw_data_indexes,
+                objects,
+                scale,
             ),
-            (true, true) => self.read_interleaved_channel_data::<_, _, BigEndian>(
+            Some(TdsType::U64) => self.read_scaled::<_, u64>(
                 reader,
                 channel_id,
+                skip,
+                segment_values,
                 buffer,
-                raw_data_indexes,
+                objects,
+                scale,
             ),
+            _ => Err(TdmsReadError::Unsupported(format!(
+                "Cannot read DAQmx raw data with data type code {}",
+                scaler.raw_data_type
+            ))),
         }
     }
 
-    fn read_contiguous_channel_data<R: Read + Seek, T: NativeType, O: ByteOrderExt>(
+    #[allow(clippy::too_many_arguments)]
+    fn read_scaled<R: Read + Seek, T: NumericValue>(
         &self,
         reader: &mut R,
         channel_id: ObjectPathId,
-        buffer: &mut [T],
-        raw_data_indexes: &Arena<RawDataIndex>,
+        skip: u64,
+        segment_values: u64,
+        buffer: &mut [f64],
+        objects: &ObjectList,
+        scale: LinearScale,
     ) -> Result<usize> {
-        let mut channel_offset = 0;
-        for obj in self.objects.iter() {
-            if let Some(raw_data_index_id) = obj.raw_data_index {
-                let raw_data_index = raw_data_indexes.get(raw_data_index_id).unwrap();
-                if obj.object_id == channel_id {
-                    reader.seek(SeekFrom::Start(self.data_position + channel_offset))?;
-                    T::read_values::<_, O>(
-                        buffer,
-                        reader,
-                        raw_data_index.number_of_values as usize,
-                    )?;
-                    return Ok(raw_data_index.number_of_values as usize);
-                } else {
-                    channel_offset += raw_data_index.data_size;
+        // Only the channel's remaining values in this segment can be read, which may be
+        // far fewer than the caller's buffer holds when reading across many segments
+        let length = min(buffer.len() as u64, segment_values.saturating_sub(skip)) as usize;
+        let mut raw_values = vec![T::default(); length];
+        let values_read = self.read_channel_data(
+            reader,
+            channel_id,
+            skip,
+            segment_values,
+            &mut raw_values,
+            objects,
+        )?;
+        for (value, raw_value) in buffer.iter_mut().zip(raw_values[..values_read].iter()) {
+            *value = scale.apply(raw_value.to_f64());
+        }
+        Ok(values_read)
+    }
+
+    /// Read string data for a channel from this segment into the buffer, skipping the first
+    /// `skip` values of the channel within the segment. `segment_values` is the number of
+    /// values of the channel in the segment from the channel's data index.
+    /// Returns the number of values read.
+    pub fn read_channel_strings<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        channel_id: ObjectPathId,
+        skip: u64,
+        segment_values: u64,
+        buffer: &mut [String],
+        objects: &ObjectList,
+    ) -> Result<usize> {
+        if self.toc_mask.has_flag(TocFlag::BigEndian) {
+            self.read_channel_strings_with_order::<_, BigEndian>(
+                reader,
+                channel_id,
+                skip,
+                segment_values,
+                buffer,
+                objects,
+            )
+        } else {
+            self.read_channel_strings_with_order::<_, LittleEndian>(
+                reader,
+                channel_id,
+                skip,
+                segment_values,
+                buffer,
+                objects,
+            )
+        }
+    }
+
+    fn read_channel_strings_with_order<R: Read + Seek, O: ByteOrder>(
+        &self,
+        reader: &mut R,
+        channel_id: ObjectPathId,
+        skip: u64,
+        segment_values: u64,
+        buffer: &mut [String],
+        objects: &ObjectList,
+    ) -> Result<usize> {
+        let layout = match self.channel_layout(channel_id, objects)? {
+            Some(layout) if layout.values_per_chunk > 0 => layout,
+            _ => return Ok(0),
+        };
+        if layout.type_size.is_some() {
+            return Err(TdmsReadError::TypeMismatch {
+                data_type: layout.data_type,
+                requested: String::from("strings"),
+            });
+        }
+        let chunks = self.chunk_info(objects);
+
+        // String data in a truncated final chunk is never counted in the data index, as
+        // the offsets can't be trusted to lie within the data that was written
+        let mut chunk_index = skip / layout.values_per_chunk;
+        let mut skip_in_chunk = skip % layout.values_per_chunk;
+        let mut values_read = 0;
+        while values_read < buffer.len() {
+            let chunk_values = layout.chunk_value_count(chunk_index, segment_values);
+            if skip_in_chunk >= chunk_values {
+                break;
+            }
+            let count = min(
+                chunk_values - skip_in_chunk,
+                (buffer.len() - values_read) as u64,
+            ) as usize;
+            let channel_position =
+                self.data_position + chunk_index * chunks.chunk_size + layout.offset;
+
+            // String data starts with the offset of the end of each string,
+            // relative to the end of the offsets
+            let start_offset = if skip_in_chunk == 0 {
+                reader.seek(SeekFrom::Start(channel_position))?;
+                0
+            } else {
+                reader.seek(SeekFrom::Start(channel_position + (skip_in_chunk - 1) * 4))?;
+                reader.read_u32::<O>()?
+            };
+            let mut end_offsets = vec![0; count];
+            reader.read_u32_into::<O>(&mut end_offsets)?;
+
+            let end_offset = end_offsets[count - 1];
+            if end_offset < start_offset {
+                return Err(TdmsReadError::InvalidData(format!(
+                    "Invalid string offsets in segment at position {}",
+                    self.position
+                )));
+            }
+            let strings_position = channel_position + layout.values_per_chunk * 4;
+            reader.seek(SeekFrom::Start(strings_position + start_offset as u64))?;
+            let mut string_bytes = vec![0; (end_offset - start_offset) as usize];
+            reader.read_exact(&mut string_bytes)?;
+
+            let mut string_start = start_offset;
+            for (value, string_end) in buffer[values_read..values_read + count]
+                .iter_mut()
+                .zip(end_offsets)
+            {
+                if string_end < string_start || string_end > end_offset {
+                    return Err(TdmsReadError::InvalidData(format!(
+                        "Invalid string offsets in segment at position {}",
+                        self.position
+                    )));
                 }
+                let bytes = &string_bytes
+                    [(string_start - start_offset) as usize..(string_end - start_offset) as usize];
+                *value = String::from_utf8(bytes.to_vec())?;
+                string_start = string_end;
             }
+
+            values_read += count;
+            chunk_index += 1;
+            skip_in_chunk = 0;
         }
-        Ok(0)
+        Ok(values_read)
     }
 
-    fn read_interleaved_channel_data<R: Read + Seek, T: NativeType, O: ByteOrderExt>(
+    /// Pass the raw bytes of a channel's data in this segment to `consume` in order,
+    /// as stored in the file and without decoding values, reading through `buffer`
+    /// about `read_size` bytes at a time. Interleaved values are gathered from each row,
+    /// and string data is passed as its offsets followed by the string bytes.
+    /// `segment_values` is the number of values of the channel in the segment from the
+    /// channel's data index. Returns the number of bytes passed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn read_channel_bytes<R: Read + Seek, F: FnMut(&[u8])>(
         &self,
         reader: &mut R,
         channel_id: ObjectPathId,
-        buffer: &mut [T],
-        raw_data_indexes: &Arena<RawDataIndex>,
-    ) -> Result<usize> {
-        let mut length = None;
-        let mut channel_params = None;
-        let mut chunk_width = 0;
-
-        for obj in self.objects.iter() {
-            if let Some(raw_data_index_id) = obj.raw_data_index {
-                let raw_data_index = raw_data_indexes.get(raw_data_index_id).unwrap();
-                let type_size = raw_data_index.data_type.size().ok_or_else(|| {
-                    TdmsReadError::TdmsError(format!(
-                        "Cannot read unsized data type {:?} in interleaved data chunk",
-                        raw_data_index.data_type
-                    ))
-                })?;
-                match length {
-                    None => length = Some(raw_data_index.number_of_values),
-                    Some(length) => {
-                        if raw_data_index.number_of_values != length {
-                            return Err(TdmsReadError::TdmsError(format!(
-                                "Different data lengths in interleaved data segment. Expected length {} but got {}",
-                                length, raw_data_index.number_of_values)));
+        segment_values: u64,
+        objects: &ObjectList,
+        read_size: usize,
+        buffer: &mut Vec<u8>,
+        consume: &mut F,
+    ) -> Result<u64> {
+        let layout = match self.channel_layout(channel_id, objects)? {
+            Some(layout) if layout.values_per_chunk > 0 => layout,
+            _ => return Ok(0),
+        };
+        let chunks = self.chunk_info(objects);
+        let mut bytes_read = 0;
+        let mut chunk_index = 0;
+        while chunk_index * layout.values_per_chunk < segment_values {
+            let chunk_values = layout.chunk_value_count(chunk_index, segment_values);
+            let chunk_position = self.data_position + chunk_index * chunks.chunk_size;
+            match (layout.row_width, layout.type_size) {
+                (Some(row_width), Some(type_size)) => {
+                    // Read whole rows, then gather the channel's value from each row
+                    reader.seek(SeekFrom::Start(chunk_position))?;
+                    let rows_per_read = (read_size as u64 / row_width).max(1);
+                    let mut values = Vec::new();
+                    let mut rows_remaining = chunk_values;
+                    while rows_remaining > 0 {
+                        let rows = min(rows_remaining, rows_per_read);
+                        buffer.resize((rows * row_width) as usize, 0);
+                        reader.read_exact(buffer)?;
+                        values.clear();
+                        for row in buffer.chunks_exact(row_width as usize) {
+                            let start = layout.offset as usize;
+                            values.extend_from_slice(&row[start..start + type_size as usize]);
                         }
+                        consume(&values);
+                        bytes_read += values.len() as u64;
+                        rows_remaining -= rows;
                     }
                 }
-                if obj.object_id == channel_id {
-                    channel_params = Some((type_size, chunk_width));
+                (_, type_size) => {
+                    let channel_length = match type_size {
+                        Some(type_size) => chunk_values * type_size,
+                        // Strings in a truncated final chunk aren't counted so are never read
+                        None => objects
+                            .objects()
+                            .iter()
+                            .find(|object| object.object_id == channel_id)
+                            .and_then(|object| object.raw_data_index.as_ref())
+                            .map_or(0, |raw_data_index| raw_data_index.data_size),
+                    };
+                    reader.seek(SeekFrom::Start(chunk_position + layout.offset))?;
+                    let mut remaining = channel_length;
+                    while remaining > 0 {
+                        let length = min(remaining, read_size as u64);
+                        buffer.resize(length as usize, 0);
+                        reader.read_exact(buffer)?;
+                        consume(buffer);
+                        remaining -= length;
+                    }
+                    bytes_read += channel_length;
                 }
-                chunk_width += type_size;
             }
+            chunk_index += 1;
         }
+        Ok(bytes_read)
+    }
 
-        if let (Some((type_size, channel_offset)), Some(length)) = (channel_params, length) {
-            let mut chunk = vec![0; (length as usize) * (chunk_width as usize)];
-            reader.seek(SeekFrom::Start(self.data_position))?;
-            reader.read_exact(&mut chunk)?;
-            let mut interleaved_reader = InterleavedReader::new(
-                &chunk,
-                chunk_width as usize,
-                type_size as usize,
-                channel_offset as usize,
-            );
-            T::read_values::<_, O>(buffer, &mut interleaved_reader, length as usize)?;
-            Ok(length as usize)
-        } else {
-            Ok(0)
+    /// Ranges of bytes containing a channel's raw data in this segment, one per chunk.
+    /// Returns no extents for DAQmx data that cannot be read.
+    pub fn channel_extents(
+        &self,
+        channel_id: ObjectPathId,
+        segment_values: u64,
+        objects: &ObjectList,
+    ) -> Result<Vec<DataExtent>> {
+        let layout = match self.channel_layout(channel_id, objects)? {
+            Some(layout) if layout.values_per_chunk > 0 => layout,
+            _ => return Ok(Vec::new()),
+        };
+        let raw_data_index = objects
+            .objects()
+            .iter()
+            .find(|object| object.object_id == channel_id)
+            .and_then(|object| object.raw_data_index.as_ref());
+        let unreadable_daqmx = match raw_data_index {
+            Some(raw_data_index) => raw_data_index.daqmx.is_some(),
+            None => true,
+        };
+        if layout.type_size.is_none() && unreadable_daqmx {
+            return Ok(Vec::new());
+        }
+        let chunks = self.chunk_info(objects);
+        let big_endian = self.big_endian();
+        let mut extents = Vec::new();
+        let mut chunk_index = 0;
+        while chunk_index * layout.values_per_chunk < segment_values {
+            let chunk_values = layout.chunk_value_count(chunk_index, segment_values);
+            let chunk_position = self.data_position + chunk_index * chunks.chunk_size;
+            let (byte_len, extent_layout) = match (layout.row_width, layout.type_size) {
+                (Some(row_width), Some(type_size)) => (
+                    (chunk_values - 1) * row_width + type_size,
+                    Layout::Interleaved { stride: row_width },
+                ),
+                (_, Some(type_size)) => (chunk_values * type_size, Layout::Contiguous),
+                (_, None) => (
+                    raw_data_index.map_or(0, |index| index.data_size),
+                    Layout::Contiguous,
+                ),
+            };
+            if chunk_values > 0 {
+                extents.push(DataExtent {
+                    file_offset: chunk_position + layout.offset,
+                    byte_len,
+                    values: chunk_values,
+                    data_type: layout.data_type,
+                    layout: extent_layout,
+                    big_endian,
+                });
+            }
+            chunk_index += 1;
         }
+        Ok(extents)
     }
 }
 
-#[derive(Debug, Clone)]
+/// Number of whole values for a channel within a truncated final chunk
+fn partial_chunk_value_count(layout: &ChannelLayout, partial_chunk_bytes: u64) -> u64 {
+    match (layout.row_width, layout.type_size) {
+        (Some(row_width), _) => min(partial_chunk_bytes / row_width, layout.values_per_chunk),
+        (None, Some(type_size)) if type_size > 0 => min(
+            partial_chunk_bytes.saturating_sub(layout.offset) / type_size,
+            layout.values_per_chunk,
+        ),
+        _ => 0,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct SegmentObject {
     pub object_id: ObjectPathId,
-    pub raw_data_index: Option<RawDataIndexId>,
+    pub raw_data_index: Option<RawDataIndex>,
 }
 
 impl SegmentObject {
@@ -164,7 +959,7 @@ impl SegmentObject {
         }
     }
 
-    pub fn with_data(object_id: ObjectPathId, raw_data_index: RawDataIndexId) -> SegmentObject {
+    pub fn with_data(object_id: ObjectPathId, raw_data_index: RawDataIndex) -> SegmentObject {
         SegmentObject {
             object_id,
             raw_data_index: Some(raw_data_index),
@@ -172,13 +967,125 @@ impl SegmentObject {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct RawDataIndex {
     pub number_of_values: u64,
     pub data_type: TdsType,
+    /// Size in bytes of the object's data in one chunk. For DAQmx raw data this is the
+    /// size of all of the segment's raw buffers, which are shared between objects.
     pub data_size: u64,
+    /// Where the values are located within the raw buffers of DAQmx raw data
+    pub daqmx: Option<DaqmxScaler>,
+}
+
+pub type RawDataIndexCache = ObjectMap<RawDataIndex>;
+
+/// The objects in a segment. Consecutive segments usually have the same objects,
+/// so object lists are stored once in `ObjectLists` and shared between segments.
+#[derive(Debug)]
+pub struct ObjectList {
+    objects: Vec<SegmentObject>,
+    /// Size in bytes of one chunk of raw data, containing data for all objects
+    chunk_size: u64,
+    /// Size in bytes of the DAQmx raw buffers in one chunk, which are shared by all
+    /// objects with DAQmx raw data
+    daqmx_buffers_size: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ObjectListId(usize);
+
+impl ObjectListId {
+    #[cfg(feature = "cache")]
+    pub fn as_usize(&self) -> usize {
+        self.0
+    }
+}
+
+/// Storage for the object lists of all segments, indexed by `ObjectListId`
+#[derive(Debug, Default)]
+pub struct ObjectLists {
+    lists: Vec<ObjectList>,
+}
+
+impl ObjectLists {
+    pub fn new() -> ObjectLists {
+        ObjectLists { lists: Vec::new() }
+    }
+
+    /// Store an object list, returning the id that segments refer to it by
+    pub fn push(&mut self, object_list: ObjectList) -> ObjectListId {
+        self.lists.push(object_list);
+        ObjectListId(self.lists.len() - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lists.len()
+    }
+
+    /// Remove the object lists stored after the first `len`, which no segment may refer to
+    pub fn truncate(&mut self, len: usize) {
+        self.lists.truncate(len);
+    }
+
+    /// Object lists in the order they were stored, so the position of each is its id
+    #[cfg(feature = "cache")]
+    pub fn iter(&self) -> impl Iterator<Item = &ObjectList> {
+        self.lists.iter()
+    }
 }
 
-pub type RawDataIndexId = Id<RawDataIndex>;
+impl Index<ObjectListId> for ObjectLists {
+    type Output = ObjectList;
 
-pub type RawDataIndexCache = ObjectMap<RawDataIndexId>;
+    fn index(&self, id: ObjectListId) -> &ObjectList {
+        &self.lists[id.0]
+    }
+}
+
+impl ObjectList {
+    /// Create an object list, or None if the total data size of the objects overflows
+    pub fn new(objects: Vec<SegmentObject>) -> Option<ObjectList> {
+        let raw_data_indexes = || objects.iter().filter_map(|obj| obj.raw_data_index.as_ref());
+        // DAQmx objects share the same raw buffers, which are only counted once. Their size
+        // comes from the widths of the buffers rather than the size of each object's values.
+        let daqmx_buffers_size = raw_data_indexes()
+            .filter(|raw_data_index| raw_data_index.daqmx.is_some())
+            .map(|raw_data_index| raw_data_index.data_size)
+            .max()
+            .unwrap_or(0);
+        let chunk_size = raw_data_indexes()
+            .filter(|raw_data_index| raw_data_index.daqmx.is_none())
+            .try_fold(daqmx_buffers_size, |size, raw_data_index| {
+                size.checked_add(raw_data_index.data_size)
+            })?;
+        Some(ObjectList {
+            objects,
+            chunk_size,
+            daqmx_buffers_size,
+        })
+    }
+
+    pub fn objects(&self) -> &[SegmentObject] {
+        &self.objects
+    }
+
+    /// Size in bytes of one chunk of raw data, containing data for all objects in the list
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk_size
+    }
+
+    /// Size in bytes of the DAQmx raw buffers in one chunk
+    pub fn daqmx_buffers_size(&self) -> u64 {
+        self.daqmx_buffers_size
+    }
+
+    /// Whether any object has a DAQmx raw data index
+    pub fn has_daqmx_data(&self) -> bool {
+        self.objects.iter().any(|object| {
+            object
+                .raw_data_index
+                .is_some_and(|index| index.daqmx.is_some())
+        })
+    }
+}
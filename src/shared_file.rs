@@ -0,0 +1,122 @@
+use crate::error::{Result, TdmsReadError};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::Path;
+
+/// Windows share mode flags, from the `FILE_SHARE_*` constants of `CreateFileW`
+const FILE_SHARE_READ: u32 = 0x1;
+const FILE_SHARE_WRITE: u32 = 0x2;
+const FILE_SHARE_DELETE: u32 = 0x4;
+
+/// Share mode used when opening files on Windows. LabVIEW keeps files it is logging to
+/// open for writing, and Windows refuses to open such a file unless the new handle
+/// shares writing, so this allows other handles to read, write and delete the file.
+/// It matches the standard library's current default, but is set explicitly so that
+/// following files that are being written doesn't depend on that default.
+pub(crate) const SHARE_MODE: u32 = FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE;
+
+/// Windows error codes for a file that is open with a share mode that excludes reading
+#[cfg(windows)]
+const ERROR_SHARING_VIOLATION: i32 = 32;
+#[cfg(windows)]
+const ERROR_LOCK_VIOLATION: i32 = 33;
+
+/// Options for opening a file read only, while other programs may still be writing it
+pub(crate) fn read_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    options.read(true);
+    set_share_mode(&mut options, SHARE_MODE);
+    options
+}
+
+#[cfg(windows)]
+fn set_share_mode(options: &mut OpenOptions, share_mode: u32) {
+    use std::os::windows::fs::OpenOptionsExt;
+    options.share_mode(share_mode);
+}
+
+/// Other platforms don't have share modes, as opening a file never prevents reading it
+#[cfg(not(windows))]
+fn set_share_mode(_options: &mut OpenOptions, _share_mode: u32) {}
+
+/// Open a file read only, allowing other programs to keep writing to it. Returns
+/// `FileLocked` if another program has the file open in a way that prevents reading.
+pub(crate) fn open_shared(path: &Path) -> Result<File> {
+    read_options().open(path).map_err(|error| {
+        if is_locked(&error) {
+            TdmsReadError::FileLocked(path.to_path_buf())
+        } else {
+            TdmsReadError::IoError(error)
+        }
+    })
+}
+
+/// Whether opening a file failed because another program has it locked
+#[cfg(windows)]
+fn is_locked(error: &io::Error) -> bool {
+    matches!(
+        error.raw_os_error(),
+        Some(ERROR_SHARING_VIOLATION) | Some(ERROR_LOCK_VIOLATION)
+    )
+}
+
+/// Whether opening a file failed because another program has it locked. Files are
+/// never locked against reading on other platforms.
+#[cfg(not(windows))]
+fn is_locked(_error: &io::Error) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::{Read, Write};
+
+    #[test]
+    fn share_mode_allows_writers() {
+        assert_eq!(SHARE_MODE & FILE_SHARE_READ, FILE_SHARE_READ);
+        assert_eq!(SHARE_MODE & FILE_SHARE_WRITE, FILE_SHARE_WRITE);
+    }
+
+    #[test]
+    fn open_file_being_written() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("rstdms_shared_file_{}.tdms", std::process::id()));
+        let mut writer = File::create(&path).unwrap();
+        writer.write_all(b"TDSm").unwrap();
+        writer.flush().unwrap();
+
+        let mut contents = Vec::new();
+        open_shared(&path)
+            .unwrap()
+            .read_to_end(&mut contents)
+            .unwrap();
+
+        assert_eq!(contents, b"TDSm");
+        drop(writer);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        let path = std::env::temp_dir().join("rstdms_shared_file_missing.tdms");
+        match open_shared(&path) {
+            Err(TdmsReadError::IoError(error)) => {
+                assert_eq!(error.kind(), io::ErrorKind::NotFound)
+            }
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn sharing_violations_are_locked_errors() {
+        assert!(is_locked(&io::Error::from_raw_os_error(
+            ERROR_SHARING_VIOLATION
+        )));
+        assert!(is_locked(&io::Error::from_raw_os_error(
+            ERROR_LOCK_VIOLATION
+        )));
+        assert!(!is_locked(&io::Error::from_raw_os_error(2)));
+    }
+}
@@ -0,0 +1,381 @@
+use crate::diff::DiffObject;
+use crate::properties::TdmsProperty;
+use crate::types::TdsType;
+use crate::TdmsFile;
+use std::fmt;
+use std::io::{Read, Seek};
+
+/// Options controlling what is included in a `FileSnapshot`
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotOptions {
+    /// Names of the properties to include, or None to include all properties
+    pub properties: Option<Vec<String>>,
+
+    /// Names of properties to leave out, eg. timestamps that change with every acquisition
+    pub ignore_properties: Vec<String>,
+}
+
+impl SnapshotOptions {
+    fn includes(&self, name: &str) -> bool {
+        let selected = match self.properties {
+            Some(ref names) => names.iter().any(|n| n == name),
+            None => true,
+        };
+        selected && !self.ignore_properties.iter().any(|n| n == name)
+    }
+}
+
+/// The structure and metadata of a TDMS file, fully owned so that it can be stored and
+/// compared with snapshots of other files taken at other times.
+///
+/// With the `serde` feature, snapshots can be serialized. The serialized form only
+/// changes with a new major version of the crate, so stored snapshots remain readable.
+/// Groups, channels and properties are in the order they first appear in the file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileSnapshot {
+    /// Properties of the root object
+    pub properties: Vec<PropertySnapshot>,
+
+    pub groups: Vec<GroupSnapshot>,
+}
+
+/// A group within a `FileSnapshot`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupSnapshot {
+    /// Name of the group, with any quotes unescaped
+    pub name: String,
+
+    pub properties: Vec<PropertySnapshot>,
+
+    pub channels: Vec<ChannelSnapshot>,
+}
+
+/// A channel within a `FileSnapshot`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChannelSnapshot {
+    /// Name of the channel, with any quotes unescaped
+    pub name: String,
+
+    /// Data type of the channel, or None if it has no data
+    pub data_type: Option<TdsType>,
+
+    /// Number of values in the channel
+    pub length: u64,
+
+    pub properties: Vec<PropertySnapshot>,
+}
+
+/// A property within a `FileSnapshot`. The value is stored as text so that snapshots
+/// can be compared exactly and hashed. Numbers are formatted so that they convert back
+/// to the same value, and timestamps are formatted as RFC 3339 with nanosecond precision.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PropertySnapshot {
+    pub name: String,
+
+    pub data_type: TdsType,
+
+    pub value: String,
+}
+
+impl PropertySnapshot {
+    fn new(property: &TdmsProperty) -> PropertySnapshot {
+        PropertySnapshot {
+            name: property.name.to_string(),
+            data_type: property.value.data_type(),
+            value: property.value.to_string(),
+        }
+    }
+}
+
+/// Take a snapshot of the groups, channels and selected properties of a file
+pub fn file_snapshot<R: Read + Seek>(
+    tdms_file: &TdmsFile<R>,
+    options: &SnapshotOptions,
+) -> FileSnapshot {
+    let properties = |properties: &[TdmsProperty]| {
+        properties
+            .iter()
+            .filter(|property| options.includes(&property.name))
+            .map(PropertySnapshot::new)
+            .collect()
+    };
+    FileSnapshot {
+        properties: properties(tdms_file.properties()),
+        groups: tdms_file
+            .groups()
+            .map(|group| GroupSnapshot {
+                name: group.name().to_string(),
+                properties: properties(group.properties()),
+                channels: group
+                    .channels()
+                    .map(|channel| ChannelSnapshot {
+                        name: channel.name().to_string(),
+                        data_type: channel.data_type(),
+                        length: channel.len(),
+                        properties: properties(channel.properties()),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+/// A single change between two snapshots, from an older snapshot to a newer one
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SnapshotChange {
+    GroupAdded {
+        group: String,
+    },
+
+    GroupRemoved {
+        group: String,
+    },
+
+    ChannelAdded {
+        group: String,
+        channel: String,
+    },
+
+    ChannelRemoved {
+        group: String,
+        channel: String,
+    },
+
+    /// A channel's data type or number of values changed
+    ChannelChanged {
+        group: String,
+        channel: String,
+        old: ChannelSnapshot,
+        new: ChannelSnapshot,
+    },
+
+    PropertyAdded {
+        object: DiffObject,
+        property: PropertySnapshot,
+    },
+
+    PropertyRemoved {
+        object: DiffObject,
+        property: PropertySnapshot,
+    },
+
+    /// A property's value or data type changed
+    PropertyChanged {
+        object: DiffObject,
+        old: PropertySnapshot,
+        new: PropertySnapshot,
+    },
+}
+
+/// The changes between two snapshots, found with `FileSnapshot::diff`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    pub changes: Vec<SnapshotChange>,
+}
+
+impl SnapshotDiff {
+    /// Whether the snapshots have the same groups, channels and properties
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl FileSnapshot {
+    /// Find the changes from this snapshot to a newer snapshot. Groups, channels and
+    /// properties are matched by name, so changes in their order are not reported.
+    pub fn diff(&self, newer: &FileSnapshot) -> SnapshotDiff {
+        let mut changes = Vec::new();
+        diff_properties(
+            DiffObject::Root,
+            &self.properties,
+            &newer.properties,
+            &mut changes,
+        );
+        for old_group in self.groups.iter() {
+            match newer.group(&old_group.name) {
+                Some(new_group) => diff_groups(old_group, new_group, &mut changes),
+                None => changes.push(SnapshotChange::GroupRemoved {
+                    group: old_group.name.clone(),
+                }),
+            }
+        }
+        for new_group in newer.groups.iter() {
+            if self.group(&new_group.name).is_none() {
+                changes.push(SnapshotChange::GroupAdded {
+                    group: new_group.name.clone(),
+                });
+            }
+        }
+        SnapshotDiff { changes }
+    }
+
+    /// Get a group by name
+    pub fn group(&self, name: &str) -> Option<&GroupSnapshot> {
+        self.groups.iter().find(|group| group.name == name)
+    }
+}
+
+impl GroupSnapshot {
+    /// Get a channel by name
+    pub fn channel(&self, name: &str) -> Option<&ChannelSnapshot> {
+        self.channels.iter().find(|channel| channel.name == name)
+    }
+}
+
+fn diff_groups(old: &GroupSnapshot, new: &GroupSnapshot, changes: &mut Vec<SnapshotChange>) {
+    diff_properties(
+        DiffObject::Group(old.name.clone()),
+        &old.properties,
+        &new.properties,
+        changes,
+    );
+    for old_channel in old.channels.iter() {
+        let new_channel = match new.channel(&old_channel.name) {
+            Some(new_channel) => new_channel,
+            None => {
+                changes.push(SnapshotChange::ChannelRemoved {
+                    group: old.name.clone(),
+                    channel: old_channel.name.clone(),
+                });
+                continue;
+            }
+        };
+        if old_channel.data_type != new_channel.data_type
+            || old_channel.length != new_channel.length
+        {
+            changes.push(SnapshotChange::ChannelChanged {
+                group: old.name.clone(),
+                channel: old_channel.name.clone(),
+                old: old_channel.clone(),
+                new: new_channel.clone(),
+            });
+        }
+        diff_properties(
+            DiffObject::Channel(old.name.clone(), old_channel.name.clone()),
+            &old_channel.properties,
+            &new_channel.properties,
+            changes,
+        );
+    }
+    for new_channel in new.channels.iter() {
+        if old.channel(&new_channel.name).is_none() {
+            changes.push(SnapshotChange::ChannelAdded {
+                group: old.name.clone(),
+                channel: new_channel.name.clone(),
+            });
+        }
+    }
+}
+
+fn diff_properties(
+    object: DiffObject,
+    old: &[PropertySnapshot],
+    new: &[PropertySnapshot],
+    changes: &mut Vec<SnapshotChange>,
+) {
+    for old_property in old.iter() {
+        match new.iter().find(|p| p.name == old_property.name) {
+            Some(new_property) if new_property != old_property => {
+                changes.push(SnapshotChange::PropertyChanged {
+                    object: object.clone(),
+                    old: old_property.clone(),
+                    new: new_property.clone(),
+                })
+            }
+            Some(_) => {}
+            None => changes.push(SnapshotChange::PropertyRemoved {
+                object: object.clone(),
+                property: old_property.clone(),
+            }),
+        }
+    }
+    for new_property in new.iter() {
+        if !old.iter().any(|p| p.name == new_property.name) {
+            changes.push(SnapshotChange::PropertyAdded {
+                object: object.clone(),
+                property: new_property.clone(),
+            });
+        }
+    }
+}
+
+impl fmt::Display for SnapshotChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SnapshotChange::GroupAdded { ref group } => write!(f, "Group {} added", group),
+            SnapshotChange::GroupRemoved { ref group } => write!(f, "Group {} removed", group),
+            SnapshotChange::ChannelAdded {
+                ref group,
+                ref channel,
+            } => write!(f, "Channel {} / {} added", group, channel),
+            SnapshotChange::ChannelRemoved {
+                ref group,
+                ref channel,
+            } => write!(f, "Channel {} / {} removed", group, channel),
+            SnapshotChange::ChannelChanged {
+                ref group,
+                ref channel,
+                ref old,
+                ref new,
+            } => {
+                let data_type = |data_type: Option<TdsType>| {
+                    data_type.map_or(String::from("no data"), |data_type| data_type.to_string())
+                };
+                write!(
+                    f,
+                    "Channel {} / {} changed from {} x {} to {} x {}",
+                    group,
+                    channel,
+                    old.length,
+                    data_type(old.data_type),
+                    new.length,
+                    data_type(new.data_type)
+                )
+            }
+            SnapshotChange::PropertyAdded {
+                ref object,
+                ref property,
+            } => write!(
+                f,
+                "Property {} of {} added: {}",
+                property.name, object, property.value
+            ),
+            SnapshotChange::PropertyRemoved {
+                ref object,
+                ref property,
+            } => write!(f, "Property {} of {} removed", property.name, object),
+            SnapshotChange::PropertyChanged {
+                ref object,
+                ref old,
+                ref new,
+            } => {
+                write!(
+                    f,
+                    "Property {} of {} changed: {} -> {}",
+                    old.name, object, old.value, new.value
+                )?;
+                if old.data_type != new.data_type {
+                    write!(f, " ({} -> {})", old.data_type, new.data_type)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.changes.is_empty() {
+            return write!(f, "No changes");
+        }
+        writeln!(f, "Found {} change(s):", self.changes.len())?;
+        for change in self.changes.iter() {
+            writeln!(f, "  {}", change)?;
+        }
+        Ok(())
+    }
+}
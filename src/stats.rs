@@ -0,0 +1,546 @@
+use crate::decimate::NumericValue;
+use crate::error::{Result, TdmsReadError};
+use crate::progress::ReadControl;
+use crate::types::{NativeTypeId, TdsType};
+use crate::visit::{self, DataVisitor, ValuesRef};
+use crate::{Channel, Timestamp};
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek};
+use std::ops::ControlFlow;
+
+/// Options for computing channel statistics
+#[derive(Debug, Clone)]
+pub struct StatisticsOptions {
+    /// Number of values read from the file at a time, or None to use the chunk
+    /// configuration of the file's read options
+    pub chunk_size: Option<usize>,
+
+    /// Maximum number of distinct strings to track for string channels
+    pub max_distinct_strings: usize,
+}
+
+impl Default for StatisticsOptions {
+    fn default() -> StatisticsOptions {
+        StatisticsOptions {
+            chunk_size: None,
+            max_distinct_strings: 10_000,
+        }
+    }
+}
+
+/// Summary statistics of numeric values, computed incrementally in a single pass
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumericStatistics {
+    count: u64,
+    nan_count: u64,
+    min: f64,
+    max: f64,
+    mean: f64,
+    sum_squared_deviations: f64,
+}
+
+impl NumericStatistics {
+    /// Create statistics with no values
+    pub fn new() -> NumericStatistics {
+        NumericStatistics {
+            count: 0,
+            nan_count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            mean: 0.0,
+            sum_squared_deviations: 0.0,
+        }
+    }
+
+    /// Update the statistics with a new value
+    pub fn update(&mut self, value: f64) {
+        self.count += 1;
+        if value.is_nan() {
+            self.nan_count += 1;
+            return;
+        }
+        if value < self.min {
+            self.min = value;
+        }
+        if value > self.max {
+            self.max = value;
+        }
+        // Welford's algorithm for a numerically stable running variance
+        let n = (self.count - self.nan_count) as f64;
+        let delta = value - self.mean;
+        self.mean += delta / n;
+        self.sum_squared_deviations += delta * (value - self.mean);
+    }
+
+    /// Combine these statistics with statistics computed over a different set of values
+    pub fn merge(&mut self, other: &NumericStatistics) {
+        let n_self = self.value_count() as f64;
+        let n_other = other.value_count() as f64;
+        if n_other > 0.0 {
+            let n = n_self + n_other;
+            let delta = other.mean - self.mean;
+            self.mean += delta * n_other / n;
+            self.sum_squared_deviations +=
+                other.sum_squared_deviations + delta * delta * n_self * n_other / n;
+            self.min = self.min.min(other.min);
+            self.max = self.max.max(other.max);
+        }
+        self.count += other.count;
+        self.nan_count += other.nan_count;
+    }
+
+    /// Total number of values, including NaN values
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Number of NaN values, which are excluded from all other statistics
+    pub fn nan_count(&self) -> u64 {
+        self.nan_count
+    }
+
+    /// Minimum value, or None if there are no non-NaN values
+    pub fn min(&self) -> Option<f64> {
+        self.if_any_values(self.min)
+    }
+
+    /// Maximum value, or None if there are no non-NaN values
+    pub fn max(&self) -> Option<f64> {
+        self.if_any_values(self.max)
+    }
+
+    /// Mean value, or None if there are no non-NaN values
+    pub fn mean(&self) -> Option<f64> {
+        self.if_any_values(self.mean)
+    }
+
+    /// Population standard deviation, or None if there are no non-NaN values
+    pub fn std_dev(&self) -> Option<f64> {
+        self.if_any_values((self.sum_squared_deviations / self.value_count() as f64).sqrt())
+    }
+
+    /// Root mean square value, or None if there are no non-NaN values
+    pub fn rms(&self) -> Option<f64> {
+        let variance = self.sum_squared_deviations / self.value_count() as f64;
+        self.if_any_values((self.mean * self.mean + variance).sqrt())
+    }
+
+    fn value_count(&self) -> u64 {
+        self.count - self.nan_count
+    }
+
+    fn if_any_values(&self, value: f64) -> Option<f64> {
+        if self.value_count() > 0 {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for NumericStatistics {
+    fn default() -> NumericStatistics {
+        NumericStatistics::new()
+    }
+}
+
+/// Summary statistics of string values
+#[derive(Debug, Clone)]
+pub struct StringStatistics {
+    count: u64,
+    distinct_values: HashSet<String>,
+    max_distinct: usize,
+    distinct_limit_reached: bool,
+}
+
+impl StringStatistics {
+    /// Create statistics with no values, tracking at most `max_distinct` distinct values
+    pub fn new(max_distinct: usize) -> StringStatistics {
+        StringStatistics {
+            count: 0,
+            distinct_values: HashSet::new(),
+            max_distinct,
+            distinct_limit_reached: false,
+        }
+    }
+
+    /// Update the statistics with a new value
+    pub fn update(&mut self, value: &str) {
+        self.count += 1;
+        if !self.distinct_values.contains(value) {
+            if self.distinct_values.len() < self.max_distinct {
+                self.distinct_values.insert(value.to_string());
+            } else {
+                self.distinct_limit_reached = true;
+            }
+        }
+    }
+
+    /// Total number of values
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Number of distinct values. If `distinct_limit_reached` is true,
+    /// this is a lower bound on the true number of distinct values.
+    pub fn distinct_count(&self) -> usize {
+        self.distinct_values.len()
+    }
+
+    /// Whether there were more distinct values than the maximum number tracked
+    pub fn distinct_limit_reached(&self) -> bool {
+        self.distinct_limit_reached
+    }
+}
+
+/// Summary statistics of timestamp values
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimestampStatistics {
+    count: u64,
+    first: Option<Timestamp>,
+    last: Option<Timestamp>,
+}
+
+impl TimestampStatistics {
+    /// Create statistics with no values
+    pub fn new() -> TimestampStatistics {
+        TimestampStatistics::default()
+    }
+
+    /// Update the statistics with a new value
+    pub fn update(&mut self, value: Timestamp) {
+        self.count += 1;
+        if self.first.is_none() {
+            self.first = Some(value);
+        }
+        self.last = Some(value);
+    }
+
+    /// Total number of values
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The first value, or None if there are no values
+    pub fn first(&self) -> Option<Timestamp> {
+        self.first
+    }
+
+    /// The last value, or None if there are no values
+    pub fn last(&self) -> Option<Timestamp> {
+        self.last
+    }
+}
+
+/// Statistics for a channel, depending on the channel's data type
+#[derive(Debug, Clone)]
+pub enum ChannelStatistics {
+    /// The channel has no data
+    NoData,
+
+    /// Statistics for a channel with a numeric data type
+    Numeric(NumericStatistics),
+
+    /// Statistics for a string channel
+    String(StringStatistics),
+
+    /// Statistics for a timestamp channel
+    Timestamp(TimestampStatistics),
+
+    /// Reading data of the channel's type is not supported
+    Unsupported(TdsType),
+}
+
+/// Compute statistics for a channel, reading its data in chunks in a single pass
+pub fn channel_statistics<R: Read + Seek>(
+    channel: &Channel<R>,
+    options: &StatisticsOptions,
+) -> Result<ChannelStatistics> {
+    channel_statistics_with_control(channel, options, &mut ReadControl::new())
+}
+
+/// Compute statistics for a channel as with `channel_statistics`, reporting progress
+/// in values and checking for cancellation using the given read control
+pub fn channel_statistics_with_control<R: Read + Seek>(
+    channel: &Channel<R>,
+    options: &StatisticsOptions,
+    control: &mut ReadControl,
+) -> Result<ChannelStatistics> {
+    let data_type = match channel.data_type() {
+        Some(data_type) => data_type,
+        None => return Ok(ChannelStatistics::NoData),
+    };
+    if data_type == TdsType::String {
+        return string_statistics(channel, options, control).map(ChannelStatistics::String);
+    }
+    let statistics = match data_type.native_type() {
+        Some(NativeTypeId::I8) => numeric_statistics::<i8, _>(channel, options, control)?,
+        Some(NativeTypeId::I16) => numeric_statistics::<i16, _>(channel, options, control)?,
+        Some(NativeTypeId::I32) => numeric_statistics::<i32, _>(channel, options, control)?,
+        Some(NativeTypeId::I64) => numeric_statistics::<i64, _>(channel, options, control)?,
+        Some(NativeTypeId::U8) => numeric_statistics::<u8, _>(channel, options, control)?,
+        Some(NativeTypeId::U16) => numeric_statistics::<u16, _>(channel, options, control)?,
+        Some(NativeTypeId::U32) => numeric_statistics::<u32, _>(channel, options, control)?,
+        Some(NativeTypeId::U64) => numeric_statistics::<u64, _>(channel, options, control)?,
+        Some(NativeTypeId::F32) => numeric_statistics::<f32, _>(channel, options, control)?,
+        Some(NativeTypeId::F64) => numeric_statistics::<f64, _>(channel, options, control)?,
+        Some(NativeTypeId::Timestamp) => {
+            return timestamp_statistics(channel, options, control)
+                .map(ChannelStatistics::Timestamp)
+        }
+        None => return Ok(ChannelStatistics::Unsupported(data_type)),
+    };
+    Ok(ChannelStatistics::Numeric(statistics))
+}
+
+/// Compute statistics for several channels of the same file in a single pass through the
+/// file's data, rather than reading through the file once for each channel as with
+/// `channel_statistics`. Returns the statistics of each channel in the order given.
+pub fn channels_statistics<R: Read + Seek>(
+    channels: &[Channel<R>],
+    options: &StatisticsOptions,
+) -> Result<Vec<ChannelStatistics>> {
+    let tdms_file = match visit::channels_file(channels)? {
+        Some(tdms_file) => tdms_file,
+        None => return Ok(Vec::new()),
+    };
+    let mut visitor = StatisticsVisitor {
+        channel_indexes: channels
+            .iter()
+            .enumerate()
+            .map(|(index, channel)| (channel.path(), index))
+            .collect(),
+        statistics: channels
+            .iter()
+            .map(|channel| empty_statistics(channel, options))
+            .collect(),
+    };
+    visit::visit_data(tdms_file, &mut visitor, options.chunk_size)?;
+    Ok(visitor.statistics)
+}
+
+/// Statistics of a channel before any values are read
+fn empty_statistics<R: Read + Seek>(
+    channel: &Channel<R>,
+    options: &StatisticsOptions,
+) -> ChannelStatistics {
+    match channel.data_type() {
+        None => ChannelStatistics::NoData,
+        Some(TdsType::String) => {
+            ChannelStatistics::String(StringStatistics::new(options.max_distinct_strings))
+        }
+        Some(data_type) => match data_type.native_type() {
+            Some(NativeTypeId::Timestamp) => {
+                ChannelStatistics::Timestamp(TimestampStatistics::new())
+            }
+            Some(_) => ChannelStatistics::Numeric(NumericStatistics::new()),
+            None => ChannelStatistics::Unsupported(data_type),
+        },
+    }
+}
+
+/// Updates the statistics of each channel with chunks of its values
+struct StatisticsVisitor<'a> {
+    channel_indexes: HashMap<&'a str, usize>,
+    statistics: Vec<ChannelStatistics>,
+}
+
+impl<'a> StatisticsVisitor<'a> {
+    fn channel_statistics(&mut self, path: &str) -> Option<&mut ChannelStatistics> {
+        let index = *self.channel_indexes.get(path)?;
+        Some(&mut self.statistics[index])
+    }
+}
+
+impl<'a> DataVisitor for StatisticsVisitor<'a> {
+    fn visit_channel(&mut self, path: &str, _data_type: TdsType) -> bool {
+        !matches!(
+            self.channel_statistics(path),
+            None | Some(ChannelStatistics::NoData) | Some(ChannelStatistics::Unsupported(_))
+        )
+    }
+
+    fn chunk(&mut self, path: &str, _data_type: TdsType, values: ValuesRef<'_>) -> ControlFlow<()> {
+        match (self.channel_statistics(path), values) {
+            (Some(ChannelStatistics::Numeric(statistics)), values) => {
+                values.for_each_f64(|value| statistics.update(value));
+            }
+            (Some(ChannelStatistics::String(statistics)), ValuesRef::String(values)) => {
+                values.iter().for_each(|value| statistics.update(value));
+            }
+            (Some(ChannelStatistics::Timestamp(statistics)), ValuesRef::Timestamp(values)) => {
+                values.iter().for_each(|value| statistics.update(*value));
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Number of values of type `T` read at a time
+fn chunk_values<T, R: Read + Seek>(channel: &Channel<R>, options: &StatisticsOptions) -> usize {
+    options
+        .chunk_size
+        .unwrap_or_else(|| channel.chunk_config().values(std::mem::size_of::<T>()))
+        .max(1)
+}
+
+fn numeric_statistics<T: NumericValue, R: Read + Seek>(
+    channel: &Channel<R>,
+    options: &StatisticsOptions,
+    control: &mut ReadControl,
+) -> Result<NumericStatistics> {
+    let mut statistics = NumericStatistics::new();
+    let mut buffer = channel
+        .scratch()
+        .take::<T>(chunk_values::<T, _>(channel, options));
+    let mut offset = 0;
+    loop {
+        if control.is_cancelled() {
+            return Err(TdmsReadError::Cancelled);
+        }
+        let values_read = channel.read_data(offset, &mut buffer[..])?;
+        if values_read == 0 {
+            break;
+        }
+        for value in buffer[..values_read].iter() {
+            statistics.update(value.to_f64());
+        }
+        offset += values_read as u64;
+        control.report_progress(offset, channel.len());
+    }
+    Ok(statistics)
+}
+
+fn timestamp_statistics<R: Read + Seek>(
+    channel: &Channel<R>,
+    options: &StatisticsOptions,
+    control: &mut ReadControl,
+) -> Result<TimestampStatistics> {
+    let mut statistics = TimestampStatistics::new();
+    let mut buffer = channel
+        .scratch()
+        .take::<Timestamp>(chunk_values::<Timestamp, _>(channel, options));
+    let mut offset = 0;
+    loop {
+        if control.is_cancelled() {
+            return Err(TdmsReadError::Cancelled);
+        }
+        let values_read = channel.read_data(offset, &mut buffer[..])?;
+        if values_read == 0 {
+            break;
+        }
+        for value in buffer[..values_read].iter() {
+            statistics.update(*value);
+        }
+        offset += values_read as u64;
+        control.report_progress(offset, channel.len());
+    }
+    Ok(statistics)
+}
+
+fn string_statistics<R: Read + Seek>(
+    channel: &Channel<R>,
+    options: &StatisticsOptions,
+    control: &mut ReadControl,
+) -> Result<StringStatistics> {
+    let mut statistics = StringStatistics::new(options.max_distinct_strings);
+    let mut buffer = channel
+        .scratch()
+        .take::<String>(chunk_values::<String, _>(channel, options));
+    let mut offset = 0;
+    loop {
+        if control.is_cancelled() {
+            return Err(TdmsReadError::Cancelled);
+        }
+        let values_read = channel.read_strings(offset, &mut buffer[..])?;
+        if values_read == 0 {
+            break;
+        }
+        for value in buffer[..values_read].iter() {
+            statistics.update(value);
+        }
+        offset += values_read as u64;
+        control.report_progress(offset, channel.len());
+    }
+    Ok(statistics)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.unwrap();
+        assert!(
+            (actual - expected).abs() < 1e-12,
+            "Expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn numeric_statistics_ignore_nan() {
+        let mut statistics = NumericStatistics::new();
+        for value in [2.0, 4.0, f64::NAN, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0].iter() {
+            statistics.update(*value);
+        }
+
+        assert_eq!(statistics.count(), 9);
+        assert_eq!(statistics.nan_count(), 1);
+        assert_eq!(statistics.min(), Some(2.0));
+        assert_eq!(statistics.max(), Some(9.0));
+        assert_close(statistics.mean(), 5.0);
+        assert_close(statistics.std_dev(), 2.0);
+        assert_close(statistics.rms(), 29.0f64.sqrt());
+    }
+
+    #[test]
+    fn merged_statistics_match_single_pass() {
+        let values = [1.5, -3.0, 8.25, 0.0, 12.0, 7.0, -1.0];
+        let mut single_pass = NumericStatistics::new();
+        let mut first = NumericStatistics::new();
+        let mut second = NumericStatistics::new();
+        for (i, value) in values.iter().enumerate() {
+            single_pass.update(*value);
+            if i < 3 {
+                first.update(*value);
+            } else {
+                second.update(*value);
+            }
+        }
+
+        first.merge(&second);
+
+        assert_eq!(first.count(), single_pass.count());
+        assert_eq!(first.min(), single_pass.min());
+        assert_eq!(first.max(), single_pass.max());
+        assert_close(first.mean(), single_pass.mean().unwrap());
+        assert_close(first.std_dev(), single_pass.std_dev().unwrap());
+    }
+
+    #[test]
+    fn empty_numeric_statistics() {
+        let mut statistics = NumericStatistics::new();
+        statistics.update(f64::NAN);
+
+        assert_eq!(statistics.count(), 1);
+        assert_eq!(statistics.min(), None);
+        assert_eq!(statistics.mean(), None);
+        assert_eq!(statistics.std_dev(), None);
+        assert_eq!(statistics.rms(), None);
+    }
+
+    #[test]
+    fn distinct_strings_are_capped() {
+        let mut statistics = StringStatistics::new(2);
+        for value in ["a", "b", "a", "c", "d"].iter() {
+            statistics.update(value);
+        }
+
+        assert_eq!(statistics.count(), 5);
+        assert_eq!(statistics.distinct_count(), 2);
+        assert!(statistics.distinct_limit_reached());
+    }
+}
@@ -0,0 +1,184 @@
+use crate::error::{Result, TdmsReadError};
+use crate::inspect::{DataExtent, Layout};
+use crate::types::NativeType;
+use crate::Channel;
+use byteorder::{BigEndian, LittleEndian};
+use std::io::{Read, Seek, SeekFrom};
+
+/// Gap in bytes between wanted values above which seeking to each value is faster than
+/// reading all of the data in between
+const MIN_SEEK_GAP: u64 = 4096;
+
+/// Read every `stride`th value of a channel starting from the first value. Within
+/// contiguous data with large enough gaps between wanted values, only the wanted values
+/// are read. Other data, such as interleaved data, is read in full and values skipped.
+pub(crate) fn read_strided<R: Read + Seek, T: NativeType>(
+    channel: &Channel<R>,
+    stride: usize,
+) -> Result<Vec<T>> {
+    if stride == 0 {
+        return Err(TdmsReadError::InvalidArgument(String::from(
+            "Stride must be greater than zero",
+        )));
+    }
+    channel.check_native_type::<T>()?;
+    let stride = stride as u64;
+    let length = channel.len();
+    let mut values = Vec::with_capacity(length.div_ceil(stride) as usize);
+    let extents = channel
+        .file
+        .tdms_reader
+        .channel_data_extents(channel.object_id)?;
+
+    let mut extent_start = 0;
+    for extent in extents.iter() {
+        let extent_end = (extent_start + extent.values).min(length);
+        // Index of the first wanted value at or after the start of the extent
+        let first = extent_start.div_ceil(stride) * stride;
+        if first < extent_end {
+            if can_seek_values(extent, stride, T::tds_type().size()) {
+                read_values_at::<R, T>(
+                    channel,
+                    extent,
+                    first - extent_start,
+                    extent_end - extent_start,
+                    stride,
+                    &mut values,
+                )?;
+            } else {
+                read_skipping(channel, first, extent_end, stride, &mut values)?;
+            }
+        }
+        extent_start = extent_end;
+    }
+    // Values without extents, such as DAQmx data, are read by the usual read path,
+    // which reports any error
+    if extent_start < length {
+        let first = extent_start.div_ceil(stride) * stride;
+        read_skipping(channel, first, length, stride, &mut values)?;
+    }
+    Ok(values)
+}
+
+/// Whether wanted values can be read individually from an extent, which requires
+/// contiguous values of a fixed size with large enough gaps between them
+fn can_seek_values(extent: &DataExtent, stride: u64, value_size: Option<usize>) -> bool {
+    match (extent.layout, value_size) {
+        (Layout::Contiguous, Some(value_size)) if value_size > 0 => {
+            extent.data_type.size() == Some(value_size)
+                && (stride - 1) * value_size as u64 >= MIN_SEEK_GAP
+        }
+        _ => false,
+    }
+}
+
+/// Read the values at indices `first`, `first + stride`, ... before `end` within an
+/// extent by seeking to each one
+fn read_values_at<R: Read + Seek, T: NativeType>(
+    channel: &Channel<R>,
+    extent: &DataExtent,
+    first: u64,
+    end: u64,
+    stride: u64,
+    values: &mut Vec<T>,
+) -> Result<()> {
+    let value_size = extent.byte_len / extent.values;
+    let reader = &mut *channel.file.file_reader.borrow_mut();
+    let mut value = [T::default()];
+    let mut index = first;
+    while index < end {
+        reader.seek(SeekFrom::Start(extent.file_offset + index * value_size))?;
+        // Read directly from the underlying reader, so that only the value's bytes
+        // are read rather than filling the whole buffer
+        if extent.big_endian {
+            T::read_values::<_, BigEndian>(&mut value, reader.get_mut(), 1)?;
+        } else {
+            T::read_values::<_, LittleEndian>(&mut value, reader.get_mut(), 1)?;
+        }
+        values.push(value[0]);
+        index += stride;
+    }
+    Ok(())
+}
+
+/// Read all values from index `first` up to `end` in chunks, keeping every `stride`th value
+fn read_skipping<R: Read + Seek, T: NativeType>(
+    channel: &Channel<R>,
+    first: u64,
+    end: u64,
+    stride: u64,
+    values: &mut Vec<T>,
+) -> Result<()> {
+    let value_size = T::tds_type().size().unwrap_or(1);
+    // Whole multiples of the stride, so that each chunk starts at a wanted value
+    let chunk_strides = (channel.chunk_config().values(value_size) as u64 / stride).max(1);
+    let chunk_length = (chunk_strides * stride).min(end.saturating_sub(first)) as usize;
+    let mut buffer = channel.scratch().take::<T>(chunk_length);
+    let mut position = first;
+    while position < end {
+        let count = (end - position).min(buffer.len() as u64) as usize;
+        let values_read = channel.read_native(position, &mut buffer[..count])?;
+        if values_read == 0 {
+            break;
+        }
+        values.extend(buffer[..values_read].iter().step_by(stride as usize));
+        position += values_read as u64;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::writer::{TdmsWriter, WriteObject};
+    use crate::TdmsFile;
+    use std::io::Cursor;
+
+    #[test]
+    fn seek_only_with_large_gaps() {
+        let extent = |layout| DataExtent {
+            file_offset: 0,
+            byte_len: 8000,
+            values: 1000,
+            data_type: crate::types::TdsType::DoubleFloat,
+            layout,
+            big_endian: false,
+        };
+
+        assert!(can_seek_values(&extent(Layout::Contiguous), 1000, Some(8)));
+        assert!(!can_seek_values(&extent(Layout::Contiguous), 2, Some(8)));
+        assert!(!can_seek_values(
+            &extent(Layout::Interleaved { stride: 16 }),
+            1000,
+            Some(8)
+        ));
+    }
+
+    #[test]
+    fn read_every_nth_value() {
+        let values: Vec<i32> = (0..20).collect();
+        let mut writer = TdmsWriter::new(Vec::new());
+        for chunk in values.chunks(7) {
+            writer
+                .write_segment(&[WriteObject::channel("Group", "Channel")
+                    .with_data(chunk)
+                    .unwrap()])
+                .unwrap();
+        }
+        let tdms_file = TdmsFile::new(Cursor::new(writer.into_inner())).unwrap();
+        let group = tdms_file.group("Group").unwrap();
+        let channel = group.channel("Channel").unwrap();
+
+        for stride in 1..=21 {
+            let expected: Vec<i32> = values.iter().copied().step_by(stride).collect();
+            assert_eq!(
+                read_strided::<_, i32>(&channel, stride).unwrap(),
+                expected,
+                "stride {}",
+                stride
+            );
+        }
+        assert!(read_strided::<_, i32>(&channel, 0).is_err());
+        assert!(read_strided::<_, f64>(&channel, 2).is_err());
+    }
+}
@@ -0,0 +1,308 @@
+use crate::error::Result;
+use crate::properties::TdmsProperty;
+use crate::segment::TdmsSegment;
+use crate::tdms_reader::{SkippedSegment, TdmsWarning, TdmsWarningKind};
+use crate::types::TdsType;
+use crate::{Channel, TdmsFile};
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::io::{Read, Seek};
+
+/// An overview of a TDMS file, computed from its metadata without reading any data
+#[derive(Debug, Clone)]
+pub struct FileSummary {
+    /// Position of the end of the last segment read. This is the length of the file
+    /// unless data at the end of the file could not be read.
+    pub length: u64,
+
+    /// Number of segments read
+    pub segment_count: usize,
+
+    /// TDMS format version number of the first segment, or None if there are no segments
+    pub format_version: Option<u32>,
+
+    pub group_count: usize,
+
+    pub channel_count: usize,
+
+    /// Total number of values of all channels
+    pub total_values: u64,
+
+    /// Number of segments with the InterleavedData table of contents flag set
+    pub interleaved_segments: usize,
+
+    /// Number of segments with the BigEndian table of contents flag set
+    pub big_endian_segments: usize,
+
+    /// Paths of channels with both interleaved and contiguous data
+    pub mixed_layout_channels: Vec<String>,
+
+    /// Paths of channels with both big endian and little endian data
+    pub mixed_byte_order_channels: Vec<String>,
+
+    /// Earliest start time and latest end time of channels with waveform timing
+    /// and a start time, or None if there are no such channels with data
+    #[cfg(feature = "chrono")]
+    pub time_span: Option<(DateTime<Utc>, DateTime<Utc>)>,
+
+    /// Whether the final segment was not completely written
+    pub incomplete: bool,
+
+    /// Total number of values missing from channels because the file was truncated
+    pub truncated_values: u64,
+
+    /// Segments that could not be read and were skipped
+    pub skipped_segments: Vec<SkippedSegment>,
+
+    /// Non-fatal anomalies found when reading the file
+    pub read_warnings: Vec<TdmsWarning>,
+}
+
+impl FileSummary {
+    /// Descriptions of problems found while reading the file
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.incomplete {
+            warnings.push(String::from("The final segment was not completely written"));
+        }
+        if self.truncated_values > 0 {
+            warnings.push(format!(
+                "{} value(s) are missing because the file was truncated",
+                self.truncated_values
+            ));
+        }
+        for skipped in self.skipped_segments.iter() {
+            warnings.push(match skipped.resume_position {
+                Some(resume_position) => format!(
+                    "Skipped segment at position {} (resumed at {}): {}",
+                    skipped.position, resume_position, skipped.error
+                ),
+                None => format!(
+                    "Skipped data from position {} to end of file: {}",
+                    skipped.position, skipped.error
+                ),
+            });
+        }
+        // Truncated channels are already included in the total of truncated values
+        for warning in self
+            .read_warnings
+            .iter()
+            .filter(|warning| warning.kind != TdmsWarningKind::TruncatedChannel)
+        {
+            warnings.push(warning.to_string());
+        }
+        warnings
+    }
+}
+
+/// Metadata of a channel, owned so that it can be kept after the file is closed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelInfo {
+    /// Name of the channel, with any quotes unescaped
+    pub name: String,
+
+    /// Name of the group the channel belongs to, with any quotes unescaped
+    pub group_name: String,
+
+    /// TDMS path of the channel as it was written in the file
+    pub path: String,
+
+    /// Data type of the channel, or None if it has no data
+    pub data_type: Option<TdsType>,
+
+    /// Number of values in the channel
+    pub len: u64,
+
+    /// How many of the segments containing the channel's data are interleaved
+    pub interleaved: SegmentFlagUsage,
+
+    /// How many of the segments containing the channel's data are big endian
+    pub big_endian: SegmentFlagUsage,
+
+    pub properties: Vec<TdmsProperty>,
+}
+
+impl ChannelInfo {
+    pub(crate) fn new<R: Read + Seek>(channel: &Channel<R>) -> ChannelInfo {
+        ChannelInfo {
+            name: channel.name().to_string(),
+            group_name: channel.group_name().to_string(),
+            path: channel.path().to_string(),
+            data_type: channel.data_type(),
+            len: channel.len(),
+            interleaved: SegmentFlagUsage::from_segments(channel, TdmsSegment::interleaved),
+            big_endian: SegmentFlagUsage::from_segments(channel, TdmsSegment::big_endian),
+            properties: channel.properties().to_vec(),
+        }
+    }
+}
+
+/// Whether a table of contents flag is set in none, some or all of the segments
+/// containing a channel's data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SegmentFlagUsage {
+    /// The flag isn't set in any segment, or the channel has no data
+    None,
+
+    /// The flag is set in some segments but not others
+    Mixed,
+
+    /// The flag is set in every segment
+    All,
+}
+
+impl SegmentFlagUsage {
+    fn from_segments<R: Read + Seek>(
+        channel: &Channel<R>,
+        has_flag: fn(&TdmsSegment) -> bool,
+    ) -> SegmentFlagUsage {
+        let segments = channel.file.tdms_reader.channel_segments(channel.object_id);
+        let flagged = segments.iter().filter(|segment| has_flag(segment)).count();
+        if flagged == 0 {
+            SegmentFlagUsage::None
+        } else if flagged == segments.len() {
+            SegmentFlagUsage::All
+        } else {
+            SegmentFlagUsage::Mixed
+        }
+    }
+
+    /// Whether the flag is set in at least one segment
+    pub fn any(self) -> bool {
+        self != SegmentFlagUsage::None
+    }
+
+    /// Whether the flag is set in some segments but not others
+    pub fn is_mixed(self) -> bool {
+        self == SegmentFlagUsage::Mixed
+    }
+}
+
+impl fmt::Display for SegmentFlagUsage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            SegmentFlagUsage::None => "none",
+            SegmentFlagUsage::Mixed => "mixed",
+            SegmentFlagUsage::All => "all",
+        })
+    }
+}
+
+/// Summarize a TDMS file from its metadata
+pub fn file_summary<R: Read + Seek>(tdms_file: &TdmsFile<R>) -> Result<FileSummary> {
+    let segments = tdms_file.tdms_reader.segments();
+    let mut summary = FileSummary {
+        length: segments
+            .last()
+            .map_or(0, |segment| segment.next_segment_position),
+        segment_count: segments.len(),
+        format_version: segments.first().map(|segment| segment.version),
+        group_count: 0,
+        channel_count: 0,
+        total_values: 0,
+        interleaved_segments: segments
+            .iter()
+            .filter(|segment| segment.interleaved())
+            .count(),
+        big_endian_segments: segments
+            .iter()
+            .filter(|segment| segment.big_endian())
+            .count(),
+        mixed_layout_channels: Vec::new(),
+        mixed_byte_order_channels: Vec::new(),
+        #[cfg(feature = "chrono")]
+        time_span: None,
+        incomplete: segments.last().is_some_and(|segment| segment.incomplete),
+        truncated_values: 0,
+        skipped_segments: tdms_file.skipped_segments().to_vec(),
+        read_warnings: tdms_file.warnings().to_vec(),
+    };
+    for group in tdms_file.groups() {
+        summary.group_count += 1;
+        for channel in group.channels() {
+            summary.channel_count += 1;
+            let length = channel.len();
+            summary.total_values += length;
+            summary.truncated_values += channel.truncated_values()?;
+            if SegmentFlagUsage::from_segments(&channel, TdmsSegment::interleaved).is_mixed() {
+                summary
+                    .mixed_layout_channels
+                    .push(channel.path().to_string());
+            }
+            if SegmentFlagUsage::from_segments(&channel, TdmsSegment::big_endian).is_mixed() {
+                summary
+                    .mixed_byte_order_channels
+                    .push(channel.path().to_string());
+            }
+            #[cfg(feature = "chrono")]
+            add_time_span(&mut summary, &channel, length);
+        }
+    }
+    Ok(summary)
+}
+
+/// Extend the summary's time span to include a channel's values
+#[cfg(feature = "chrono")]
+fn add_time_span<R: Read + Seek>(summary: &mut FileSummary, channel: &Channel<R>, length: u64) {
+    if length == 0 {
+        return;
+    }
+    let timing = match channel.waveform_timing() {
+        Some(timing) => timing,
+        None => return,
+    };
+    if let (Some(start), Some(end)) = (timing.absolute_time(0), timing.absolute_time(length - 1)) {
+        let (start, end) = (start.min(end), start.max(end));
+        summary.time_span = Some(match summary.time_span {
+            Some((span_start, span_end)) => (span_start.min(start), span_end.max(end)),
+            None => (start, end),
+        });
+    }
+}
+
+impl fmt::Display for FileSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Length: {} bytes", self.length)?;
+        writeln!(f, "Segments: {}", self.segment_count)?;
+        if let Some(format_version) = self.format_version {
+            writeln!(f, "Format version: {}", format_version)?;
+        }
+        writeln!(f, "Groups: {}", self.group_count)?;
+        writeln!(f, "Channels: {}", self.channel_count)?;
+        writeln!(f, "Total values: {}", self.total_values)?;
+        writeln!(f, "Interleaved segments: {}", self.interleaved_segments)?;
+        writeln!(f, "Big endian segments: {}", self.big_endian_segments)?;
+        for path in self.mixed_layout_channels.iter() {
+            writeln!(f, "Mixed interleaved and contiguous data: {}", path)?;
+        }
+        for path in self.mixed_byte_order_channels.iter() {
+            writeln!(f, "Mixed big and little endian data: {}", path)?;
+        }
+        #[cfg(feature = "chrono")]
+        match self.time_span {
+            Some((start, end)) => writeln!(
+                f,
+                "Time span: {} to {} ({} s)",
+                start.to_rfc3339(),
+                end.to_rfc3339(),
+                duration_seconds(start, end)
+            )?,
+            None => writeln!(f, "Time span: -")?,
+        }
+        for warning in self.warnings() {
+            writeln!(f, "Warning: {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "chrono")]
+fn duration_seconds(start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+    let duration = end - start;
+    match duration.num_nanoseconds() {
+        Some(nanoseconds) => nanoseconds as f64 * 1e-9,
+        None => duration.num_milliseconds() as f64 * 1e-3,
+    }
+}
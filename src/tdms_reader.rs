@@ -1,75 +1,347 @@
-use crate::error::{Result, TdmsReadError};
+#[cfg(feature = "cache")]
+use crate::cache::{CachedMetadata, CachedRawDataIndex, CachedSegment, CachedSegmentObject};
+use crate::custom::CustomTypes;
+use crate::daqmx::{DaqmxScaler, LinearScale};
+use crate::error::{DetectedFormat, Result, TdmsReadError};
+use crate::inspect::{DataExtent, Layout};
 use crate::object_map::ObjectMap;
 use crate::object_path::{ObjectPath, ObjectPathCache, ObjectPathId};
-use crate::properties::TdmsProperty;
-use crate::segment::{RawDataIndex, RawDataIndexCache, SegmentObject, TdmsSegment};
+use crate::options::{ErrorPolicy, ReadOptions};
+use crate::properties::{read_value, PropertyNameCache, TdmsProperty, TdmsValue};
+use crate::segment::{
+    ObjectList, ObjectLists, RawDataIndex, RawDataIndexCache, SegmentObject, TdmsSegment,
+};
 use crate::toc::{TocFlag, TocMask};
 use crate::types::{read_string, ByteOrderExt, NativeType, TdsType};
-use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
-use id_arena::Arena;
-use std::collections::HashMap;
-use std::io::{Read, Seek, SeekFrom};
+use crate::waveform::WAVEFORM_PROPERTIES;
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
+use log::{debug, trace};
+use std::cmp::min;
+use std::fmt;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
 
 const RAW_DATA_INDEX_NO_DATA: u32 = 0xFFFFFFFF;
 const RAW_DATA_INDEX_MATCHES_PREVIOUS: u32 = 0x00000000;
 const FORMAT_CHANGING_SCALER: u32 = 0x00001269;
 const DIGITAL_LINE_SCALER: u32 = 0x0000126A;
 
-pub fn read_metadata<R: Read + Seek>(reader: &mut R) -> Result<TdmsReader> {
+const SEGMENT_TAG: [u8; 4] = [0x54, 0x44, 0x53, 0x6d];
+const LEAD_IN_LENGTH: u64 = 28;
+const INCOMPLETE_SEGMENT_OFFSET: u64 = 0xFFFFFFFFFFFFFFFF;
+
+/// Largest number of objects allocated for before a segment's objects have been read
+const OBJECT_CAPACITY_LIMIT: u32 = 1024;
+
+/// Segment version numbers of TDMS 1.0 and 2.0 files. The segment layout is
+/// the same for both versions, apart from DAQmx raw data which was added in 2.0.
+const VERSION_1_0: u32 = 4712;
+const VERSION_2_0: u32 = 4713;
+
+/// Largest number of bytes between the data of consecutive segments that are read
+/// over rather than seeked past when coalescing reads
+const MAX_COALESCED_GAP: u64 = 4096;
+
+/// Number of segments above which a file is reported as unusually fragmented
+const MANY_SEGMENTS_WARNING_COUNT: usize = 10_000;
+
+/// Number of bytes after the segment tag read to detect the format of a file that isn't TDMS
+const FORMAT_DETECTION_LENGTH: u64 = 256;
+
+/// Number of segments whose metadata is read into memory and parsed in parallel at once
+#[cfg(feature = "parallel")]
+const PARALLEL_BATCH_SIZE: usize = 4096;
+
+pub fn read_metadata<R: Read + Seek>(reader: &mut R, options: &ReadOptions) -> Result<TdmsReader> {
+    if reader.seek(SeekFrom::End(0))? == 0 {
+        return Err(TdmsReadError::NotATdmsFile(DetectedFormat::Empty));
+    }
+    #[cfg(feature = "parallel")]
+    if options.get_parallel_metadata() {
+        let mut tdms_reader = TdmsReader::new();
+        match tdms_reader.read_segments_parallel(reader, options) {
+            Ok(()) => return Ok(tdms_reader),
+            Err(e) => debug!(
+                "Parallel metadata read failed, reading sequentially instead: {}",
+                e
+            ),
+        }
+    }
     let mut tdms_reader = TdmsReader::new();
-    match tdms_reader.read_segments(reader) {
+    match tdms_reader.read_segments(reader, 0, options, false) {
         Ok(()) => Ok(tdms_reader),
         Err(e) => Err(e),
     }
 }
 
+/// A segment that could not be read and was skipped when using `ErrorPolicy::Skip`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkippedSegment {
+    /// Position in the file of the start of the segment
+    pub position: u64,
+
+    /// Position in the file where reading resumed, or None if no further segments were found
+    pub resume_position: Option<u64>,
+
+    /// Description of the error that caused the segment to be skipped
+    pub error: String,
+}
+
+/// The kind of a non-fatal anomaly found when reading a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TdmsWarningKind {
+    /// A property was written again in a later segment with a different data type
+    PropertyTypeChanged,
+    /// A channel is missing values because the final segment was truncated
+    TruncatedChannel,
+    /// A segment's table of contents has bits set that don't correspond to any known flag
+    UnknownTocFlags,
+    /// The file has an unusually large number of segments, which makes reading slow
+    ManySegments,
+    /// A segment's DAQmxRawData table of contents flag doesn't match whether its objects
+    /// have DAQmx raw data indexes
+    DaqmxFlagMismatch,
+    /// The root object has a raw data index. The root object can't have data, so its
+    /// data is skipped when reading channels and can't be read.
+    RootObjectData,
+}
+
+/// A non-fatal anomaly found when reading a file, which didn't prevent it being read
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TdmsWarning {
+    pub kind: TdmsWarningKind,
+
+    /// Description of the anomaly
+    pub message: String,
+
+    /// Position in the file of the start of the segment where the anomaly was found,
+    /// or None if it applies to the whole file
+    pub position: Option<u64>,
+}
+
+impl fmt::Display for TdmsWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{} (segment at position {})", self.message, position),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
 pub struct ChannelDataIndex {
     pub number_of_values: u64,
     pub data_type: TdsType,
+    segments: Vec<ChannelSegmentRun>,
+}
+
+/// Location of a channel's data within a single segment
+struct ChannelSegment {
+    /// Index of the segment in the reader's segment list
+    segment_index: usize,
+
+    /// Index of the first value in this segment within the whole channel
+    start_value: u64,
+
+    /// Number of whole values for this channel in the segment, which excludes any
+    /// values cut off by the end of a truncated final segment
+    number_of_values: u64,
+}
+
+/// Location of a channel's data within a run of consecutive segments that each have
+/// the same number of values for the channel. Files written by streaming data usually
+/// have very many segments with the same layout, so these are stored as a single run.
+struct ChannelSegmentRun {
+    /// Index of the first segment of the run in the reader's segment list
+    first_segment: usize,
+
+    /// Number of segments in the run
+    segment_count: usize,
+
+    /// Index of the first value in the run within the whole channel
+    start_value: u64,
+
+    /// Number of values for this channel in each segment of the run
+    values_per_segment: u64,
+}
+
+impl ChannelSegmentRun {
+    fn end_value(&self) -> u64 {
+        self.start_value + self.segment_count as u64 * self.values_per_segment
+    }
+
+    fn segment(&self, index_in_run: usize) -> ChannelSegment {
+        ChannelSegment {
+            segment_index: self.first_segment + index_in_run,
+            start_value: self.start_value + index_in_run as u64 * self.values_per_segment,
+            number_of_values: self.values_per_segment,
+        }
+    }
 }
 
 impl ChannelDataIndex {
-    fn from_segment_index(index: &RawDataIndex) -> ChannelDataIndex {
-        ChannelDataIndex {
+    fn from_segment_index(
+        index: &RawDataIndex,
+        segment_index: usize,
+        number_of_values: u64,
+    ) -> ChannelDataIndex {
+        let mut channel_data_index = ChannelDataIndex {
             data_type: index.data_type,
-            number_of_values: index.number_of_values,
+            number_of_values: 0,
+            segments: Vec::new(),
+        };
+        channel_data_index.add_segment(segment_index, number_of_values);
+        channel_data_index
+    }
+
+    /// Index of the channel's first value in a segment within all of its values,
+    /// or None if the channel has no values in the segment
+    pub fn segment_start_value(&self, segment_index: usize) -> Option<u64> {
+        self.segment_values(segment_index)
+            .map(|(start_value, _)| start_value)
+    }
+
+    /// Index of the channel's first value in the segment at `segment_index` and the
+    /// number of the channel's values in the segment, or None if the segment has no
+    /// values for the channel
+    pub fn segment_values(&self, segment_index: usize) -> Option<(u64, u64)> {
+        let run_index = self
+            .segments
+            .partition_point(|run| run.first_segment + run.segment_count <= segment_index);
+        self.segments
+            .get(run_index)
+            .filter(|run| run.first_segment <= segment_index)
+            .map(|run| {
+                let segment = run.segment(segment_index - run.first_segment);
+                (segment.start_value, segment.number_of_values)
+            })
+    }
+
+    /// Index of the segment containing the channel's value at `value_index`, and the
+    /// index of the value within that segment
+    pub fn value_segment(&self, value_index: u64) -> Option<(usize, u64)> {
+        self.segments_from(value_index)
+            .next()
+            .filter(|segment| {
+                segment.start_value <= value_index
+                    && value_index < segment.start_value + segment.number_of_values
+            })
+            .map(|segment| (segment.segment_index, value_index - segment.start_value))
+    }
+
+    /// Index of the channel's first value in the first segment at or after
+    /// `segment_index` with values for the channel, or the number of values in the
+    /// channel if no later segment has values
+    pub fn start_value_from_segment(&self, segment_index: usize) -> u64 {
+        let run_index = self
+            .segments
+            .partition_point(|run| run.first_segment + run.segment_count <= segment_index);
+        match self.segments.get(run_index) {
+            Some(run) if run.first_segment < segment_index => {
+                run.segment(segment_index - run.first_segment).start_value
+            }
+            Some(run) => run.start_value,
+            None => self.number_of_values,
+        }
+    }
+
+    /// Segments containing the channel's data, starting from the segment
+    /// containing the value at index `offset`
+    fn segments_from(&self, offset: u64) -> impl Iterator<Item = ChannelSegment> + '_ {
+        let first_run = self
+            .segments
+            .partition_point(|run| run.end_value() <= offset);
+        self.segments[first_run..].iter().flat_map(move |run| {
+            let first_in_run = match offset.checked_sub(run.start_value) {
+                Some(run_offset) => (run_offset / run.values_per_segment) as usize,
+                None => 0,
+            };
+            (first_in_run..run.segment_count).map(move |index| run.segment(index))
+        })
+    }
+
+    fn add_segment(&mut self, segment_index: usize, number_of_values: u64) {
+        if number_of_values > 0 {
+            match self.segments.last_mut() {
+                Some(run)
+                    if run.first_segment + run.segment_count == segment_index
+                        && run.values_per_segment == number_of_values =>
+                {
+                    run.segment_count += 1;
+                }
+                _ => self.segments.push(ChannelSegmentRun {
+                    first_segment: segment_index,
+                    segment_count: 1,
+                    start_value: self.number_of_values,
+                    values_per_segment: number_of_values,
+                }),
+            }
+            self.number_of_values += number_of_values;
         }
     }
 
-    fn update_with_segment_index(&mut self, index: &RawDataIndex) -> Result<()> {
-        // We have data in this segment for an object that already had data in a
-        // previous segment, check the raw data index is compatible.
+    /// Check that a raw data index for data in a later segment is compatible with the
+    /// channel's data in previous segments
+    fn check_compatible(&self, index: &RawDataIndex) -> Result<()> {
         if index.data_type != self.data_type {
-            return Err(TdmsReadError::TdmsError(format!(
+            return Err(TdmsReadError::InvalidData(format!(
                 "Data type {:?} does not match existing data type {:?}",
                 index.data_type, self.data_type
             )));
         }
-        self.number_of_values += index.number_of_values;
         Ok(())
     }
 }
 
 type ChannelDataIndexMap = ObjectMap<ChannelDataIndex>;
 
+/// Waveform timing properties written in a segment's metadata for an object
+pub struct WaveformPropertyUpdate {
+    /// Index of the segment in the reader's segment list
+    pub segment_index: usize,
+
+    pub properties: Vec<TdmsProperty>,
+}
+
 pub struct TdmsReader {
-    pub properties: HashMap<ObjectPathId, Vec<TdmsProperty>>,
+    properties: ObjectMap<Vec<TdmsProperty>>,
+    /// Waveform timing properties of each object in every segment they were written in,
+    /// as only the latest values are kept in the properties
+    waveform_property_updates: ObjectMap<Vec<WaveformPropertyUpdate>>,
+    property_names: PropertyNameCache,
     object_paths: ObjectPathCache,
-    data_indexes: Arena<RawDataIndex>,
+    /// Latest raw data index of every object that has had one, used only to resolve
+    /// indexes that match the previous index. An object having a cached index doesn't
+    /// mean it has data in later segments, which is decided by each segment's object list.
     raw_data_index_cache: RawDataIndexCache,
+    /// Number of segments in which each object's raw data index was written in full
+    /// rather than reusing the previous index
+    new_raw_data_index_counts: ObjectMap<u64>,
+    object_lists: ObjectLists,
     segments: Vec<TdmsSegment>,
     channel_data_index_map: ChannelDataIndexMap,
+    skipped_segments: Vec<SkippedSegment>,
+    warnings: Vec<TdmsWarning>,
+    /// Position in the file after the last segment read
+    read_position: u64,
 }
 
 impl TdmsReader {
     fn new() -> TdmsReader {
         TdmsReader {
-            properties: HashMap::new(),
+            properties: ObjectMap::new(),
+            waveform_property_updates: ObjectMap::new(),
+            property_names: PropertyNameCache::new(),
             object_paths: ObjectPathCache::new(),
-            data_indexes: Arena::<RawDataIndex>::new(),
             raw_data_index_cache: RawDataIndexCache::new(),
+            new_raw_data_index_counts: ObjectMap::new(),
+            object_lists: ObjectLists::new(),
             segments: Vec::new(),
             channel_data_index_map: ChannelDataIndexMap::new(),
+            skipped_segments: Vec::new(),
+            warnings: Vec::new(),
+            read_position: 0,
         }
     }
 
@@ -81,6 +353,11 @@ impl TdmsReader {
         self.object_paths.get_path(object_path_id)
     }
 
+    /// Get the path of an object as it was written in the file
+    pub fn get_object_path_string(&self, object_path_id: ObjectPathId) -> Option<&str> {
+        self.object_paths.get_path_string(object_path_id)
+    }
+
     pub fn objects(&self) -> impl Iterator<Item = (ObjectPathId, &ObjectPath)> {
         self.object_paths.objects()
     }
@@ -89,47 +366,470 @@ impl TdmsReader {
         self.channel_data_index_map.get(object_id)
     }
 
+    /// Number of segments in which an object's raw data index was written in full,
+    /// rather than reusing the index from a previous segment
+    pub fn new_raw_data_index_count(&self, object_id: ObjectPathId) -> u64 {
+        self.new_raw_data_index_counts
+            .get(object_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn segments(&self) -> &[TdmsSegment] {
+        &self.segments
+    }
+
+    /// The objects in a segment
+    pub fn object_list(&self, segment: &TdmsSegment) -> &ObjectList {
+        &self.object_lists[segment.object_list]
+    }
+
+    pub fn skipped_segments(&self) -> &[SkippedSegment] {
+        &self.skipped_segments
+    }
+
+    pub fn warnings(&self) -> &[TdmsWarning] {
+        &self.warnings
+    }
+
+    /// Number of values declared for a channel in the final segment but missing
+    /// as the segment was truncated
+    pub fn get_truncated_value_count(&self, object_id: ObjectPathId) -> Result<u64> {
+        match self.segments.last() {
+            Some(segment) if segment.incomplete => {
+                segment.channel_truncated_count(object_id, self.object_list(segment))
+            }
+            _ => Ok(0),
+        }
+    }
+
+    pub fn get_properties(&self, object_id: ObjectPathId) -> &[TdmsProperty] {
+        match self.properties.get(object_id) {
+            Some(properties) => properties,
+            None => &[],
+        }
+    }
+
+    /// Waveform timing properties of an object in each segment they were written in,
+    /// in segment order
+    pub fn get_waveform_property_updates(
+        &self,
+        object_id: ObjectPathId,
+    ) -> &[WaveformPropertyUpdate] {
+        match self.waveform_property_updates.get(object_id) {
+            Some(updates) => updates,
+            None => &[],
+        }
+    }
+
+    /// Read segments written since the metadata was last read, for following a file that
+    /// is still being written. Segments whose lead in or metadata haven't been completely
+    /// written yet are left to be read by a later refresh. If the final segment was
+    /// incomplete when last read, all metadata is read again as the segment may have
+    /// since been completed. Returns whether any new data was found.
+    pub fn refresh<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        options: &ReadOptions,
+    ) -> Result<bool> {
+        let file_length = reader.seek(SeekFrom::End(0))?;
+        if file_length == self.read_position {
+            return Ok(false);
+        }
+        if file_length < self.read_position {
+            return Err(TdmsReadError::InvalidData(format!(
+                "File length {} is less than the length {} when last read",
+                file_length, self.read_position
+            )));
+        }
+        if self
+            .segments
+            .last()
+            .is_some_and(|segment| segment.incomplete)
+        {
+            let mut tdms_reader = TdmsReader::new();
+            tdms_reader.read_segments(reader, 0, options, true)?;
+            *self = tdms_reader;
+            return Ok(true);
+        }
+        let segment_count = self.segments.len();
+        let position = self.read_position;
+        self.read_segments(reader, position, options, true)?;
+        Ok(self.segments.len() > segment_count)
+    }
+
+    /// Read channel data starting at the value with index `offset` into the buffer,
+    /// returning the number of values read.
     pub fn read_channel_data<R: Read + Seek, T: NativeType>(
         &self,
         reader: &mut R,
         channel_id: ObjectPathId,
+        offset: u64,
         buffer: &mut [T],
-    ) -> Result<()> {
-        let mut offset = 0;
-        for segment in self.segments.iter() {
-            if segment
-                .objects
-                .iter()
-                .any(|o| o.object_id == channel_id && o.raw_data_index.is_some())
-            {
-                offset += segment.read_channel_data(
+    ) -> Result<usize> {
+        trace_span!("read_channel_data", channel = ?channel_id, offset, length = buffer.len());
+        self.read_segments_data(
+            channel_id,
+            offset,
+            buffer.len(),
+            |segment, skip, segment_values, values_read| {
+                segment.read_channel_data(
+                    reader,
+                    channel_id,
+                    skip,
+                    segment_values,
+                    &mut buffer[values_read..],
+                    self.object_list(segment),
+                )
+            },
+        )
+    }
+
+    /// Read channel data like `read_channel_data`, but read the contiguous data of
+    /// consecutive segments that are close together in the file with a single read of up
+    /// to `max_read_size` bytes rather than seeking to each segment's data. This avoids
+    /// making a read for every segment of files with many small segments.
+    /// Interleaved data and segments with more than `max_read_size` bytes to read are
+    /// read directly into the buffer.
+    pub fn read_channel_data_coalesced<R: Read + Seek, T: NativeType>(
+        &self,
+        reader: &mut R,
+        channel_id: ObjectPathId,
+        offset: u64,
+        buffer: &mut [T],
+        max_read_size: u64,
+        scratch: &mut Vec<u8>,
+    ) -> Result<usize> {
+        trace_span!("read_channel_data_coalesced", channel = ?channel_id, offset, length = buffer.len());
+        let channel_data_index = match self.channel_data_index_map.get(channel_id) {
+            Some(channel_data_index) => channel_data_index,
+            None => return Ok(0),
+        };
+        let mut block = CoalescedBlock::new();
+        let mut values_read = 0;
+        for channel_segment in channel_data_index.segments_from(offset) {
+            let values_wanted = buffer.len() - values_read - block.values;
+            if values_wanted == 0 {
+                break;
+            }
+            let skip = offset + (values_read + block.values) as u64 - channel_segment.start_value;
+            let segment = &self.segments[channel_segment.segment_index];
+            let objects = self.object_list(segment);
+            let extents =
+                segment.channel_extents(channel_id, channel_segment.number_of_values, objects)?;
+            let pieces = contiguous_pieces(&extents, skip, values_wanted as u64);
+            let piece_bytes: u64 = pieces.iter().map(|piece| piece.byte_len).sum();
+            if pieces.is_empty() || piece_bytes > max_read_size {
+                values_read += block.read(reader, &mut buffer[values_read..], scratch)?;
+                values_read += segment.read_channel_data(
                     reader,
                     channel_id,
-                    &mut buffer[offset..],
-                    &self.data_indexes,
+                    skip,
+                    channel_segment.number_of_values,
+                    &mut buffer[values_read..],
+                    objects,
                 )?;
+                continue;
+            }
+            for piece in pieces {
+                if !block.can_extend(&piece, max_read_size) {
+                    values_read += block.read(reader, &mut buffer[values_read..], scratch)?;
+                }
+                block.push(piece);
             }
         }
-        Ok(())
+        values_read += block.read(reader, &mut buffer[values_read..], scratch)?;
+        Ok(values_read)
     }
 
-    fn read_segments<R: Read + Seek>(&mut self, reader: &mut R) -> Result<()> {
-        let mut object_merger = ObjectMerger::new();
-        loop {
-            let position = reader.seek(SeekFrom::Current(0))?;
-            match self.read_segment(reader, position, &mut object_merger) {
-                Err(e) => return Err(e),
-                Ok(None) => {
-                    // Reached end of file
-                    break;
+    /// Read string channel data starting at the value with index `offset` into the buffer,
+    /// returning the number of values read.
+    pub fn read_channel_strings<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        channel_id: ObjectPathId,
+        offset: u64,
+        buffer: &mut [String],
+    ) -> Result<usize> {
+        trace_span!("read_channel_strings", channel = ?channel_id, offset, length = buffer.len());
+        self.read_segments_data(
+            channel_id,
+            offset,
+            buffer.len(),
+            |segment, skip, segment_values, values_read| {
+                segment.read_channel_strings(
+                    reader,
+                    channel_id,
+                    skip,
+                    segment_values,
+                    &mut buffer[values_read..],
+                    self.object_list(segment),
+                )
+            },
+        )
+    }
+
+    /// Read DAQmx raw data of a channel starting at the value with index `offset` into
+    /// the buffer, scaling raw values with the scale returned by `scale` for the scaler
+    /// of each segment. Returns the number of values read.
+    pub fn read_channel_daqmx_data<R: Read + Seek, S>(
+        &self,
+        reader: &mut R,
+        channel_id: ObjectPathId,
+        offset: u64,
+        buffer: &mut [f64],
+        scale: S,
+    ) -> Result<usize>
+    where
+        S: Fn(&DaqmxScaler) -> Result<LinearScale>,
+    {
+        trace_span!("read_channel_daqmx_data", channel = ?channel_id, offset, length = buffer.len());
+        self.read_segments_data(
+            channel_id,
+            offset,
+            buffer.len(),
+            |segment, skip, segment_values, values_read| {
+                segment.read_daqmx_data(
+                    reader,
+                    channel_id,
+                    skip,
+                    segment_values,
+                    &mut buffer[values_read..],
+                    self.object_list(segment),
+                    &scale,
+                )
+            },
+        )
+    }
+
+    /// Pass all raw bytes of a channel's data to `consume` in order, without decoding
+    /// values, reading at most about `read_size` bytes at a time into `buffer`.
+    /// Returns the total number of bytes passed.
+    pub fn read_channel_bytes<R: Read + Seek, F: FnMut(&[u8])>(
+        &self,
+        reader: &mut R,
+        channel_id: ObjectPathId,
+        read_size: usize,
+        buffer: &mut Vec<u8>,
+        mut consume: F,
+    ) -> Result<u64> {
+        trace_span!("read_channel_bytes", channel = ?channel_id);
+        let channel_data_index = match self.channel_data_index_map.get(channel_id) {
+            Some(channel_data_index) => channel_data_index,
+            None => return Ok(0),
+        };
+        let mut bytes_read = 0;
+        for channel_segment in channel_data_index.segments_from(0) {
+            bytes_read += self.read_channel_segment_bytes(
+                reader,
+                channel_id,
+                channel_segment.segment_index,
+                channel_segment.number_of_values,
+                read_size,
+                buffer,
+                &mut consume,
+            )?;
+        }
+        Ok(bytes_read)
+    }
+
+    /// Pass the raw bytes of a channel's `segment_values` values in the segment at
+    /// `segment_index` to `consume`, as with `read_channel_bytes`
+    #[allow(clippy::too_many_arguments)]
+    pub fn read_channel_segment_bytes<R: Read + Seek, F: FnMut(&[u8])>(
+        &self,
+        reader: &mut R,
+        channel_id: ObjectPathId,
+        segment_index: usize,
+        segment_values: u64,
+        read_size: usize,
+        buffer: &mut Vec<u8>,
+        consume: &mut F,
+    ) -> Result<u64> {
+        let segment = &self.segments[segment_index];
+        segment.read_channel_bytes(
+            reader,
+            channel_id,
+            segment_values,
+            self.object_list(segment),
+            read_size,
+            buffer,
+            consume,
+        )
+    }
+
+    /// Ranges of bytes containing a channel's raw values, in file order. Adjacent ranges
+    /// that continue on from each other are merged, except for string data where each
+    /// extent has its own offset table.
+    pub fn channel_data_extents(&self, channel_id: ObjectPathId) -> Result<Vec<DataExtent>> {
+        let channel_data_index = match self.channel_data_index_map.get(channel_id) {
+            Some(channel_data_index) => channel_data_index,
+            None => return Ok(Vec::new()),
+        };
+        let mut extents: Vec<DataExtent> = Vec::new();
+        for channel_segment in channel_data_index.segments_from(0) {
+            let segment = &self.segments[channel_segment.segment_index];
+            let segment_extents = segment.channel_extents(
+                channel_id,
+                channel_segment.number_of_values,
+                self.object_list(segment),
+            )?;
+            for extent in segment_extents {
+                let merged = match extents.last_mut() {
+                    Some(last) => last.try_merge(&extent),
+                    None => false,
+                };
+                if !merged {
+                    extents.push(extent);
+                }
+            }
+        }
+        Ok(extents)
+    }
+
+    /// The segments containing values of a channel, in order
+    pub fn channel_segments(&self, channel_id: ObjectPathId) -> Vec<&TdmsSegment> {
+        match self.channel_data_index_map.get(channel_id) {
+            Some(channel_data_index) => channel_data_index
+                .segments_from(0)
+                .map(|channel_segment| &self.segments[channel_segment.segment_index])
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Positions and lengths of the raw data of the segments containing values of a
+    /// channel from index `offset` up to `offset + length`, with adjacent segments merged.
+    /// This includes the data of other objects in the segments.
+    pub fn channel_data_ranges(
+        &self,
+        channel_id: ObjectPathId,
+        offset: u64,
+        length: u64,
+    ) -> Vec<(u64, u64)> {
+        let channel_data_index = match self.channel_data_index_map.get(channel_id) {
+            Some(channel_data_index) => channel_data_index,
+            None => return Vec::new(),
+        };
+        let end = offset.saturating_add(length);
+        let mut ranges: Vec<(u64, u64)> = Vec::new();
+        for channel_segment in channel_data_index.segments_from(offset) {
+            if channel_segment.start_value >= end {
+                break;
+            }
+            let segment = &self.segments[channel_segment.segment_index];
+            let (position, length) = (segment.data_position(), segment.data_length());
+            match ranges.last_mut() {
+                Some(last) if last.0 + last.1 == segment.position => {
+                    last.1 = position + length - last.0;
                 }
-                Ok(Some(segment)) => {
-                    // Seek to the start of the next segment
-                    reader.seek(SeekFrom::Start(segment.next_segment_position))?;
+                _ => ranges.push((position, length)),
+            }
+        }
+        ranges
+    }
+
+    /// Call `read_segment` for each segment containing data for the channel, starting
+    /// from the segment containing the value at index `offset`, until `length` values
+    /// have been read. The closure is passed the segment, the number of values to skip
+    /// within the segment, the number of values for the channel in the segment and the
+    /// number of values read so far.
+    fn read_segments_data<F>(
+        &self,
+        channel_id: ObjectPathId,
+        offset: u64,
+        length: usize,
+        mut read_segment: F,
+    ) -> Result<usize>
+    where
+        F: FnMut(&TdmsSegment, u64, u64, usize) -> Result<usize>,
+    {
+        let channel_data_index = match self.channel_data_index_map.get(channel_id) {
+            Some(channel_data_index) => channel_data_index,
+            None => return Ok(0),
+        };
+        trace!(
+            "Reading {} values of object {:?} from offset {}",
+            length,
+            channel_id,
+            offset
+        );
+        let mut values_read = 0;
+        for channel_segment in channel_data_index.segments_from(offset) {
+            if values_read >= length {
+                break;
+            }
+            let skip = offset + values_read as u64 - channel_segment.start_value;
+            let segment = &self.segments[channel_segment.segment_index];
+            values_read +=
+                read_segment(segment, skip, channel_segment.number_of_values, values_read)?;
+        }
+        Ok(values_read)
+    }
+
+    /// Read segments starting at the given position until the end of the file. When
+    /// `following` a file that is still being written, reading stops at a segment
+    /// whose lead in or metadata extends beyond the end of the file.
+    fn read_segments<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        start_position: u64,
+        options: &ReadOptions,
+        following: bool,
+    ) -> Result<()> {
+        debug_span!("read_segments", start_position, following);
+        let mut object_merger = ObjectMerger::new();
+        let file_length = reader.seek(SeekFrom::End(0))?;
+        let mut position = reader.seek(SeekFrom::Start(start_position))?;
+        let segment_count = self.segments.len();
+        while position < file_length {
+            if following && !metadata_written(reader, position, file_length)? {
+                break;
+            }
+            match self.read_segment(reader, position, file_length, options, &mut object_merger) {
+                Ok(segment) => {
+                    position = segment.next_segment_position;
                     self.segments.push(segment);
                 }
+                Err(e) => match options.get_error_policy() {
+                    ErrorPolicy::Fail => return Err(e),
+                    // Skipping can't help read a file in another format, but can
+                    // recover a TDMS file that starts with invalid data
+                    ErrorPolicy::Skip
+                        if matches!(e, TdmsReadError::NotATdmsFile(format)
+                            if format != DetectedFormat::Unknown) =>
+                    {
+                        return Err(e)
+                    }
+                    ErrorPolicy::Skip => {
+                        let resume_position = find_next_segment(reader, position, file_length)?;
+                        debug!(
+                            "Skipping segment at position {}, resuming at {:?}: {}",
+                            position, resume_position, e
+                        );
+                        self.skipped_segments.push(SkippedSegment {
+                            position,
+                            resume_position,
+                            error: e.to_string(),
+                        });
+                        match resume_position {
+                            Some(resume_position) => position = resume_position,
+                            None => break,
+                        }
+                    }
+                },
             }
+            reader.seek(SeekFrom::Start(position))?;
         }
+        self.read_position = position.min(file_length);
+        self.shrink_properties();
+        self.update_file_warnings()?;
+        debug!(
+            "Read {} segments from position {} to {}",
+            self.segments.len() - segment_count,
+            start_position,
+            self.read_position
+        );
         Ok(())
     }
 
@@ -137,148 +837,1095 @@ impl TdmsReader {
         &mut self,
         reader: &mut R,
         position: u64,
+        file_length: u64,
+        options: &ReadOptions,
         object_merger: &mut ObjectMerger,
-    ) -> Result<Option<TdmsSegment>> {
-        let mut header_bytes = [0u8; 4];
-        let mut bytes_read = 0;
-        while bytes_read < 4 {
-            match reader.read(&mut header_bytes[bytes_read..])? {
-                0 => return Ok(None),
-                n => bytes_read += n,
+    ) -> Result<TdmsSegment> {
+        trace_span!("read_segment", position);
+        let lead_in = LeadIn::read(reader, position, file_length)?;
+        lead_in.check_toc_flags(options)?;
+        let custom_types = options.get_custom_types();
+        let objects = if lead_in.toc_mask.has_flag(TocFlag::MetaData) {
+            let objects = if lead_in.toc_mask.has_flag(TocFlag::BigEndian) {
+                read_object_metadata::<R, BigEndian>(reader, position, custom_types)?
+            } else {
+                read_object_metadata::<R, LittleEndian>(reader, position, custom_types)?
+            };
+            Some(objects)
+        } else {
+            None
+        };
+        lead_in.check_positions(file_length)?;
+        let checkpoint = self.checkpoint();
+        let segment = self.add_segment(&lead_in, objects, object_merger);
+        if segment.is_err() {
+            self.restore(checkpoint);
+        }
+        segment
+    }
+
+    /// Add a segment's objects to the reader and update the channel data indexes,
+    /// returning the segment to add to the segment list. Properties, raw data indexes and
+    /// warnings are only added once the segment has been validated, but object paths and
+    /// object lists may have been added if this fails, and should be removed with `restore`.
+    fn add_segment(
+        &mut self,
+        lead_in: &LeadIn,
+        objects: Option<Vec<ObjectMetadata>>,
+        object_merger: &mut ObjectMerger,
+    ) -> Result<TdmsSegment> {
+        let segment_objects = match &objects {
+            Some(objects) => Some(self.resolve_segment_objects(objects)?),
+            None => None,
+        };
+        let object_ids: Vec<ObjectPathId> = segment_objects
+            .iter()
+            .flatten()
+            .map(|object| object.object_id)
+            .collect();
+        let segment = self.create_segment(lead_in, segment_objects, object_merger)?;
+        self.update_data_indexes(self.segments.len(), &segment)?;
+        if let Some(objects) = objects {
+            self.add_object_metadata(objects, &object_ids, lead_in.position);
+        }
+        self.add_segment_warnings(lead_in, &segment);
+        Ok(segment)
+    }
+
+    /// Record the numbers of object paths and object lists, which can grow before a
+    /// segment is validated
+    fn checkpoint(&self) -> ReaderCheckpoint {
+        ReaderCheckpoint {
+            object_paths: self.object_paths.len(),
+            object_lists: self.object_lists.len(),
+        }
+    }
+
+    /// Remove metadata added since a checkpoint by a segment that couldn't be added
+    fn restore(&mut self, checkpoint: ReaderCheckpoint) {
+        self.object_paths.truncate(checkpoint.object_paths);
+        self.object_lists.truncate(checkpoint.object_lists);
+    }
+
+    /// Create a segment from its lead in and the objects in its metadata, or None if
+    /// the segment has no metadata. Fails if the total data size of the objects overflows.
+    fn create_segment(
+        &mut self,
+        lead_in: &LeadIn,
+        objects: Option<Vec<SegmentObject>>,
+        object_merger: &mut ObjectMerger,
+    ) -> Result<TdmsSegment> {
+        let toc_mask = lead_in.toc_mask;
+        let new_list = |objects| {
+            ObjectList::new(objects).ok_or_else(|| {
+                TdmsReadError::InvalidData(format!(
+                    "Total data size of the objects in segment at position {} is too large",
+                    lead_in.position
+                ))
+            })
+        };
+        let previous_list = self.segments.last().map(|segment| segment.object_list);
+        let object_list = match (objects, previous_list) {
+            (Some(this_segment_objects), _) => {
+                let segment_objects = if toc_mask.has_flag(TocFlag::NewObjList) {
+                    this_segment_objects
+                } else {
+                    // Not a new object list so merge with previous segment objects
+                    let prev_objs = previous_list.map(|id| self.object_lists[id].objects());
+                    object_merger.merge_objects(prev_objs, this_segment_objects)
+                };
+                // Share the previous segment's list if the objects haven't changed
+                match previous_list {
+                    Some(id) if self.object_lists[id].objects() == segment_objects.as_slice() => id,
+                    _ => self.object_lists.push(new_list(segment_objects)?),
+                }
+            }
+            // No meta data in this segment, re-use metadata from the previous segment
+            (None, Some(id)) => id,
+            (None, None) => self.object_lists.push(new_list(Vec::new())?),
+        };
+
+        trace!(
+            "Segment at position {}: ToC flags {:#x}, {} objects, raw data from {} to {}{}",
+            lead_in.position,
+            toc_mask.flags(),
+            self.object_lists[object_list].objects().len(),
+            lead_in.raw_data_position,
+            lead_in.next_segment_position,
+            if lead_in.incomplete {
+                " (incomplete)"
+            } else {
+                ""
+            }
+        );
+        Ok(TdmsSegment::new(
+            toc_mask,
+            lead_in.version,
+            lead_in.position,
+            lead_in.raw_data_position,
+            lead_in.next_segment_position,
+            lead_in.incomplete,
+            object_list,
+        ))
+    }
+
+    /// Add warnings about unexpected table of contents flags in a segment
+    fn add_segment_warnings(&mut self, lead_in: &LeadIn, segment: &TdmsSegment) {
+        let toc_mask = lead_in.toc_mask;
+        if toc_mask.unknown_bits() != 0 {
+            self.warnings.push(TdmsWarning {
+                kind: TdmsWarningKind::UnknownTocFlags,
+                message: format!(
+                    "Unknown table of contents flags {:#x}",
+                    toc_mask.unknown_bits()
+                ),
+                position: Some(lead_in.position),
+            });
+        }
+        let has_daqmx_data = self.object_lists[segment.object_list].has_daqmx_data();
+        if toc_mask.has_flag(TocFlag::RawData)
+            && toc_mask.has_flag(TocFlag::DaqMxRawData) != has_daqmx_data
+        {
+            // The raw data layout is taken from the raw data indexes, as they describe the
+            // DAQmx raw buffers, but a mismatch suggests the writer's bookkeeping is wrong
+            self.warnings.push(TdmsWarning {
+                kind: TdmsWarningKind::DaqmxFlagMismatch,
+                message: String::from(if has_daqmx_data {
+                    "Segment has DAQmx raw data indexes but the DAQmxRawData flag is not set"
+                } else {
+                    "Segment has the DAQmxRawData flag set but no DAQmx raw data indexes"
+                }),
+                position: Some(lead_in.position),
+            });
+        }
+    }
+
+    /// Resolve the objects read from a segment's metadata to the segment's objects,
+    /// adding any new object paths but nothing else to the reader
+    fn resolve_segment_objects(
+        &mut self,
+        objects: &[ObjectMetadata],
+    ) -> Result<Vec<SegmentObject>> {
+        let mut segment_objects: Vec<SegmentObject> = Vec::with_capacity(objects.len());
+        for object in objects {
+            let object_id = match self.object_paths.get_id(&object.path) {
+                Some(object_id) => object_id,
+                None => self.object_paths.get_or_create_id(object.path.clone())?,
+            };
+            let segment_object = match object.raw_data_index {
+                ObjectRawDataIndex::NoData => SegmentObject::no_data(object_id),
+                ObjectRawDataIndex::MatchesPrevious => {
+                    // An index written earlier in the same segment is the previous index
+                    let previous_index = segment_objects
+                        .iter()
+                        .rev()
+                        .filter(|previous| previous.object_id == object_id)
+                        .find_map(|previous| previous.raw_data_index.as_ref())
+                        .or_else(|| self.raw_data_index_cache.get(object_id));
+                    match previous_index {
+                        Some(raw_data_index) => {
+                            SegmentObject::with_data(object_id, *raw_data_index)
+                        }
+                        None => {
+                            return Err(TdmsReadError::InvalidData(String::from(
+                                "Object has no previous raw data index",
+                            )))
+                        }
+                    }
+                }
+                ObjectRawDataIndex::New(raw_data_index) => {
+                    SegmentObject::with_data(object_id, raw_data_index)
+                }
+            };
+            segment_objects.push(segment_object);
+        }
+        Ok(segment_objects)
+    }
+
+    /// Add the raw data indexes and properties of objects read from a segment's metadata
+    /// to the reader, once the segment has been validated
+    fn add_object_metadata(
+        &mut self,
+        objects: Vec<ObjectMetadata>,
+        object_ids: &[ObjectPathId],
+        position: u64,
+    ) {
+        for (object, &object_id) in objects.into_iter().zip(object_ids) {
+            if let ObjectRawDataIndex::New(raw_data_index) = object.raw_data_index {
+                if self.object_paths.get_path(object_id) == Some(&ObjectPath::Root) {
+                    // The index still describes bytes in the segment's raw data, so
+                    // it's kept for the layout of the other objects' data
+                    self.warnings.push(TdmsWarning {
+                        kind: TdmsWarningKind::RootObjectData,
+                        message: String::from("Root object has a raw data index"),
+                        position: Some(position),
+                    });
+                }
+                self.raw_data_index_cache.set(object_id, raw_data_index);
+                *self
+                    .new_raw_data_index_counts
+                    .get_or_insert_with(object_id, || 0) += 1;
+            }
+            let waveform_properties: Vec<TdmsProperty> = object
+                .properties
+                .iter()
+                .filter(|(name, _)| WAVEFORM_PROPERTIES.contains(&name.as_str()))
+                .map(|(name, value)| TdmsProperty {
+                    name: self.property_names.get_or_insert(name),
+                    value: value.clone(),
+                })
+                .collect();
+            if !waveform_properties.is_empty() {
+                self.waveform_property_updates
+                    .get_or_insert_with(object_id, Vec::new)
+                    .push(WaveformPropertyUpdate {
+                        segment_index: self.segments.len(),
+                        properties: waveform_properties,
+                    });
+            }
+            for (name, value) in object.properties {
+                let object_properties = self.properties.get_or_insert_with(object_id, Vec::new);
+                // Properties written again in later segments replace the previous value
+                match object_properties
+                    .iter_mut()
+                    .find(|existing| existing.name == name)
+                {
+                    Some(existing) => {
+                        if existing.value.data_type() != value.data_type() {
+                            let path = self.object_paths.get_path(object_id);
+                            self.warnings.push(TdmsWarning {
+                                kind: TdmsWarningKind::PropertyTypeChanged,
+                                message: format!(
+                                    "Property {} of {} changed type from {} to {}",
+                                    name,
+                                    path.map_or(String::new(), |path| path.to_string()),
+                                    existing.value.data_type(),
+                                    value.data_type()
+                                ),
+                                position: Some(position),
+                            });
+                        }
+                        existing.value = value
+                    }
+                    None => object_properties.push(TdmsProperty {
+                        name: self.property_names.get_or_insert(&name),
+                        value,
+                    }),
+                }
+            }
+        }
+    }
+
+    /// Update warnings that depend on the state of the whole file, which may change
+    /// when the file is refreshed
+    fn update_file_warnings(&mut self) -> Result<()> {
+        self.warnings.retain(|warning| {
+            !matches!(
+                warning.kind,
+                TdmsWarningKind::TruncatedChannel | TdmsWarningKind::ManySegments
+            )
+        });
+        let mut file_warnings = Vec::new();
+        if let Some(segment) = self.segments.last().filter(|segment| segment.incomplete) {
+            let objects = self.object_list(segment);
+            for object in objects.objects() {
+                let truncated_count = segment.channel_truncated_count(object.object_id, objects)?;
+                if truncated_count > 0 {
+                    let path = self.object_paths.get_path(object.object_id);
+                    file_warnings.push(TdmsWarning {
+                        kind: TdmsWarningKind::TruncatedChannel,
+                        message: format!(
+                            "Channel {} is missing {} value(s) as the final segment was truncated",
+                            path.map_or(String::new(), |path| path.to_string()),
+                            truncated_count
+                        ),
+                        position: Some(segment.position),
+                    });
+                }
+            }
+        }
+        if self.segments.len() > MANY_SEGMENTS_WARNING_COUNT {
+            file_warnings.push(TdmsWarning {
+                kind: TdmsWarningKind::ManySegments,
+                message: format!(
+                    "The file has {} segments, which makes reading slow. \
+                    Defragmenting the file would make reading faster.",
+                    self.segments.len()
+                ),
+                position: None,
+            });
+        }
+        self.warnings.extend(file_warnings);
+        Ok(())
+    }
+
+    /// Release capacity of the property lists that won't be used, as files can have
+    /// very many objects with many properties
+    fn shrink_properties(&mut self) {
+        for object_properties in self.properties.values_mut() {
+            object_properties.shrink_to_fit();
+        }
+    }
+
+    /// Update the channel data indexes with data indexes for the current objects in a segment
+    fn update_data_indexes(&mut self, segment_index: usize, segment: &TdmsSegment) -> Result<()> {
+        let objects = &self.object_lists[segment.object_list];
+        let value_counts = segment.channel_value_counts(objects)?;
+        // Check every channel before updating any, so an invalid segment leaves the
+        // indexes unchanged
+        for (segment_obj, _) in &value_counts {
+            if let Some(existing_data_index) =
+                self.channel_data_index_map.get(segment_obj.object_id)
+            {
+                // Only objects with a raw data index have a value count
+                existing_data_index
+                    .check_compatible(segment_obj.raw_data_index.as_ref().unwrap())?;
+            }
+        }
+        for (segment_obj, number_of_values) in value_counts {
+            let segment_raw_data_index = segment_obj.raw_data_index.as_ref().unwrap();
+            let existing_data_index = self.channel_data_index_map.get_mut(segment_obj.object_id);
+            match existing_data_index {
+                Some(existing_data_index) => {
+                    existing_data_index.add_segment(segment_index, number_of_values);
+                }
+                None => {
+                    let new_data_index = ChannelDataIndex::from_segment_index(
+                        segment_raw_data_index,
+                        segment_index,
+                        number_of_values,
+                    );
+                    self.channel_data_index_map
+                        .set(segment_obj.object_id, new_data_index);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Numbers of the reader's object paths and object lists before a segment was added, so
+/// that a segment that turns out to be invalid can be removed
+struct ReaderCheckpoint {
+    object_paths: usize,
+    object_lists: usize,
+}
+
+/// Contiguous values of a channel to be read as part of a coalesced read
+struct DataPiece {
+    file_offset: u64,
+    byte_len: u64,
+    values: usize,
+    big_endian: bool,
+}
+
+/// Pieces of a segment's data extents needed to read `length` values after skipping
+/// `skip` values, or none if the data isn't contiguous values of a fixed size type
+fn contiguous_pieces(extents: &[DataExtent], mut skip: u64, length: u64) -> Vec<DataPiece> {
+    let mut pieces = Vec::new();
+    let mut remaining = length;
+    for extent in extents {
+        let type_size = match (extent.layout, extent.data_type.size()) {
+            (Layout::Contiguous, Some(type_size)) => type_size as u64,
+            _ => return Vec::new(),
+        };
+        if remaining == 0 {
+            break;
+        }
+        if skip >= extent.values {
+            skip -= extent.values;
+            continue;
+        }
+        let values = min(extent.values - skip, remaining);
+        pieces.push(DataPiece {
+            file_offset: extent.file_offset + skip * type_size,
+            byte_len: values * type_size,
+            values: values as usize,
+            big_endian: extent.big_endian,
+        });
+        remaining -= values;
+        skip = 0;
+    }
+    pieces
+}
+
+/// Pieces of data that are close together in the file, to be read with a single read
+struct CoalescedBlock {
+    pieces: Vec<DataPiece>,
+    values: usize,
+}
+
+impl CoalescedBlock {
+    fn new() -> CoalescedBlock {
+        CoalescedBlock {
+            pieces: Vec::new(),
+            values: 0,
+        }
+    }
+
+    /// Whether a piece can be added to the block without seeking backwards, reading over
+    /// a large gap, mixing byte orders or making the read larger than `max_read_size`
+    fn can_extend(&self, piece: &DataPiece, max_read_size: u64) -> bool {
+        match (self.pieces.first(), self.pieces.last()) {
+            (Some(first), Some(last)) => {
+                let end = last.file_offset + last.byte_len;
+                piece.file_offset >= end
+                    && piece.file_offset - end <= MAX_COALESCED_GAP
+                    && piece.big_endian == first.big_endian
+                    && piece.file_offset + piece.byte_len - first.file_offset <= max_read_size
+            }
+            _ => true,
+        }
+    }
+
+    fn push(&mut self, piece: DataPiece) {
+        self.values += piece.values;
+        self.pieces.push(piece);
+    }
+
+    /// Read the block's values into the start of the buffer with a single read of the file,
+    /// leaving the block empty. Returns the number of values read.
+    fn read<R: Read + Seek, T: NativeType>(
+        &mut self,
+        reader: &mut R,
+        buffer: &mut [T],
+        scratch: &mut Vec<u8>,
+    ) -> Result<usize> {
+        let (start, end) = match (self.pieces.first(), self.pieces.last()) {
+            (Some(first), Some(last)) => (first.file_offset, last.file_offset + last.byte_len),
+            _ => return Ok(0),
+        };
+        scratch.resize((end - start) as usize, 0);
+        reader.seek(SeekFrom::Start(start))?;
+        reader.read_exact(scratch)?;
+        let mut values_read = 0;
+        for piece in self.pieces.drain(..) {
+            let piece_start = (piece.file_offset - start) as usize;
+            let mut bytes = &scratch[piece_start..piece_start + piece.byte_len as usize];
+            let target = &mut buffer[values_read..];
+            if piece.big_endian {
+                T::read_values::<_, BigEndian>(target, &mut bytes, piece.values)?;
+            } else {
+                T::read_values::<_, LittleEndian>(target, &mut bytes, piece.values)?;
             }
+            values_read += piece.values;
         }
+        self.values = 0;
+        Ok(values_read)
+    }
+}
+
+/// The lead in of a segment, giving the positions of its metadata, raw data and the next segment
+struct LeadIn {
+    position: u64,
+    toc_mask: TocMask,
+    version: u32,
+    raw_data_position: u64,
+    next_segment_position: u64,
+    incomplete: bool,
+}
+
+impl LeadIn {
+    /// Read the lead in of the segment at the given position, leaving the reader
+    /// positioned at the start of the segment metadata
+    fn read<R: Read>(reader: &mut R, position: u64, file_length: u64) -> Result<LeadIn> {
+        let mut header_bytes = [0u8; 4];
+        reader
+            .read_exact(&mut header_bytes)
+            .parsing(position, "segment lead in")?;
 
         // Check segment header
-        let expected_header = [0x54, 0x44, 0x53, 0x6d];
-        if header_bytes != expected_header {
-            return Err(TdmsReadError::TdmsError(format!(
+        if header_bytes != SEGMENT_TAG {
+            if position == 0 {
+                // Give a more helpful error if the file is in another format
+                let mut prefix = header_bytes.to_vec();
+                reader
+                    .take(FORMAT_DETECTION_LENGTH)
+                    .read_to_end(&mut prefix)?;
+                return Err(TdmsReadError::NotATdmsFile(DetectedFormat::detect(&prefix)));
+            }
+            return Err(TdmsReadError::InvalidData(format!(
                 "Invalid segment header at position {}: {:?}",
                 position, header_bytes,
             )));
         }
 
-        let toc_mask = TocMask::from_flags(reader.read_u32::<LittleEndian>()?);
-
+        let toc_flags = reader
+            .read_u32::<LittleEndian>()
+            .parsing(position, "segment lead in")?;
+        let toc_mask = TocMask::from_flags(toc_flags);
         if toc_mask.has_flag(TocFlag::BigEndian) {
-            self.read_segment_metadata::<R, BigEndian>(reader, toc_mask, position, object_merger)
+            LeadIn::read_offsets::<R, BigEndian>(reader, toc_mask, position, file_length)
         } else {
-            self.read_segment_metadata::<R, LittleEndian>(reader, toc_mask, position, object_merger)
+            LeadIn::read_offsets::<R, LittleEndian>(reader, toc_mask, position, file_length)
         }
     }
 
-    fn read_segment_metadata<R: Read + Seek, O: ByteOrderExt>(
-        &mut self,
+    /// Refuse a segment with unknown table of contents flags when reading strictly
+    fn check_toc_flags(&self, options: &ReadOptions) -> Result<()> {
+        let unknown_bits = self.toc_mask.unknown_bits();
+        if options.get_strict_toc_flags() && unknown_bits != 0 {
+            return Err(TdmsReadError::UnknownTocFlags(unknown_bits));
+        }
+        Ok(())
+    }
+
+    /// Check that the positions given in the lead in are consistent with the file length.
+    /// This is checked after reading the metadata, so that a segment truncated within its
+    /// metadata reports what was being read.
+    fn check_positions(&self, file_length: u64) -> Result<()> {
+        if self.raw_data_position > file_length {
+            return Err(TdmsReadError::InvalidData(format!(
+                "Raw data position {} of segment at position {} is beyond the end of the file",
+                self.raw_data_position, self.position
+            )));
+        }
+        if self.next_segment_position < self.raw_data_position {
+            return Err(TdmsReadError::InvalidData(format!(
+                "Next segment position {} of segment at position {} is before its raw data",
+                self.next_segment_position, self.position
+            )));
+        }
+        Ok(())
+    }
+
+    fn read_offsets<R: Read, O: ByteOrder>(
         reader: &mut R,
         toc_mask: TocMask,
         position: u64,
-        object_merger: &mut ObjectMerger,
-    ) -> Result<Option<TdmsSegment>> {
-        let _version = reader.read_i32::<O>()?;
-        let next_segment_offset = reader.read_u64::<O>()?;
-        let raw_data_offset = reader.read_u64::<O>()?;
-
-        let lead_in_length = 28;
-        let next_segment_position = position + lead_in_length + next_segment_offset;
-        let raw_data_position = position + lead_in_length + raw_data_offset;
-
-        let segment_objects = if toc_mask.has_flag(TocFlag::MetaData) {
-            let this_segment_objects = self.read_object_metadata::<R, O>(reader)?;
-            if toc_mask.has_flag(TocFlag::NewObjList) {
-                this_segment_objects
-            } else {
-                // Not a new object list so merge with previous segment objects
-                let prev_objs = self.segments.last().map(|segment| &segment.objects);
-                object_merger.merge_objects(prev_objs, this_segment_objects)
-            }
+        file_length: u64,
+    ) -> Result<LeadIn> {
+        let version = reader
+            .read_u32::<O>()
+            .parsing(position, "segment lead in")?;
+        if version != VERSION_1_0 && version != VERSION_2_0 {
+            return Err(TdmsReadError::UnsupportedVersion(version));
+        }
+        let next_segment_offset = reader
+            .read_u64::<O>()
+            .parsing(position, "segment lead in")?;
+        let raw_data_offset = reader
+            .read_u64::<O>()
+            .parsing(position, "segment lead in")?;
+
+        // Offsets may be corrupt, so saturate rather than overflowing
+        let metadata_position = position + LEAD_IN_LENGTH;
+        let raw_data_position = metadata_position.saturating_add(raw_data_offset);
+
+        // A segment that was not completely written, eg. due to an application crash,
+        // has its next segment offset set to all ones or has a length beyond the end
+        // of the file. In this case the segment data extends to the end of the file.
+        let (next_segment_position, incomplete) = if next_segment_offset
+            == INCOMPLETE_SEGMENT_OFFSET
+            || metadata_position.saturating_add(next_segment_offset) > file_length
+        {
+            (file_length, true)
         } else {
-            // No meta data in this segment, re-use metadata from the previous segment
-            match self.segments.last() {
-                // TODO: Share references to object vectors?
-                Some(segment) => segment.objects.to_vec(),
-                None => Vec::new(),
-            }
+            (metadata_position + next_segment_offset, false)
         };
 
-        self.update_data_indexes(&segment_objects)?;
-
-        Ok(Some(TdmsSegment::new(
+        Ok(LeadIn {
+            position,
             toc_mask,
+            version,
             raw_data_position,
             next_segment_position,
-            segment_objects,
-        )))
+            incomplete,
+        })
+    }
+}
+
+/// An object from a segment's metadata, before it has been added to the reader
+struct ObjectMetadata {
+    path: String,
+    raw_data_index: ObjectRawDataIndex,
+    properties: Vec<(String, TdmsValue)>,
+}
+
+enum ObjectRawDataIndex {
+    NoData,
+    MatchesPrevious,
+    New(RawDataIndex),
+}
+
+fn read_object_metadata<R: Read, O: ByteOrderExt>(
+    reader: &mut R,
+    segment_position: u64,
+    custom_types: &CustomTypes,
+) -> Result<Vec<ObjectMetadata>> {
+    let num_objects = reader
+        .read_u32::<O>()
+        .parsing(segment_position, "number of objects")?;
+    // Limit the up front allocation as the number of objects may be corrupt
+    let mut objects = Vec::with_capacity(num_objects.min(OBJECT_CAPACITY_LIMIT) as usize);
+    for _ in 0..num_objects {
+        let path = read_string::<R, O>(reader).parsing(segment_position, "object path string")?;
+        let raw_data_index_header = reader
+            .read_u32::<O>()
+            .parsing(segment_position, "raw data index header")?;
+        let raw_data_index = match raw_data_index_header {
+            RAW_DATA_INDEX_NO_DATA => ObjectRawDataIndex::NoData,
+            RAW_DATA_INDEX_MATCHES_PREVIOUS => ObjectRawDataIndex::MatchesPrevious,
+            FORMAT_CHANGING_SCALER | DIGITAL_LINE_SCALER => {
+                ObjectRawDataIndex::New(read_daqmx_raw_data_index::<R, O>(
+                    reader,
+                    &path,
+                    segment_position,
+                    raw_data_index_header == DIGITAL_LINE_SCALER,
+                )?)
+            }
+            // Raw data index header gives length of index information
+            _ => ObjectRawDataIndex::New(read_raw_data_index::<R, O>(
+                reader,
+                &path,
+                segment_position,
+                custom_types,
+            )?),
+        };
+        let num_properties = reader
+            .read_u32::<O>()
+            .parsing(segment_position, "number of properties")?;
+        let properties = (0..num_properties)
+            .map(|_| read_property::<R, O>(reader, segment_position))
+            .collect::<Result<Vec<(String, TdmsValue)>>>()?;
+        objects.push(ObjectMetadata {
+            path,
+            raw_data_index,
+            properties,
+        });
+    }
+    Ok(objects)
+}
+
+/// Read the name and value of a property of an object in a segment's metadata
+fn read_property<R: Read, O: ByteOrderExt>(
+    reader: &mut R,
+    segment_position: u64,
+) -> Result<(String, TdmsValue)> {
+    let name = read_string::<R, O>(reader).parsing(segment_position, "property name string")?;
+    let type_id = reader
+        .read_u32::<O>()
+        .parsing(segment_position, "property data type")?;
+    let type_id = TdsType::from_u32(type_id)?;
+    let value = read_value::<R, O>(type_id, reader)
+        .parsing(segment_position, property_value_description(type_id))?;
+    Ok((name, value))
+}
+
+/// Description of a property value for errors, which must be static so lists each type
+fn property_value_description(type_id: TdsType) -> &'static str {
+    match type_id {
+        TdsType::I8 => "property value of type I8",
+        TdsType::I16 => "property value of type I16",
+        TdsType::I32 => "property value of type I32",
+        TdsType::I64 => "property value of type I64",
+        TdsType::U8 => "property value of type U8",
+        TdsType::U16 => "property value of type U16",
+        TdsType::U32 => "property value of type U32",
+        TdsType::U64 => "property value of type U64",
+        TdsType::SingleFloat => "property value of type SGL",
+        TdsType::DoubleFloat => "property value of type DBL",
+        TdsType::String => "property value of type String",
+        TdsType::Boolean => "property value of type Boolean",
+        TdsType::TimeStamp => "property value of type Timestamp",
+        _ => "property value",
+    }
+}
+
+/// Adds what was being read to an unexpected end of file error while reading metadata
+trait MetadataContext<T> {
+    fn parsing(self, segment_position: u64, parsing: &'static str) -> Result<T>;
+}
+
+impl<T, E: Into<TdmsReadError>> MetadataContext<T> for std::result::Result<T, E> {
+    fn parsing(self, segment_position: u64, parsing: &'static str) -> Result<T> {
+        self.map_err(|e| match e.into() {
+            TdmsReadError::IoError(ref e) if e.kind() == ErrorKind::UnexpectedEof => {
+                TdmsReadError::TruncatedMetadata {
+                    segment_position,
+                    parsing,
+                }
+            }
+            e => e,
+        })
     }
+}
 
-    fn read_object_metadata<R: Read, O: ByteOrderExt>(
+#[cfg(feature = "parallel")]
+impl TdmsReader {
+    /// Read all segments from the start of the file. The segment lead ins are read first
+    /// to find the position of each segment's metadata, then the metadata is parsed in
+    /// parallel and added to the reader in segment order, giving the same result as
+    /// `read_segments`. Returns an error if any segment can't be read, in which case
+    /// the file should be read sequentially to handle the error.
+    fn read_segments_parallel<R: Read + Seek>(
         &mut self,
         reader: &mut R,
-    ) -> Result<Vec<SegmentObject>> {
-        let num_objects = reader.read_u32::<O>()?;
-        let mut segment_objects = Vec::with_capacity(num_objects as usize);
-        for _ in 0..num_objects {
-            let object_path = read_string::<R, O>(reader)?;
-            let object_id = self.object_paths.get_or_create_id(object_path)?;
-            let raw_data_index_header = reader.read_u32::<O>()?;
-            let segment_object = match raw_data_index_header {
-                RAW_DATA_INDEX_NO_DATA => SegmentObject::no_data(object_id),
-                RAW_DATA_INDEX_MATCHES_PREVIOUS => match self.raw_data_index_cache.get(object_id) {
-                    Some(raw_data_index_id) => {
-                        SegmentObject::with_data(object_id, *raw_data_index_id)
-                    }
-                    None => {
-                        return Err(TdmsReadError::TdmsError(String::from(
-                            "Object has no previous raw data index",
-                        )))
-                    }
-                },
-                FORMAT_CHANGING_SCALER => unimplemented!(),
-                DIGITAL_LINE_SCALER => unimplemented!(),
-                _ => {
-                    // Raw data index header gives length of index information
-                    let raw_data_index = self
-                        .data_indexes
-                        .alloc(read_raw_data_index::<R, O>(reader)?);
-                    self.raw_data_index_cache.set(object_id, raw_data_index);
-                    SegmentObject::with_data(object_id, raw_data_index)
+        options: &ReadOptions,
+    ) -> Result<()> {
+        use rayon::prelude::*;
+
+        debug_span!("read_segments_parallel");
+        let file_length = reader.seek(SeekFrom::End(0))?;
+        let mut lead_ins = Vec::new();
+        let mut position = 0;
+        while position < file_length {
+            reader.seek(SeekFrom::Start(position))?;
+            let lead_in = LeadIn::read(reader, position, file_length)?;
+            lead_in.check_toc_flags(options)?;
+            lead_in.check_positions(file_length)?;
+            position = lead_in.next_segment_position;
+            lead_ins.push(lead_in);
+        }
+        debug!("Read {} segment lead ins", lead_ins.len());
+
+        let custom_types = options.get_custom_types();
+        let mut object_merger = ObjectMerger::new();
+        for batch in lead_ins.chunks(PARALLEL_BATCH_SIZE) {
+            let mut metadata = Vec::with_capacity(batch.len());
+            for lead_in in batch {
+                if lead_in.toc_mask.has_flag(TocFlag::MetaData) {
+                    let metadata_position = lead_in.position + LEAD_IN_LENGTH;
+                    let mut bytes =
+                        vec![0u8; (lead_in.raw_data_position - metadata_position) as usize];
+                    reader.seek(SeekFrom::Start(metadata_position))?;
+                    reader.read_exact(&mut bytes)?;
+                    metadata.push(Some(bytes));
+                } else {
+                    metadata.push(None);
                 }
-            };
-            segment_objects.push(segment_object);
-            let num_properties = reader.read_u32::<O>()?;
-            for _ in 0..num_properties {
-                let property = TdmsProperty::read::<_, O>(reader)?;
-                self.properties
-                    .entry(object_id)
-                    .or_insert_with(Vec::new)
-                    .push(property);
+            }
+            let batch_objects = batch
+                .par_iter()
+                .zip(metadata.par_iter())
+                .map(|(lead_in, bytes)| {
+                    let mut bytes = match bytes {
+                        Some(bytes) => bytes.as_slice(),
+                        None => return Ok(None),
+                    };
+                    let objects = if lead_in.toc_mask.has_flag(TocFlag::BigEndian) {
+                        read_object_metadata::<_, BigEndian>(
+                            &mut bytes,
+                            lead_in.position,
+                            custom_types,
+                        )?
+                    } else {
+                        read_object_metadata::<_, LittleEndian>(
+                            &mut bytes,
+                            lead_in.position,
+                            custom_types,
+                        )?
+                    };
+                    Ok(Some(objects))
+                })
+                .collect::<Result<Vec<Option<Vec<ObjectMetadata>>>>>()?;
+            for (lead_in, objects) in batch.iter().zip(batch_objects) {
+                let segment = self.add_segment(lead_in, objects, &mut object_merger)?;
+                self.segments.push(segment);
             }
         }
+        self.read_position = position.min(file_length);
+        self.shrink_properties();
+        self.update_file_warnings()?;
+        debug!(
+            "Read {} segments in parallel to position {}",
+            self.segments.len(),
+            self.read_position
+        );
+        Ok(())
+    }
+}
 
-        Ok(segment_objects)
+#[cfg(feature = "cache")]
+impl TdmsReader {
+    /// Copy the parsed metadata into a form that can be written to a cache
+    pub fn to_cached(&self) -> CachedMetadata {
+        let cached_raw_data_index = |raw_data_index: &RawDataIndex| CachedRawDataIndex {
+            number_of_values: raw_data_index.number_of_values,
+            data_type: raw_data_index.data_type.to_u32(),
+            data_size: raw_data_index.data_size,
+            daqmx: raw_data_index.daqmx,
+        };
+        let object_ids: Vec<ObjectPathId> =
+            self.objects().map(|(object_id, _)| object_id).collect();
+        CachedMetadata {
+            object_paths: object_ids
+                .iter()
+                .map(|object_id| {
+                    self.get_object_path_string(*object_id)
+                        .unwrap_or_default()
+                        .to_string()
+                })
+                .collect(),
+            properties: object_ids
+                .iter()
+                .map(|object_id| {
+                    self.get_properties(*object_id)
+                        .iter()
+                        .map(|property| (property.name.to_string(), property.value.clone()))
+                        .collect()
+                })
+                .collect(),
+            waveform_property_updates: object_ids
+                .iter()
+                .map(|object_id| {
+                    self.get_waveform_property_updates(*object_id)
+                        .iter()
+                        .map(|update| {
+                            let properties = update
+                                .properties
+                                .iter()
+                                .map(|property| (property.name.to_string(), property.value.clone()))
+                                .collect();
+                            (update.segment_index, properties)
+                        })
+                        .collect()
+                })
+                .collect(),
+            object_lists: self
+                .object_lists
+                .iter()
+                .map(|object_list| {
+                    object_list
+                        .objects()
+                        .iter()
+                        .map(|object| CachedSegmentObject {
+                            object_id: object.object_id.as_usize(),
+                            raw_data_index: object
+                                .raw_data_index
+                                .as_ref()
+                                .map(cached_raw_data_index),
+                        })
+                        .collect()
+                })
+                .collect(),
+            segments: self
+                .segments
+                .iter()
+                .map(|segment| CachedSegment {
+                    position: segment.position,
+                    next_segment_position: segment.next_segment_position,
+                    data_position: segment.position + segment.header_length(),
+                    version: segment.version,
+                    toc_flags: segment.toc_mask().flags(),
+                    incomplete: segment.incomplete,
+                    object_list: segment.object_list.as_usize(),
+                })
+                .collect(),
+            raw_data_indexes: object_ids
+                .iter()
+                .map(|object_id| {
+                    self.raw_data_index_cache
+                        .get(*object_id)
+                        .map(cached_raw_data_index)
+                })
+                .collect(),
+            new_raw_data_index_counts: object_ids
+                .iter()
+                .map(|object_id| self.new_raw_data_index_count(*object_id))
+                .collect(),
+            skipped_segments: self.skipped_segments.clone(),
+            warnings: self.warnings.clone(),
+            read_position: self.read_position,
+        }
     }
 
-    /// Update the channel data indexes with data indexes for the current objects in a segment
-    fn update_data_indexes(&mut self, segment_objects: &[SegmentObject]) -> Result<()> {
-        for segment_obj in segment_objects {
-            if let Some(segment_data_index_id) = segment_obj.raw_data_index {
-                // If we have a valid raw data index id it must correspond to a raw data index
-                // in data_indexes so unwrap here is safe.
-                let segment_raw_data_index = self.data_indexes.get(segment_data_index_id).unwrap();
-                let existing_data_index =
-                    self.channel_data_index_map.get_mut(segment_obj.object_id);
-                match existing_data_index {
-                    Some(existing_data_index) => {
-                        existing_data_index.update_with_segment_index(segment_raw_data_index)?;
-                    }
-                    None => {
-                        let new_data_index =
-                            ChannelDataIndex::from_segment_index(segment_raw_data_index);
-                        self.channel_data_index_map
-                            .set(segment_obj.object_id, new_data_index);
-                    }
+    /// Restore parsed metadata from a cache, rebuilding the channel data indexes
+    pub fn from_cached(cached: CachedMetadata, custom_types: &CustomTypes) -> Result<TdmsReader> {
+        let invalid = |message: &str| TdmsReadError::InvalidCache(String::from(message));
+        let mut tdms_reader = TdmsReader::new();
+        let mut object_ids = Vec::with_capacity(cached.object_paths.len());
+        for (index, path) in cached.object_paths.into_iter().enumerate() {
+            let object_id = tdms_reader.object_paths.get_or_create_id(path)?;
+            // Implicitly created group objects are cached with their own path,
+            // so ids are assigned in the same order as when the file was read
+            if object_id.as_usize() != index {
+                return Err(invalid("object ids are out of order"));
+            }
+            object_ids.push(object_id);
+        }
+        let object_id = |index: usize| {
+            object_ids
+                .get(index)
+                .copied()
+                .ok_or_else(|| invalid("object id is out of range"))
+        };
+        let raw_data_index = |cached_index: CachedRawDataIndex| -> Result<RawDataIndex> {
+            Ok(RawDataIndex {
+                number_of_values: cached_index.number_of_values,
+                data_type: custom_types.data_type(cached_index.data_type)?,
+                data_size: cached_index.data_size,
+                daqmx: cached_index.daqmx,
+            })
+        };
+
+        for (index, properties) in cached.properties.into_iter().enumerate() {
+            if properties.is_empty() {
+                continue;
+            }
+            let properties = properties
+                .into_iter()
+                .map(|(name, value)| TdmsProperty {
+                    name: tdms_reader.property_names.get_or_insert(&name),
+                    value,
+                })
+                .collect();
+            tdms_reader.properties.set(object_id(index)?, properties);
+        }
+        for (index, updates) in cached.waveform_property_updates.into_iter().enumerate() {
+            if updates.is_empty() {
+                continue;
+            }
+            let updates = updates
+                .into_iter()
+                .map(|(segment_index, properties)| WaveformPropertyUpdate {
+                    segment_index,
+                    properties: properties
+                        .into_iter()
+                        .map(|(name, value)| TdmsProperty {
+                            name: tdms_reader.property_names.get_or_insert(&name),
+                            value,
+                        })
+                        .collect(),
+                })
+                .collect();
+            tdms_reader
+                .waveform_property_updates
+                .set(object_id(index)?, updates);
+        }
+        for (index, cached_index) in cached.raw_data_indexes.into_iter().enumerate() {
+            if let Some(cached_index) = cached_index {
+                tdms_reader
+                    .raw_data_index_cache
+                    .set(object_id(index)?, raw_data_index(cached_index)?);
+            }
+        }
+        for (index, count) in cached.new_raw_data_index_counts.into_iter().enumerate() {
+            if count > 0 {
+                tdms_reader
+                    .new_raw_data_index_counts
+                    .set(object_id(index)?, count);
+            }
+        }
+
+        let mut object_list_ids = Vec::with_capacity(cached.object_lists.len());
+        for cached_objects in cached.object_lists {
+            let objects = cached_objects
+                .into_iter()
+                .map(|object| {
+                    Ok(SegmentObject {
+                        object_id: object_id(object.object_id)?,
+                        raw_data_index: object.raw_data_index.map(raw_data_index).transpose()?,
+                    })
+                })
+                .collect::<Result<Vec<SegmentObject>>>()?;
+            let object_list = ObjectList::new(objects)
+                .ok_or_else(|| invalid("object list data size is too large"))?;
+            object_list_ids.push(tdms_reader.object_lists.push(object_list));
+        }
+        for cached_segment in cached.segments {
+            let object_list = *object_list_ids
+                .get(cached_segment.object_list)
+                .ok_or_else(|| invalid("object list is out of range"))?;
+            let segment = TdmsSegment::new(
+                TocMask::from_flags(cached_segment.toc_flags),
+                cached_segment.version,
+                cached_segment.position,
+                cached_segment.data_position,
+                cached_segment.next_segment_position,
+                cached_segment.incomplete,
+                object_list,
+            );
+            tdms_reader.update_data_indexes(tdms_reader.segments.len(), &segment)?;
+            tdms_reader.segments.push(segment);
+        }
+        tdms_reader.skipped_segments = cached.skipped_segments;
+        tdms_reader.warnings = cached.warnings;
+        tdms_reader.read_position = cached.read_position;
+        Ok(tdms_reader)
+    }
+}
+
+/// Check whether the lead in and metadata of the segment at the given position have been
+/// completely written, leaving the reader positioned at the start of the segment
+fn metadata_written<R: Read + Seek>(
+    reader: &mut R,
+    position: u64,
+    file_length: u64,
+) -> Result<bool> {
+    if position + LEAD_IN_LENGTH > file_length {
+        return Ok(false);
+    }
+    let mut lead_in = [0u8; LEAD_IN_LENGTH as usize];
+    reader.read_exact(&mut lead_in)?;
+    reader.seek(SeekFrom::Start(position))?;
+    let toc_mask = TocMask::from_flags(LittleEndian::read_u32(&lead_in[4..8]));
+    let raw_data_offset = if toc_mask.has_flag(TocFlag::BigEndian) {
+        BigEndian::read_u64(&lead_in[20..28])
+    } else {
+        LittleEndian::read_u64(&lead_in[20..28])
+    };
+    Ok((position + LEAD_IN_LENGTH).saturating_add(raw_data_offset) <= file_length)
+}
+
+/// Search for the start of the next valid segment after an invalid segment at the given position.
+/// If the segment lead in is valid and its length is within the file this is used, otherwise
+/// the file is scanned for the next segment tag.
+fn find_next_segment<R: Read + Seek>(
+    reader: &mut R,
+    position: u64,
+    file_length: u64,
+) -> Result<Option<u64>> {
+    reader.seek(SeekFrom::Start(position))?;
+    let mut lead_in = [0u8; LEAD_IN_LENGTH as usize];
+    if position + LEAD_IN_LENGTH <= file_length {
+        reader.read_exact(&mut lead_in)?;
+        if lead_in[0..4] == SEGMENT_TAG {
+            let toc_mask = TocMask::from_flags(LittleEndian::read_u32(&lead_in[4..8]));
+            let next_segment_offset = if toc_mask.has_flag(TocFlag::BigEndian) {
+                BigEndian::read_u64(&lead_in[12..20])
+            } else {
+                LittleEndian::read_u64(&lead_in[12..20])
+            };
+            // A corrupt offset may overflow, or point back to this segment, so the
+            // next position is only used if it's after this segment and within the file
+            let next_position = (position + LEAD_IN_LENGTH).checked_add(next_segment_offset);
+            if let Some(next_position) = next_position {
+                if next_segment_offset != INCOMPLETE_SEGMENT_OFFSET
+                    && next_position > position
+                    && next_position <= file_length
+                {
+                    return Ok(Some(next_position));
                 }
             }
         }
-        Ok(())
+    }
+
+    // Scan forward for the next segment tag
+    let mut search_position = position + 1;
+    reader.seek(SeekFrom::Start(search_position))?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut tag_progress = 0;
+    loop {
+        let bytes_read = reader.read(&mut buffer)?;
+        if bytes_read == 0 {
+            return Ok(None);
+        }
+        for (i, byte) in buffer[..bytes_read].iter().enumerate() {
+            if *byte == SEGMENT_TAG[tag_progress] {
+                tag_progress += 1;
+                if tag_progress == SEGMENT_TAG.len() {
+                    return Ok(Some(
+                        search_position + i as u64 + 1 - SEGMENT_TAG.len() as u64,
+                    ));
+                }
+            } else if *byte == SEGMENT_TAG[0] {
+                tag_progress = 1;
+            } else {
+                tag_progress = 0;
+            }
+        }
+        search_position += bytes_read as u64;
     }
 }
 
@@ -296,7 +1943,7 @@ impl ObjectMerger {
     /// Combine previous segment's object list with objects in the current segment
     pub fn merge_objects(
         &mut self,
-        previous_segment_objects: Option<&Vec<SegmentObject>>,
+        previous_segment_objects: Option<&[SegmentObject]>,
         new_objects: Vec<SegmentObject>,
     ) -> Vec<SegmentObject> {
         if let Some(prev_objs) = previous_segment_objects {
@@ -325,26 +1972,48 @@ impl ObjectMerger {
     }
 }
 
-fn read_raw_data_index<R: Read, O: ByteOrderExt>(reader: &mut R) -> Result<RawDataIndex> {
-    let data_type = reader.read_u32::<O>()?;
-    let data_type = TdsType::from_u32(data_type)?;
-    let dimension = reader.read_u32::<O>()?;
-    let number_of_values = reader.read_u64::<O>()?;
+fn read_raw_data_index<R: Read, O: ByteOrderExt>(
+    reader: &mut R,
+    path: &str,
+    segment_position: u64,
+    custom_types: &CustomTypes,
+) -> Result<RawDataIndex> {
+    let data_type = reader
+        .read_u32::<O>()
+        .parsing(segment_position, "raw data index data type")?;
+    let data_type = custom_types.data_type(data_type)?;
+    let dimension = reader
+        .read_u32::<O>()
+        .parsing(segment_position, "raw data index dimension")?;
+    let number_of_values = reader
+        .read_u64::<O>()
+        .parsing(segment_position, "raw data index number of values")?;
 
+    // The layout of multi-dimensional data isn't documented, so its size can't be
+    // determined and the rest of the segment can't be read
     if dimension != 1 {
-        return Err(TdmsReadError::TdmsError(format!(
-            "Dimension must be 1, got {}",
-            dimension
-        )));
+        return Err(TdmsReadError::UnsupportedDimension {
+            path: path.to_string(),
+            dimension,
+        });
     }
 
     let data_size = match data_type.size() {
-        Some(type_size) => (type_size as u64) * number_of_values,
+        Some(type_size) => (type_size as u64)
+            .checked_mul(number_of_values)
+            .ok_or_else(|| {
+                TdmsReadError::InvalidData(format!(
+                    "Object {} in segment at position {} has too many values: {}",
+                    path, segment_position, number_of_values
+                ))
+            })?,
         None => {
             if data_type == TdsType::String {
-                reader.read_u64::<O>()?
+                reader
+                    .read_u64::<O>()
+                    .parsing(segment_position, "raw data index data size")?
             } else {
-                return Err(TdmsReadError::TdmsError(format!(
+                return Err(TdmsReadError::Unsupported(format!(
                     "Unsupported data type: {:?}",
                     data_type
                 )));
@@ -355,5 +2024,101 @@ fn read_raw_data_index<R: Read, O: ByteOrderExt>(reader: &mut R) -> Result<RawDa
         number_of_values,
         data_type,
         data_size,
+        daqmx: None,
+    })
+}
+
+/// Read the raw data index of an object with DAQmx raw data, which describes the
+/// object's scalers and the widths of the segment's raw buffers. Only the first
+/// scaler is kept, as data with more than one scaler can't be read.
+fn read_daqmx_raw_data_index<R: Read, O: ByteOrderExt>(
+    reader: &mut R,
+    path: &str,
+    segment_position: u64,
+    digital_line: bool,
+) -> Result<RawDataIndex> {
+    let data_type = reader
+        .read_u32::<O>()
+        .parsing(segment_position, "raw data index data type")?;
+    let data_type = TdsType::from_u32(data_type)?;
+    if data_type != TdsType::DaqmxRawData {
+        return Err(TdmsReadError::InvalidData(format!(
+            "Object {} has a DAQmx raw data index with data type {:?}",
+            path, data_type
+        )));
+    }
+    let dimension = reader
+        .read_u32::<O>()
+        .parsing(segment_position, "raw data index dimension")?;
+    if dimension != 1 {
+        return Err(TdmsReadError::UnsupportedDimension {
+            path: path.to_string(),
+            dimension,
+        });
+    }
+    let number_of_values = reader
+        .read_u64::<O>()
+        .parsing(segment_position, "raw data index number of values")?;
+
+    let scaler_count = reader
+        .read_u32::<O>()
+        .parsing(segment_position, "number of DAQmx scalers")?;
+    let mut first_scaler = None;
+    for _ in 0..scaler_count {
+        let raw_data_type = reader
+            .read_u32::<O>()
+            .parsing(segment_position, "DAQmx scaler")?;
+        let buffer_index = reader
+            .read_u32::<O>()
+            .parsing(segment_position, "DAQmx scaler")?;
+        // Digital line scalers give an offset in bits rather than bytes
+        let offset = reader
+            .read_u32::<O>()
+            .parsing(segment_position, "DAQmx scaler")?;
+        if digital_line {
+            reader.read_u8().parsing(segment_position, "DAQmx scaler")?;
+        } else {
+            reader
+                .read_u32::<O>()
+                .parsing(segment_position, "DAQmx scaler")?;
+        }
+        let scale_id = reader
+            .read_u32::<O>()
+            .parsing(segment_position, "DAQmx scaler")?;
+        first_scaler.get_or_insert((raw_data_type, buffer_index, offset, scale_id));
+    }
+
+    let buffer_count = reader
+        .read_u32::<O>()
+        .parsing(segment_position, "number of DAQmx raw data widths")?;
+    let mut buffer_widths = Vec::with_capacity(buffer_count.min(OBJECT_CAPACITY_LIMIT) as usize);
+    for _ in 0..buffer_count {
+        buffer_widths.push(
+            reader
+                .read_u32::<O>()
+                .parsing(segment_position, "DAQmx raw data width")?,
+        );
+    }
+
+    let (raw_data_type, buffer_index, byte_offset, scale_id) = first_scaler.unwrap_or_default();
+    let total_width: u64 = buffer_widths.iter().map(|width| *width as u64).sum();
+    Ok(RawDataIndex {
+        number_of_values,
+        data_type,
+        data_size: total_width.saturating_mul(number_of_values),
+        daqmx: Some(DaqmxScaler {
+            digital_line,
+            scaler_count,
+            buffer_count,
+            raw_data_type,
+            byte_offset,
+            // A buffer index outside the buffer widths gives a row width of zero,
+            // which is reported as unsupported when the data is read
+            row_width: buffer_widths
+                .get(buffer_index as usize)
+                .copied()
+                .unwrap_or(0),
+            scale_id,
+        }),
     })
 }
@@ -1,12 +1,32 @@
+#[cfg(feature = "chrono")]
 use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::convert::TryInto;
+use std::fmt::Write;
+#[cfg(feature = "chrono")]
+use std::ops::{Add, Sub};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+/// A TDMS timestamp, stored exactly as in files as whole seconds since the epoch of
+/// 1904-01-01 00:00:00 UTC and positive fractions of a second as a multiple of 2^-64 s.
+/// Times before 1904 have negative seconds. Timestamps are ordered chronologically.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Timestamp {
-    second_fractions: u64,
+    // Seconds are declared first so that the derived ordering is chronological
     seconds: i64,
+    second_fractions: u64,
 }
 
-const FRACTIONS_PER_NS: u64 = 18446744073; // 2 ** 64 / 10 ** 9;
+/// Seconds from the TDMS epoch of 1904-01-01 to the Unix epoch of 1970-01-01
+const UNIX_EPOCH_SECONDS: i64 = 2_082_844_800;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Range of years that a `DateTime` can represent. Formatting is limited to the same
+/// range so that it gives the same results with or without the `chrono` feature.
+const MIN_YEAR: i64 = -262_144;
+const MAX_YEAR: i64 = 262_143;
 
 impl Timestamp {
     pub fn new(seconds: i64, second_fractions: u64) -> Timestamp {
@@ -16,13 +36,396 @@ impl Timestamp {
         }
     }
 
+    /// Create a timestamp from a UTC date and time. Nanoseconds are converted to the
+    /// nearest fraction that converts back to the same number of nanoseconds.
+    #[cfg(feature = "chrono")]
+    pub fn from_datetime(datetime: DateTime<Utc>) -> Timestamp {
+        // Leap seconds are represented with more than a billion nanoseconds
+        let nanos = datetime.timestamp_subsec_nanos() as u64;
+        let seconds = datetime.timestamp() + UNIX_EPOCH_SECONDS + (nanos / NANOS_PER_SECOND) as i64;
+        Timestamp::new(seconds, nanos_to_fractions(nanos % NANOS_PER_SECOND))
+    }
+
+    /// Whole seconds since the epoch of 1904-01-01 00:00:00 UTC
+    pub fn seconds(&self) -> i64 {
+        self.seconds
+    }
+
+    /// Fractions of a second, as a multiple of 2^-64 seconds
+    pub fn second_fractions(&self) -> u64 {
+        self.second_fractions
+    }
+
+    /// Nanoseconds since the Unix epoch of 1970-01-01 00:00:00 UTC, rounded down.
+    /// Unlike a `DateTime`, this can represent every timestamp.
+    pub fn to_unix_nanos(&self) -> i128 {
+        let seconds = self.seconds as i128 - UNIX_EPOCH_SECONDS as i128;
+        seconds * NANOS_PER_SECOND as i128 + fractions_to_nanos(self.second_fractions) as i128
+    }
+
+    /// Create a timestamp from nanoseconds since the Unix epoch of 1970-01-01 00:00:00 UTC,
+    /// returning None if the seconds would overflow
+    pub fn from_unix_nanos(nanos: i128) -> Option<Timestamp> {
+        let unix_seconds = nanos.div_euclid(NANOS_PER_SECOND as i128);
+        let nanos = nanos.rem_euclid(NANOS_PER_SECOND as i128) as u64;
+        let seconds = (unix_seconds + UNIX_EPOCH_SECONDS as i128)
+            .try_into()
+            .ok()?;
+        Some(Timestamp::new(seconds, nanos_to_fractions(nanos)))
+    }
+
+    /// Format as an RFC 3339 UTC date and time, with as many digits of fractional
+    /// seconds as needed out of 0, 3, 6 or 9, eg. `2015-09-08T10:05:47.669260594+00:00`.
+    /// This matches `DateTime::to_rfc3339` and doesn't need the `chrono` feature.
+    /// Returns None if the timestamp is outside the range of `DateTime`.
+    pub fn to_rfc3339(&self) -> Option<String> {
+        self.format_rfc3339("+00:00")
+    }
+
+    /// Format as RFC 3339 with the given suffix for the UTC offset
+    pub(crate) fn format_rfc3339(&self, utc_suffix: &str) -> Option<String> {
+        let unix_seconds = self.seconds.checked_sub(UNIX_EPOCH_SECONDS)?;
+        let days = unix_seconds.div_euclid(SECONDS_PER_DAY);
+        let seconds_of_day = unix_seconds.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        if !(MIN_YEAR..=MAX_YEAR).contains(&year) {
+            return None;
+        }
+
+        let mut formatted = String::with_capacity(35);
+        if (0..=9999).contains(&year) {
+            write!(formatted, "{:04}", year).ok()?;
+        } else {
+            write!(formatted, "{:+05}", year).ok()?;
+        }
+        write!(
+            formatted,
+            "-{:02}-{:02}T{:02}:{:02}:{:02}",
+            month,
+            day,
+            seconds_of_day / 3600,
+            seconds_of_day / 60 % 60,
+            seconds_of_day % 60
+        )
+        .ok()?;
+        match fractions_to_nanos(self.second_fractions) {
+            0 => Ok(()),
+            nanos if nanos % 1_000_000 == 0 => write!(formatted, ".{:03}", nanos / 1_000_000),
+            nanos if nanos % 1_000 == 0 => write!(formatted, ".{:06}", nanos / 1_000),
+            nanos => write!(formatted, ".{:09}", nanos),
+        }
+        .ok()?;
+        formatted.push_str(utc_suffix);
+        Some(formatted)
+    }
+
+    /// Convert to a UTC date and time, rounding down to a whole number of nanoseconds.
+    /// Returns None if the timestamp is outside the range of `DateTime`.
+    #[cfg(feature = "chrono")]
     pub fn to_datetime(&self) -> Option<DateTime<Utc>> {
-        let seconds_duration = Duration::seconds(self.seconds);
-        let fractions_duration =
-            Duration::nanoseconds((self.second_fractions / FRACTIONS_PER_NS) as i64);
-        let epoch = Utc.ymd(1904, 1, 1).and_hms(0, 0, 0);
-        epoch
-            .checked_add_signed(seconds_duration)
-            .and_then(|dt| dt.checked_add_signed(fractions_duration))
+        let unix_seconds = self.seconds.checked_sub(UNIX_EPOCH_SECONDS)?;
+        let nanos = fractions_to_nanos(self.second_fractions) as u32;
+        Utc.timestamp_opt(unix_seconds, nanos).single()
+    }
+
+    /// Add a duration, returning None if the result would overflow
+    #[cfg(feature = "chrono")]
+    pub fn checked_add(&self, duration: Duration) -> Option<Timestamp> {
+        let (seconds, nanos) = duration_parts(duration);
+        let (second_fractions, carry) = self
+            .second_fractions
+            .overflowing_add(nanos_to_fractions(nanos));
+        let seconds = self
+            .seconds
+            .checked_add(seconds)?
+            .checked_add(carry as i64)?;
+        Some(Timestamp::new(seconds, second_fractions))
+    }
+
+    /// Subtract a duration, returning None if the result would overflow
+    #[cfg(feature = "chrono")]
+    pub fn checked_sub(&self, duration: Duration) -> Option<Timestamp> {
+        self.checked_add(-duration)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(datetime: DateTime<Utc>) -> Timestamp {
+        Timestamp::from_datetime(datetime)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    /// Add a duration, panicking if the result overflows
+    fn add(self, duration: Duration) -> Timestamp {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to timestamp")
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Sub<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    /// Subtract a duration, panicking if the result overflows
+    fn sub(self, duration: Duration) -> Timestamp {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting duration from timestamp")
+    }
+}
+
+/// Split a duration into whole seconds, rounded down, and the remaining nanoseconds
+#[cfg(feature = "chrono")]
+fn duration_parts(duration: Duration) -> (i64, u64) {
+    let seconds = duration.num_seconds();
+    let nanos = (duration - Duration::seconds(seconds))
+        .num_nanoseconds()
+        .unwrap_or(0);
+    if nanos < 0 {
+        (seconds - 1, (nanos + NANOS_PER_SECOND as i64) as u64)
+    } else {
+        (seconds, nanos as u64)
+    }
+}
+
+/// Convert days since the Unix epoch to a proleptic Gregorian (year, month, day),
+/// using the algorithm from http://howardhinnant.github.io/date_algorithms.html
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Shift the epoch to 0000-03-01, so that leap days are at the end of each year
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Convert fractions of a second to whole nanoseconds, rounding down
+fn fractions_to_nanos(second_fractions: u64) -> u64 {
+    ((second_fractions as u128 * NANOS_PER_SECOND as u128) >> 64) as u64
+}
+
+/// Convert nanoseconds below one second to fractions of a second, rounding up so
+/// that converting back to nanoseconds gives the same value
+fn nanos_to_fractions(nanos: u64) -> u64 {
+    let scaled = (nanos as u128) << 64;
+    let divisor = NANOS_PER_SECOND as u128;
+    scaled.div_ceil(divisor) as u64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn convert_to_unix_nanos() {
+        assert_eq!(Timestamp::new(UNIX_EPOCH_SECONDS, 0).to_unix_nanos(), 0);
+        assert_eq!(
+            Timestamp::new(UNIX_EPOCH_SECONDS + 1, 1 << 63).to_unix_nanos(),
+            1_500_000_000
+        );
+        assert_eq!(
+            Timestamp::new(0, 0).to_unix_nanos(),
+            -2_082_844_800_000_000_000
+        );
+        // Before 1904 the seconds are negative and the fractions still add to them
+        assert_eq!(
+            Timestamp::new(-1, 1 << 62).to_unix_nanos(),
+            -2_082_844_800_750_000_000
+        );
+        assert_eq!(
+            Timestamp::new(i64::MIN, 0).to_unix_nanos(),
+            (i64::MIN as i128 - UNIX_EPOCH_SECONDS as i128) * 1_000_000_000
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    pub fn convert_to_datetime() {
+        let datetime = Timestamp::new(UNIX_EPOCH_SECONDS, 1 << 63).to_datetime();
+        assert_eq!(datetime, Some(Utc.timestamp(0, 500_000_000)));
+
+        let datetime = Timestamp::new(-1, 0).to_datetime();
+        assert_eq!(datetime, Some(Utc.ymd(1903, 12, 31).and_hms(23, 59, 59)));
+
+        assert_eq!(Timestamp::new(i64::MAX, 0).to_datetime(), None);
+        assert_eq!(Timestamp::new(i64::MIN, 0).to_datetime(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    pub fn datetime_round_trip() {
+        let datetimes = [
+            Utc.timestamp(0, 0),
+            Utc.timestamp(1_441_706_747, 669_260_594),
+            Utc.timestamp(-2_082_844_801, 999_999_999),
+            Utc.ymd(1800, 6, 1).and_hms_nano(12, 0, 0, 1),
+        ];
+        for datetime in datetimes.iter() {
+            let timestamp = Timestamp::from(*datetime);
+            assert_eq!(timestamp.to_datetime(), Some(*datetime));
+        }
+        assert_eq!(
+            Timestamp::from_datetime(Utc.ymd(1904, 1, 1).and_hms(0, 0, 0)),
+            Timestamp::new(0, 0)
+        );
+    }
+
+    #[test]
+    pub fn convert_from_unix_nanos() {
+        for nanos in [0, 1_500_000_000, -2_082_844_800_750_000_000, -1].iter() {
+            let timestamp = Timestamp::from_unix_nanos(*nanos).unwrap();
+            assert_eq!(timestamp.to_unix_nanos(), *nanos);
+        }
+        assert_eq!(
+            Timestamp::from_unix_nanos(-1),
+            Some(Timestamp::new(
+                UNIX_EPOCH_SECONDS - 1,
+                nanos_to_fractions(999_999_999)
+            ))
+        );
+        assert_eq!(Timestamp::from_unix_nanos(i128::MAX), None);
+    }
+
+    #[test]
+    pub fn format_as_rfc3339() {
+        let rfc3339 = |seconds, nanos| {
+            Timestamp::new(UNIX_EPOCH_SECONDS + seconds, nanos_to_fractions(nanos)).to_rfc3339()
+        };
+
+        assert_eq!(
+            rfc3339(1_441_706_747, 669_260_594).unwrap(),
+            "2015-09-08T10:05:47.669260594+00:00"
+        );
+        assert_eq!(rfc3339(0, 0).unwrap(), "1970-01-01T00:00:00+00:00");
+        assert_eq!(
+            rfc3339(951_782_400, 0).unwrap(),
+            "2000-02-29T00:00:00+00:00"
+        );
+        assert_eq!(
+            rfc3339(-1, 500_000_000).unwrap(),
+            "1969-12-31T23:59:59.500+00:00"
+        );
+        assert_eq!(
+            rfc3339(0, 120_000).unwrap(),
+            "1970-01-01T00:00:00.000120+00:00"
+        );
+        assert_eq!(
+            rfc3339(-62_198_755_200, 0).unwrap(),
+            "-0001-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            rfc3339(253_402_300_800, 0).unwrap(),
+            "+10000-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            Timestamp::new(0, 0).format_rfc3339("Z").unwrap(),
+            "1904-01-01T00:00:00Z"
+        );
+        assert_eq!(Timestamp::new(i64::MAX, 0).to_rfc3339(), None);
+        assert_eq!(Timestamp::new(i64::MIN, 0).to_rfc3339(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    pub fn rfc3339_matches_chrono() {
+        let seconds = [
+            0,
+            1,
+            86_399,
+            -86_401,
+            i32::MAX as i64,
+            -(i32::MAX as i64),
+            8_210_298_412_799,
+            8_210_298_412_800,
+            -8_334_632_851_200,
+            -8_334_632_851_201,
+        ];
+        let fractions = [0, 1, 1 << 63, u64::MAX, nanos_to_fractions(123_000_000)];
+        for seconds in seconds.iter() {
+            for fractions in fractions.iter() {
+                let timestamp = Timestamp::new(UNIX_EPOCH_SECONDS + *seconds, *fractions);
+                assert_eq!(
+                    timestamp.to_rfc3339(),
+                    timestamp
+                        .to_datetime()
+                        .map(|datetime| datetime.to_rfc3339()),
+                    "{:?}",
+                    timestamp
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn fraction_conversion_round_trip() {
+        for nanos in [0, 1, 2, 499_999_999, 500_000_000, 999_999_999].iter() {
+            assert_eq!(fractions_to_nanos(nanos_to_fractions(*nanos)), *nanos);
+        }
+        assert_eq!(nanos_to_fractions(500_000_000), 1 << 63);
+        assert_eq!(fractions_to_nanos(u64::MAX), 999_999_999);
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    pub fn add_and_subtract_durations() {
+        let timestamp = Timestamp::new(100, 3 << 62);
+
+        assert_eq!(
+            timestamp + Duration::milliseconds(500),
+            Timestamp::new(101, 1 << 62)
+        );
+        assert_eq!(
+            timestamp - Duration::milliseconds(1250),
+            Timestamp::new(99, 1 << 63)
+        );
+        assert_eq!(
+            timestamp + Duration::seconds(-200),
+            Timestamp::new(-100, 3 << 62)
+        );
+        // Whole seconds leave the fractions exactly unchanged
+        let timestamp = Timestamp::new(5, 1234567890123456789);
+        assert_eq!(
+            timestamp + Duration::seconds(10),
+            Timestamp::new(15, 1234567890123456789)
+        );
+        assert_eq!(
+            Timestamp::new(i64::MAX, 0).checked_add(Duration::seconds(1)),
+            None
+        );
+    }
+
+    #[test]
+    pub fn timestamps_are_ordered_chronologically() {
+        let mut timestamps = vec![
+            Timestamp::new(1, 0),
+            Timestamp::new(0, u64::MAX),
+            Timestamp::new(-1, 1 << 63),
+            Timestamp::new(1, 1),
+        ];
+        timestamps.sort();
+
+        assert_eq!(
+            timestamps,
+            vec![
+                Timestamp::new(-1, 1 << 63),
+                Timestamp::new(0, u64::MAX),
+                Timestamp::new(1, 0),
+                Timestamp::new(1, 1),
+            ]
+        );
     }
 }
@@ -1,6 +1,7 @@
 use num_enum::IntoPrimitive;
 
-#[derive(IntoPrimitive, Debug)]
+/// A flag in the table of contents of a segment lead in, describing what the segment contains
+#[derive(IntoPrimitive, Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 pub enum TocFlag {
     MetaData = 1 << 1,
@@ -11,7 +12,45 @@ pub enum TocFlag {
     DaqMxRawData = 1 << 7,
 }
 
-#[derive(Debug)]
+/// All flags with a known meaning, in order of their bit position
+const ALL_FLAGS: [TocFlag; 6] = [
+    TocFlag::MetaData,
+    TocFlag::NewObjList,
+    TocFlag::RawData,
+    TocFlag::InterleavedData,
+    TocFlag::BigEndian,
+    TocFlag::DaqMxRawData,
+];
+
+/// Bits of all flags with a known meaning
+const KNOWN_FLAGS: u32 = TocFlag::MetaData as u32
+    | TocFlag::NewObjList as u32
+    | TocFlag::RawData as u32
+    | TocFlag::InterleavedData as u32
+    | TocFlag::BigEndian as u32
+    | TocFlag::DaqMxRawData as u32;
+
+impl TocFlag {
+    /// Iterate over all flags with a known meaning, in order of their bit position
+    pub fn all() -> impl Iterator<Item = TocFlag> {
+        ALL_FLAGS.iter().copied()
+    }
+
+    /// Name of the flag, eg. "MetaData" for the kTocMetaData flag
+    pub fn name(&self) -> &'static str {
+        match *self {
+            TocFlag::MetaData => "MetaData",
+            TocFlag::NewObjList => "NewObjList",
+            TocFlag::RawData => "RawData",
+            TocFlag::InterleavedData => "InterleavedData",
+            TocFlag::BigEndian => "BigEndian",
+            TocFlag::DaqMxRawData => "DAQmxRawData",
+        }
+    }
+}
+
+/// The table of contents flags of a segment, including any bits without a known meaning
+#[derive(Debug, Clone, Copy)]
 pub struct TocMask {
     flags: u32,
 }
@@ -25,6 +64,21 @@ impl TocMask {
         let flag_val: u32 = flag.into();
         (self.flags & flag_val) == flag_val
     }
+
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Iterate over the known flags that are set
+    pub fn known_flags(&self) -> impl Iterator<Item = TocFlag> + '_ {
+        TocFlag::all().filter(move |flag| self.has_flag(*flag))
+    }
+
+    /// Bits that are set but don't correspond to any known flag. These may be used by
+    /// newer versions of the format to change the segment layout.
+    pub fn unknown_bits(&self) -> u32 {
+        self.flags & !KNOWN_FLAGS
+    }
 }
 
 impl std::fmt::Display for TocMask {
@@ -49,4 +103,26 @@ mod test {
         assert_eq!(toc_mask.has_flag(TocFlag::BigEndian), false);
         assert_eq!(toc_mask.has_flag(TocFlag::DaqMxRawData), false);
     }
+
+    #[test]
+    pub fn can_find_unknown_bits() {
+        assert_eq!(TocMask::from_flags(0xEE).unknown_bits(), 0);
+        assert_eq!(TocMask::from_flags(0x10E).unknown_bits(), 0x100);
+        assert_eq!(TocMask::from_flags(0x11).unknown_bits(), 0x11);
+    }
+
+    #[test]
+    pub fn can_iterate_known_flags() {
+        let all_bits = TocFlag::all().fold(0, |bits, flag| bits | u32::from(flag));
+        assert_eq!(all_bits, KNOWN_FLAGS);
+
+        let toc_mask = TocMask::from_flags(0x14A);
+        let flags: Vec<TocFlag> = toc_mask.known_flags().collect();
+
+        assert_eq!(
+            flags,
+            vec![TocFlag::MetaData, TocFlag::RawData, TocFlag::BigEndian]
+        );
+        assert_eq!(toc_mask.unknown_bits(), 0x100);
+    }
 }
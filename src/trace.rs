@@ -0,0 +1,17 @@
+/// Enter a tracing span at debug level for the rest of the enclosing block.
+/// This does nothing unless the `tracing` feature is enabled.
+macro_rules! debug_span {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($($args)*).entered();
+    };
+}
+
+/// Enter a tracing span at trace level for the rest of the enclosing block.
+/// This does nothing unless the `tracing` feature is enabled.
+macro_rules! trace_span {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!($($args)*).entered();
+    };
+}
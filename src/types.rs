@@ -1,44 +1,119 @@
 use crate::error::{Result, TdmsReadError};
 use crate::timestamp::Timestamp;
-use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
-use num_enum::TryFromPrimitive;
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::convert::TryFrom;
-use std::io::Read;
+use std::fmt;
+use std::io::{Read, Write};
 
-#[derive(Clone, Copy, TryFromPrimitive, Debug, PartialEq, Eq)]
-#[repr(u32)]
+/// The data type of a property value or of channel raw data, with the type codes used in TDMS files.
+/// Converting from a type code with `TdsType::try_from` fails for codes that are not valid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TdsType {
-    Void = 0,
-    I8 = 1,
-    I16 = 2,
-    I32 = 3,
-    I64 = 4,
-    U8 = 5,
-    U16 = 6,
-    U32 = 7,
-    U64 = 8,
-    SingleFloat = 9,
-    DoubleFloat = 10,
-    ExtendedFloat = 11,
-    SingleFloatWithUnit = 0x19,
-    DoubleFloatWithUnit = 0x1A,
-    ExtendedFloatWithUnit = 0x1B,
-    String = 0x20,
-    Boolean = 0x21,
-    TimeStamp = 0x44,
-    FixedPoint = 0x4F,
-    ComplexSingleFloat = 0x08000C,
-    ComplexDoubleFloat = 0x10000D,
-    DaqmxRawData = 0xFFFFFFFF,
+    Void,
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    SingleFloat,
+    DoubleFloat,
+    ExtendedFloat,
+    SingleFloatWithUnit,
+    DoubleFloatWithUnit,
+    ExtendedFloatWithUnit,
+    String,
+    Boolean,
+    TimeStamp,
+    FixedPoint,
+    ComplexSingleFloat,
+    ComplexDoubleFloat,
+    DaqmxRawData,
+    /// A vendor specific type registered with `ReadOptions::register_custom_type`,
+    /// with values of `size` bytes. Type codes are never converted to custom types
+    /// by `TdsType::try_from`.
+    Custom {
+        code: u32,
+        size: usize,
+    },
+}
+
+impl TryFrom<u32> for TdsType {
+    type Error = TdmsReadError;
+
+    fn try_from(code: u32) -> Result<TdsType> {
+        Ok(match code {
+            0 => TdsType::Void,
+            1 => TdsType::I8,
+            2 => TdsType::I16,
+            3 => TdsType::I32,
+            4 => TdsType::I64,
+            5 => TdsType::U8,
+            6 => TdsType::U16,
+            7 => TdsType::U32,
+            8 => TdsType::U64,
+            9 => TdsType::SingleFloat,
+            10 => TdsType::DoubleFloat,
+            11 => TdsType::ExtendedFloat,
+            0x19 => TdsType::SingleFloatWithUnit,
+            0x1A => TdsType::DoubleFloatWithUnit,
+            0x1B => TdsType::ExtendedFloatWithUnit,
+            0x20 => TdsType::String,
+            0x21 => TdsType::Boolean,
+            0x44 => TdsType::TimeStamp,
+            0x4F => TdsType::FixedPoint,
+            0x08000C => TdsType::ComplexSingleFloat,
+            0x10000D => TdsType::ComplexDoubleFloat,
+            0xFFFFFFFF => TdsType::DaqmxRawData,
+            _ => {
+                return Err(TdmsReadError::InvalidData(format!(
+                    "Invalid type id: {}",
+                    code
+                )))
+            }
+        })
+    }
 }
 
 impl TdsType {
     pub fn from_u32(type_id_raw: u32) -> Result<TdsType> {
         TdsType::try_from(type_id_raw)
-            .map_err(|_| TdmsReadError::TdmsError(format!("Invalid type id: {}", type_id_raw)))
     }
 
-    pub fn size(&self) -> Option<u32> {
+    /// The type code used for this type in TDMS files
+    pub fn to_u32(self) -> u32 {
+        match self {
+            TdsType::Void => 0,
+            TdsType::I8 => 1,
+            TdsType::I16 => 2,
+            TdsType::I32 => 3,
+            TdsType::I64 => 4,
+            TdsType::U8 => 5,
+            TdsType::U16 => 6,
+            TdsType::U32 => 7,
+            TdsType::U64 => 8,
+            TdsType::SingleFloat => 9,
+            TdsType::DoubleFloat => 10,
+            TdsType::ExtendedFloat => 11,
+            TdsType::SingleFloatWithUnit => 0x19,
+            TdsType::DoubleFloatWithUnit => 0x1A,
+            TdsType::ExtendedFloatWithUnit => 0x1B,
+            TdsType::String => 0x20,
+            TdsType::Boolean => 0x21,
+            TdsType::TimeStamp => 0x44,
+            TdsType::FixedPoint => 0x4F,
+            TdsType::ComplexSingleFloat => 0x08000C,
+            TdsType::ComplexDoubleFloat => 0x10000D,
+            TdsType::DaqmxRawData => 0xFFFFFFFF,
+            TdsType::Custom { code, .. } => code,
+        }
+    }
+
+    /// Size in bytes of a single value, or None if values don't have a fixed size
+    pub fn size(&self) -> Option<usize> {
         match *self {
             TdsType::Void => Some(0),
             TdsType::I8 => Some(1),
@@ -62,9 +137,59 @@ impl TdsType {
             TdsType::ComplexSingleFloat => Some(8),
             TdsType::ComplexDoubleFloat => Some(16),
             TdsType::DaqmxRawData => None,
+            TdsType::Custom { size, .. } => Some(size),
         }
     }
 
+    /// Whether this is a signed or unsigned integer type
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            *self,
+            TdsType::I8
+                | TdsType::I16
+                | TdsType::I32
+                | TdsType::I64
+                | TdsType::U8
+                | TdsType::U16
+                | TdsType::U32
+                | TdsType::U64
+        )
+    }
+
+    /// Whether this is a real floating point type, with or without a unit
+    pub fn is_float(&self) -> bool {
+        matches!(
+            *self,
+            TdsType::SingleFloat
+                | TdsType::DoubleFloat
+                | TdsType::ExtendedFloat
+                | TdsType::SingleFloatWithUnit
+                | TdsType::DoubleFloatWithUnit
+                | TdsType::ExtendedFloatWithUnit
+        )
+    }
+
+    /// Whether this is a complex floating point type
+    pub fn is_complex(&self) -> bool {
+        matches!(
+            *self,
+            TdsType::ComplexSingleFloat | TdsType::ComplexDoubleFloat
+        )
+    }
+
+    /// Whether this is any integer, floating point, complex or fixed point type
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || self.is_float() || self.is_complex() || *self == TdsType::FixedPoint
+    }
+
+    pub fn is_string(&self) -> bool {
+        *self == TdsType::String
+    }
+
+    pub fn is_timestamp(&self) -> bool {
+        *self == TdsType::TimeStamp
+    }
+
     pub fn native_type(&self) -> Option<NativeTypeId> {
         match *self {
             TdsType::Void => None,
@@ -89,12 +214,45 @@ impl TdsType {
             TdsType::ComplexSingleFloat => None,
             TdsType::ComplexDoubleFloat => None,
             TdsType::DaqmxRawData => None,
+            TdsType::Custom { .. } => None,
         }
     }
 }
 
+/// Short names of types as used by LabVIEW, eg. "DBL" for a double precision float
+impl fmt::Display for TdsType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            TdsType::Void => "Void",
+            TdsType::I8 => "I8",
+            TdsType::I16 => "I16",
+            TdsType::I32 => "I32",
+            TdsType::I64 => "I64",
+            TdsType::U8 => "U8",
+            TdsType::U16 => "U16",
+            TdsType::U32 => "U32",
+            TdsType::U64 => "U64",
+            TdsType::SingleFloat => "SGL",
+            TdsType::DoubleFloat => "DBL",
+            TdsType::ExtendedFloat => "EXT",
+            TdsType::SingleFloatWithUnit => "SGL with unit",
+            TdsType::DoubleFloatWithUnit => "DBL with unit",
+            TdsType::ExtendedFloatWithUnit => "EXT with unit",
+            TdsType::String => "String",
+            TdsType::Boolean => "Boolean",
+            TdsType::TimeStamp => "Timestamp",
+            TdsType::FixedPoint => "FXP",
+            TdsType::ComplexSingleFloat => "CSG",
+            TdsType::ComplexDoubleFloat => "CDB",
+            TdsType::DaqmxRawData => "DAQmx raw data",
+            TdsType::Custom { code, .. } => return f.pad(&format!("Custom {:#x}", code)),
+        };
+        f.pad(name)
+    }
+}
+
 /// Represents a native rust type that TDMS channel data can be read as.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NativeTypeId {
     I8,
     I16,
@@ -111,10 +269,16 @@ pub enum NativeTypeId {
 
 /// A native rust type that TDMS channel data can be read as.
 /// This is a sealed trait that cannot be implemented outside this crate.
-pub trait NativeType: private::SealedNativeType + Sized {
+pub trait NativeType: private::SealedNativeType + Sized + Copy + Default + Send + 'static {
     #[doc(hidden)]
     fn native_type() -> NativeTypeId;
 
+    #[doc(hidden)]
+    fn tds_type() -> TdsType;
+
+    #[doc(hidden)]
+    fn write_values<W: Write>(values: &[Self], writer: &mut W) -> Result<()>;
+
     #[doc(hidden)]
     fn read_values<R: Read, O: ByteOrderExt>(
         target_buffer: &mut [Self],
@@ -128,6 +292,17 @@ impl NativeType for i8 {
         NativeTypeId::I8
     }
 
+    fn tds_type() -> TdsType {
+        TdsType::I8
+    }
+
+    fn write_values<W: Write>(values: &[Self], writer: &mut W) -> Result<()> {
+        for value in values {
+            writer.write_i8(*value)?;
+        }
+        Ok(())
+    }
+
     fn read_values<R: Read, O: ByteOrderExt>(
         target_buffer: &mut [Self],
         reader: &mut R,
@@ -143,6 +318,17 @@ impl NativeType for i16 {
         NativeTypeId::I16
     }
 
+    fn tds_type() -> TdsType {
+        TdsType::I16
+    }
+
+    fn write_values<W: Write>(values: &[Self], writer: &mut W) -> Result<()> {
+        for value in values {
+            writer.write_i16::<LittleEndian>(*value)?;
+        }
+        Ok(())
+    }
+
     fn read_values<R: Read, O: ByteOrderExt>(
         target_buffer: &mut [Self],
         reader: &mut R,
@@ -158,6 +344,17 @@ impl NativeType for i32 {
         NativeTypeId::I32
     }
 
+    fn tds_type() -> TdsType {
+        TdsType::I32
+    }
+
+    fn write_values<W: Write>(values: &[Self], writer: &mut W) -> Result<()> {
+        for value in values {
+            writer.write_i32::<LittleEndian>(*value)?;
+        }
+        Ok(())
+    }
+
     fn read_values<R: Read, O: ByteOrderExt>(
         target_buffer: &mut [Self],
         reader: &mut R,
@@ -173,6 +370,17 @@ impl NativeType for i64 {
         NativeTypeId::I64
     }
 
+    fn tds_type() -> TdsType {
+        TdsType::I64
+    }
+
+    fn write_values<W: Write>(values: &[Self], writer: &mut W) -> Result<()> {
+        for value in values {
+            writer.write_i64::<LittleEndian>(*value)?;
+        }
+        Ok(())
+    }
+
     fn read_values<R: Read, O: ByteOrderExt>(
         target_buffer: &mut [Self],
         reader: &mut R,
@@ -188,6 +396,17 @@ impl NativeType for u8 {
         NativeTypeId::U8
     }
 
+    fn tds_type() -> TdsType {
+        TdsType::U8
+    }
+
+    fn write_values<W: Write>(values: &[Self], writer: &mut W) -> Result<()> {
+        for value in values {
+            writer.write_u8(*value)?;
+        }
+        Ok(())
+    }
+
     fn read_values<R: Read, O: ByteOrderExt>(
         target_buffer: &mut [Self],
         reader: &mut R,
@@ -203,6 +422,17 @@ impl NativeType for u16 {
         NativeTypeId::U16
     }
 
+    fn tds_type() -> TdsType {
+        TdsType::U16
+    }
+
+    fn write_values<W: Write>(values: &[Self], writer: &mut W) -> Result<()> {
+        for value in values {
+            writer.write_u16::<LittleEndian>(*value)?;
+        }
+        Ok(())
+    }
+
     fn read_values<R: Read, O: ByteOrderExt>(
         target_buffer: &mut [Self],
         reader: &mut R,
@@ -218,6 +448,17 @@ impl NativeType for u32 {
         NativeTypeId::U32
     }
 
+    fn tds_type() -> TdsType {
+        TdsType::U32
+    }
+
+    fn write_values<W: Write>(values: &[Self], writer: &mut W) -> Result<()> {
+        for value in values {
+            writer.write_u32::<LittleEndian>(*value)?;
+        }
+        Ok(())
+    }
+
     fn read_values<R: Read, O: ByteOrderExt>(
         target_buffer: &mut [Self],
         reader: &mut R,
@@ -233,6 +474,17 @@ impl NativeType for u64 {
         NativeTypeId::U64
     }
 
+    fn tds_type() -> TdsType {
+        TdsType::U64
+    }
+
+    fn write_values<W: Write>(values: &[Self], writer: &mut W) -> Result<()> {
+        for value in values {
+            writer.write_u64::<LittleEndian>(*value)?;
+        }
+        Ok(())
+    }
+
     fn read_values<R: Read, O: ByteOrderExt>(
         target_buffer: &mut [Self],
         reader: &mut R,
@@ -248,6 +500,17 @@ impl NativeType for f32 {
         NativeTypeId::F32
     }
 
+    fn tds_type() -> TdsType {
+        TdsType::SingleFloat
+    }
+
+    fn write_values<W: Write>(values: &[Self], writer: &mut W) -> Result<()> {
+        for value in values {
+            writer.write_f32::<LittleEndian>(*value)?;
+        }
+        Ok(())
+    }
+
     fn read_values<R: Read, O: ByteOrderExt>(
         target_buffer: &mut [Self],
         reader: &mut R,
@@ -263,6 +526,17 @@ impl NativeType for f64 {
         NativeTypeId::F64
     }
 
+    fn tds_type() -> TdsType {
+        TdsType::DoubleFloat
+    }
+
+    fn write_values<W: Write>(values: &[Self], writer: &mut W) -> Result<()> {
+        for value in values {
+            writer.write_f64::<LittleEndian>(*value)?;
+        }
+        Ok(())
+    }
+
     fn read_values<R: Read, O: ByteOrderExt>(
         target_buffer: &mut [Self],
         reader: &mut R,
@@ -278,13 +552,31 @@ impl NativeType for Timestamp {
         NativeTypeId::Timestamp
     }
 
+    fn tds_type() -> TdsType {
+        TdsType::TimeStamp
+    }
+
+    fn write_values<W: Write>(values: &[Self], writer: &mut W) -> Result<()> {
+        for value in values {
+            write_timestamp(writer, value)?;
+        }
+        Ok(())
+    }
+
     fn read_values<R: Read, O: ByteOrderExt>(
         target_buffer: &mut [Self],
         reader: &mut R,
         num_values: usize,
     ) -> Result<()> {
-        for i in 0..num_values {
-            target_buffer[i] = read_timestamp::<_, O>(reader)?;
+        // Read all values at once, as reading each timestamp separately is slow
+        // for interleaved data and unbuffered readers
+        let mut bytes = vec![0; num_values * TIMESTAMP_SIZE];
+        reader.read_exact(&mut bytes)?;
+        for (value, value_bytes) in target_buffer[0..num_values]
+            .iter_mut()
+            .zip(bytes.chunks_exact(TIMESTAMP_SIZE))
+        {
+            *value = O::read_timestamp(value_bytes);
         }
         Ok(())
     }
@@ -293,17 +585,46 @@ impl NativeType for Timestamp {
 pub fn read_string<R: Read, O: ByteOrder>(reader: &mut R) -> Result<String> {
     let string_length = reader.read_u32::<O>()?;
 
-    let mut string_bytes = vec![0; string_length as usize];
-    reader.read_exact(&mut string_bytes)?;
+    // Read up to the length rather than allocating it up front, so that a corrupt
+    // length in a truncated file fails at the end of the file without a huge allocation
+    let mut string_bytes = Vec::with_capacity((string_length as usize).min(STRING_CAPACITY_LIMIT));
+    reader
+        .by_ref()
+        .take(string_length as u64)
+        .read_to_end(&mut string_bytes)?;
+    if string_bytes.len() < string_length as usize {
+        return Err(TdmsReadError::IoError(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "String ends before its length",
+        )));
+    }
     Ok(String::from_utf8(string_bytes)?)
 }
 
+/// Largest number of bytes allocated for a string before any of it has been read
+const STRING_CAPACITY_LIMIT: usize = 64 * 1024;
+
+/// Size in bytes of a timestamp value
+const TIMESTAMP_SIZE: usize = 16;
+
 pub fn read_timestamp<R: Read, O: ByteOrderExt>(reader: &mut R) -> std::io::Result<Timestamp> {
-    let mut buf = [0; 16];
+    let mut buf = [0; TIMESTAMP_SIZE];
     reader.read_exact(&mut buf)?;
     Ok(O::read_timestamp(&buf))
 }
 
+pub fn write_string<W: Write>(writer: &mut W, value: &str) -> Result<()> {
+    writer.write_u32::<LittleEndian>(value.len() as u32)?;
+    writer.write_all(value.as_bytes())?;
+    Ok(())
+}
+
+pub fn write_timestamp<W: Write>(writer: &mut W, value: &Timestamp) -> Result<()> {
+    writer.write_u64::<LittleEndian>(value.second_fractions())?;
+    writer.write_i64::<LittleEndian>(value.seconds())?;
+    Ok(())
+}
+
 pub trait ByteOrderExt: ByteOrder {
     fn read_timestamp(buf: &[u8]) -> Timestamp;
 }
@@ -366,4 +687,159 @@ mod test {
 
         assert_eq!(value, "hello");
     }
+
+    #[test]
+    pub fn can_read_timestamp_values() {
+        let timestamps = vec![
+            Timestamp::new(3524551547, 1234567890),
+            Timestamp::new(-1, 0),
+            Timestamp::new(0, u64::MAX),
+        ];
+        let mut bytes = Vec::new();
+        Timestamp::write_values(&timestamps, &mut bytes).unwrap();
+        let mut reader = Cursor::new(bytes);
+        let mut values = vec![Timestamp::new(0, 0); 4];
+        Timestamp::read_values::<_, LittleEndian>(&mut values, &mut reader, 3).unwrap();
+
+        assert_eq!(&values[0..3], &timestamps[..]);
+        assert_eq!(values[3], Timestamp::new(0, 0));
+    }
+
+    #[test]
+    pub fn can_read_timestamp_le_and_be() {
+        let mut reader = Cursor::new(hex!("00 00 00 00 00 00 00 80 7B 00 00 00 00 00 00 00"));
+        let value = read_timestamp::<_, LittleEndian>(&mut reader).unwrap();
+        assert_eq!(value, Timestamp::new(123, 1 << 63));
+
+        let mut reader = Cursor::new(hex!("FF FF FF FF FF FF FF FF 40 00 00 00 00 00 00 00"));
+        let value = read_timestamp::<_, BigEndian>(&mut reader).unwrap();
+        assert_eq!(value, Timestamp::new(-1, 1 << 62));
+    }
+
+    const ALL_TYPES: [(TdsType, u32, &str); 22] = [
+        (TdsType::Void, 0, "Void"),
+        (TdsType::I8, 1, "I8"),
+        (TdsType::I16, 2, "I16"),
+        (TdsType::I32, 3, "I32"),
+        (TdsType::I64, 4, "I64"),
+        (TdsType::U8, 5, "U8"),
+        (TdsType::U16, 6, "U16"),
+        (TdsType::U32, 7, "U32"),
+        (TdsType::U64, 8, "U64"),
+        (TdsType::SingleFloat, 9, "SGL"),
+        (TdsType::DoubleFloat, 10, "DBL"),
+        (TdsType::ExtendedFloat, 11, "EXT"),
+        (TdsType::SingleFloatWithUnit, 0x19, "SGL with unit"),
+        (TdsType::DoubleFloatWithUnit, 0x1A, "DBL with unit"),
+        (TdsType::ExtendedFloatWithUnit, 0x1B, "EXT with unit"),
+        (TdsType::String, 0x20, "String"),
+        (TdsType::Boolean, 0x21, "Boolean"),
+        (TdsType::TimeStamp, 0x44, "Timestamp"),
+        (TdsType::FixedPoint, 0x4F, "FXP"),
+        (TdsType::ComplexSingleFloat, 0x08000C, "CSG"),
+        (TdsType::ComplexDoubleFloat, 0x10000D, "CDB"),
+        (TdsType::DaqmxRawData, 0xFFFFFFFF, "DAQmx raw data"),
+    ];
+
+    #[test]
+    pub fn type_codes_round_trip() {
+        for (data_type, code, _) in ALL_TYPES.iter() {
+            assert_eq!(TdsType::try_from(*code).unwrap(), *data_type);
+            assert_eq!(TdsType::from_u32(*code).unwrap(), *data_type);
+            assert_eq!(data_type.to_u32(), *code);
+        }
+    }
+
+    #[test]
+    pub fn invalid_type_codes_are_rejected() {
+        let valid_codes: Vec<u32> = ALL_TYPES.iter().map(|(_, code, _)| *code).collect();
+        for code in (0..0x100).chain([0x08000B, 0x08000D, 0x10000C, 0xFFFFFFFE]) {
+            if valid_codes.contains(&code) {
+                continue;
+            }
+            assert!(TdsType::try_from(code).is_err(), "code {:#x}", code);
+            assert!(TdsType::from_u32(code).is_err(), "code {:#x}", code);
+        }
+    }
+
+    #[test]
+    pub fn custom_types() {
+        let data_type = TdsType::Custom {
+            code: 0x3000,
+            size: 12,
+        };
+
+        assert_eq!(data_type.to_u32(), 0x3000);
+        assert_eq!(data_type.size(), Some(12));
+        assert_eq!(data_type.native_type(), None);
+        assert!(!data_type.is_numeric());
+        assert_eq!(data_type.to_string(), "Custom 0x3000");
+        assert!(TdsType::try_from(0x3000).is_err());
+    }
+
+    #[test]
+    pub fn type_names() {
+        for (data_type, _, name) in ALL_TYPES.iter() {
+            assert_eq!(data_type.to_string(), *name);
+        }
+        assert_eq!(format!("{:<5}|", TdsType::I8), "I8   |");
+    }
+
+    #[test]
+    pub fn type_categories() {
+        for (data_type, _, _) in ALL_TYPES.iter() {
+            let categories = [
+                data_type.is_integer(),
+                data_type.is_float(),
+                data_type.is_complex(),
+                data_type.is_string(),
+                data_type.is_timestamp(),
+            ];
+            let category_count = categories
+                .iter()
+                .filter(|is_category| **is_category)
+                .count();
+            let expected_count = match data_type {
+                TdsType::Void | TdsType::Boolean | TdsType::FixedPoint | TdsType::DaqmxRawData => 0,
+                _ => 1,
+            };
+            assert_eq!(category_count, expected_count, "{:?}", data_type);
+            assert_eq!(
+                data_type.is_numeric(),
+                data_type.is_integer()
+                    || data_type.is_float()
+                    || data_type.is_complex()
+                    || *data_type == TdsType::FixedPoint,
+                "{:?}",
+                data_type
+            );
+        }
+        assert!(TdsType::U16.is_integer());
+        assert!(TdsType::DoubleFloatWithUnit.is_float());
+        assert!(TdsType::ComplexSingleFloat.is_numeric());
+        assert!(!TdsType::Boolean.is_numeric());
+        assert!(TdsType::String.is_string());
+        assert!(TdsType::TimeStamp.is_timestamp());
+    }
+
+    #[test]
+    pub fn type_sizes() {
+        for (data_type, _, _) in ALL_TYPES.iter() {
+            let expected_size = match data_type {
+                TdsType::String | TdsType::FixedPoint | TdsType::DaqmxRawData => None,
+                TdsType::Void => Some(0),
+                TdsType::I8 | TdsType::U8 | TdsType::Boolean => Some(1),
+                TdsType::I16 | TdsType::U16 => Some(2),
+                TdsType::I32 | TdsType::U32 => Some(4),
+                TdsType::SingleFloat | TdsType::SingleFloatWithUnit => Some(4),
+                TdsType::I64 | TdsType::U64 => Some(8),
+                TdsType::DoubleFloat | TdsType::DoubleFloatWithUnit => Some(8),
+                TdsType::ComplexSingleFloat => Some(8),
+                TdsType::ExtendedFloat | TdsType::ExtendedFloatWithUnit => Some(16),
+                TdsType::TimeStamp | TdsType::ComplexDoubleFloat => Some(16),
+                TdsType::Custom { size, .. } => Some(*size),
+            };
+            assert_eq!(data_type.size(), expected_size, "{:?}", data_type);
+        }
+    }
 }
@@ -0,0 +1,304 @@
+use crate::error::{Result, TdmsReadError};
+use crate::object_path::{ObjectPath, ObjectPathId};
+use crate::timestamp::Timestamp;
+use crate::types::{NativeType, NativeTypeId, TdsType};
+use crate::{Channel, TdmsFile};
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+use std::ops::ControlFlow;
+
+/// A chunk of channel values passed to a `DataVisitor`, borrowed for the duration of
+/// the call. Numeric and timestamp values are as stored in the file, without applying
+/// any scaling, except for DAQmx raw data and custom types, which are converted to f64.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValuesRef<'a> {
+    I8(&'a [i8]),
+    I16(&'a [i16]),
+    I32(&'a [i32]),
+    I64(&'a [i64]),
+    U8(&'a [u8]),
+    U16(&'a [u16]),
+    U32(&'a [u32]),
+    U64(&'a [u64]),
+    F32(&'a [f32]),
+    F64(&'a [f64]),
+    Timestamp(&'a [Timestamp]),
+    String(&'a [String]),
+}
+
+impl<'a> ValuesRef<'a> {
+    /// Number of values in the chunk
+    pub fn len(&self) -> usize {
+        match self {
+            ValuesRef::I8(values) => values.len(),
+            ValuesRef::I16(values) => values.len(),
+            ValuesRef::I32(values) => values.len(),
+            ValuesRef::I64(values) => values.len(),
+            ValuesRef::U8(values) => values.len(),
+            ValuesRef::U16(values) => values.len(),
+            ValuesRef::U32(values) => values.len(),
+            ValuesRef::U64(values) => values.len(),
+            ValuesRef::F32(values) => values.len(),
+            ValuesRef::F64(values) => values.len(),
+            ValuesRef::Timestamp(values) => values.len(),
+            ValuesRef::String(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Call `f` with each value converted to f64 if the values are numeric, returning
+    /// false without calling `f` for timestamp and string values
+    pub fn for_each_f64<F: FnMut(f64)>(&self, mut f: F) -> bool {
+        fn each<T: Copy + Into<f64>, F: FnMut(f64)>(values: &[T], f: &mut F) -> bool {
+            values.iter().for_each(|value| f((*value).into()));
+            true
+        }
+        match self {
+            ValuesRef::I8(values) => each(values, &mut f),
+            ValuesRef::I16(values) => each(values, &mut f),
+            ValuesRef::I32(values) => each(values, &mut f),
+            ValuesRef::I64(values) => {
+                values.iter().for_each(|value| f(*value as f64));
+                true
+            }
+            ValuesRef::U8(values) => each(values, &mut f),
+            ValuesRef::U16(values) => each(values, &mut f),
+            ValuesRef::U32(values) => each(values, &mut f),
+            ValuesRef::U64(values) => {
+                values.iter().for_each(|value| f(*value as f64));
+                true
+            }
+            ValuesRef::F32(values) => each(values, &mut f),
+            ValuesRef::F64(values) => each(values, &mut f),
+            ValuesRef::Timestamp(_) | ValuesRef::String(_) => false,
+        }
+    }
+}
+
+/// Receives the data of every channel in a file, a chunk at a time, from
+/// `TdmsFile::visit_data`
+pub trait DataVisitor {
+    /// Whether to visit the values of the channel with path `path`, called once for each
+    /// channel before its first chunk. Data of channels that aren't visited isn't read.
+    fn visit_channel(&mut self, _path: &str, _data_type: TdsType) -> bool {
+        true
+    }
+
+    /// Receive a chunk of values of the channel with path `path`, in file order.
+    /// Returning `ControlFlow::Break` stops visiting without reading any more data.
+    fn chunk(&mut self, path: &str, data_type: TdsType, values: ValuesRef<'_>) -> ControlFlow<()>;
+}
+
+/// The values of a channel in one segment, found by `for_each_segment_values`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SegmentValues {
+    pub segment_index: usize,
+
+    /// Index of the first of the segment's values within all of the channel's values
+    pub start_value: u64,
+
+    pub value_count: u64,
+}
+
+/// Call `visit` with each channel that has values in each segment of a file from the
+/// segment at index `first_segment`, making a single pass through the segments in file
+/// order. Within a segment, channels are visited in the order of the segment's objects.
+/// Stops when `visit` returns an error or `ControlFlow::Break`.
+pub(crate) fn for_each_segment_values<'a, R, F>(
+    tdms_file: &'a TdmsFile<R>,
+    first_segment: usize,
+    mut visit: F,
+) -> Result<()>
+where
+    R: Read + Seek,
+    F: FnMut(&Channel<'a, R>, SegmentValues) -> Result<ControlFlow<()>>,
+{
+    let tdms_reader = &tdms_file.tdms_reader;
+    let segments = tdms_reader.segments().iter().enumerate();
+    for (segment_index, segment) in segments.skip(first_segment) {
+        for object in tdms_reader.object_list(segment).objects() {
+            if object.raw_data_index.is_none() || !is_channel(tdms_file, object.object_id) {
+                continue;
+            }
+            let (start_value, value_count) = match tdms_reader
+                .get_channel_data_index(object.object_id)
+                .and_then(|channel_data_index| channel_data_index.segment_values(segment_index))
+            {
+                Some((start_value, value_count)) if value_count > 0 => (start_value, value_count),
+                _ => continue,
+            };
+            let channel = Channel::new(tdms_file, object.object_id);
+            let segment_values = SegmentValues {
+                segment_index,
+                start_value,
+                value_count,
+            };
+            if visit(&channel, segment_values)?.is_break() {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_channel<R: Read + Seek>(tdms_file: &TdmsFile<R>, object_id: ObjectPathId) -> bool {
+    matches!(
+        tdms_file.tdms_reader.get_object_path(object_id),
+        Some(ObjectPath::Channel(_, _))
+    )
+}
+
+/// Pass the data of every channel in a file to a visitor in a single pass through the
+/// file, reading at most `chunk_size` values at a time, or the number given by the
+/// file's chunk configuration if None
+pub(crate) fn visit_data<R: Read + Seek>(
+    tdms_file: &TdmsFile<R>,
+    visitor: &mut dyn DataVisitor,
+    chunk_size: Option<usize>,
+) -> Result<()> {
+    // Whether each channel is visited, decided by the visitor when the channel is first seen
+    let mut visited_channels: HashMap<ObjectPathId, bool> = HashMap::new();
+    for_each_segment_values(tdms_file, 0, |channel, segment_values| {
+        let data_type = match channel.data_type() {
+            Some(data_type) if is_visitable(data_type) => data_type,
+            _ => return Ok(ControlFlow::Continue(())),
+        };
+        let visited = *visited_channels
+            .entry(channel.object_id)
+            .or_insert_with(|| visitor.visit_channel(channel.path(), data_type));
+        if !visited {
+            return Ok(ControlFlow::Continue(()));
+        }
+        let mut visit = ValueVisit {
+            channel,
+            data_type,
+            segment_values,
+            chunk_size,
+            visitor: &mut *visitor,
+        };
+        match data_type.native_type() {
+            Some(NativeTypeId::I8) => visit.native(|values| ValuesRef::I8(values)),
+            Some(NativeTypeId::I16) => visit.native(|values| ValuesRef::I16(values)),
+            Some(NativeTypeId::I32) => visit.native(|values| ValuesRef::I32(values)),
+            Some(NativeTypeId::I64) => visit.native(|values| ValuesRef::I64(values)),
+            Some(NativeTypeId::U8) => visit.native(|values| ValuesRef::U8(values)),
+            Some(NativeTypeId::U16) => visit.native(|values| ValuesRef::U16(values)),
+            Some(NativeTypeId::U32) => visit.native(|values| ValuesRef::U32(values)),
+            Some(NativeTypeId::U64) => visit.native(|values| ValuesRef::U64(values)),
+            Some(NativeTypeId::F32) => visit.native(|values| ValuesRef::F32(values)),
+            Some(NativeTypeId::F64) => visit.native(|values| ValuesRef::F64(values)),
+            Some(NativeTypeId::Timestamp) => visit.native(|values| ValuesRef::Timestamp(values)),
+            None if data_type == TdsType::String => visit.values(
+                |offset, buffer| channel.read_strings(offset, buffer),
+                |values| ValuesRef::String(values),
+            ),
+            None => visit.values(
+                |offset, buffer| channel.read_data_as_f64(offset, buffer),
+                |values| ValuesRef::F64(values),
+            ),
+        }
+    })
+}
+
+/// Whether values of a data type can be passed to a visitor. DAQmx raw data and
+/// custom types are converted to f64.
+pub(crate) fn is_visitable(data_type: TdsType) -> bool {
+    data_type.native_type().is_some()
+        || matches!(
+            data_type,
+            TdsType::String | TdsType::DaqmxRawData | TdsType::Custom { .. }
+        )
+}
+
+/// Reads of a channel's values in one segment, passed to a visitor a chunk at a time
+struct ValueVisit<'c, 'a, 'v, R: Read + Seek> {
+    channel: &'c Channel<'a, R>,
+    data_type: TdsType,
+    segment_values: SegmentValues,
+    chunk_size: Option<usize>,
+    visitor: &'v mut dyn DataVisitor,
+}
+
+impl<'c, 'a, 'v, R: Read + Seek> ValueVisit<'c, 'a, 'v, R> {
+    fn native<T: NativeType>(
+        &mut self,
+        values_ref: fn(&[T]) -> ValuesRef<'_>,
+    ) -> Result<ControlFlow<()>> {
+        let channel = self.channel;
+        self.values(
+            |offset, buffer| channel.read_data(offset, buffer),
+            values_ref,
+        )
+    }
+
+    fn values<T, F>(
+        &mut self,
+        mut read: F,
+        values_ref: fn(&[T]) -> ValuesRef<'_>,
+    ) -> Result<ControlFlow<()>>
+    where
+        T: Clone + Default + Send + 'static,
+        F: FnMut(u64, &mut [T]) -> Result<usize>,
+    {
+        let chunk_size = self
+            .chunk_size
+            .unwrap_or_else(|| self.channel.chunk_config().values(std::mem::size_of::<T>()))
+            .max(1);
+        let SegmentValues {
+            start_value,
+            value_count,
+            ..
+        } = self.segment_values;
+        let mut buffer = self
+            .channel
+            .scratch()
+            .take::<T>(chunk_size.min(value_count as usize));
+        let end = start_value + value_count;
+        let mut offset = start_value;
+        while offset < end {
+            let length = buffer.len().min((end - offset) as usize);
+            let values_read = read(offset, &mut buffer[..length])?;
+            if values_read == 0 {
+                return Err(TdmsReadError::InvalidData(format!(
+                    "Expected {} values of channel {} from index {} but none could be read",
+                    end - offset,
+                    self.channel.path(),
+                    offset
+                )));
+            }
+            let values = values_ref(&buffer[..values_read]);
+            if self
+                .visitor
+                .chunk(self.channel.path(), self.data_type, values)
+                .is_break()
+            {
+                return Ok(ControlFlow::Break(()));
+            }
+            offset += values_read as u64;
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+}
+
+/// The file that a list of channels belongs to, or None if the list is empty. Returns an
+/// error if the channels don't all belong to the same file.
+pub(crate) fn channels_file<'a, R: Read + Seek>(
+    channels: &[Channel<'a, R>],
+) -> Result<Option<&'a TdmsFile<R>>> {
+    let file = match channels.first() {
+        Some(channel) => channel.file,
+        None => return Ok(None),
+    };
+    if channels
+        .iter()
+        .any(|channel| !std::ptr::eq(channel.file, file))
+    {
+        return Err(TdmsReadError::InvalidArgument(String::from(
+            "Channels must all belong to the same file",
+        )));
+    }
+    Ok(Some(file))
+}
@@ -0,0 +1,324 @@
+use crate::properties::names::{WF_INCREMENT, WF_START_OFFSET, WF_START_TIME};
+use crate::properties::{TdmsProperty, TdmsValue};
+use crate::timestamp::Timestamp;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Duration, Utc};
+
+/// Names of the properties that determine a channel's waveform timing
+pub(crate) const WAVEFORM_PROPERTIES: [&str; 3] = [WF_START_TIME, WF_START_OFFSET, WF_INCREMENT];
+
+/// Timing information for a waveform channel, read from its
+/// `wf_start_time`, `wf_start_offset` and `wf_increment` properties
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaveformTiming {
+    /// Absolute time of the first sample, if known
+    pub start_time: Option<Timestamp>,
+
+    /// Time in seconds of the first sample, relative to the start time
+    pub start_offset: f64,
+
+    /// Time in seconds between consecutive samples
+    pub increment: f64,
+}
+
+impl WaveformTiming {
+    /// Get the waveform timing from a channel's properties. Returns None unless
+    /// the channel has a numeric `wf_increment` property.
+    pub fn from_properties(properties: &[TdmsProperty]) -> Option<WaveformTiming> {
+        let increment = find_property(properties, WF_INCREMENT).and_then(TdmsValue::as_f64)?;
+        let start_offset = find_property(properties, WF_START_OFFSET)
+            .and_then(TdmsValue::as_f64)
+            .unwrap_or(0.0);
+        let start_time = match find_property(properties, WF_START_TIME) {
+            Some(TdmsValue::Timestamp(timestamp)) => Some(*timestamp),
+            _ => None,
+        };
+        Some(WaveformTiming {
+            start_time,
+            start_offset,
+            increment,
+        })
+    }
+
+    /// The timing shared by all of a set of channels, given the timing of each channel.
+    /// Returns None if there are no channels, or if any channel has no timing or
+    /// timing that differs from the others.
+    pub(crate) fn common<I>(timings: I) -> Option<WaveformTiming>
+    where
+        I: IntoIterator<Item = Option<WaveformTiming>>,
+    {
+        let mut timings = timings.into_iter();
+        let first = timings.next()??;
+        for timing in timings {
+            if timing? != first {
+                return None;
+            }
+        }
+        Some(first)
+    }
+
+    /// Time in seconds of the sample at `index`, relative to the start time
+    pub fn relative_time(&self, index: u64) -> f64 {
+        self.start_offset + index as f64 * self.increment
+    }
+
+    /// Absolute time of the sample at `index`, rounded to a whole number of nanoseconds,
+    /// or None if the start time is unknown
+    pub fn absolute_timestamp(&self, index: u64) -> Option<Timestamp> {
+        add_seconds(self.start_time?, self.relative_time(index))
+    }
+
+    /// Absolute time of the sample at `index`, or None if the start time is unknown
+    #[cfg(feature = "chrono")]
+    pub fn absolute_time(&self, index: u64) -> Option<DateTime<Utc>> {
+        self.absolute_timestamp(index)?.to_datetime()
+    }
+}
+
+/// Waveform timing of a range of a channel's values, which starts in a segment where
+/// the channel's timing properties were written and continues until they are next written
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegmentTiming {
+    /// Index of the first value in the range within all of the channel's values
+    pub start_index: u64,
+
+    /// Absolute time of the first value in the range, including any start offset,
+    /// rounded to a whole number of nanoseconds, or None if the start time is unknown
+    pub start_time: Option<Timestamp>,
+
+    /// Time in seconds between consecutive samples
+    pub increment: f64,
+
+    /// Number of values in the range
+    pub samples: u64,
+}
+
+impl SegmentTiming {
+    /// Time that a sample following the last one in the range would have,
+    /// extrapolated from the start time and increment
+    pub fn end_time(&self) -> Option<Timestamp> {
+        add_seconds(self.start_time?, self.samples as f64 * self.increment)
+    }
+}
+
+/// A discontinuity in a waveform channel's timing, where a range of values starts at a
+/// different time to the extrapolated end of the previous range, eg. because
+/// acquisition was paused
+#[cfg(feature = "chrono")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gap {
+    /// Index of the last value before the gap
+    pub after_sample_index: u64,
+
+    /// Time the value after the gap would have had if acquisition was continuous
+    pub expected: DateTime<Utc>,
+
+    /// Time of the value after the gap
+    pub actual: DateTime<Utc>,
+}
+
+#[cfg(feature = "chrono")]
+impl Gap {
+    /// Length of the gap, which is negative if time went backwards
+    pub fn duration(&self) -> Duration {
+        self.actual - self.expected
+    }
+}
+
+/// Timing of each range of a channel's values between updates to its waveform timing
+/// properties, given the updates in segment order, a function giving the index of the
+/// channel's first value at or after a segment, and the total number of values.
+/// Values before the channel has an increment aren't included in any range.
+pub(crate) fn segments_timing<'a, I, F>(
+    updates: I,
+    start_value_from_segment: F,
+    number_of_values: u64,
+) -> Vec<SegmentTiming>
+where
+    I: IntoIterator<Item = (usize, &'a [TdmsProperty])>,
+    F: Fn(usize) -> u64,
+{
+    let mut properties: Vec<TdmsProperty> = Vec::new();
+    let mut ranges: Vec<(u64, Option<WaveformTiming>)> = Vec::new();
+    for (segment_index, segment_properties) in updates {
+        for property in segment_properties {
+            match properties
+                .iter_mut()
+                .find(|existing| existing.name == property.name)
+            {
+                Some(existing) => existing.value = property.value.clone(),
+                None => properties.push(property.clone()),
+            }
+        }
+        let start_index = start_value_from_segment(segment_index);
+        let timing = WaveformTiming::from_properties(&properties);
+        // Properties written again before any more values replace the previous timing
+        match ranges.last_mut() {
+            Some(last) if last.0 == start_index => last.1 = timing,
+            _ => ranges.push((start_index, timing)),
+        }
+    }
+    let mut timings = Vec::with_capacity(ranges.len());
+    for (index, (start_index, timing)) in ranges.iter().enumerate() {
+        let end_index = ranges
+            .get(index + 1)
+            .map_or(number_of_values, |(next_start, _)| *next_start);
+        if let Some(timing) = timing {
+            if end_index > *start_index {
+                timings.push(SegmentTiming {
+                    start_index: *start_index,
+                    start_time: timing.absolute_timestamp(0),
+                    increment: timing.increment,
+                    samples: end_index - start_index,
+                });
+            }
+        }
+    }
+    timings
+}
+
+/// Find gaps between consecutive ranges of values where the start time of a range differs
+/// from the extrapolated end of the previous range by more than `tolerance`
+#[cfg(feature = "chrono")]
+pub(crate) fn detect_gaps(timings: &[SegmentTiming], tolerance: Duration) -> Vec<Gap> {
+    timings
+        .windows(2)
+        .filter(|pair| pair[0].start_index + pair[0].samples == pair[1].start_index)
+        .filter_map(|pair| {
+            let expected = pair[0].end_time()?.to_datetime()?;
+            let actual = pair[1].start_time?.to_datetime()?;
+            let difference = actual - expected;
+            if difference > tolerance || -difference > tolerance {
+                Some(Gap {
+                    after_sample_index: pair[1].start_index - 1,
+                    expected,
+                    actual,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Add a number of seconds to a time, rounding down the time and rounding the seconds
+/// to whole nanoseconds. Returns None if the seconds are too large or not finite.
+fn add_seconds(time: Timestamp, seconds: f64) -> Option<Timestamp> {
+    let nanoseconds = (seconds * 1e9).round();
+    if !nanoseconds.is_finite() || nanoseconds.abs() > i64::MAX as f64 {
+        return None;
+    }
+    Timestamp::from_unix_nanos(time.to_unix_nanos() + nanoseconds as i128)
+}
+
+fn find_property<'a>(properties: &'a [TdmsProperty], name: &str) -> Option<&'a TdmsValue> {
+    properties
+        .iter()
+        .find(|property| property.name == name)
+        .map(|property| &property.value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn timing_from_properties() {
+        let properties = vec![
+            TdmsProperty::new(WF_START_TIME, TdmsValue::Timestamp(Timestamp::new(60, 0))),
+            TdmsProperty::new(WF_START_OFFSET, TdmsValue::Float64(0.5)),
+            TdmsProperty::new(WF_INCREMENT, TdmsValue::Float64(0.25)),
+        ];
+
+        let timing = WaveformTiming::from_properties(&properties).unwrap();
+
+        assert_eq!(timing.relative_time(2), 1.0);
+        assert_eq!(
+            timing.absolute_timestamp(2).unwrap().to_rfc3339().unwrap(),
+            "1904-01-01T00:01:01+00:00"
+        );
+    }
+
+    #[test]
+    fn no_timing_without_increment() {
+        let properties = vec![TdmsProperty::new(WF_START_OFFSET, TdmsValue::Float64(0.5))];
+
+        assert_eq!(WaveformTiming::from_properties(&properties), None);
+    }
+
+    #[test]
+    fn common_timing() {
+        let timing = |increment| {
+            Some(WaveformTiming {
+                start_time: None,
+                start_offset: 0.0,
+                increment,
+            })
+        };
+
+        assert_eq!(
+            WaveformTiming::common(vec![timing(0.5), timing(0.5)]),
+            timing(0.5)
+        );
+        assert_eq!(
+            WaveformTiming::common(vec![timing(0.5), timing(0.25)]),
+            None
+        );
+        assert_eq!(WaveformTiming::common(vec![timing(0.5), None]), None);
+        assert_eq!(WaveformTiming::common(Vec::new()), None);
+    }
+
+    fn timing_properties(start_seconds: i64, increment: f64) -> Vec<TdmsProperty> {
+        vec![
+            TdmsProperty::new(
+                WF_START_TIME,
+                TdmsValue::Timestamp(Timestamp::new(start_seconds, 0)),
+            ),
+            TdmsProperty::new(WF_INCREMENT, TdmsValue::Float64(increment)),
+        ]
+    }
+
+    #[test]
+    fn timing_of_each_update() {
+        let first = timing_properties(100, 0.5);
+        let restart = [TdmsProperty::new(
+            WF_START_TIME,
+            TdmsValue::Timestamp(Timestamp::new(200, 0)),
+        )];
+        // Segments 0 and 1 have 4 values each, segment 2 has none and segment 3 has 2
+        let start_value = |segment_index| [0, 4, 8, 8, 10][segment_index];
+        let updates = vec![(0, &first[..]), (2, &restart[..]), (3, &restart[..])];
+
+        let timings = segments_timing(updates, start_value, 10);
+
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings[0].start_index, 0);
+        assert_eq!(timings[0].samples, 8);
+        assert_eq!(timings[0].end_time(), Some(Timestamp::new(104, 0)));
+        assert_eq!(timings[1].start_index, 8);
+        assert_eq!(timings[1].samples, 2);
+        assert_eq!(timings[1].increment, 0.5);
+        assert_eq!(timings[1].start_time, Some(Timestamp::new(200, 0)));
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn gaps_beyond_tolerance() {
+        let timing = |start_index, start_seconds| SegmentTiming {
+            start_index,
+            start_time: Some(Timestamp::new(start_seconds, 0)),
+            increment: 1.0,
+            samples: 10,
+        };
+        let timings = vec![timing(0, 0), timing(10, 10), timing(20, 25), timing(30, 33)];
+
+        let gaps = detect_gaps(&timings, Duration::seconds(1));
+
+        assert_eq!(gaps.len(), 2);
+        assert_eq!(gaps[0].after_sample_index, 19);
+        assert_eq!(gaps[0].duration(), Duration::seconds(5));
+        assert_eq!(gaps[1].after_sample_index, 29);
+        assert_eq!(gaps[1].duration(), Duration::seconds(-2));
+        assert_eq!(detect_gaps(&timings, Duration::seconds(5)).len(), 0);
+    }
+}
@@ -0,0 +1,535 @@
+use crate::error::Result;
+use crate::object_path::{path_from_channel, path_from_group};
+use crate::properties::TdmsProperty;
+use crate::toc::TocFlag;
+use crate::types::{write_string, NativeType, NativeTypeId, TdsType};
+use crate::{Channel, Timestamp};
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::io::{Read, Seek, Write};
+
+const SEGMENT_TAG: [u8; 4] = [0x54, 0x44, 0x53, 0x6d];
+const VERSION: u32 = 4713;
+const RAW_DATA_INDEX_NO_DATA: u32 = 0xFFFFFFFF;
+const RAW_DATA_INDEX_LENGTH: u32 = 20;
+const STRING_RAW_DATA_INDEX_LENGTH: u32 = 28;
+
+/// Writes TDMS files as a sequence of segments.
+///
+/// Every segment is written with a new object list and full metadata for the
+/// objects it contains, and all data is written in little endian byte order.
+pub struct TdmsWriter<W: Write> {
+    writer: W,
+}
+
+/// An object to be written in a segment, with optional properties and data
+pub struct WriteObject {
+    path: String,
+    properties: Vec<TdmsProperty>,
+    data: Option<WriteData>,
+}
+
+struct WriteData {
+    data_type: TdsType,
+    number_of_values: u64,
+    bytes: Vec<u8>,
+}
+
+impl WriteObject {
+    /// Create an object representing the root of the file
+    pub fn root() -> WriteObject {
+        WriteObject::with_path(String::from("/"))
+    }
+
+    /// Create a group object
+    pub fn group(group_name: &str) -> WriteObject {
+        WriteObject::with_path(path_from_group(group_name))
+    }
+
+    /// Create a channel object
+    pub fn channel(group_name: &str, channel_name: &str) -> WriteObject {
+        WriteObject::with_path(path_from_channel(group_name, channel_name))
+    }
+
+    fn with_path(path: String) -> WriteObject {
+        WriteObject {
+            path,
+            properties: Vec::new(),
+            data: None,
+        }
+    }
+
+    /// Set properties to be written for this object
+    pub fn with_properties(mut self, properties: &[TdmsProperty]) -> WriteObject {
+        self.properties = properties.to_vec();
+        self
+    }
+
+    /// Set channel data to be written for this object. Values of any fixed size type
+    /// are written with their own data type, and floating point values are written
+    /// bit for bit, including NaN and infinite values.
+    pub fn with_data<T: NativeType>(mut self, values: &[T]) -> Result<WriteObject> {
+        let mut bytes = Vec::new();
+        T::write_values(values, &mut bytes)?;
+        self.data = Some(WriteData {
+            data_type: T::tds_type(),
+            number_of_values: values.len() as u64,
+            bytes,
+        });
+        Ok(self)
+    }
+
+    /// Set string channel data to be written for this object
+    pub fn with_string_data<S: AsRef<str>>(mut self, values: &[S]) -> Result<WriteObject> {
+        let mut offsets = Vec::with_capacity(values.len() * 4);
+        let mut string_bytes = Vec::new();
+        for value in values {
+            string_bytes.extend_from_slice(value.as_ref().as_bytes());
+            offsets.write_u32::<LittleEndian>(string_bytes.len() as u32)?;
+        }
+        offsets.extend(string_bytes);
+        self.data = Some(WriteData {
+            data_type: TdsType::String,
+            number_of_values: values.len() as u64,
+            bytes: offsets,
+        });
+        Ok(self)
+    }
+}
+
+impl<W: Write> TdmsWriter<W> {
+    /// Create a new writer that writes TDMS data to the underlying writer
+    pub fn new(writer: W) -> TdmsWriter<W> {
+        TdmsWriter { writer }
+    }
+
+    /// Write a segment containing the given objects
+    pub fn write_segment(&mut self, objects: &[WriteObject]) -> Result<()> {
+        let mut metadata = Vec::new();
+        metadata.write_u32::<LittleEndian>(objects.len() as u32)?;
+        let mut data_length = 0;
+        for object in objects {
+            write_string(&mut metadata, &object.path)?;
+            match object.data {
+                Some(ref data) if data.data_type == TdsType::String => {
+                    metadata.write_u32::<LittleEndian>(STRING_RAW_DATA_INDEX_LENGTH)?;
+                    metadata.write_u32::<LittleEndian>(data.data_type.to_u32())?;
+                    metadata.write_u32::<LittleEndian>(1)?;
+                    metadata.write_u64::<LittleEndian>(data.number_of_values)?;
+                    metadata.write_u64::<LittleEndian>(data.bytes.len() as u64)?;
+                    data_length += data.bytes.len() as u64;
+                }
+                Some(ref data) => {
+                    metadata.write_u32::<LittleEndian>(RAW_DATA_INDEX_LENGTH)?;
+                    metadata.write_u32::<LittleEndian>(data.data_type.to_u32())?;
+                    metadata.write_u32::<LittleEndian>(1)?;
+                    metadata.write_u64::<LittleEndian>(data.number_of_values)?;
+                    data_length += data.bytes.len() as u64;
+                }
+                None => metadata.write_u32::<LittleEndian>(RAW_DATA_INDEX_NO_DATA)?,
+            }
+            metadata.write_u32::<LittleEndian>(object.properties.len() as u32)?;
+            for property in object.properties.iter() {
+                property.write(&mut metadata)?;
+            }
+        }
+
+        let mut toc_mask: u32 = TocFlag::MetaData.into();
+        toc_mask |= u32::from(TocFlag::NewObjList);
+        if data_length > 0 {
+            toc_mask |= u32::from(TocFlag::RawData);
+        }
+
+        self.writer.write_all(&SEGMENT_TAG)?;
+        self.writer.write_u32::<LittleEndian>(toc_mask)?;
+        self.writer.write_u32::<LittleEndian>(VERSION)?;
+        self.writer
+            .write_u64::<LittleEndian>(metadata.len() as u64 + data_length)?;
+        self.writer
+            .write_u64::<LittleEndian>(metadata.len() as u64)?;
+        self.writer.write_all(&metadata)?;
+        for object in objects {
+            if let Some(ref data) = object.data {
+                self.writer.write_all(&data.bytes)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flush any buffered data to the underlying writer
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Get the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Copy all data for a channel to the writer, one segment per chunk of values.
+/// Returns the number of values copied, or `None` if reading data of the
+/// channel's type is not supported.
+pub(crate) fn copy_channel_data<R: Read + Seek, W: Write>(
+    channel: &Channel<R>,
+    writer: &mut TdmsWriter<W>,
+) -> Result<Option<u64>> {
+    copy_channel_data_as(channel, (channel.group_name(), channel.name()), writer)
+}
+
+/// Copy all data for a channel to the writer as with `copy_channel_data`, also writing
+/// the channel's waveform timing properties with the first value they applied to, so
+/// that the start time of each part of the data is kept
+pub(crate) fn copy_channel_data_with_timing<R: Read + Seek, W: Write>(
+    channel: &Channel<R>,
+    writer: &mut TdmsWriter<W>,
+) -> Result<Option<u64>> {
+    let target = (channel.group_name(), channel.name());
+    let timing = TimingUpdates::new(channel.waveform_property_updates());
+    copy_data(channel, target, timing, writer)
+}
+
+/// Copy all data for a channel to the writer as with `copy_channel_data`, writing it
+/// to the channel with the target group and channel name
+pub(crate) fn copy_channel_data_as<R: Read + Seek, W: Write>(
+    channel: &Channel<R>,
+    target: (&str, &str),
+    writer: &mut TdmsWriter<W>,
+) -> Result<Option<u64>> {
+    copy_data(channel, target, TimingUpdates::new(Vec::new()), writer)
+}
+
+fn copy_data<R: Read + Seek, W: Write>(
+    channel: &Channel<R>,
+    target: (&str, &str),
+    mut timing: TimingUpdates,
+    writer: &mut TdmsWriter<W>,
+) -> Result<Option<u64>> {
+    let data_type = match channel.data_type() {
+        Some(data_type) => data_type,
+        None => return Ok(Some(0)),
+    };
+    let timing = &mut timing;
+    if data_type == TdsType::String {
+        return Ok(Some(copy_strings(channel, target, timing, writer)?));
+    }
+    let copied = match data_type.native_type() {
+        Some(NativeTypeId::I8) => copy_values::<i8, _, _>(channel, target, timing, writer)?,
+        Some(NativeTypeId::I16) => copy_values::<i16, _, _>(channel, target, timing, writer)?,
+        Some(NativeTypeId::I32) => copy_values::<i32, _, _>(channel, target, timing, writer)?,
+        Some(NativeTypeId::I64) => copy_values::<i64, _, _>(channel, target, timing, writer)?,
+        Some(NativeTypeId::U8) => copy_values::<u8, _, _>(channel, target, timing, writer)?,
+        Some(NativeTypeId::U16) => copy_values::<u16, _, _>(channel, target, timing, writer)?,
+        Some(NativeTypeId::U32) => copy_values::<u32, _, _>(channel, target, timing, writer)?,
+        Some(NativeTypeId::U64) => copy_values::<u64, _, _>(channel, target, timing, writer)?,
+        Some(NativeTypeId::F32) => copy_values::<f32, _, _>(channel, target, timing, writer)?,
+        Some(NativeTypeId::F64) => copy_values::<f64, _, _>(channel, target, timing, writer)?,
+        Some(NativeTypeId::Timestamp) => {
+            copy_values::<Timestamp, _, _>(channel, target, timing, writer)?
+        }
+        None => return Ok(None),
+    };
+    Ok(Some(copied))
+}
+
+/// Waveform timing properties to write in the segment of the first value they apply to,
+/// given as the index of the value and the properties, in order of value index
+struct TimingUpdates<'a> {
+    updates: Vec<(u64, &'a [TdmsProperty])>,
+    next: usize,
+}
+
+impl<'a> TimingUpdates<'a> {
+    fn new(updates: Vec<(u64, &'a [TdmsProperty])>) -> TimingUpdates<'a> {
+        TimingUpdates { updates, next: 0 }
+    }
+
+    /// Properties to write in the segment starting at the value at index `offset`
+    fn properties_at(&mut self, offset: u64) -> Vec<TdmsProperty> {
+        let mut properties: Vec<TdmsProperty> = Vec::new();
+        while let Some((start_value, update)) = self.updates.get(self.next) {
+            if *start_value > offset {
+                break;
+            }
+            for property in update.iter() {
+                match properties
+                    .iter_mut()
+                    .find(|existing| existing.name == property.name)
+                {
+                    Some(existing) => existing.value = property.value.clone(),
+                    None => properties.push(property.clone()),
+                }
+            }
+            self.next += 1;
+        }
+        properties
+    }
+
+    /// Number of values of up to `length` from the value at index `offset` that can be
+    /// written in one segment, which ends before the value of the next update
+    fn segment_length(&self, offset: u64, length: usize) -> usize {
+        match self.updates.get(self.next) {
+            Some((start_value, _)) if *start_value > offset => {
+                (*start_value - offset).min(length as u64) as usize
+            }
+            _ => length,
+        }
+    }
+}
+
+fn copy_values<T: NativeType + Clone + Default, R: Read + Seek, W: Write>(
+    channel: &Channel<R>,
+    target: (&str, &str),
+    timing: &mut TimingUpdates,
+    writer: &mut TdmsWriter<W>,
+) -> Result<u64> {
+    let (group_name, channel_name) = target;
+    let chunk_size = channel.chunk_config().values(std::mem::size_of::<T>());
+    let mut buffer = channel.scratch().take::<T>(chunk_size);
+    let mut offset = 0;
+    loop {
+        let properties = timing.properties_at(offset);
+        let length = timing.segment_length(offset, buffer.len());
+        let values_read = channel.read_data(offset, &mut buffer[..length])?;
+        if values_read == 0 {
+            break;
+        }
+        writer.write_segment(&[WriteObject::channel(group_name, channel_name)
+            .with_properties(&properties)
+            .with_data(&buffer[..values_read])?])?;
+        offset += values_read as u64;
+    }
+    Ok(offset)
+}
+
+fn copy_strings<R: Read + Seek, W: Write>(
+    channel: &Channel<R>,
+    target: (&str, &str),
+    timing: &mut TimingUpdates,
+    writer: &mut TdmsWriter<W>,
+) -> Result<u64> {
+    let (group_name, channel_name) = target;
+    let chunk_size = channel.chunk_config().values(std::mem::size_of::<String>());
+    let mut buffer = channel.scratch().take::<String>(chunk_size);
+    let mut offset = 0;
+    loop {
+        let properties = timing.properties_at(offset);
+        let length = timing.segment_length(offset, buffer.len());
+        let values_read = channel.read_strings(offset, &mut buffer[..length])?;
+        if values_read == 0 {
+            break;
+        }
+        writer.write_segment(&[WriteObject::channel(group_name, channel_name)
+            .with_properties(&properties)
+            .with_string_data(&buffer[..values_read])?])?;
+        offset += values_read as u64;
+    }
+    Ok(offset)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::properties::TdmsValue;
+    use crate::TdmsFile;
+    use std::convert::TryInto;
+    use std::io::Cursor;
+
+    #[test]
+    fn write_and_read_segments() {
+        let mut writer = TdmsWriter::new(Vec::new());
+        writer
+            .write_segment(&[
+                WriteObject::root()
+                    .with_properties(&[TdmsProperty::new("name", TdmsValue::Int32(1))]),
+                WriteObject::group("Group"),
+                WriteObject::channel("Group", "Channel1")
+                    .with_data(&[1.0f64, 2.0])
+                    .unwrap(),
+                WriteObject::channel("Group", "Channel2")
+                    .with_data(&[1i16, 2, 3])
+                    .unwrap(),
+                WriteObject::channel("Group", "Channel3")
+                    .with_string_data(&["a", "", "bcd"])
+                    .unwrap(),
+            ])
+            .unwrap();
+        writer
+            .write_segment(&[WriteObject::channel("Group", "Channel1")
+                .with_data(&[3.0f64])
+                .unwrap()])
+            .unwrap();
+
+        let tdms_file = TdmsFile::new(Cursor::new(writer.into_inner())).unwrap();
+
+        assert_eq!(
+            tdms_file.properties(),
+            &[TdmsProperty::new("name", TdmsValue::Int32(1))]
+        );
+        let group = tdms_file.group("Group").unwrap();
+        let channel = group.channel("Channel1").unwrap();
+        let mut data = vec![0.0f64; channel.len() as usize];
+        channel.read_all_data(&mut data).unwrap();
+        assert_eq!(data, vec![1.0, 2.0, 3.0]);
+
+        let channel = group.channel("Channel2").unwrap();
+        let mut data = vec![0i16; channel.len() as usize];
+        channel.read_all_data(&mut data).unwrap();
+        assert_eq!(data, vec![1, 2, 3]);
+
+        let channel = group.channel("Channel3").unwrap();
+        let mut data = vec![String::new(); channel.len() as usize];
+        assert_eq!(channel.read_strings(0, &mut data).unwrap(), 3);
+        assert_eq!(data, vec!["a", "", "bcd"]);
+        let mut data = vec![String::new(); 2];
+        assert_eq!(channel.read_strings(1, &mut data).unwrap(), 2);
+        assert_eq!(data, vec!["", "bcd"]);
+    }
+
+    /// Raw data index type code and number of values, and the number of bytes of raw
+    /// data, written for a single channel
+    fn written_index<T: NativeType>(values: &[T]) -> (u32, u64, usize) {
+        let mut writer = TdmsWriter::new(Vec::new());
+        writer
+            .write_segment(&[WriteObject::channel("g", "c").with_data(values).unwrap()])
+            .unwrap();
+        let bytes = writer.into_inner();
+        // Lead in, object count, then the path "/'g'/'c'" and its length
+        let index = &bytes[28 + 4 + 4 + "/'g'/'c'".len()..];
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(index[offset..offset + 4].try_into().unwrap());
+        assert_eq!(u32_at(0), RAW_DATA_INDEX_LENGTH);
+        assert_eq!(u32_at(8), 1);
+        let number_of_values = u64::from_le_bytes(index[12..20].try_into().unwrap());
+        // Raw data follows the property count
+        let data_length = index.len() - 24;
+        (u32_at(4), number_of_values, data_length)
+    }
+
+    #[test]
+    fn raw_data_indexes_for_all_types() {
+        assert_eq!(written_index(&[1i8, -2, 3]), (0x01, 3, 3));
+        assert_eq!(written_index(&[1i16, -2, 3]), (0x02, 3, 6));
+        assert_eq!(written_index(&[1i32, -2, 3]), (0x03, 3, 12));
+        assert_eq!(written_index(&[1i64, -2, 3]), (0x04, 3, 24));
+        assert_eq!(written_index(&[1u8, 2, 3]), (0x05, 3, 3));
+        assert_eq!(written_index(&[1u16, 2, 3]), (0x06, 3, 6));
+        assert_eq!(written_index(&[1u32, 2, 3]), (0x07, 3, 12));
+        assert_eq!(written_index(&[1u64, 2, 3]), (0x08, 3, 24));
+        assert_eq!(written_index(&[1.0f32, 2.0, 3.0]), (0x09, 3, 12));
+        assert_eq!(written_index(&[1.0f64, 2.0, 3.0]), (0x0A, 3, 24));
+    }
+
+    #[test]
+    fn write_mixed_types_in_one_segment() {
+        let mut writer = TdmsWriter::new(Vec::new());
+        writer
+            .write_segment(&[
+                WriteObject::channel("Group", "i8")
+                    .with_data(&[i8::MIN, -1, i8::MAX])
+                    .unwrap(),
+                WriteObject::channel("Group", "u16")
+                    .with_data(&[u16::MAX, 0])
+                    .unwrap(),
+                WriteObject::channel("Group", "f32")
+                    .with_data(&[1.5f32, -2.5, 3.25, 4.0])
+                    .unwrap(),
+                WriteObject::channel("Group", "i64")
+                    .with_data(&[i64::MIN, i64::MAX])
+                    .unwrap(),
+                WriteObject::channel("Group", "u8")
+                    .with_data(&[7u8])
+                    .unwrap(),
+                WriteObject::channel("Group", "u32")
+                    .with_data(&[u32::MAX, 1, 2])
+                    .unwrap(),
+                WriteObject::channel("Group", "i16")
+                    .with_data(&[i16::MIN, i16::MAX])
+                    .unwrap(),
+                WriteObject::channel("Group", "u64")
+                    .with_data(&[u64::MAX])
+                    .unwrap(),
+                WriteObject::channel("Group", "i32")
+                    .with_data(&[i32::MIN, 0, i32::MAX])
+                    .unwrap(),
+            ])
+            .unwrap();
+
+        let tdms_file = TdmsFile::new(Cursor::new(writer.into_inner())).unwrap();
+        let segments = tdms_file.segments().unwrap();
+        assert_eq!(
+            segments[0].data_length,
+            3 + 4 + 16 + 16 + 1 + 12 + 4 + 8 + 12
+        );
+        let group = tdms_file.group("Group").unwrap();
+        fn read<T: NativeType>(group: &crate::Group<'_, Cursor<Vec<u8>>>, name: &str) -> Vec<T> {
+            let channel = group.channel(name).unwrap();
+            let mut values = vec![T::default(); channel.len() as usize];
+            channel.read_all_data(&mut values).unwrap();
+            values
+        }
+        assert_eq!(read::<i8>(&group, "i8"), vec![i8::MIN, -1, i8::MAX]);
+        assert_eq!(read::<u16>(&group, "u16"), vec![u16::MAX, 0]);
+        assert_eq!(read::<f32>(&group, "f32"), vec![1.5, -2.5, 3.25, 4.0]);
+        assert_eq!(read::<i64>(&group, "i64"), vec![i64::MIN, i64::MAX]);
+        assert_eq!(read::<u8>(&group, "u8"), vec![7]);
+        assert_eq!(read::<u32>(&group, "u32"), vec![u32::MAX, 1, 2]);
+        assert_eq!(read::<i16>(&group, "i16"), vec![i16::MIN, i16::MAX]);
+        assert_eq!(read::<u64>(&group, "u64"), vec![u64::MAX]);
+        assert_eq!(read::<i32>(&group, "i32"), vec![i32::MIN, 0, i32::MAX]);
+    }
+
+    #[test]
+    fn floats_round_trip_bit_exactly() {
+        let f32_values = [
+            f32::NAN,
+            -f32::NAN,
+            f32::from_bits(0x7F80_0001), // Signalling NaN
+            f32::from_bits(0x7FC1_2345), // NaN with a payload
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            -0.0,
+            f32::MIN_POSITIVE / 2.0, // Subnormal
+        ];
+        let f64_values = [
+            f64::NAN,
+            -f64::NAN,
+            f64::from_bits(0x7FF0_0000_0000_0001),
+            f64::from_bits(0xFFF8_DEAD_BEEF_0001),
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            -0.0,
+            f64::MIN_POSITIVE / 2.0,
+        ];
+        let mut writer = TdmsWriter::new(Vec::new());
+        writer
+            .write_segment(&[
+                WriteObject::channel("Group", "f32")
+                    .with_data(&f32_values)
+                    .unwrap(),
+                WriteObject::channel("Group", "f64")
+                    .with_data(&f64_values)
+                    .unwrap(),
+            ])
+            .unwrap();
+
+        let tdms_file = TdmsFile::new(Cursor::new(writer.into_inner())).unwrap();
+        let group = tdms_file.group("Group").unwrap();
+        let mut read_f32 = vec![0.0f32; f32_values.len()];
+        group
+            .channel("f32")
+            .unwrap()
+            .read_all_data(&mut read_f32)
+            .unwrap();
+        let bits = |values: &[f32]| values.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(&read_f32), bits(&f32_values));
+        let mut read_f64 = vec![0.0f64; f64_values.len()];
+        group
+            .channel("f64")
+            .unwrap()
+            .read_all_data(&mut read_f64)
+            .unwrap();
+        let bits = |values: &[f64]| values.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(&read_f64), bits(&f64_values));
+    }
+}
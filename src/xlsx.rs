@@ -0,0 +1,482 @@
+use crate::decimate;
+use crate::error::{Result, TdmsReadError};
+use crate::properties::names::UNIT_STRING;
+use crate::properties::{TdmsProperty, TdmsValue};
+use crate::timestamp::Timestamp;
+use crate::types::TdsType;
+use crate::waveform::WaveformTiming;
+use crate::{Channel, Group, TdmsFile};
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+use std::collections::HashSet;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+/// The column written before the channel values of each group when exporting to Excel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XlsxTimeColumn {
+    /// No time column is written
+    None,
+
+    /// Time in seconds relative to the waveform start time
+    Relative,
+
+    /// Absolute time of each value as an Excel date and time in UTC,
+    /// which has millisecond resolution
+    Absolute,
+}
+
+/// Options for exporting a file to an Excel workbook
+#[derive(Debug, Clone)]
+pub struct XlsxExportOptions {
+    /// The column written before each group's channel values. Times are computed from
+    /// the waveform timing shared by all channels in the group. If any channel has no
+    /// waveform timing or timing that differs from the others, the sample index is
+    /// written instead.
+    pub time_column: XlsxTimeColumn,
+
+    /// Whether a group with more values than fit in a worksheet is split over numbered
+    /// worksheets, rather than being an error
+    pub split_sheets: bool,
+
+    /// Number of values read from each channel at a time, or None to use the chunk
+    /// configuration of the file's read options
+    pub chunk_size: Option<usize>,
+}
+
+impl Default for XlsxExportOptions {
+    fn default() -> XlsxExportOptions {
+        XlsxExportOptions {
+            time_column: XlsxTimeColumn::None,
+            split_sheets: false,
+            chunk_size: None,
+        }
+    }
+}
+
+/// Number of rows of data that fit in a worksheet below the header row
+pub const XLSX_MAX_DATA_ROWS: u64 = 1_048_575;
+
+/// Number of columns in a worksheet
+const XLSX_MAX_COLUMNS: usize = 16_384;
+
+/// Longest worksheet name allowed, in characters
+const MAX_SHEET_NAME_LENGTH: usize = 31;
+
+/// Days from the start of Excel's 1900 date system to the Unix epoch
+const EXCEL_UNIX_EPOCH_DAYS: f64 = 25_569.0;
+
+const NANOSECONDS_PER_DAY: f64 = 86_400e9;
+
+/// Export a file to an Excel workbook, with a worksheet of channel data for each group
+/// and a final worksheet listing the properties of every object
+pub(crate) fn export_xlsx<R: Read + Seek>(
+    tdms_file: &TdmsFile<R>,
+    path: &Path,
+    options: &XlsxExportOptions,
+) -> Result<()> {
+    let mut workbook = Workbook::new();
+    let datetime_format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss.000");
+    let mut sheet_names = SheetNames::new();
+    for group in tdms_file.groups() {
+        write_group(
+            &mut workbook,
+            &group,
+            options,
+            &datetime_format,
+            &mut sheet_names,
+        )?;
+    }
+    write_properties(&mut workbook, tdms_file, &datetime_format, &mut sheet_names)?;
+    workbook.save(path)?;
+    Ok(())
+}
+
+enum TimeColumn {
+    Time(XlsxTimeColumn, WaveformTiming),
+    Index,
+}
+
+/// Values read from a channel for one chunk of rows
+enum ColumnValues {
+    Numbers(Vec<f64>),
+    Timestamps(Vec<Timestamp>),
+    Strings(Vec<String>),
+    Empty,
+}
+
+fn write_group<R: Read + Seek>(
+    workbook: &mut Workbook,
+    group: &Group<R>,
+    options: &XlsxExportOptions,
+    datetime_format: &Format,
+    sheet_names: &mut SheetNames,
+) -> Result<()> {
+    let channels: Vec<Channel<R>> = group.channels().collect();
+    let mut columns = Vec::with_capacity(channels.len());
+    for channel in channels.iter() {
+        columns.push(match channel.data_type() {
+            None => ColumnValues::Empty,
+            Some(TdsType::String) => ColumnValues::Strings(Vec::new()),
+            Some(TdsType::TimeStamp) => ColumnValues::Timestamps(Vec::new()),
+            Some(TdsType::DaqmxRawData) => ColumnValues::Numbers(Vec::new()),
+            Some(data_type) if data_type.native_type().is_some() => {
+                ColumnValues::Numbers(Vec::new())
+            }
+            Some(data_type) => {
+                return Err(TdmsReadError::Unsupported(format!(
+                    "Cannot export channel {} / {} as its data type {:?} is not supported",
+                    channel.group_name(),
+                    channel.name(),
+                    data_type
+                )))
+            }
+        });
+    }
+
+    let timing = WaveformTiming::common(channels.iter().map(|channel| channel.waveform_timing()));
+    let time_column = match (options.time_column, timing) {
+        (XlsxTimeColumn::None, _) => None,
+        (time_column, Some(timing)) => Some(TimeColumn::Time(time_column, timing)),
+        (_, None) => Some(TimeColumn::Index),
+    };
+    let first_channel_column = time_column.is_some() as usize;
+    if first_channel_column + channels.len() > XLSX_MAX_COLUMNS {
+        return Err(TdmsReadError::Unsupported(format!(
+            "Group {} has {} channels, more than fit in the {} columns of an Excel worksheet",
+            group.name(),
+            channels.len(),
+            XLSX_MAX_COLUMNS
+        )));
+    }
+
+    let total_rows = channels
+        .iter()
+        .map(|channel| channel.len())
+        .max()
+        .unwrap_or(0);
+    if total_rows > XLSX_MAX_DATA_ROWS && !options.split_sheets {
+        return Err(TdmsReadError::TooManyRows {
+            group: group.name().to_string(),
+            rows: total_rows,
+            max_rows: XLSX_MAX_DATA_ROWS,
+        });
+    }
+    let chunk_size = match (options.chunk_size, channels.first()) {
+        (Some(chunk_size), _) => chunk_size,
+        (None, Some(channel)) => channel
+            .chunk_config()
+            .values(std::mem::size_of::<f64>() * channels.len()),
+        (None, None) => 1,
+    }
+    .max(1) as u64;
+
+    let sheet_count = total_rows.div_ceil(XLSX_MAX_DATA_ROWS).max(1);
+    for sheet_index in 0..sheet_count {
+        let worksheet = workbook.add_worksheet_with_constant_memory();
+        worksheet.set_name(sheet_names.add(group.name(), sheet_index as usize + 1))?;
+
+        match time_column {
+            Some(TimeColumn::Time(..)) => {
+                worksheet.write_string(0, 0, "Time")?;
+            }
+            Some(TimeColumn::Index) => {
+                worksheet.write_string(0, 0, "Index")?;
+            }
+            None => {}
+        }
+        for (column, channel) in channels.iter().enumerate() {
+            let unit = channel
+                .properties()
+                .iter()
+                .find(|property| property.name == UNIT_STRING)
+                .and_then(|property| property.value.as_str());
+            let header = match unit {
+                Some(unit) if !unit.is_empty() => format!("{} ({})", channel.name(), unit),
+                _ => channel.name().to_string(),
+            };
+            worksheet.write_string(0, (first_channel_column + column) as u16, header)?;
+        }
+
+        let first_row = sheet_index * XLSX_MAX_DATA_ROWS;
+        let end_row = total_rows.min(first_row + XLSX_MAX_DATA_ROWS);
+        let mut position = first_row;
+        while position < end_row {
+            let chunk_rows = (end_row - position).min(chunk_size);
+            for (channel, values) in channels.iter().zip(columns.iter_mut()) {
+                let count = channel.len().saturating_sub(position).min(chunk_rows) as usize;
+                read_column(channel, position, count, values)?;
+            }
+            for row_index in 0..chunk_rows {
+                let index = position + row_index;
+                let row = (index - first_row + 1) as u32;
+                match time_column {
+                    Some(TimeColumn::Time(XlsxTimeColumn::Absolute, timing)) => {
+                        if let Some(time) = timing.absolute_timestamp(index) {
+                            let nanos = time.to_unix_nanos();
+                            worksheet.write_number_with_format(
+                                row,
+                                0,
+                                excel_date(nanos),
+                                datetime_format,
+                            )?;
+                        }
+                    }
+                    Some(TimeColumn::Time(_, timing)) => {
+                        worksheet.write_number(row, 0, timing.relative_time(index))?;
+                    }
+                    Some(TimeColumn::Index) => {
+                        worksheet.write_number(row, 0, index as f64)?;
+                    }
+                    None => {}
+                }
+                for (column, values) in columns.iter().enumerate() {
+                    let column = (first_channel_column + column) as u16;
+                    write_cell(
+                        worksheet,
+                        row,
+                        column,
+                        values,
+                        row_index as usize,
+                        datetime_format,
+                    )?;
+                }
+            }
+            position += chunk_rows;
+        }
+    }
+    Ok(())
+}
+
+/// Read `count` values of a channel starting at `offset` into the column's buffer
+fn read_column<R: Read + Seek>(
+    channel: &Channel<R>,
+    offset: u64,
+    count: usize,
+    values: &mut ColumnValues,
+) -> Result<()> {
+    match values {
+        ColumnValues::Numbers(buffer) => {
+            buffer.resize(count, 0.0);
+            let values_read = decimate::read_data_as_f64(channel, offset, buffer)?;
+            buffer.truncate(values_read);
+        }
+        ColumnValues::Timestamps(buffer) => {
+            buffer.resize(count, Timestamp::default());
+            let values_read = channel.read_data(offset, buffer)?;
+            buffer.truncate(values_read);
+        }
+        ColumnValues::Strings(buffer) => {
+            buffer.resize(count, String::new());
+            let values_read = channel.read_strings(offset, buffer)?;
+            buffer.truncate(values_read);
+        }
+        ColumnValues::Empty => {}
+    }
+    Ok(())
+}
+
+fn write_cell(
+    worksheet: &mut Worksheet,
+    row: u32,
+    column: u16,
+    values: &ColumnValues,
+    index: usize,
+    datetime_format: &Format,
+) -> Result<()> {
+    match values {
+        ColumnValues::Numbers(values) => {
+            if let Some(&value) = values.get(index) {
+                worksheet.write_number(row, column, value)?;
+            }
+        }
+        ColumnValues::Timestamps(values) => {
+            if let Some(value) = values.get(index) {
+                let date = excel_date(value.to_unix_nanos());
+                worksheet.write_number_with_format(row, column, date, datetime_format)?;
+            }
+        }
+        ColumnValues::Strings(values) => {
+            if let Some(value) = values.get(index) {
+                worksheet.write_string(row, column, value)?;
+            }
+        }
+        ColumnValues::Empty => {}
+    }
+    Ok(())
+}
+
+/// Write a worksheet listing the properties of the file, each group and each channel
+fn write_properties<R: Read + Seek>(
+    workbook: &mut Workbook,
+    tdms_file: &TdmsFile<R>,
+    datetime_format: &Format,
+    sheet_names: &mut SheetNames,
+) -> Result<()> {
+    let worksheet = workbook.add_worksheet_with_constant_memory();
+    worksheet.set_name(sheet_names.add("Properties", 1))?;
+    for (column, header) in ["Object", "Property", "Type", "Value"].iter().enumerate() {
+        worksheet.write_string(0, column as u16, *header)?;
+    }
+    let mut row = 1;
+    write_object_properties(
+        worksheet,
+        &mut row,
+        "/",
+        tdms_file.properties(),
+        datetime_format,
+    )?;
+    for group in tdms_file.groups() {
+        write_object_properties(
+            worksheet,
+            &mut row,
+            group.path(),
+            group.properties(),
+            datetime_format,
+        )?;
+        for channel in group.channels() {
+            write_object_properties(
+                worksheet,
+                &mut row,
+                channel.path(),
+                channel.properties(),
+                datetime_format,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn write_object_properties(
+    worksheet: &mut Worksheet,
+    row: &mut u32,
+    path: &str,
+    properties: &[TdmsProperty],
+    datetime_format: &Format,
+) -> Result<()> {
+    for property in properties {
+        if *row as u64 > XLSX_MAX_DATA_ROWS {
+            return Err(TdmsReadError::TooManyRows {
+                group: String::from("Properties"),
+                rows: *row as u64,
+                max_rows: XLSX_MAX_DATA_ROWS,
+            });
+        }
+        worksheet.write_string(*row, 0, path)?;
+        worksheet.write_string(*row, 1, property.name.as_str())?;
+        worksheet.write_string(*row, 2, format!("{:?}", property.value.data_type()))?;
+        write_property_value(worksheet, *row, &property.value, datetime_format)?;
+        *row += 1;
+    }
+    Ok(())
+}
+
+/// Write a property value as a number where Excel can store it exactly,
+/// and otherwise as text
+fn write_property_value(
+    worksheet: &mut Worksheet,
+    row: u32,
+    value: &TdmsValue,
+    datetime_format: &Format,
+) -> Result<()> {
+    /// Largest integer that can be stored exactly as an f64
+    const MAX_EXACT_INTEGER: i64 = 1 << 53;
+
+    match *value {
+        TdmsValue::Boolean(value) => worksheet.write_boolean(row, 3, value)?,
+        TdmsValue::Timestamp(value) => worksheet.write_number_with_format(
+            row,
+            3,
+            excel_date(value.to_unix_nanos()),
+            datetime_format,
+        )?,
+        TdmsValue::String(ref value) => worksheet.write_string(row, 3, value)?,
+        TdmsValue::Float32(_) | TdmsValue::Float64(_) => {
+            worksheet.write_number(row, 3, value.as_f64().unwrap_or_default())?
+        }
+        _ => match value.as_i64() {
+            Some(integer) if integer.abs() <= MAX_EXACT_INTEGER => {
+                worksheet.write_number(row, 3, integer as f64)?
+            }
+            _ => worksheet.write_string(row, 3, value.to_string())?,
+        },
+    };
+    Ok(())
+}
+
+/// Convert a time in nanoseconds since the Unix epoch to an Excel date, which is a
+/// number of days since the start of Excel's 1900 date system
+fn excel_date(unix_nanos: i128) -> f64 {
+    EXCEL_UNIX_EPOCH_DAYS + unix_nanos as f64 / NANOSECONDS_PER_DAY
+}
+
+/// Names of the worksheets added to a workbook, for giving new worksheets valid
+/// and unique names. Excel compares worksheet names ignoring case.
+struct SheetNames {
+    used: HashSet<String>,
+}
+
+impl SheetNames {
+    fn new() -> SheetNames {
+        SheetNames {
+            used: HashSet::new(),
+        }
+    }
+
+    /// Add a worksheet named after `name`, numbered with `number` if it is greater
+    /// than one or if the name is already used, returning the new worksheet's name
+    fn add(&mut self, name: &str, number: usize) -> String {
+        let base: String = name
+            .chars()
+            .map(|c| match c {
+                '[' | ']' | ':' | '*' | '?' | '/' | '\\' => '_',
+                c => c,
+            })
+            .collect();
+        let base = base.trim_matches('\'');
+        let base = if base.is_empty() { "Sheet" } else { base };
+        let mut number = number;
+        loop {
+            let suffix = if number > 1 {
+                format!(" ({})", number)
+            } else {
+                String::new()
+            };
+            let mut sheet_name: String = base
+                .chars()
+                .take(MAX_SHEET_NAME_LENGTH - suffix.chars().count())
+                .collect();
+            sheet_name.push_str(&suffix);
+            if self.used.insert(sheet_name.to_lowercase()) {
+                return sheet_name;
+            }
+            number += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn sheet_names_are_valid_and_unique() {
+        let mut names = SheetNames::new();
+
+        assert_eq!(names.add("Group", 1), "Group");
+        assert_eq!(names.add("group", 1), "group (2)");
+        assert_eq!(names.add("Group", 2), "Group (3)");
+        assert_eq!(names.add("a/b:c", 1), "a_b_c");
+        assert_eq!(names.add("'quoted'", 1), "quoted");
+        assert_eq!(names.add("", 1), "Sheet");
+
+        let long_name = "x".repeat(40);
+        assert_eq!(names.add(&long_name, 1), "x".repeat(31));
+        assert_eq!(names.add(&long_name, 1), format!("{} (2)", "x".repeat(27)));
+    }
+
+    #[test]
+    pub fn excel_dates() {
+        assert_eq!(excel_date(0), 25_569.0);
+        assert_eq!(excel_date(86_400_000_000_000 / 2), 25_569.5);
+    }
+}
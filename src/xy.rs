@@ -0,0 +1,216 @@
+use crate::decimate::{self, MinMaxBucket};
+use crate::error::{Result, TdmsReadError};
+use crate::progress::ReadControl;
+use crate::properties::names::{WF_INCREMENT, WF_START_TIME};
+use crate::Channel;
+use std::io::{Read, Seek};
+
+/// The x value each channel value is paired with when reading (x, y) pairs for plotting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XMode {
+    /// Index of the value within the channel
+    Index,
+
+    /// Time in seconds relative to the waveform start time, from the channel's
+    /// `wf_start_offset` and `wf_increment` properties
+    RelativeTime,
+
+    /// Seconds since the Unix epoch, from the channel's `wf_start_time` property and its
+    /// relative time. Times are only precise to around a microsecond, as they are
+    /// stored as f64.
+    AbsoluteTimeSeconds,
+}
+
+/// Linear mapping from value indices to x values
+#[derive(Debug, Clone, Copy)]
+struct XMapping {
+    start: f64,
+    increment: f64,
+}
+
+impl XMapping {
+    fn new<R: Read + Seek>(channel: &Channel<R>, x_mode: XMode) -> Result<XMapping> {
+        if x_mode == XMode::Index {
+            return Ok(XMapping {
+                start: 0.0,
+                increment: 1.0,
+            });
+        }
+        let missing = |property| TdmsReadError::MissingProperty {
+            path: channel.path().to_string(),
+            property,
+        };
+        let timing = channel
+            .waveform_timing()
+            .ok_or_else(|| missing(WF_INCREMENT))?;
+        let start_time = match x_mode {
+            XMode::AbsoluteTimeSeconds => match timing.start_time {
+                Some(start_time) => start_time.to_unix_nanos() as f64 * 1e-9,
+                None => return Err(missing(WF_START_TIME)),
+            },
+            _ => 0.0,
+        };
+        Ok(XMapping {
+            start: start_time + timing.start_offset,
+            increment: timing.increment,
+        })
+    }
+
+    /// x value at a (possibly fractional) value index
+    fn x(&self, index: f64) -> f64 {
+        self.start + index * self.increment
+    }
+}
+
+/// Read `length` values of a numeric channel starting at `offset` as (x, y) pairs,
+/// computing each value's x as it is read
+pub(crate) fn read_xy<R: Read + Seek>(
+    channel: &Channel<R>,
+    x_mode: XMode,
+    offset: u64,
+    length: u64,
+    control: &mut ReadControl,
+) -> Result<Vec<(f64, f64)>> {
+    let mapping = XMapping::new(channel, x_mode)?;
+    let length = length.min(channel.len().saturating_sub(offset));
+    let mut points = Vec::with_capacity(length as usize);
+    let chunk_values = channel.chunk_config().values(std::mem::size_of::<f64>());
+    let mut buffer = channel
+        .scratch()
+        .take::<f64>(chunk_values.min(length as usize));
+
+    let end = offset + length;
+    let mut position = offset;
+    while position < end {
+        if control.is_cancelled() {
+            return Err(TdmsReadError::Cancelled);
+        }
+        let count = (end - position).min(buffer.len() as u64) as usize;
+        let values_read = decimate::read_data_as_f64(channel, position, &mut buffer[..count])?;
+        if values_read == 0 {
+            break;
+        }
+        points.extend(
+            buffer[..values_read]
+                .iter()
+                .enumerate()
+                .map(|(i, &y)| (mapping.x((position + i as u64) as f64), y)),
+        );
+        position += values_read as u64;
+        control.report_progress(position - offset, length);
+    }
+    Ok(points)
+}
+
+/// Read a min/max envelope of `length` values of a numeric channel starting at `offset`,
+/// pairing each bucket with the x value at its midpoint
+pub(crate) fn read_xy_decimated<R: Read + Seek>(
+    channel: &Channel<R>,
+    x_mode: XMode,
+    offset: u64,
+    length: u64,
+    bucket_count: usize,
+    control: &mut ReadControl,
+) -> Result<Vec<(f64, MinMaxBucket)>> {
+    let mapping = XMapping::new(channel, x_mode)?;
+    let buckets = decimate::read_decimated(channel, offset, length, bucket_count, control)?;
+    Ok(buckets
+        .into_iter()
+        .map(|bucket| {
+            let midpoint = bucket.first_index as f64 + (bucket.count - 1) as f64 / 2.0;
+            (mapping.x(midpoint), bucket)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::properties::{TdmsProperty, TdmsValue};
+    use crate::timestamp::Timestamp;
+    use crate::writer::{TdmsWriter, WriteObject};
+    use crate::TdmsFile;
+    use std::io::Cursor;
+
+    fn write_file(values: &[i32], properties: &[TdmsProperty]) -> TdmsFile<Cursor<Vec<u8>>> {
+        let mut writer = TdmsWriter::new(Vec::new());
+        writer
+            .write_segment(&[WriteObject::channel("Group", "Channel")
+                .with_properties(properties)
+                .with_data(values)
+                .unwrap()])
+            .unwrap();
+        TdmsFile::new(Cursor::new(writer.into_inner())).unwrap()
+    }
+
+    fn timing_properties() -> Vec<TdmsProperty> {
+        vec![
+            TdmsProperty::new(
+                "wf_start_time",
+                // 1970-01-01 00:00:10 UTC
+                TdmsValue::Timestamp(Timestamp::new(2_082_844_810, 0)),
+            ),
+            TdmsProperty::new("wf_start_offset", TdmsValue::Float64(0.5)),
+            TdmsProperty::new("wf_increment", TdmsValue::Float64(0.25)),
+        ]
+    }
+
+    #[test]
+    fn read_xy_pairs() {
+        let tdms_file = write_file(&[3, 1, 4], &timing_properties());
+        let group = tdms_file.group("Group").unwrap();
+        let channel = group.channel("Channel").unwrap();
+
+        assert_eq!(
+            channel.read_xy(XMode::Index).unwrap(),
+            vec![(0.0, 3.0), (1.0, 1.0), (2.0, 4.0)]
+        );
+        assert_eq!(
+            channel.read_xy(XMode::RelativeTime).unwrap(),
+            vec![(0.5, 3.0), (0.75, 1.0), (1.0, 4.0)]
+        );
+        assert_eq!(
+            channel.read_xy(XMode::AbsoluteTimeSeconds).unwrap(),
+            vec![(10.5, 3.0), (10.75, 1.0), (11.0, 4.0)]
+        );
+        assert_eq!(
+            channel
+                .read_xy_with_control(XMode::RelativeTime, 1, 10, &mut ReadControl::new())
+                .unwrap(),
+            vec![(0.75, 1.0), (1.0, 4.0)]
+        );
+    }
+
+    #[test]
+    fn read_xy_without_timing() {
+        let tdms_file = write_file(&[3, 1, 4], &[]);
+        let group = tdms_file.group("Group").unwrap();
+        let channel = group.channel("Channel").unwrap();
+
+        assert_eq!(channel.read_xy(XMode::Index).unwrap().len(), 3);
+        assert!(channel.read_xy(XMode::RelativeTime).is_err());
+        assert!(channel.read_xy(XMode::AbsoluteTimeSeconds).is_err());
+    }
+
+    #[test]
+    fn read_xy_decimated_at_bucket_midpoints() {
+        let tdms_file = write_file(&[3, -1, 4, 1, -5, 9, 2], &timing_properties());
+        let group = tdms_file.group("Group").unwrap();
+        let channel = group.channel("Channel").unwrap();
+
+        let points = channel.read_xy_decimated(XMode::RelativeTime, 3).unwrap();
+
+        let summary: Vec<(f64, u64, f64, f64)> = points
+            .iter()
+            .map(|(x, bucket)| (*x, bucket.first_index, bucket.min, bucket.max))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (0.75, 0, -1.0, 4.0),
+                (1.5, 3, -5.0, 9.0),
+                (2.0, 6, 2.0, 2.0)
+            ]
+        );
+    }
+}
@@ -0,0 +1,232 @@
+#![allow(dead_code)]
+
+use rstdms::raw_writer::{self, RawIndex};
+use rstdms::{TdmsProperty, TdmsValue, TdsType};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+pub struct TestFile {
+    bytes: Vec<u8>,
+}
+
+pub const TOC_METADATA: u32 = 1 << 1;
+pub const TOC_NEW_OBJ_LIST: u32 = 1 << 2;
+pub const TOC_RAW_DATA: u32 = 1 << 3;
+pub const TOC_INTERLEAVED_DATA: u32 = 1 << 5;
+pub const TOC_BIG_ENDIAN: u32 = 1 << 6;
+pub const TOC_DAQMX_RAW_DATA: u32 = 1 << 7;
+
+impl TestFile {
+    pub fn new() -> TestFile {
+        TestFile { bytes: Vec::new() }
+    }
+
+    pub fn add_segment(&mut self, toc_mask: u32, metadata_bytes: &[u8], data_bytes: &[u8]) {
+        raw_writer::write_segment(&mut self.bytes, toc_mask, metadata_bytes, data_bytes).unwrap();
+    }
+
+    /// Add a segment that was not completely written, with the next segment offset
+    /// left set to all ones
+    pub fn add_incomplete_segment(
+        &mut self,
+        toc_mask: u32,
+        metadata_bytes: &Vec<u8>,
+        data_bytes: &Vec<u8>,
+    ) {
+        raw_writer::write_lead_in(
+            &mut self.bytes,
+            toc_mask,
+            raw_writer::VERSION_2_0,
+            raw_writer::INCOMPLETE_SEGMENT_OFFSET,
+            metadata_bytes.len() as u64,
+        )
+        .unwrap();
+        self.bytes.extend(metadata_bytes);
+        self.bytes.extend(data_bytes);
+    }
+
+    /// Add a segment with the BigEndian flag set, writing the lead in offsets in big
+    /// endian order. The metadata and data must already be big endian.
+    pub fn add_big_endian_segment(
+        &mut self,
+        toc_mask: u32,
+        metadata_bytes: &[u8],
+        data_bytes: &[u8],
+    ) {
+        self.bytes.extend(b"TDSm");
+        self.bytes
+            .extend(&((toc_mask | TOC_BIG_ENDIAN).to_le_bytes()));
+        self.bytes.extend(&raw_writer::VERSION_2_0.to_be_bytes());
+        self.bytes
+            .extend(&((metadata_bytes.len() + data_bytes.len()) as u64).to_be_bytes());
+        self.bytes
+            .extend(&(metadata_bytes.len() as u64).to_be_bytes());
+        self.bytes.extend(metadata_bytes);
+        self.bytes.extend(data_bytes);
+    }
+
+    pub fn add_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend(bytes);
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn into_cursor(self) -> Cursor<Vec<u8>> {
+        Cursor::new(self.bytes)
+    }
+}
+
+pub fn object_metadata(
+    path: &'static str,
+    raw_data_index: &[u8],
+    properties: Vec<(&'static str, u32, &[u8])>,
+) -> Vec<u8> {
+    let mut metadata_bytes = Vec::new();
+    write_string(path, &mut metadata_bytes);
+    metadata_bytes.extend(raw_data_index);
+    metadata_bytes.extend(&((properties.len() as u32).to_le_bytes()));
+    for (name, type_id, val) in properties {
+        write_string(name, &mut metadata_bytes);
+        metadata_bytes.extend(&(type_id.to_le_bytes()));
+        metadata_bytes.extend(val);
+    }
+    metadata_bytes
+}
+
+pub fn raw_data_index(data_type: u32, number_of_values: u64) -> Vec<u8> {
+    let mut index_bytes = Vec::new();
+    index_bytes.extend(&(20_u32.to_le_bytes())); // Raw data index length
+    index_bytes.extend(&(data_type.to_le_bytes())); // Data type
+    index_bytes.extend(&(1_u32.to_le_bytes())); // Dimension
+    index_bytes.extend(&(number_of_values.to_le_bytes())); // Number of values
+    index_bytes
+}
+
+/// A DAQmx raw data index with format changing scalers, each given as
+/// (DAQmx data type, raw buffer index, byte offset, scale id), and raw buffer widths
+pub fn daqmx_raw_data_index(
+    number_of_values: u64,
+    scalers: &[(u32, u32, u32, u32)],
+    widths: &[u32],
+) -> Vec<u8> {
+    let mut index_bytes = Vec::new();
+    index_bytes.extend(&(0x1269_u32.to_le_bytes())); // Format changing scaler header
+    index_bytes.extend(&(0xFFFFFFFF_u32.to_le_bytes())); // Data type
+    index_bytes.extend(&(1_u32.to_le_bytes())); // Dimension
+    index_bytes.extend(&(number_of_values.to_le_bytes())); // Number of values
+    index_bytes.extend(&((scalers.len() as u32).to_le_bytes()));
+    for (data_type, buffer_index, byte_offset, scale_id) in scalers {
+        index_bytes.extend(&(data_type.to_le_bytes()));
+        index_bytes.extend(&(buffer_index.to_le_bytes()));
+        index_bytes.extend(&(byte_offset.to_le_bytes()));
+        index_bytes.extend(&(0_u32.to_le_bytes())); // Sample format bitmap
+        index_bytes.extend(&(scale_id.to_le_bytes()));
+    }
+    index_bytes.extend(&((widths.len() as u32).to_le_bytes()));
+    for width in widths {
+        index_bytes.extend(&(width.to_le_bytes()));
+    }
+    index_bytes
+}
+
+pub fn string_raw_data_index(number_of_values: u64, total_size: u64) -> Vec<u8> {
+    let mut index_bytes = Vec::new();
+    index_bytes.extend(&(28_u32.to_le_bytes())); // Raw data index length
+    index_bytes.extend(&(0x20_u32.to_le_bytes())); // Data type
+    index_bytes.extend(&(1_u32.to_le_bytes())); // Dimension
+    index_bytes.extend(&(number_of_values.to_le_bytes())); // Number of values
+    index_bytes.extend(&(total_size.to_le_bytes())); // Total size in bytes
+    index_bytes
+}
+
+/// Segment metadata for objects given as a path, raw data index and properties
+pub fn raw_metadata(objects: &[(&str, RawIndex, Vec<TdmsProperty>)]) -> Vec<u8> {
+    let mut metadata_bytes = Vec::new();
+    raw_writer::write_object_count(&mut metadata_bytes, objects.len() as u32).unwrap();
+    for (path, index, properties) in objects {
+        raw_writer::write_object_meta(&mut metadata_bytes, path, index, properties).unwrap();
+    }
+    metadata_bytes
+}
+
+/// Raw data index for I32 values
+pub fn i32_index(number_of_values: u64) -> RawIndex {
+    RawIndex::Values {
+        data_type: TdsType::I32,
+        number_of_values,
+    }
+}
+
+pub fn metadata(objects: Vec<Vec<u8>>) -> Vec<u8> {
+    let mut metadata_bytes = Vec::new();
+    metadata_bytes.extend(&((objects.len() as u32).to_le_bytes()));
+    for object in objects {
+        metadata_bytes.extend(object);
+    }
+    metadata_bytes
+}
+
+pub fn data_bytes_i32(data: Vec<i32>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    raw_writer::write_raw_values(&mut bytes, &data).unwrap();
+    bytes
+}
+
+pub fn data_bytes_f64(data: Vec<f64>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    raw_writer::write_raw_values(&mut bytes, &data).unwrap();
+    bytes
+}
+
+/// String channel data as end offsets followed by the concatenated strings
+pub fn data_bytes_strings(data: Vec<&str>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    raw_writer::write_raw_strings(&mut bytes, &data).unwrap();
+    bytes
+}
+
+pub fn write_string(string: &str, bytes: &mut Vec<u8>) {
+    bytes.extend(&(string.len() as u32).to_le_bytes());
+    bytes.extend(string.bytes());
+}
+
+/// A segment with a new object list containing a group with a property and the group's
+/// I32 channel "Channel1"
+pub fn channel_segment(values: Vec<i32>) -> Vec<u8> {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = raw_metadata(&[
+        (
+            "/'Group'",
+            RawIndex::NoData,
+            vec![TdmsProperty::new("group_property", TdmsValue::Int32(7))],
+        ),
+        (
+            "/'Group'/'Channel1'",
+            i32_index(values.len() as u64),
+            Vec::new(),
+        ),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(values));
+    test_file.into_bytes()
+}
+
+/// Create an empty temporary directory to hold a test's files
+pub fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("rstdms_{}_{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    path
+}
+
+/// Create a temporary file containing the given bytes, which can be appended to
+/// while it is open for reading
+pub fn write_temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("rstdms_{}_{}.tdms", std::process::id(), name));
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
@@ -0,0 +1,175 @@
+#![cfg(feature = "cache")]
+
+extern crate hex_literal;
+
+mod common;
+
+use common::*;
+use rstdms::{ErrorPolicy, ReadOptions, TdmsFile};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+fn read_channel(tdms_file: &TdmsFile<File>) -> Vec<i32> {
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    let mut data = vec![0; channel.len() as usize];
+    channel.read_all_data(&mut data).unwrap();
+    data
+}
+
+fn cache_files(cache_dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(cache_dir)
+        .map(|entries| entries.map(|entry| entry.unwrap().path()).collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn reopen_from_cache() {
+    let dir = temp_dir("reopen");
+    let cache_dir = dir.join("cache");
+    let path = dir.join("test.tdms");
+    let mut bytes = channel_segment(vec![1, 2, 3]);
+    bytes.extend(channel_segment(vec![4, 5]));
+    File::create(&path).unwrap().write_all(&bytes).unwrap();
+
+    let tdms_file = TdmsFile::open_cached(&path, &cache_dir).unwrap();
+    assert_eq!(read_channel(&tdms_file), vec![1, 2, 3, 4, 5]);
+    assert_eq!(cache_files(&cache_dir).len(), 1);
+
+    let tdms_file = TdmsFile::open_cached(&path, &cache_dir).unwrap();
+    assert_eq!(read_channel(&tdms_file), vec![1, 2, 3, 4, 5]);
+    assert_eq!(tdms_file.segments().unwrap().len(), 2);
+    let group = tdms_file.group("Group").unwrap();
+    assert_eq!(group.properties().len(), 1);
+    assert_eq!(group.properties()[0].name.as_str(), "group_property");
+    assert_eq!(cache_files(&cache_dir).len(), 1);
+}
+
+#[test]
+fn out_of_date_cache_is_replaced() {
+    let dir = temp_dir("stale");
+    let cache_dir = dir.join("cache");
+    let path = dir.join("test.tdms");
+    File::create(&path)
+        .unwrap()
+        .write_all(&channel_segment(vec![1, 2]))
+        .unwrap();
+    TdmsFile::open_cached(&path, &cache_dir).unwrap();
+
+    OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap()
+        .write_all(&channel_segment(vec![3]))
+        .unwrap();
+
+    let tdms_file = TdmsFile::open_cached(&path, &cache_dir).unwrap();
+    assert_eq!(read_channel(&tdms_file), vec![1, 2, 3]);
+    let tdms_file = TdmsFile::open_cached(&path, &cache_dir).unwrap();
+    assert_eq!(read_channel(&tdms_file), vec![1, 2, 3]);
+}
+
+#[test]
+fn corrupt_cache_is_replaced() {
+    let dir = temp_dir("corrupt");
+    let cache_dir = dir.join("cache");
+    let path = dir.join("test.tdms");
+    File::create(&path)
+        .unwrap()
+        .write_all(&channel_segment(vec![1, 2]))
+        .unwrap();
+    TdmsFile::open_cached(&path, &cache_dir).unwrap();
+    let cache_path = cache_files(&cache_dir).pop().unwrap();
+    let mut cache_bytes = std::fs::read(&cache_path).unwrap();
+    let length = cache_bytes.len();
+    cache_bytes.truncate(length / 2);
+    std::fs::write(&cache_path, &cache_bytes).unwrap();
+
+    let tdms_file = TdmsFile::open_cached(&path, &cache_dir).unwrap();
+    assert_eq!(read_channel(&tdms_file), vec![1, 2]);
+    assert_eq!(std::fs::read(&cache_path).unwrap().len(), length);
+}
+
+#[test]
+fn cache_is_not_used_with_different_options() {
+    let dir = temp_dir("options");
+    let cache_dir = dir.join("cache");
+    let path = dir.join("test.tdms");
+    let mut bytes = channel_segment(vec![1, 2]);
+    bytes.extend(b"not a segment");
+    File::create(&path).unwrap().write_all(&bytes).unwrap();
+
+    let options = ReadOptions::new().error_policy(ErrorPolicy::Skip);
+    let tdms_file = TdmsFile::open_cached_with_options(&path, &cache_dir, &options).unwrap();
+    assert_eq!(read_channel(&tdms_file), vec![1, 2]);
+    assert_eq!(tdms_file.skipped_segments().len(), 1);
+    let tdms_file = TdmsFile::open_cached_with_options(&path, &cache_dir, &options).unwrap();
+    assert_eq!(tdms_file.skipped_segments().len(), 1);
+
+    assert!(TdmsFile::open_cached(&path, &cache_dir).is_err());
+}
+
+#[test]
+fn overview_is_stored_in_cache() {
+    let dir = temp_dir("overview");
+    let cache_dir = dir.join("cache");
+    let path = dir.join("test.tdms");
+    let mut bytes = channel_segment(vec![3, 1, 2]);
+    bytes.extend(channel_segment(vec![5, 4]));
+    File::create(&path).unwrap().write_all(&bytes).unwrap();
+    let options = ReadOptions::new().overview(true);
+
+    // A cache written without an overview gains one when opened with the option
+    let tdms_file = TdmsFile::open_cached(&path, &cache_dir).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    assert_eq!(channel.overview(), None);
+    let cache_path = cache_files(&cache_dir).pop().unwrap();
+    let cache_length = std::fs::metadata(&cache_path).unwrap().len();
+
+    for _ in 0..2 {
+        let tdms_file = TdmsFile::open_cached_with_options(&path, &cache_dir, &options).unwrap();
+        let group = tdms_file.group("Group").unwrap();
+        let channel = group.channel("Channel1").unwrap();
+        assert_eq!(channel.overview(), Some(vec![(1.0, 3.0), (4.0, 5.0)]));
+        assert_eq!(channel.overview_value_ranges(), Some(vec![0..3, 3..5]));
+    }
+    assert!(std::fs::metadata(&cache_path).unwrap().len() > cache_length);
+
+    // The overview is kept in the cache but not used when not requested
+    let tdms_file = TdmsFile::open_cached(&path, &cache_dir).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    assert_eq!(channel.overview(), None);
+    let tdms_file = TdmsFile::open_cached_with_options(&path, &cache_dir, &options).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    assert_eq!(channel.overview(), Some(vec![(1.0, 3.0), (4.0, 5.0)]));
+}
+
+#[test]
+fn overview_is_updated_on_refresh() {
+    let dir = temp_dir("overview_refresh");
+    let cache_dir = dir.join("cache");
+    let path = dir.join("test.tdms");
+    File::create(&path)
+        .unwrap()
+        .write_all(&channel_segment(vec![1, 2]))
+        .unwrap();
+    let options = ReadOptions::new().overview(true);
+    let mut tdms_file = TdmsFile::open_cached_with_options(&path, &cache_dir, &options).unwrap();
+
+    OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap()
+        .write_all(&channel_segment(vec![-3]))
+        .unwrap();
+
+    assert!(tdms_file.refresh().unwrap());
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    assert_eq!(channel.overview(), Some(vec![(1.0, 2.0), (-3.0, -3.0)]));
+    assert_eq!(channel.overview_value_ranges(), Some(vec![0..2, 2..3]));
+}
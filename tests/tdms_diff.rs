@@ -0,0 +1,266 @@
+extern crate hex_literal;
+
+mod common;
+
+use common::*;
+use rstdms::{
+    diff, DiffObject, DiffOptions, DiffSide, Difference, PropertySnapshot, SnapshotChange,
+    SnapshotOptions, TdmsFile, TdmsValue, TdsType,
+};
+use std::collections::HashSet;
+use std::io::Cursor;
+
+fn file_with_channels(channels: Vec<(&'static str, Vec<f64>)>, unit: &'static str) -> TestFile {
+    let mut test_file = TestFile::new();
+    let mut objects = Vec::new();
+    let mut data = Vec::new();
+    for (path, values) in channels.iter() {
+        let mut unit_bytes = Vec::new();
+        write_string(unit, &mut unit_bytes);
+        objects.push(object_metadata(
+            path,
+            &raw_data_index(10, values.len() as u64),
+            vec![("unit_string", 0x20, &unit_bytes)],
+        ));
+        data.extend(values);
+    }
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata(objects), &data_bytes_f64(data));
+    test_file
+}
+
+fn open(test_file: TestFile) -> TdmsFile<Cursor<Vec<u8>>> {
+    TdmsFile::new(test_file.into_cursor()).unwrap()
+}
+
+#[test]
+fn identical_files_have_no_differences() {
+    let left = open(file_with_channels(
+        vec![("/'Group'/'Channel1'", vec![1.0, 2.0, 3.0])],
+        "V",
+    ));
+    let right = open(file_with_channels(
+        vec![("/'Group'/'Channel1'", vec![1.0, 2.0, 3.0])],
+        "V",
+    ));
+    let options = DiffOptions {
+        compare_data: true,
+        ..DiffOptions::default()
+    };
+
+    let report = diff(&left, &right, &options).unwrap();
+
+    assert!(report.is_empty(), "Unexpected differences: {}", report);
+}
+
+#[test]
+fn missing_channels_are_reported() {
+    let left = open(file_with_channels(
+        vec![
+            ("/'Group'/'Channel1'", vec![1.0]),
+            ("/'Group'/'Channel2'", vec![1.0]),
+        ],
+        "V",
+    ));
+    let right = open(file_with_channels(
+        vec![
+            ("/'Group'/'Channel1'", vec![1.0]),
+            ("/'Group'/'Channel3'", vec![1.0]),
+        ],
+        "V",
+    ));
+
+    let report = diff(&left, &right, &DiffOptions::default()).unwrap();
+
+    assert_eq!(
+        report.differences,
+        vec![
+            Difference::ChannelOnlyIn {
+                side: DiffSide::Left,
+                group: String::from("Group"),
+                channel: String::from("Channel2"),
+            },
+            Difference::ChannelOnlyIn {
+                side: DiffSide::Right,
+                group: String::from("Group"),
+                channel: String::from("Channel3"),
+            },
+        ]
+    );
+}
+
+#[test]
+fn property_differences_are_reported_unless_ignored() {
+    let left = open(file_with_channels(
+        vec![("/'Group'/'Channel1'", vec![1.0])],
+        "V",
+    ));
+    let right = open(file_with_channels(
+        vec![("/'Group'/'Channel1'", vec![1.0])],
+        "mV",
+    ));
+
+    let report = diff(&left, &right, &DiffOptions::default()).unwrap();
+    assert_eq!(report.differences.len(), 1);
+    match report.differences[0] {
+        Difference::Property {
+            ref name,
+            ref left,
+            ref right,
+            ..
+        } => {
+            assert_eq!(name, "unit_string");
+            assert_eq!(left, &Some(TdmsValue::String(String::from("V"))));
+            assert_eq!(right, &Some(TdmsValue::String(String::from("mV"))));
+        }
+        ref other => panic!("Unexpected difference {:?}", other),
+    }
+
+    let options = DiffOptions {
+        ignore_properties: vec![String::from("unit_string")],
+        ..DiffOptions::default()
+    };
+    let report = diff(&left, &right, &options).unwrap();
+    assert!(report.is_empty(), "Unexpected differences: {}", report);
+}
+
+#[test]
+fn first_differing_data_index_is_reported() {
+    let left = open(file_with_channels(
+        vec![("/'Group'/'Channel1'", vec![1.0, 2.0, 3.0, 4.0, 5.0])],
+        "V",
+    ));
+    let right = open(file_with_channels(
+        vec![("/'Group'/'Channel1'", vec![1.0, 2.0, 3.0, 4.5, 5.5])],
+        "V",
+    ));
+    let options = DiffOptions {
+        compare_data: true,
+        chunk_size: Some(2),
+        ..DiffOptions::default()
+    };
+
+    let report = diff(&left, &right, &options).unwrap();
+
+    assert_eq!(
+        report.differences,
+        vec![Difference::Data {
+            group: String::from("Group"),
+            channel: String::from("Channel1"),
+            first_index: 3,
+        }]
+    );
+}
+
+#[test]
+fn data_within_tolerance_matches() {
+    let left = open(file_with_channels(
+        vec![("/'Group'/'Channel1'", vec![1.0, 2.0, 3.0])],
+        "V",
+    ));
+    let right = open(file_with_channels(
+        vec![("/'Group'/'Channel1'", vec![1.0, 2.0 + 1e-12, 3.0])],
+        "V",
+    ));
+    let options = DiffOptions {
+        compare_data: true,
+        absolute_tolerance: 1e-9,
+        ..DiffOptions::default()
+    };
+
+    let report = diff(&left, &right, &options).unwrap();
+
+    assert!(report.is_empty(), "Unexpected differences: {}", report);
+}
+
+#[test]
+fn snapshot_changes_are_categorized() {
+    let old = open(file_with_channels(
+        vec![
+            ("/'Group'/'Channel1'", vec![1.0]),
+            ("/'Group'/'Channel2'", vec![1.0]),
+        ],
+        "V",
+    ))
+    .snapshot();
+    let new = open(file_with_channels(
+        vec![
+            ("/'Group'/'Channel1'", vec![1.0, 2.0]),
+            ("/'Group'/'Channel3'", vec![1.0]),
+        ],
+        "mV",
+    ))
+    .snapshot();
+
+    let group = old.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    assert_eq!(channel.data_type, Some(TdsType::DoubleFloat));
+    assert_eq!(channel.length, 1);
+    assert_eq!(
+        channel.properties,
+        vec![PropertySnapshot {
+            name: String::from("unit_string"),
+            data_type: TdsType::String,
+            value: String::from("V"),
+        }]
+    );
+
+    let changes = old.diff(&new).changes;
+    assert_eq!(changes.len(), 4, "Unexpected changes: {:?}", changes);
+    assert!(matches!(
+        changes[0],
+        SnapshotChange::ChannelChanged { ref channel, ref old, ref new, .. }
+            if channel == "Channel1" && old.length == 1 && new.length == 2
+    ));
+    assert!(matches!(
+        changes[1],
+        SnapshotChange::PropertyChanged { ref object, ref old, ref new }
+            if *object == DiffObject::Channel(String::from("Group"), String::from("Channel1"))
+                && old.value == "V"
+                && new.value == "mV"
+    ));
+    assert_eq!(
+        changes[2],
+        SnapshotChange::ChannelRemoved {
+            group: String::from("Group"),
+            channel: String::from("Channel2"),
+        }
+    );
+    assert_eq!(
+        changes[3],
+        SnapshotChange::ChannelAdded {
+            group: String::from("Group"),
+            channel: String::from("Channel3"),
+        }
+    );
+    assert!(new.diff(&new).is_empty());
+}
+
+#[test]
+fn snapshots_include_selected_properties() {
+    let file = open(file_with_channels(
+        vec![("/'Group'/'Channel1'", vec![1.0])],
+        "V",
+    ));
+    let options = SnapshotOptions {
+        properties: Some(vec![String::from("wf_increment")]),
+        ..SnapshotOptions::default()
+    };
+    let selected = file.snapshot_with_options(&options);
+    assert!(selected.groups[0].channels[0].properties.is_empty());
+
+    let other = open(file_with_channels(
+        vec![("/'Group'/'Channel1'", vec![2.0])],
+        "mV",
+    ));
+    assert!(selected
+        .diff(&other.snapshot_with_options(&options))
+        .is_empty());
+    assert!(!file.snapshot().diff(&other.snapshot()).is_empty());
+
+    // Snapshots of files with the same metadata are equal and hash the same
+    let snapshots: HashSet<_> = vec![file.snapshot(), other.snapshot(), file.snapshot()]
+        .into_iter()
+        .collect();
+    assert_eq!(snapshots.len(), 2);
+}
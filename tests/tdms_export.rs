@@ -0,0 +1,324 @@
+mod common;
+
+use common::*;
+use rstdms::{
+    export_csv, export_line_protocol, CsvExportOptions, CsvTimeColumn, LineProtocolOptions,
+    RawExportOptions, TdmsFile, TdmsProperty, TdmsReadError, TdmsValue, TdmsWriter, TdsType,
+    Timestamp, WriteObject,
+};
+use std::convert::TryInto;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+fn write_file(segments: Vec<Vec<WriteObject>>) -> TdmsFile<Cursor<Vec<u8>>> {
+    let mut writer = TdmsWriter::new(Vec::new());
+    for objects in segments {
+        writer.write_segment(&objects).unwrap();
+    }
+    TdmsFile::new(Cursor::new(writer.into_inner())).unwrap()
+}
+
+fn export(tdms_file: &TdmsFile<Cursor<Vec<u8>>>, options: &CsvExportOptions) -> String {
+    let group = tdms_file.group("Group").unwrap();
+    let channels: Vec<_> = group.channels().collect();
+    let mut output = Vec::new();
+    export_csv(&channels, &mut output, options).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn export_channels_of_different_lengths() {
+    let tdms_file = write_file(vec![vec![
+        WriteObject::channel("Group", "Ints")
+            .with_data(&[1i32, 2, 3])
+            .unwrap(),
+        WriteObject::channel("Group", "Strings")
+            .with_string_data(&["a", "b,c"])
+            .unwrap(),
+    ]]);
+
+    let csv = export(&tdms_file, &CsvExportOptions::default());
+
+    assert_eq!(csv, "Group/Ints,Group/Strings\n1,a\n2,\"b,c\"\n3,\n");
+}
+
+#[test]
+fn export_range_with_relative_time() {
+    let timing = vec![TdmsProperty::new("wf_increment", TdmsValue::Float64(0.5))];
+    let tdms_file = write_file(vec![
+        vec![WriteObject::channel("Group", "Channel")
+            .with_properties(&timing)
+            .with_data(&[0.0f64, 1.0, 2.0])
+            .unwrap()],
+        vec![WriteObject::channel("Group", "Channel")
+            .with_data(&[3.0f64, 4.0, 5.0])
+            .unwrap()],
+    ]);
+    let options = CsvExportOptions {
+        time_column: CsvTimeColumn::Relative,
+        offset: 2,
+        length: Some(3),
+        chunk_size: Some(2),
+    };
+
+    let csv = export(&tdms_file, &options);
+
+    assert_eq!(csv, "Time,Group/Channel\n1,2\n1.5,3\n2,4\n");
+}
+
+#[test]
+fn export_index_without_timing() {
+    let tdms_file = write_file(vec![vec![WriteObject::channel("Group", "Channel")
+        .with_data(&[7u8, 8])
+        .unwrap()]]);
+    let options = CsvExportOptions {
+        time_column: CsvTimeColumn::Absolute,
+        ..CsvExportOptions::default()
+    };
+
+    let csv = export(&tdms_file, &options);
+
+    assert_eq!(csv, "Index,Group/Channel\n0,7\n1,8\n");
+}
+
+#[test]
+fn export_index_when_timing_differs() {
+    let fast = vec![TdmsProperty::new("wf_increment", TdmsValue::Float64(0.5))];
+    let slow = vec![TdmsProperty::new("wf_increment", TdmsValue::Float64(1.0))];
+    let tdms_file = write_file(vec![vec![
+        WriteObject::channel("Group", "Fast")
+            .with_properties(&fast)
+            .with_data(&[1i32, 2])
+            .unwrap(),
+        WriteObject::channel("Group", "Slow")
+            .with_properties(&slow)
+            .with_data(&[3i32, 4])
+            .unwrap(),
+    ]]);
+    let options = CsvExportOptions {
+        time_column: CsvTimeColumn::Relative,
+        ..CsvExportOptions::default()
+    };
+
+    let csv = export(&tdms_file, &options);
+
+    assert_eq!(csv, "Index,Group/Fast,Group/Slow\n0,1,3\n1,2,4\n");
+}
+
+#[test]
+fn export_line_protocol_in_file_order() {
+    let timing = vec![
+        TdmsProperty::new(
+            "wf_start_time",
+            TdmsValue::Timestamp(Timestamp::from_unix_nanos(1_000_000_000).unwrap()),
+        ),
+        TdmsProperty::new("wf_increment", TdmsValue::Float64(0.5)),
+    ];
+    let tdms_file = write_file(vec![
+        vec![
+            WriteObject::channel("My Group", "Pressure")
+                .with_properties(&timing)
+                .with_data(&[1.5f64, f64::NAN])
+                .unwrap(),
+            WriteObject::channel("My Group", "Count, total")
+                .with_data(&[-3i32])
+                .unwrap(),
+        ],
+        vec![
+            WriteObject::channel("My Group", "Pressure")
+                .with_data(&[2.0f64])
+                .unwrap(),
+            WriteObject::channel("My Group", "Note")
+                .with_string_data(&["say \"hi\""])
+                .unwrap(),
+            WriteObject::channel("My Group", "Flags")
+                .with_data(&[7u8])
+                .unwrap(),
+        ],
+    ]);
+    let group = tdms_file.group("My Group").unwrap();
+    let channels: Vec<_> = group.channels().collect();
+    let mut output = Vec::new();
+
+    let lines =
+        export_line_protocol(&channels, &mut output, &LineProtocolOptions::default()).unwrap();
+
+    assert_eq!(lines, 5);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "My\\ Group,channel=Pressure value=1.5 1000000000\n\
+         My\\ Group,channel=Count\\,\\ total value=-3i\n\
+         My\\ Group,channel=Pressure value=2 2000000000\n\
+         My\\ Group,channel=Note value=\"say \\\"hi\\\"\"\n\
+         My\\ Group,channel=Flags value=7u\n"
+    );
+
+    let options = LineProtocolOptions {
+        measurement: Some(String::from("tdms")),
+        field_key: String::from("v"),
+    };
+    let mut output = Vec::new();
+    export_line_protocol(&channels[1..2], &mut output, &options).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "tdms,channel=Count\\,\\ total v=-3i\n"
+    );
+}
+
+fn raw_export_file() -> TdmsFile<Cursor<Vec<u8>>> {
+    let unit = vec![TdmsProperty::new(
+        "unit_string",
+        TdmsValue::String(String::from("V")),
+    )];
+    write_file(vec![
+        vec![
+            WriteObject::channel("Group", "Volts")
+                .with_properties(&unit)
+                .with_data(&[1.5f64, 2.5, 3.5])
+                .unwrap(),
+            WriteObject::channel("Group", "Counts")
+                .with_data(&[1i16, 2])
+                .unwrap(),
+            WriteObject::channel("Other/Group", "Counts")
+                .with_data(&[3u32])
+                .unwrap(),
+            WriteObject::channel("other?group", "counts")
+                .with_data(&[4u32])
+                .unwrap(),
+            WriteObject::channel("Group", "Names")
+                .with_string_data(&["a"])
+                .unwrap(),
+        ],
+        vec![WriteObject::channel("Group", "Counts")
+            .with_data(&[3i16, 4, 5])
+            .unwrap()],
+    ])
+}
+
+#[test]
+fn export_raw_split_files() {
+    let tdms_file = raw_export_file();
+    let out_dir = temp_dir("split");
+    let options = RawExportOptions {
+        chunk_size: Some(2),
+        ..RawExportOptions::default()
+    };
+
+    let files = tdms_file
+        .export_raw_split(
+            &out_dir,
+            &[
+                "/'Group'/'Volts'",
+                "/'Group'/'Counts'",
+                "/'Other/Group'/'Counts'",
+                "/'other?group'/'counts'",
+            ],
+            &options,
+        )
+        .unwrap();
+
+    let names: Vec<String> = files
+        .iter()
+        .map(|file| {
+            file.data_path
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "Group__Volts.f64",
+            "Group__Counts.f64",
+            "Other_Group__Counts.f64",
+            "other_group__counts_2.f64",
+        ]
+    );
+    assert!(files
+        .iter()
+        .all(|file| file.data_type == TdsType::DoubleFloat));
+    assert_eq!(
+        files.iter().map(|file| file.count).collect::<Vec<_>>(),
+        vec![3, 5, 1, 1]
+    );
+
+    let values = |path: &PathBuf| -> Vec<f64> {
+        std::fs::read(path)
+            .unwrap()
+            .chunks(8)
+            .map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap()))
+            .collect()
+    };
+    assert_eq!(values(&files[0].data_path), vec![1.5, 2.5, 3.5]);
+    assert_eq!(values(&files[1].data_path), vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(
+        std::fs::read_to_string(&files[0].metadata_path).unwrap(),
+        "{\n  \"group\": \"Group\",\n  \"channel\": \"Volts\",\n  \
+        \"data_file\": \"Group__Volts.f64\",\n  \"dtype\": \"f64\",\n  \
+        \"byte_order\": \"little\",\n  \"count\": 3,\n  \"unit\": \"V\"\n}\n"
+    );
+    assert!(std::fs::read_to_string(&files[1].metadata_path)
+        .unwrap()
+        .contains("\"unit\": null"));
+    std::fs::remove_dir_all(&out_dir).unwrap();
+}
+
+#[test]
+fn export_raw_split_native() {
+    let tdms_file = raw_export_file();
+    let out_dir = temp_dir("native");
+    let options = RawExportOptions {
+        native: true,
+        ..RawExportOptions::default()
+    };
+
+    let files = tdms_file
+        .export_raw_split(
+            &out_dir,
+            &["/'Group'/'Counts'", "/'Group'/'Volts'"],
+            &options,
+        )
+        .unwrap();
+
+    assert!(files[0].data_path.ends_with("Group__Counts.i16"));
+    assert_eq!(files[0].data_type, TdsType::I16);
+    assert_eq!(
+        std::fs::read(&files[0].data_path).unwrap(),
+        vec![1, 0, 2, 0, 3, 0, 4, 0, 5, 0]
+    );
+    assert!(std::fs::read_to_string(&files[0].metadata_path)
+        .unwrap()
+        .contains("\"dtype\": \"i16\""));
+    assert!(files[1].data_path.ends_with("Group__Volts.f64"));
+    std::fs::remove_dir_all(&out_dir).unwrap();
+}
+
+#[test]
+fn export_raw_split_errors() {
+    let tdms_file = raw_export_file();
+    let out_dir = temp_dir("errors").join("raw");
+    let options = RawExportOptions::default();
+
+    match tdms_file.export_raw_split(&out_dir, &["/'Group'/'Volt'"], &options) {
+        Err(TdmsReadError::NoSuchChannel { similar_names, .. }) => {
+            assert_eq!(similar_names, vec!["Volts"])
+        }
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert!(matches!(
+        tdms_file.export_raw_split(&out_dir, &["/'Group'"], &options),
+        Err(TdmsReadError::InvalidArgument(_))
+    ));
+    // Unsupported channels are found before any files are written
+    assert!(matches!(
+        tdms_file.export_raw_split(
+            &out_dir,
+            &["/'Group'/'Volts'", "/'Group'/'Names'"],
+            &options
+        ),
+        Err(TdmsReadError::Unsupported(_))
+    ));
+    assert!(!out_dir.exists());
+}
@@ -0,0 +1,124 @@
+#![cfg(feature = "sha256")]
+
+extern crate hex_literal;
+
+mod common;
+
+use common::*;
+use rstdms::{HashAlgo, TdmsFile};
+use sha2::{Digest, Sha256};
+
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(bytes).into()
+}
+
+#[test]
+fn channel_hash_is_hash_of_raw_bytes() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 2), Vec::new()),
+        object_metadata("/'Group'/'Channel2'", &raw_data_index(3, 2), Vec::new()),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![1, 2, 3, 4]));
+    test_file.add_segment(TOC_RAW_DATA, &Vec::new(), &data_bytes_i32(vec![5, 6, 7, 8]));
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel2").unwrap();
+    let hash = channel.content_hash(HashAlgo::Sha256).unwrap();
+
+    assert_eq!(hash, sha256(&data_bytes_i32(vec![3, 4, 7, 8])));
+}
+
+#[test]
+fn string_channel_hash_includes_offsets_and_strings() {
+    let mut test_file = TestFile::new();
+    let strings = vec!["abc", "de"];
+    let metadata_bytes = metadata(vec![object_metadata(
+        "/'Group'/'Channel1'",
+        &string_raw_data_index(2, 13),
+        Vec::new(),
+    )]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(
+        toc_mask,
+        &metadata_bytes,
+        &data_bytes_strings(strings.clone()),
+    );
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    let hash = channel.content_hash(HashAlgo::Sha256).unwrap();
+
+    assert_eq!(hash, sha256(&data_bytes_strings(strings)));
+}
+
+#[test]
+fn manifest_ignores_layout_and_channel_order() {
+    let mut interleaved_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 3), Vec::new()),
+        object_metadata("/'Group'/'Channel2'", &raw_data_index(3, 3), Vec::new()),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA | TOC_INTERLEAVED_DATA;
+    interleaved_file.add_segment(
+        toc_mask,
+        &metadata_bytes,
+        &data_bytes_i32(vec![1, 4, 2, 5, 3, 6]),
+    );
+    let interleaved_file = TdmsFile::new(interleaved_file.into_cursor()).unwrap();
+
+    // The same values, written contiguously in separate segments with the channels reversed
+    let mut contiguous_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Channel2'", &raw_data_index(3, 3), Vec::new()),
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 1), Vec::new()),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    contiguous_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![4, 5, 6, 1]));
+    let metadata_bytes = metadata(vec![object_metadata(
+        "/'Group'/'Channel1'",
+        &raw_data_index(3, 2),
+        Vec::new(),
+    )]);
+    contiguous_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![2, 3]));
+    let contiguous_file = TdmsFile::new(contiguous_file.into_cursor()).unwrap();
+
+    let interleaved_manifest = interleaved_file.content_manifest(HashAlgo::Sha256).unwrap();
+    let contiguous_manifest = contiguous_file.content_manifest(HashAlgo::Sha256).unwrap();
+
+    assert_eq!(interleaved_manifest, contiguous_manifest);
+    assert_eq!(
+        interleaved_manifest.channels["/'Group'/'Channel1'"],
+        sha256(&data_bytes_i32(vec![1, 2, 3]))
+    );
+    assert_eq!(
+        interleaved_manifest.channels["/'Group'/'Channel2'"],
+        sha256(&data_bytes_i32(vec![4, 5, 6]))
+    );
+}
+
+#[test]
+fn manifest_data_hash_changes_with_data() {
+    let manifest_of = |values: Vec<i32>| {
+        let mut test_file = TestFile::new();
+        let metadata_bytes = metadata(vec![object_metadata(
+            "/'Group'/'Channel1'",
+            &raw_data_index(3, values.len() as u64),
+            Vec::new(),
+        )]);
+        let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+        test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(values));
+        TdmsFile::new(test_file.into_cursor())
+            .unwrap()
+            .content_manifest(HashAlgo::Sha256)
+            .unwrap()
+    };
+
+    assert_ne!(
+        manifest_of(vec![1, 2, 3]).data_hash,
+        manifest_of(vec![1, 2, 4]).data_hash
+    );
+}
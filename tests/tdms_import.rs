@@ -0,0 +1,179 @@
+use rstdms::{
+    import_csv, ErrorPolicy, ImportOptions, ImportReport, SkippedRow, TdmsFile, TdmsReadError,
+    TdmsValue, TdsType, Timestamp,
+};
+use std::io::Cursor;
+
+fn import(csv: &str, options: &ImportOptions) -> (TdmsFile<Cursor<Vec<u8>>>, ImportReport) {
+    let mut output = Vec::new();
+    let report = import_csv(csv.as_bytes(), &mut output, options).unwrap();
+    (TdmsFile::new(Cursor::new(output)).unwrap(), report)
+}
+
+fn read_f64(tdms_file: &TdmsFile<Cursor<Vec<u8>>>, channel: &str) -> Vec<f64> {
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel(channel).unwrap();
+    let mut values = vec![0.0; channel.len() as usize];
+    channel.read_all_data(&mut values).unwrap();
+    values
+}
+
+#[test]
+fn import_typed_columns_in_segments() {
+    let csv = "Ints,Floats,Strings,Times\n\
+        1,1.5,a,2021-03-04T05:06:07Z\n\
+        2,,\"b,c\",2021-03-04T05:06:08Z\n\
+        3,3.5,d,2021-03-04T05:06:10Z\n";
+    let options = ImportOptions {
+        rows_per_segment: 2,
+        ..ImportOptions::default()
+    };
+
+    let (tdms_file, report) = import(csv, &options);
+
+    assert_eq!(report.rows_imported, 3);
+    assert_eq!(
+        report.channels,
+        vec![
+            (String::from("Ints"), TdsType::I64),
+            (String::from("Floats"), TdsType::DoubleFloat),
+            (String::from("Strings"), TdsType::String),
+            (String::from("Times"), TdsType::TimeStamp),
+        ]
+    );
+    assert_eq!(tdms_file.segments().unwrap().len(), 2);
+
+    let group = tdms_file.group("Group").unwrap();
+    let ints = group.channel("Ints").unwrap();
+    let mut values = vec![0i64; 3];
+    ints.read_all_data(&mut values).unwrap();
+    assert_eq!(values, vec![1, 2, 3]);
+
+    let floats = read_f64(&tdms_file, "Floats");
+    assert_eq!(floats[0], 1.5);
+    assert!(floats[1].is_nan());
+    assert_eq!(floats[2], 3.5);
+
+    let strings = group.channel("Strings").unwrap();
+    let mut values = vec![String::new(); 3];
+    strings.read_strings(0, &mut values).unwrap();
+    assert_eq!(values, vec!["a", "b,c", "d"]);
+
+    let times = group.channel("Times").unwrap();
+    let mut values = vec![Timestamp::new(0, 0); 3];
+    times.read_all_data(&mut values).unwrap();
+    let start = chrono::DateTime::parse_from_rfc3339("2021-03-04T05:06:07Z").unwrap();
+    assert_eq!(values[0], Timestamp::from_datetime(start.into()));
+}
+
+#[test]
+fn constant_rate_time_column_becomes_waveform_timing() {
+    let csv = "Time (s),Voltage (V)\n0.5,1.5\n0.75,2\n1.0,3\n1.25,4\n";
+    let options = ImportOptions {
+        parse_units: true,
+        time_column: Some(String::from("Time")),
+        ..ImportOptions::default()
+    };
+
+    let (tdms_file, report) = import(csv, &options);
+
+    assert!(report.waveform_timing);
+    let group = tdms_file.group("Group").unwrap();
+    assert!(group.channel("Time").is_none());
+    let channel = group.channel("Voltage").unwrap();
+    let unit = channel
+        .properties()
+        .iter()
+        .find(|property| property.name == "unit_string")
+        .map(|property| &property.value);
+    assert_eq!(unit, Some(&TdmsValue::String(String::from("V"))));
+    let timing = channel.waveform_timing().unwrap();
+    assert_eq!(timing.start_offset, 0.5);
+    assert_eq!(timing.increment, 0.25);
+    assert_eq!(timing.start_time, None);
+    assert_eq!(read_f64(&tdms_file, "Voltage"), vec![1.5, 2.0, 3.0, 4.0]);
+}
+
+#[test]
+fn irregular_time_column_is_written_as_a_channel() {
+    let csv = "Time,Value\n\
+        2021-03-04 05:06:07,1.0\n\
+        2021-03-04 05:06:08,2.0\n\
+        2021-03-04 05:06:10,3.0\n";
+    let options = ImportOptions {
+        time_column: Some(String::from("Time")),
+        ..ImportOptions::default()
+    };
+
+    let (tdms_file, report) = import(csv, &options);
+
+    assert!(!report.waveform_timing);
+    let group = tdms_file.group("Group").unwrap();
+    let time = group.channel("Time").unwrap();
+    assert_eq!(time.data_type(), Some(TdsType::TimeStamp));
+    assert!(group.channel("Value").unwrap().waveform_timing().is_none());
+}
+
+#[test]
+fn malformed_rows_are_skipped_or_fatal() {
+    let csv = "Time,Value\n0,1\n1,2\n2,3,3\n2,x\n2,3\n4,5\n";
+    let options = ImportOptions {
+        time_column: Some(String::from("Time")),
+        rows_per_segment: 2,
+        malformed_rows: ErrorPolicy::Skip,
+        ..ImportOptions::default()
+    };
+
+    let (tdms_file, report) = import(csv, &options);
+
+    assert_eq!(report.rows_imported, 3);
+    assert_eq!(
+        report.skipped_rows,
+        vec![
+            SkippedRow {
+                line: 4,
+                error: String::from("expected 2 fields but found 3"),
+            },
+            SkippedRow {
+                line: 5,
+                error: String::from("could not parse \"x\" as an integer in column \"Value\""),
+            },
+            SkippedRow {
+                line: 7,
+                error: String::from(
+                    "time \"4\" does not follow the constant rate of the time column, \
+                    expected 3 seconds"
+                ),
+            },
+        ]
+    );
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Value").unwrap();
+    let mut values = vec![0i64; channel.len() as usize];
+    channel.read_all_data(&mut values).unwrap();
+    assert_eq!(values, vec![1, 2, 3]);
+
+    let options = ImportOptions {
+        malformed_rows: ErrorPolicy::Fail,
+        ..options
+    };
+    match import_csv(csv.as_bytes(), Vec::new(), &options) {
+        Err(TdmsReadError::MalformedCsv { line, .. }) => assert_eq!(line, 4),
+        other => panic!("Expected malformed CSV error, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn missing_time_column_suggests_names() {
+    let options = ImportOptions {
+        time_column: Some(String::from("time")),
+        ..ImportOptions::default()
+    };
+
+    let error = import_csv("Time,Value\n0,1\n".as_bytes(), Vec::new(), &options).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "CSV data has no time column named \"time\", did you mean \"Time\"?"
+    );
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use common::*;
+use rstdms::{check_index, index_path, write_index, IndexStatus, TdmsFile, TdsType};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+fn append(path: &PathBuf, bytes: &[u8]) {
+    let mut file = OpenOptions::new().append(true).open(path).unwrap();
+    file.write_all(bytes).unwrap();
+}
+
+#[test]
+fn write_and_check_index() {
+    let dir = temp_dir("write");
+    let path = dir.join("data.tdms");
+    let mut bytes = channel_segment(vec![1, 2, 3]);
+    bytes.extend(channel_segment(vec![4, 5]));
+    std::fs::write(&path, &bytes).unwrap();
+
+    assert_eq!(check_index(&path).unwrap(), IndexStatus::Missing);
+    assert_eq!(write_index(&path).unwrap(), 2);
+    assert_eq!(check_index(&path).unwrap(), IndexStatus::Current);
+
+    // The index is each segment's lead in and metadata, with the index tag
+    let index = std::fs::read(index_path(&path)).unwrap();
+    assert_eq!(index_path(&path), dir.join("data.tdms_index"));
+    let segments = TdmsFile::open(&path).unwrap().segments().unwrap();
+    let data_length: u64 = segments.iter().map(|segment| segment.data_length).sum();
+    assert_eq!(index.len() as u64, bytes.len() as u64 - data_length);
+    assert_eq!(&index[..4], b"TDSh");
+    assert_eq!(&index[4..28], &bytes[4..28]);
+    let second_lead_in = index.len() / 2;
+    assert_eq!(&index[second_lead_in..second_lead_in + 4], b"TDSh");
+    assert!(!dir.join("data.tdms_index.tmp").exists());
+}
+
+#[test]
+fn index_is_stale_after_appending() {
+    let dir = temp_dir("stale");
+    let path = dir.join("data.tdms");
+    let first_segment = channel_segment(vec![1, 2, 3]);
+    std::fs::write(&path, &first_segment).unwrap();
+    write_index(&path).unwrap();
+
+    let second_segment = channel_segment(vec![4, 5]);
+    append(&path, &second_segment);
+
+    assert_eq!(
+        check_index(&path).unwrap(),
+        IndexStatus::Stale {
+            index_segments: 1,
+            index_end: first_segment.len() as u64,
+            data_segments: 2,
+            data_end: (first_segment.len() + second_segment.len()) as u64,
+        }
+    );
+    assert_eq!(write_index(&path).unwrap(), 2);
+    assert_eq!(check_index(&path).unwrap(), IndexStatus::Current);
+}
+
+#[test]
+fn incomplete_segment_is_not_indexed() {
+    let dir = temp_dir("incomplete");
+    let path = dir.join("data.tdms");
+    let mut test_file = TestFile::new();
+    test_file.add_bytes(&channel_segment(vec![1, 2, 3]));
+    let metadata_bytes = metadata(vec![object_metadata(
+        "/'Group'/'Channel1'",
+        &raw_data_index(TdsType::I32.to_u32(), 2),
+        Vec::new(),
+    )]);
+    test_file.add_incomplete_segment(
+        TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA,
+        &metadata_bytes,
+        &data_bytes_i32(vec![4]),
+    );
+    std::fs::write(&path, test_file.into_bytes()).unwrap();
+
+    assert_eq!(write_index(&path).unwrap(), 1);
+    assert_eq!(check_index(&path).unwrap(), IndexStatus::Current);
+}
+
+#[test]
+fn index_big_endian_segments() {
+    let dir = temp_dir("big_endian");
+    let path = dir.join("data.tdms");
+    let mut test_file = TestFile::new();
+    test_file.add_bytes(&channel_segment(vec![1, 2]));
+    test_file.add_big_endian_segment(TOC_RAW_DATA, &[], &[0, 0, 0, 3, 0, 0, 0, 4]);
+    test_file.add_bytes(&channel_segment(vec![5]));
+    std::fs::write(&path, test_file.into_bytes()).unwrap();
+
+    assert_eq!(write_index(&path).unwrap(), 3);
+    assert_eq!(check_index(&path).unwrap(), IndexStatus::Current);
+}
+
+#[test]
+fn invalid_index() {
+    let dir = temp_dir("invalid");
+    let path = dir.join("data.tdms");
+    std::fs::write(&path, channel_segment(vec![1, 2, 3])).unwrap();
+    std::fs::write(index_path(&path), b"not an index file, but long enough").unwrap();
+
+    match check_index(&path).unwrap() {
+        IndexStatus::Invalid(reason) => {
+            assert!(
+                reason.contains("Invalid index segment header"),
+                "{}",
+                reason
+            )
+        }
+        status => panic!("Unexpected status {:?}", status),
+    }
+
+    // A data file that isn't a TDMS file is an error rather than a status
+    std::fs::write(&path, b"not a TDMS file, but long enough to have a lead in").unwrap();
+    assert!(check_index(&path).is_err());
+    assert!(write_index(&path).is_err());
+    assert!(!dir.join("data.tdms_index.tmp").exists());
+}
@@ -0,0 +1,149 @@
+use rstdms::{TdmsFile, TdmsProperty, TdmsValue, TdmsWriter, WriteObject};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::Cursor;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Allocator that tracks the number of bytes currently allocated
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const CHANNEL_COUNT: usize = 1000;
+const PROPERTY_COUNT: usize = 50;
+
+#[test]
+fn property_metadata_memory() {
+    let properties: Vec<TdmsProperty> = (0..PROPERTY_COUNT)
+        .map(|i| TdmsProperty::new(&format!("property_name_{}", i), TdmsValue::Int32(i as i32)))
+        .collect();
+    let objects: Vec<WriteObject> = (0..CHANNEL_COUNT)
+        .map(|i| {
+            WriteObject::channel("Group", &format!("Channel{}", i))
+                .with_properties(&properties)
+                .with_data(&[1.0f64])
+                .unwrap()
+        })
+        .collect();
+    let mut writer = TdmsWriter::new(Vec::new());
+    writer.write_segment(&objects).unwrap();
+    drop(objects);
+    drop(properties);
+    let bytes = writer.into_inner();
+
+    let allocated_before = ALLOCATED.load(Ordering::SeqCst);
+    let tdms_file = TdmsFile::new(Cursor::new(bytes)).unwrap();
+    let allocated = ALLOCATED.load(Ordering::SeqCst) - allocated_before;
+
+    let group = tdms_file.group("Group").unwrap();
+    assert_eq!(group.channels().count(), CHANNEL_COUNT);
+
+    // Property names are shared between channels
+    let first = group.channel("Channel0").unwrap();
+    let last = group
+        .channel(&format!("Channel{}", CHANNEL_COUNT - 1))
+        .unwrap();
+    assert_eq!(first.properties().len(), PROPERTY_COUNT);
+    for (first, last) in first.properties().iter().zip(last.properties()) {
+        assert_eq!(first.name, last.name);
+        assert_eq!(first.name.as_ptr(), last.name.as_ptr());
+    }
+
+    // Allocations are dominated by the property values, not one allocation per name
+    let bytes_per_property = allocated / (CHANNEL_COUNT * PROPERTY_COUNT);
+    assert!(
+        bytes_per_property <= 64,
+        "Allocated {} bytes per property",
+        bytes_per_property
+    );
+}
+
+const SEGMENT_COUNT: usize = 2000;
+const SEGMENT_CHANNEL_COUNT: usize = 100;
+
+#[test]
+fn segment_metadata_memory() {
+    let mut writer = TdmsWriter::new(Vec::new());
+    for segment in 0..SEGMENT_COUNT {
+        let objects: Vec<WriteObject> = (0..SEGMENT_CHANNEL_COUNT)
+            .map(|i| {
+                WriteObject::channel("Group", &format!("Channel{}", i))
+                    .with_data(&[segment as f64])
+                    .unwrap()
+            })
+            .collect();
+        writer.write_segment(&objects).unwrap();
+    }
+    let bytes = writer.into_inner();
+
+    let allocated_before = ALLOCATED.load(Ordering::SeqCst);
+    let tdms_file = TdmsFile::new(Cursor::new(bytes)).unwrap();
+    let allocated = ALLOCATED.load(Ordering::SeqCst) - allocated_before;
+
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel0").unwrap();
+    assert_eq!(channel.len(), SEGMENT_COUNT as u64);
+    let mut data = vec![0.0; 10];
+    channel.read_data(1000, &mut data).unwrap();
+    assert_eq!(data[0], 1000.0);
+
+    // Segments share one object list and the channel data index stores a single
+    // run of segments per channel, so there is no per-channel cost for each segment
+    let bytes_per_segment = allocated / SEGMENT_COUNT;
+    assert!(
+        bytes_per_segment <= 200,
+        "Allocated {} bytes per segment",
+        bytes_per_segment
+    );
+}
+
+const STRING_VALUE_COUNT: usize = 100_000;
+
+#[test]
+fn estimated_read_memory() {
+    let strings: Vec<String> = (0..STRING_VALUE_COUNT)
+        .map(|i| format!("value {}", i))
+        .collect();
+    let string_refs: Vec<&str> = strings.iter().map(String::as_str).collect();
+    let mut writer = TdmsWriter::new(Vec::new());
+    for chunk in string_refs.chunks(STRING_VALUE_COUNT / 4) {
+        writer
+            .write_segment(&[WriteObject::channel("Group", "Strings")
+                .with_string_data(chunk)
+                .unwrap()])
+            .unwrap();
+    }
+    drop(string_refs);
+    let tdms_file = TdmsFile::new(Cursor::new(writer.into_inner())).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Strings").unwrap();
+    let estimate = channel.estimated_read_bytes(None) as usize;
+
+    let allocated_before = ALLOCATED.load(Ordering::SeqCst);
+    let mut values = vec![String::new(); channel.len() as usize];
+    channel.read_strings(0, &mut values).unwrap();
+    let allocated = ALLOCATED.load(Ordering::SeqCst) - allocated_before;
+
+    assert_eq!(values, strings);
+    // Reading also allocates reusable buffers, and strings may have spare capacity
+    assert!(
+        estimate <= allocated && allocated <= estimate * 3 / 2,
+        "Estimated {} bytes but allocated {}",
+        estimate,
+        allocated
+    );
+}
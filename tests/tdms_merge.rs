@@ -0,0 +1,251 @@
+mod common;
+
+use common::*;
+use rstdms::{
+    merge, MergeCompatibility, MergeOptions, PropertyPrecedence, SegmentTiming, TdmsFile,
+    TdmsProperty, TdmsValue, TdmsWriter, Timestamp, WriteObject,
+};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+/// Write a TDMS file to a temporary path, with one segment per set of objects
+fn write_input_file(name: &str, segments: Vec<Vec<WriteObject>>) -> PathBuf {
+    let mut writer = TdmsWriter::new(Vec::new());
+    for objects in segments {
+        writer.write_segment(&objects).unwrap();
+    }
+    write_temp_file(name, &writer.into_inner())
+}
+
+fn channel(group: &str, channel: &str, start: f64, properties: &[TdmsProperty]) -> WriteObject {
+    WriteObject::channel(group, channel)
+        .with_properties(properties)
+        .with_data(&[start, start + 1.0])
+        .unwrap()
+}
+
+fn read_f64_channel(tdms_file: &TdmsFile<Cursor<Vec<u8>>>, group: &str, channel: &str) -> Vec<f64> {
+    let group = tdms_file.group(group).unwrap();
+    let channel = group.channel(channel).unwrap();
+    let mut data = vec![0.0; channel.len() as usize];
+    channel.read_all_data(&mut data[..]).unwrap();
+    data
+}
+
+fn start_property(value: i32) -> Vec<TdmsProperty> {
+    vec![TdmsProperty::new("start", TdmsValue::Int32(value))]
+}
+
+fn remove_files(paths: &[PathBuf]) {
+    for path in paths {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[test]
+fn merge_identical_layouts() {
+    let inputs = vec![
+        write_input_file(
+            "identical_1",
+            vec![
+                vec![
+                    WriteObject::root().with_properties(&start_property(1)),
+                    WriteObject::group("Group"),
+                    channel("Group", "Channel1", 0.0, &start_property(1)),
+                    channel("Group", "Channel2", 100.0, &[]),
+                ],
+                vec![channel("Group", "Channel1", 2.0, &[])],
+            ],
+        ),
+        write_input_file(
+            "identical_2",
+            vec![vec![
+                WriteObject::root().with_properties(&start_property(2)),
+                WriteObject::group("Group"),
+                channel("Group", "Channel1", 10.0, &start_property(2)),
+                channel("Group", "Channel2", 110.0, &[]),
+            ]],
+        ),
+    ];
+
+    let mut output = Vec::new();
+    merge(&inputs, &mut output, &MergeOptions::default()).unwrap();
+    remove_files(&inputs);
+
+    let tdms_file = TdmsFile::new(Cursor::new(output)).unwrap();
+    assert_eq!(
+        read_f64_channel(&tdms_file, "Group", "Channel1"),
+        vec![0.0, 1.0, 2.0, 3.0, 10.0, 11.0]
+    );
+    assert_eq!(
+        read_f64_channel(&tdms_file, "Group", "Channel2"),
+        vec![100.0, 101.0, 110.0, 111.0]
+    );
+    assert_eq!(tdms_file.properties(), &start_property(1)[..]);
+    let group = tdms_file.group("Group").unwrap();
+    assert_eq!(
+        group.channel("Channel1").unwrap().properties(),
+        &start_property(1)[..]
+    );
+}
+
+#[test]
+fn merge_with_last_property_precedence() {
+    let inputs = vec![
+        write_input_file(
+            "last_1",
+            vec![vec![
+                WriteObject::root().with_properties(&start_property(1)),
+                channel("Group", "Channel1", 0.0, &start_property(1)),
+            ]],
+        ),
+        write_input_file(
+            "last_2",
+            vec![vec![
+                WriteObject::root().with_properties(&start_property(2)),
+                channel("Group", "Channel1", 10.0, &start_property(2)),
+            ]],
+        ),
+    ];
+    let options = MergeOptions {
+        property_precedence: PropertyPrecedence::Last,
+        ..MergeOptions::default()
+    };
+
+    let mut output = Vec::new();
+    merge(&inputs, &mut output, &options).unwrap();
+    remove_files(&inputs);
+
+    let tdms_file = TdmsFile::new(Cursor::new(output)).unwrap();
+    assert_eq!(tdms_file.properties(), &start_property(2)[..]);
+    let group = tdms_file.group("Group").unwrap();
+    assert_eq!(
+        group.channel("Channel1").unwrap().properties(),
+        &start_property(2)[..]
+    );
+}
+
+#[test]
+fn merge_different_channels() {
+    let inputs = vec![
+        write_input_file(
+            "union_1",
+            vec![vec![channel("Group", "Channel1", 0.0, &[])]],
+        ),
+        write_input_file(
+            "union_2",
+            vec![vec![
+                channel("Group", "Channel1", 10.0, &[]),
+                channel("Group", "Channel2", 20.0, &[]),
+            ]],
+        ),
+    ];
+
+    let mut output = Vec::new();
+    let strict_result = merge(&inputs, &mut output, &MergeOptions::default());
+    assert!(strict_result.is_err());
+
+    let options = MergeOptions {
+        compatibility: MergeCompatibility::Union,
+        ..MergeOptions::default()
+    };
+    let mut output = Vec::new();
+    merge(&inputs, &mut output, &options).unwrap();
+    remove_files(&inputs);
+
+    let tdms_file = TdmsFile::new(Cursor::new(output)).unwrap();
+    assert_eq!(
+        read_f64_channel(&tdms_file, "Group", "Channel1"),
+        vec![0.0, 1.0, 10.0, 11.0]
+    );
+    assert_eq!(
+        read_f64_channel(&tdms_file, "Group", "Channel2"),
+        vec![20.0, 21.0]
+    );
+}
+
+#[test]
+fn merge_mismatched_data_types_fails() {
+    let inputs = vec![
+        write_input_file(
+            "types_1",
+            vec![vec![channel("Group", "Channel1", 0.0, &[])]],
+        ),
+        write_input_file(
+            "types_2",
+            vec![vec![WriteObject::channel("Group", "Channel1")
+                .with_data(&[1i32, 2])
+                .unwrap()]],
+        ),
+    ];
+    let options = MergeOptions {
+        compatibility: MergeCompatibility::Union,
+        ..MergeOptions::default()
+    };
+
+    let mut output = Vec::new();
+    let result = merge(&inputs, &mut output, &options);
+    remove_files(&inputs);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn merge_keeps_start_time_of_each_file() {
+    let waveform_properties = |seconds: i64| {
+        vec![
+            TdmsProperty::new(
+                "wf_start_time",
+                TdmsValue::Timestamp(Timestamp::new(seconds, 0)),
+            ),
+            TdmsProperty::new("wf_increment", TdmsValue::Float64(1.0)),
+        ]
+    };
+    let inputs = vec![
+        write_input_file(
+            "timing_1",
+            vec![
+                vec![channel("Group", "Channel1", 0.0, &waveform_properties(0))],
+                vec![channel("Group", "Channel1", 2.0, &[])],
+            ],
+        ),
+        write_input_file(
+            "timing_2",
+            vec![vec![channel(
+                "Group",
+                "Channel1",
+                10.0,
+                &waveform_properties(3600),
+            )]],
+        ),
+    ];
+
+    let mut output = Vec::new();
+    merge(&inputs, &mut output, &MergeOptions::default()).unwrap();
+    remove_files(&inputs);
+
+    let tdms_file = TdmsFile::new(Cursor::new(output)).unwrap();
+    assert_eq!(
+        read_f64_channel(&tdms_file, "Group", "Channel1"),
+        vec![0.0, 1.0, 2.0, 3.0, 10.0, 11.0]
+    );
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    assert_eq!(
+        channel.segments_timing(),
+        vec![
+            SegmentTiming {
+                start_index: 0,
+                start_time: Some(Timestamp::new(0, 0)),
+                increment: 1.0,
+                samples: 4,
+            },
+            SegmentTiming {
+                start_index: 4,
+                start_time: Some(Timestamp::new(3600, 0)),
+                increment: 1.0,
+                samples: 2,
+            },
+        ]
+    );
+}
@@ -1,97 +1,20 @@
 extern crate hex_literal;
 
-use hex_literal::hex;
-use std::io::Cursor;
-
-use rstdms::TdmsFile;
-
-struct TestFile {
-    bytes: Vec<u8>,
-}
-
-const TOC_METADATA: u32 = 1 << 1;
-const TOC_NEW_OBJ_LIST: u32 = 1 << 2;
-const TOC_RAW_DATA: u32 = 1 << 3;
-const TOC_INTERLEAVED_DATA: u32 = 1 << 5;
-
-impl TestFile {
-    fn new() -> TestFile {
-        TestFile { bytes: Vec::new() }
-    }
-
-    fn add_segment(&mut self, toc_mask: u32, metadata_bytes: &Vec<u8>, data_bytes: &Vec<u8>) {
-        // TDSm tag
-        self.bytes.extend(&hex!("54 44 53 6D"));
-
-        // ToC mask
-        self.bytes.extend(&toc_mask.to_le_bytes());
-
-        // Version number
-        self.bytes.extend(&hex!("69 12 00 00"));
-
-        // Offsets
-        let raw_data_offset = metadata_bytes.len();
-        let next_segment_offset = raw_data_offset + data_bytes.len();
-        self.bytes
-            .extend(&(next_segment_offset as u64).to_le_bytes());
-        self.bytes.extend(&(raw_data_offset as u64).to_le_bytes());
-
-        self.bytes.extend(metadata_bytes);
-        self.bytes.extend(data_bytes);
-    }
-
-    fn to_cursor(self) -> Cursor<Vec<u8>> {
-        Cursor::new(self.bytes)
-    }
-}
+mod common;
 
-fn object_metadata(
-    path: &'static str,
-    raw_data_index: &[u8],
-    properties: Vec<(&'static str, u32, &[u8])>,
-) -> Vec<u8> {
-    let mut metadata_bytes = Vec::new();
-    write_string(path, &mut metadata_bytes);
-    metadata_bytes.extend(raw_data_index);
-    metadata_bytes.extend(&((properties.len() as u32).to_le_bytes()));
-    for (name, type_id, val) in properties {
-        write_string(name, &mut metadata_bytes);
-        metadata_bytes.extend(&(type_id.to_le_bytes()));
-        metadata_bytes.extend(val);
-    }
-    metadata_bytes
-}
-
-fn raw_data_index(data_type: u32, number_of_values: u64) -> Vec<u8> {
-    let mut index_bytes = Vec::new();
-    index_bytes.extend(&(20_u32.to_le_bytes())); // Raw data index length
-    index_bytes.extend(&(data_type.to_le_bytes())); // Data type
-    index_bytes.extend(&(1_u32.to_le_bytes())); // Dimension
-    index_bytes.extend(&(number_of_values.to_le_bytes())); // Number of values
-    index_bytes
-}
-
-fn metadata(objects: Vec<Vec<u8>>) -> Vec<u8> {
-    let mut metadata_bytes = Vec::new();
-    metadata_bytes.extend(&((objects.len() as u32).to_le_bytes()));
-    for object in objects {
-        metadata_bytes.extend(object);
-    }
-    metadata_bytes
-}
-
-fn data_bytes_i32(data: Vec<i32>) -> Vec<u8> {
-    let mut bytes = Vec::new();
-    for val in data {
-        bytes.extend(&(val.to_le_bytes()));
-    }
-    bytes
-}
+use common::*;
+use hex_literal::hex;
+use std::convert::TryInto;
+use std::io::{Cursor, Read, Seek};
 
-fn write_string(string: &str, bytes: &mut Vec<u8>) {
-    bytes.extend(&(string.len() as u32).to_le_bytes());
-    bytes.extend(string.bytes());
-}
+use rstdms::properties::names::{NI_CHANNEL_NAME, WF_INCREMENT, WF_START_TIME};
+use rstdms::raw_writer::{RawIndex, INDEX_SEGMENT_TAG, LEAD_IN_LENGTH};
+use rstdms::{
+    channel_statistics, ChannelOrder, ChannelStatistics, ChunkConfig, CustomDecoder, CustomValues,
+    DetectedFormat, ErrorPolicy, Layout, LocationInfo, PassthroughDecoder, PropertyFilter,
+    ReadOptions, SegmentInfo, StatisticsOptions, TdmsFile, TdmsProperty, TdmsReadError, TdmsValue,
+    TdmsWarningKind, TdmsWriter, TdsType, Timestamp, WaveformTiming, WriteObject,
+};
 
 #[test]
 fn read_metadata() {
@@ -108,7 +31,7 @@ fn read_metadata() {
     let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
     test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
 
-    let tdms_file = TdmsFile::new(test_file.to_cursor());
+    let tdms_file = TdmsFile::new(test_file.into_cursor());
 
     assert!(tdms_file.is_ok(), "Got error: {:?}", tdms_file.unwrap_err());
 
@@ -139,7 +62,7 @@ fn read_metadata_with_repeated_raw_data_index() {
     )]);
     test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
 
-    let tdms_file = TdmsFile::new(test_file.to_cursor());
+    let tdms_file = TdmsFile::new(test_file.into_cursor());
 
     assert!(tdms_file.is_ok(), "Got error: {:?}", tdms_file.unwrap_err());
 
@@ -164,7 +87,7 @@ fn multiple_channels() {
     let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
     test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
 
-    let tdms_file = TdmsFile::new(test_file.to_cursor());
+    let tdms_file = TdmsFile::new(test_file.into_cursor());
 
     assert!(tdms_file.is_ok(), "Got error: {:?}", tdms_file.unwrap_err());
 
@@ -194,7 +117,7 @@ fn interleaved_data() {
     let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA | TOC_INTERLEAVED_DATA;
     test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
 
-    let tdms_file = TdmsFile::new(test_file.to_cursor());
+    let tdms_file = TdmsFile::new(test_file.into_cursor());
 
     assert!(tdms_file.is_ok(), "Got error: {:?}", tdms_file.unwrap_err());
 
@@ -227,7 +150,7 @@ fn iterate_over_objects() {
     let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST;
     test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
 
-    let tdms_file = TdmsFile::new(test_file.to_cursor());
+    let tdms_file = TdmsFile::new(test_file.into_cursor());
 
     assert!(tdms_file.is_ok(), "Got error: {:?}", tdms_file.unwrap_err());
 
@@ -244,3 +167,2377 @@ fn iterate_over_objects() {
         }
     }
 }
+
+#[test]
+fn objects_and_properties_are_in_first_appearance_order() {
+    let mut test_file = TestFile::new();
+    let no_data = hex!("FF FF FF FF");
+    let values: Vec<[u8; 4]> = (0..5_i32).map(i32::to_le_bytes).collect();
+    // Channels are written before their group objects, and names aren't sorted
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Zeta'/'b'", &raw_data_index(3, 1), Vec::new()),
+        object_metadata(
+            "/'Alpha'",
+            &no_data,
+            vec![("z_prop", 3, &values[1]), ("a_prop", 3, &values[2])],
+        ),
+        object_metadata("/'Alpha'/'y'", &raw_data_index(3, 1), Vec::new()),
+        object_metadata("/'Alpha'/'x'", &raw_data_index(3, 1), Vec::new()),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![1, 2, 3]));
+    let metadata_bytes = metadata(vec![
+        object_metadata(
+            "/'Alpha'",
+            &no_data,
+            vec![("m_prop", 3, &values[3]), ("z_prop", 3, &values[4])],
+        ),
+        object_metadata("/'Zeta'", &no_data, Vec::new()),
+        object_metadata("/'Middle'", &no_data, Vec::new()),
+    ]);
+    test_file.add_segment(TOC_METADATA | TOC_RAW_DATA, &metadata_bytes, &[]);
+    let bytes = test_file.into_bytes();
+
+    let tdms_file = TdmsFile::new(Cursor::new(bytes.clone())).unwrap();
+
+    let groups: Vec<(&str, Vec<&str>)> = tdms_file
+        .groups()
+        .map(|group| (group.name(), group.channels().map(|c| c.name()).collect()))
+        .collect();
+    assert_eq!(
+        groups,
+        vec![
+            ("Zeta", vec!["b"]),
+            ("Alpha", vec!["y", "x"]),
+            ("Middle", vec![])
+        ]
+    );
+    let group = tdms_file.group("Alpha").unwrap();
+    let properties: Vec<(&str, &TdmsValue)> = group
+        .properties()
+        .iter()
+        .map(|property| (&property.name[..], &property.value))
+        .collect();
+    assert_eq!(
+        properties,
+        vec![
+            ("z_prop", &TdmsValue::Int32(4)),
+            ("a_prop", &TdmsValue::Int32(2)),
+            ("m_prop", &TdmsValue::Int32(3)),
+        ]
+    );
+
+    // Reading the file again, or with different options, describes it identically
+    let description = describe_file(&tdms_file);
+    let reread = TdmsFile::new(Cursor::new(bytes.clone())).unwrap();
+    assert_eq!(describe_file(&reread), description);
+    let options = ReadOptions::new().parallel_metadata(true);
+    let parallel = TdmsFile::new_with_options(Cursor::new(bytes), &options).unwrap();
+    assert_eq!(describe_file(&parallel), description);
+}
+
+#[test]
+fn missing_objects_suggest_similar_names() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Voltage '", &raw_data_index(3, 1), Vec::new()),
+        object_metadata("/'Group'/'Current'", &raw_data_index(3, 1), Vec::new()),
+    ]);
+    let data_bytes = data_bytes_i32(vec![1, 2]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+
+    let group = tdms_file.try_group("Group").unwrap();
+    assert_eq!(group.try_channel("Current").unwrap().name(), "Current");
+
+    match group.try_channel("Voltage") {
+        Err(TdmsReadError::NoSuchChannel {
+            group,
+            name,
+            similar_names,
+        }) => {
+            assert_eq!(group, "Group");
+            assert_eq!(name, "Voltage");
+            assert_eq!(similar_names, vec!["Voltage "]);
+        }
+        Err(error) => panic!("Unexpected error: {}", error),
+        Ok(_) => panic!("Expected an error"),
+    }
+    let error = group.try_channel("Voltage").err().unwrap();
+    assert_eq!(
+        error.to_string(),
+        "No channel named \"Voltage\" in group \"Group\", did you mean \"Voltage \"?"
+    );
+
+    match tdms_file.try_group("group") {
+        Err(TdmsReadError::NoSuchGroup {
+            name,
+            similar_names,
+        }) => {
+            assert_eq!(name, "group");
+            assert_eq!(similar_names, vec!["Group"]);
+        }
+        Err(error) => panic!("Unexpected error: {}", error),
+        Ok(_) => panic!("Expected an error"),
+    }
+    let error = tdms_file.try_group("Unrelated").err().unwrap();
+    assert_eq!(error.to_string(), "No group named \"Unrelated\"");
+}
+
+#[test]
+fn groups_without_group_objects_or_channels() {
+    let mut test_file = TestFile::new();
+    let no_data = hex!("FF FF FF FF");
+    let metadata_bytes = metadata(vec![
+        // Channels written without ever writing their group object
+        object_metadata("/'Implicit'/'Channel1'", &raw_data_index(3, 1), Vec::new()),
+        object_metadata("/'Implicit'/'Channel2'", &raw_data_index(3, 1), Vec::new()),
+        // A group with properties but no channels
+        object_metadata(
+            "/'Empty'",
+            &no_data,
+            vec![("group_property", 3, &1_i32.to_le_bytes())],
+        ),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![1, 2]));
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+
+    let group_names: Vec<String> = tdms_file
+        .groups()
+        .map(|group| group.name().to_string())
+        .collect();
+    assert_eq!(group_names, vec!["Implicit", "Empty"]);
+
+    let implicit = tdms_file.group("Implicit").unwrap();
+    assert!(implicit.properties().is_empty());
+    let channel_names: Vec<String> = implicit
+        .channels()
+        .map(|channel| channel.name().to_string())
+        .collect();
+    assert_eq!(channel_names, vec!["Channel1", "Channel2"]);
+    let mut data = vec![0; 1];
+    implicit
+        .channel("Channel2")
+        .unwrap()
+        .read_all_data(&mut data)
+        .unwrap();
+    assert_eq!(data, vec![2]);
+
+    let empty = tdms_file.group("Empty").unwrap();
+    assert_eq!(empty.properties().len(), 1);
+    assert_eq!(empty.channels().count(), 0);
+}
+
+#[test]
+fn read_data_from_offset() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 3), Vec::new()),
+        object_metadata("/'Group'/'Channel2'", &raw_data_index(3, 3), Vec::new()),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(
+        toc_mask,
+        &metadata_bytes,
+        &data_bytes_i32(vec![1, 2, 3, 11, 12, 13]),
+    );
+    test_file.add_segment(
+        TOC_RAW_DATA,
+        &Vec::new(),
+        &data_bytes_i32(vec![4, 5, 6, 14, 15, 16]),
+    );
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel2").unwrap();
+
+    let mut data = vec![0i32; 3];
+    let values_read = channel.read_data(2, &mut data[..]).unwrap();
+    assert_eq!(values_read, 3);
+    assert_eq!(data, vec![13, 14, 15]);
+
+    let values_read = channel.read_data(4, &mut data[..]).unwrap();
+    assert_eq!(values_read, 2);
+    assert_eq!(data[..2], [15, 16]);
+}
+
+#[test]
+fn read_interleaved_data_from_offset() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 4), Vec::new()),
+        object_metadata("/'Group'/'Channel2'", &raw_data_index(3, 4), Vec::new()),
+    ]);
+    let data_bytes = data_bytes_i32(vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA | TOC_INTERLEAVED_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel2").unwrap();
+
+    let mut data = vec![0i32; 2];
+    let values_read = channel.read_data(1, &mut data[..]).unwrap();
+    assert_eq!(values_read, 2);
+    assert_eq!(data, vec![4, 6]);
+}
+
+#[test]
+fn read_segment_with_multiple_chunks() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 2), Vec::new()),
+        object_metadata("/'Group'/'Channel2'", &raw_data_index(3, 1), Vec::new()),
+    ]);
+    let data_bytes = data_bytes_i32(vec![1, 2, 11, 3, 4, 12, 5, 6, 13]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+
+    let channel = group.channel("Channel1").unwrap();
+    let mut data = vec![0i32; channel.len() as usize];
+    channel.read_all_data(&mut data[..]).unwrap();
+    assert_eq!(data, vec![1, 2, 3, 4, 5, 6]);
+
+    let channel = group.channel("Channel2").unwrap();
+    let mut data = vec![0i32; channel.len() as usize];
+    channel.read_all_data(&mut data[..]).unwrap();
+    assert_eq!(data, vec![11, 12, 13]);
+
+    let mut data = vec![0i32; 2];
+    assert_eq!(channel.read_data(1, &mut data[..]).unwrap(), 2);
+    assert_eq!(data, vec![12, 13]);
+}
+
+#[test]
+fn read_string_data_with_multiple_chunks() {
+    let mut test_file = TestFile::new();
+    let first_chunk = data_bytes_strings(vec!["ab", "c"]);
+    let metadata_bytes = metadata(vec![
+        object_metadata(
+            "/'Group'/'Strings'",
+            &string_raw_data_index(2, first_chunk.len() as u64),
+            Vec::new(),
+        ),
+        object_metadata("/'Group'/'Values'", &raw_data_index(3, 1), Vec::new()),
+    ]);
+    let mut data_bytes = first_chunk;
+    data_bytes.extend(data_bytes_i32(vec![1]));
+    // The second chunk must have the same size as the first
+    data_bytes.extend(data_bytes_strings(vec!["d", "ef"]));
+    data_bytes.extend(data_bytes_i32(vec![2]));
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+
+    let channel = group.channel("Strings").unwrap();
+    assert_eq!(channel.len(), 4);
+    let mut data = vec![String::new(); 4];
+    assert_eq!(channel.read_strings(0, &mut data[..]).unwrap(), 4);
+    assert_eq!(data, vec!["ab", "c", "d", "ef"]);
+    let mut data = vec![String::new(); 2];
+    assert_eq!(channel.read_strings(1, &mut data[..]).unwrap(), 2);
+    assert_eq!(data, vec!["c", "d"]);
+
+    let channel = group.channel("Values").unwrap();
+    let mut data = vec![0i32; 2];
+    channel.read_all_data(&mut data[..]).unwrap();
+    assert_eq!(data, vec![1, 2]);
+    assert!(channel
+        .read_strings(0, &mut vec![String::new(); 2])
+        .is_err());
+}
+
+#[test]
+fn read_values_after_string_channel() {
+    let mut test_file = TestFile::new();
+    // Strings of different lengths so the string data size isn't a multiple of the count
+    let string_bytes = data_bytes_strings(vec!["a", "bcd", "efghij"]);
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Floats'", &raw_data_index(10, 2), Vec::new()),
+        object_metadata(
+            "/'Group'/'Strings'",
+            &string_raw_data_index(3, string_bytes.len() as u64),
+            Vec::new(),
+        ),
+        object_metadata("/'Group'/'Integers'", &raw_data_index(3, 3), Vec::new()),
+    ]);
+    let mut data_bytes = data_bytes_f64(vec![0.5, 1.5]);
+    data_bytes.extend(&string_bytes);
+    data_bytes.extend(data_bytes_i32(vec![7, -8, 9]));
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
+    test_file.add_segment(TOC_RAW_DATA, &[], &data_bytes);
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+
+    let channel = group.channel("Integers").unwrap();
+    assert_eq!(channel.len(), 6);
+    let mut data = vec![0i32; 6];
+    channel.read_all_data(&mut data[..]).unwrap();
+    assert_eq!(data, vec![7, -8, 9, 7, -8, 9]);
+    let channel = group.channel("Strings").unwrap();
+    let mut data = vec![String::new(); 6];
+    assert_eq!(channel.read_strings(0, &mut data[..]).unwrap(), 6);
+    assert_eq!(data[3..], ["a", "bcd", "efghij"]);
+    let channel = group.channel("Floats").unwrap();
+    let mut data = vec![0.0; 4];
+    channel.read_all_data(&mut data[..]).unwrap();
+    assert_eq!(data, vec![0.5, 1.5, 0.5, 1.5]);
+}
+
+#[test]
+fn interleaved_string_data_is_an_error() {
+    let mut test_file = TestFile::new();
+    let string_bytes = data_bytes_strings(vec!["a", "b"]);
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Integers'", &raw_data_index(3, 2), Vec::new()),
+        object_metadata(
+            "/'Group'/'Strings'",
+            &string_raw_data_index(2, string_bytes.len() as u64),
+            Vec::new(),
+        ),
+    ]);
+    let mut data_bytes = data_bytes_i32(vec![1, 2]);
+    data_bytes.extend(&string_bytes);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA | TOC_INTERLEAVED_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
+
+    match TdmsFile::new(test_file.into_cursor()) {
+        Err(TdmsReadError::InvalidData(message)) => {
+            assert!(
+                message.contains("strings can't be interleaved"),
+                "{}",
+                message
+            )
+        }
+        Err(error) => panic!("Unexpected error: {}", error),
+        Ok(_) => panic!("Expected interleaved strings to be an error"),
+    }
+}
+
+#[test]
+fn describe_segments() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'", &hex!("FF FF FF FF"), Vec::new()),
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 2), Vec::new()),
+    ]);
+    let data_bytes = data_bytes_i32(vec![1, 2, 3, 4, 5, 6]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
+    let data_bytes = data_bytes_i32(vec![7, 8]);
+    test_file.add_segment(TOC_RAW_DATA, &Vec::new(), &data_bytes);
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+    let segments = tdms_file.segments().unwrap();
+    assert_eq!(segments.len(), 2);
+
+    let segment = &segments[0];
+    assert_eq!(segment.position, 0);
+    assert_eq!(segment.next_segment_position, segments[1].position);
+    assert_eq!(
+        segment.toc_flag_names(),
+        vec!["MetaData", "NewObjList", "RawData"]
+    );
+    assert_eq!(segment.data_length, 24);
+    assert_eq!(segment.chunk_count, 3);
+    assert!(!segment.incomplete);
+    assert_eq!(segment.objects.len(), 2);
+    assert_eq!(segment.objects[0].path, "/'Group'");
+    assert_eq!(segment.objects[0].data_type, None);
+    assert_eq!(segment.objects[0].first_value, None);
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    assert_eq!(segment.objects[1].path, channel.path());
+    assert_eq!(segment.objects[1].data_type, Some(TdsType::I32));
+    assert_eq!(segment.objects[1].number_of_values, 6);
+    assert_eq!(segment.objects[1].first_value, Some(0));
+
+    let segment = &segments[1];
+    assert_eq!(segment.toc_flag_names(), vec!["RawData"]);
+    assert_eq!(segment.chunk_count, 1);
+    assert_eq!(segment.objects.len(), 2);
+    assert_eq!(segment.objects[1].number_of_values, 2);
+    assert_eq!(segment.objects[1].first_value, Some(6));
+}
+
+#[test]
+fn object_with_zero_values_alongside_data() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Empty'", &raw_data_index(3, 0), Vec::new()),
+        object_metadata("/'Group'/'Values'", &raw_data_index(3, 2), Vec::new()),
+    ]);
+    let data_bytes = data_bytes_i32(vec![1, 2, 3, 4]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
+    test_file.add_segment(
+        toc_mask | TOC_INTERLEAVED_DATA,
+        &metadata_bytes,
+        &data_bytes,
+    );
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+
+    let channel = group.channel("Empty").unwrap();
+    assert_eq!(channel.len(), 0);
+    assert_eq!(channel.locate_value(0).unwrap(), None);
+    let channel = group.channel("Values").unwrap();
+    let mut data = vec![0i32; channel.len() as usize];
+    channel.read_all_data(&mut data[..]).unwrap();
+    assert_eq!(data, vec![1, 2, 3, 4, 1, 2, 3, 4]);
+    let segments = tdms_file.segments().unwrap();
+    assert_eq!(segments[0].chunk_count, 2);
+    assert_eq!(segments[0].objects[0].number_of_values, 0);
+    assert_eq!(segments[0].objects[0].first_value, None);
+}
+
+#[test]
+fn segment_without_data_followed_by_data() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 0), Vec::new()),
+        object_metadata("/'Group'/'Channel2'", &raw_data_index(3, 0), Vec::new()),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &Vec::new());
+    test_file.add_segment(
+        toc_mask | TOC_INTERLEAVED_DATA,
+        &metadata_bytes,
+        &Vec::new(),
+    );
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 2), Vec::new()),
+        object_metadata("/'Group'/'Channel2'", &raw_data_index(3, 1), Vec::new()),
+    ]);
+    let data_bytes = data_bytes_i32(vec![1, 2, 3]);
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+    let segments = tdms_file.segments().unwrap();
+    assert_eq!(segments.len(), 3);
+    assert_eq!(segments[0].chunk_count, 0);
+    assert_eq!(segments[1].chunk_count, 0);
+    assert_eq!(segments[2].objects[0].first_value, Some(0));
+
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    let mut data = vec![0i32; channel.len() as usize];
+    channel.read_all_data(&mut data[..]).unwrap();
+    assert_eq!(data, vec![1, 2]);
+    assert_eq!(
+        channel.locate_value(0).unwrap().map(|(segment, _)| segment),
+        Some(2)
+    );
+    let channel = group.channel("Channel2").unwrap();
+    let mut data = vec![0i32; channel.len() as usize];
+    channel.read_all_data(&mut data[..]).unwrap();
+    assert_eq!(data, vec![3]);
+}
+
+#[test]
+fn locate_byte_offsets_and_values() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 2), Vec::new()),
+        object_metadata("/'Group'/'Channel2'", &raw_data_index(3, 1), Vec::new()),
+    ]);
+    let data_bytes = data_bytes_i32(vec![1, 2, 10, 3, 4, 11]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
+    let data_bytes = data_bytes_i32(vec![5, 6, 12]);
+    test_file.add_segment(TOC_RAW_DATA, &Vec::new(), &data_bytes);
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+    let segments = tdms_file.segments().unwrap();
+    let data_position = |segment: &SegmentInfo| segment.next_segment_position - segment.data_length;
+    let first_data = data_position(&segments[0]);
+    let second_data = data_position(&segments[1]);
+    let channel_location = |segment_index, channel: &str, value_index| {
+        Some(LocationInfo {
+            segment_index,
+            object_path_at_offset: Some(format!("/'Group'/'{}'", channel)),
+            value_index_within_channel: Some(value_index),
+        })
+    };
+
+    assert_eq!(
+        tdms_file.locate(segments[0].position + 1).unwrap(),
+        Some(LocationInfo {
+            segment_index: 0,
+            object_path_at_offset: None,
+            value_index_within_channel: None,
+        })
+    );
+    assert_eq!(
+        tdms_file.locate(first_data + 4).unwrap(),
+        channel_location(0, "Channel1", 1)
+    );
+    assert_eq!(
+        tdms_file.locate(first_data + 9).unwrap(),
+        channel_location(0, "Channel2", 0)
+    );
+    assert_eq!(
+        tdms_file.locate(first_data + 12).unwrap(),
+        channel_location(0, "Channel1", 2)
+    );
+    assert_eq!(
+        tdms_file.locate(first_data + 23).unwrap(),
+        channel_location(0, "Channel2", 1)
+    );
+    assert_eq!(
+        tdms_file.locate(second_data).unwrap(),
+        channel_location(1, "Channel1", 4)
+    );
+    assert_eq!(
+        tdms_file.locate(second_data + 8).unwrap(),
+        channel_location(1, "Channel2", 2)
+    );
+    assert_eq!(
+        tdms_file.locate(segments[1].next_segment_position).unwrap(),
+        None
+    );
+
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    assert_eq!(channel.locate_value(0).unwrap(), Some((0, first_data)));
+    assert_eq!(channel.locate_value(3).unwrap(), Some((0, first_data + 16)));
+    assert_eq!(channel.locate_value(5).unwrap(), Some((1, second_data + 4)));
+    assert_eq!(channel.locate_value(6).unwrap(), None);
+    let channel = group.channel("Channel2").unwrap();
+    assert_eq!(channel.locate_value(2).unwrap(), Some((1, second_data + 8)));
+}
+
+#[test]
+fn locate_interleaved_and_string_values() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 2), Vec::new()),
+        object_metadata("/'Group'/'Channel2'", &raw_data_index(3, 2), Vec::new()),
+    ]);
+    let data_bytes = data_bytes_i32(vec![1, 2, 3, 4]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA | TOC_INTERLEAVED_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
+    let string_bytes = data_bytes_strings(vec!["ab", "c"]);
+    let metadata_bytes = metadata(vec![object_metadata(
+        "/'Group'/'Strings'",
+        &string_raw_data_index(2, string_bytes.len() as u64),
+        Vec::new(),
+    )]);
+    test_file.add_segment(
+        TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA,
+        &metadata_bytes,
+        &string_bytes,
+    );
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+    let segments = tdms_file.segments().unwrap();
+    let first_data = segments[0].next_segment_position - segments[0].data_length;
+    let second_data = segments[1].next_segment_position - segments[1].data_length;
+    let group = tdms_file.group("Group").unwrap();
+
+    let channel = group.channel("Channel2").unwrap();
+    assert_eq!(channel.locate_value(1).unwrap(), Some((0, first_data + 12)));
+    let location = tdms_file.locate(first_data + 13).unwrap().unwrap();
+    assert_eq!(location.object_path_at_offset.unwrap(), channel.path());
+    assert_eq!(location.value_index_within_channel, Some(1));
+
+    let channel = group.channel("Strings").unwrap();
+    assert_eq!(channel.locate_value(1).unwrap(), Some((1, second_data + 4)));
+    let location = tdms_file.locate(second_data + 5).unwrap().unwrap();
+    assert_eq!(location.value_index_within_channel, Some(1));
+    let location = tdms_file.locate(second_data + 8).unwrap().unwrap();
+    assert_eq!(location.object_path_at_offset.unwrap(), channel.path());
+    assert_eq!(location.value_index_within_channel, None);
+}
+
+#[test]
+fn data_extents_match_read_data() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 2), Vec::new()),
+        object_metadata("/'Group'/'Channel2'", &raw_data_index(3, 2), Vec::new()),
+    ]);
+    // Two chunks of contiguous data
+    let data_bytes = data_bytes_i32(vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    test_file.add_segment(
+        TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA,
+        &metadata_bytes,
+        &data_bytes,
+    );
+    let data_bytes = data_bytes_i32(vec![9, 10, 11, 12, 13, 14, 15, 16]);
+    test_file.add_segment(TOC_RAW_DATA | TOC_INTERLEAVED_DATA, &[], &data_bytes);
+    let metadata_bytes = metadata(vec![object_metadata(
+        "/'Group'/'Channel1'",
+        &raw_data_index(3, 4),
+        Vec::new(),
+    )]);
+    let data_bytes = data_bytes_i32(vec![17, 18, 19, 20, 21, 22, 23, 24]);
+    test_file.add_segment(
+        TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA,
+        &metadata_bytes,
+        &data_bytes,
+    );
+    let bytes = test_file.into_bytes();
+
+    let tdms_file = TdmsFile::new(Cursor::new(bytes.clone())).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+
+    let extents = channel.data_extents().unwrap();
+    let layouts: Vec<(Layout, u64)> = extents
+        .iter()
+        .map(|extent| (extent.layout, extent.values))
+        .collect();
+    assert_eq!(
+        layouts,
+        vec![
+            (Layout::Contiguous, 2),
+            (Layout::Contiguous, 2),
+            (Layout::Interleaved { stride: 8 }, 4),
+            // The final segment's two chunks are adjacent so are merged
+            (Layout::Contiguous, 8),
+        ]
+    );
+    for pair in extents.windows(2) {
+        assert!(pair[0].file_offset + pair[0].byte_len <= pair[1].file_offset);
+    }
+
+    let mut extent_values = Vec::new();
+    for extent in &extents {
+        assert_eq!(extent.data_type, TdsType::I32);
+        assert!(!extent.big_endian);
+        let start = extent.file_offset as usize;
+        let extent_bytes = &bytes[start..start + extent.byte_len as usize];
+        let stride = match extent.layout {
+            Layout::Contiguous => 4,
+            Layout::Interleaved { stride } => stride as usize,
+        };
+        for value_index in 0..extent.values as usize {
+            let value_bytes = &extent_bytes[value_index * stride..value_index * stride + 4];
+            extent_values.push(i32::from_le_bytes(value_bytes.try_into().unwrap()));
+        }
+    }
+    let mut data = vec![0i32; channel.len() as usize];
+    channel.read_all_data(&mut data).unwrap();
+    assert_eq!(extent_values, data);
+    assert_eq!(
+        data,
+        vec![1, 2, 5, 6, 9, 11, 13, 15, 17, 18, 19, 20, 21, 22, 23, 24]
+    );
+}
+
+/// Reader that counts the number of reads made from the underlying data
+struct CountingReader {
+    inner: Cursor<Vec<u8>>,
+    reads: std::rc::Rc<std::cell::Cell<usize>>,
+    bytes_read: std::rc::Rc<std::cell::Cell<usize>>,
+}
+
+impl CountingReader {
+    fn new(bytes: Vec<u8>) -> CountingReader {
+        CountingReader {
+            inner: Cursor::new(bytes),
+            reads: Default::default(),
+            bytes_read: Default::default(),
+        }
+    }
+}
+
+impl Read for CountingReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let length = self.inner.read(buf)?;
+        self.reads.set(self.reads.get() + 1);
+        self.bytes_read.set(self.bytes_read.get() + length);
+        Ok(length)
+    }
+}
+
+impl Seek for CountingReader {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+fn reading_into_buffer_of_other_type_is_an_error() {
+    let mut writer = TdmsWriter::new(Vec::new());
+    writer
+        .write_segment(&[
+            WriteObject::channel("Group", "i32")
+                .with_data(&[1i32, 2, 3, 4])
+                .unwrap(),
+            WriteObject::channel("Group", "f32")
+                .with_data(&[1.0f32, 2.0, 3.0, 4.0])
+                .unwrap(),
+            WriteObject::channel("Group", "u64")
+                .with_data(&[1u64, 2, 3, 4])
+                .unwrap(),
+        ])
+        .unwrap();
+    let tdms_file = TdmsFile::new(Cursor::new(writer.into_inner())).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+
+    let channels = [
+        ("i32", TdsType::I32),
+        ("f32", TdsType::SingleFloat),
+        ("u64", TdsType::U64),
+    ];
+    for (name, data_type) in channels.iter() {
+        let channel = group.channel(name).unwrap();
+        let mut buffer = vec![0.0f64; 4];
+        let check = |result: rstdms::Result<usize>| match result {
+            Err(TdmsReadError::TypeMismatch {
+                data_type: actual,
+                requested,
+            }) => {
+                assert_eq!(actual, *data_type);
+                assert_eq!(requested, "F64 values");
+            }
+            other => panic!("Expected a type mismatch reading {}, got {:?}", name, other),
+        };
+
+        check(channel.read_all_data(&mut buffer).map(|_| 4));
+        check(channel.read_data(2, &mut buffer));
+        check(
+            channel
+                .read()
+                .chunks::<f64>()
+                .next()
+                .unwrap()
+                .map(|c| c.len()),
+        );
+        assert_eq!(buffer, vec![0.0; 4]);
+        let error = channel.read_all_data(&mut buffer).unwrap_err();
+        assert!(error.to_string().contains("read_data_as_f64"), "{}", error);
+
+        // Types of the same size aren't reinterpreted either
+        let mut same_size = vec![0u32; 4];
+        assert!(matches!(
+            channel.read_all_data(&mut same_size),
+            Err(TdmsReadError::TypeMismatch { .. })
+        ));
+
+        assert_eq!(channel.read_data_as_f64(0, &mut buffer).unwrap(), 4);
+        assert_eq!(buffer, vec![1.0, 2.0, 3.0, 4.0]);
+    }
+}
+
+#[test]
+fn coalesce_reads_of_consecutive_segments() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![object_metadata(
+        "/'Group'/'Channel1'",
+        &raw_data_index(3, 4),
+        Vec::new(),
+    )]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![0, 1, 2, 3]));
+    for i in 1..1000 {
+        let data: Vec<i32> = (i * 4..i * 4 + 4).collect();
+        test_file.add_segment(TOC_RAW_DATA, &[], &data_bytes_i32(data));
+    }
+    let bytes = test_file.into_bytes();
+    let expected: Vec<i32> = (0..4000).collect();
+
+    let mut read_counts = Vec::new();
+    for coalesce in [true, false].iter() {
+        let reader = CountingReader::new(bytes.clone());
+        let reads = reader.reads.clone();
+        let options = ReadOptions::new().coalesce_reads(*coalesce);
+        let tdms_file = TdmsFile::new_with_options(reader, &options).unwrap();
+        let group = tdms_file.group("Group").unwrap();
+        let channel = group.channel("Channel1").unwrap();
+        reads.set(0);
+
+        let mut values = vec![0; channel.len() as usize];
+        channel.read_all_data(&mut values).unwrap();
+        assert_eq!(values, expected);
+        let mut values = vec![0; 10];
+        channel.read_data(1998, &mut values).unwrap();
+        assert_eq!(values, (1998..2008).collect::<Vec<i32>>());
+        read_counts.push(reads.get());
+    }
+    assert!(read_counts[0] <= 2, "{:?}", read_counts);
+    assert!(read_counts[1] >= 1000, "{:?}", read_counts);
+}
+
+#[test]
+fn read_strided_values() {
+    let values: Vec<f64> = (0..100_000).map(|i| i as f64).collect();
+    let mut writer = TdmsWriter::new(Vec::new());
+    for chunk in values[..80_000].chunks(40_000) {
+        writer
+            .write_segment(&[WriteObject::channel("Group", "Channel")
+                .with_data(chunk)
+                .unwrap()])
+            .unwrap();
+    }
+    // The remaining values are interleaved with another channel
+    let mut test_file = TestFile::new();
+    test_file.add_bytes(&writer.into_inner());
+    let metadata_bytes = metadata(vec![
+        object_metadata(
+            "/'Group'/'Channel'",
+            &raw_data_index(10, 20_000),
+            Vec::new(),
+        ),
+        object_metadata("/'Group'/'Other'", &raw_data_index(10, 20_000), Vec::new()),
+    ]);
+    let data: Vec<f64> = values[80_000..].iter().flat_map(|&v| vec![v, -v]).collect();
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA | TOC_INTERLEAVED_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_f64(data));
+    let reader = CountingReader::new(test_file.into_bytes());
+    let bytes_read = reader.bytes_read.clone();
+    let tdms_file = TdmsFile::new(reader).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+
+    for stride in [1, 3, 1000, 99_999, 100_000, 200_000].iter() {
+        let strided = channel.read_strided::<f64>(*stride).unwrap();
+        let expected: Vec<f64> = values.iter().copied().step_by(*stride).collect();
+        assert_eq!(strided.len(), values.len().div_ceil(*stride));
+        assert_eq!(strided, expected, "stride {}", stride);
+    }
+
+    // Only values are read from the contiguous segments, and all of the interleaved data
+    bytes_read.set(0);
+    channel.read_strided::<f64>(1000).unwrap();
+    let contiguous_bytes = 80_000 * 8;
+    let interleaved_bytes = 2 * 20_000 * 8;
+    assert!(
+        bytes_read.get() < interleaved_bytes + contiguous_bytes / 100,
+        "Read {} bytes",
+        bytes_read.get()
+    );
+    assert!(matches!(
+        channel.read_strided::<f64>(0),
+        Err(TdmsReadError::InvalidArgument(_))
+    ));
+}
+
+/// Describe all of the metadata and data in a file, for comparing files read with
+/// different options
+fn describe_file<R: Read + Seek>(tdms_file: &TdmsFile<R>) -> String {
+    let mut description = format!(
+        "{:?}\n{:?}\n{:?}\n",
+        tdms_file.segments().unwrap(),
+        tdms_file.skipped_segments(),
+        tdms_file.properties()
+    );
+    for group in tdms_file.groups() {
+        description.push_str(&format!("{} {:?}\n", group.name(), group.properties()));
+        for channel in group.channels() {
+            let mut data = vec![0.0; channel.len() as usize];
+            channel.read_data_as_f64(0, &mut data).unwrap();
+            description.push_str(&format!(
+                "{} {:?} {:?}\n",
+                channel.path(),
+                channel.properties(),
+                data
+            ));
+        }
+    }
+    description
+}
+
+fn many_segments_file() -> TestFile {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata(
+            "/",
+            &hex!("FF FF FF FF"),
+            vec![("file_property", 3, &1_i32.to_le_bytes())],
+        ),
+        object_metadata("/'Group'", &hex!("FF FF FF FF"), Vec::new()),
+        object_metadata(
+            "/'Group'/'Channel1'",
+            &raw_data_index(3, 2),
+            vec![("index", 3, &0_i32.to_le_bytes())],
+        ),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![1, 2]));
+    for i in 1..20_i32 {
+        if i % 5 == 0 {
+            // Add a channel and update a property, merging with the previous object list
+            let metadata_bytes = metadata(vec![
+                object_metadata(
+                    "/'Group'/'Channel1'",
+                    &hex!("00 00 00 00"),
+                    vec![("index", 3, &i.to_le_bytes())],
+                ),
+                object_metadata("/'Group'/'Channel2'", &raw_data_index(3, 1), Vec::new()),
+            ]);
+            let toc_mask = TOC_METADATA | TOC_RAW_DATA;
+            let data_bytes = data_bytes_i32(vec![i, i + 1, 100 * i]);
+            test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
+        } else {
+            let data_bytes = data_bytes_i32(vec![i, i + 1]);
+            test_file.add_segment(TOC_RAW_DATA, &Vec::new(), &data_bytes);
+        }
+    }
+    test_file
+}
+
+#[test]
+fn read_data_across_segments_with_different_lengths() {
+    let mut test_file = TestFile::new();
+    let segment_lengths = [3, 3, 2, 2, 2, 4, 1];
+    let mut all_data = Vec::new();
+    for (segment, length) in segment_lengths.iter().enumerate() {
+        let metadata_bytes = metadata(vec![object_metadata(
+            "/'Group'/'Channel1'",
+            &raw_data_index(3, *length),
+            Vec::new(),
+        )]);
+        let data: Vec<i32> = (0..*length as i32)
+            .map(|i| 10 * segment as i32 + i)
+            .collect();
+        all_data.extend(&data);
+        let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+        test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(data));
+    }
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    assert_eq!(channel.len(), all_data.len() as u64);
+    for offset in 0..all_data.len() {
+        let mut data = vec![0; 5];
+        let values_read = channel.read_data(offset as u64, &mut data).unwrap();
+        let expected = &all_data[offset..(offset + 5).min(all_data.len())];
+        assert_eq!(&data[..values_read], expected);
+    }
+
+    let first_values: Vec<Option<u64>> = tdms_file
+        .segments()
+        .unwrap()
+        .iter()
+        .map(|segment| segment.objects[0].first_value)
+        .collect();
+    let expected: Vec<Option<u64>> = [0, 3, 6, 8, 10, 12, 16].iter().copied().map(Some).collect();
+    assert_eq!(first_values, expected);
+}
+
+#[test]
+fn parallel_metadata_matches_sequential() {
+    let bytes = many_segments_file().into_bytes();
+    let sequential = TdmsFile::new(Cursor::new(bytes.clone())).unwrap();
+    let options = ReadOptions::new().parallel_metadata(true);
+    let parallel = TdmsFile::new_with_options(Cursor::new(bytes), &options).unwrap();
+    assert_eq!(describe_file(&parallel), describe_file(&sequential));
+}
+
+#[test]
+fn parallel_metadata_with_invalid_segment() {
+    let mut test_file = many_segments_file();
+    test_file.add_bytes(&hex!("00 01 02 03 04 05 06 07"));
+    let data_bytes = data_bytes_i32(vec![20, 21]);
+    test_file.add_segment(TOC_RAW_DATA, &Vec::new(), &data_bytes);
+    let bytes = test_file.into_bytes();
+
+    let options = ReadOptions::new().error_policy(ErrorPolicy::Skip);
+    let sequential = TdmsFile::new_with_options(Cursor::new(bytes.clone()), &options).unwrap();
+    let options = options.parallel_metadata(true);
+    let parallel = TdmsFile::new_with_options(Cursor::new(bytes), &options).unwrap();
+    assert_eq!(parallel.skipped_segments().len(), 1);
+    assert_eq!(describe_file(&parallel), describe_file(&sequential));
+}
+
+#[test]
+fn open_file_from_path() {
+    let mut path = std::env::temp_dir();
+    path.push(format!("rstdms_read_{}_open.tdms", std::process::id()));
+    std::fs::write(&path, many_segments_file().into_bytes()).unwrap();
+
+    let tdms_file = TdmsFile::open(&path);
+    std::fs::remove_file(&path).unwrap();
+    let tdms_file = tdms_file.unwrap();
+    assert_eq!(tdms_file.path(), Some(path.as_path()));
+    let group = tdms_file.group("Group").unwrap();
+    assert_eq!(group.channels().count(), 2);
+
+    let error = TdmsFile::open(&path).err().unwrap();
+    assert!(matches!(error, TdmsReadError::PathError(_, _)));
+    assert!(error.to_string().contains(&path.display().to_string()));
+}
+
+#[test]
+fn detect_files_in_other_formats() {
+    let files: Vec<(&[u8], DetectedFormat)> = vec![
+        (b"", DetectedFormat::Empty),
+        (&hex!("54 44 53 68 0E 00 00 00"), DetectedFormat::TdmsIndex),
+        (
+            b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<usi:tdm xmlns:usi=\"http://www.ni.com/Schemas/USI/1_0\">",
+            DetectedFormat::Tdm,
+        ),
+        (b"\xEF\xBB\xBF<?xml version=\"1.0\"?><data/>", DetectedFormat::Xml),
+        (&hex!("50 4B 03 04 14 00 00 00"), DetectedFormat::Zip),
+        (b"time,value\n0,1\n", DetectedFormat::Unknown),
+    ];
+    for (bytes, expected_format) in files {
+        let error = TdmsFile::new(Cursor::new(bytes.to_vec())).err().unwrap();
+        match error {
+            TdmsReadError::NotATdmsFile(format) => assert_eq!(format, expected_format),
+            _ => panic!("Unexpected error: {}", error),
+        }
+    }
+}
+
+#[test]
+fn index_file_is_not_read_when_skipping_errors() {
+    let mut bytes = many_segments_file().into_bytes();
+    bytes[3] = b'h';
+    let options = ReadOptions::new().error_policy(ErrorPolicy::Skip);
+    let error = TdmsFile::new_with_options(Cursor::new(bytes), &options)
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.to_string(),
+        "Not a TDMS file: this looks like a .tdms_index file, open the .tdms file instead"
+    );
+}
+
+#[test]
+fn opening_index_file_suggests_data_file() {
+    let mut writer = TdmsWriter::new(Vec::new());
+    for values in [[1, 2], [3, 4]].iter() {
+        writer
+            .write_segment(&[WriteObject::channel("Group", "Channel")
+                .with_data(&values[..])
+                .unwrap()])
+            .unwrap();
+    }
+    let data_bytes = writer.into_inner();
+    // An index file has the lead in and metadata of each segment without the raw data
+    let mut index_bytes = Vec::new();
+    let mut position = 0;
+    while position < data_bytes.len() {
+        let lead_in = &data_bytes[position..position + LEAD_IN_LENGTH as usize];
+        let next_segment_offset = u64::from_le_bytes(lead_in[12..20].try_into().unwrap());
+        let raw_data_offset = u64::from_le_bytes(lead_in[20..28].try_into().unwrap());
+        index_bytes.extend_from_slice(&INDEX_SEGMENT_TAG);
+        index_bytes.extend_from_slice(
+            &data_bytes
+                [position + 4..position + LEAD_IN_LENGTH as usize + raw_data_offset as usize],
+        );
+        position += LEAD_IN_LENGTH as usize + next_segment_offset as usize;
+    }
+    let directory = std::env::temp_dir();
+    let data_path = directory.join(format!("rstdms_index_pair_{}.tdms", std::process::id()));
+    let index_path = data_path.with_extension("tdms_index");
+    std::fs::write(&data_path, &data_bytes).unwrap();
+    std::fs::write(&index_path, &index_bytes).unwrap();
+
+    match TdmsFile::open(&index_path) {
+        Err(TdmsReadError::IndexFileProvided {
+            index_path: error_index_path,
+            data_path: error_data_path,
+        }) => {
+            assert_eq!(error_index_path, index_path);
+            assert_eq!(error_data_path, data_path);
+        }
+        Err(error) => panic!("Unexpected error: {}", error),
+        Ok(_) => panic!("Expected opening the index file to fail"),
+    }
+    let tdms_file = TdmsFile::open(&data_path).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+    assert_eq!(channel.read().into_vec::<i32>().unwrap(), vec![1, 2, 3, 4]);
+
+    drop(tdms_file);
+    std::fs::remove_file(&data_path).unwrap();
+    std::fs::remove_file(&index_path).unwrap();
+}
+
+#[test]
+fn read_segment_versions() {
+    let mut bytes = many_segments_file().into_bytes();
+    bytes[8..12].copy_from_slice(&4712_u32.to_le_bytes());
+    let tdms_file = TdmsFile::new(Cursor::new(bytes.clone())).unwrap();
+    assert_eq!(tdms_file.format_version(), Some(4712));
+    let segments = tdms_file.segments().unwrap();
+    assert_eq!(segments[0].version, 4712);
+    assert_eq!(segments[1].version, 4713);
+    let group = tdms_file.group("Group").unwrap();
+    assert_eq!(group.channel("Channel1").unwrap().len(), 40);
+
+    bytes[8..12].copy_from_slice(&4714_u32.to_le_bytes());
+    let error = TdmsFile::new(Cursor::new(bytes)).err().unwrap();
+    assert!(matches!(error, TdmsReadError::UnsupportedVersion(4714)));
+}
+
+#[test]
+fn warn_about_anomalies() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = raw_metadata(&[(
+        "/'Group'/'Channel1'",
+        i32_index(2),
+        vec![TdmsProperty::new("gain", TdmsValue::Int32(2))],
+    )]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![1, 2]));
+    let metadata_bytes = raw_metadata(&[(
+        "/'Group'/'Channel1'",
+        RawIndex::MatchesPrevious,
+        vec![TdmsProperty::new("gain", TdmsValue::Float64(2.5))],
+    )]);
+    test_file.add_segment(
+        toc_mask | 1 << 12,
+        &metadata_bytes,
+        &data_bytes_i32(vec![3, 4]),
+    );
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+
+    let warnings = tdms_file.warnings();
+    let kinds: Vec<TdmsWarningKind> = warnings.iter().map(|warning| warning.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TdmsWarningKind::PropertyTypeChanged,
+            TdmsWarningKind::UnknownTocFlags
+        ]
+    );
+    let second_segment_position = tdms_file.segments().unwrap()[1].position;
+    assert!(warnings
+        .iter()
+        .all(|warning| warning.position == Some(second_segment_position)));
+    assert_eq!(
+        warnings[0].message,
+        "Property gain of /'Group'/'Channel1' changed type from I32 to DBL"
+    );
+    assert_eq!(
+        warnings[1].to_string(),
+        format!(
+            "Unknown table of contents flags 0x1000 (segment at position {})",
+            second_segment_position
+        )
+    );
+    let summary = tdms_file.summary().unwrap();
+    assert_eq!(summary.warnings().len(), 2);
+}
+
+#[test]
+fn refuse_unknown_toc_flags_when_strict() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = raw_metadata(&[("/'Group'/'Channel1'", i32_index(2), Vec::new())]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![1, 2]));
+    test_file.add_segment(
+        toc_mask | 1 << 12,
+        &metadata_bytes,
+        &data_bytes_i32(vec![3, 4]),
+    );
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![5, 6]));
+    let file_bytes = test_file.into_bytes();
+
+    let options = ReadOptions::new().strict_toc_flags(true);
+    let result = TdmsFile::new_with_options(Cursor::new(file_bytes.clone()), &options);
+    assert!(matches!(
+        result,
+        Err(TdmsReadError::UnknownTocFlags(0x1000))
+    ));
+
+    let options = options.error_policy(ErrorPolicy::Skip);
+    let tdms_file = TdmsFile::new_with_options(Cursor::new(file_bytes), &options).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    let mut values = vec![0; channel.len() as usize];
+    channel.read_all_data(&mut values).unwrap();
+    assert_eq!(values, vec![1, 2, 5, 6]);
+    assert_eq!(tdms_file.skipped_segments().len(), 1);
+}
+
+#[test]
+fn object_repeated_in_later_segment_replaces_its_index() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = raw_metadata(&[
+        ("/'Group'/'Channel1'", i32_index(2), Vec::new()),
+        ("/'Group'/'Channel2'", i32_index(1), Vec::new()),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![1, 2, 10]));
+    // Channel1 is listed again without a new object list, so it keeps its
+    // position before Channel2 rather than being added to the end of the list
+    let metadata_bytes = raw_metadata(&[
+        ("/'Group'/'Channel1'", i32_index(1), Vec::new()),
+        (
+            "/'Group'/'Channel1'",
+            RawIndex::MatchesPrevious,
+            vec![TdmsProperty::new("gain", TdmsValue::Int32(2))],
+        ),
+    ]);
+    test_file.add_segment(
+        TOC_METADATA | TOC_RAW_DATA,
+        &metadata_bytes,
+        &data_bytes_i32(vec![3, 11]),
+    );
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+
+    let segments = tdms_file.segments().unwrap();
+    assert_eq!(segments[1].objects.len(), 2);
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    let mut values = vec![0; channel.len() as usize];
+    channel.read_all_data(&mut values).unwrap();
+    assert_eq!(values, vec![1, 2, 3]);
+    assert_eq!(
+        channel.properties(),
+        &[TdmsProperty::new("gain", TdmsValue::Int32(2))]
+    );
+    let channel = group.channel("Channel2").unwrap();
+    let mut values = vec![0; channel.len() as usize];
+    channel.read_all_data(&mut values).unwrap();
+    assert_eq!(values, vec![10, 11]);
+}
+
+#[test]
+fn shrunken_object_list_reexpanded_with_previous_indexes() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = raw_metadata(&[
+        ("/'Group'/'Channel1'", i32_index(2), Vec::new()),
+        ("/'Group'/'Channel2'", i32_index(2), Vec::new()),
+        ("/'Group'/'Channel3'", i32_index(1), Vec::new()),
+    ]);
+    test_file.add_segment(
+        TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA,
+        &metadata_bytes,
+        &data_bytes_i32(vec![1, 2, 10, 11, 100]),
+    );
+    // A new object list with only Channel1, so the other channels keep their cached
+    // indexes but have no data in this segment
+    let metadata_bytes =
+        raw_metadata(&[("/'Group'/'Channel1'", RawIndex::MatchesPrevious, Vec::new())]);
+    test_file.add_segment(
+        TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA,
+        &metadata_bytes,
+        &data_bytes_i32(vec![3, 4]),
+    );
+    // The other channels are added back to the list using their previous indexes
+    let metadata_bytes = raw_metadata(&[
+        ("/'Group'/'Channel2'", RawIndex::MatchesPrevious, Vec::new()),
+        ("/'Group'/'Channel3'", RawIndex::MatchesPrevious, Vec::new()),
+    ]);
+    test_file.add_segment(
+        TOC_METADATA | TOC_RAW_DATA,
+        &metadata_bytes,
+        &data_bytes_i32(vec![5, 6, 12, 13, 101]),
+    );
+    test_file.add_segment(TOC_RAW_DATA, &[], &data_bytes_i32(vec![7, 8, 14, 15, 102]));
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+
+    let object_counts: Vec<usize> = tdms_file
+        .segments()
+        .unwrap()
+        .iter()
+        .map(|segment| segment.objects.len())
+        .collect();
+    assert_eq!(object_counts, vec![3, 1, 3, 3]);
+    let group = tdms_file.group("Group").unwrap();
+    let expected: [(&str, Vec<i32>); 3] = [
+        ("Channel1", vec![1, 2, 3, 4, 5, 6, 7, 8]),
+        ("Channel2", vec![10, 11, 12, 13, 14, 15]),
+        ("Channel3", vec![100, 101, 102]),
+    ];
+    for (name, expected_values) in expected.iter() {
+        let channel = group.channel(name).unwrap();
+        assert_eq!(channel.len(), expected_values.len() as u64, "{}", name);
+        let mut values = vec![0; channel.len() as usize];
+        channel.read_all_data(&mut values).unwrap();
+        assert_eq!(&values, expected_values, "{}", name);
+    }
+}
+
+#[test]
+fn no_warnings_for_normal_file() {
+    let tdms_file = TdmsFile::new(many_segments_file().into_cursor()).unwrap();
+
+    assert!(tdms_file.warnings().is_empty());
+}
+
+#[test]
+fn warn_about_many_segments() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![object_metadata(
+        "/'Group'/'Channel1'",
+        &raw_data_index(3, 1),
+        Vec::new(),
+    )]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![0]));
+    for i in 1..10_001 {
+        test_file.add_segment(TOC_RAW_DATA, &Vec::new(), &data_bytes_i32(vec![i]));
+    }
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+
+    let warnings = tdms_file.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, TdmsWarningKind::ManySegments);
+    assert_eq!(warnings[0].position, None);
+}
+
+#[test]
+fn multi_dimensional_data_is_skipped_when_skipping_errors() {
+    let mut test_file = TestFile::new();
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    let channel1_metadata = metadata(vec![object_metadata(
+        "/'Group'/'Channel1'",
+        &raw_data_index(3, 2),
+        Vec::new(),
+    )]);
+    test_file.add_segment(toc_mask, &channel1_metadata, &data_bytes_i32(vec![1, 2]));
+    let mut two_dimensional_index = raw_data_index(3, 2);
+    two_dimensional_index[8..12].copy_from_slice(&2_u32.to_le_bytes());
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 2), Vec::new()),
+        object_metadata("/'Group'/'Image'", &two_dimensional_index, Vec::new()),
+    ]);
+    test_file.add_segment(
+        toc_mask,
+        &metadata_bytes,
+        &data_bytes_i32(vec![3, 4, 0, 0, 0, 0]),
+    );
+    test_file.add_segment(toc_mask, &channel1_metadata, &data_bytes_i32(vec![5, 6]));
+    let bytes = test_file.into_bytes();
+
+    match TdmsFile::new(Cursor::new(bytes.clone())) {
+        Err(TdmsReadError::UnsupportedDimension { path, dimension }) => {
+            assert_eq!(path, "/'Group'/'Image'");
+            assert_eq!(dimension, 2);
+        }
+        Err(error) => panic!("Unexpected error: {}", error),
+        Ok(_) => panic!("Expected an error"),
+    }
+
+    let options = ReadOptions::new().error_policy(ErrorPolicy::Skip);
+    let tdms_file = TdmsFile::new_with_options(Cursor::new(bytes), &options).unwrap();
+    assert_eq!(tdms_file.skipped_segments().len(), 1);
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    let mut data = vec![0; channel.len() as usize];
+    channel.read_all_data(&mut data).unwrap();
+    assert_eq!(data, vec![1, 2, 5, 6]);
+}
+
+#[test]
+fn report_fragmentation() {
+    let mut test_file = TestFile::new();
+    let string_data = || data_bytes_strings(vec!["ab", "cde"]);
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 2), Vec::new()),
+        object_metadata(
+            "/'Group'/'Strings'",
+            &string_raw_data_index(2, 13),
+            Vec::new(),
+        ),
+    ]);
+    let mut data = data_bytes_i32(vec![1, 2]);
+    data.extend(string_data());
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data);
+    let metadata_bytes = metadata(vec![object_metadata(
+        "/'Group'/'Channel1'",
+        &(0_u32.to_le_bytes()), // Raw data index matches previous
+        Vec::new(),
+    )]);
+    let mut data = data_bytes_i32(vec![3, 4]);
+    data.extend(string_data());
+    test_file.add_segment(TOC_METADATA | TOC_RAW_DATA, &metadata_bytes, &data);
+    let mut data = data_bytes_i32(vec![5, 6]);
+    data.extend(string_data());
+    test_file.add_segment(TOC_RAW_DATA, &Vec::new(), &data);
+    let metadata_bytes = metadata(vec![object_metadata(
+        "/'Group'/'Channel1'",
+        &raw_data_index(3, 4),
+        Vec::new(),
+    )]);
+    test_file.add_segment(
+        toc_mask,
+        &metadata_bytes,
+        &data_bytes_i32(vec![7, 8, 9, 10]),
+    );
+    let file_bytes = test_file.into_bytes();
+    let file_length = file_bytes.len() as u64;
+
+    let tdms_file = TdmsFile::new(Cursor::new(file_bytes)).unwrap();
+    let report = tdms_file.fragmentation_report().unwrap();
+
+    assert_eq!(report.segment_count, 4);
+    assert_eq!(report.data_bytes, 3 * (8 + 13) + 16);
+    assert_eq!(report.metadata_bytes + report.data_bytes, file_length);
+    assert_eq!(report.channels.len(), 2);
+
+    let channel = &report.channels[0];
+    assert_eq!(channel.path, "/'Group'/'Channel1'");
+    assert_eq!(channel.segment_count, 4);
+    assert_eq!(channel.min_values_per_segment, 2);
+    assert_eq!(channel.max_values_per_segment, 4);
+    assert_eq!(channel.mean_values_per_segment, 2.5);
+    assert_eq!(channel.data_bytes, 40);
+    assert_eq!(channel.reused_index_fraction, 0.5);
+
+    let channel = &report.channels[1];
+    assert_eq!(channel.path, "/'Group'/'Strings'");
+    assert_eq!(channel.segment_count, 3);
+    assert_eq!(channel.min_values_per_segment, 2);
+    assert_eq!(channel.max_values_per_segment, 2);
+    assert_eq!(channel.data_bytes, 39);
+    assert!((channel.reused_index_fraction - 2.0 / 3.0).abs() < 1e-12);
+}
+
+#[test]
+fn names_and_paths_with_special_characters() {
+    let group_path = "/'Group/''quoted''\nname'";
+    let channel_path = "/'Group/''quoted''\nname'/'Channel ''1''/\n'";
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata(group_path, &hex!("FF FF FF FF"), Vec::new()),
+        object_metadata(channel_path, &raw_data_index(3, 1), Vec::new()),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![1]));
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+
+    let group = tdms_file.groups().next().unwrap();
+    assert_eq!(group.name(), "Group/'quoted'\nname");
+    assert_eq!(group.path(), group_path);
+    let channel = group.channels().next().unwrap();
+    assert_eq!(channel.name(), "Channel '1'/\n");
+    assert_eq!(channel.group_name(), "Group/'quoted'\nname");
+    assert_eq!(channel.path(), channel_path);
+
+    let group = tdms_file.group("Group/'quoted'\nname").unwrap();
+    let channel = group.channel("Channel '1'/\n").unwrap();
+    assert_eq!(channel.path(), channel_path);
+    assert_eq!(
+        tdms_file.segments().unwrap()[0].objects[1].path,
+        channel_path
+    );
+}
+
+#[test]
+fn truncated_metadata_errors_describe_what_was_read() {
+    let segment = |number_of_values: u64, values: Vec<i32>| {
+        let mut test_file = TestFile::new();
+        let metadata_bytes = metadata(vec![object_metadata(
+            "/'Group'/'Channel1'",
+            &raw_data_index(3, number_of_values),
+            vec![("unit", 0x20, &hex!("03 00 00 00 62 61 72"))],
+        )]);
+        let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+        test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(values));
+        test_file.into_bytes()
+    };
+    let first_segment = segment(2, vec![1, 2]);
+    assert_eq!(segment(1, vec![3]).len(), 98 + 4);
+    // Ranges of bytes within the second segment, its number of values and either what
+    // the bytes contain or the error once they have been read
+    let layout = [
+        (0..28, 1, "segment lead in"),
+        (28..32, 1, "number of objects"),
+        (32..55, 1, "object path string"),
+        (55..59, 1, "raw data index header"),
+        (59..63, 1, "raw data index data type"),
+        (63..67, 1, "raw data index dimension"),
+        (67..75, 1, "raw data index number of values"),
+        (75..79, 1, "number of properties"),
+        (79..87, 1, "property name string"),
+        (87..91, 1, "property data type"),
+        (91..98, 1, "property value of type String"),
+        (
+            75..103,
+            u64::MAX,
+            "Object /'Group'/'Channel1' in segment at position 106 has too many values: \
+            18446744073709551615",
+        ),
+    ];
+
+    for (range, number_of_values, expected) in layout.iter() {
+        let second_segment = segment(*number_of_values, vec![3]);
+        for length in range.clone().filter(|length| *length > 0) {
+            let mut bytes = first_segment.clone();
+            bytes.extend(&second_segment[..length]);
+
+            let error = TdmsFile::new(Cursor::new(bytes.clone())).err();
+            match error {
+                Some(TdmsReadError::TruncatedMetadata {
+                    segment_position,
+                    parsing,
+                }) => {
+                    assert_eq!(segment_position, first_segment.len() as u64);
+                    assert_eq!(parsing, *expected, "Truncated at {}", length);
+                }
+                Some(TdmsReadError::InvalidData(message)) => {
+                    assert_eq!(message, *expected, "Truncated at {}", length);
+                }
+                error => panic!("Truncated at {} gave {:?}", length, error),
+            }
+
+            // Earlier segments are still read when skipping errors
+            let options = ReadOptions::new().error_policy(ErrorPolicy::Skip);
+            let tdms_file = TdmsFile::new_with_options(Cursor::new(bytes), &options).unwrap();
+            let group = tdms_file.group("Group").unwrap();
+            let channel = group.channel("Channel1").unwrap();
+            let mut values = vec![0; channel.len() as usize];
+            channel.read_all_data(&mut values).unwrap();
+            assert_eq!(values, vec![1, 2]);
+            assert_eq!(tdms_file.skipped_segments().len(), 1);
+        }
+    }
+}
+
+#[test]
+fn truncated_metadata_error_message() {
+    let error = TdmsReadError::TruncatedMetadata {
+        segment_position: 100,
+        parsing: "object path string",
+    };
+    assert_eq!(
+        error.to_string(),
+        "Metadata of segment at position 100 ended unexpectedly while reading the object path string"
+    );
+}
+
+/// Properties of a DAQmx channel with a linear scale
+fn linear_scale_properties(slope: f64, intercept: f64) -> Vec<(&'static str, u32, Vec<u8>)> {
+    vec![
+        ("NI_Number_Of_Scales", 7, 2_u32.to_le_bytes().to_vec()),
+        (
+            "NI_Scale[1]_Scale_Type",
+            0x20,
+            hex!("06 00 00 00 4C 69 6E 65 61 72").to_vec(),
+        ),
+        ("NI_Scale[1]_Linear_Slope", 10, slope.to_le_bytes().to_vec()),
+        (
+            "NI_Scale[1]_Linear_Y_Intercept",
+            10,
+            intercept.to_le_bytes().to_vec(),
+        ),
+    ]
+}
+
+fn daqmx_object_metadata(
+    path: &'static str,
+    raw_data_index: &[u8],
+    properties: &[(&'static str, u32, Vec<u8>)],
+) -> Vec<u8> {
+    object_metadata(
+        path,
+        raw_data_index,
+        properties
+            .iter()
+            .map(|(name, data_type, bytes)| (*name, *data_type, &bytes[..]))
+            .collect(),
+    )
+}
+
+#[test]
+fn read_daqmx_single_scaler_data() {
+    let mut test_file = TestFile::new();
+    // Two i16 channels in one raw buffer with rows of 6 bytes, the last 2 unused
+    let metadata_bytes = metadata(vec![
+        daqmx_object_metadata(
+            "/'Group'/'Channel1'",
+            &daqmx_raw_data_index(3, &[(3, 0, 0, 1)], &[6]),
+            &linear_scale_properties(2.0, 1.0),
+        ),
+        daqmx_object_metadata(
+            "/'Group'/'Channel2'",
+            &daqmx_raw_data_index(3, &[(3, 0, 2, 1)], &[6]),
+            &linear_scale_properties(0.5, 0.0),
+        ),
+    ]);
+    let rows = |values: &[(i16, i16)]| {
+        let mut bytes = Vec::new();
+        for (value1, value2) in values {
+            bytes.extend(&value1.to_le_bytes());
+            bytes.extend(&value2.to_le_bytes());
+            bytes.extend(&[0xFF, 0xFF]);
+        }
+        bytes
+    };
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA | TOC_DAQMX_RAW_DATA;
+    test_file.add_segment(
+        toc_mask,
+        &metadata_bytes,
+        &rows(&[(1, 10), (2, 20), (3, 30)]),
+    );
+    test_file.add_segment(
+        TOC_RAW_DATA | TOC_DAQMX_RAW_DATA,
+        &Vec::new(),
+        &rows(&[(-4, -40), (5, 50), (6, 60)]),
+    );
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+
+    let channel = group.channel("Channel1").unwrap();
+    assert_eq!(channel.data_type(), Some(TdsType::DaqmxRawData));
+    assert_eq!(channel.len(), 6);
+    assert_eq!(
+        channel.read().convert_lossy().into_vec().unwrap(),
+        vec![3.0, 5.0, 7.0, -7.0, 11.0, 13.0]
+    );
+
+    let channel = group.channel("Channel2").unwrap();
+    let mut data = vec![0.0; 3];
+    assert_eq!(channel.read_data_as_f64(2, &mut data).unwrap(), 3);
+    assert_eq!(data, vec![15.0, -20.0, 25.0]);
+    assert!(tdms_file.warnings().is_empty());
+    assert!(!tdms_file.segments().unwrap()[0].daqmx_flag_mismatch());
+}
+
+#[test]
+fn warn_about_daqmx_flag_mismatch() {
+    let metadata_bytes = metadata(vec![daqmx_object_metadata(
+        "/'Group'/'Channel1'",
+        &daqmx_raw_data_index(2, &[(3, 0, 0, 1)], &[2]),
+        &linear_scale_properties(1.0, 0.0),
+    )]);
+    let mut test_file = TestFile::new();
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &[1, 0, 2, 0]);
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+
+    // The raw data indexes describe the layout, so data is still read
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    assert_eq!(
+        channel.read().convert_lossy().into_vec().unwrap(),
+        vec![1.0, 2.0]
+    );
+    let warnings = tdms_file.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, TdmsWarningKind::DaqmxFlagMismatch);
+    assert_eq!(warnings[0].position, Some(0));
+    let segments = tdms_file.segments().unwrap();
+    assert!(segments[0].has_daqmx_indexes);
+    assert!(segments[0].daqmx_flag_mismatch());
+
+    let metadata_bytes = metadata(vec![object_metadata(
+        "/'Group'/'Channel1'",
+        &raw_data_index(3, 2),
+        Vec::new(),
+    )]);
+    let mut test_file = TestFile::new();
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA | TOC_DAQMX_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![1, 2]));
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    let mut data = vec![0; 2];
+    channel.read_all_data(&mut data).unwrap();
+    assert_eq!(data, vec![1, 2]);
+    let warnings = tdms_file.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, TdmsWarningKind::DaqmxFlagMismatch);
+    let segments = tdms_file.segments().unwrap();
+    assert!(!segments[0].has_daqmx_indexes);
+    assert!(segments[0].daqmx_flag_mismatch());
+}
+
+#[test]
+fn unsupported_daqmx_data_is_an_error_when_read() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        daqmx_object_metadata(
+            "/'Group'/'Channel1'",
+            &daqmx_raw_data_index(2, &[(3, 0, 0, 1)], &[2, 2]),
+            &linear_scale_properties(1.0, 0.0),
+        ),
+        daqmx_object_metadata(
+            "/'Group'/'Channel2'",
+            &daqmx_raw_data_index(2, &[(3, 0, 0, 1)], &[2]),
+            &[],
+        ),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA | TOC_DAQMX_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &[0; 8]);
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+
+    let channel = group.channel("Channel1").unwrap();
+    assert_eq!(channel.len(), 2);
+    let error = channel.read().convert_lossy().into_vec().unwrap_err();
+    assert!(matches!(
+        error,
+        TdmsReadError::UnsupportedDaqmxData { ref path, .. } if path == "/'Group'/'Channel1'"
+    ));
+    assert_eq!(
+        error.to_string(),
+        "Channel /'Group'/'Channel1' has DAQmx raw data that can't be read, \
+        only a single raw data buffer is supported"
+    );
+
+    let channel = group.channel("Channel2").unwrap();
+    let error = channel.read().convert_lossy().into_vec().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Channel /'Group'/'Channel2' has DAQmx raw data that can't be read, \
+        only linear scales are supported"
+    );
+}
+
+#[test]
+fn daqmx_scaler_outside_raw_buffer_is_an_error_when_read() {
+    let mut test_file = TestFile::new();
+    // The first scaler's buffer doesn't exist, and the second's values would extend
+    // past the end of its 4 byte rows
+    let metadata_bytes = metadata(vec![
+        daqmx_object_metadata(
+            "/'Group'/'Channel1'",
+            &daqmx_raw_data_index(3, &[(3, 1, 0, 1)], &[4]),
+            &linear_scale_properties(1.0, 0.0),
+        ),
+        daqmx_object_metadata(
+            "/'Group'/'Channel2'",
+            &daqmx_raw_data_index(3, &[(3, 0, 4, 1)], &[4]),
+            &linear_scale_properties(1.0, 0.0),
+        ),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA | TOC_DAQMX_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &[0; 24]);
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+
+    let channel = group.channel("Channel1").unwrap();
+    let error = channel.read().convert_lossy().into_vec().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Channel /'Group'/'Channel1' has DAQmx raw data that can't be read, \
+        the scaler's raw buffer is missing or has zero width"
+    );
+
+    let channel = group.channel("Channel2").unwrap();
+    let mut data = vec![0.0; 3];
+    let error = channel.read_data_as_f64(0, &mut data).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Channel /'Group'/'Channel2' has DAQmx raw data that can't be read, \
+        the scaler's values extend past the end of its raw buffer rows"
+    );
+}
+
+/// Decodes a vendor struct of an i32 count followed by an f64 scale as their product
+struct ScaledCountDecoder {
+    calls: std::sync::atomic::AtomicUsize,
+}
+
+impl CustomDecoder for ScaledCountDecoder {
+    fn decode(&self, bytes: &[u8], count: usize, big_endian: bool) -> rstdms::Result<CustomValues> {
+        assert!(!big_endian);
+        assert_eq!(bytes.len(), count * 12);
+        self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(CustomValues::Numbers(
+            bytes
+                .chunks(12)
+                .map(|value| {
+                    let count = i32::from_le_bytes(value[..4].try_into().unwrap());
+                    let scale = f64::from_le_bytes(value[4..].try_into().unwrap());
+                    count as f64 * scale
+                })
+                .collect(),
+        ))
+    }
+}
+
+fn custom_type_file() -> TestFile {
+    let custom_values = |values: &[(i32, f64)]| {
+        values
+            .iter()
+            .flat_map(|(count, scale)| {
+                let mut bytes = count.to_le_bytes().to_vec();
+                bytes.extend(&scale.to_le_bytes());
+                bytes
+            })
+            .collect::<Vec<u8>>()
+    };
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Custom'", &raw_data_index(0x3000, 3), Vec::new()),
+        object_metadata("/'Group'/'Other'", &raw_data_index(3, 3), Vec::new()),
+    ]);
+    let mut data: Vec<u8> = custom_values(&[(1, 0.5), (2, 0.5), (3, 0.5)]);
+    data.extend(data_bytes_i32(vec![10, 20, 30]));
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data);
+
+    // Interleaved rows of a custom value then an i32 value
+    let mut data = Vec::new();
+    for (value, other) in [(4, 2.0), (5, 2.0)].iter().zip([40_i32, 50].iter()) {
+        data.extend(custom_values(&[*value]));
+        data.extend(&other.to_le_bytes());
+    }
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Custom'", &raw_data_index(0x3000, 2), Vec::new()),
+        object_metadata("/'Group'/'Other'", &raw_data_index(3, 2), Vec::new()),
+    ]);
+    test_file.add_segment(toc_mask | TOC_INTERLEAVED_DATA, &metadata_bytes, &data);
+    test_file
+}
+
+#[test]
+fn read_registered_custom_type() {
+    let bytes = custom_type_file().into_bytes();
+    match TdmsFile::new(Cursor::new(bytes.clone())) {
+        Err(TdmsReadError::InvalidData(message)) => {
+            assert_eq!(message, "Invalid type id: 12288")
+        }
+        Err(error) => panic!("Unexpected error: {}", error),
+        Ok(_) => panic!("Expected an error"),
+    }
+
+    let decoder = std::sync::Arc::new(ScaledCountDecoder {
+        calls: std::sync::atomic::AtomicUsize::new(0),
+    });
+    let options = ReadOptions::new()
+        .chunk_config(ChunkConfig::new(24))
+        .register_custom_type(0x3000, 12, decoder.clone());
+    let tdms_file = TdmsFile::new_with_options(Cursor::new(bytes), &options).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Custom").unwrap();
+
+    assert_eq!(
+        channel.data_type(),
+        Some(TdsType::Custom {
+            code: 0x3000,
+            size: 12
+        })
+    );
+    assert_eq!(channel.len(), 5);
+    assert_eq!(
+        channel.read_custom(0, 5).unwrap(),
+        CustomValues::Numbers(vec![0.5, 1.0, 1.5, 8.0, 10.0])
+    );
+    // Data is decoded two values at a time, as configured by the chunk size
+    assert_eq!(decoder.calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+    assert_eq!(
+        channel.read_custom(2, 2).unwrap(),
+        CustomValues::Numbers(vec![1.5, 8.0])
+    );
+    assert_eq!(
+        channel.read().convert_lossy().into_vec().unwrap(),
+        vec![0.5, 1.0, 1.5, 8.0, 10.0]
+    );
+
+    let other = group.channel("Other").unwrap();
+    let mut data = vec![0; 5];
+    other.read_all_data(&mut data).unwrap();
+    assert_eq!(data, vec![10, 20, 30, 40, 50]);
+}
+
+#[test]
+fn custom_type_bytes_can_be_passed_through() {
+    let options = ReadOptions::new().register_custom_type(
+        0x3000,
+        12,
+        std::sync::Arc::new(PassthroughDecoder),
+    );
+    let tdms_file = TdmsFile::new_with_options(custom_type_file().into_cursor(), &options).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Custom").unwrap();
+
+    match channel.read_custom(3, 2).unwrap() {
+        CustomValues::Bytes(bytes) => {
+            assert_eq!(bytes.len(), 24);
+            assert_eq!(&bytes[..4], &4_i32.to_le_bytes());
+            assert_eq!(&bytes[12..16], &5_i32.to_le_bytes());
+        }
+        values => panic!("Unexpected values {:?}", values),
+    }
+    assert!(matches!(
+        channel.read().convert_lossy().into_vec(),
+        Err(TdmsReadError::TypeMismatch { .. })
+    ));
+}
+
+fn root_object_file(root_raw_data_index: &[u8]) -> TestFile {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata(
+            "/",
+            root_raw_data_index,
+            vec![
+                ("name", 0x20, &string_bytes("Test file")),
+                ("version", 3, &1_i32.to_le_bytes()),
+            ],
+        ),
+        object_metadata("/'Group'", &hex!("FF FF FF FF"), Vec::new()),
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 2), Vec::new()),
+        object_metadata("/''", &hex!("FF FF FF FF"), Vec::new()),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    let mut data_bytes = Vec::new();
+    if root_raw_data_index.len() > 4 {
+        data_bytes.extend(data_bytes_i32(vec![-1, -2]));
+    }
+    data_bytes.extend(data_bytes_i32(vec![1, 2]));
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes);
+    // The root object appears again with no data and an updated property
+    let metadata_bytes = metadata(vec![object_metadata(
+        "/",
+        &hex!("FF FF FF FF"),
+        vec![("version", 3, &2_i32.to_le_bytes())],
+    )]);
+    test_file.add_segment(TOC_METADATA | TOC_RAW_DATA, &metadata_bytes, &[]);
+    test_file
+}
+
+fn string_bytes(value: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_string(value, &mut bytes);
+    bytes
+}
+
+#[test]
+fn root_object_is_not_a_group() {
+    let tdms_file = TdmsFile::new(root_object_file(&hex!("FF FF FF FF")).into_cursor()).unwrap();
+
+    // A group with an empty name is a different object to the root object
+    let group_names: Vec<String> = tdms_file
+        .groups()
+        .map(|group| group.name().to_string())
+        .collect();
+    assert_eq!(group_names, vec!["Group", ""]);
+    assert!(tdms_file.group("").unwrap().properties().is_empty());
+    assert_eq!(tdms_file.group("").unwrap().channels().count(), 0);
+
+    let properties = tdms_file.properties();
+    assert_eq!(properties.len(), 2);
+    assert_eq!(properties[0].name, "name");
+    assert_eq!(
+        properties[0].value,
+        TdmsValue::String(String::from("Test file"))
+    );
+    assert_eq!(properties[1].name, "version");
+    assert_eq!(properties[1].value, TdmsValue::Int32(2));
+    assert!(tdms_file.warnings().is_empty());
+}
+
+#[test]
+fn root_object_data_is_ignored() {
+    let tdms_file = TdmsFile::new(root_object_file(&raw_data_index(3, 2)).into_cursor()).unwrap();
+
+    let warnings = tdms_file.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, TdmsWarningKind::RootObjectData);
+    assert_eq!(warnings[0].position, Some(0));
+
+    // The root object's data is skipped, so other channels are read correctly
+    let group_names: Vec<String> = tdms_file
+        .groups()
+        .map(|group| group.name().to_string())
+        .collect();
+    assert_eq!(group_names, vec!["Group", ""]);
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    let mut data = vec![0; channel.len() as usize];
+    channel.read_all_data(&mut data).unwrap();
+    assert_eq!(data, vec![1, 2]);
+    assert_eq!(tdms_file.find_channels_by(|_| true).len(), 1);
+    assert_eq!(tdms_file.properties().len(), 2);
+    let report = tdms_file.fragmentation_report().unwrap();
+    assert_eq!(report.channels.len(), 1);
+    assert_eq!(report.channels[0].path, "/'Group'/'Channel1'");
+}
+
+#[test]
+fn estimate_read_bytes() {
+    let mut writer = TdmsWriter::new(Vec::new());
+    for _ in 0..2 {
+        writer
+            .write_segment(&[
+                WriteObject::channel("Group", "Ints")
+                    .with_data(&[1i32, 2, 3])
+                    .unwrap(),
+                WriteObject::channel("Group", "Strings")
+                    .with_string_data(&["a", "bc", ""])
+                    .unwrap(),
+                WriteObject::channel("Group", "Empty"),
+            ])
+            .unwrap();
+    }
+    let tdms_file = TdmsFile::new(Cursor::new(writer.into_inner())).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let ints = group.channel("Ints").unwrap();
+    let strings = group.channel("Strings").unwrap();
+    let string_size = std::mem::size_of::<String>() as u64;
+
+    assert_eq!(ints.estimated_read_bytes(None), 24);
+    assert_eq!(ints.estimated_read_bytes(Some(TdsType::DoubleFloat)), 48);
+    assert_eq!(ints.estimated_read_bytes(Some(TdsType::U8)), 6);
+    assert_eq!(
+        ints.estimated_read_bytes(Some(TdsType::String)),
+        6 * (string_size + 16)
+    );
+    // String sizes come from the data stored in the file
+    assert_eq!(strings.estimated_read_bytes(None), 6 * string_size + 6);
+    assert_eq!(
+        group.channel("Empty").unwrap().estimated_read_bytes(None),
+        0
+    );
+
+    assert_eq!(
+        tdms_file
+            .estimated_read_bytes(
+                &["/'Group'/'Ints'", "/'Group'/'Strings'", "/'Group'/'Empty'"],
+                None
+            )
+            .unwrap(),
+        24 + 6 * string_size + 6
+    );
+    assert!(matches!(
+        tdms_file.estimated_read_bytes(&["/'Group'/'Int'"], None),
+        Err(TdmsReadError::NoSuchChannel { .. })
+    ));
+}
+
+#[test]
+fn find_channels_by_properties() {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata(
+            "/'Group1'/'Pressure'",
+            &raw_data_index(3, 1),
+            vec![
+                ("unit_string", 0x20, &hex!("03 00 00 00 62 61 72")),
+                ("wf_increment", 10, &0.0001_f64.to_le_bytes()),
+            ],
+        ),
+        object_metadata(
+            "/'Group1'/'Temperature'",
+            &raw_data_index(3, 1),
+            vec![("unit_string", 0x20, &hex!("04 00 00 00 64 65 67 43"))],
+        ),
+        object_metadata(
+            "/'Group2'/'Pressure'",
+            &raw_data_index(3, 1),
+            vec![
+                ("unit_string", 0x20, &hex!("03 00 00 00 62 61 72")),
+                ("wf_increment", 10, &0.01_f64.to_le_bytes()),
+            ],
+        ),
+    ]);
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(vec![1, 2, 3]));
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+
+    let paths = |expression: &str| -> Vec<String> {
+        let filter = PropertyFilter::parse(expression).unwrap();
+        tdms_file
+            .channels_matching(&filter)
+            .iter()
+            .map(|channel| channel.path().to_string())
+            .collect()
+    };
+    assert_eq!(
+        paths("unit_string == 'bar'"),
+        vec!["/'Group1'/'Pressure'", "/'Group2'/'Pressure'"]
+    );
+    assert_eq!(
+        paths("unit_string == 'bar' && wf_increment < 0.001"),
+        vec!["/'Group1'/'Pressure'"]
+    );
+    assert_eq!(
+        paths("unit_string == 'degC' || wf_increment > 0.001"),
+        vec!["/'Group1'/'Temperature'", "/'Group2'/'Pressure'"]
+    );
+
+    let channels = tdms_file.find_channels_by(|channel| channel.group_name() == "Group2");
+    assert_eq!(channels.len(), 1);
+    assert_eq!(channels[0].path(), "/'Group2'/'Pressure'");
+}
+
+#[test]
+fn group_task_properties_and_common_timing() {
+    let task_properties = vec![TdmsProperty::new(
+        NI_CHANNEL_NAME,
+        TdmsValue::String(String::from("Dev1/ai0, Dev1/ai1")),
+    )];
+    let timing = vec![TdmsProperty::new(WF_INCREMENT, TdmsValue::Float64(0.001))];
+    let other_timing = vec![TdmsProperty::new(WF_INCREMENT, TdmsValue::Float64(0.01))];
+    let mut writer = TdmsWriter::new(Vec::new());
+    writer
+        .write_segment(&[
+            WriteObject::group("Task").with_properties(&task_properties),
+            WriteObject::channel("Task", "ai0")
+                .with_properties(&timing)
+                .with_data(&[1.0f64, 2.0])
+                .unwrap(),
+            WriteObject::channel("Task", "ai1")
+                .with_properties(&timing)
+                .with_data(&[3.0f64, 4.0])
+                .unwrap(),
+            WriteObject::channel("Mixed", "ai0")
+                .with_properties(&timing)
+                .with_data(&[5.0f64])
+                .unwrap(),
+            WriteObject::channel("Mixed", "ai1")
+                .with_properties(&other_timing)
+                .with_data(&[6.0f64])
+                .unwrap(),
+            WriteObject::group("Empty"),
+        ])
+        .unwrap();
+    let tdms_file = TdmsFile::new(Cursor::new(writer.into_inner())).unwrap();
+
+    let task = tdms_file.group("Task").unwrap();
+    assert_eq!(task.channel_count(), 2);
+    assert_eq!(
+        task.property(NI_CHANNEL_NAME)
+            .and_then(|value| value.as_str()),
+        Some("Dev1/ai0, Dev1/ai1")
+    );
+    assert_eq!(task.property("missing"), None);
+    assert_eq!(
+        task.common_waveform_timing(),
+        Some(WaveformTiming {
+            start_time: None,
+            start_offset: 0.0,
+            increment: 0.001,
+        })
+    );
+
+    let mixed = tdms_file.group("Mixed").unwrap();
+    assert_eq!(mixed.channel_count(), 2);
+    assert_eq!(mixed.common_waveform_timing(), None);
+
+    let empty = tdms_file.group("Empty").unwrap();
+    assert_eq!(empty.channel_count(), 0);
+    assert_eq!(empty.common_waveform_timing(), None);
+}
+
+#[test]
+fn group_channels_properties_and_display() {
+    let group_properties = vec![
+        TdmsProperty::new("count", TdmsValue::Uint32(3)),
+        TdmsProperty::new("gain", TdmsValue::Float32(0.5)),
+        TdmsProperty::new("name", TdmsValue::String(String::from("Task"))),
+    ];
+    let mut writer = TdmsWriter::new(Vec::new());
+    writer
+        .write_segment(&[
+            WriteObject::group("It's").with_properties(&group_properties),
+            WriteObject::channel("It's", "c")
+                .with_data(&[1i32])
+                .unwrap(),
+            WriteObject::channel("It's", "a")
+                .with_data(&[2i32, 3])
+                .unwrap(),
+        ])
+        .unwrap();
+    writer
+        .write_segment(&[
+            WriteObject::channel("It's", "b")
+                .with_data(&[4.0f64])
+                .unwrap(),
+            WriteObject::channel("It's", "a")
+                .with_data(&[5i32])
+                .unwrap(),
+        ])
+        .unwrap();
+    let tdms_file = TdmsFile::new(Cursor::new(writer.into_inner())).unwrap();
+    let group = tdms_file.group("It's").unwrap();
+
+    assert_eq!(group.to_string(), "It's");
+    assert_eq!(format!("{:?}", group), "Group { name: \"It's\" }");
+    assert_eq!(group.len(), 3);
+    assert!(!group.is_empty());
+
+    assert_eq!(group.get_property::<u32>("count"), Some(3));
+    assert_eq!(group.get_property::<i64>("count"), Some(3));
+    assert_eq!(group.get_property::<u8>("count"), Some(3));
+    assert_eq!(group.get_property::<f64>("gain"), Some(0.5));
+    assert_eq!(
+        group.get_property::<String>("name"),
+        Some(String::from("Task"))
+    );
+    assert_eq!(group.get_property::<String>("count"), None);
+    assert_eq!(group.get_property::<f64>("missing"), None);
+
+    let names = |channels: rstdms::ChannelIterator<_>| -> Vec<String> {
+        channels.map(|channel| channel.name().to_string()).collect()
+    };
+    assert_eq!(names(group.channels()), vec!["c", "a", "b"]);
+    assert_eq!(
+        format!("{:?}", group.channel("a").unwrap()),
+        "Channel { group_name: \"It's\", name: \"a\" }"
+    );
+    assert_eq!(
+        names(group.channels_in_order(ChannelOrder::Name)),
+        vec!["a", "b", "c"]
+    );
+
+    let infos = group.channel_infos();
+    let lengths: Vec<(&str, u64)> = infos
+        .iter()
+        .map(|info| (info.name.as_str(), info.len))
+        .collect();
+    assert_eq!(lengths, vec![("c", 1), ("a", 3), ("b", 1)]);
+    assert_eq!(infos[2].group_name, "It's");
+    assert_eq!(infos[2].path, "/'It''s'/'b'");
+    assert_eq!(infos[2].data_type, Some(TdsType::DoubleFloat));
+}
+
+#[test]
+fn gaps_between_segments() {
+    let start_time = |seconds| {
+        vec![TdmsProperty::new(
+            WF_START_TIME,
+            TdmsValue::Timestamp(Timestamp::new(seconds, 0)),
+        )]
+    };
+    let increment = vec![TdmsProperty::new(WF_INCREMENT, TdmsValue::Float64(0.5))];
+    let mut writer = TdmsWriter::new(Vec::new());
+    writer
+        .write_segment(&[WriteObject::channel("Group", "Channel")
+            .with_properties(&[start_time(100), increment].concat())
+            .with_data(&[1i32, 2, 3, 4])
+            .unwrap()])
+        .unwrap();
+    writer
+        .write_segment(&[WriteObject::channel("Group", "Channel")
+            .with_data(&[5i32, 6])
+            .unwrap()])
+        .unwrap();
+    // Continues exactly where the previous data ended
+    writer
+        .write_segment(&[WriteObject::channel("Group", "Channel")
+            .with_properties(&start_time(103))
+            .with_data(&[7i32, 8])
+            .unwrap()])
+        .unwrap();
+    // Acquisition paused for 10 seconds
+    writer
+        .write_segment(&[WriteObject::channel("Group", "Channel")
+            .with_properties(&start_time(114))
+            .with_data(&[9i32])
+            .unwrap()])
+        .unwrap();
+    let tdms_file = TdmsFile::new(Cursor::new(writer.into_inner())).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+
+    let timings = channel.segments_timing();
+
+    let ranges: Vec<(u64, u64)> = timings
+        .iter()
+        .map(|timing| (timing.start_index, timing.samples))
+        .collect();
+    assert_eq!(ranges, vec![(0, 6), (6, 2), (8, 1)]);
+    assert_eq!(timings[2].start_time, Some(Timestamp::new(114, 0)));
+    assert_eq!(timings[2].increment, 0.5);
+
+    let gaps = channel.detect_gaps(chrono::Duration::milliseconds(1));
+
+    assert_eq!(gaps.len(), 1);
+    assert_eq!(gaps[0].after_sample_index, 7);
+    assert_eq!(Some(gaps[0].expected), Timestamp::new(104, 0).to_datetime());
+    assert_eq!(Some(gaps[0].actual), Timestamp::new(114, 0).to_datetime());
+    assert_eq!(gaps[0].duration(), chrono::Duration::seconds(10));
+}
+
+/// Check that every way of reading a channel agrees on the values it has
+fn assert_channel_values(tdms_file: &TdmsFile<Cursor<Vec<u8>>>, channel: &str, expected: &[i32]) {
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel(channel).unwrap();
+
+    assert_eq!(channel.len(), expected.len() as u64);
+
+    let mut data = vec![0i32; expected.len()];
+    channel.read_all_data(&mut data).unwrap();
+    assert_eq!(data, expected);
+
+    let mut data = vec![0i32; expected.len() + 2];
+    let values_read = channel.read_data(1, &mut data).unwrap();
+    assert_eq!(&data[..values_read], &expected[1..]);
+
+    let chunks = channel
+        .read()
+        .chunk_size(2)
+        .chunks::<i32>()
+        .collect::<Result<Vec<Vec<i32>>, TdmsReadError>>()
+        .unwrap();
+    assert_eq!(chunks.concat(), expected);
+
+    match channel_statistics(&channel, &StatisticsOptions::default()).unwrap() {
+        ChannelStatistics::Numeric(statistics) => {
+            assert_eq!(statistics.count(), expected.len() as u64);
+            assert_eq!(
+                statistics.max(),
+                expected.iter().max().map(|&max| max as f64)
+            );
+        }
+        statistics => panic!("Unexpected statistics {:?}", statistics),
+    }
+}
+
+fn truncated_file(toc_mask: u32, final_data_length: usize) -> TdmsFile<Cursor<Vec<u8>>> {
+    let mut test_file = TestFile::new();
+    let metadata_bytes = metadata(vec![
+        object_metadata("/'Group'/'Channel1'", &raw_data_index(3, 3), Vec::new()),
+        object_metadata("/'Group'/'Channel2'", &raw_data_index(3, 3), Vec::new()),
+    ]);
+    let first_data = if toc_mask & TOC_INTERLEAVED_DATA != 0 {
+        vec![1, 4, 2, 5, 3, 6]
+    } else {
+        vec![1, 2, 3, 4, 5, 6]
+    };
+    test_file.add_segment(
+        toc_mask | TOC_METADATA | TOC_NEW_OBJ_LIST,
+        &metadata_bytes,
+        &data_bytes_i32(first_data),
+    );
+    let final_data = if toc_mask & TOC_INTERLEAVED_DATA != 0 {
+        vec![10, 20, 11, 21, 12, 22]
+    } else {
+        vec![10, 11, 12, 20, 21, 22]
+    };
+    let mut final_data = data_bytes_i32(final_data);
+    final_data.truncate(final_data_length);
+    test_file.add_incomplete_segment(toc_mask, &Vec::new(), &final_data);
+    TdmsFile::new(test_file.into_cursor()).unwrap()
+}
+
+#[test]
+fn truncated_within_first_channel_block() {
+    // Two and a half values of the first channel were written
+    let tdms_file = truncated_file(TOC_RAW_DATA, 10);
+
+    assert_channel_values(&tdms_file, "Channel1", &[1, 2, 3, 10, 11]);
+    assert_channel_values(&tdms_file, "Channel2", &[4, 5, 6]);
+}
+
+#[test]
+fn truncated_within_later_channel_block() {
+    // All of the first channel and one and a quarter values of the second were written
+    let tdms_file = truncated_file(TOC_RAW_DATA, 17);
+
+    assert_channel_values(&tdms_file, "Channel1", &[1, 2, 3, 10, 11, 12]);
+    assert_channel_values(&tdms_file, "Channel2", &[4, 5, 6, 20]);
+}
+
+#[test]
+fn truncated_mid_row_of_interleaved_data() {
+    // One whole row, then the first channel's value and one byte of the second channel's
+    let tdms_file = truncated_file(TOC_RAW_DATA | TOC_INTERLEAVED_DATA, 13);
+
+    assert_channel_values(&tdms_file, "Channel1", &[1, 2, 3, 10]);
+    assert_channel_values(&tdms_file, "Channel2", &[4, 5, 6, 20]);
+}
@@ -0,0 +1,401 @@
+mod common;
+
+use common::*;
+use std::cell::RefCell;
+
+use rstdms::{CancellationToken, ChunkConfig, ReadOptions, TdmsFile, TdmsReadError, TdsType};
+
+/// A file with one I32 channel with values 0..20 split over segments of different lengths
+fn test_file() -> TestFile {
+    let mut test_file = TestFile::new();
+    let mut next_value = 0;
+    for length in [3, 7, 1, 5, 4].iter() {
+        let metadata_bytes = metadata(vec![object_metadata(
+            "/'Group'/'Channel'",
+            &raw_data_index(3, *length),
+            Vec::new(),
+        )]);
+        let data: Vec<i32> = (next_value..next_value + *length as i32).collect();
+        next_value += *length as i32;
+        let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+        test_file.add_segment(toc_mask, &metadata_bytes, &data_bytes_i32(data));
+    }
+    test_file
+}
+
+#[test]
+fn read_all_values() {
+    let tdms_file = TdmsFile::new(test_file().into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+
+    let values = channel.read().into_vec::<i32>().unwrap();
+
+    assert_eq!(values, (0..20).collect::<Vec<i32>>());
+}
+
+#[test]
+fn read_ranges() {
+    let tdms_file = TdmsFile::new(test_file().into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+
+    assert_eq!(
+        channel.read().range(2..6).into_vec::<i32>().unwrap(),
+        vec![2, 3, 4, 5]
+    );
+    assert_eq!(
+        channel.read().range(17..).into_vec::<i32>().unwrap(),
+        vec![17, 18, 19]
+    );
+    assert_eq!(
+        channel.read().range(..=2).into_vec::<i32>().unwrap(),
+        vec![0, 1, 2]
+    );
+    assert_eq!(
+        channel.read().range(18..100).into_vec::<i32>().unwrap(),
+        vec![18, 19]
+    );
+    assert!(channel
+        .read()
+        .range(25..30)
+        .into_vec::<i32>()
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn read_decimated_values() {
+    let tdms_file = TdmsFile::new(test_file().into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+
+    for stride in 1..22_u64 {
+        for chunk_size in [1, 2, 3, 7, 64].iter() {
+            let builder = channel
+                .read()
+                .range(1..)
+                .decimate(stride)
+                .chunk_size(*chunk_size);
+            let expected: Vec<i32> = (1..20).step_by(stride as usize).collect();
+            assert_eq!(builder.len(), expected.len() as u64);
+
+            let values = builder.into_vec::<i32>().unwrap();
+
+            assert_eq!(
+                values, expected,
+                "stride {}, chunk size {}",
+                stride, chunk_size
+            );
+        }
+    }
+}
+
+#[test]
+fn read_into_slice() {
+    let tdms_file = TdmsFile::new(test_file().into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+
+    let mut buffer = vec![0; 4];
+    let values_read = channel
+        .read()
+        .range(5..)
+        .decimate(3)
+        .into_slice(&mut buffer)
+        .unwrap();
+    assert_eq!(values_read, 4);
+    assert_eq!(buffer, vec![5, 8, 11, 14]);
+
+    let mut buffer = vec![0; 4];
+    let values_read = channel
+        .read()
+        .range(15..)
+        .decimate(2)
+        .into_slice(&mut buffer)
+        .unwrap();
+    assert_eq!(values_read, 3);
+    assert_eq!(&buffer[..values_read], &[15, 17, 19]);
+}
+
+#[test]
+fn read_converted_values() {
+    let tdms_file = TdmsFile::new(test_file().into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+
+    let values = channel
+        .read()
+        .range(10..16)
+        .decimate(2)
+        .convert_lossy()
+        .into_vec()
+        .unwrap();
+
+    assert_eq!(values, vec![10.0, 12.0, 14.0]);
+}
+
+#[test]
+fn read_chunks() {
+    let tdms_file = TdmsFile::new(test_file().into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+
+    let chunks: Vec<Vec<i32>> = channel
+        .read()
+        .range(2..13)
+        .chunk_size(4)
+        .chunks::<i32>()
+        .collect::<Result<Vec<Vec<i32>>, TdmsReadError>>()
+        .unwrap();
+    assert_eq!(
+        chunks,
+        vec![vec![2, 3, 4, 5], vec![6, 7, 8, 9], vec![10, 11, 12]]
+    );
+
+    let chunks: Vec<Vec<f64>> = channel
+        .read()
+        .decimate(5)
+        .chunk_size(3)
+        .convert_lossy()
+        .chunks()
+        .collect::<Result<Vec<Vec<f64>>, TdmsReadError>>()
+        .unwrap();
+    assert_eq!(chunks, vec![vec![0.0, 5.0, 10.0], vec![15.0]]);
+}
+
+#[test]
+fn chunk_size_from_chunk_config() {
+    let options = ReadOptions::new().chunk_config(ChunkConfig::new(32));
+    let tdms_file = TdmsFile::new_with_options(test_file().into_cursor(), &options).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+
+    let chunk_lengths =
+        |chunks: Vec<Vec<i32>>| -> Vec<usize> { chunks.iter().map(|chunk| chunk.len()).collect() };
+    let read_chunks = |builder: rstdms::ReadBuilder<_>| {
+        builder
+            .chunks::<i32>()
+            .collect::<Result<Vec<Vec<i32>>, TdmsReadError>>()
+            .unwrap()
+    };
+
+    // 32 bytes is 8 i32 values
+    let chunks = read_chunks(channel.read());
+    assert_eq!(chunks.concat(), (0..20).collect::<Vec<i32>>());
+    assert_eq!(chunk_lengths(chunks), vec![8, 8, 4]);
+
+    let chunks = read_chunks(channel.read().chunk_config(ChunkConfig::new(40)));
+    assert_eq!(chunk_lengths(chunks), vec![10, 10]);
+
+    let chunks = read_chunks(channel.read().chunk_size(6));
+    assert_eq!(chunk_lengths(chunks), vec![6, 6, 6, 2]);
+}
+
+#[test]
+fn report_progress() {
+    let tdms_file = TdmsFile::new(test_file().into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+    let progress = RefCell::new(Vec::new());
+
+    channel
+        .read()
+        .range(5..15)
+        .decimate(2)
+        .chunk_size(4)
+        .progress(|processed, total| progress.borrow_mut().push((processed, total)))
+        .into_vec::<i32>()
+        .unwrap();
+
+    let progress = progress.into_inner();
+    assert!(!progress.is_empty());
+    assert!(progress.iter().all(|(_, total)| *total == 10));
+    assert!(progress.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    assert_eq!(progress.last(), Some(&(10, 10)));
+}
+
+#[test]
+fn cancel_read() {
+    let tdms_file = TdmsFile::new(test_file().into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let result = channel.read().cancel(token.clone()).into_vec::<i32>();
+    assert!(matches!(result, Err(TdmsReadError::Cancelled)));
+
+    let mut chunks = channel.read().cancel(token).chunks::<i32>();
+    assert!(matches!(chunks.next(), Some(Err(TdmsReadError::Cancelled))));
+    assert!(chunks.next().is_none());
+}
+
+#[test]
+fn invalid_reads_are_errors() {
+    let tdms_file = TdmsFile::new(test_file().into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+
+    assert!(matches!(
+        channel.read().decimate(0).into_vec::<i32>(),
+        Err(TdmsReadError::InvalidArgument(_))
+    ));
+    assert!(matches!(
+        channel.read().chunk_size(0).into_vec::<i32>(),
+        Err(TdmsReadError::InvalidArgument(_))
+    ));
+    match channel.read().into_vec::<f64>() {
+        Err(TdmsReadError::TypeMismatch {
+            data_type,
+            requested,
+        }) => {
+            assert_eq!(data_type, TdsType::I32);
+            assert_eq!(requested, "F64 values");
+        }
+        other => panic!("Expected a type mismatch, got {:?}", other),
+    }
+
+    let mut chunks = channel.read().chunks::<u8>();
+    assert!(matches!(
+        chunks.next(),
+        Some(Err(TdmsReadError::TypeMismatch { .. }))
+    ));
+    assert!(chunks.next().is_none());
+}
+
+#[test]
+fn prefetch_chunks() {
+    let mut path = std::env::temp_dir();
+    path.push(format!(
+        "rstdms_read_builder_{}_prefetch.tdms",
+        std::process::id()
+    ));
+    std::fs::write(&path, test_file().into_bytes()).unwrap();
+    let tdms_file = TdmsFile::open(&path).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+
+    for depth in [0, 1, 3, 100].iter() {
+        let chunks: Vec<Vec<i32>> = channel
+            .read()
+            .range(2..)
+            .decimate(2)
+            .chunk_size(2)
+            .prefetch(*depth)
+            .chunks::<i32>()
+            .collect::<Result<Vec<Vec<i32>>, TdmsReadError>>()
+            .unwrap();
+        let expected: Vec<Vec<i32>> = (2..20)
+            .step_by(2)
+            .collect::<Vec<i32>>()
+            .chunks(2)
+            .map(|chunk| chunk.to_vec())
+            .collect();
+        assert_eq!(chunks, expected, "prefetch depth {}", depth);
+    }
+
+    // Stopping iteration early stops prefetching
+    let mut chunks = channel.read().chunk_size(1).prefetch(4).chunks::<i32>();
+    assert_eq!(chunks.next().unwrap().unwrap(), vec![0]);
+    drop(chunks);
+    drop(tdms_file);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn prefetch_is_ignored_without_a_path() {
+    let tdms_file = TdmsFile::new(test_file().into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+
+    let values: Vec<i32> = channel
+        .read()
+        .chunk_size(3)
+        .prefetch(2)
+        .chunks::<i32>()
+        .flat_map(|chunk| chunk.unwrap())
+        .collect();
+
+    assert_eq!(values, (0..20).collect::<Vec<i32>>());
+}
+
+#[test]
+fn resume_chunks_from_position() {
+    let tdms_file = TdmsFile::new(test_file().into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+
+    let mut chunks = channel
+        .read()
+        .range(1..18)
+        .decimate(2)
+        .chunk_size(3)
+        .chunks::<i32>();
+    assert_eq!(chunks.next().unwrap().unwrap(), vec![1, 3, 5]);
+    assert_eq!(chunks.next().unwrap().unwrap(), vec![7, 9, 11]);
+    let position = chunks.position().unwrap();
+    drop(chunks);
+    assert_eq!(position.value_offset(), 13);
+    assert_eq!(position.chunk_index(), 2);
+
+    // The range and stride are continued from the position, with a new chunk size
+    let tdms_file = TdmsFile::new(test_file().into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+    let mut chunks = channel
+        .read()
+        .chunk_size(2)
+        .resume_from(&position)
+        .unwrap()
+        .chunks::<i32>();
+    assert_eq!(chunks.next().unwrap().unwrap(), vec![13, 15]);
+    assert_eq!(chunks.position().unwrap().chunk_index(), 3);
+    assert_eq!(chunks.next().unwrap().unwrap(), vec![17]);
+    assert!(chunks.next().is_none());
+
+    let values: Vec<i32> = channel
+        .data_chunks_from::<i32>(&position)
+        .unwrap()
+        .flat_map(|chunk| chunk.unwrap())
+        .collect();
+    assert_eq!(values, vec![13, 15, 17]);
+}
+
+#[test]
+fn resuming_from_invalid_position_is_an_error() {
+    let tdms_file = TdmsFile::new(test_file().into_cursor()).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel").unwrap();
+    let mut chunks = channel.read().chunk_size(4).chunks::<i32>();
+    chunks.next().unwrap().unwrap();
+    let position = chunks.position().unwrap();
+
+    let mut changed_file = test_file();
+    let metadata_bytes = metadata(vec![object_metadata(
+        "/'Group'/'Other'",
+        &raw_data_index(3, 1),
+        Vec::new(),
+    )]);
+    changed_file.add_segment(
+        TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA,
+        &metadata_bytes,
+        &data_bytes_i32(vec![0]),
+    );
+    let changed_file = TdmsFile::new(changed_file.into_cursor()).unwrap();
+    let group = changed_file.group("Group").unwrap();
+    assert!(matches!(
+        group
+            .channel("Channel")
+            .unwrap()
+            .data_chunks_from::<i32>(&position),
+        Err(TdmsReadError::Incompatible(_))
+    ));
+    assert!(matches!(
+        group
+            .channel("Other")
+            .unwrap()
+            .data_chunks_from::<i32>(&position),
+        Err(TdmsReadError::InvalidArgument(_))
+    ));
+}
@@ -0,0 +1,98 @@
+extern crate hex_literal;
+
+mod common;
+
+use common::*;
+use rstdms::TdmsFile;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+fn append_bytes(path: &PathBuf, bytes: &[u8]) {
+    let mut file = OpenOptions::new().append(true).open(path).unwrap();
+    file.write_all(bytes).unwrap();
+}
+
+fn read_channel(tdms_file: &TdmsFile<File>) -> Vec<i32> {
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    let mut data = vec![0; channel.len() as usize];
+    channel.read_all_data(&mut data[..]).unwrap();
+    data
+}
+
+#[test]
+fn refresh_reads_appended_segments() {
+    let path = write_temp_file("appended", &channel_segment(vec![1, 2, 3]));
+    let mut tdms_file = TdmsFile::new(File::open(&path).unwrap()).unwrap();
+    assert_eq!(read_channel(&tdms_file), vec![1, 2, 3]);
+
+    assert!(!tdms_file.refresh().unwrap());
+
+    append_bytes(&path, &channel_segment(vec![4, 5]));
+    let refreshed = tdms_file.refresh();
+    let _ = std::fs::remove_file(&path);
+
+    assert!(refreshed.unwrap());
+    assert_eq!(read_channel(&tdms_file), vec![1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn refresh_waits_for_partially_written_metadata() {
+    let path = write_temp_file("partial", &channel_segment(vec![1, 2, 3]));
+    let mut tdms_file = TdmsFile::new(File::open(&path).unwrap()).unwrap();
+
+    // Write the lead in and part of the metadata of the next segment
+    let segment = channel_segment(vec![4, 5]);
+    append_bytes(&path, &segment[..10]);
+    let first_refresh = tdms_file.refresh();
+    append_bytes(&path, &segment[10..40]);
+    let second_refresh = tdms_file.refresh();
+    let partial_data = read_channel(&tdms_file);
+    append_bytes(&path, &segment[40..]);
+    let final_refresh = tdms_file.refresh();
+    let _ = std::fs::remove_file(&path);
+
+    assert!(!first_refresh.unwrap());
+    assert!(!second_refresh.unwrap());
+    assert_eq!(partial_data, vec![1, 2, 3]);
+    assert!(final_refresh.unwrap());
+    assert_eq!(read_channel(&tdms_file), vec![1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn refresh_rereads_incomplete_final_segment() {
+    // The final segment's length is written before its data
+    let segment = channel_segment(vec![4, 5]);
+    let data_length = 8;
+    let mut bytes = channel_segment(vec![1, 2, 3]);
+    bytes.extend(&segment[..segment.len() - data_length]);
+    let path = write_temp_file("incomplete", &bytes);
+    let mut tdms_file = TdmsFile::new(File::open(&path).unwrap()).unwrap();
+    assert_eq!(read_channel(&tdms_file), vec![1, 2, 3]);
+
+    append_bytes(&path, &segment[segment.len() - data_length..]);
+    append_bytes(&path, &channel_segment(vec![6]));
+    let refreshed = tdms_file.refresh();
+    let _ = std::fs::remove_file(&path);
+
+    assert!(refreshed.unwrap());
+    assert_eq!(read_channel(&tdms_file), vec![1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn refresh_fails_if_file_is_truncated() {
+    let mut bytes = channel_segment(vec![1, 2, 3]);
+    bytes.extend(channel_segment(vec![4, 5]));
+    let path = write_temp_file("truncated", &bytes);
+    let mut tdms_file = TdmsFile::new(File::open(&path).unwrap()).unwrap();
+
+    File::create(&path)
+        .unwrap()
+        .write_all(&channel_segment(vec![1, 2, 3]))
+        .unwrap();
+    let refreshed = tdms_file.refresh();
+    let _ = std::fs::remove_file(&path);
+
+    assert!(refreshed.is_err());
+}
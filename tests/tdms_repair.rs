@@ -0,0 +1,315 @@
+extern crate hex_literal;
+
+mod common;
+
+use common::*;
+use rstdms::raw_writer::RawIndex;
+use rstdms::{
+    finalize_in_place, repair, ErrorPolicy, FinalizeResult, ReadOptions, TdmsFile, TdmsProperty,
+    TdmsValue, TdmsWarningKind, TdsType,
+};
+use std::io::Cursor;
+
+fn read_i32_channel<R: std::io::Read + std::io::Seek>(
+    tdms_file: &TdmsFile<R>,
+    channel_name: &str,
+) -> Vec<i32> {
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel(channel_name).unwrap();
+    let mut data = vec![0; channel.len() as usize];
+    channel.read_all_data(&mut data[..]).unwrap();
+    data
+}
+
+fn two_channel_metadata(values_per_chunk: u64) -> Vec<u8> {
+    raw_metadata(&[
+        (
+            "/'Group'/'Channel1'",
+            i32_index(values_per_chunk),
+            vec![TdmsProperty::new(
+                "unit_string",
+                TdmsValue::String(String::from("V")),
+            )],
+        ),
+        (
+            "/'Group'/'Channel2'",
+            i32_index(values_per_chunk),
+            Vec::new(),
+        ),
+    ])
+}
+
+#[test]
+fn read_incomplete_final_segment() {
+    let mut test_file = TestFile::new();
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(
+        toc_mask,
+        &two_channel_metadata(2),
+        &data_bytes_i32(vec![1, 2, 11, 12]),
+    );
+    // Final segment has one complete chunk, then a partial chunk with
+    // all of channel 1's data and half a value of channel 2
+    let mut data_bytes = data_bytes_i32(vec![3, 4, 13, 14, 5, 6]);
+    data_bytes.extend(&[0, 0]);
+    test_file.add_incomplete_segment(toc_mask, &two_channel_metadata(2), &data_bytes);
+
+    let tdms_file = TdmsFile::new(test_file.into_cursor()).unwrap();
+
+    assert_eq!(
+        read_i32_channel(&tdms_file, "Channel1"),
+        vec![1, 2, 3, 4, 5, 6]
+    );
+    assert_eq!(
+        read_i32_channel(&tdms_file, "Channel2"),
+        vec![11, 12, 13, 14]
+    );
+    let group = tdms_file.group("Group").unwrap();
+    assert_eq!(
+        group
+            .channel("Channel1")
+            .unwrap()
+            .truncated_values()
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        group
+            .channel("Channel2")
+            .unwrap()
+            .truncated_values()
+            .unwrap(),
+        2
+    );
+    let warnings = tdms_file.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, TdmsWarningKind::TruncatedChannel);
+    assert_eq!(
+        warnings[0].message,
+        "Channel /'Group'/'Channel2' is missing 2 value(s) as the final segment was truncated"
+    );
+}
+
+#[test]
+fn skip_invalid_segment() {
+    let mut test_file = TestFile::new();
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(
+        toc_mask,
+        &two_channel_metadata(2),
+        &data_bytes_i32(vec![1, 2, 11, 12]),
+    );
+    test_file.add_bytes(&[0xAB; 37]);
+    test_file.add_segment(
+        toc_mask,
+        &two_channel_metadata(1),
+        &data_bytes_i32(vec![3, 13]),
+    );
+    let bytes = test_file.into_bytes();
+
+    let strict_result = TdmsFile::new(Cursor::new(bytes.clone()));
+    assert!(strict_result.is_err());
+
+    let options = ReadOptions::new().error_policy(ErrorPolicy::Skip);
+    let tdms_file = TdmsFile::new_with_options(Cursor::new(bytes), &options).unwrap();
+
+    let skipped = tdms_file.skipped_segments();
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].resume_position, Some(skipped[0].position + 37));
+    assert_eq!(read_i32_channel(&tdms_file, "Channel1"), vec![1, 2, 3]);
+    assert_eq!(read_i32_channel(&tdms_file, "Channel2"), vec![11, 12, 13]);
+}
+
+#[test]
+fn skip_segment_with_overflowing_offset() {
+    let mut test_file = TestFile::new();
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(
+        toc_mask,
+        &two_channel_metadata(2),
+        &data_bytes_i32(vec![1, 2, 11, 12]),
+    );
+    // A lead in with an unsupported version and a next segment offset that overflows
+    // when added to the segment position
+    let mut lead_in = Vec::new();
+    lead_in.extend(b"TDSm");
+    lead_in.extend(&toc_mask.to_le_bytes());
+    lead_in.extend(&4714_u32.to_le_bytes());
+    lead_in.extend(&(u64::MAX - 27).to_le_bytes());
+    lead_in.extend(&0_u64.to_le_bytes());
+    test_file.add_bytes(&lead_in);
+    test_file.add_segment(
+        toc_mask,
+        &two_channel_metadata(1),
+        &data_bytes_i32(vec![3, 13]),
+    );
+    let bytes = test_file.into_bytes();
+
+    let options = ReadOptions::new().error_policy(ErrorPolicy::Skip);
+    let tdms_file = TdmsFile::new_with_options(Cursor::new(bytes.clone()), &options).unwrap();
+
+    let skipped = tdms_file.skipped_segments();
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].resume_position, Some(skipped[0].position + 28));
+    assert_eq!(read_i32_channel(&tdms_file, "Channel1"), vec![1, 2, 3]);
+    assert_eq!(read_i32_channel(&tdms_file, "Channel2"), vec![11, 12, 13]);
+
+    let mut output = Vec::new();
+    let report = repair(Cursor::new(bytes), &mut output).unwrap();
+    assert_eq!(report.skipped_segments.len(), 1);
+    let repaired = TdmsFile::new(Cursor::new(output)).unwrap();
+    assert_eq!(read_i32_channel(&repaired, "Channel1"), vec![1, 2, 3]);
+}
+
+#[test]
+fn skip_segment_that_changes_channel_type() {
+    let mut test_file = TestFile::new();
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(
+        toc_mask,
+        &two_channel_metadata(2),
+        &data_bytes_i32(vec![1, 2, 11, 12]),
+    );
+    // Channel1 changes from I32 to F64, and the segment adds a new channel
+    let metadata = raw_metadata(&[
+        (
+            "/'Group'/'Channel1'",
+            RawIndex::Values {
+                data_type: TdsType::DoubleFloat,
+                number_of_values: 1,
+            },
+            vec![TdmsProperty::new(
+                "unit_string",
+                TdmsValue::String(String::from("A")),
+            )],
+        ),
+        ("/'Group'/'Channel2'", i32_index(1), Vec::new()),
+        ("/'Group'/'Channel3'", i32_index(1), Vec::new()),
+    ]);
+    let mut data_bytes = data_bytes_f64(vec![3.0]);
+    data_bytes.extend(data_bytes_i32(vec![13, 23]));
+    test_file.add_segment(toc_mask, &metadata, &data_bytes);
+    test_file.add_segment(
+        toc_mask,
+        &two_channel_metadata(1),
+        &data_bytes_i32(vec![4, 14]),
+    );
+    let bytes = test_file.into_bytes();
+
+    assert!(TdmsFile::new(Cursor::new(bytes.clone())).is_err());
+
+    let options = ReadOptions::new().error_policy(ErrorPolicy::Skip);
+    let tdms_file = TdmsFile::new_with_options(Cursor::new(bytes.clone()), &options).unwrap();
+
+    let skipped = tdms_file.skipped_segments();
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(read_i32_channel(&tdms_file, "Channel1"), vec![1, 2, 4]);
+    assert_eq!(read_i32_channel(&tdms_file, "Channel2"), vec![11, 12, 14]);
+    // None of the skipped segment's metadata is kept
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Channel1").unwrap();
+    assert_eq!(channel.properties().len(), 1);
+    assert_eq!(
+        channel.properties()[0].value,
+        TdmsValue::String(String::from("V"))
+    );
+    assert!(group.channel("Channel3").is_none());
+
+    let mut output = Vec::new();
+    let report = repair(Cursor::new(bytes), &mut output).unwrap();
+    assert_eq!(report.skipped_segments.len(), 1);
+    let repaired = TdmsFile::new(Cursor::new(output)).unwrap();
+    assert_eq!(read_i32_channel(&repaired, "Channel1"), vec![1, 2, 4]);
+}
+
+#[test]
+fn repair_truncated_file() {
+    let mut test_file = TestFile::new();
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(
+        toc_mask,
+        &two_channel_metadata(2),
+        &data_bytes_i32(vec![1, 2, 11, 12]),
+    );
+    let mut data_bytes = data_bytes_i32(vec![3, 4, 13]);
+    data_bytes.extend(&[0, 0, 0]);
+    test_file.add_incomplete_segment(toc_mask, &two_channel_metadata(2), &data_bytes);
+
+    let mut output = Vec::new();
+    let report = repair(test_file.into_cursor(), &mut output).unwrap();
+
+    assert!(report.skipped_segments.is_empty());
+    assert_eq!(
+        report.truncated_channels,
+        vec![(String::from("Group"), String::from("Channel2"), 1)]
+    );
+    let repaired = TdmsFile::new(Cursor::new(output)).unwrap();
+    assert_eq!(read_i32_channel(&repaired, "Channel1"), vec![1, 2, 3, 4]);
+    assert_eq!(read_i32_channel(&repaired, "Channel2"), vec![11, 12, 13]);
+    let channel_properties = repaired
+        .group("Group")
+        .unwrap()
+        .channel("Channel1")
+        .unwrap()
+        .properties()
+        .to_vec();
+    assert_eq!(
+        channel_properties,
+        vec![TdmsProperty::new(
+            "unit_string",
+            TdmsValue::String(String::from("V"))
+        )]
+    );
+}
+
+#[test]
+fn finalize_incomplete_file_in_place() {
+    let mut test_file = TestFile::new();
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_segment(
+        toc_mask,
+        &two_channel_metadata(2),
+        &data_bytes_i32(vec![1, 2, 11, 12]),
+    );
+    test_file.add_incomplete_segment(
+        toc_mask,
+        &two_channel_metadata(1),
+        &data_bytes_i32(vec![3, 13, 4, 14]),
+    );
+    let mut file = test_file.into_cursor();
+
+    let result = finalize_in_place(&mut file).unwrap();
+
+    match result {
+        FinalizeResult::Finalized { .. } => {}
+        other => panic!("Unexpected result {:?}", other),
+    }
+    let bytes = file.into_inner();
+    assert!(!bytes.windows(8).any(|w| w == [0xFF; 8]));
+    let tdms_file = TdmsFile::new(Cursor::new(bytes.clone())).unwrap();
+    assert_eq!(read_i32_channel(&tdms_file, "Channel1"), vec![1, 2, 3, 4]);
+    assert_eq!(
+        read_i32_channel(&tdms_file, "Channel2"),
+        vec![11, 12, 13, 14]
+    );
+
+    let mut file = Cursor::new(bytes);
+    assert_eq!(
+        finalize_in_place(&mut file).unwrap(),
+        FinalizeResult::AlreadyComplete
+    );
+}
+
+#[test]
+fn finalize_partial_chunk_fails() {
+    let mut test_file = TestFile::new();
+    let toc_mask = TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA;
+    test_file.add_incomplete_segment(
+        toc_mask,
+        &two_channel_metadata(2),
+        &data_bytes_i32(vec![1, 2, 11]),
+    );
+    let mut file = test_file.into_cursor();
+
+    assert!(finalize_in_place(&mut file).is_err());
+}
@@ -0,0 +1,183 @@
+use rstdms::{
+    rewrite, RewriteObject, RewriteOptions, RewriteReport, TdmsFile, TdmsProperty, TdmsValue,
+    TdmsWriter, TdsType, WriteObject,
+};
+use std::io::Cursor;
+
+fn source_file() -> Vec<u8> {
+    let mut writer = TdmsWriter::new(Vec::new());
+    writer
+        .write_segment(&[
+            WriteObject::root().with_properties(&[TdmsProperty::new(
+                "author",
+                TdmsValue::String(String::from("someone")),
+            )]),
+            WriteObject::group("Raw")
+                .with_properties(&[TdmsProperty::new("rate", TdmsValue::Float64(10.0))]),
+            WriteObject::channel("Raw", "ch0")
+                .with_properties(&[TdmsProperty::new(
+                    "unit_string",
+                    TdmsValue::String(String::from("V")),
+                )])
+                .with_data(&[1.5f64, 2.5])
+                .unwrap(),
+            WriteObject::channel("Raw", "junk")
+                .with_properties(&[TdmsProperty::new("junk", TdmsValue::Int32(1))])
+                .with_data(&[1i32, 2])
+                .unwrap(),
+            WriteObject::channel("Raw", "names")
+                .with_string_data(&["a", "b"])
+                .unwrap(),
+        ])
+        .unwrap();
+    writer
+        .write_segment(&[WriteObject::channel("Raw", "ch0")
+            .with_data(&[3.5f64])
+            .unwrap()])
+        .unwrap();
+    writer.into_inner()
+}
+
+fn rewrite_source(options: &RewriteOptions) -> (TdmsFile<Cursor<Vec<u8>>>, RewriteReport) {
+    let mut output = Vec::new();
+    let report = rewrite(Cursor::new(source_file()), &mut output, options).unwrap();
+    (TdmsFile::new(Cursor::new(output)).unwrap(), report)
+}
+
+fn property<'a>(properties: &'a [TdmsProperty], name: &str) -> Option<&'a TdmsValue> {
+    properties
+        .iter()
+        .find(|property| property.name == name)
+        .map(|property| &property.value)
+}
+
+#[test]
+fn rewrite_without_changes() {
+    let (tdms_file, report) = rewrite_source(&RewriteOptions::new());
+
+    assert_eq!(
+        report.copied_channels,
+        vec![
+            (String::from("Raw"), String::from("ch0"), 3),
+            (String::from("Raw"), String::from("junk"), 2),
+            (String::from("Raw"), String::from("names"), 2),
+        ]
+    );
+    let group = tdms_file.group("Raw").unwrap();
+    assert_eq!(
+        property(group.properties(), "rate"),
+        Some(&TdmsValue::Float64(10.0))
+    );
+    let channel = group.channel("ch0").unwrap();
+    let mut values = vec![0.0; 3];
+    channel.read_all_data(&mut values).unwrap();
+    assert_eq!(values, vec![1.5, 2.5, 3.5]);
+    let channel = group.channel("names").unwrap();
+    let mut values = vec![String::new(); 2];
+    channel.read_strings(0, &mut values).unwrap();
+    assert_eq!(values, vec!["a", "b"]);
+}
+
+#[test]
+fn rename_drop_and_edit_properties() {
+    let options = RewriteOptions::new()
+        .drop_channel("Raw", "junk")
+        .rename_channel("Raw", "names", "Labels", "Names")
+        .map_channels(|group, channel| Some((group.to_string(), channel.to_uppercase())))
+        .set_property(
+            RewriteObject::Root,
+            TdmsProperty::new("processed_by", TdmsValue::String(String::from("rewrite"))),
+        )
+        .remove_property(RewriteObject::Root, "author")
+        .set_property(
+            RewriteObject::Channel(String::from("Raw"), String::from("CH0")),
+            TdmsProperty::new("unit_string", TdmsValue::String(String::from("mV"))),
+        );
+
+    let (tdms_file, report) = rewrite_source(&options);
+
+    assert_eq!(
+        report.dropped_channels,
+        vec![(String::from("Raw"), String::from("junk"))]
+    );
+    assert_eq!(
+        tdms_file.properties(),
+        &[TdmsProperty::new(
+            "processed_by",
+            TdmsValue::String(String::from("rewrite"))
+        )]
+    );
+    let group_names: Vec<&str> = tdms_file.groups().map(|group| group.name()).collect();
+    assert_eq!(group_names, vec!["Raw", "Labels"]);
+
+    let raw = tdms_file.group("Raw").unwrap();
+    let channel_names: Vec<&str> = raw.channels().map(|channel| channel.name()).collect();
+    assert_eq!(channel_names, vec!["CH0"]);
+    let channel = raw.channel("CH0").unwrap();
+    assert_eq!(
+        channel.properties(),
+        &[TdmsProperty::new(
+            "unit_string",
+            TdmsValue::String(String::from("mV"))
+        )]
+    );
+    assert_eq!(channel.len(), 3);
+
+    let labels = tdms_file.group("Labels").unwrap();
+    assert!(labels.properties().is_empty());
+    assert_eq!(labels.channel("Names").unwrap().len(), 2);
+}
+
+#[test]
+fn groups_without_channels_are_dropped() {
+    let options = RewriteOptions::new()
+        .map_channels(|_, channel| Some((String::from("Other"), channel.to_string())));
+
+    let (tdms_file, _) = rewrite_source(&options);
+
+    let group_names: Vec<&str> = tdms_file.groups().map(|group| group.name()).collect();
+    assert_eq!(group_names, vec!["Other"]);
+}
+
+#[test]
+fn convert_data_type() {
+    let options = RewriteOptions::new().convert_channel("Raw", "ch0", TdsType::I16);
+
+    let (tdms_file, _) = rewrite_source(&options);
+
+    let group = tdms_file.group("Raw").unwrap();
+    let channel = group.channel("ch0").unwrap();
+    assert_eq!(channel.data_type(), Some(TdsType::I16));
+    let mut values = vec![0i16; 3];
+    channel.read_all_data(&mut values).unwrap();
+    assert_eq!(values, vec![2, 3, 4]);
+}
+
+#[test]
+fn invalid_options_are_errors() {
+    let rewrite_error = |options: RewriteOptions| {
+        rewrite(Cursor::new(source_file()), Vec::new(), &options)
+            .unwrap_err()
+            .to_string()
+    };
+
+    assert_eq!(
+        rewrite_error(RewriteOptions::new().rename_channel("Raw", "junk", "Raw", "ch0")),
+        "More than one channel would be written as Raw / ch0"
+    );
+    assert_eq!(
+        rewrite_error(RewriteOptions::new().convert_channel("Raw", "names", TdsType::I32)),
+        "Cannot convert channel Raw / names from String, only numeric data can be converted"
+    );
+    assert_eq!(
+        rewrite_error(
+            RewriteOptions::new()
+                .drop_channel("Raw", "junk")
+                .remove_property(
+                    RewriteObject::Channel(String::from("Raw"), String::from("junk")),
+                    "junk"
+                )
+        ),
+        "Cannot edit Channel(\"Raw\", \"junk\") as it is not in the rewritten file"
+    );
+}
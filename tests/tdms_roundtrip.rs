@@ -0,0 +1,740 @@
+// Round trip tests that write randomly generated files and check they are read back
+// unchanged. Segments are written with `TdmsWriter`, or encoded here for the layouts
+// it doesn't write, with multiple chunks, interleaved data or big endian byte order.
+
+use proptest::prelude::*;
+use rstdms::{
+    NativeType, TdmsFile, TdmsProperty, TdmsValue, TdmsWriter, TdsType, Timestamp, TocFlag,
+    WriteObject,
+};
+use std::collections::HashMap;
+use std::io::Cursor;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DataType {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+    Timestamp,
+    String,
+}
+
+const DATA_TYPES: [DataType; 12] = [
+    DataType::I8,
+    DataType::I16,
+    DataType::I32,
+    DataType::I64,
+    DataType::U8,
+    DataType::U16,
+    DataType::U32,
+    DataType::U64,
+    DataType::F32,
+    DataType::F64,
+    DataType::Timestamp,
+    DataType::String,
+];
+
+impl DataType {
+    fn tds_type(self) -> TdsType {
+        match self {
+            DataType::I8 => TdsType::I8,
+            DataType::I16 => TdsType::I16,
+            DataType::I32 => TdsType::I32,
+            DataType::I64 => TdsType::I64,
+            DataType::U8 => TdsType::U8,
+            DataType::U16 => TdsType::U16,
+            DataType::U32 => TdsType::U32,
+            DataType::U64 => TdsType::U64,
+            DataType::F32 => TdsType::SingleFloat,
+            DataType::F64 => TdsType::DoubleFloat,
+            DataType::Timestamp => TdsType::TimeStamp,
+            DataType::String => TdsType::String,
+        }
+    }
+}
+
+/// Characters that strings are built from, including multi-byte UTF-8 characters
+const STRING_CHARS: [char; 8] = ['a', 'Z', ' ', '\'', 'é', 'Ω', '測', '🎵'];
+
+/// Values of a channel, generated from the channel's seed and the index of each value
+#[derive(Debug, Clone, PartialEq)]
+enum Values {
+    I8(Vec<i8>),
+    I16(Vec<i16>),
+    I32(Vec<i32>),
+    I64(Vec<i64>),
+    U8(Vec<u8>),
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+    U64(Vec<u64>),
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+    Timestamp(Vec<Timestamp>),
+    String(Vec<String>),
+}
+
+/// Pseudo random bits for a value, so values don't need to be part of the generated
+/// layout and failing cases shrink to a minimal layout
+fn value_bits(seed: u64, index: usize) -> u64 {
+    let mut z = seed.wrapping_add((index as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn string_from_bits(bits: u64) -> String {
+    let length = (bits % 6) as usize;
+    (0..length)
+        .map(|i| STRING_CHARS[((bits >> (8 + 3 * i)) % 8) as usize])
+        .collect()
+}
+
+impl Values {
+    fn generate(data_type: DataType, seed: u64, start: usize, count: usize) -> Values {
+        let bits = (start..start + count).map(|index| value_bits(seed, index));
+        match data_type {
+            DataType::I8 => Values::I8(bits.map(|b| b as i8).collect()),
+            DataType::I16 => Values::I16(bits.map(|b| b as i16).collect()),
+            DataType::I32 => Values::I32(bits.map(|b| b as i32).collect()),
+            DataType::I64 => Values::I64(bits.map(|b| b as i64).collect()),
+            DataType::U8 => Values::U8(bits.map(|b| b as u8).collect()),
+            DataType::U16 => Values::U16(bits.map(|b| b as u16).collect()),
+            DataType::U32 => Values::U32(bits.map(|b| b as u32).collect()),
+            DataType::U64 => Values::U64(bits.collect()),
+            DataType::F32 => Values::F32(bits.map(|b| f32::from_bits(b as u32)).collect()),
+            DataType::F64 => Values::F64(bits.map(f64::from_bits).collect()),
+            DataType::Timestamp => Values::Timestamp(
+                bits.map(|b| Timestamp::new((b >> 1) as i64 - (1 << 62), b.rotate_left(17)))
+                    .collect(),
+            ),
+            DataType::String => Values::String(bits.map(string_from_bits).collect()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Values::I8(values) => values.len(),
+            Values::I16(values) => values.len(),
+            Values::I32(values) => values.len(),
+            Values::I64(values) => values.len(),
+            Values::U8(values) => values.len(),
+            Values::U16(values) => values.len(),
+            Values::U32(values) => values.len(),
+            Values::U64(values) => values.len(),
+            Values::F32(values) => values.len(),
+            Values::F64(values) => values.len(),
+            Values::Timestamp(values) => values.len(),
+            Values::String(values) => values.len(),
+        }
+    }
+
+    fn with_data(&self, object: WriteObject) -> WriteObject {
+        match self {
+            Values::I8(values) => object.with_data(values),
+            Values::I16(values) => object.with_data(values),
+            Values::I32(values) => object.with_data(values),
+            Values::I64(values) => object.with_data(values),
+            Values::U8(values) => object.with_data(values),
+            Values::U16(values) => object.with_data(values),
+            Values::U32(values) => object.with_data(values),
+            Values::U64(values) => object.with_data(values),
+            Values::F32(values) => object.with_data(values),
+            Values::F64(values) => object.with_data(values),
+            Values::Timestamp(values) => object.with_data(values),
+            Values::String(values) => object.with_string_data(values),
+        }
+        .unwrap()
+    }
+
+    /// Encode the value at `index`, which must be of a fixed size type
+    fn encode_value(&self, index: usize, encoder: &mut Encoder) {
+        let big_endian = encoder.big_endian;
+        macro_rules! bytes {
+            ($value:expr) => {
+                if big_endian {
+                    $value.to_be_bytes().to_vec()
+                } else {
+                    $value.to_le_bytes().to_vec()
+                }
+            };
+        }
+        let bytes = match self {
+            Values::I8(values) => bytes!(values[index]),
+            Values::I16(values) => bytes!(values[index]),
+            Values::I32(values) => bytes!(values[index]),
+            Values::I64(values) => bytes!(values[index]),
+            Values::U8(values) => bytes!(values[index]),
+            Values::U16(values) => bytes!(values[index]),
+            Values::U32(values) => bytes!(values[index]),
+            Values::U64(values) => bytes!(values[index]),
+            Values::F32(values) => bytes!(values[index].to_bits()),
+            Values::F64(values) => bytes!(values[index].to_bits()),
+            Values::Timestamp(values) => {
+                encoder.timestamp(&values[index]);
+                return;
+            }
+            Values::String(_) => panic!("Strings are not a fixed size type"),
+        };
+        encoder.bytes.extend(bytes);
+    }
+
+    /// Encode all values, as a string offset table and string data for strings
+    fn encode(&self, encoder: &mut Encoder) {
+        match self {
+            Values::String(values) => {
+                let mut end = 0;
+                for value in values {
+                    end += value.len() as u32;
+                    encoder.u32(end);
+                }
+                for value in values {
+                    encoder.bytes.extend(value.as_bytes());
+                }
+            }
+            _ => {
+                for index in 0..self.len() {
+                    self.encode_value(index, encoder);
+                }
+            }
+        }
+    }
+
+    fn extend(&mut self, other: Values) {
+        match (self, other) {
+            (Values::I8(values), Values::I8(other)) => values.extend(other),
+            (Values::I16(values), Values::I16(other)) => values.extend(other),
+            (Values::I32(values), Values::I32(other)) => values.extend(other),
+            (Values::I64(values), Values::I64(other)) => values.extend(other),
+            (Values::U8(values), Values::U8(other)) => values.extend(other),
+            (Values::U16(values), Values::U16(other)) => values.extend(other),
+            (Values::U32(values), Values::U32(other)) => values.extend(other),
+            (Values::U64(values), Values::U64(other)) => values.extend(other),
+            (Values::F32(values), Values::F32(other)) => values.extend(other),
+            (Values::F64(values), Values::F64(other)) => values.extend(other),
+            (Values::Timestamp(values), Values::Timestamp(other)) => values.extend(other),
+            (Values::String(values), Values::String(other)) => values.extend(other),
+            (values, other) => panic!("Cannot extend {:?} with {:?}", values, other),
+        }
+    }
+
+    /// Read a channel's values as the same type as these values. Floats are compared
+    /// by their bits so that NaN values must also be read exactly.
+    fn read_matches<R: std::io::Read + std::io::Seek>(
+        &self,
+        channel: &rstdms::Channel<R>,
+    ) -> Result<(), TestCaseError> {
+        fn read<T: NativeType, R: std::io::Read + std::io::Seek>(
+            channel: &rstdms::Channel<R>,
+            length: usize,
+        ) -> Vec<T> {
+            let mut values = vec![T::default(); length];
+            channel.read_all_data(&mut values).unwrap();
+            values
+        }
+        let length = self.len();
+        match self {
+            Values::I8(values) => prop_assert_eq!(&read::<i8, _>(channel, length), values),
+            Values::I16(values) => prop_assert_eq!(&read::<i16, _>(channel, length), values),
+            Values::I32(values) => prop_assert_eq!(&read::<i32, _>(channel, length), values),
+            Values::I64(values) => prop_assert_eq!(&read::<i64, _>(channel, length), values),
+            Values::U8(values) => prop_assert_eq!(&read::<u8, _>(channel, length), values),
+            Values::U16(values) => prop_assert_eq!(&read::<u16, _>(channel, length), values),
+            Values::U32(values) => prop_assert_eq!(&read::<u32, _>(channel, length), values),
+            Values::U64(values) => prop_assert_eq!(&read::<u64, _>(channel, length), values),
+            Values::F32(values) => {
+                let read_bits: Vec<u32> = read::<f32, _>(channel, length)
+                    .iter()
+                    .map(|value| value.to_bits())
+                    .collect();
+                let bits: Vec<u32> = values.iter().map(|value| value.to_bits()).collect();
+                prop_assert_eq!(read_bits, bits);
+            }
+            Values::F64(values) => {
+                let read_bits: Vec<u64> = read::<f64, _>(channel, length)
+                    .iter()
+                    .map(|value| value.to_bits())
+                    .collect();
+                let bits: Vec<u64> = values.iter().map(|value| value.to_bits()).collect();
+                prop_assert_eq!(read_bits, bits);
+            }
+            Values::Timestamp(values) => {
+                prop_assert_eq!(&read::<Timestamp, _>(channel, length), values)
+            }
+            Values::String(values) => {
+                let mut read_values = vec![String::new(); length];
+                prop_assert_eq!(channel.read_strings(0, &mut read_values).unwrap(), length);
+                prop_assert_eq!(&read_values, values);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Encodes segments in either byte order, for the layouts `TdmsWriter` doesn't write
+struct Encoder {
+    big_endian: bool,
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    fn new(big_endian: bool) -> Encoder {
+        Encoder {
+            big_endian,
+            bytes: Vec::new(),
+        }
+    }
+
+    fn u32(&mut self, value: u32) {
+        if self.big_endian {
+            self.bytes.extend(value.to_be_bytes());
+        } else {
+            self.bytes.extend(value.to_le_bytes());
+        }
+    }
+
+    fn u64(&mut self, value: u64) {
+        if self.big_endian {
+            self.bytes.extend(value.to_be_bytes());
+        } else {
+            self.bytes.extend(value.to_le_bytes());
+        }
+    }
+
+    fn string(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.bytes.extend(value.as_bytes());
+    }
+
+    fn timestamp(&mut self, value: &Timestamp) {
+        if self.big_endian {
+            self.u64(value.seconds() as u64);
+            self.u64(value.second_fractions());
+        } else {
+            self.u64(value.second_fractions());
+            self.u64(value.seconds() as u64);
+        }
+    }
+
+    fn property(&mut self, property: &TdmsProperty) {
+        self.string(&property.name);
+        self.u32(property.value.data_type().to_u32());
+        match &property.value {
+            TdmsValue::Int32(value) => self.u32(*value as u32),
+            TdmsValue::Float64(value) => self.u64(value.to_bits()),
+            TdmsValue::String(value) => self.string(value),
+            TdmsValue::Boolean(value) => self.bytes.push(*value as u8),
+            TdmsValue::Timestamp(value) => self.timestamp(value),
+            value => panic!("Unexpected property value {:?}", value),
+        }
+    }
+}
+
+/// Properties written with a segment, for the root when the channel index is None
+type PropertyUpdate = (Option<usize>, TdmsProperty);
+
+#[derive(Debug, Clone)]
+struct ChannelSpec {
+    group: usize,
+    name: String,
+    data_type: DataType,
+    seed: u64,
+}
+
+#[derive(Debug, Clone)]
+enum Encoding {
+    Writer,
+    Raw {
+        interleaved: bool,
+        big_endian: bool,
+        chunks: usize,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct SegmentSpec {
+    encoding: Encoding,
+    /// Number of values of each channel in each chunk of the segment
+    values_per_chunk: Vec<usize>,
+    properties: Vec<PropertyUpdate>,
+}
+
+#[derive(Debug, Clone)]
+struct FileSpec {
+    groups: Vec<String>,
+    channels: Vec<ChannelSpec>,
+    segments: Vec<SegmentSpec>,
+}
+
+impl FileSpec {
+    fn channel_path<'a>(&'a self, channel: &'a ChannelSpec) -> (&'a str, &'a str) {
+        (&self.groups[channel.group], &channel.name)
+    }
+
+    /// Channels with values in a segment and the number of values in each chunk.
+    /// Interleaved data and data with multiple chunks don't include string channels, as
+    /// string data can't be interleaved and would need the same size in each chunk, and
+    /// all channels of interleaved data have the same number of values.
+    fn segment_channels(&self, segment: &SegmentSpec) -> Vec<(usize, usize)> {
+        let (interleaved, chunks) = match segment.encoding {
+            Encoding::Writer => (false, 1),
+            Encoding::Raw {
+                interleaved,
+                chunks,
+                ..
+            } => (interleaved, chunks),
+        };
+        let shared_count = segment.values_per_chunk.first().copied().unwrap_or(0);
+        self.channels
+            .iter()
+            .enumerate()
+            .filter(|(_, channel)| {
+                channel.data_type != DataType::String || (!interleaved && chunks == 1)
+            })
+            .map(|(index, _)| {
+                if interleaved {
+                    (index, shared_count)
+                } else {
+                    (index, segment.values_per_chunk[index])
+                }
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+}
+
+fn property_strategy() -> impl Strategy<Value = TdmsProperty> {
+    let value = prop_oneof![
+        any::<i32>().prop_map(TdmsValue::Int32),
+        prop::num::f64::NORMAL.prop_map(TdmsValue::Float64),
+        "\\PC{0,6}".prop_map(TdmsValue::String),
+        any::<bool>().prop_map(TdmsValue::Boolean),
+        (any::<i64>(), any::<u64>()).prop_map(|(seconds, fractions)| TdmsValue::Timestamp(
+            Timestamp::new(seconds, fractions)
+        )),
+    ];
+    ("\\PC{1,6}", value).prop_map(|(name, value)| TdmsProperty::new(&name, value))
+}
+
+const MAX_CHANNELS: usize = 20;
+
+/// Generates file layouts without depending on the number of channels generated, so
+/// that failing cases shrink the channels and segments independently. Channel indexes
+/// and per channel value counts are reduced to the generated channels afterwards.
+fn file_strategy() -> impl Strategy<Value = FileSpec> {
+    let groups = prop::collection::vec("\\PC{1,6}", 1..=3);
+    let channel = (
+        any::<prop::sample::Index>(),
+        "\\PC{0,6}",
+        prop::sample::select(DATA_TYPES.to_vec()),
+        any::<u64>(),
+    );
+    let encoding = prop_oneof![
+        Just(Encoding::Writer),
+        (any::<bool>(), any::<bool>(), 1..=3usize).prop_map(|(interleaved, big_endian, chunks)| {
+            Encoding::Raw {
+                interleaved,
+                big_endian,
+                chunks,
+            }
+        }),
+    ];
+    let properties = prop::collection::vec(
+        (
+            prop::option::of(any::<prop::sample::Index>()),
+            property_strategy(),
+        ),
+        0..=3,
+    );
+    let segment = (
+        encoding,
+        prop::collection::vec(0..=20usize, MAX_CHANNELS),
+        properties,
+    );
+    (
+        groups,
+        prop::collection::vec(channel, 1..=MAX_CHANNELS),
+        prop::collection::vec(segment, 1..=6),
+    )
+        .prop_map(|(groups, channels, segments)| {
+            // Make names unique so each value is written to the object it was generated for
+            let groups: Vec<String> = groups
+                .into_iter()
+                .enumerate()
+                .map(|(index, name)| format!("{}_{}", name, index))
+                .collect();
+            let channels: Vec<ChannelSpec> = channels
+                .into_iter()
+                .enumerate()
+                .map(|(index, (group, name, data_type, seed))| ChannelSpec {
+                    group: group.index(groups.len()),
+                    name: format!("{}_{}", name, index),
+                    data_type,
+                    seed,
+                })
+                .collect();
+            let channel_count = channels.len();
+            let segments = segments
+                .into_iter()
+                .map(|(encoding, mut values_per_chunk, properties)| {
+                    values_per_chunk.truncate(channel_count);
+                    SegmentSpec {
+                        encoding,
+                        values_per_chunk,
+                        properties: properties
+                            .into_iter()
+                            .map(|(channel, property)| {
+                                (channel.map(|index| index.index(channel_count)), property)
+                            })
+                            .collect(),
+                    }
+                })
+                .collect();
+            FileSpec {
+                groups,
+                channels,
+                segments,
+            }
+        })
+}
+
+/// Write a file from the layout, returning the file bytes and the expected values of
+/// each channel
+fn write_file(spec: &FileSpec) -> (Vec<u8>, Vec<Values>) {
+    let mut expected: Vec<Values> = spec
+        .channels
+        .iter()
+        .map(|channel| Values::generate(channel.data_type, channel.seed, 0, 0))
+        .collect();
+
+    // Every channel is written in a first segment without data, so channels without
+    // any values or properties are still in the file
+    let mut writer = TdmsWriter::new(Vec::new());
+    let objects: Vec<WriteObject> = spec
+        .channels
+        .iter()
+        .map(|channel| {
+            let (group, name) = spec.channel_path(channel);
+            WriteObject::channel(group, name)
+        })
+        .collect();
+    writer.write_segment(&objects).unwrap();
+    let mut bytes = writer.into_inner();
+
+    for segment in spec.segments.iter() {
+        let chunk_count = match segment.encoding {
+            Encoding::Writer => 1,
+            Encoding::Raw { chunks, .. } => chunks,
+        };
+        // Values for each channel with data, for each chunk
+        let mut channel_values = Vec::new();
+        for (index, count) in spec.segment_channels(segment) {
+            let channel = &spec.channels[index];
+            let chunks: Vec<Values> = (0..chunk_count)
+                .map(|chunk| {
+                    let start = expected[index].len() + chunk * count;
+                    Values::generate(channel.data_type, channel.seed, start, count)
+                })
+                .collect();
+            for values in chunks.iter() {
+                expected[index].extend(values.clone());
+            }
+            channel_values.push((index, chunks));
+        }
+        match segment.encoding {
+            Encoding::Writer => write_with_writer(spec, segment, &channel_values, &mut bytes),
+            Encoding::Raw {
+                interleaved,
+                big_endian,
+                ..
+            } => encode_segment(
+                spec,
+                segment,
+                &channel_values,
+                interleaved,
+                big_endian,
+                &mut bytes,
+            ),
+        }
+    }
+    (bytes, expected)
+}
+
+fn write_with_writer(
+    spec: &FileSpec,
+    segment: &SegmentSpec,
+    channel_values: &[(usize, Vec<Values>)],
+    bytes: &mut Vec<u8>,
+) {
+    let channel_properties = |index: Option<usize>| -> Vec<TdmsProperty> {
+        segment
+            .properties
+            .iter()
+            .filter(|(channel, _)| *channel == index)
+            .map(|(_, property)| property.clone())
+            .collect()
+    };
+    let mut objects = vec![WriteObject::root().with_properties(&channel_properties(None))];
+    for (index, channel) in spec.channels.iter().enumerate() {
+        let (group, name) = spec.channel_path(channel);
+        let mut object =
+            WriteObject::channel(group, name).with_properties(&channel_properties(Some(index)));
+        if let Some((_, chunks)) = channel_values.iter().find(|(i, _)| *i == index) {
+            object = chunks[0].with_data(object);
+        }
+        objects.push(object);
+    }
+    let mut writer = TdmsWriter::new(Vec::new());
+    writer.write_segment(&objects).unwrap();
+    bytes.extend(writer.into_inner());
+}
+
+fn encode_segment(
+    spec: &FileSpec,
+    segment: &SegmentSpec,
+    channel_values: &[(usize, Vec<Values>)],
+    interleaved: bool,
+    big_endian: bool,
+    bytes: &mut Vec<u8>,
+) {
+    // Objects in the segment are the root and every channel, with data or properties
+    let mut metadata = Encoder::new(big_endian);
+    metadata.u32(spec.channels.len() as u32 + 1);
+    let mut write_object = |path: &str, index: Option<usize>| {
+        metadata.string(path);
+        match index.and_then(|index| channel_values.iter().find(|(i, _)| *i == index)) {
+            Some((index, chunks)) => {
+                let data_type = spec.channels[*index].data_type;
+                let values = &chunks[0];
+                if data_type == DataType::String {
+                    let mut data = Encoder::new(big_endian);
+                    values.encode(&mut data);
+                    metadata.u32(28);
+                    metadata.u32(data_type.tds_type().to_u32());
+                    metadata.u32(1);
+                    metadata.u64(values.len() as u64);
+                    metadata.u64(data.bytes.len() as u64);
+                } else {
+                    metadata.u32(20);
+                    metadata.u32(data_type.tds_type().to_u32());
+                    metadata.u32(1);
+                    metadata.u64(values.len() as u64);
+                }
+            }
+            None => metadata.u32(0xFFFFFFFF),
+        }
+        let properties: Vec<&TdmsProperty> = segment
+            .properties
+            .iter()
+            .filter(|(channel, _)| *channel == index)
+            .map(|(_, property)| property)
+            .collect();
+        metadata.u32(properties.len() as u32);
+        for property in properties {
+            metadata.property(property);
+        }
+    };
+    write_object("/", None);
+    for (index, channel) in spec.channels.iter().enumerate() {
+        let (group, name) = spec.channel_path(channel);
+        let path = format!(
+            "/'{}'/'{}'",
+            group.replace('\'', "''"),
+            name.replace('\'', "''")
+        );
+        write_object(&path, Some(index));
+    }
+
+    let mut data = Encoder::new(big_endian);
+    let chunk_count = channel_values
+        .first()
+        .map(|(_, chunks)| chunks.len())
+        .unwrap_or(0);
+    for chunk in 0..chunk_count {
+        if interleaved {
+            let rows = channel_values[0].1[chunk].len();
+            for row in 0..rows {
+                for (_, chunks) in channel_values.iter() {
+                    chunks[chunk].encode_value(row, &mut data);
+                }
+            }
+        } else {
+            for (_, chunks) in channel_values.iter() {
+                chunks[chunk].encode(&mut data);
+            }
+        }
+    }
+
+    let mut toc_mask = TocFlag::MetaData as u32 | TocFlag::NewObjList as u32;
+    if !data.bytes.is_empty() {
+        toc_mask |= TocFlag::RawData as u32;
+    }
+    if interleaved {
+        toc_mask |= TocFlag::InterleavedData as u32;
+    }
+    if big_endian {
+        toc_mask |= TocFlag::BigEndian as u32;
+    }
+    let mut lead_in = Encoder::new(big_endian);
+    lead_in.bytes.extend(b"TDSm");
+    lead_in.bytes.extend(toc_mask.to_le_bytes());
+    lead_in.u32(4713);
+    lead_in.u64((metadata.bytes.len() + data.bytes.len()) as u64);
+    lead_in.u64(metadata.bytes.len() as u64);
+    bytes.extend(lead_in.bytes);
+    bytes.extend(metadata.bytes);
+    bytes.extend(data.bytes);
+}
+
+/// The latest value of each property of an object, in the order properties were first
+/// written
+fn expected_properties(spec: &FileSpec, index: Option<usize>) -> Vec<TdmsProperty> {
+    let mut properties: Vec<TdmsProperty> = Vec::new();
+    let mut positions = HashMap::new();
+    for segment in spec.segments.iter() {
+        for (channel, property) in segment.properties.iter() {
+            if *channel != index {
+                continue;
+            }
+            match positions.get(&property.name.to_string()) {
+                Some(&position) => properties[position] = property.clone(),
+                None => {
+                    positions.insert(property.name.to_string(), properties.len());
+                    properties.push(property.clone());
+                }
+            }
+        }
+    }
+    properties
+}
+
+proptest! {
+    #[test]
+    fn written_files_are_read_back(spec in file_strategy()) {
+        let (bytes, expected) = write_file(&spec);
+
+        let tdms_file = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        prop_assert_eq!(tdms_file.properties(), &expected_properties(&spec, None)[..]);
+        for (index, channel_spec) in spec.channels.iter().enumerate() {
+            let (group_name, channel_name) = spec.channel_path(channel_spec);
+            let group = tdms_file.group(group_name).unwrap();
+            let channel = group.channel(channel_name).unwrap();
+            prop_assert_eq!(channel.len(), expected[index].len() as u64);
+            if expected[index].len() > 0 {
+                prop_assert_eq!(channel.data_type(), Some(channel_spec.data_type.tds_type()));
+            }
+            expected[index].read_matches(&channel)?;
+            prop_assert_eq!(
+                channel.properties(),
+                &expected_properties(&spec, Some(index))[..]
+            );
+        }
+    }
+}
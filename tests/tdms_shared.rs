@@ -0,0 +1,136 @@
+// Tests of reading data through shared references to a TdmsFile, including from
+// multiple threads with the `sync` feature
+
+use rstdms::{TdmsFile, TdmsReadError, TdmsWriter, WriteObject};
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const CHANNEL_COUNT: usize = 4;
+const SEGMENT_COUNT: i32 = 10;
+const SEGMENT_VALUES: i32 = 100;
+
+/// Bytes of a file with channels "0" to "3" written in many segments, where each
+/// channel's values are its index times 1000 plus the value index
+fn channels_file() -> Vec<u8> {
+    let mut writer = TdmsWriter::new(Vec::new());
+    for segment in 0..SEGMENT_COUNT {
+        let objects: Vec<WriteObject> = (0..CHANNEL_COUNT)
+            .map(|channel| {
+                let values: Vec<i32> = (0..SEGMENT_VALUES)
+                    .map(|i| expected_value(channel, segment * SEGMENT_VALUES + i))
+                    .collect();
+                WriteObject::channel("Group", &channel.to_string())
+                    .with_data(&values)
+                    .unwrap()
+            })
+            .collect();
+        writer.write_segment(&objects).unwrap();
+    }
+    writer.into_inner()
+}
+
+fn expected_value(channel: usize, index: i32) -> i32 {
+    channel as i32 * 1000 + index
+}
+
+/// Read all values of a channel in small reads, so reads of different channels interleave
+fn read_channel<R: Read + Seek>(tdms_file: &TdmsFile<R>, channel: usize) -> Vec<i32> {
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel(&channel.to_string()).unwrap();
+    let mut values = vec![0; channel.len() as usize];
+    for (index, chunk) in values.chunks_mut(37).enumerate() {
+        channel.read_data((index * 37) as u64, chunk).unwrap();
+    }
+    values
+}
+
+#[test]
+fn interleave_reads_of_channels() {
+    let tdms_file = TdmsFile::new(Cursor::new(channels_file())).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let first = group.channel("0").unwrap();
+    let second = group.channel("1").unwrap();
+
+    let mut first_values = vec![0i32; 10];
+    let mut second_values = vec![0i32; 10];
+    for offset in (0..300).step_by(90) {
+        first.read_data(offset, &mut first_values).unwrap();
+        second.read_data(offset, &mut second_values).unwrap();
+        assert_eq!(first_values[0], expected_value(0, offset as i32));
+        assert_eq!(second_values[9], expected_value(1, offset as i32 + 9));
+    }
+}
+
+/// A reader that fails to read while its flag is set
+struct FailingReader {
+    inner: Cursor<Vec<u8>>,
+    fail: Arc<AtomicBool>,
+}
+
+impl Read for FailingReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.fail.load(Ordering::SeqCst) {
+            return Err(std::io::Error::other("Read failed"));
+        }
+        self.inner.read(buf)
+    }
+}
+
+impl Seek for FailingReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+fn failed_read_releases_reader() {
+    let fail = Arc::new(AtomicBool::new(false));
+    let reader = FailingReader {
+        inner: Cursor::new(channels_file()),
+        fail: fail.clone(),
+    };
+    let tdms_file = TdmsFile::new(reader).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("2").unwrap();
+    let mut values = vec![0i32; 5];
+
+    fail.store(true, Ordering::SeqCst);
+    let error = channel.read_data(150, &mut values).unwrap_err();
+    assert!(matches!(error, TdmsReadError::IoError(_)), "{:?}", error);
+
+    fail.store(false, Ordering::SeqCst);
+    assert_eq!(channel.read_data(150, &mut values).unwrap(), 5);
+    assert_eq!(values[0], expected_value(2, 150));
+    assert_eq!(read_channel(&tdms_file, 3)[999], expected_value(3, 999));
+}
+
+#[cfg(feature = "sync")]
+#[test]
+fn files_can_be_shared_between_threads() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<TdmsFile<std::fs::File>>();
+    assert_send_sync::<TdmsFile<Cursor<Vec<u8>>>>();
+}
+
+#[cfg(feature = "sync")]
+#[test]
+fn concurrent_reads_through_shared_reference() {
+    let tdms_file = TdmsFile::new(Cursor::new(channels_file())).unwrap();
+
+    std::thread::scope(|scope| {
+        let readers: Vec<_> = (0..CHANNEL_COUNT)
+            .map(|channel| {
+                let tdms_file = &tdms_file;
+                scope.spawn(move || (channel, read_channel(tdms_file, channel)))
+            })
+            .collect();
+        for reader in readers {
+            let (channel, values) = reader.join().unwrap();
+            let expected: Vec<i32> = (0..SEGMENT_COUNT * SEGMENT_VALUES)
+                .map(|index| expected_value(channel, index))
+                .collect();
+            assert_eq!(values, expected, "channel {}", channel);
+        }
+    });
+}
@@ -0,0 +1,214 @@
+mod common;
+
+use common::*;
+use rstdms::{
+    SegmentFlagUsage, TdmsFile, TdmsProperty, TdmsValue, TdmsWriter, TdsType, Timestamp,
+    WriteObject,
+};
+use std::io::Cursor;
+
+fn write_file(segments: Vec<Vec<WriteObject>>) -> TdmsFile<Cursor<Vec<u8>>> {
+    let mut writer = TdmsWriter::new(Vec::new());
+    for objects in segments {
+        writer.write_segment(&objects).unwrap();
+    }
+    TdmsFile::new(Cursor::new(writer.into_inner())).unwrap()
+}
+
+#[test]
+fn summarize_file() {
+    let early_timing = vec![
+        TdmsProperty::new(
+            "wf_start_time",
+            TdmsValue::Timestamp(Timestamp::new(100, 0)),
+        ),
+        TdmsProperty::new("wf_increment", TdmsValue::Float64(0.5)),
+    ];
+    let late_timing = vec![
+        TdmsProperty::new(
+            "wf_start_time",
+            TdmsValue::Timestamp(Timestamp::new(101, 0)),
+        ),
+        TdmsProperty::new("wf_increment", TdmsValue::Float64(2.0)),
+    ];
+    let tdms_file = write_file(vec![
+        vec![
+            WriteObject::channel("Group", "Early")
+                .with_properties(&early_timing)
+                .with_data(&[1.0f64, 2.0, 3.0])
+                .unwrap(),
+            WriteObject::channel("Group", "Late")
+                .with_properties(&late_timing)
+                .with_data(&[1i32, 2])
+                .unwrap(),
+        ],
+        vec![
+            WriteObject::channel("Group", "Early")
+                .with_data(&[4.0f64])
+                .unwrap(),
+            WriteObject::channel("Other", "Untimed")
+                .with_data(&[1u8, 2, 3, 4, 5])
+                .unwrap(),
+        ],
+    ]);
+
+    let summary = tdms_file.summary().unwrap();
+
+    assert_eq!(summary.segment_count, 2);
+    assert_eq!(summary.group_count, 2);
+    assert_eq!(summary.channel_count, 3);
+    assert_eq!(summary.total_values, 11);
+    assert_eq!(
+        summary.length,
+        tdms_file.segments().unwrap()[1].next_segment_position
+    );
+    let (start, end) = summary.time_span.unwrap();
+    assert_eq!(Some(start), Timestamp::new(100, 0).to_datetime());
+    assert_eq!(Some(end), Timestamp::new(103, 0).to_datetime());
+    assert!(summary.warnings().is_empty());
+}
+
+#[test]
+fn summarize_file_without_timing() {
+    let tdms_file = write_file(vec![vec![WriteObject::channel("Group", "Channel")
+        .with_data(&[1i32, 2, 3])
+        .unwrap()]]);
+
+    let summary = tdms_file.summary().unwrap();
+
+    assert_eq!(summary.total_values, 3);
+    assert!(summary.time_span.is_none());
+}
+
+/// A file where channels a and b have contiguous, interleaved and big endian segments,
+/// channel c only has contiguous data and channel d only has interleaved data
+fn mixed_layout_file() -> TdmsFile<Cursor<Vec<u8>>> {
+    let i32_type = TdsType::I32.to_u32();
+    let mut test_file = TestFile::new();
+    test_file.add_segment(
+        TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA,
+        &metadata(vec![
+            object_metadata("/'Group'/'a'", &raw_data_index(i32_type, 2), vec![]),
+            object_metadata("/'Group'/'b'", &raw_data_index(i32_type, 2), vec![]),
+        ]),
+        &data_bytes_i32(vec![1, 2, 3, 4]),
+    );
+    test_file.add_segment(
+        TOC_RAW_DATA | TOC_INTERLEAVED_DATA,
+        &[],
+        &data_bytes_i32(vec![5, 7, 6, 8]),
+    );
+    let big_endian_data: Vec<u8> = [9i32, 10, 11, 12]
+        .iter()
+        .flat_map(|value| value.to_be_bytes())
+        .collect();
+    test_file.add_big_endian_segment(TOC_RAW_DATA, &[], &big_endian_data);
+    test_file.add_segment(
+        TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA,
+        &metadata(vec![object_metadata(
+            "/'Group'/'c'",
+            &raw_data_index(i32_type, 2),
+            vec![],
+        )]),
+        &data_bytes_i32(vec![13, 14]),
+    );
+    test_file.add_segment(
+        TOC_METADATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA | TOC_INTERLEAVED_DATA,
+        &metadata(vec![object_metadata(
+            "/'Group'/'d'",
+            &raw_data_index(i32_type, 2),
+            vec![],
+        )]),
+        &data_bytes_i32(vec![15, 16]),
+    );
+    TdmsFile::new(test_file.into_cursor()).unwrap()
+}
+
+#[test]
+fn report_segment_layouts() {
+    let tdms_file = mixed_layout_file();
+
+    let segments = tdms_file.segments().unwrap();
+    let flags: Vec<(bool, bool)> = segments
+        .iter()
+        .map(|segment| (segment.interleaved(), segment.big_endian()))
+        .collect();
+    assert_eq!(
+        flags,
+        vec![
+            (false, false),
+            (true, false),
+            (false, true),
+            (false, false),
+            (true, false)
+        ]
+    );
+    assert_eq!(segments[2].toc_flag_names(), vec!["RawData", "BigEndian"]);
+}
+
+#[test]
+fn report_channel_layouts() {
+    let tdms_file = mixed_layout_file();
+    let group = tdms_file.group("Group").unwrap();
+
+    let layouts: Vec<(String, SegmentFlagUsage, SegmentFlagUsage)> = group
+        .channel_infos()
+        .into_iter()
+        .map(|info| (info.name, info.interleaved, info.big_endian))
+        .collect();
+    assert_eq!(
+        layouts,
+        vec![
+            (
+                String::from("a"),
+                SegmentFlagUsage::Mixed,
+                SegmentFlagUsage::Mixed
+            ),
+            (
+                String::from("b"),
+                SegmentFlagUsage::Mixed,
+                SegmentFlagUsage::Mixed
+            ),
+            (
+                String::from("c"),
+                SegmentFlagUsage::None,
+                SegmentFlagUsage::None
+            ),
+            (
+                String::from("d"),
+                SegmentFlagUsage::All,
+                SegmentFlagUsage::None
+            ),
+        ]
+    );
+    // The reported layouts match how the data is read
+    let channel = group.channel("a").unwrap();
+    let mut values = vec![0i32; 6];
+    channel.read_data(0, &mut values).unwrap();
+    assert_eq!(values, vec![1, 2, 5, 6, 9, 10]);
+    let channel = group.channel("b").unwrap();
+    channel.read_data(0, &mut values).unwrap();
+    assert_eq!(values, vec![3, 4, 7, 8, 11, 12]);
+}
+
+#[test]
+fn summarize_segment_layouts() {
+    let tdms_file = mixed_layout_file();
+
+    let summary = tdms_file.summary().unwrap();
+
+    assert_eq!(summary.interleaved_segments, 2);
+    assert_eq!(summary.big_endian_segments, 1);
+    assert_eq!(
+        summary.mixed_layout_channels,
+        vec!["/'Group'/'a'", "/'Group'/'b'"]
+    );
+    assert_eq!(
+        summary.mixed_byte_order_channels,
+        vec!["/'Group'/'a'", "/'Group'/'b'"]
+    );
+    assert!(summary.warnings().is_empty());
+    let text = summary.to_string();
+    assert!(text.contains("Interleaved segments: 2"));
+    assert!(text.contains("Mixed big and little endian data: /'Group'/'b'"));
+}
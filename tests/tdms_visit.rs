@@ -0,0 +1,246 @@
+use rstdms::{
+    channel_statistics, channels_statistics, ChannelStatistics, ChunkConfig, DataVisitor,
+    ReadOptions, StatisticsOptions, TdmsFile, TdmsReadError, TdmsWriter, TdsType, ValuesRef,
+    WriteObject,
+};
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+fn write_bytes(segments: Vec<Vec<WriteObject>>) -> Vec<u8> {
+    let mut writer = TdmsWriter::new(Vec::new());
+    for objects in segments {
+        writer.write_segment(&objects).unwrap();
+    }
+    writer.into_inner()
+}
+
+/// A file with channels of different types written across two segments
+fn mixed_file_bytes() -> Vec<u8> {
+    write_bytes(vec![
+        vec![
+            WriteObject::channel("Group", "Ints")
+                .with_data(&[1i32, 2, 3])
+                .unwrap(),
+            WriteObject::channel("Group", "Strings")
+                .with_string_data(&["a", "b"])
+                .unwrap(),
+            WriteObject::channel("Group", "Empty"),
+        ],
+        vec![
+            WriteObject::channel("Group", "Ints")
+                .with_data(&[4i32, 5])
+                .unwrap(),
+            WriteObject::channel("Other", "Floats")
+                .with_data(&[0.5f64, f64::NAN])
+                .unwrap(),
+        ],
+    ])
+}
+
+/// Records every chunk it is passed, formatted as "path data_type: values"
+#[derive(Default)]
+struct RecordingVisitor {
+    skip_path: Option<&'static str>,
+    chunks: Vec<String>,
+    break_after: Option<usize>,
+}
+
+impl DataVisitor for RecordingVisitor {
+    fn visit_channel(&mut self, path: &str, _data_type: TdsType) -> bool {
+        self.skip_path != Some(path)
+    }
+
+    fn chunk(&mut self, path: &str, data_type: TdsType, values: ValuesRef<'_>) -> ControlFlow<()> {
+        let values = match values {
+            ValuesRef::I32(values) => format!("{:?}", values),
+            ValuesRef::F64(values) => format!("{:?}", values),
+            ValuesRef::String(values) => format!("{:?}", values),
+            values => format!("{:?}", values),
+        };
+        self.chunks
+            .push(format!("{} {:?}: {}", path, data_type, values));
+        if self.break_after == Some(self.chunks.len()) {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+#[test]
+fn visit_chunks_in_file_order() {
+    let tdms_file = TdmsFile::new(Cursor::new(mixed_file_bytes())).unwrap();
+    let mut visitor = RecordingVisitor::default();
+
+    tdms_file.visit_data(&mut visitor).unwrap();
+
+    assert_eq!(
+        visitor.chunks,
+        vec![
+            "/'Group'/'Ints' I32: [1, 2, 3]",
+            "/'Group'/'Strings' String: [\"a\", \"b\"]",
+            "/'Group'/'Ints' I32: [4, 5]",
+            "/'Other'/'Floats' DoubleFloat: [0.5, NaN]",
+        ]
+    );
+}
+
+#[test]
+fn chunks_are_bounded_by_chunk_config() {
+    let options = ReadOptions::new().chunk_config(ChunkConfig::new(8));
+    let tdms_file = TdmsFile::new_with_options(Cursor::new(mixed_file_bytes()), &options).unwrap();
+    let mut visitor = RecordingVisitor {
+        skip_path: Some("/'Group'/'Strings'"),
+        ..RecordingVisitor::default()
+    };
+
+    tdms_file.visit_data(&mut visitor).unwrap();
+
+    assert_eq!(
+        visitor.chunks,
+        vec![
+            "/'Group'/'Ints' I32: [1, 2]",
+            "/'Group'/'Ints' I32: [3]",
+            "/'Group'/'Ints' I32: [4, 5]",
+            "/'Other'/'Floats' DoubleFloat: [0.5]",
+            "/'Other'/'Floats' DoubleFloat: [NaN]",
+        ]
+    );
+}
+
+/// A reader that counts the bytes read from it
+struct CountingReader {
+    inner: Cursor<Vec<u8>>,
+    bytes_read: Arc<AtomicU64>,
+}
+
+impl Read for CountingReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let bytes_read = self.inner.read(buf)?;
+        self.bytes_read
+            .fetch_add(bytes_read as u64, Ordering::SeqCst);
+        Ok(bytes_read)
+    }
+}
+
+impl Seek for CountingReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+fn break_stops_reading() {
+    let segments = (0..20)
+        .map(|segment| {
+            let values: Vec<f64> = (0..1000).map(|i| (segment * 1000 + i) as f64).collect();
+            vec![WriteObject::channel("Group", "Channel")
+                .with_data(&values)
+                .unwrap()]
+        })
+        .collect();
+    let bytes = write_bytes(segments);
+    let file_length = bytes.len() as u64;
+    let bytes_read = Arc::new(AtomicU64::new(0));
+    let reader = CountingReader {
+        inner: Cursor::new(bytes),
+        bytes_read: bytes_read.clone(),
+    };
+    let tdms_file = TdmsFile::new(reader).unwrap();
+    bytes_read.store(0, Ordering::SeqCst);
+    let mut visitor = RecordingVisitor {
+        break_after: Some(1),
+        ..RecordingVisitor::default()
+    };
+
+    tdms_file.visit_data(&mut visitor).unwrap();
+
+    assert_eq!(visitor.chunks.len(), 1);
+    assert!(
+        bytes_read.load(Ordering::SeqCst) < file_length / 4,
+        "Read {} of {} bytes",
+        bytes_read.load(Ordering::SeqCst),
+        file_length
+    );
+}
+
+#[test]
+fn statistics_of_all_channels_in_one_pass() {
+    let tdms_file = TdmsFile::new(Cursor::new(mixed_file_bytes())).unwrap();
+    let channels = tdms_file.find_channels_by(|_| true);
+    let options = StatisticsOptions::default();
+
+    let all_statistics = channels_statistics(&channels, &options).unwrap();
+
+    assert_eq!(all_statistics.len(), 4);
+    for (channel, statistics) in channels.iter().zip(all_statistics.iter()) {
+        let expected = channel_statistics(channel, &options).unwrap();
+        match (statistics, &expected) {
+            (ChannelStatistics::Numeric(statistics), ChannelStatistics::Numeric(expected)) => {
+                assert_eq!(statistics, expected, "{}", channel.path())
+            }
+            (ChannelStatistics::String(statistics), ChannelStatistics::String(expected)) => {
+                assert_eq!(statistics.count(), expected.count());
+                assert_eq!(statistics.distinct_count(), expected.distinct_count());
+            }
+            (ChannelStatistics::NoData, ChannelStatistics::NoData) => {}
+            _ => panic!(
+                "Statistics of {} differ: {:?} and {:?}",
+                channel.path(),
+                statistics,
+                expected
+            ),
+        }
+    }
+    match &all_statistics[0] {
+        ChannelStatistics::Numeric(statistics) => {
+            assert_eq!(statistics.count(), 5);
+            assert_eq!(statistics.mean(), Some(3.0));
+        }
+        statistics => panic!("Unexpected statistics {:?}", statistics),
+    }
+}
+
+#[test]
+fn statistics_of_channels_from_different_files() {
+    let first_file = TdmsFile::new(Cursor::new(mixed_file_bytes())).unwrap();
+    let second_file = TdmsFile::new(Cursor::new(mixed_file_bytes())).unwrap();
+    let mut channels = first_file.find_channels_by(|_| true);
+    channels.extend(second_file.find_channels_by(|_| true));
+
+    let error = channels_statistics(&channels, &StatisticsOptions::default()).unwrap_err();
+
+    assert!(
+        matches!(error, TdmsReadError::InvalidArgument(_)),
+        "{:?}",
+        error
+    );
+}
+
+#[test]
+fn overview_of_numeric_channels() {
+    let options = ReadOptions::new().overview(true);
+    let tdms_file = TdmsFile::new_with_options(Cursor::new(mixed_file_bytes()), &options).unwrap();
+    let overview = |group: &str, channel: &str| {
+        let group = tdms_file.group(group).unwrap();
+        let channel = group.channel(channel).unwrap();
+        channel.overview()
+    };
+
+    assert_eq!(
+        overview("Group", "Ints"),
+        Some(vec![(1.0, 3.0), (4.0, 5.0)])
+    );
+    // NaN values are ignored
+    assert_eq!(overview("Other", "Floats"), Some(vec![(0.5, 0.5)]));
+    assert_eq!(overview("Group", "Strings"), None);
+    assert_eq!(overview("Group", "Empty"), None);
+
+    let tdms_file = TdmsFile::new(Cursor::new(mixed_file_bytes())).unwrap();
+    let group = tdms_file.group("Group").unwrap();
+    let channel = group.channel("Ints").unwrap();
+    assert_eq!(channel.overview(), None);
+    assert_eq!(channel.overview_value_ranges(), None);
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "xlsx")]
+
+use rstdms::{
+    TdmsFile, TdmsProperty, TdmsReadError, TdmsValue, TdmsWriter, WriteObject, XlsxExportOptions,
+    XlsxTimeColumn, XLSX_MAX_DATA_ROWS,
+};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+fn write_file(objects: Vec<WriteObject>) -> TdmsFile<Cursor<Vec<u8>>> {
+    let mut writer = TdmsWriter::new(Vec::new());
+    writer.write_segment(&objects).unwrap();
+    TdmsFile::new(Cursor::new(writer.into_inner())).unwrap()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("rstdms_xlsx_{}_{}.xlsx", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Number of worksheets in a saved workbook, found from the entry names in the zip
+/// archive, which are stored uncompressed
+fn worksheet_count(path: &PathBuf) -> usize {
+    let bytes = std::fs::read(path).unwrap();
+    assert!(bytes.starts_with(b"PK"));
+    (1..)
+        .take_while(|index| {
+            let name = format!("xl/worksheets/sheet{}.xml", index);
+            bytes
+                .windows(name.len())
+                .any(|window| window == name.as_bytes())
+        })
+        .count()
+}
+
+#[test]
+fn export_sheet_per_group_and_properties() {
+    let tdms_file = write_file(vec![
+        WriteObject::group("Group1").with_properties(&[TdmsProperty::new(
+            "description",
+            TdmsValue::String(String::from("first")),
+        )]),
+        WriteObject::channel("Group1", "Ints")
+            .with_properties(&[TdmsProperty::new(
+                "unit_string",
+                TdmsValue::String(String::from("V")),
+            )])
+            .with_data(&[1i32, 2, 3])
+            .unwrap(),
+        WriteObject::channel("Group1", "Strings")
+            .with_string_data(&["a", "b"])
+            .unwrap(),
+        WriteObject::channel("Group2", "Doubles")
+            .with_data(&[1.5f64, 2.5])
+            .unwrap(),
+    ]);
+    let path = temp_path("groups");
+
+    let options = XlsxExportOptions {
+        time_column: XlsxTimeColumn::Relative,
+        ..XlsxExportOptions::default()
+    };
+    tdms_file.export_xlsx(&path, &options).unwrap();
+
+    assert_eq!(worksheet_count(&path), 3);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn too_many_rows_for_a_sheet() {
+    let values = vec![0u8; XLSX_MAX_DATA_ROWS as usize + 1];
+    let tdms_file = write_file(vec![WriteObject::channel("Group", "Channel")
+        .with_data(&values)
+        .unwrap()]);
+    let path = temp_path("rows");
+
+    let result = tdms_file.export_xlsx(&path, &XlsxExportOptions::default());
+    match result {
+        Err(TdmsReadError::TooManyRows {
+            group,
+            rows,
+            max_rows,
+        }) => {
+            assert_eq!(group, "Group");
+            assert_eq!(rows, XLSX_MAX_DATA_ROWS + 1);
+            assert_eq!(max_rows, XLSX_MAX_DATA_ROWS);
+        }
+        other => panic!("Expected too many rows error, got {:?}", other.err()),
+    }
+
+    let options = XlsxExportOptions {
+        split_sheets: true,
+        ..XlsxExportOptions::default()
+    };
+    tdms_file.export_xlsx(&path, &options).unwrap();
+
+    assert_eq!(worksheet_count(&path), 3);
+    std::fs::remove_file(&path).unwrap();
+}